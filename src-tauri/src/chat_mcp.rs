@@ -0,0 +1,50 @@
+//! 工作台驱动的引擎（Ollama、OpenRouter）的 MCP 配置文件操作模块
+//!
+//! 负责读写 ~/.anycode/<engine>-mcp.json 中的 mcpServers 配置
+//!
+//! 这些引擎本身不调用 MCP 工具，工具由工作台在会话中代为调用
+//! （见 `commands::ollama::session`），因此配置直接使用统一 MCP 格式保存。
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// 获取引擎的 MCP 配置文件路径
+pub(crate) fn user_config_path(engine: &str) -> PathBuf {
+    let home_dir = dirs::home_dir().expect("Failed to get home directory");
+    home_dir
+        .join(".anycode")
+        .join(format!("{}-mcp.json", engine))
+}
+
+/// 读取引擎的 MCP 服务器配置
+pub fn read_mcp_servers_map(engine: &str) -> Result<HashMap<String, Value>, String> {
+    let path = user_config_path(engine);
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("读取 {} MCP 配置失败: {}", engine, e))?;
+    let root: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("解析 {} MCP 配置失败: {}", engine, e))?;
+
+    Ok(root
+        .get("mcpServers")
+        .and_then(|v| v.as_object())
+        .map(|obj| obj.iter().map(|(k, v)| (k.clone(), v.clone())).collect())
+        .unwrap_or_default())
+}
+
+/// 将 MCP 服务器映射写入引擎的 MCP 配置（整体替换）
+pub fn set_mcp_servers_map(engine: &str, servers: &HashMap<String, Value>) -> Result<(), String> {
+    let path = user_config_path(engine);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建配置目录失败: {}", e))?;
+    }
+
+    let content = serde_json::to_string_pretty(&json!({ "mcpServers": servers }))
+        .map_err(|e| format!("序列化 {} MCP 配置失败: {}", engine, e))?;
+    fs::write(&path, content).map_err(|e| format!("写入 {} MCP 配置失败: {}", engine, e))
+}
@@ -23,13 +23,19 @@ pub struct McpStatus {
 /// - 如果设置了 CLAUDE_CONFIG_DIR 环境变量，使用派生路径
 ///
 /// 注意：~/.claude/settings.json 是 Claude Code CLI 的主配置文件，MCP 配置应该在 ~/.claude.json
-fn user_config_path() -> PathBuf {
+pub(crate) fn user_config_path() -> PathBuf {
     let home_dir = dirs::home_dir().expect("Failed to get home directory");
 
     // Claude MCP 配置文件固定为 ~/.claude.json（参考 cc-switch 项目实现）
     home_dir.join(".claude.json")
 }
 
+/// Claude Code 用户设置文件（~/.claude/settings.json），单独禁用的 MCP 工具写在其 permissions.deny 中
+fn user_settings_path() -> PathBuf {
+    let home_dir = dirs::home_dir().expect("Failed to get home directory");
+    home_dir.join(".claude").join("settings.json")
+}
+
 /// 读取 JSON 文件
 fn read_json_value(path: &Path) -> Result<Value, String> {
     if !path.exists() {
@@ -256,6 +262,7 @@ pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), Strin
 
     // 构建 mcpServers 对象：移除 UI 辅助字段，仅保留实际 MCP 规范
     let mut out: Map<String, Value> = Map::new();
+    let mut disabled_tools: HashMap<String, Vec<String>> = HashMap::new();
     for (id, spec) in servers.iter() {
         let mut obj = if let Some(map) = spec.as_object() {
             map.clone()
@@ -271,6 +278,9 @@ pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), Strin
             obj = server_obj;
         }
 
+        // Claude 的服务器配置不支持禁用单个工具，改为写入 permissions.deny
+        disabled_tools.insert(id.clone(), take_disabled_tools(&mut obj));
+
         // 移除 UI 辅助字段
         obj.remove("enabled");
         obj.remove("source");
@@ -292,5 +302,66 @@ pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), Strin
     }
 
     write_json_value(&path, &root)?;
+    write_disabled_tools(&user_settings_path(), &disabled_tools)?;
     Ok(())
 }
+
+/// 取出统一格式中的 disabledTools 字段
+pub(crate) fn take_disabled_tools(spec: &mut Map<String, Value>) -> Vec<String> {
+    spec.remove("disabledTools")
+        .and_then(|v| v.as_array().cloned())
+        .map(|tools| {
+            tools
+                .iter()
+                .filter_map(|t| t.as_str())
+                .map(|t| t.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 将单独禁用的 MCP 工具写入 Claude 设置文件的 permissions.deny（规则形如 `mcp__<服务器>__<工具>`）
+///
+/// `servers` 中每个服务器以前写入的规则都会被替换为当前的列表，其他规则保持不变
+pub(crate) fn write_disabled_tools(
+    settings_path: &Path,
+    servers: &HashMap<String, Vec<String>>,
+) -> Result<(), String> {
+    if !settings_path.exists() && servers.values().all(Vec::is_empty) {
+        return Ok(());
+    }
+
+    let mut root = read_json_value(settings_path)?;
+    let obj = root
+        .as_object_mut()
+        .ok_or_else(|| "Claude 设置文件根必须是对象".to_string())?;
+    let permissions = obj
+        .entry("permissions")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or_else(|| "Claude 设置文件的 permissions 字段不是对象".to_string())?;
+    let deny = permissions
+        .entry("deny")
+        .or_insert_with(|| Value::Array(Vec::new()))
+        .as_array_mut()
+        .ok_or_else(|| "Claude 设置文件的 permissions.deny 字段不是数组".to_string())?;
+
+    let before = deny.clone();
+    let prefixes: Vec<String> = servers.keys().map(|id| format!("mcp__{}__", id)).collect();
+    deny.retain(|rule| {
+        rule.as_str()
+            .map_or(true, |rule| !prefixes.iter().any(|p| rule.starts_with(p)))
+    });
+    let mut ids: Vec<&String> = servers.keys().collect();
+    ids.sort();
+    for id in ids {
+        for tool in &servers[id] {
+            deny.push(Value::String(format!("mcp__{}__{}", id, tool)));
+        }
+    }
+
+    if *deny == before {
+        return Ok(());
+    }
+    write_json_value(settings_path, &root)
+}
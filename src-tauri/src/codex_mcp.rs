@@ -18,7 +18,7 @@ use std::fs;
 use std::path::PathBuf;
 
 /// 获取 Codex 配置文件路径
-fn user_config_path() -> PathBuf {
+pub(crate) fn user_config_path() -> PathBuf {
     let home_dir = dirs::home_dir().expect("Failed to get home directory");
     home_dir.join(".codex").join("config.toml")
 }
@@ -81,13 +81,15 @@ pub fn read_mcp_servers_map() -> Result<HashMap<String, Value>, String> {
 }
 
 /// 将 TOML 服务器条目转换为 JSON
-fn toml_server_to_json(id: &str, entry_val: &toml::Value) -> Option<Value> {
+pub(crate) fn toml_server_to_json(id: &str, entry_val: &toml::Value) -> Option<Value> {
     let entry_tbl = entry_val.as_table()?;
 
-    let typ = entry_tbl
-        .get("type")
-        .and_then(|v| v.as_str())
-        .unwrap_or("stdio");
+    // Codex 自身的配置不写 type，远程服务器只有 url（streamable HTTP）
+    let typ = match entry_tbl.get("type").and_then(|v| v.as_str()) {
+        Some(typ) => typ,
+        None if entry_tbl.contains_key("url") && !entry_tbl.contains_key("command") => "http",
+        None => "stdio",
+    };
 
     let mut spec = serde_json::Map::new();
     spec.insert("type".into(), json!(typ));
@@ -157,12 +159,24 @@ fn toml_server_to_json(id: &str, entry_val: &toml::Value) -> Option<Value> {
         }
     }
 
+    // disabled_tools -> disabledTools
+    if let Some(tools) = entry_tbl.get("disabled_tools").and_then(|v| v.as_array()) {
+        let arr: Vec<_> = tools
+            .iter()
+            .filter_map(|x| x.as_str())
+            .map(|s| json!(s))
+            .collect();
+        if !arr.is_empty() {
+            spec.insert("disabledTools".into(), Value::Array(arr));
+        }
+    }
+
     Some(Value::Object(spec))
 }
 
 /// 写入 Codex MCP 服务器配置（从 JSON 转换为 TOML）
 pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), String> {
-    use toml_edit::{DocumentMut, Item, Table};
+    use toml_edit::DocumentMut;
 
     let path = user_config_path();
 
@@ -189,36 +203,107 @@ pub fn set_mcp_servers_map(servers: &HashMap<String, Value>) -> Result<(), Strin
         }
     }
 
-    // 构建 [mcp_servers] 表
+    merge_mcp_servers(&mut doc, servers);
+
+    // 写回文件
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+
+    fs::write(&path, doc.to_string()).map_err(|e| format!("写入 Codex 配置失败: {}", e))?;
+
+    log::info!("Codex 配置写入成功");
+    Ok(())
+}
+
+/// 由 JSON 规范生成的键；服务器表中的其他键（如 `enabled`、`startup_timeout_sec`）由用户维护
+const MANAGED_SERVER_KEYS: &[&str] = &[
+    "type",
+    "command",
+    "args",
+    "cwd",
+    "env",
+    "url",
+    "http_headers",
+    "headers",
+    "disabled_tools",
+];
+
+/// 将 MCP 服务器合并进 config.toml 文档的 [mcp_servers] 表
+///
+/// 已存在的服务器只替换受管理的键，保留用户添加的键、注释和格式；
+/// 不在 `servers` 中的服务器会被移除
+pub(crate) fn merge_mcp_servers(doc: &mut toml_edit::DocumentMut, servers: &HashMap<String, Value>) {
+    use toml_edit::{Item, Table};
+
     if servers.is_empty() {
         // 无服务器：移除 mcp_servers 表
         doc.as_table_mut().remove("mcp_servers");
-    } else {
+        return;
+    }
+
+    if !doc.get("mcp_servers").is_some_and(Item::is_table) {
         let mut servers_tbl = Table::new();
+        servers_tbl.set_implicit(true);
+        doc["mcp_servers"] = Item::Table(servers_tbl);
+    }
+    let Some(servers_tbl) = doc["mcp_servers"].as_table_mut() else {
+        return;
+    };
 
-        for (id, spec) in servers.iter() {
-            match json_server_to_toml_table(spec) {
-                Ok(table) => {
-                    servers_tbl[&id[..]] = Item::Table(table);
+    let stale: Vec<String> = servers_tbl
+        .iter()
+        .map(|(id, _)| id.to_string())
+        .filter(|id| !servers.contains_key(id))
+        .collect();
+    for id in stale {
+        servers_tbl.remove(&id);
+    }
+
+    let mut ids: Vec<&String> = servers.keys().collect();
+    ids.sort();
+    for id in ids {
+        let table = match json_server_to_toml_table(&servers[id]) {
+            Ok(table) => table,
+            Err(err) => {
+                log::error!("跳过无效的 MCP 服务器 '{}': {}", id, err);
+                continue;
+            }
+        };
+
+        match servers_tbl.get_mut(id).and_then(Item::as_table_mut) {
+            Some(existing) => {
+                for key in MANAGED_SERVER_KEYS {
+                    if !table.contains_key(key) {
+                        existing.remove(key);
+                    }
                 }
-                Err(err) => {
-                    log::error!("跳过无效的 MCP 服务器 '{}': {}", id, err);
+                for (key, item) in table.iter() {
+                    set_preserving_decor(existing, key, item.clone());
                 }
             }
+            None => {
+                servers_tbl[&id[..]] = Item::Table(table);
+            }
         }
-
-        doc["mcp_servers"] = Item::Table(servers_tbl);
-    }
-
-    // 写回文件
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
     }
+}
 
-    fs::write(&path, doc.to_string()).map_err(|e| format!("写入 Codex 配置失败: {}", e))?;
+/// 替换表中的值，保留原值的行尾注释等装饰
+fn set_preserving_decor(table: &mut toml_edit::Table, key: &str, item: toml_edit::Item) {
+    use toml_edit::Item;
 
-    log::info!("Codex 配置写入成功");
-    Ok(())
+    if !table.contains_key(key) {
+        table.insert(key, item);
+        return;
+    }
+    match (&mut table[key], item) {
+        (Item::Value(existing), Item::Value(mut value)) => {
+            *value.decor_mut() = existing.decor().clone();
+            *existing = value;
+        }
+        (existing, item) => *existing = item,
+    }
 }
 
 /// 将 JSON MCP 服务器规范转换为 TOML Table
@@ -263,6 +348,9 @@ fn json_server_to_toml_table(spec: &Value) -> Result<toml_edit::Table, String> {
             }
         }
         "http" | "sse" => {
+            if typ == "sse" {
+                log::warn!("Codex 只支持 streamable HTTP 远程服务器，SSE 服务器可能无法连接");
+            }
             let url = spec.get("url").and_then(|v| v.as_str()).unwrap_or("");
             t["url"] = toml_edit::value(url);
 
@@ -283,5 +371,16 @@ fn json_server_to_toml_table(spec: &Value) -> Result<toml_edit::Table, String> {
         }
     }
 
+    // 单独禁用的工具
+    if let Some(tools) = spec.get("disabledTools").and_then(|v| v.as_array()) {
+        let mut arr_v = Array::default();
+        for tool in tools.iter().filter_map(|x| x.as_str()) {
+            arr_v.push(tool);
+        }
+        if !arr_v.is_empty() {
+            t["disabled_tools"] = Item::Value(toml_edit::Value::Array(arr_v));
+        }
+    }
+
     Ok(t)
 }
@@ -0,0 +1,683 @@
+//! Agent packs
+//!
+//! A pack bundles subagents (`.claude/agents/*.md`), slash commands
+//! (`.claude/commands/**/*.md`) and hooks (the `hooks` section of Claude
+//! settings) into one JSON file that a team can commit next to its repo.
+//! Packs are signed with the exporter's Ed25519 key; the private key lives in
+//! the system keyring and trusted publisher keys in `~/.anycode/pack-trust.json`.
+//! Importing always goes through a preview that lists every file to be written
+//! and every hook command to be added, and unsigned or untrusted packs are only
+//! installed when explicitly allowed.
+
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::claude::{get_claude_dir, get_hooks_config, update_hooks_config};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// Identifies pack files
+const PACK_FORMAT: &str = "anycode-agent-pack";
+const PACK_VERSION: u32 = 1;
+
+/// Keyring entry holding the signing key seed
+const SIGNING_KEY_SECRET: &str = "agent-pack-signing-key";
+
+lazy_static::lazy_static! {
+    static ref TRUST_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Pack metadata
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackManifest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub author: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An agent or slash command file; `name` is relative to its directory without `.md`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackFile {
+    pub name: String,
+    pub content: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackSignature {
+    /// Base64 Ed25519 public key
+    pub public_key: String,
+    /// Base64 signature of the pack without its signature
+    pub signature: String,
+}
+
+/// A pack file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentPack {
+    pub format: String,
+    pub version: u32,
+    pub manifest: PackManifest,
+    #[serde(default)]
+    pub agents: Vec<PackFile>,
+    #[serde(default)]
+    pub commands: Vec<PackFile>,
+    /// Same shape as the `hooks` section of Claude settings
+    #[serde(default)]
+    pub hooks: serde_json::Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<PackSignature>,
+}
+
+/// What to put in an exported pack
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackExportRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub author: Option<String>,
+    /// Agent file paths (as returned by `list_subagents`)
+    #[serde(default)]
+    pub agent_paths: Vec<String>,
+    /// Slash command file paths (as returned by `list_custom_slash_commands`)
+    #[serde(default)]
+    pub command_paths: Vec<String>,
+    /// Include the hooks of this settings scope ("user", "project" or "local")
+    pub hooks_scope: Option<String>,
+    pub project_path: Option<String>,
+    #[serde(default = "default_sign")]
+    pub sign: bool,
+}
+
+fn default_sign() -> bool {
+    true
+}
+
+/// A publisher whose packs are trusted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrustedPublisher {
+    pub name: String,
+    pub public_key: String,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct TrustStore {
+    #[serde(default)]
+    publishers: Vec<TrustedPublisher>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum SignatureStatus {
+    Unsigned,
+    /// Valid signature by a trusted publisher
+    #[serde(rename_all = "camelCase")]
+    Trusted {
+        publisher: String,
+        public_key: String,
+    },
+    /// Valid signature by an unknown key
+    #[serde(rename_all = "camelCase")]
+    Untrusted {
+        public_key: String,
+    },
+    /// The pack was modified after signing
+    Invalid,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PackItemAction {
+    Create,
+    Overwrite,
+    Unchanged,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackItemPreview {
+    /// "agent" or "command"
+    pub kind: String,
+    pub name: String,
+    pub target_path: String,
+    pub action: PackItemAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HookPreview {
+    pub event: String,
+    pub matcher: Option<String>,
+    pub commands: Vec<String>,
+    /// Already present in the target settings
+    pub already_installed: bool,
+}
+
+/// What importing a pack into a scope will do
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackPreview {
+    pub manifest: PackManifest,
+    pub signature: SignatureStatus,
+    pub items: Vec<PackItemPreview>,
+    pub hooks: Vec<HookPreview>,
+}
+
+// ============================================================================
+// Signing
+// ============================================================================
+
+fn trust_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("pack-trust.json"))
+}
+
+fn load_trust() -> TrustStore {
+    trust_path().and_then(load_json_config).unwrap_or_default()
+}
+
+/// Load the signing key, generating one on first use
+fn signing_key() -> Result<SigningKey, String> {
+    if let Some(seed) = keyring_store::get_secret(SIGNING_KEY_SECRET)? {
+        let bytes: [u8; 32] = BASE64
+            .decode(seed.trim())
+            .ok()
+            .and_then(|bytes| bytes.try_into().ok())
+            .ok_or("The stored pack signing key is corrupted")?;
+        return Ok(SigningKey::from_bytes(&bytes));
+    }
+    let key = SigningKey::generate(&mut rand::rngs::OsRng);
+    keyring_store::set_secret(SIGNING_KEY_SECRET, &BASE64.encode(key.to_bytes()))?;
+    log::info!("[AgentPacks] Generated a new pack signing key");
+    Ok(key)
+}
+
+/// Bytes covered by the signature: the pack without its signature
+fn signed_bytes(pack: &AgentPack) -> Result<Vec<u8>, String> {
+    let mut unsigned = pack.clone();
+    unsigned.signature = None;
+    serde_json::to_vec(&unsigned).map_err(|e| format!("Failed to serialize pack: {}", e))
+}
+
+fn sign_pack(pack: &mut AgentPack, key: &SigningKey) -> Result<(), String> {
+    let signature = key.sign(&signed_bytes(pack)?);
+    pack.signature = Some(PackSignature {
+        public_key: BASE64.encode(key.verifying_key().to_bytes()),
+        signature: BASE64.encode(signature.to_bytes()),
+    });
+    Ok(())
+}
+
+fn verify_pack(pack: &AgentPack, trust: &TrustStore) -> SignatureStatus {
+    let Some(signed) = &pack.signature else {
+        return SignatureStatus::Unsigned;
+    };
+    let key = BASE64
+        .decode(&signed.public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .and_then(|bytes| VerifyingKey::from_bytes(&bytes).ok());
+    let signature = BASE64
+        .decode(&signed.signature)
+        .ok()
+        .and_then(|bytes| Signature::from_slice(&bytes).ok());
+    let valid = match (key, signature, signed_bytes(pack)) {
+        (Some(key), Some(signature), Ok(bytes)) => key.verify(&bytes, &signature).is_ok(),
+        _ => false,
+    };
+    if !valid {
+        return SignatureStatus::Invalid;
+    }
+
+    match trust
+        .publishers
+        .iter()
+        .find(|publisher| publisher.public_key == signed.public_key)
+    {
+        Some(publisher) => SignatureStatus::Trusted {
+            publisher: publisher.name.clone(),
+            public_key: signed.public_key.clone(),
+        },
+        None => SignatureStatus::Untrusted {
+            public_key: signed.public_key.clone(),
+        },
+    }
+}
+
+// ============================================================================
+// Pack contents
+// ============================================================================
+
+/// Reject names that would escape the agents/commands directory
+fn validate_name(name: &str) -> Result<(), String> {
+    let path = Path::new(name);
+    let valid = !name.trim().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid pack entry name: {}", name))
+    }
+}
+
+/// Name of a slash command relative to the `commands` directory containing it
+fn command_name(path: &Path) -> String {
+    let components: Vec<String> = path
+        .with_extension("")
+        .components()
+        .map(|component| component.as_os_str().to_string_lossy().to_string())
+        .collect();
+    match components.iter().rposition(|c| c == "commands") {
+        Some(index) if index + 1 < components.len() => components[index + 1..].join("/"),
+        _ => components.last().cloned().unwrap_or_default(),
+    }
+}
+
+fn read_pack_file(path: &str, name: String) -> Result<PackFile, String> {
+    let content =
+        std::fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    validate_name(&name)?;
+    Ok(PackFile { name, content })
+}
+
+/// `.claude` directory of the import scope
+fn scope_dir(scope: &str, project_path: Option<&str>) -> Result<PathBuf, String> {
+    match scope {
+        "user" => get_claude_dir().map_err(|e| e.to_string()),
+        "project" => Ok(
+            Path::new(project_path.ok_or("Project path required for project scope")?)
+                .join(".claude"),
+        ),
+        _ => Err(format!("Invalid scope: {}", scope)),
+    }
+}
+
+fn preview_item(kind: &str, file: &PackFile, dir: &Path) -> PackItemPreview {
+    let target = dir.join(format!("{}.md", file.name));
+    let action = match std::fs::read_to_string(&target) {
+        Ok(existing) if existing == file.content => PackItemAction::Unchanged,
+        Ok(_) => PackItemAction::Overwrite,
+        Err(_) => PackItemAction::Create,
+    };
+    PackItemPreview {
+        kind: kind.to_string(),
+        name: file.name.clone(),
+        target_path: target.to_string_lossy().to_string(),
+        action,
+    }
+}
+
+/// Hook entries (`{matcher, hooks: [...]}`) of the pack, per event
+fn hook_entries(hooks: &serde_json::Value) -> Vec<(String, serde_json::Value)> {
+    let Some(events) = hooks.as_object() else {
+        return Vec::new();
+    };
+    events
+        .iter()
+        .flat_map(|(event, entries)| {
+            entries
+                .as_array()
+                .into_iter()
+                .flatten()
+                .map(move |entry| (event.clone(), entry.clone()))
+        })
+        .collect()
+}
+
+fn is_installed(existing: &serde_json::Value, event: &str, entry: &serde_json::Value) -> bool {
+    existing
+        .get(event)
+        .and_then(|entries| entries.as_array())
+        .is_some_and(|entries| entries.contains(entry))
+}
+
+/// Append the pack's hook entries that are not installed yet
+fn merge_hooks(existing: &mut serde_json::Value, pack_hooks: &serde_json::Value) {
+    if !existing.is_object() {
+        *existing = serde_json::json!({});
+    }
+    for (event, entry) in hook_entries(pack_hooks) {
+        if is_installed(existing, &event, &entry) {
+            continue;
+        }
+        let entries = existing
+            .as_object_mut()
+            .expect("hooks is an object")
+            .entry(event)
+            .or_insert_with(|| serde_json::json!([]));
+        if !entries.is_array() {
+            *entries = serde_json::json!([]);
+        }
+        if let Some(entries) = entries.as_array_mut() {
+            entries.push(entry);
+        }
+    }
+}
+
+fn load_pack(pack_path: &str) -> Result<AgentPack, String> {
+    let content = std::fs::read_to_string(pack_path)
+        .map_err(|e| format!("Failed to read pack {}: {}", pack_path, e))?;
+    let pack: AgentPack =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid pack file: {}", e))?;
+    if pack.format != PACK_FORMAT {
+        return Err("Not an agent pack file".to_string());
+    }
+    if pack.version > PACK_VERSION {
+        return Err(format!(
+            "Pack format version {} is not supported, please update the app",
+            pack.version
+        ));
+    }
+    for file in pack.agents.iter().chain(&pack.commands) {
+        validate_name(&file.name)?;
+    }
+    Ok(pack)
+}
+
+async fn build_preview(
+    pack: &AgentPack,
+    scope: &str,
+    project_path: Option<String>,
+) -> Result<PackPreview, String> {
+    let dir = scope_dir(scope, project_path.as_deref())?;
+    let mut items: Vec<PackItemPreview> = pack
+        .agents
+        .iter()
+        .map(|file| preview_item("agent", file, &dir.join("agents")))
+        .collect();
+    items.extend(
+        pack.commands
+            .iter()
+            .map(|file| preview_item("command", file, &dir.join("commands"))),
+    );
+
+    let existing = get_hooks_config(scope.to_string(), project_path).await?;
+    let hooks = hook_entries(&pack.hooks)
+        .into_iter()
+        .map(|(event, entry)| HookPreview {
+            already_installed: is_installed(&existing, &event, &entry),
+            matcher: entry
+                .get("matcher")
+                .and_then(|m| m.as_str())
+                .map(str::to_string),
+            commands: entry
+                .get("hooks")
+                .and_then(|h| h.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|hook| hook.get("command").and_then(|c| c.as_str()))
+                .map(str::to_string)
+                .collect(),
+            event,
+        })
+        .collect();
+
+    Ok(PackPreview {
+        manifest: pack.manifest.clone(),
+        signature: verify_pack(pack, &load_trust()),
+        items,
+        hooks,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Export the selected agents, commands and hooks as a pack file
+#[tauri::command]
+pub async fn export_agent_pack(
+    request: PackExportRequest,
+    output_path: String,
+) -> Result<AgentPack, String> {
+    let agents = request
+        .agent_paths
+        .iter()
+        .map(|path| {
+            let name = Path::new(path)
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().to_string())
+                .unwrap_or_default();
+            read_pack_file(path, name)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let commands = request
+        .command_paths
+        .iter()
+        .map(|path| read_pack_file(path, command_name(Path::new(path))))
+        .collect::<Result<Vec<_>, _>>()?;
+    let hooks = match request.hooks_scope {
+        Some(scope) => get_hooks_config(scope, request.project_path.clone()).await?,
+        None => serde_json::Value::Null,
+    };
+
+    let mut pack = AgentPack {
+        format: PACK_FORMAT.to_string(),
+        version: PACK_VERSION,
+        manifest: PackManifest {
+            name: request.name,
+            description: request.description,
+            author: request.author,
+            created_at: Utc::now(),
+        },
+        agents,
+        commands,
+        hooks,
+        signature: None,
+    };
+    if request.sign {
+        sign_pack(&mut pack, &signing_key()?)?;
+    }
+
+    let json = serde_json::to_string_pretty(&pack)
+        .map_err(|e| format!("Failed to serialize pack: {}", e))?;
+    std::fs::write(&output_path, json)
+        .map_err(|e| format!("Failed to write pack {}: {}", output_path, e))?;
+    log::info!(
+        "[AgentPacks] Exported '{}' ({} agents, {} commands) to {}",
+        pack.manifest.name,
+        pack.agents.len(),
+        pack.commands.len(),
+        output_path
+    );
+    Ok(pack)
+}
+
+/// Show what importing a pack into a scope ("user" or "project") would install
+#[tauri::command]
+pub async fn preview_agent_pack(
+    pack_path: String,
+    scope: String,
+    project_path: Option<String>,
+) -> Result<PackPreview, String> {
+    let pack = load_pack(&pack_path)?;
+    build_preview(&pack, &scope, project_path).await
+}
+
+/// Install a pack; unsigned or untrusted packs require `allow_untrusted`
+#[tauri::command]
+pub async fn import_agent_pack(
+    pack_path: String,
+    scope: String,
+    project_path: Option<String>,
+    allow_untrusted: Option<bool>,
+) -> Result<PackPreview, String> {
+    let pack = load_pack(&pack_path)?;
+    let preview = build_preview(&pack, &scope, project_path.clone()).await?;
+    match &preview.signature {
+        SignatureStatus::Trusted { .. } => {}
+        SignatureStatus::Invalid => {
+            return Err("The pack signature is invalid; it was modified after signing".to_string())
+        }
+        SignatureStatus::Unsigned | SignatureStatus::Untrusted { .. } => {
+            if !allow_untrusted.unwrap_or(false) {
+                return Err("The pack is not signed by a trusted publisher".to_string());
+            }
+        }
+    }
+
+    for (item, file) in preview
+        .items
+        .iter()
+        .zip(pack.agents.iter().chain(&pack.commands))
+    {
+        if item.action == PackItemAction::Unchanged {
+            continue;
+        }
+        let target = Path::new(&item.target_path);
+        if let Some(parent) = target.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        std::fs::write(target, &file.content)
+            .map_err(|e| format!("Failed to write {}: {}", item.target_path, e))?;
+    }
+
+    if preview.hooks.iter().any(|hook| !hook.already_installed) {
+        let mut hooks = get_hooks_config(scope.clone(), project_path.clone()).await?;
+        merge_hooks(&mut hooks, &pack.hooks);
+        update_hooks_config(scope, hooks, project_path).await?;
+    }
+
+    log::info!(
+        "[AgentPacks] Imported '{}' ({} items, {} hooks)",
+        pack.manifest.name,
+        preview.items.len(),
+        preview.hooks.len()
+    );
+    Ok(preview)
+}
+
+/// Public key packs exported from this machine are signed with
+#[tauri::command]
+pub async fn get_pack_public_key() -> Result<String, String> {
+    Ok(BASE64.encode(signing_key()?.verifying_key().to_bytes()))
+}
+
+#[tauri::command]
+pub async fn list_trusted_pack_publishers() -> Result<Vec<TrustedPublisher>, String> {
+    Ok(load_trust().publishers)
+}
+
+/// Trust packs signed with `public_key`
+#[tauri::command]
+pub async fn trust_pack_publisher(name: String, public_key: String) -> Result<(), String> {
+    let public_key = public_key.trim().to_string();
+    let valid = BASE64
+        .decode(&public_key)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .is_some_and(|bytes| VerifyingKey::from_bytes(&bytes).is_ok());
+    if !valid {
+        return Err("Invalid public key".to_string());
+    }
+
+    let _guard = TRUST_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut trust = load_trust();
+    trust.publishers.retain(|p| p.public_key != public_key);
+    trust.publishers.push(TrustedPublisher {
+        name,
+        public_key,
+        added_at: Utc::now(),
+    });
+    save_json_config(&trust, trust_path()?)
+}
+
+#[tauri::command]
+pub async fn remove_trusted_pack_publisher(public_key: String) -> Result<(), String> {
+    let _guard = TRUST_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut trust = load_trust();
+    trust.publishers.retain(|p| p.public_key != public_key);
+    save_json_config(&trust, trust_path()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_pack() -> AgentPack {
+        AgentPack {
+            format: PACK_FORMAT.to_string(),
+            version: PACK_VERSION,
+            manifest: PackManifest {
+                name: "team".to_string(),
+                description: None,
+                author: None,
+                created_at: Utc::now(),
+            },
+            agents: vec![PackFile {
+                name: "reviewer".to_string(),
+                content: "---\nname: reviewer\n---\nReview code".to_string(),
+            }],
+            commands: Vec::new(),
+            hooks: serde_json::json!({
+                "PostToolUse": [{"matcher": "Edit", "hooks": [{"type": "command", "command": "cargo fmt"}]}]
+            }),
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn test_signature_verification() {
+        let key = SigningKey::from_bytes(&[7u8; 32]);
+        let mut pack = sample_pack();
+        assert!(matches!(
+            verify_pack(&pack, &TrustStore::default()),
+            SignatureStatus::Unsigned
+        ));
+
+        sign_pack(&mut pack, &key).unwrap();
+        assert!(matches!(
+            verify_pack(&pack, &TrustStore::default()),
+            SignatureStatus::Untrusted { .. }
+        ));
+
+        let trust = TrustStore {
+            publishers: vec![TrustedPublisher {
+                name: "Platform team".to_string(),
+                public_key: pack.signature.as_ref().unwrap().public_key.clone(),
+                added_at: Utc::now(),
+            }],
+        };
+        assert!(matches!(
+            verify_pack(&pack, &trust),
+            SignatureStatus::Trusted { .. }
+        ));
+
+        pack.agents[0].content.push_str("\nAlso run rm -rf /");
+        assert!(matches!(
+            verify_pack(&pack, &trust),
+            SignatureStatus::Invalid
+        ));
+    }
+
+    #[test]
+    fn test_names_and_hook_merge() {
+        assert!(validate_name("git/commit").is_ok());
+        assert!(validate_name("../settings").is_err());
+        assert!(validate_name("/etc/passwd").is_err());
+        assert_eq!(
+            command_name(Path::new("/repo/.claude/commands/git/commit.md")),
+            "git/commit"
+        );
+
+        let pack = sample_pack();
+        let mut existing = serde_json::json!({});
+        merge_hooks(&mut existing, &pack.hooks);
+        merge_hooks(&mut existing, &pack.hooks);
+        assert_eq!(existing["PostToolUse"].as_array().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,285 @@
+//! Automatic pre-run checkpoints
+//!
+//! When enabled, uncommitted work is snapshotted before an engine touches the
+//! project, so "the state before the AI touched anything" can always be
+//! restored. A snapshot is either a commit (which then becomes the prompt's
+//! `commit_before`) or a stash entry that leaves the working tree as it is.
+//!
+//! Granularity decides when snapshots are taken:
+//! - `perSession`: before the first prompt of a session
+//! - `perPrompt`: before every prompt
+//! - `perFileEdit`: before every prompt and, best effort, whenever the engine
+//!   output announces a file edit (the engine may already be writing by then);
+//!   a project gets at most one such snapshot at a time, and edits announced
+//!   within [`FILE_EDIT_DEBOUNCE`] of it are covered by it
+//!
+//! Snapshots write to the repository, so they run as git operations (see
+//! [`crate::commands::git_executor`]): [`before_run`] inside the prompt
+//! recording's operation, file-edit snapshots in one of their own.
+//!
+//! Settings live in `~/.anycode/auto-checkpoint.json`; disabled by default.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+
+use crate::commands::commit_metadata;
+use crate::commands::engine_safety::project_key;
+use crate::commands::git_backend::backend_for;
+use crate::commands::git_executor::GitOperation;
+use crate::commands::git_status_cache;
+use crate::commands::monorepo;
+use crate::commands::simple_git::{self, WORKBENCH_STASH_MARKER};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Tool names that write files (Claude, and Gemini after conversion to the unified format)
+pub(crate) const FILE_EDIT_TOOLS: &[&str] = &[
+    "Edit",
+    "MultiEdit",
+    "Write",
+    "NotebookEdit",
+    "replace",
+    "write_file",
+];
+
+/// Minimum time between two file-edit snapshots of a project
+const FILE_EDIT_DEBOUNCE: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapshotMethod {
+    #[default]
+    Commit,
+    Stash,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CheckpointGranularity {
+    PerSession,
+    #[default]
+    PerPrompt,
+    PerFileEdit,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoCheckpointSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub method: SnapshotMethod,
+    #[serde(default)]
+    pub granularity: CheckpointGranularity,
+}
+
+static SETTINGS: Lazy<RwLock<AutoCheckpointSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+/// `engine:session` pairs already snapshotted (per-session granularity)
+static SNAPSHOTTED_SESSIONS: Lazy<Mutex<HashSet<String>>> =
+    Lazy::new(|| Mutex::new(HashSet::new()));
+
+/// Projects with a file-edit snapshot in flight or within its debounce window
+static FILE_EDIT_SNAPSHOTS: Lazy<Mutex<HashSet<String>>> = Lazy::new(|| Mutex::new(HashSet::new()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("auto-checkpoint.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+fn settings() -> AutoCheckpointSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Snapshot uncommitted changes; returns the commit or stash created, None when clean
+fn snapshot(
+    project_path: &str,
+    method: SnapshotMethod,
+    engine: &str,
+    session_id: &str,
+    reason: &str,
+) -> Result<Option<String>, String> {
+    let backend = backend_for(project_path);
+    if !git_status_cache::has_uncommitted_changes(project_path)? {
+        return Ok(None);
+    }
+    let message = format!(
+        "{} Auto-checkpoint before {} {} (session {})",
+        WORKBENCH_STASH_MARKER,
+        engine,
+        reason,
+        &session_id[..8.min(session_id.len())]
+    );
+    let snapshot = match method {
+        SnapshotMethod::Commit => {
+            let scope = monorepo::session_package_scope(session_id);
+            let message = commit_metadata::tag_message(&message, "workbench", Some(session_id));
+            simple_git::git_commit_changes_in(project_path, &message, scope.as_deref())?;
+            simple_git::git_current_commit(project_path)?
+        }
+        SnapshotMethod::Stash => backend.stash_snapshot(project_path, &message)?,
+    };
+    log::info!(
+        "[AutoCheckpoint] {:?} snapshot {} before {} {}",
+        method,
+        &snapshot[..8.min(snapshot.len())],
+        engine,
+        reason
+    );
+    if let Err(e) = crate::session_store::record_checkpoint(engine, session_id, &snapshot) {
+        log::warn!("[AutoCheckpoint] Failed to record checkpoint: {}", e);
+    }
+    Ok(Some(snapshot))
+}
+
+/// Snapshot before an engine run, if the settings ask for it; failures are logged
+///
+/// Called from within the git operation that records the prompt.
+pub fn before_run(project_path: &str, engine: &str, session_id: &str) {
+    let settings = settings();
+    if !settings.enabled {
+        return;
+    }
+    if settings.granularity == CheckpointGranularity::PerSession {
+        let first_run = SNAPSHOTTED_SESSIONS
+            .lock()
+            .map(|mut sessions| sessions.insert(format!("{}:{}", engine, session_id)))
+            .unwrap_or(true);
+        if !first_run {
+            return;
+        }
+    }
+    if let Err(e) = snapshot(project_path, settings.method, engine, session_id, "run") {
+        log::warn!(
+            "[AutoCheckpoint] Snapshot before {} run failed: {}",
+            engine,
+            e
+        );
+    }
+}
+
+/// Whether an output line announces a file edit
+fn is_file_edit_event(line: &str) -> bool {
+    // Cheap filter before parsing every streamed line
+    if !["tool_use", "file_change", "patch_apply_begin"]
+        .iter()
+        .any(|marker| line.contains(marker))
+    {
+        return false;
+    }
+    let Ok(event) = serde_json::from_str::<Value>(line) else {
+        return false;
+    };
+
+    // Claude stream-json / unified format
+    let edits_file = event["message"]["content"]
+        .as_array()
+        .map(|items| {
+            items.iter().any(|item| {
+                item["type"] == "tool_use"
+                    && item["name"]
+                        .as_str()
+                        .is_some_and(|name| FILE_EDIT_TOOLS.contains(&name))
+            })
+        })
+        .unwrap_or(false);
+
+    // Codex `exec --json` events
+    edits_file
+        || (event["type"] == "item.started" && event["item"]["type"] == "file_change")
+        || event["msg"]["type"] == "patch_apply_begin"
+}
+
+/// Per-file-edit snapshots, fed with each line of engine output
+pub fn on_engine_output(project_path: &str, engine: &str, session_id: &str, line: &str) {
+    let settings = settings();
+    if !settings.enabled
+        || settings.granularity != CheckpointGranularity::PerFileEdit
+        || !is_file_edit_event(line)
+    {
+        return;
+    }
+    let key = project_key(project_path);
+    let scheduled = FILE_EDIT_SNAPSHOTS
+        .lock()
+        .map(|mut projects| projects.insert(key.clone()))
+        .unwrap_or(false);
+    if !scheduled {
+        return;
+    }
+
+    let project_path = project_path.to_string();
+    let engine = engine.to_string();
+    let session_id = session_id.to_string();
+    tauri::async_runtime::spawn(async move {
+        let result = GitOperation::new(&project_path, "auto-checkpoint")
+            .run(move || {
+                snapshot(
+                    &project_path,
+                    settings.method,
+                    &engine,
+                    &session_id,
+                    "file edit",
+                )
+            })
+            .await;
+        if let Err(e) = result {
+            log::warn!("[AutoCheckpoint] Snapshot before file edit failed: {}", e);
+        }
+        tokio::time::sleep(FILE_EDIT_DEBOUNCE).await;
+        if let Ok(mut projects) = FILE_EDIT_SNAPSHOTS.lock() {
+            projects.remove(&key);
+        }
+    });
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_auto_checkpoint_settings() -> Result<AutoCheckpointSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_auto_checkpoint_settings(
+    settings: AutoCheckpointSettings,
+) -> Result<(), String> {
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_file_edit_event() {
+        assert!(is_file_edit_event(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Edit","input":{}}]}}"#
+        ));
+        assert!(!is_file_edit_event(
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Read","input":{}}]}}"#
+        ));
+        assert!(is_file_edit_event(
+            r#"{"type":"item.started","item":{"id":"1","type":"file_change"}}"#
+        ));
+        assert!(!is_file_edit_event(r#"{"type":"text","text":"tool_use"}"#));
+    }
+}
@@ -0,0 +1,259 @@
+//! Safe commit mode for protected branches
+//!
+//! With the mode on, AI commits never land directly on a protected branch
+//! (`main` and `master` unless configured otherwise; glob patterns such as
+//! `release/*` work too). Instead a branch named after the session is created
+//! at HEAD and checked out, and the commit goes there. The working tree is
+//! untouched by the switch, since the new branch starts at the same commit.
+//!
+//! Settings live in `~/.anycode/branch-protection.json`.
+
+use chrono::Local;
+use glob::Pattern;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::commands::commit_metadata;
+use crate::commands::git_backend::GitBackend;
+use crate::commands::project_settings;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+const DEFAULT_BRANCH_PREFIX: &str = "anycode/";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchProtectionSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Branch names or glob patterns AI commits must not land on
+    #[serde(default = "default_protected_branches")]
+    pub protected_branches: Vec<String>,
+    /// Prefix of the branches created instead
+    #[serde(default = "default_branch_prefix")]
+    pub branch_prefix: String,
+}
+
+fn default_protected_branches() -> Vec<String> {
+    vec!["main".to_string(), "master".to_string()]
+}
+
+fn default_branch_prefix() -> String {
+    DEFAULT_BRANCH_PREFIX.to_string()
+}
+
+impl Default for BranchProtectionSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            protected_branches: default_protected_branches(),
+            branch_prefix: default_branch_prefix(),
+        }
+    }
+}
+
+impl BranchProtectionSettings {
+    fn is_protected(&self, branch: &str) -> bool {
+        self.protected_branches.iter().any(|protected| {
+            protected == branch
+                || Pattern::new(protected).is_ok_and(|pattern| pattern.matches(branch))
+        })
+    }
+}
+
+static SETTINGS: Lazy<RwLock<BranchProtectionSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("branch-protection.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+pub fn settings() -> BranchProtectionSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Characters git accepts in a branch name component; anything else becomes `-`
+fn sanitize(name: &str) -> String {
+    let sanitized: String = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    sanitized.trim_matches(['-', '.']).to_string()
+}
+
+/// `<prefix><session>`, or a timestamp for commits outside a session
+fn session_branch_name(prefix: &str, session_id: Option<&str>) -> String {
+    let suffix = session_id
+        .map(sanitize)
+        .filter(|session| !session.is_empty())
+        .unwrap_or_else(|| Local::now().format("%Y%m%d-%H%M%S").to_string());
+    format!("{}{}", prefix, suffix)
+}
+
+/// Move off a protected branch before committing `message`
+///
+/// Returns the branch created and checked out, or None when the commit can go
+/// to the current branch (mode off, unprotected branch, detached or unborn HEAD).
+/// A project's own protected branch list takes precedence over the global one.
+pub fn redirect_commit(
+    backend: &dyn GitBackend,
+    project_path: &str,
+    message: &str,
+) -> Result<Option<String>, String> {
+    let mut settings = settings();
+    if let Some(protected_branches) = project_settings::load(project_path).protected_branches {
+        settings.enabled = !protected_branches.is_empty();
+        settings.protected_branches = protected_branches;
+    }
+    redirect_with(&settings, backend, project_path, message)
+}
+
+/// Return to `original` and drop the session branch a failed commit was redirected to
+pub fn abandon_redirect(
+    backend: &dyn GitBackend,
+    project_path: &str,
+    original: &str,
+    branch: &str,
+) {
+    if let Err(e) = backend.switch_branch(project_path, original) {
+        log::warn!(
+            "[BranchProtection] Failed to switch back to {}: {}",
+            original,
+            e
+        );
+        return;
+    }
+    if let Err(e) = backend.delete_branch(project_path, branch, true) {
+        log::warn!("[BranchProtection] Failed to delete {}: {}", branch, e);
+    }
+}
+
+fn redirect_with(
+    settings: &BranchProtectionSettings,
+    backend: &dyn GitBackend,
+    project_path: &str,
+    message: &str,
+) -> Result<Option<String>, String> {
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let Ok(current) = backend.current_branch(project_path) else {
+        return Ok(None);
+    };
+    if !settings.is_protected(&current) {
+        return Ok(None);
+    }
+
+    let session_id = commit_metadata::parse(message).session_id;
+    let base = session_branch_name(&settings.branch_prefix, session_id.as_deref());
+    // Never reuse a branch: it may have moved on since
+    let existing: Vec<String> = backend
+        .list_branches(project_path)?
+        .into_iter()
+        .map(|branch| branch.name)
+        .collect();
+    let name = std::iter::once(base.clone())
+        .chain((2..).map(|n| format!("{}-{}", base, n)))
+        .find(|name| !existing.contains(name))
+        .unwrap_or(base);
+
+    backend.create_branch(project_path, &name, None)?;
+    backend.switch_branch(project_path, &name)?;
+    log::info!(
+        "[BranchProtection] {} is protected; committing on new branch {}",
+        current,
+        name
+    );
+    Ok(Some(name))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_branch_protection_settings() -> Result<BranchProtectionSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_branch_protection_settings(
+    settings: BranchProtectionSettings,
+) -> Result<(), String> {
+    if sanitize(&settings.branch_prefix).is_empty() {
+        return Err("Branch prefix must contain letters or digits".to_string());
+    }
+    if let Some(pattern) = settings
+        .protected_branches
+        .iter()
+        .find(|pattern| pattern.trim().is_empty() || Pattern::new(pattern).is_err())
+    {
+        return Err(format!("Invalid protected branch pattern: '{}'", pattern));
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::Git2Backend;
+
+    #[test]
+    fn test_redirect_commit_from_protected_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "initial").unwrap();
+        let main = backend.current_branch(&path).unwrap();
+
+        let settings = BranchProtectionSettings {
+            enabled: true,
+            protected_branches: vec![main.clone(), "release/*".to_string()],
+            ..BranchProtectionSettings::default()
+        };
+        assert!(settings.is_protected("release/1.0"));
+        assert!(!settings.is_protected("feature/x"));
+
+        let message = commit_metadata::tag_message("[Codex] p prompt #0", "codex", Some("s/1"));
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        let branch = redirect_with(&settings, &backend, &path, &message).unwrap();
+        assert_eq!(branch.as_deref(), Some("anycode/s-1"));
+        assert_eq!(backend.current_branch(&path).unwrap(), "anycode/s-1");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "two\n"
+        );
+
+        // Already off the protected branch
+        assert_eq!(
+            redirect_with(&settings, &backend, &path, &message).unwrap(),
+            None
+        );
+
+        backend.switch_branch(&path, &main).unwrap();
+        let branch = redirect_with(&settings, &backend, &path, &message).unwrap();
+        assert_eq!(branch.as_deref(), Some("anycode/s-1-2"));
+    }
+}
@@ -0,0 +1,442 @@
+//! Checkpoint timeline
+//!
+//! Every commit an engine creates after a prompt is a checkpoint. Besides the
+//! commit itself, the engine, session, prompt and time are recorded in a
+//! sidecar file, `<project>/.anycode/checkpoints.json` (kept out of git via
+//! `info/exclude`), so the UI can show and act on a timeline without parsing
+//! `git log` subjects.
+//!
+//! Restoring is non-destructive: the changes made since the checkpoint are
+//! reverted in a new commit, and uncommitted work is stashed first (and put
+//! back if the restore fails).
+//!
+//! A checkpoint can be given names ("before-refactor", "v0.3-working"), which
+//! are git tags on its commit; restoring and diffing accept a name in place of
+//! the commit hash.
+//!
+//! Projects on the snapshot undo backend record snapshot ids instead of
+//! commits; restoring then writes the snapshot back, and names are not
+//! available.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::git_backend::backend_for;
+use crate::commands::git_diff::{parse_unified_diff, StructuredDiff};
+use crate::commands::git_executor::GitOperation;
+use crate::commands::gitignore;
+use crate::commands::simple_git::{self, RevertResult};
+use crate::commands::snapshots;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Project-local directory holding workbench metadata
+const SIDECAR_DIR: &str = ".anycode";
+
+/// Characters of the prompt kept in the timeline
+const PROMPT_PREVIEW_CHARS: usize = 200;
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// An engine-created commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Checkpoint {
+    /// Commit hash
+    pub id: String,
+    /// HEAD before the prompt ran
+    pub parent: Option<String>,
+    /// `claude`, `codex` or `gemini`
+    pub engine: String,
+    pub session_id: String,
+    pub prompt_index: usize,
+    pub prompt: Option<String>,
+    pub created_at: DateTime<Utc>,
+    /// Tags on the commit; filled in when listing, never stored
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub names: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct CheckpointStore {
+    #[serde(default)]
+    checkpoints: Vec<Checkpoint>,
+}
+
+fn store_path(project_path: &str) -> PathBuf {
+    ConfigPathBuilder::new(Path::new(project_path).join(SIDECAR_DIR)).build("checkpoints.json")
+}
+
+fn load_store(project_path: &str) -> CheckpointStore {
+    load_json_config(store_path(project_path)).unwrap_or_default()
+}
+
+fn update_store<T>(
+    project_path: &str,
+    f: impl FnOnce(&mut CheckpointStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store(project_path);
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path(project_path))?;
+    Ok(result)
+}
+
+/// Add the sidecar directory to the repository's `info/exclude`, so checkpoints never commit it
+fn ensure_sidecar_ignored(project_path: &str) -> Result<(), String> {
+    gitignore::exclude_locally(project_path, &[format!("{}/", SIDECAR_DIR)]).map(|_| ())
+}
+
+fn prompt_preview(prompt: &str) -> Option<String> {
+    let preview: String = prompt.trim().chars().take(PROMPT_PREVIEW_CHARS).collect();
+    (!preview.is_empty()).then_some(preview)
+}
+
+/// Record the commit an engine created after a prompt; failures are logged, not returned
+pub fn record_checkpoint(
+    project_path: &str,
+    engine: &str,
+    session_id: &str,
+    prompt_index: usize,
+    prompt: Option<&str>,
+    parent: &str,
+    commit: &str,
+) {
+    if parent == commit {
+        return;
+    }
+    if !snapshots::enabled(project_path) {
+        if let Err(e) = ensure_sidecar_ignored(project_path) {
+            log::warn!("[Checkpoints] Could not exclude sidecar from git: {}", e);
+        }
+    }
+    let checkpoint = Checkpoint {
+        id: commit.to_string(),
+        parent: Some(parent.to_string()),
+        engine: engine.to_string(),
+        session_id: session_id.to_string(),
+        prompt_index,
+        prompt: prompt.and_then(prompt_preview),
+        created_at: Utc::now(),
+        names: Vec::new(),
+    };
+    let result = update_store(project_path, |store| {
+        store.checkpoints.retain(|c| c.id != checkpoint.id);
+        store.checkpoints.push(checkpoint);
+        Ok(())
+    });
+    match result {
+        Ok(()) => log::debug!(
+            "[Checkpoints] Recorded {} for {} prompt #{}",
+            &commit[..8.min(commit.len())],
+            engine,
+            prompt_index
+        ),
+        Err(e) => log::warn!("[Checkpoints] Failed to record checkpoint: {}", e),
+    }
+}
+
+/// Replace the checkpoints of squashed commits with one for the commit replacing them
+///
+/// The new checkpoint keeps the newest replaced checkpoint's engine, session and prompt.
+pub fn collapse_checkpoints(project_path: &str, replaced: &[String], parent: &str, commit: &str) {
+    let store = load_store(project_path);
+    if !store.checkpoints.iter().any(|c| replaced.contains(&c.id)) {
+        return;
+    }
+    let result = update_store(project_path, |store| {
+        let mut removed: Vec<Checkpoint> = Vec::new();
+        store.checkpoints.retain(|c| {
+            let keep = !replaced.contains(&c.id);
+            if !keep {
+                removed.push(c.clone());
+            }
+            keep
+        });
+        if let Some(latest) = removed.into_iter().max_by_key(|c| c.created_at) {
+            store.checkpoints.push(Checkpoint {
+                id: commit.to_string(),
+                parent: Some(parent.to_string()),
+                created_at: Utc::now(),
+                ..latest
+            });
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[Checkpoints] Failed to collapse checkpoints: {}", e);
+    }
+}
+
+/// A checkpoint by commit hash or by name
+fn find_checkpoint(project_path: &str, checkpoint_id: &str) -> Result<Checkpoint, String> {
+    let checkpoints = load_store(project_path).checkpoints;
+    let commit = if checkpoints.iter().any(|c| c.id == checkpoint_id) {
+        checkpoint_id.to_string()
+    } else if snapshots::enabled(project_path) {
+        return Err(format!("Checkpoint not found: {}", checkpoint_id));
+    } else {
+        backend_for(project_path)
+            .list_tags(project_path)?
+            .into_iter()
+            .find(|tag| tag.name == checkpoint_id)
+            .map(|tag| tag.commit)
+            .ok_or_else(|| format!("Checkpoint not found: {}", checkpoint_id))?
+    };
+    checkpoints
+        .into_iter()
+        .find(|c| c.id == commit)
+        .ok_or_else(|| format!("{} does not name a checkpoint", checkpoint_id))
+}
+
+/// Write a snapshot checkpoint back, reported like a git restore
+fn restore_snapshot_checkpoint(
+    project_path: &str,
+    checkpoint: &Checkpoint,
+) -> Result<RevertResult, String> {
+    let restore = snapshots::restore_snapshot(project_path, &checkpoint.id)?;
+    Ok(RevertResult {
+        success: true,
+        commits_reverted: 0,
+        new_commit: Some(restore.backup),
+        message: format!(
+            "Restored {} file(s) and removed {} from {} prompt #{}",
+            restore.restored, restore.removed, checkpoint.engine, checkpoint.prompt_index
+        ),
+        has_conflicts: false,
+    })
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Checkpoints of a project, newest first, optionally only one session's
+#[tauri::command]
+pub async fn list_checkpoints(
+    project_path: String,
+    session_id: Option<String>,
+) -> Result<Vec<Checkpoint>, String> {
+    let mut checkpoints: Vec<Checkpoint> = load_store(&project_path)
+        .checkpoints
+        .into_iter()
+        .filter(|c| match &session_id {
+            Some(id) => &c.session_id == id,
+            None => true,
+        })
+        .collect();
+    checkpoints.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+
+    match backend_for(&project_path).list_tags(&project_path) {
+        Ok(tags) => {
+            for checkpoint in &mut checkpoints {
+                checkpoint.names = tags
+                    .iter()
+                    .filter(|tag| tag.commit == checkpoint.id)
+                    .map(|tag| tag.name.clone())
+                    .collect();
+            }
+        }
+        Err(e) => log::debug!("[Checkpoints] Could not list tags: {}", e),
+    }
+    Ok(checkpoints)
+}
+
+/// Give a checkpoint a name by tagging its commit
+#[tauri::command]
+pub async fn name_checkpoint(
+    project_path: String,
+    checkpoint_id: String,
+    name: String,
+) -> Result<Checkpoint, String> {
+    simple_git::validate_tag_name(&name)?;
+    if snapshots::enabled(&project_path) {
+        return Err("Checkpoint names need git; this project uses snapshots".to_string());
+    }
+    GitOperation::new(&project_path, "git tag")
+        .run(move || {
+            let mut checkpoint = find_checkpoint(&project_path, &checkpoint_id)?;
+            let message = format!(
+                "Checkpoint: {} prompt #{}",
+                checkpoint.engine, checkpoint.prompt_index
+            );
+            backend_for(&project_path).create_tag(
+                &project_path,
+                &name,
+                Some(&checkpoint.id),
+                Some(&message),
+            )?;
+            log::info!("[Checkpoints] Named {} '{}'", checkpoint.id, name);
+            checkpoint.names.push(name);
+            Ok(checkpoint)
+        })
+        .await
+}
+
+/// Bring the working tree back to a checkpoint with a new commit, keeping history
+#[tauri::command]
+pub async fn restore_checkpoint(
+    project_path: String,
+    checkpoint_id: String,
+) -> Result<RevertResult, String> {
+    GitOperation::new(&project_path, "restore checkpoint")
+        .run(move || {
+            let checkpoint = find_checkpoint(&project_path, &checkpoint_id)?;
+            if snapshots::enabled(&project_path) {
+                return restore_snapshot_checkpoint(&project_path, &checkpoint);
+            }
+            let head = simple_git::git_current_commit(&project_path)?;
+            let short_id = &checkpoint.id[..8.min(checkpoint.id.len())];
+
+            let auto_stash = simple_git::git_stash_save(
+                &project_path,
+                &format!("Auto-stash before restoring checkpoint {}", short_id),
+            )?;
+            let message = format!(
+                "[Restore] Restore checkpoint {} ({} prompt #{})",
+                short_id, checkpoint.engine, checkpoint.prompt_index
+            );
+            let result = simple_git::git_revert_range_with_retry(
+                &project_path,
+                &checkpoint.id,
+                &head,
+                &message,
+                3,
+            );
+
+            match result {
+                Ok(result) if result.success => {
+                    log::info!("[Checkpoints] Restored checkpoint {}", short_id);
+                    Ok(result)
+                }
+                other => {
+                    simple_git::git_reset_hard_and_restore(
+                        &project_path,
+                        &head,
+                        auto_stash.as_deref(),
+                    )?;
+                    other
+                }
+            }
+        })
+        .await
+}
+
+/// What a checkpoint changed, or with `against_head` everything changed since it
+#[tauri::command]
+pub async fn diff_checkpoint(
+    project_path: String,
+    checkpoint_id: String,
+    against_head: Option<bool>,
+) -> Result<StructuredDiff, String> {
+    GitOperation::new(&project_path, "git diff")
+        .shared()
+        .run(move || {
+            let checkpoint = find_checkpoint(&project_path, &checkpoint_id)?;
+            if snapshots::enabled(&project_path) {
+                return match (against_head.unwrap_or(false), &checkpoint.parent) {
+                    (true, _) => snapshots::diff(&project_path, &checkpoint.id, None),
+                    (false, Some(parent)) => {
+                        snapshots::diff(&project_path, parent, Some(&checkpoint.id))
+                    }
+                    (false, None) => Err("The checkpoint has no parent snapshot".to_string()),
+                };
+            }
+            let (from, to) = if against_head.unwrap_or(false) {
+                (checkpoint.id.clone(), "HEAD".to_string())
+            } else {
+                let parent = checkpoint
+                    .parent
+                    .clone()
+                    .unwrap_or_else(|| format!("{}~1", checkpoint.id));
+                (parent, checkpoint.id.clone())
+            };
+            backend_for(&project_path)
+                .diff_commits(&project_path, &from, &to)
+                .map(|patch| parse_unified_diff(&patch))
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::{Git2Backend, GitBackend};
+
+    #[test]
+    fn test_record_checkpoint_excludes_sidecar() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let first = backend.current_commit(&path).unwrap();
+
+        record_checkpoint(
+            &path,
+            "claude",
+            "s1",
+            0,
+            Some("  fix the bug  "),
+            &first,
+            "abc123",
+        );
+        record_checkpoint(&path, "claude", "s1", 1, None, &first, &first);
+        let store = load_store(&path);
+        assert_eq!(store.checkpoints.len(), 1);
+        assert_eq!(store.checkpoints[0].prompt.as_deref(), Some("fix the bug"));
+
+        // The sidecar is not a change the next checkpoint would commit
+        assert!(!backend.has_uncommitted_changes(&path).unwrap());
+        ensure_sidecar_ignored(&path).unwrap();
+        let exclude = std::fs::read_to_string(dir.path().join(".git/info/exclude")).unwrap();
+        assert_eq!(exclude.matches(".anycode/").count(), 1);
+    }
+
+    #[test]
+    fn test_find_checkpoint_by_name() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let first = backend.current_commit(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "second").unwrap();
+        let second = backend.current_commit(&path).unwrap();
+        record_checkpoint(&path, "codex", "s1", 0, None, &first, &second);
+
+        backend
+            .create_tag(&path, "before-refactor", Some(&second), Some("Checkpoint"))
+            .unwrap();
+        backend
+            .create_tag(&path, "start", Some(&first), None)
+            .unwrap();
+        assert_eq!(
+            find_checkpoint(&path, "before-refactor").unwrap().id,
+            second
+        );
+        assert_eq!(find_checkpoint(&path, &second).unwrap().id, second);
+        assert!(find_checkpoint(&path, "start")
+            .unwrap_err()
+            .contains("does not name a checkpoint"));
+        assert!(find_checkpoint(&path, "missing").is_err());
+
+        let tags = backend.list_tags(&path).unwrap();
+        let summary: Vec<(&str, bool)> = tags
+            .iter()
+            .map(|t| (t.name.as_str(), t.message.is_some()))
+            .collect();
+        assert_eq!(summary, vec![("before-refactor", true), ("start", false)]);
+        backend.delete_tag(&path, "start").unwrap();
+        assert_eq!(backend.list_tags(&path).unwrap().len(), 1);
+    }
+}
@@ -6,14 +6,24 @@ use tauri::{AppHandle, Emitter, Manager};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
+use crate::commands::auto_checkpoint;
+use crate::commands::claude_profiles::{self, ClaudeProfile};
+use crate::commands::dev_containers;
+use crate::commands::engine_safety;
+use crate::commands::event_batcher;
+use crate::commands::project_defaults::{self, EngineDefaults};
 use crate::commands::permission_config::{
     build_execution_args, ClaudeExecutionConfig, ClaudePermissionConfig,
 };
+use crate::process::interrupt::{self, Shutdown};
+use crate::session_runner::{self, SessionRunner};
 #[cfg(windows)]
 use crate::process::JobObject;
+#[cfg(windows)]
+use crate::commands::wsl_utils;
 
 use super::config::get_claude_execution_config;
-use super::paths::{encode_project_path, get_claude_dir};
+use super::paths::encode_project_path;
 use super::platform;
 
 /// Global state to track current Claude process
@@ -94,13 +104,27 @@ pub(super) fn map_model_to_claude_alias(model: &str) -> String {
     }
 }
 
+/// Remember the model and mode used for this project, so reopening it restores them
+fn remember_project_defaults(project_path: &str, model: &str, plan_mode: bool) {
+    project_defaults::record_last_used(
+        project_path,
+        "claude",
+        EngineDefaults {
+            model: Some(model.to_string()),
+            reasoning_effort: None,
+            permission_mode: Some(if plan_mode { "plan" } else { "default" }.to_string()),
+        },
+    );
+}
+
 // 🔥 已移除 escape_prompt_for_cli 函数
 // prompt 现在通过 stdin 管道传递，不再需要命令行转义
 // 这样可以避免操作系统命令行长度限制（Windows ~8KB, Linux/macOS ~128KB-2MB）
 
 /// Helper function to create a tokio Command with proper environment variables
 /// This ensures commands like Claude can find Node.js and other dependencies
-fn create_command_with_env(program: &str) -> Command {
+/// When a Claude profile is given, its config directory and env overlay are applied
+fn create_command_with_env(program: &str, profile: Option<&ClaudeProfile>) -> Command {
     // On Windows, if the program is a .cmd file, try to resolve it to direct Node.js invocation
     // This prevents the cmd.exe window from appearing
     #[cfg(target_os = "windows")]
@@ -185,7 +209,8 @@ fn create_command_with_env(program: &str) -> Command {
 
     // 🔥 新增：读取 ~/.claude/settings.json 中的自定义环境变量
     // 这些变量会覆盖系统环境变量，确保用户的自定义配置生效
-    if let Ok(claude_dir) = get_claude_dir() {
+    // 使用 profile 时读取该 profile 配置目录下的 settings.json
+    if let Some(claude_dir) = claude_profiles::settings_dir(profile) {
         let settings_path = claude_dir.join("settings.json");
         if settings_path.exists() {
             if let Ok(content) = fs::read_to_string(&settings_path) {
@@ -207,6 +232,11 @@ fn create_command_with_env(program: &str) -> Command {
         }
     }
 
+    // Profile 的 CLAUDE_CONFIG_DIR 和环境变量覆盖 settings.json 中的值
+    if let Some(profile) = profile {
+        claude_profiles::apply_to_command(&mut tokio_cmd, profile);
+    }
+
     tokio_cmd
 }
 
@@ -218,8 +248,9 @@ fn create_system_command(
     project_path: &str,
     model: Option<&str>,
     _max_thinking_tokens: Option<u32>, // Keep parameter for compatibility but don't use it
+    profile: Option<&ClaudeProfile>,
 ) -> Result<Command, String> {
-    create_windows_command(claude_path, args, project_path, model)
+    create_windows_command(claude_path, args, project_path, model, profile)
 }
 
 /// Create a Windows command
@@ -229,8 +260,15 @@ fn create_windows_command(
     args: Vec<String>,
     project_path: &str,
     model: Option<&str>,
+    profile: Option<&ClaudeProfile>,
 ) -> Result<Command, String> {
-    let mut cmd = create_command_with_env(claude_path);
+    // WSL 内的项目必须使用发行版内的 Claude，而不是 Windows 版本
+    #[cfg(target_os = "windows")]
+    if let Some(location) = wsl_utils::detect_wsl_project(project_path) {
+        return Ok(create_wsl_command(args, project_path, &location, model));
+    }
+
+    let mut cmd = create_command_with_env(claude_path, profile);
 
     // 🔥 修复：设置ANTHROPIC_MODEL环境变量以确保模型选择生效
     if let Some(model_name) = model {
@@ -250,26 +288,57 @@ fn create_windows_command(
     // Set working directory
     cmd.current_dir(project_path);
 
+    // Dev container 模式：在项目容器内运行
+    if let Some(container_cmd) = dev_containers::container_command(project_path, "claude", &cmd)? {
+        cmd = container_cmd;
+    }
+
     // Configure stdio for capturing output
     // 🔥 添加 stdin pipe 以支持通过管道传递长文本 prompt
     cmd.stdin(Stdio::piped());
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // Apply platform-specific no-window configuration
-    platform::apply_no_window_async(&mut cmd);
-
-    // On Unix-like systems, create a new process group
-    // This allows us to kill the entire process tree with a single signal
-    #[cfg(unix)]
-    {
-        use std::os::unix::process::CommandExt;
-        cmd.process_group(0); // Create new process group, process becomes group leader
-    }
+    // Run Claude as the leader of its own process group, without a console window
+    // This lets cancellation interrupt the entire process tree with a single signal
+    interrupt::new_process_group(&mut cmd);
+    crate::process::limits::prepare_engine_command("claude", &mut cmd);
 
     Ok(cmd)
 }
 
+/// Create a command running Claude inside the WSL distro that holds the project
+#[cfg(target_os = "windows")]
+fn create_wsl_command(
+    args: Vec<String>,
+    project_path: &str,
+    location: &wsl_utils::WslProjectLocation,
+    model: Option<&str>,
+) -> Command {
+    let program = wsl_utils::wsl_engine_program("claude", location.distro.as_deref())
+        .unwrap_or_else(|| "claude".to_string());
+    log::info!(
+        "Project is inside WSL (distro: {:?}), running {} through wsl.exe",
+        location.distro,
+        program
+    );
+
+    let mut cmd = wsl_utils::build_wsl_command_async(
+        &program,
+        &args,
+        Some(project_path),
+        location.distro.as_deref(),
+    );
+    if let Some(model_name) = model {
+        cmd.env("ANTHROPIC_MODEL", model_name);
+        wsl_utils::forward_env_to_wsl(&mut cmd, &["ANTHROPIC_MODEL"]);
+    }
+    cmd.stdin(Stdio::piped());
+    cmd.stdout(Stdio::piped());
+    cmd.stderr(Stdio::piped());
+    cmd
+}
+
 /// Execute Claude Code session with project context resume and streaming output
 /// Always tries to resume project context first for better continuity
 /// Enhanced for Windows with better error handling
@@ -284,6 +353,10 @@ pub async fn execute_claude_code(
     tab_id: Option<String>,
 ) -> Result<(), String> {
     let plan_mode = plan_mode.unwrap_or(false);
+    if crate::engines::require("claude")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Claude")?;
+    }
+    remember_project_defaults(&project_path, &model, plan_mode);
     log::info!(
         "Starting Claude Code session with project context resume in: {} with model: {}, plan_mode: {}",
         project_path,
@@ -307,6 +380,9 @@ pub async fn execute_claude_code(
         log::info!("Setting maxThinkingTokens to {}", tokens);
     }
 
+    // 项目级安全策略（Plan Mode 仍然优先）
+    engine_safety::apply_claude_policy(&project_path, &mut execution_config.permissions);
+
     // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
     if plan_mode {
         execution_config.permissions = ClaudePermissionConfig::plan_mode();
@@ -325,14 +401,16 @@ pub async fn execute_claude_code(
     let args = build_execution_args(&execution_config, &mapped_model);
 
     // Create command
+    let profile = claude_profiles::active_profile();
     let cmd = create_system_command(
         &claude_path,
         args,
         &project_path,
         Some(&mapped_model),
         max_thinking_tokens,
+        profile.as_ref(),
     )?;
-    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id).await
+    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id, profile).await
 }
 
 /// Continue an existing Claude Code conversation with streaming output
@@ -348,6 +426,10 @@ pub async fn continue_claude_code(
     tab_id: Option<String>,
 ) -> Result<(), String> {
     let plan_mode = plan_mode.unwrap_or(false);
+    if crate::engines::require("claude")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Claude")?;
+    }
+    remember_project_defaults(&project_path, &model, plan_mode);
     log::info!(
         "Continuing Claude Code conversation in: {} with model: {}, plan_mode: {}",
         project_path,
@@ -371,6 +453,9 @@ pub async fn continue_claude_code(
         log::info!("Setting maxThinkingTokens to {}", tokens);
     }
 
+    // 项目级安全策略（Plan Mode 仍然优先）
+    engine_safety::apply_claude_policy(&project_path, &mut execution_config.permissions);
+
     // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
     if plan_mode {
         execution_config.permissions = ClaudePermissionConfig::plan_mode();
@@ -392,14 +477,16 @@ pub async fn continue_claude_code(
     args.insert(0, "-c".to_string());
 
     // Create command
+    let profile = claude_profiles::active_profile();
     let cmd = create_system_command(
         &claude_path,
         args,
         &project_path,
         Some(&mapped_model),
         max_thinking_tokens,
+        profile.as_ref(),
     )?;
-    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id).await
+    spawn_claude_process(app, cmd, prompt, model, project_path, tab_id, profile).await
 }
 
 /// Resume an existing Claude Code session by ID with streaming output
@@ -416,6 +503,10 @@ pub async fn resume_claude_code(
     tab_id: Option<String>,
 ) -> Result<(), String> {
     let plan_mode = plan_mode.unwrap_or(false);
+    if crate::engines::require("claude")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Claude")?;
+    }
+    remember_project_defaults(&project_path, &model, plan_mode);
     log::info!(
         "Resuming Claude Code session: {} in: {} with model: {}, plan_mode: {}",
         session_id,
@@ -451,6 +542,9 @@ pub async fn resume_claude_code(
         log::info!("Setting maxThinkingTokens to {}", tokens);
     }
 
+    // 项目级安全策略（Plan Mode 仍然优先）
+    engine_safety::apply_claude_policy(&project_path, &mut execution_config.permissions);
+
     // 如果启用 Plan Mode，使用 Claude CLI 原生的 plan 权限模式
     if plan_mode {
         execution_config.permissions = ClaudePermissionConfig::plan_mode();
@@ -474,6 +568,10 @@ pub async fn resume_claude_code(
 
     log::info!("Resume command: claude {}", args.join(" "));
 
+    // Resume with the profile the session was started with, so it finds the right account
+    let profile = claude_profiles::profile_for_session(&session_id)
+        .or_else(claude_profiles::active_profile);
+
     // Create command
     let cmd = create_system_command(
         &claude_path,
@@ -481,6 +579,7 @@ pub async fn resume_claude_code(
         &project_path,
         Some(&mapped_model),
         max_thinking_tokens,
+        profile.as_ref(),
     )?;
 
     // Try to spawn the process - if it fails, fall back to continue mode
@@ -491,6 +590,7 @@ pub async fn resume_claude_code(
         model.clone(),
         project_path.clone(),
         tab_id.clone(),
+        profile,
     )
     .await
     {
@@ -521,15 +621,23 @@ pub async fn cancel_claude_execution(
     app: AppHandle,
     session_id: Option<String>,
 ) -> Result<(), String> {
+    cancel_claude(&app, session_id).await;
+    Ok(())
+}
+
+/// Cancel a Claude Code execution, interrupting it before falling back to a kill
+///
+/// Returns how the process went away, or None when no running process was found.
+pub(crate) async fn cancel_claude(app: &AppHandle, session_id: Option<String>) -> Option<Shutdown> {
     log::info!(
         "Cancelling Claude Code execution for session: {:?}",
         session_id
     );
 
-    let mut killed = false;
+    let mut outcome = None;
     let mut attempted_methods = Vec::new();
 
-    // Method 1: Try to find and kill via ProcessRegistry using session ID
+    // Method 1: Try to find and interrupt via ProcessRegistry using session ID
     if let Some(sid) = &session_id {
         let registry = app.state::<crate::process::ProcessRegistryState>();
         match registry.0.get_claude_session_by_id(sid) {
@@ -540,17 +648,16 @@ pub async fn cancel_claude_execution(
                     process_info.run_id,
                     process_info.pid
                 );
-                match registry.0.kill_process(process_info.run_id).await {
-                    Ok(success) => {
-                        if success {
-                            log::info!("Successfully killed process via registry");
-                            killed = true;
-                        } else {
-                            log::warn!("Registry kill returned false");
-                        }
+                match registry.0.interrupt_process(process_info.run_id).await {
+                    Ok(Some(shutdown)) => {
+                        log::info!("Stopped process via registry ({:?})", shutdown);
+                        outcome = Some(shutdown);
+                    }
+                    Ok(None) => {
+                        log::warn!("Process exited before it could be interrupted");
                     }
                     Err(e) => {
-                        log::warn!("Failed to kill via registry: {}", e);
+                        log::warn!("Failed to interrupt via registry: {}", e);
                     }
                 }
                 attempted_methods.push("registry");
@@ -565,7 +672,7 @@ pub async fn cancel_claude_execution(
     }
 
     // Method 2: Try the legacy approach via ClaudeProcessState
-    if !killed {
+    if outcome.is_none() {
         let claude_state = app.state::<ClaudeProcessState>();
         let mut current_process = claude_state.current_process.lock().await;
 
@@ -581,7 +688,7 @@ pub async fn cancel_claude_execution(
             match child.kill().await {
                 Ok(_) => {
                     log::info!("Successfully killed Claude process via ClaudeProcessState");
-                    killed = true;
+                    outcome = Some(Shutdown::Forced);
                 }
                 Err(e) => {
                     log::error!(
@@ -595,7 +702,7 @@ pub async fn cancel_claude_execution(
                         match platform::kill_process_tree(pid) {
                             Ok(_) => {
                                 log::info!("Successfully killed process tree via platform module");
-                                killed = true;
+                                outcome = Some(Shutdown::Forced);
                             }
                             Err(e) => {
                                 log::error!("Failed to kill process tree: {}", e);
@@ -611,7 +718,7 @@ pub async fn cancel_claude_execution(
     }
 
     // Method 3: Try killing the last spawned PID when session_id is not available
-    if !killed {
+    if outcome.is_none() {
         let claude_state = app.state::<ClaudeProcessState>();
         let last_pid = { *claude_state.last_spawned_pid.lock().await };
         if let Some(pid) = last_pid {
@@ -623,7 +730,7 @@ pub async fn cancel_claude_execution(
                     if last_pid_guard.as_ref() == Some(&pid) {
                         *last_pid_guard = None;
                     }
-                    killed = true;
+                    outcome = Some(Shutdown::Forced);
                 }
                 Err(e) => {
                     log::error!("Failed to kill process tree via last spawned PID: {}", e);
@@ -633,11 +740,12 @@ pub async fn cancel_claude_execution(
         }
     }
 
-    if !killed && attempted_methods.is_empty() {
+    if outcome.is_none() && attempted_methods.is_empty() {
         log::warn!("No active Claude process found to cancel");
     }
 
     // Always emit cancellation events for UI consistency
+    event_batcher::flush_all(app);
     if let Some(sid) = session_id {
         let _ = app.emit(&format!("claude-cancelled:{}", sid), true);
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -649,13 +757,13 @@ pub async fn cancel_claude_execution(
     tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     let _ = app.emit("claude-complete", false);
 
-    if killed {
+    if outcome.is_some() {
         log::info!("Claude process cancellation completed successfully");
     } else if !attempted_methods.is_empty() {
         log::warn!("Claude process cancellation attempted but process may have already exited. Attempted methods: {:?}", attempted_methods);
     }
 
-    Ok(())
+    outcome
 }
 
 /// Get all running Claude sessions
@@ -698,6 +806,7 @@ async fn spawn_claude_process(
     model: String,
     project_path: String,
     tab_id: Option<String>,
+    profile: Option<ClaudeProfile>,
 ) -> Result<(), String> {
     use std::sync::Mutex;
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
@@ -711,10 +820,22 @@ async fn spawn_claude_process(
         cmd.arg(&prompt);
     }
 
+    let safety_flags = engine_safety::command_safety_flags(&cmd);
+    crate::commands::prompt_history::record_prompt(&project_path, "claude", &prompt);
+
     // Spawn the process
     let mut child = cmd
         .spawn()
-        .map_err(|e| format!("Failed to spawn Claude: {}", e))?;
+        .map_err(|e| {
+            crate::commands::engine_doctor::report_spawn_error(
+                &app,
+                "claude",
+                &cmd,
+                &project_path,
+                &e,
+            );
+            format!("Failed to spawn Claude: {}", e)
+        })?;
 
     // 🔥 普通 prompt 通过 stdin 管道传递，避免命令行长度限制
     // 斜杠命令已通过 -p 参数传递，不需要 stdin
@@ -758,6 +879,7 @@ async fn spawn_claude_process(
     // Get the child PID for logging
     let pid = child.id().unwrap_or(0);
     log::info!("Spawned Claude process with PID: {:?}", pid);
+    crate::commands::key_pools::record_request("claude");
 
     // 🔧 FIX: Create Job Object IMMEDIATELY after spawn, before Claude starts MCP servers
     // This ensures all child processes (including MCP node processes) are automatically
@@ -790,6 +912,12 @@ async fn spawn_claude_process(
         None
     };
 
+    // Apply configured CPU/memory ceilings before Claude starts its MCP servers
+    #[cfg(windows)]
+    crate::process::limits::apply_to_engine("claude", pid, job_object.as_deref());
+    #[cfg(not(windows))]
+    crate::process::limits::apply_to_engine("claude", pid, None);
+
     // Create readers first (before moving child)
     let stdout_reader = BufReader::new(stdout);
     let stderr_reader = BufReader::new(stderr);
@@ -835,11 +963,20 @@ async fn spawn_claude_process(
     let project_path_clone = project_path.clone();
     let prompt_clone = prompt.clone();
     let model_clone = model.clone();
+    let profile_id = profile.map(|p| p.id);
     // 🔒 CRITICAL FIX: 克隆 tab_id 用于事件发送
     let tab_id_for_stdout = tab_id.clone();
     // 🔧 FIX: Clone job_object_holder for passing to register_claude_session
     #[cfg(windows)]
     let job_object_holder_clone = job_object_holder.clone();
+    let mut session_events = SessionRunner::start(
+        &app,
+        "claude",
+        None,
+        tab_id.clone(),
+        &project_path,
+        Some(&prompt),
+    );
     let stdout_task = tokio::spawn(async move {
         let mut lines = stdout_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
@@ -855,6 +992,18 @@ async fn spawn_claude_process(
                             *session_id_guard = Some(claude_session_id.to_string());
                             log::info!("Extracted Claude session ID: {}", claude_session_id);
 
+                            // Record which profile (account) this session runs under
+                            claude_profiles::record_session_profile(
+                                claude_session_id,
+                                profile_id.as_deref(),
+                            );
+                            engine_safety::record_session(
+                                "claude",
+                                claude_session_id,
+                                &project_path_clone,
+                                safety_flags.clone(),
+                            );
+
                             // Register with auto-compact manager
                             if auto_compact_available {
                                 if let Some(auto_compact_state) = app_handle.try_state::<crate::commands::context_manager::AutoCompactState>() {
@@ -977,21 +1126,42 @@ async fn spawn_claude_process(
                 }
             }
 
+            // Snapshot before file edits when auto-checkpoints are per edit
+            let session_id_for_checkpoint =
+                { session_id_holder_clone.lock().unwrap().as_ref().cloned() };
+            if let Some(session_id_str) = &session_id_for_checkpoint {
+                auto_checkpoint::on_engine_output(
+                    &project_path_clone,
+                    "claude",
+                    session_id_str,
+                    &line,
+                );
+            }
+
             // Store live output in registry if we have a run_id
             if let Some(run_id) = *run_id_holder_clone.lock().unwrap() {
                 let _ = registry_clone.append_live_output(run_id, &line);
             }
 
-            // Emit the line to the frontend with session isolation if we have session ID
-            if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
-                let _ = app_handle.emit(&format!("claude-output:{}", session_id), &line);
+            if session_runner::raw_output_enabled() {
+                // Emit the line to the frontend with session isolation if we have session ID
+                if let Some(ref session_id) = *session_id_holder_clone.lock().unwrap() {
+                    let _ = event_batcher::emit(&app_handle, &format!("claude-output:{}", session_id), &line);
+                }
+                // 🔒 CRITICAL FIX: 全局事件包含 tab_id，用于前端过滤新建会话的消息
+                let global_payload = serde_json::json!({
+                    "tab_id": tab_id_for_stdout,
+                    "payload": &line
+                });
+                let _ = event_batcher::emit(&app_handle, "claude-output", &global_payload);
+            }
+
+            if let Some(events) = session_events.as_mut() {
+                events.push_line(&line).await;
             }
-            // 🔒 CRITICAL FIX: 全局事件包含 tab_id，用于前端过滤新建会话的消息
-            let global_payload = serde_json::json!({
-                "tab_id": tab_id_for_stdout,
-                "payload": &line
-            });
-            let _ = app_handle.emit("claude-output", &global_payload);
+        }
+        if let Some(events) = session_events {
+            events.finish().await;
         }
     });
 
@@ -1003,16 +1173,21 @@ async fn spawn_claude_process(
         let mut lines = stderr_reader.lines();
         while let Ok(Some(line)) = lines.next_line().await {
             log::error!("Claude stderr: {}", line);
+            crate::commands::key_pools::observe_engine_error(&app_handle_stderr, "claude", &line);
             // Emit error lines to the frontend with session isolation if we have session ID
             if let Some(ref session_id) = *session_id_holder_clone2.lock().unwrap() {
-                let _ = app_handle_stderr.emit(&format!("claude-error:{}", session_id), &line);
+                let _ = event_batcher::emit(
+                    &app_handle_stderr,
+                    &format!("claude-error:{}", session_id),
+                    &line,
+                );
             }
             // 🔒 CRITICAL FIX: 全局事件包含 tab_id
             let global_payload = serde_json::json!({
                 "tab_id": tab_id_for_stderr,
                 "payload": &line
             });
-            let _ = app_handle_stderr.emit("claude-error", &global_payload);
+            let _ = event_batcher::emit(&app_handle_stderr, "claude-error", &global_payload);
         }
     });
 
@@ -1029,6 +1204,7 @@ async fn spawn_claude_process(
     tokio::spawn(async move {
         let _ = stdout_task.await;
         let _ = stderr_task.await;
+        event_batcher::flush_all(&app_handle_wait);
 
         // 🔒 CRITICAL FIX: 直接等待 child，不再从全局 state 取出
         // child 已经被移动到这个 async block 中
@@ -6,6 +6,8 @@ use std::fs;
 use std::path::PathBuf;
 
 use super::models::FileEntry;
+use crate::commands::exclusion_policy::{self, ExclusionPolicy};
+use crate::commands::profiling;
 
 /// Lists files and directories in a given path
 ///
@@ -97,17 +99,24 @@ pub async fn list_directory_contents(directory_path: String) -> Result<Vec<FileE
 /// Search for files and directories matching a pattern
 ///
 /// Performs a recursive search within the specified base path,
-/// returning up to 50 matching entries sorted by relevance.
+/// returning up to 50 matching entries sorted by relevance. Paths matched by
+/// the project's exclusion policy are neither listed nor descended into.
 ///
 /// # Arguments
 /// * `base_path` - The base directory to search in
 /// * `query` - The search query (case-insensitive)
+/// * `package_scope` - Optional monorepo package directory (relative to `base_path`) to restrict the search to
 ///
 /// # Returns
 /// * `Ok(Vec<FileEntry>)` - List of matching entries
 /// * `Err(String)` - Error description if the operation fails
 #[tauri::command]
-pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEntry>, String> {
+pub async fn search_files(
+    base_path: String,
+    query: String,
+    package_scope: Option<String>,
+) -> Result<Vec<FileEntry>, String> {
+    let mut timer = profiling::CommandTimer::start("search_files");
     log::info!("Searching files in '{}' for: '{}'", base_path, query);
 
     // Check if path is empty
@@ -122,7 +131,14 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
         return Ok(Vec::new());
     }
 
-    let path = PathBuf::from(&base_path);
+    let root = PathBuf::from(&base_path);
+    let mut path = root.clone();
+    if let Some(package) = package_scope.as_deref().filter(|p| !p.is_empty()) {
+        if package.split(['/', '\\']).any(|part| part == "..") {
+            return Err(format!("Invalid package scope: {}", package));
+        }
+        path = path.join(package);
+    }
     log::debug!("Resolved search base path: {:?}", path);
 
     if !path.exists() {
@@ -133,7 +149,8 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
     let query_lower = query.to_lowercase();
     let mut results = Vec::new();
 
-    search_files_recursive(&path, &path, &query_lower, &mut results, 0)?;
+    let policy = exclusion_policy::policy_for_project(&base_path);
+    search_files_recursive(&path, &root, &query_lower, &policy, &mut results, 0)?;
 
     // Sort by relevance: exact matches first, then by name
     results.sort_by(|a, b| {
@@ -150,6 +167,7 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
     // Limit results to prevent overwhelming the UI
     results.truncate(50);
 
+    timer.payload(&results);
     Ok(results)
 }
 
@@ -157,14 +175,16 @@ pub async fn search_files(base_path: String, query: String) -> Result<Vec<FileEn
 ///
 /// # Arguments
 /// * `current_path` - Current directory being searched
-/// * `base_path` - Base search directory (for reference)
+/// * `base_path` - Project root, which exclusion patterns are relative to
 /// * `query` - Search query (lowercase)
+/// * `policy` - Exclusion policy of the project
 /// * `results` - Mutable reference to results vector
 /// * `depth` - Current recursion depth
 fn search_files_recursive(
     current_path: &PathBuf,
     base_path: &PathBuf,
     query: &str,
+    policy: &ExclusionPolicy,
     results: &mut Vec<FileEntry>,
     depth: usize,
 ) -> Result<(), String> {
@@ -181,9 +201,9 @@ fn search_files_recursive(
         let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
         let entry_path = entry.path();
 
-        // Skip hidden files/directories
+        // Skip hidden and excluded files/directories
         if let Some(name) = entry_path.file_name().and_then(|n| n.to_str()) {
-            if name.starts_with('.') {
+            if name.starts_with('.') || policy.is_path_excluded(base_path, &entry_path) {
                 continue;
             }
 
@@ -214,17 +234,7 @@ fn search_files_recursive(
 
         // Recurse into directories
         if entry_path.is_dir() {
-            // Skip common directories that shouldn't be searched
-            if let Some(dir_name) = entry_path.file_name().and_then(|n| n.to_str()) {
-                if matches!(
-                    dir_name,
-                    "node_modules" | "target" | ".git" | "dist" | "build" | ".next" | "__pycache__"
-                ) {
-                    continue;
-                }
-            }
-
-            search_files_recursive(&entry_path, base_path, query, results, depth + 1)?;
+            search_files_recursive(&entry_path, base_path, query, policy, results, depth + 1)?;
         }
     }
 
@@ -243,7 +253,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_search_files_empty_query() {
-        let result = search_files("/tmp".to_string(), "".to_string()).await;
+        let result = search_files("/tmp".to_string(), "".to_string(), None).await;
         assert!(result.is_ok());
         assert_eq!(result.unwrap().len(), 0);
     }
@@ -15,6 +15,7 @@ pub use self::cli_runner::{
     cancel_claude_execution, continue_claude_code, execute_claude_code, get_claude_session_output,
     list_running_claude_sessions, resume_claude_code, ClaudeProcessState,
 };
+pub(crate) use self::cli_runner::cancel_claude;
 pub use self::config::{
     check_claude_version, clear_custom_claude_path, find_claude_md_files, get_available_tools,
     get_claude_execution_config, get_claude_path, get_claude_permission_config,
@@ -28,21 +29,28 @@ pub use self::config::{
 };
 pub use self::hooks::{get_hooks_config, update_hooks_config, validate_hook_command};
 use self::project_store::ProjectStore;
+use crate::commands::profiling;
 pub use file_ops::{list_directory_contents, search_files};
 pub use platform::{apply_no_window_async, kill_process_tree};
 // Agent functionality removed
 
 #[tauri::command]
 pub async fn list_projects() -> Result<Vec<Project>, String> {
+    let mut timer = profiling::CommandTimer::start("list_projects");
     let store = ProjectStore::new()?;
-    store.list_projects()
+    let projects = store.list_projects()?;
+    timer.payload(&projects);
+    Ok(projects)
 }
 
 /// Gets sessions for a specific project
 #[tauri::command]
 pub async fn get_project_sessions(project_id: String) -> Result<Vec<Session>, String> {
+    let mut timer = profiling::CommandTimer::start("get_project_sessions");
     let store = ProjectStore::new()?;
-    store.get_project_sessions(&project_id)
+    let sessions = store.get_project_sessions(&project_id)?;
+    timer.payload(&sessions);
+    Ok(sessions)
 }
 
 /// Deletes a session and all its associated data
@@ -152,5 +160,8 @@ pub async fn load_session_history(
     session_id: String,
     project_id: String,
 ) -> Result<Vec<serde_json::Value>, String> {
-    session_history::load_session_history(&session_id, &project_id)
+    let mut timer = profiling::CommandTimer::start("load_session_history");
+    let history = session_history::load_session_history(&session_id, &project_id)?;
+    timer.payload(&history);
+    Ok(history)
 }
@@ -35,6 +35,9 @@ pub struct Session {
     pub last_message_timestamp: Option<String>,
     /// The model used in this session (if available)
     pub model: Option<String>,
+    /// Generated or user-assigned title (if available)
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 /// Represents a message entry in the JSONL file
@@ -2,11 +2,33 @@ use anyhow::{Context, Result};
 use std::fs;
 use std::path::PathBuf;
 
-/// Gets the path to the ~/.claude directory
+/// Gets the Claude config directory the spawned CLI uses
+///
+/// That is the active profile's config directory (passed as `CLAUDE_CONFIG_DIR`)
+/// when one is set, otherwise [`default_claude_dir`].
 pub fn get_claude_dir() -> Result<PathBuf> {
-    let claude_dir = dirs::home_dir()
-        .context("Could not find home directory")?
-        .join(".claude");
+    match crate::commands::claude_profiles::active_profile()
+        .and_then(|profile| profile.config_dir_path())
+    {
+        Some(claude_dir) => {
+            fs::create_dir_all(&claude_dir).with_context(|| {
+                format!("Failed to create Claude config directory {:?}", claude_dir)
+            })?;
+            Ok(claude_dir)
+        }
+        None => default_claude_dir(),
+    }
+}
+
+/// Gets the Claude config directory without a profile: `CLAUDE_CONFIG_DIR` if the
+/// app was started with it, otherwise ~/.claude
+pub fn default_claude_dir() -> Result<PathBuf> {
+    let claude_dir = match std::env::var_os("CLAUDE_CONFIG_DIR").filter(|dir| !dir.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => dirs::home_dir()
+            .context("Could not find home directory")?
+            .join(".claude"),
+    };
 
     // Ensure the directory exists
     fs::create_dir_all(&claude_dir).context("Failed to create ~/.claude directory")?;
@@ -96,6 +96,10 @@ impl ProjectStore {
                             decode_project_path(dir_name)
                         }
                     };
+                    // One-off prompts (titles, cached prompts) are not projects
+                    if crate::engines::is_prompt_dir(&project_path) {
+                        continue;
+                    }
 
                     let mut sessions = Vec::new();
                     let mut latest_activity = created_at;
@@ -170,6 +174,7 @@ impl ProjectStore {
             }
         };
 
+        let titles = crate::commands::session_titles::load_index();
         let mut sessions = Vec::new();
         let entries = fs::read_dir(&project_dir)
             .map_err(|e| format!("Failed to read project directory: {}", e))?;
@@ -244,6 +249,9 @@ impl ProjectStore {
                         message_timestamp,
                         last_message_timestamp,
                         model,
+                        title: crate::commands::session_titles::title_for(
+                            &titles, "claude", session_id,
+                        ),
                     });
                 }
             }
@@ -305,6 +313,9 @@ impl ProjectStore {
             }
         }
 
+        crate::commands::session_titles::remove_title("claude", session_id);
+        crate::commands::session_bookmarks::remove_session_bookmarks("claude", session_id);
+
         Ok(session_deleted)
     }
 
@@ -0,0 +1,349 @@
+//! Claude profiles (account switching)
+//!
+//! A profile is a named Claude configuration: either a separate config
+//! directory (passed to the CLI as `CLAUDE_CONFIG_DIR`, so a work and a personal
+//! account can stay logged in side by side) and/or an env overlay applied on
+//! top of `settings.json`. Profiles, the active profile and the profile each
+//! Claude session was started with live in `~/.anycode/claude-profiles.json`.
+//! Overlay values whose key looks like a secret are kept in the system keyring
+//! and replaced by a marker in that file. Resuming a session reuses the profile
+//! it was recorded with.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+use crate::mcp::secrets::SECRET_MARKER;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::{keyring_store, redaction};
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A named Claude configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeProfile {
+    pub id: String,
+    pub name: String,
+    /// Claude config directory for this profile; `None` uses the default `~/.claude`
+    pub config_dir: Option<String>,
+    /// Environment overlay applied after the settings.json `env` block
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl ClaudeProfile {
+    /// Resolved config directory (`~` expanded)
+    pub fn config_dir_path(&self) -> Option<PathBuf> {
+        let dir = self.config_dir.as_deref()?.trim();
+        if dir.is_empty() {
+            return None;
+        }
+        match dir.strip_prefix("~/").or_else(|| dir.strip_prefix("~\\")) {
+            Some(rest) => dirs::home_dir().map(|home| home.join(rest)),
+            None if dir == "~" => dirs::home_dir(),
+            None => Some(PathBuf::from(dir)),
+        }
+    }
+}
+
+/// Stored profiles and session assignments
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeProfileStore {
+    #[serde(default)]
+    pub profiles: Vec<ClaudeProfile>,
+    /// Profile used for new sessions; `None` is the default configuration
+    pub active_profile: Option<String>,
+    /// Claude session id → profile id the session was started with
+    #[serde(default)]
+    pub session_profiles: BTreeMap<String, String>,
+}
+
+/// Profiles overview returned to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClaudeProfilesInfo {
+    pub profiles: Vec<ClaudeProfile>,
+    pub active_profile: Option<String>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("claude-profiles.json"))
+}
+
+fn load_store() -> ClaudeProfileStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(
+    f: impl FnOnce(&mut ClaudeProfileStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    for profile in &mut store.profiles {
+        protect_env(profile)?;
+    }
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+fn keyring_name(profile_id: &str, key: &str) -> String {
+    format!("claude-profile-{}-{}", profile_id, key)
+}
+
+/// Whether an overlay value is a secret still stored in plaintext
+fn is_plaintext_secret(key: &str, value: &str) -> bool {
+    redaction::is_secret_key(key) && !value.is_empty() && value != SECRET_MARKER
+}
+
+/// Move the secret-looking overlay values of a profile into the keyring
+fn protect_env(profile: &mut ClaudeProfile) -> Result<(), String> {
+    for (key, value) in profile.env.iter_mut() {
+        if is_plaintext_secret(key, value) {
+            keyring_store::set_secret(&keyring_name(&profile.id, key), value)?;
+            *value = SECRET_MARKER.to_string();
+        }
+    }
+    Ok(())
+}
+
+/// The overlay with keyring values in place of their markers
+fn resolved_env(profile: &ClaudeProfile) -> BTreeMap<String, String> {
+    let mut env = BTreeMap::new();
+    for (key, value) in &profile.env {
+        let value = if value == SECRET_MARKER {
+            match keyring_store::get_secret(&keyring_name(&profile.id, key)) {
+                Ok(Some(secret)) => secret,
+                Ok(None) => {
+                    log::warn!(
+                        "[Claude Profiles] {} of profile '{}' is missing from the keyring",
+                        key,
+                        profile.name
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("[Claude Profiles] {}", e);
+                    continue;
+                }
+            }
+        } else {
+            value.clone()
+        };
+        // Only secrets are masked; base URLs and model ids stay readable in logs
+        if redaction::is_secret_key(key) {
+            redaction::register_secret(&value);
+        }
+        env.insert(key.clone(), value);
+    }
+    env
+}
+
+/// The profile new sessions should use, if one is active
+pub fn active_profile() -> Option<ClaudeProfile> {
+    let store = load_store();
+    let id = store.active_profile.as_ref()?;
+    store.profiles.into_iter().find(|p| &p.id == id)
+}
+
+/// The profile a session was started with
+pub fn profile_for_session(session_id: &str) -> Option<ClaudeProfile> {
+    let store = load_store();
+    let id = store.session_profiles.get(session_id)?;
+    store.profiles.into_iter().find(|p| &p.id == id)
+}
+
+/// Remember which profile a session used (`None` = default configuration)
+pub fn record_session_profile(session_id: &str, profile_id: Option<&str>) {
+    // Avoid rewriting (and triggering the config watcher) when nothing changes
+    if load_store().session_profiles.get(session_id).map(String::as_str) == profile_id {
+        return;
+    }
+    let result = update_store(|store| {
+        match profile_id {
+            Some(id) => store
+                .session_profiles
+                .insert(session_id.to_string(), id.to_string()),
+            None => store.session_profiles.remove(session_id),
+        };
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "[Claude Profiles] Failed to record profile for session {}: {}",
+            session_id,
+            e
+        );
+    }
+}
+
+/// Settings directory a Claude process will read for the given profile
+pub fn settings_dir(profile: Option<&ClaudeProfile>) -> Option<PathBuf> {
+    profile
+        .and_then(ClaudeProfile::config_dir_path)
+        .or_else(|| crate::commands::claude::default_claude_dir().ok())
+}
+
+/// Point a Claude command at the profile's config directory and apply its env overlay
+pub fn apply_to_command(cmd: &mut Command, profile: &ClaudeProfile) {
+    log::info!("[Claude Profiles] Using profile '{}'", profile.name);
+    if let Some(dir) = profile.config_dir_path() {
+        cmd.env("CLAUDE_CONFIG_DIR", dir);
+    }
+    for (key, value) in resolved_env(profile) {
+        cmd.env(key, value);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// List profiles and the active one
+#[tauri::command]
+pub async fn list_claude_profiles() -> Result<ClaudeProfilesInfo, String> {
+    let mut store = load_store();
+    // Profiles saved by earlier versions kept their secrets in plaintext
+    let plaintext = store.profiles.iter().any(|profile| {
+        profile
+            .env
+            .iter()
+            .any(|(key, value)| is_plaintext_secret(key, value))
+    });
+    if plaintext {
+        update_store(|_| Ok(()))?;
+        store = load_store();
+    }
+    Ok(ClaudeProfilesInfo {
+        profiles: store.profiles,
+        active_profile: store.active_profile,
+    })
+}
+
+/// Create or update a profile (an empty id creates a new one)
+#[tauri::command]
+pub async fn save_claude_profile(mut profile: ClaudeProfile) -> Result<ClaudeProfile, String> {
+    profile.name = profile.name.trim().to_string();
+    if profile.name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    if let Some(dir) = profile.config_dir_path() {
+        std::fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create config directory {:?}: {}", dir, e))?;
+    }
+
+    update_store(move |store| {
+        if store
+            .profiles
+            .iter()
+            .any(|p| p.name.eq_ignore_ascii_case(&profile.name) && p.id != profile.id)
+        {
+            return Err(format!("A profile named '{}' already exists", profile.name));
+        }
+
+        match store.profiles.iter_mut().find(|p| p.id == profile.id) {
+            Some(existing) => {
+                profile.created_at = existing.created_at;
+                *existing = profile.clone();
+            }
+            None => {
+                profile.id = uuid::Uuid::new_v4().to_string();
+                profile.created_at = Utc::now();
+                store.profiles.push(profile.clone());
+            }
+        }
+        Ok(profile)
+    })
+}
+
+/// Delete a profile; sessions recorded with it fall back to the default configuration
+#[tauri::command]
+pub async fn delete_claude_profile(id: String) -> Result<(), String> {
+    update_store(|store| {
+        for profile in store.profiles.iter().filter(|p| p.id == id) {
+            for (key, value) in &profile.env {
+                if value == SECRET_MARKER {
+                    keyring_store::delete_secret(&keyring_name(&id, key))?;
+                }
+            }
+        }
+        store.profiles.retain(|p| p.id != id);
+        store
+            .session_profiles
+            .retain(|_, profile_id| *profile_id != id);
+        if store.active_profile.as_deref() == Some(id.as_str()) {
+            store.active_profile = None;
+        }
+        Ok(())
+    })
+}
+
+/// Switch the profile used by new Claude sessions (`None` = default configuration)
+#[tauri::command]
+pub async fn switch_claude_profile(id: Option<String>) -> Result<(), String> {
+    update_store(|store| {
+        if let Some(id) = &id {
+            if !store.profiles.iter().any(|p| &p.id == id) {
+                return Err(format!("Claude profile not found: {}", id));
+            }
+        }
+        log::info!("[Claude Profiles] Active profile: {:?}", id);
+        store.active_profile = id;
+        Ok(())
+    })
+}
+
+/// Get the profile id a session was started with
+#[tauri::command]
+pub async fn get_session_claude_profile(session_id: String) -> Result<Option<String>, String> {
+    Ok(load_store().session_profiles.get(&session_id).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_dir_path() {
+        let mut profile = ClaudeProfile {
+            id: "work".to_string(),
+            name: "Work".to_string(),
+            config_dir: None,
+            env: BTreeMap::new(),
+            created_at: Utc::now(),
+        };
+        assert_eq!(profile.config_dir_path(), None);
+
+        profile.config_dir = Some("/tmp/claude-work".to_string());
+        assert_eq!(
+            profile.config_dir_path(),
+            Some(PathBuf::from("/tmp/claude-work"))
+        );
+
+        profile.config_dir = Some("~/.claude-work".to_string());
+        assert_eq!(
+            profile.config_dir_path(),
+            dirs::home_dir().map(|home| home.join(".claude-work"))
+        );
+    }
+
+    #[test]
+    fn test_only_secret_keys_are_protected() {
+        assert!(is_plaintext_secret("ANTHROPIC_API_KEY", "sk-ant-123"));
+        assert!(is_plaintext_secret("ANTHROPIC_AUTH_TOKEN", "abc"));
+        assert!(!is_plaintext_secret("ANTHROPIC_API_KEY", SECRET_MARKER));
+        assert!(!is_plaintext_secret(
+            "ANTHROPIC_BASE_URL",
+            "https://proxy.example.com"
+        ));
+        assert!(!is_plaintext_secret("ANTHROPIC_MODEL", "claude-sonnet"));
+    }
+}
@@ -313,7 +313,7 @@ pub async fn check_codex_availability() -> Result<CodexAvailability, String> {
 }
 
 /// 实际执行 Codex 可用性检测（内部函数）
-async fn do_check_codex_availability() -> CodexAvailability {
+pub(crate) async fn do_check_codex_availability() -> CodexAvailability {
     // 1) Windows: Check WSL mode first
     #[cfg(target_os = "windows")]
     {
@@ -979,7 +979,7 @@ fn should_use_wsl_config() -> bool {
 /// Get Codex config directory path (supports both Native and WSL modes)
 /// When WSL mode is enabled, returns the WSL UNC path (e.g., \\wsl$\Ubuntu\home\user\.codex)
 /// Otherwise returns the Windows native path (e.g., C:\Users\xxx\.codex)
-fn get_codex_config_dir() -> Result<PathBuf, String> {
+pub(super) fn get_codex_config_dir() -> Result<PathBuf, String> {
     // Check if WSL mode is enabled
     if should_use_wsl_config() {
         if let Some(wsl_dir) = wsl_utils::get_wsl_codex_dir() {
@@ -1001,7 +1001,7 @@ fn get_codex_auth_path() -> Result<PathBuf, String> {
 }
 
 /// Get Codex config.toml path
-fn get_codex_config_path() -> Result<PathBuf, String> {
+pub(super) fn get_codex_config_path() -> Result<PathBuf, String> {
     Ok(get_codex_config_dir()?.join("config.toml"))
 }
 
@@ -1022,6 +1022,41 @@ fn extract_api_key_from_auth(auth: &serde_json::Value) -> Option<String> {
         .map(|s| s.to_string())
 }
 
+/// Replace the API key in auth.json, keeping every other credential (used by key rotation)
+pub(crate) fn set_codex_api_key(key: &str) -> Result<(), String> {
+    let auth_path = get_codex_auth_path()?;
+    let mut auth: serde_json::Map<String, serde_json::Value> = if auth_path.exists() {
+        let content = fs::read_to_string(&auth_path)
+            .map_err(|e| format!("Failed to read auth.json: {}", e))?;
+        serde_json::from_str(&content).map_err(|e| format!("Failed to parse auth.json: {}", e))?
+    } else {
+        serde_json::Map::new()
+    };
+
+    auth.insert(
+        "OPENAI_API_KEY".to_string(),
+        serde_json::Value::String(key.to_string()),
+    );
+
+    if let Some(parent) = auth_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+    }
+    let content = serde_json::to_string_pretty(&auth)
+        .map_err(|e| format!("Failed to serialize auth: {}", e))?;
+    fs::write(&auth_path, content).map_err(|e| format!("Failed to write auth.json: {}", e))
+}
+
+/// Base URL of the configured provider, falling back to `OPENAI_BASE_URL`
+pub fn configured_base_url() -> Option<String> {
+    get_codex_config_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|config| extract_base_url_from_config(&config))
+        .or_else(|| std::env::var("OPENAI_BASE_URL").ok())
+        .filter(|url| !url.trim().is_empty())
+}
+
 /// Extract base_url from config.toml text
 fn extract_base_url_from_config(config: &str) -> Option<String> {
     let re = regex::Regex::new(r#"base_url\s*=\s*"([^"]+)""#).ok()?;
@@ -1248,6 +1283,9 @@ pub async fn switch_codex_provider(config: CodexProviderConfig) -> Result<String
 
     log::info!("[Codex Provider] Successfully switched to: {}", config.name);
 
+    // Use the provider's key pool, if it has one
+    crate::commands::key_pools::on_provider_switched("codex", &config.id);
+
     // Return success message with mode info
     let mode_info = if is_wsl_mode { " (WSL)" } else { "" };
     Ok(format!(
@@ -1438,6 +1476,7 @@ pub async fn clear_codex_provider_config() -> Result<String, String> {
     }
 
     log::info!("[Codex Provider] Successfully cleared config");
+    crate::commands::key_pools::on_provider_cleared("codex");
     Ok("Successfully cleared Codex configuration. Now using official OpenAI.".to_string())
 }
 
@@ -1449,6 +1488,8 @@ pub async fn test_codex_provider_connection(
 ) -> Result<String, String> {
     log::info!("[Codex Provider] Testing connection to: {}", base_url);
 
+    crate::commands::connectivity::ensure_online("Provider connection test")?;
+
     // Simple connectivity test - just try to reach the endpoint
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(10))
@@ -1489,8 +1530,7 @@ pub async fn update_codex_reasoning_level(level: String) -> Result<String, Strin
 
     // Validate level
     // Note: 'xhigh' is used in config.toml for extra high reasoning level
-    let valid_levels = ["low", "medium", "high", "xhigh"];
-    if !valid_levels.contains(&level.as_str()) {
+    if !super::config_toml::REASONING_EFFORTS.contains(&level.as_str()) {
         return Err(format!(
             "Invalid reasoning level: {}. Valid values are: low, medium, high, xhigh",
             level
@@ -1506,33 +1546,10 @@ pub async fn update_codex_reasoning_level(level: String) -> Result<String, Strin
     log::info!("[Codex] Config directory: {:?}", config_dir);
     log::info!("[Codex] Config path: {:?}", config_path);
 
-    // Ensure config directory exists
-    if !config_dir.exists() {
-        log::info!("[Codex] Creating config directory: {:?}", config_dir);
-        fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory at {:?}: {}", config_dir, e))?;
-    }
-
-    // Read existing config or create new one
-    let mut config_table: toml::Table = if config_path.exists() {
-        let existing_content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?;
-        toml::from_str(&existing_content).unwrap_or_else(|_| toml::Table::new())
-    } else {
-        toml::Table::new()
-    };
-
-    // Update reasoning level
-    config_table.insert(
-        "model_reasoning_effort".to_string(),
-        toml::Value::String(level.clone()),
-    );
-
-    // Write back to config.toml
-    let final_config = toml::to_string_pretty(&config_table)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&config_path, &final_config)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+    // Update reasoning level, preserving comments and formatting
+    let mut doc = super::config_toml::load_document(&config_path)?;
+    super::config_toml::set_string(doc.as_table_mut(), "model_reasoning_effort", &level);
+    super::config_toml::save_document(&config_path, &doc)?;
 
     log::info!("[Codex] Successfully updated reasoning level to: {}", level);
 
@@ -1610,59 +1627,37 @@ pub async fn get_codex_multi_agent_config() -> Result<CodexMultiAgentConfig, Str
 pub async fn set_codex_multi_agent_config(config: CodexMultiAgentConfig) -> Result<String, String> {
     log::info!("[Codex] Setting multi-agent config: enabled={}", config.enabled);
 
-    let config_dir = get_codex_config_dir()?;
     let config_path = get_codex_config_path()?;
+    let mut doc = super::config_toml::load_document(&config_path)?;
 
-    // Ensure config directory exists
-    if !config_dir.exists() {
-        fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+    // Update [features] section, preserving comments and formatting
+    if !doc.get("features").is_some_and(toml_edit::Item::is_table) {
+        doc["features"] = toml_edit::Item::Table(toml_edit::Table::new());
     }
+    if let Some(features_table) = doc["features"].as_table_mut() {
+        features_table["multi_agent"] = toml_edit::value(config.enabled);
 
-    // Read existing config
-    let mut config_table: toml::Table = if config_path.exists() {
-        let content = fs::read_to_string(&config_path)
-            .map_err(|e| format!("Failed to read config.toml: {}", e))?;
-        toml::from_str(&content).unwrap_or_else(|_| toml::Table::new())
-    } else {
-        toml::Table::new()
-    };
-
-    // Update [features] section
-    let features = config_table
-        .entry("features".to_string())
-        .or_insert_with(|| toml::Value::Table(toml::Table::new()));
-
-    if let Some(features_table) = features.as_table_mut() {
-        features_table.insert(
-            "multi_agent".to_string(),
-            toml::Value::Boolean(config.enabled),
-        );
-
-        if let Some(model) = &config.subagent_model {
-            features_table.insert(
-                "subagent_model".to_string(),
-                toml::Value::String(model.clone()),
-            );
-        } else {
-            features_table.remove("subagent_model");
+        match &config.subagent_model {
+            Some(model) => super::config_toml::set_string(features_table, "subagent_model", model),
+            None => {
+                features_table.remove("subagent_model");
+            }
         }
 
-        if let Some(effort) = &config.subagent_reasoning_effort {
-            features_table.insert(
-                "subagent_reasoning_effort".to_string(),
-                toml::Value::String(effort.clone()),
-            );
-        } else {
-            features_table.remove("subagent_reasoning_effort");
+        match &config.subagent_reasoning_effort {
+            Some(effort) => super::config_toml::set_string(
+                features_table,
+                "subagent_reasoning_effort",
+                effort,
+            ),
+            None => {
+                features_table.remove("subagent_reasoning_effort");
+            }
         }
     }
 
     // Write back
-    let final_config = toml::to_string_pretty(&config_table)
-        .map_err(|e| format!("Failed to serialize config: {}", e))?;
-    fs::write(&config_path, &final_config)
-        .map_err(|e| format!("Failed to write config.toml: {}", e))?;
+    super::config_toml::save_document(&config_path, &doc)?;
 
     log::info!("[Codex] Multi-agent config updated successfully");
     Ok(format!(
@@ -0,0 +1,256 @@
+/**
+ * Codex config.toml Management
+ *
+ * Typed read/update commands for the settings the workbench manages in
+ * Codex's config.toml (model, reasoning effort, approval policy, sandbox
+ * mode, MCP servers). Edits go through toml_edit so the user's comments,
+ * key order and formatting survive.
+ */
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use toml_edit::{DocumentMut, Item, Table};
+
+use super::config::{get_codex_config_dir, get_codex_config_path};
+
+pub const APPROVAL_POLICIES: &[&str] = &["untrusted", "on-failure", "on-request", "never"];
+pub const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+pub const REASONING_EFFORTS: &[&str] = &["low", "medium", "high", "xhigh"];
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Settings read from config.toml
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexTomlSettings {
+    pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub model_reasoning_effort: Option<String>,
+    pub approval_policy: Option<String>,
+    pub sandbox_mode: Option<String>,
+    /// MCP servers in the same JSON shape used by the MCP registry
+    pub mcp_servers: BTreeMap<String, Value>,
+}
+
+/// Partial update of config.toml
+///
+/// `None` leaves a setting untouched, an empty string removes the key.
+/// `mcp_servers` replaces the whole server set when present.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexTomlUpdate {
+    pub model: Option<String>,
+    pub model_provider: Option<String>,
+    pub model_reasoning_effort: Option<String>,
+    pub approval_policy: Option<String>,
+    pub sandbox_mode: Option<String>,
+    pub mcp_servers: Option<HashMap<String, Value>>,
+}
+
+// ============================================================================
+// Document Helpers
+// ============================================================================
+
+/// Load config.toml as an editable document (empty when missing)
+pub(super) fn load_document(path: &Path) -> Result<DocumentMut, String> {
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read config.toml: {}", e))?;
+    content
+        .parse::<DocumentMut>()
+        .map_err(|e| format!("Failed to parse config.toml: {}", e))
+}
+
+/// Write the document back, creating the config directory if needed
+pub(super) fn save_document(path: &Path, doc: &DocumentMut) -> Result<(), String> {
+    let config_dir = get_codex_config_dir()?;
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .map_err(|e| format!("Failed to create .codex directory: {}", e))?;
+    }
+    fs::write(path, doc.to_string()).map_err(|e| format!("Failed to write config.toml: {}", e))
+}
+
+/// Set a string key, keeping the existing value's trailing comment; an empty value removes the key
+pub(super) fn set_string(table: &mut Table, key: &str, value: &str) {
+    let value = value.trim();
+    if value.is_empty() {
+        table.remove(key);
+        return;
+    }
+    match table.get_mut(key).and_then(Item::as_value_mut) {
+        Some(existing) => {
+            let decor = existing.decor().clone();
+            *existing = toml_edit::Value::from(value);
+            *existing.decor_mut() = decor;
+        }
+        None => {
+            table.insert(key, toml_edit::value(value));
+        }
+    }
+}
+
+fn validate(name: &str, value: &Option<String>, allowed: &[&str]) -> Result<(), String> {
+    match value.as_deref().map(str::trim) {
+        Some(v) if !v.is_empty() && !allowed.contains(&v) => Err(format!(
+            "Invalid {}: {}. Valid values are: {}",
+            name,
+            v,
+            allowed.join(", ")
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Extract the typed settings from config.toml content
+fn parse_settings(content: &str) -> Result<CodexTomlSettings, String> {
+    let root: toml::Table =
+        toml::from_str(content).map_err(|e| format!("Failed to parse config.toml: {}", e))?;
+    let string = |key: &str| root.get(key).and_then(|v| v.as_str()).map(String::from);
+
+    let mcp_servers = root
+        .get("mcp_servers")
+        .and_then(|v| v.as_table())
+        .map(|servers| {
+            servers
+                .iter()
+                .filter_map(|(id, entry)| {
+                    crate::codex_mcp::toml_server_to_json(id, entry).map(|spec| (id.clone(), spec))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(CodexTomlSettings {
+        model: string("model"),
+        model_provider: string("model_provider"),
+        model_reasoning_effort: string("model_reasoning_effort"),
+        approval_policy: string("approval_policy"),
+        sandbox_mode: string("sandbox_mode"),
+        mcp_servers,
+    })
+}
+
+/// Apply an update to the document
+fn apply_update(doc: &mut DocumentMut, update: &CodexTomlUpdate) -> Result<(), String> {
+    validate(
+        "approval policy",
+        &update.approval_policy,
+        APPROVAL_POLICIES,
+    )?;
+    validate("sandbox mode", &update.sandbox_mode, SANDBOX_MODES)?;
+    validate(
+        "reasoning level",
+        &update.model_reasoning_effort,
+        REASONING_EFFORTS,
+    )?;
+
+    let root = doc.as_table_mut();
+    for (key, value) in [
+        ("model", &update.model),
+        ("model_provider", &update.model_provider),
+        ("model_reasoning_effort", &update.model_reasoning_effort),
+        ("approval_policy", &update.approval_policy),
+        ("sandbox_mode", &update.sandbox_mode),
+    ] {
+        if let Some(value) = value {
+            set_string(root, key, value);
+        }
+    }
+
+    if let Some(servers) = &update.mcp_servers {
+        crate::codex_mcp::merge_mcp_servers(doc, servers);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read the workbench-managed settings from Codex's config.toml
+#[tauri::command]
+pub async fn get_codex_toml_settings() -> Result<CodexTomlSettings, String> {
+    let config_path = get_codex_config_path()?;
+    if !config_path.exists() {
+        return Ok(CodexTomlSettings::default());
+    }
+    let content = fs::read_to_string(&config_path)
+        .map_err(|e| format!("Failed to read config.toml: {}", e))?;
+    parse_settings(&content)
+}
+
+/// Update settings in Codex's config.toml without touching anything else in the file
+#[tauri::command]
+pub async fn update_codex_toml_settings(
+    update: CodexTomlUpdate,
+) -> Result<CodexTomlSettings, String> {
+    log::info!("[Codex] Updating config.toml settings");
+
+    let config_path = get_codex_config_path()?;
+    let mut doc = load_document(&config_path)?;
+    apply_update(&mut doc, &update)?;
+    save_document(&config_path, &doc)?;
+
+    parse_settings(&doc.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"# My Codex settings
+model = "gpt-5" # default model
+approval_policy = "on-request"
+
+[mcp_servers.docs]
+type = "stdio"
+command = "node"
+args = ["docs.js"]
+startup_timeout_sec = 30 # slow server
+"#;
+
+    #[test]
+    fn test_update_preserves_comments_and_unknown_keys() {
+        let mut doc = SAMPLE.parse::<DocumentMut>().unwrap();
+        let mut servers = HashMap::new();
+        servers.insert(
+            "docs".to_string(),
+            serde_json::json!({ "type": "stdio", "command": "bun", "args": ["docs.js"] }),
+        );
+        let update = CodexTomlUpdate {
+            model: Some("gpt-5-codex".to_string()),
+            approval_policy: Some(String::new()),
+            sandbox_mode: Some("workspace-write".to_string()),
+            mcp_servers: Some(servers),
+            ..Default::default()
+        };
+        apply_update(&mut doc, &update).unwrap();
+        let output = doc.to_string();
+
+        assert!(output.starts_with("# My Codex settings"));
+        assert!(output.contains("model = \"gpt-5-codex\" # default model"));
+        assert!(!output.contains("approval_policy"));
+        assert!(output.contains("startup_timeout_sec = 30 # slow server"));
+
+        let settings = parse_settings(&output).unwrap();
+        assert_eq!(settings.sandbox_mode.as_deref(), Some("workspace-write"));
+        assert_eq!(settings.mcp_servers["docs"]["command"], "bun");
+    }
+
+    #[test]
+    fn test_update_rejects_invalid_values() {
+        let mut doc = DocumentMut::new();
+        let update = CodexTomlUpdate {
+            sandbox_mode: Some("everything".to_string()),
+            ..Default::default()
+        };
+        assert!(apply_update(&mut doc, &update).is_err());
+    }
+}
@@ -13,7 +13,14 @@ use std::fs;
 use std::path::PathBuf;
 
 // Import simple_git for rewind operations
+use super::super::auto_checkpoint;
+use super::super::checkpoints;
+use super::super::commit_metadata;
+use super::super::commit_templates;
+use super::super::git_executor::GitOperation;
+use super::super::monorepo;
 use super::super::simple_git;
+use super::super::snapshots;
 // Import rewind helpers/types shared with Claude
 use super::super::prompt_tracker::{
     load_execution_config, PromptRecord as ClaudePromptRecord, RewindCapabilities, RewindMode,
@@ -250,23 +257,6 @@ pub async fn get_codex_prompt_list(session_id: String) -> Result<Vec<PromptRecor
     extract_codex_prompts(&session_id)
 }
 
-fn build_prompt_commit_message(
-    prefix: &str,
-    prompt_text: Option<&str>,
-    prompt_index: usize,
-) -> String {
-    let prompt_text = prompt_text.unwrap_or("");
-    let sanitized = prompt_text.replace('\n', " ").replace('\r', " ");
-    let sanitized = sanitized.trim();
-    let truncated: String = sanitized.chars().take(80).collect();
-
-    if truncated.is_empty() {
-        return format!("{prefix} After prompt #{prompt_index}");
-    }
-
-    format!("{prefix} {truncated} prompt #{prompt_index}")
-}
-
 // ============================================================================
 // Rewind Capabilities
 // ============================================================================
@@ -519,13 +509,30 @@ pub async fn record_codex_prompt_sent(
         return Ok(prompt_index);
     }
 
-    // Ensure Git repository is initialized
-    simple_git::ensure_git_repo(&project_path)
-        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
-
-    // Get current commit (state before prompt execution)
-    let commit_before = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+    let commit_before = {
+        let project_path = project_path.clone();
+        let session_id = session_id.clone();
+        GitOperation::new(&project_path, "record prompt")
+            .run(move || {
+                Ok(if snapshots::enabled(&project_path) {
+                    // Snapshot projects never get a Git repository; a snapshot id stands in for the commit
+                    snapshots::take_snapshot(&project_path)
+                        .map_err(|e| format!("Failed to snapshot project: {}", e))?
+                } else {
+                    // Ensure Git repository is initialized
+                    simple_git::ensure_git_repo(&project_path)
+                        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
+
+                    // Snapshot uncommitted work first, so the prompt's starting point includes it
+                    auto_checkpoint::before_run(&project_path, "codex", &session_id);
+
+                    // Get current commit (state before prompt execution)
+                    simple_git::git_current_commit(&project_path)
+                        .map_err(|e| format!("Failed to get current commit: {}", e))?
+                })
+            })
+            .await?
+    };
 
     // Load existing records
     let mut git_records = load_codex_git_records(&session_id)?;
@@ -581,30 +588,59 @@ pub async fn record_codex_prompt_completed(
         return Ok(());
     }
 
-    // Auto-commit any changes made by AI
-    let commit_message = build_prompt_commit_message("[Codex]", prompt_text.as_deref(), prompt_index);
-    match simple_git::git_commit_changes(&project_path, &commit_message) {
-        Ok(true) => {
-            log::info!(
-                "[Codex Record] Auto-committed changes after prompt #{}",
-                prompt_index
-            );
-        }
-        Ok(false) => {
-            log::debug!(
-                "[Codex Record] No changes to commit after prompt #{}",
-                prompt_index
-            );
-        }
-        Err(e) => {
-            log::warn!("[Codex Record] Failed to auto-commit: {}", e);
-            // Continue anyway
-        }
-    }
+    let commit_after = {
+        let project_path = project_path.clone();
+        let session_id = session_id.clone();
+        let prompt_text = prompt_text.clone();
+        GitOperation::new(&project_path, "auto-commit")
+            .run(move || {
+                Ok(if snapshots::enabled(&project_path) {
+                    snapshots::take_snapshot(&project_path)
+                        .map_err(|e| format!("Failed to snapshot project: {}", e))?
+                } else {
+                    // Auto-commit any changes made by AI
+                    let package_scope = monorepo::session_package_scope(&session_id);
+                    let commit_message = commit_metadata::tag_message(
+                        &commit_templates::prompt_commit_message(
+                            &project_path,
+                            "codex",
+                            prompt_text.as_deref(),
+                            prompt_index,
+                            package_scope.as_deref(),
+                        ),
+                        "codex",
+                        Some(&session_id),
+                    );
+                    match simple_git::git_commit_changes_in(
+                        &project_path,
+                        &commit_message,
+                        package_scope.as_deref(),
+                    ) {
+                        Ok(outcome) if outcome.committed => {
+                            log::info!(
+                                "[Codex Record] Auto-committed changes after prompt #{}",
+                                prompt_index
+                            );
+                        }
+                        Ok(_) => {
+                            log::debug!(
+                                "[Codex Record] No changes to commit after prompt #{}",
+                                prompt_index
+                            );
+                        }
+                        Err(e) => {
+                            log::warn!("[Codex Record] Failed to auto-commit: {}", e);
+                            // Continue anyway
+                        }
+                    }
 
-    // Get current commit (state after AI completion)
-    let commit_after = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+                    // Get current commit (state after AI completion)
+                    simple_git::git_current_commit(&project_path)
+                        .map_err(|e| format!("Failed to get current commit: {}", e))?
+                })
+            })
+            .await?
+    };
 
     // Update the record
     let mut git_records = load_codex_git_records(&session_id)?;
@@ -615,6 +651,16 @@ pub async fn record_codex_prompt_completed(
         .find(|r| r.prompt_index == prompt_index)
     {
         record.commit_after = Some(commit_after.clone());
+        checkpoints::record_checkpoint(
+            &project_path,
+            "codex",
+            &session_id,
+            prompt_index,
+            prompt_text.as_deref(),
+            &record.commit_before,
+            &commit_after,
+        );
+
         save_codex_git_records(&session_id, &git_records)?;
 
         log::info!(
@@ -693,6 +739,24 @@ pub async fn revert_codex_to_prompt(
         RewindMode::ConversationOnly => {}
     }
 
+    // Snapshot projects have no commits to revert: put the files back as they were
+    if let (RewindMode::CodeOnly | RewindMode::Both, Some(record)) = (&mode, git_record) {
+        if snapshots::enabled(&project_path) {
+            let restore = snapshots::restore_snapshot(&project_path, &record.commit_before)?;
+            log::info!(
+                "[Codex Rewind] Restored snapshot before prompt #{} ({} written, {} deleted)",
+                prompt_index,
+                restore.restored,
+                restore.removed
+            );
+            if mode == RewindMode::Both {
+                truncate_codex_session_to_prompt(&session_id, prompt_index)?;
+                truncate_codex_git_records(&session_id, prompt_index)?;
+            }
+            return Ok(prompt.text.clone());
+        }
+    }
+
     // Execute revert based on mode
     match mode {
         RewindMode::ConversationOnly => {
@@ -716,7 +780,7 @@ pub async fn revert_codex_to_prompt(
             log::info!("[Codex Rewind] Reverting code to state before prompt #{}", prompt_index);
 
             // Stash uncommitted changes
-            simple_git::git_stash_save(
+            let auto_stash = simple_git::git_stash_save(
                 &project_path,
                 &format!(
                     "Auto-stash before Codex code revert to prompt #{}",
@@ -843,8 +907,12 @@ pub async fn revert_codex_to_prompt(
                     "[Codex Precise Revert] Rolling back to original HEAD {} due to failure",
                     &original_head[..8.min(original_head.len())]
                 );
-                simple_git::git_reset_hard(&project_path, &original_head)
-                    .map_err(|e| format!("Failed to rollback: {}", e))?;
+                simple_git::git_reset_hard_and_restore(
+                    &project_path,
+                    &original_head,
+                    auto_stash.as_deref(),
+                )
+                .map_err(|e| format!("Failed to rollback: {}", e))?;
 
                 return Err(format!(
                     "撤回失败，已回滚到操作前状态。原因: {}",
@@ -864,7 +932,7 @@ pub async fn revert_codex_to_prompt(
             log::info!("[Codex Rewind] Reverting both to state before prompt #{}", prompt_index);
 
             // Stash uncommitted changes
-            simple_git::git_stash_save(
+            let auto_stash = simple_git::git_stash_save(
                 &project_path,
                 &format!(
                     "Auto-stash before Codex full revert to prompt #{}",
@@ -991,8 +1059,12 @@ pub async fn revert_codex_to_prompt(
                     "[Codex Precise Revert] Rolling back to original HEAD {} due to failure",
                     &original_head[..8.min(original_head.len())]
                 );
-                simple_git::git_reset_hard(&project_path, &original_head)
-                    .map_err(|e| format!("Failed to rollback: {}", e))?;
+                simple_git::git_reset_hard_and_restore(
+                    &project_path,
+                    &original_head,
+                    auto_stash.as_deref(),
+                )
+                .map_err(|e| format!("Failed to rollback: {}", e))?;
 
                 return Err(format!(
                     "撤回失败，已回滚到操作前状态。原因: {}",
@@ -1015,7 +1087,11 @@ pub async fn revert_codex_to_prompt(
                     e
                 );
 
-                if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
+                if let Err(rollback_err) = simple_git::git_reset_hard_and_restore(
+                    &project_path,
+                    &original_head,
+                    auto_stash.as_deref(),
+                ) {
                     log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
                     return Err(format!(
                         "会话截断失败且 Git 回滚失败。\n\
@@ -1040,7 +1116,11 @@ pub async fn revert_codex_to_prompt(
                         e
                     );
 
-                    if let Err(rollback_err) = simple_git::git_reset_hard(&project_path, &original_head) {
+                    if let Err(rollback_err) = simple_git::git_reset_hard_and_restore(
+                        &project_path,
+                        &original_head,
+                        auto_stash.as_deref(),
+                    ) {
                         log::error!("[CRITICAL] Git rollback failed: {}", rollback_err);
                         return Err(format!(
                             "Git 记录截断失败且回滚失败。\n\
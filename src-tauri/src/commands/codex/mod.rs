@@ -8,8 +8,10 @@
  * - session.rs: Session lifecycle management (execute, resume, cancel, list, delete)
  * - git_ops.rs: Git operations for rewind functionality (records, truncate, revert)
  * - config.rs: Configuration management (availability, paths, mode, providers)
+ * - config_toml.rs: Typed, format-preserving config.toml settings
  */
 pub mod config;
+pub mod config_toml;
 pub mod git_ops;
 pub mod session;
 pub mod session_converter;
@@ -31,6 +33,10 @@ pub use git_ops::{CodexGitRecords, CodexPromptGitRecord, CodexPromptRecord, Prom
 #[allow(unused_imports)]
 pub use config::{CodexAvailability, CodexModeInfo, CodexMultiAgentConfig, CodexProviderConfig, CurrentCodexConfig};
 
+// config.toml types
+#[allow(unused_imports)]
+pub use config_toml::{CodexTomlSettings, CodexTomlUpdate};
+
 // Session converter types
 #[allow(unused_imports)]
 pub use session_converter::{ConversionResult, ConversionSource};
@@ -73,6 +79,12 @@ pub use config::{
     test_codex_provider_connection, update_codex_provider_config, update_codex_reasoning_level,
 };
 
+// ============================================================================
+// Re-export Tauri Commands - config.toml Settings
+// ============================================================================
+
+pub use config_toml::{get_codex_toml_settings, update_codex_toml_settings};
+
 // ============================================================================
 // Re-export Tauri Commands - Session Conversion
 // ============================================================================
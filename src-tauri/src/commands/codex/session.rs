@@ -6,6 +6,7 @@
  * - Session listing and history
  * - Session deletion
  */
+use futures::future::join_all;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::process::Stdio;
@@ -18,8 +19,13 @@ use tokio::sync::Mutex;
 
 // Import platform-specific utilities for window hiding
 use crate::claude_binary::detect_binary_for_tool;
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::auto_checkpoint;
+use crate::commands::dev_containers;
+use crate::commands::engine_safety;
+use crate::commands::event_batcher;
+use crate::process::interrupt::{self, Shutdown};
 use crate::process::JobObject;
+use crate::session_runner::{self, SessionRunner};
 // Import WSL utilities for Windows + WSL Codex support
 use super::super::wsl_utils;
 // Import config module for sessions directory
@@ -47,6 +53,16 @@ impl Default for CodexExecutionMode {
     }
 }
 
+impl CodexExecutionMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only",
+            Self::FullAuto => "full-auto",
+            Self::DangerFullAccess => "danger-full-access",
+        }
+    }
+}
+
 /// Codex execution options
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
@@ -123,6 +139,10 @@ pub struct CodexSession {
 
     /// Last message timestamp (ISO string)
     pub last_message_timestamp: Option<String>,
+
+    /// Generated or user-assigned title
+    #[serde(default)]
+    pub title: Option<String>,
 }
 
 /// Codex process handle with PID for proper cleanup
@@ -174,6 +194,21 @@ pub async fn execute_codex(
         options.prompt.len()
     );
 
+    if crate::engines::require("codex")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Codex")?;
+    }
+
+    // Remember the model and mode used for this project
+    crate::commands::project_defaults::record_last_used(
+        &options.project_path,
+        "codex",
+        crate::commands::project_defaults::EngineDefaults {
+            model: options.model.clone(),
+            reasoning_effort: None,
+            permission_mode: Some(options.mode.as_str().to_string()),
+        },
+    );
+
     // Build codex exec command
     let (cmd, prompt) = build_codex_command(&options, false, None)?;
 
@@ -191,6 +226,10 @@ pub async fn resume_codex(
 ) -> Result<(), String> {
     log::info!("resume_codex called for session: {}", session_id);
 
+    if crate::engines::require("codex")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Codex")?;
+    }
+
     // Build codex exec resume command (session_id added inside build function)
     let (cmd, prompt) = build_codex_command(&options, true, Some(&session_id))?;
 
@@ -214,6 +253,10 @@ pub async fn resume_last_codex(
 ) -> Result<(), String> {
     log::info!("resume_last_codex called");
 
+    if crate::engines::require("codex")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Codex")?;
+    }
+
     // Build codex exec resume --last command
     let (cmd, prompt) = build_codex_command(&options, true, Some("--last"))?;
 
@@ -225,54 +268,58 @@ pub async fn resume_last_codex(
 /// Cancels a running Codex execution
 #[tauri::command]
 pub async fn cancel_codex(session_id: Option<String>, app_handle: AppHandle) -> Result<(), String> {
-    use crate::commands::claude::kill_process_tree;
-
     log::info!("cancel_codex called for session: {:?}", session_id);
 
-    let state: tauri::State<'_, CodexProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
-
     if let Some(sid) = session_id {
         // Cancel specific session
-        if let Some(handle) = processes.remove(&sid) {
-            let pid = handle.pid;
-            log::info!("Killing Codex process tree for session: {} (PID: {})", sid, pid);
-
-            // Kill the entire process tree (parent + all children)
-            if let Err(e) = kill_process_tree(pid) {
-                log::error!("Failed to kill process tree for session {}: {}", sid, e);
-                // Fallback: try to kill main process directly
-                let mut child = handle.child;
-                if let Err(e2) = child.kill().await {
-                    log::error!("Fallback kill also failed: {}", e2);
-                }
-            } else {
-                log::info!("Successfully killed Codex process tree for session: {}", sid);
-            }
-        } else {
+        if stop_codex(&app_handle, &sid).await.is_none() {
             log::warn!("No running process found for session: {}", sid);
         }
     } else {
         // Cancel all processes
-        for (sid, handle) in processes.drain() {
-            let pid = handle.pid;
-            log::info!("Killing Codex process tree for session: {} (PID: {})", sid, pid);
-
-            if let Err(e) = kill_process_tree(pid) {
-                log::error!("Failed to kill process tree for session {}: {}", sid, e);
-                let mut child = handle.child;
-                if let Err(e2) = child.kill().await {
-                    log::error!("Fallback kill also failed: {}", e2);
-                }
-            } else {
-                log::info!("Successfully killed Codex process tree for session: {}", sid);
-            }
-        }
+        stop_all_codex(&app_handle).await;
     }
 
     Ok(())
 }
 
+/// Interrupts a running Codex session and waits until its process tree is gone
+///
+/// Returns None when the session has no running process.
+pub(crate) async fn stop_codex(app_handle: &AppHandle, session_id: &str) -> Option<Shutdown> {
+    let state: tauri::State<'_, CodexProcessState> = app_handle.state();
+    let handle = state.processes.lock().await.remove(session_id)?;
+    Some(shutdown_codex_process(session_id, handle).await)
+}
+
+/// Interrupts every running Codex session, returning how many were stopped
+pub(crate) async fn stop_all_codex(app_handle: &AppHandle) -> usize {
+    let state: tauri::State<'_, CodexProcessState> = app_handle.state();
+    let handles: Vec<_> = state.processes.lock().await.drain().collect();
+    let count = handles.len();
+    join_all(
+        handles
+            .into_iter()
+            .map(|(sid, handle)| async move { shutdown_codex_process(&sid, handle).await }),
+    )
+    .await;
+    count
+}
+
+async fn shutdown_codex_process(session_id: &str, mut handle: CodexProcessHandle) -> Shutdown {
+    log::info!(
+        "Interrupting Codex process tree for session: {} (PID: {})",
+        session_id,
+        handle.pid
+    );
+    let outcome = interrupt::shutdown(handle.pid, handle.job_object.as_ref(), || {
+        interrupt::child_exited(&mut handle.child)
+    })
+    .await;
+    log::info!("Codex session {} stopped: {:?}", session_id, outcome);
+    outcome
+}
+
 // ============================================================================
 // Session Management
 // ============================================================================
@@ -336,6 +383,14 @@ pub async fn list_codex_sessions() -> Result<Vec<CodexSession>, String> {
         }
     }
 
+    // Skip the runs behind one-off prompts (titles, cached prompts)
+    sessions.retain(|session| !crate::engines::is_prompt_dir(&session.project_path));
+
+    let titles = crate::commands::session_titles::load_index();
+    for session in &mut sessions {
+        session.title = crate::commands::session_titles::title_for(&titles, "codex", &session.id);
+    }
+
     // Sort by creation time (newest first)
     sessions.sort_by(|a, b| b.created_at.cmp(&a.created_at));
 
@@ -455,6 +510,7 @@ pub fn parse_codex_session_file(path: &std::path::Path) -> Option<CodexSession>
         status: "completed".to_string(),
         first_message,
         last_message_timestamp: last_timestamp,
+        title: None,
     })
 }
 
@@ -586,6 +642,8 @@ pub async fn delete_codex_session(session_id: String) -> Result<String, String>
         "Successfully deleted Codex session file: {:?}",
         session_file
     );
+    crate::commands::session_titles::remove_title("codex", &session_id);
+    crate::commands::session_bookmarks::remove_session_bookmarks("codex", &session_id);
     Ok(format!("Session {} deleted", session_id))
 }
 
@@ -609,6 +667,24 @@ fn build_codex_command(
             log::info!("[Codex] Using WSL mode (distro: {:?})", wsl_config.distro);
             return build_wsl_codex_command(options, is_resume, session_id, &wsl_config);
         }
+
+        // Projects inside WSL must run the distro's Codex, not the Windows one
+        if let Some(location) = wsl_utils::detect_wsl_project(&options.project_path) {
+            log::info!(
+                "[Codex] Project is inside WSL (distro: {:?}), routing through wsl.exe",
+                location.distro
+            );
+            let project_config = wsl_utils::WslConfig {
+                enabled: true,
+                codex_path_in_wsl: wsl_utils::wsl_engine_program(
+                    "codex",
+                    location.distro.as_deref(),
+                ),
+                distro: location.distro,
+                codex_dir_unc: None,
+            };
+            return build_wsl_codex_command(options, is_resume, session_id, &project_config);
+        }
     }
 
     // Native mode: Use system-installed Codex
@@ -653,18 +729,8 @@ fn build_codex_command(
         // For new sessions: add other options
         // (--json already added above)
 
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                cmd.arg("--full-auto");
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                cmd.arg("--sandbox");
-                cmd.arg("danger-full-access");
-            }
-            CodexExecutionMode::ReadOnly => {
-                // Read-only is default
-            }
-        }
+        // Sandbox / approval flags (project safety policy, else the selected mode)
+        cmd.args(engine_safety::codex_args(&options.project_path, &options.mode));
 
         if let Some(ref model) = options.model {
             cmd.arg("--model");
@@ -703,6 +769,13 @@ fn build_codex_command(
     // Add "-" to indicate reading from stdin (common CLI convention)
     cmd.arg("-");
 
+    // Dev container mode: run Codex inside the project's container
+    if let Some(container_cmd) =
+        dev_containers::container_command(&options.project_path, "codex", &cmd)?
+    {
+        cmd = container_cmd;
+    }
+
     let prompt_for_stdin = if is_resume {
         // For resume mode, prompt is still needed but passed via stdin
         Some(options.prompt.clone())
@@ -737,16 +810,7 @@ fn build_wsl_codex_command(
             args.push(sid.to_string());
         }
     } else {
-        match options.mode {
-            CodexExecutionMode::FullAuto => {
-                args.push("--full-auto".to_string());
-            }
-            CodexExecutionMode::DangerFullAccess => {
-                args.push("--sandbox".to_string());
-                args.push("danger-full-access".to_string());
-            }
-            CodexExecutionMode::ReadOnly => {}
-        }
+        args.extend(engine_safety::codex_args(&options.project_path, &options.mode));
 
         if let Some(ref model) = options.model {
             args.push("--model".to_string());
@@ -832,9 +896,19 @@ async fn execute_codex_process(
     session_id: String,
     mut cmd: Command,
     prompt: Option<String>,
-    _project_path: String,
+    project_path: String,
     app_handle: AppHandle,
 ) -> Result<(), String> {
+    engine_safety::record_session(
+        "codex",
+        &session_id,
+        &project_path,
+        engine_safety::command_safety_flags(&cmd),
+    );
+    if let Some(prompt) = &prompt {
+        crate::commands::prompt_history::record_prompt(&project_path, "codex", prompt);
+    }
+
     // 启动流程一开始就发送 session_init，确保即使启动失败也能让前端拿到 session_id 做隔离与错误反馈
     let init_payload = serde_json::json!({
         "type": "session_init",
@@ -850,14 +924,22 @@ async fn execute_codex_process(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // Fix: Apply platform-specific no-window configuration to hide console
-    // This prevents the terminal window from flashing when starting Codex sessions
-    apply_no_window_async(&mut cmd);
+    // Fix: Run Codex as the leader of its own process group without a console window
+    // This prevents the terminal window from flashing and lets cancel interrupt the whole tree
+    interrupt::new_process_group(&mut cmd);
+    crate::process::limits::prepare_engine_command("codex", &mut cmd);
 
     // Spawn process
     let mut child = match cmd.spawn() {
         Ok(child) => child,
         Err(e) => {
+            crate::commands::engine_doctor::report_spawn_error(
+                &app_handle,
+                "codex",
+                &cmd,
+                &project_path,
+                &e,
+            );
             emit_codex_error(&app_handle, &session_id, "启动 Codex 失败", Some(&e.to_string()));
             // 这里不返回错误给前端（避免覆盖错误事件的可诊断信息），统一走事件通道
             return Ok(());
@@ -879,6 +961,7 @@ async fn execute_codex_process(
         }
     };
     log::info!("[Codex] Spawned process with PID: {}", pid);
+    crate::commands::key_pools::record_request("codex");
 
     // Windows robustness: assign the process to a Job Object so *all* descendants are cleaned up
     // even if Codex/MCP spawns detached node.exe processes.
@@ -903,9 +986,12 @@ async fn execute_codex_process(
     #[cfg(not(windows))]
     let job_object: Option<JobObject> = None;
 
+    // Apply configured CPU/memory ceilings before the engine starts its MCP servers
+    crate::process::limits::apply_to_engine("codex", pid, job_object.as_ref());
+
     // FIX: Write prompt to stdin if provided
     // This avoids command line length limits and special character issues
-    if let Some(prompt_text) = prompt {
+    if let Some(prompt_text) = &prompt {
         if let Some(mut stdin) = child.stdin.take() {
             use tokio::io::AsyncWriteExt;
 
@@ -975,9 +1061,11 @@ async fn execute_codex_process(
     // Clone handles for async tasks
     let app_handle_stdout = app_handle.clone();
     let app_handle_complete = app_handle.clone();
+    let app_handle_stderr = app_handle.clone();
     let session_id_stdout = session_id.clone(); // Clone for stdout task
     let session_id_stderr = session_id.clone(); // Clone for stderr task
     let session_id_complete = session_id.clone();
+    let project_path_stdout = project_path.clone();
 
     // 用于判断是否收到了任何 stdout 事件；仅当 stdout 完全无输出且存在 stderr 时，才触发 codex-error
     let saw_stdout = Arc::new(AtomicBool::new(false));
@@ -992,6 +1080,14 @@ async fn execute_codex_process(
 
     // Spawn task to read stdout (JSONL events)
     // FIX: Emit to both session-specific and global channels for proper multi-tab isolation
+    let mut session_events = SessionRunner::start(
+        &app_handle,
+        "codex",
+        Some(session_id.clone()),
+        None,
+        &project_path,
+        prompt.as_deref(),
+    );
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         let mut done_tx = Some(done_tx);
@@ -1000,17 +1096,33 @@ async fn execute_codex_process(
                 saw_stdout.store(true, Ordering::Relaxed);
                 // Use trace level to avoid flooding logs in debug mode
                 log::trace!("Codex output: {}", line);
-                // Emit to session-specific channel first (for multi-tab isolation)
-                if let Err(e) =
-                    app_handle_stdout.emit(&format!("codex-output:{}", session_id_stdout), &line)
-                {
-                    log::error!("Failed to emit codex-output (session-specific): {}", e);
+                if session_runner::raw_output_enabled() {
+                    // Emit to session-specific channel first (for multi-tab isolation)
+                    if let Err(e) = event_batcher::emit(
+                        &app_handle_stdout,
+                        &format!("codex-output:{}", session_id_stdout),
+                        &line,
+                    ) {
+                        log::error!("Failed to emit codex-output (session-specific): {}", e);
+                    }
+                    // Also emit to global channel for backward compatibility
+                    if let Err(e) = event_batcher::emit(&app_handle_stdout, "codex-output", &line)
+                    {
+                        log::error!("Failed to emit codex-output (global): {}", e);
+                    }
                 }
-                // Also emit to global channel for backward compatibility
-                if let Err(e) = app_handle_stdout.emit("codex-output", &line) {
-                    log::error!("Failed to emit codex-output (global): {}", e);
+                if let Some(events) = session_events.as_mut() {
+                    events.push_line(&line).await;
                 }
 
+                // Snapshot before file edits when auto-checkpoints are per edit
+                auto_checkpoint::on_engine_output(
+                    &project_path_stdout,
+                    "codex",
+                    &session_id_stdout,
+                    &line,
+                );
+
                 // Detect turn completion to trigger backend cleanup even if stdout never closes.
                 if done_tx.is_some() {
                     let is_done_event = serde_json::from_str::<serde_json::Value>(&line)
@@ -1036,6 +1148,9 @@ async fn execute_codex_process(
             }
         }
         log::info!("[Codex] Stdout closed for session: {}", session_id_stdout);
+        if let Some(events) = session_events {
+            events.finish().await;
+        }
         // Fallback: stdout closed, treat as completion if not already signaled.
         if let Some(tx) = done_tx.take() {
             let _ = tx.send(());
@@ -1049,6 +1164,7 @@ async fn execute_codex_process(
             // Log error messages for debugging
             if !line.trim().is_empty() {
                 log::warn!("Codex stderr: {}", line);
+                crate::commands::key_pools::observe_engine_error(&app_handle_stderr, "codex", &line);
                 // 仅缓存少量 stderr 以便在“无 stdout 输出”的启动失败场景下进行汇总反馈
                 let mut buf = stderr_buffer_for_stderr.lock().await;
                 if buf.len() < 20 {
@@ -1095,6 +1211,7 @@ async fn execute_codex_process(
             "[Codex] Sending completion event for session: {}",
             session_id_complete
         );
+        event_batcher::flush_all(&app_handle_complete);
         if let Err(e) =
             app_handle_complete.emit(&format!("codex-complete:{}", session_id_complete), true)
         {
@@ -32,6 +32,20 @@ pub struct CodexSessionUsage {
     pub created_at: u64,
     pub updated_at: u64,
     pub first_message: Option<String>,
+    /// Per-turn usage, so records are dated by when the tokens were spent
+    #[serde(skip)]
+    turns: Vec<TurnUsage>,
+}
+
+/// Tokens reported by one usage event of a session
+#[derive(Debug, Clone)]
+struct TurnUsage {
+    timestamp: DateTime<chrono::Utc>,
+    /// Model selected when the event was written
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
+    cached_input_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -276,11 +290,14 @@ fn parse_session_for_usage(path: &PathBuf) -> Option<CodexSessionUsage> {
     let mut last_total_input_tokens: Option<u64> = None;
     let mut last_total_output_tokens: Option<u64> = None;
     let mut last_total_cached_tokens: Option<u64> = None;
+    let mut turns: Vec<TurnUsage> = Vec::new();
 
     // Parse all lines to extract usage data
     for line_result in lines {
         if let Ok(line) = line_result {
             if let Ok(event) = serde_json::from_str::<serde_json::Value>(&line) {
+                let totals_before = (total_input_tokens, total_output_tokens, total_cached_tokens);
+
                 // Update last timestamp
                 if let Some(ts) = event["timestamp"].as_str() {
                     last_timestamp = Some(ts.to_string());
@@ -408,6 +425,24 @@ fn parse_session_for_usage(path: &PathBuf) -> Option<CodexSessionUsage> {
                     }
                 }
 
+                // Record what this event added, dated by the event itself
+                if (total_input_tokens, total_output_tokens, total_cached_tokens) != totals_before {
+                    let timestamp = event["timestamp"]
+                        .as_str()
+                        .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+                        .map(|dt| dt.with_timezone(&chrono::Utc))
+                        .or_else(|| chrono::DateTime::from_timestamp(created_at as i64, 0));
+                    if let Some(timestamp) = timestamp {
+                        turns.push(TurnUsage {
+                            timestamp,
+                            model: model.clone(),
+                            input_tokens: total_input_tokens - totals_before.0,
+                            output_tokens: total_output_tokens - totals_before.1,
+                            cached_input_tokens: total_cached_tokens - totals_before.2,
+                        });
+                    }
+                }
+
                 // Find first user message
                 if first_message.is_none() && event_type == "response_item" {
                     if let Some(payload_obj) = event["payload"].as_object() {
@@ -455,6 +490,7 @@ fn parse_session_for_usage(path: &PathBuf) -> Option<CodexSessionUsage> {
         created_at,
         updated_at,
         first_message,
+        turns,
     })
 }
 
@@ -503,6 +539,47 @@ fn collect_all_sessions() -> Vec<CodexSessionUsage> {
     sessions
 }
 
+/// A session's usage as engine-neutral records, one per usage event
+fn session_records(session: CodexSessionUsage) -> Vec<crate::usage::UsageRecord> {
+    session
+        .turns
+        .into_iter()
+        .map(|turn| {
+            // Events written before any model was announced count for the session's model
+            let model = if turn.model == "unknown" {
+                session.model.clone()
+            } else {
+                turn.model
+            };
+            crate::usage::UsageRecord {
+                engine: "codex",
+                session_id: session.session_id.clone(),
+                project_path: session.project_path.clone(),
+                cost: calculate_cost(
+                    &model,
+                    turn.input_tokens,
+                    turn.output_tokens,
+                    turn.cached_input_tokens,
+                ),
+                model,
+                timestamp: turn.timestamp,
+                input_tokens: turn.input_tokens,
+                output_tokens: turn.output_tokens,
+                cache_creation_tokens: 0,
+                cache_read_tokens: turn.cached_input_tokens,
+            }
+        })
+        .collect()
+}
+
+/// Codex usage as engine-neutral records, one per usage event
+pub(crate) fn usage_records() -> Vec<crate::usage::UsageRecord> {
+    collect_all_sessions()
+        .into_iter()
+        .flat_map(session_records)
+        .collect()
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -652,3 +729,33 @@ pub async fn get_codex_usage_stats(
         sessions: filtered_sessions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_records_are_dated_per_event() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("rollout.jsonl");
+        let lines = [
+            r#"{"type":"session_meta","payload":{"id":"s1","timestamp":"2025-11-01T23:00:00Z","cwd":"/p"}}"#,
+            r#"{"timestamp":"2025-11-01T23:00:01Z","type":"turn_context","payload":{"model":"gpt-5"}}"#,
+            r#"{"timestamp":"2025-11-01T23:10:00Z","type":"event_msg","payload":{"type":"token_count","info":{"last_token_usage":{"input_tokens":100,"output_tokens":10}}}}"#,
+            r#"{"timestamp":"2025-11-03T09:00:00Z","type":"event_msg","payload":{"type":"token_count","info":{"last_token_usage":{"input_tokens":200,"output_tokens":20,"cached_input_tokens":50}}}}"#,
+        ];
+        std::fs::write(&path, lines.join("\n")).unwrap();
+
+        let session = parse_session_for_usage(&path).unwrap();
+        assert_eq!((session.input_tokens, session.output_tokens), (300, 30));
+        let records = session_records(session);
+        let days: Vec<String> = records
+            .iter()
+            .map(|r| r.timestamp.format("%Y-%m-%d").to_string())
+            .collect();
+        assert_eq!(days, vec!["2025-11-01", "2025-11-03"]);
+        assert_eq!(records[1].input_tokens, 200);
+        assert_eq!(records[1].cache_read_tokens, 50);
+        assert_eq!(records[1].model, "gpt-5");
+    }
+}
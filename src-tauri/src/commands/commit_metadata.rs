@@ -0,0 +1,218 @@
+//! Engine identity of commits
+//!
+//! Commits made for an engine carry git trailers naming the engine and the
+//! session they belong to:
+//!
+//! ```text
+//! [Codex] fix the login form prompt #3
+//!
+//! Anycode-Engine: codex
+//! Anycode-Session: 0199a1b2-...
+//! ```
+//!
+//! Trailers are checked first. Commits without them (made before trailers
+//! existed, by reverts, or by other tools) are recognized by their subject
+//! prefix, using a registry kept in `~/.anycode/commit-tags.json`, so new
+//! engines and custom naming schemes can be added without code changes.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+pub const ENGINE_TRAILER: &str = "Anycode-Engine";
+pub const SESSION_TRAILER: &str = "Anycode-Session";
+
+/// Subject prefixes that identify an engine's commits
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnginePrefixes {
+    pub engine: String,
+    /// Matched case-sensitively at the start of the subject
+    pub prefixes: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitTagSettings {
+    /// Checked in order; the first engine with a matching prefix wins
+    #[serde(default = "default_engines")]
+    pub engines: Vec<EnginePrefixes>,
+}
+
+impl Default for CommitTagSettings {
+    fn default() -> Self {
+        Self {
+            engines: default_engines(),
+        }
+    }
+}
+
+/// Engine and session recorded for a commit
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitMetadata {
+    pub engine: Option<String>,
+    pub session_id: Option<String>,
+}
+
+fn default_engines() -> Vec<EnginePrefixes> {
+    // Workbench commits come first: "[Claude Workbench]" also starts with "[Claude"
+    [
+        (
+            "workbench",
+            &["[Claude Workbench]", "[Revert]", "[Restore]"][..],
+        ),
+        ("claude", &["[Claude"][..]),
+        ("codex", &["[Codex"][..]),
+        ("gemini", &["[Gemini"][..]),
+    ]
+    .iter()
+    .map(|(engine, prefixes)| EnginePrefixes {
+        engine: engine.to_string(),
+        prefixes: prefixes.iter().map(|p| p.to_string()).collect(),
+    })
+    .collect()
+}
+
+static SETTINGS: Lazy<RwLock<CommitTagSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("commit-tags.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+fn settings() -> CommitTagSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Append the engine (and session) trailers to a commit message
+pub fn tag_message(message: &str, engine: &str, session_id: Option<&str>) -> String {
+    let mut tagged = format!("{}\n\n{}: {}", message.trim_end(), ENGINE_TRAILER, engine);
+    if let Some(session_id) = session_id.filter(|id| !id.is_empty()) {
+        tagged.push_str(&format!("\n{}: {}", SESSION_TRAILER, session_id));
+    }
+    tagged
+}
+
+/// Value of a trailer; the subject line is never a trailer
+fn trailer<'a>(message: &'a str, key: &str) -> Option<&'a str> {
+    message.lines().skip(1).rev().find_map(|line| {
+        let (name, value) = line.split_once(':')?;
+        let value = value.trim();
+        (name.trim().eq_ignore_ascii_case(key) && !value.is_empty()).then_some(value)
+    })
+}
+
+fn engine_for_subject(engines: &[EnginePrefixes], subject: &str) -> Option<String> {
+    let subject = subject.trim_start();
+    engines
+        .iter()
+        .find(|e| {
+            e.prefixes
+                .iter()
+                .any(|prefix| !prefix.is_empty() && subject.starts_with(prefix.as_str()))
+        })
+        .map(|e| e.engine.clone())
+}
+
+fn parse_with(engines: &[EnginePrefixes], message: &str) -> CommitMetadata {
+    let subject = message.lines().next().unwrap_or("");
+    CommitMetadata {
+        engine: trailer(message, ENGINE_TRAILER)
+            .map(str::to_string)
+            .or_else(|| engine_for_subject(engines, subject)),
+        session_id: trailer(message, SESSION_TRAILER).map(str::to_string),
+    }
+}
+
+/// Engine and session of a commit, from its trailers or else its subject prefix
+pub fn parse(message: &str) -> CommitMetadata {
+    parse_with(&settings().engines, message)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_commit_tag_settings() -> Result<CommitTagSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_commit_tag_settings(settings: CommitTagSettings) -> Result<(), String> {
+    if settings
+        .engines
+        .iter()
+        .any(|e| e.engine.trim().is_empty() || e.prefixes.iter().all(|p| p.is_empty()))
+    {
+        return Err("Every engine needs a name and at least one prefix".to_string());
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commit_metadata() {
+        let engines = default_engines();
+        let tagged = tag_message("[Codex] fix it prompt #3\n", "codex", Some("s-1"));
+        assert_eq!(
+            tagged,
+            "[Codex] fix it prompt #3\n\nAnycode-Engine: codex\nAnycode-Session: s-1"
+        );
+        assert_eq!(
+            parse_with(&engines, &tagged),
+            CommitMetadata {
+                engine: Some("codex".to_string()),
+                session_id: Some("s-1".to_string()),
+            }
+        );
+
+        // The trailer wins over a reworded subject
+        let reworded = tagged.replace("[Codex]", "[Claude Code]");
+        assert_eq!(
+            parse_with(&engines, &reworded).engine.as_deref(),
+            Some("codex")
+        );
+
+        let engine = |message: &str| parse_with(&engines, message).engine;
+        assert_eq!(
+            engine("[Claude Code] a prompt #1").as_deref(),
+            Some("claude")
+        );
+        assert_eq!(engine("[Gemini Revert] 撤回").as_deref(), Some("gemini"));
+        assert_eq!(
+            engine("[Claude Workbench] Initial commit").as_deref(),
+            Some("workbench")
+        );
+        assert_eq!(engine("Anycode-Engine: codex"), None);
+        assert_eq!(engine("fix typo"), None);
+
+        let custom = vec![EnginePrefixes {
+            engine: "aider".to_string(),
+            prefixes: vec!["aider:".to_string()],
+        }];
+        assert_eq!(
+            parse_with(&custom, "aider: refactor").engine.as_deref(),
+            Some("aider")
+        );
+    }
+}
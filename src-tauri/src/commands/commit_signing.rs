@@ -0,0 +1,185 @@
+//! Commit signing
+//!
+//! libgit2 cannot sign commits, so repositories that require signatures
+//! (`commit.gpgsign = true`, or signing enabled here) are handled by the git
+//! CLI, which signs with the user's gpg, ssh or x509 setup like any other
+//! commit. The key and format can be chosen in `~/.anycode/commit-signing.json`;
+//! they are passed per command and never written to the repository config.
+//!
+//! Signing failures (locked key, missing agent, unknown key) are reported with
+//! a distinct prefix so the UI can tell them apart from ordinary commit errors.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Prefix of errors caused by signing rather than by the commit itself
+pub const SIGNING_ERROR_PREFIX: &str = "Commit signing failed";
+
+/// Stderr fragments git, gpg and ssh-keygen print when signing fails
+const SIGNING_FAILURES: &[&str] = &[
+    "gpg failed to sign",
+    "failed to sign the data",
+    "signing failed",
+    "no secret key",
+    "secret key not available",
+    "couldn't load public key",
+    "ssh-keygen",
+    "gpg.ssh.defaultkeycommand",
+    "inappropriate ioctl for device",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SigningFormat {
+    Openpgp,
+    Ssh,
+    X509,
+}
+
+impl SigningFormat {
+    fn as_git(self) -> &'static str {
+        match self {
+            SigningFormat::Openpgp => "openpgp",
+            SigningFormat::Ssh => "ssh",
+            SigningFormat::X509 => "x509",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitSigningSettings {
+    /// Sign even where the repository does not set `commit.gpgsign`
+    #[serde(default)]
+    pub enabled: bool,
+    /// `user.signingkey` override: a gpg key id or an ssh key path
+    #[serde(default)]
+    pub key: Option<String>,
+    /// `gpg.format` override
+    #[serde(default)]
+    pub format: Option<SigningFormat>,
+}
+
+static SETTINGS: Lazy<RwLock<CommitSigningSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("commit-signing.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+pub fn settings() -> CommitSigningSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Whether commits in a repository with this config must be signed
+pub fn required(config: &git2::Config) -> bool {
+    settings().enabled || config.get_bool("commit.gpgsign").unwrap_or(false)
+}
+
+/// `-c` overrides for `git commit` applying the configured key and format
+pub fn cli_overrides() -> Vec<String> {
+    overrides_for(&settings())
+}
+
+fn overrides_for(settings: &CommitSigningSettings) -> Vec<String> {
+    let mut overrides = Vec::new();
+    if settings.enabled {
+        overrides.push("commit.gpgsign=true".to_string());
+    }
+    if let Some(key) = settings.key.as_deref().filter(|key| !key.trim().is_empty()) {
+        overrides.push(format!("user.signingkey={}", key.trim()));
+    }
+    if let Some(format) = settings.format {
+        overrides.push(format!("gpg.format={}", format.as_git()));
+    }
+    overrides
+        .into_iter()
+        .flat_map(|value| ["-c".to_string(), value])
+        .collect()
+}
+
+/// Mark a failed commit's error as a signing failure when its output says so
+pub fn classify_error(error: String) -> String {
+    let lower = error.to_lowercase();
+    if SIGNING_FAILURES
+        .iter()
+        .any(|failure| lower.contains(failure))
+    {
+        format!("{}: {}", SIGNING_ERROR_PREFIX, error)
+    } else {
+        error
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_commit_signing_settings() -> Result<CommitSigningSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_commit_signing_settings(settings: CommitSigningSettings) -> Result<(), String> {
+    if settings
+        .key
+        .as_deref()
+        .is_some_and(|key| key.contains(['\n', '\r']))
+    {
+        return Err("Invalid signing key".to_string());
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signing_overrides_and_errors() {
+        assert!(overrides_for(&CommitSigningSettings::default()).is_empty());
+        let settings = CommitSigningSettings {
+            enabled: true,
+            key: Some("~/.ssh/id_ed25519.pub".to_string()),
+            format: Some(SigningFormat::Ssh),
+        };
+        assert_eq!(
+            overrides_for(&settings),
+            vec![
+                "-c",
+                "commit.gpgsign=true",
+                "-c",
+                "user.signingkey=~/.ssh/id_ed25519.pub",
+                "-c",
+                "gpg.format=ssh",
+            ]
+        );
+
+        let error = classify_error(
+            "Git commit failed: error: gpg failed to sign the data\nfatal: failed to write commit object"
+                .to_string(),
+        );
+        assert!(error.starts_with(SIGNING_ERROR_PREFIX));
+        assert_eq!(
+            classify_error("Git commit failed: nothing added".to_string()),
+            "Git commit failed: nothing added"
+        );
+    }
+}
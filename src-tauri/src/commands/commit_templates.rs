@@ -0,0 +1,464 @@
+//! Commit message templates
+//!
+//! Prompt commits are titled `[Engine] <prompt> prompt #<n>` unless a template
+//! is configured. Templates use placeholders, e.g. for conventional commits:
+//!
+//! ```text
+//! {type}({scope}): {prompt}
+//! ```
+//!
+//! - `{prefix}`: the engine's subject prefix, e.g. `[Codex]`
+//! - `{engine}`: `claude`, `codex` or `gemini`
+//! - `{prompt}`: the prompt on one line, shortened
+//! - `{index}`: the prompt number
+//! - `{type}`: conventional commit type guessed from the prompt and changed files
+//! - `{scope}`: the innermost directory shared by all changed files;
+//!   `({scope})` is dropped when there is none
+//! - `{files}`: number of changed files
+//!
+//! The template is set globally in `~/.anycode/commit-template.json` and can
+//! be overridden per project in `<project>/.anycode/commit-template.json`.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use crate::commands::git_backend::backend_for;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+pub const CONVENTIONAL_TEMPLATE: &str = "{type}({scope}): {prompt}";
+
+const PLACEHOLDERS: &[&str] = &[
+    "prefix", "engine", "prompt", "index", "type", "scope", "files",
+];
+
+/// Characters of the prompt kept in the subject
+const PROMPT_CHARS: usize = 80;
+
+/// Directory names too generic to be a scope
+const GENERIC_DIRS: &[&str] = &["src", "lib", "app", "packages", "crates", "apps"];
+
+/// Prompt keywords mapped to conventional commit types, checked in order
+const TYPE_KEYWORDS: &[(&str, &[&str])] = &[
+    ("fix", &["fix", "bug", "error", "crash", "broken", "修复"]),
+    (
+        "refactor",
+        &["refactor", "rename", "clean", "simplify", "重构"],
+    ),
+    ("docs", &["doc", "docs", "readme", "documentation", "文档"]),
+    ("test", &["test", "tests", "spec", "测试"]),
+    (
+        "perf",
+        &["performance", "perf", "optimize", "faster", "优化"],
+    ),
+    (
+        "feat",
+        &["add", "implement", "create", "support", "feature", "新增"],
+    ),
+];
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitTemplateSettings {
+    /// `None` keeps the `[Engine] <prompt> prompt #<n>` format
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+/// Values a template is rendered with
+#[derive(Debug, Clone)]
+struct MessageContext<'a> {
+    engine: &'a str,
+    prompt: &'a str,
+    index: usize,
+    paths: &'a [String],
+}
+
+static SETTINGS: Lazy<RwLock<CommitTemplateSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("commit-template.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+fn project_config_path(project_path: &str) -> PathBuf {
+    ConfigPathBuilder::new(Path::new(project_path).join(".anycode")).build("commit-template.json")
+}
+
+fn settings() -> CommitTemplateSettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+fn project_template(project_path: &str) -> Option<String> {
+    load_json_config::<CommitTemplateSettings>(project_config_path(project_path))
+        .ok()
+        .and_then(|settings| settings.template)
+}
+
+/// The project's override, else the global template
+fn effective_template(project_path: &str) -> Option<String> {
+    project_template(project_path).or_else(|| settings().template)
+}
+
+fn engine_prefix(engine: &str) -> &'static str {
+    crate::engines::get(engine)
+        .unwrap_or(&crate::engines::ClaudeEngine)
+        .commit_tag()
+}
+
+fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("Commit template is empty".to_string());
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(end) = rest[start..].find('}') else {
+            return Err("Unclosed placeholder in commit template".to_string());
+        };
+        let name = &rest[start + 1..start + end];
+        if !PLACEHOLDERS.contains(&name) {
+            return Err(format!("Unknown placeholder {{{}}}", name));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+/// The prompt on one line, shortened
+fn prompt_summary(prompt: &str) -> String {
+    let sanitized = prompt.replace(['\n', '\r'], " ");
+    sanitized.trim().chars().take(PROMPT_CHARS).collect()
+}
+
+fn commit_type(prompt: &str, paths: &[String]) -> &'static str {
+    let is_doc = |p: &String| p.ends_with(".md") || p.starts_with("docs/");
+    let is_test = |p: &String| p.contains("test") || p.contains("spec");
+    if !paths.is_empty() && paths.iter().all(is_doc) {
+        return "docs";
+    }
+    if !paths.is_empty() && paths.iter().all(is_test) {
+        return "test";
+    }
+
+    let prompt = prompt.to_lowercase();
+    let words: Vec<&str> = prompt
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .collect();
+    TYPE_KEYWORDS
+        .iter()
+        .find(|(_, keywords)| {
+            keywords.iter().any(|keyword| {
+                if keyword.is_ascii() {
+                    words.contains(keyword)
+                } else {
+                    prompt.contains(keyword)
+                }
+            })
+        })
+        .map_or("chore", |(kind, _)| *kind)
+}
+
+/// Innermost non-generic directory shared by all paths
+fn commit_scope(paths: &[String]) -> String {
+    let Some((first, others)) = paths.split_first() else {
+        return String::new();
+    };
+    let dirs = |path: &str| -> Vec<String> {
+        let mut parts: Vec<String> = path.split('/').map(str::to_string).collect();
+        parts.pop();
+        parts
+    };
+    let mut common = dirs(first);
+    for path in others {
+        let other = dirs(path);
+        let shared = common
+            .iter()
+            .zip(&other)
+            .take_while(|(a, b)| a == b)
+            .count();
+        common.truncate(shared);
+    }
+    common
+        .into_iter()
+        .rev()
+        .find(|dir| !GENERIC_DIRS.contains(&dir.as_str()))
+        .unwrap_or_default()
+}
+
+fn render(template: &str, context: &MessageContext) -> String {
+    let scope = commit_scope(context.paths);
+    let template = if scope.is_empty() {
+        template.replace("({scope})", "")
+    } else {
+        template.to_string()
+    };
+    let value = |name: &str| -> String {
+        match name {
+            "prefix" => engine_prefix(context.engine).to_string(),
+            "engine" => context.engine.to_string(),
+            "prompt" => {
+                let prompt = prompt_summary(context.prompt);
+                if prompt.is_empty() {
+                    format!("after prompt #{}", context.index)
+                } else {
+                    prompt
+                }
+            }
+            "index" => context.index.to_string(),
+            "type" => commit_type(context.prompt, context.paths).to_string(),
+            "scope" => scope.clone(),
+            "files" => context.paths.len().to_string(),
+            other => format!("{{{}}}", other),
+        }
+    };
+
+    // Single pass, so placeholders inside the prompt are left alone
+    let mut message = String::new();
+    let mut rest = template.as_str();
+    while let Some(start) = rest.find('{') {
+        message.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => {
+                message.push_str(&value(&rest[start + 1..start + end]));
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+    message.push_str(rest);
+    message
+        .lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// The untemplated `[Engine] <prompt> prompt #<n>` format
+fn default_message(engine: &str, prompt: &str, index: usize) -> String {
+    let prefix = engine_prefix(engine);
+    let summary = prompt_summary(prompt);
+    if summary.is_empty() {
+        return format!("{prefix} After prompt #{index}");
+    }
+    format!("{prefix} {summary} prompt #{index}")
+}
+
+/// Uncommitted changes, limited to `pathspec` when the commit is scoped to a package
+fn pending_paths(project_path: &str, pathspec: Option<&str>) -> Vec<String> {
+    let paths = backend_for(project_path)
+        .changed_paths(project_path)
+        .unwrap_or_else(|e| {
+            log::debug!("Could not list changed files: {}", e);
+            Vec::new()
+        });
+    match pathspec {
+        Some(spec) => {
+            let spec = spec.trim_end_matches('/');
+            paths
+                .into_iter()
+                .filter(|p| p == spec || p.starts_with(&format!("{}/", spec)))
+                .collect()
+        }
+        None => paths,
+    }
+}
+
+fn message_with(
+    template: Option<&str>,
+    project_path: &str,
+    engine: &str,
+    prompt_text: Option<&str>,
+    prompt_index: usize,
+    pathspec: Option<&str>,
+) -> String {
+    let prompt = prompt_text.unwrap_or("");
+    match template {
+        Some(template) => {
+            let paths = pending_paths(project_path, pathspec);
+            render(
+                template,
+                &MessageContext {
+                    engine,
+                    prompt,
+                    index: prompt_index,
+                    paths: &paths,
+                },
+            )
+        }
+        None => default_message(engine, prompt, prompt_index),
+    }
+}
+
+/// Subject for the commit made after an engine prompt, from the uncommitted changes
+pub fn prompt_commit_message(
+    project_path: &str,
+    engine: &str,
+    prompt_text: Option<&str>,
+    prompt_index: usize,
+    pathspec: Option<&str>,
+) -> String {
+    message_with(
+        effective_template(project_path).as_deref(),
+        project_path,
+        engine,
+        prompt_text,
+        prompt_index,
+        pathspec,
+    )
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_commit_template_settings() -> Result<CommitTemplateSettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_commit_template_settings(
+    settings: CommitTemplateSettings,
+) -> Result<(), String> {
+    if let Some(template) = &settings.template {
+        validate_template(template)?;
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_project_commit_template(project_path: String) -> Result<Option<String>, String> {
+    Ok(project_template(&project_path))
+}
+
+/// Set the project's template override; `None` removes it
+#[tauri::command]
+pub async fn set_project_commit_template(
+    project_path: String,
+    template: Option<String>,
+) -> Result<(), String> {
+    let path = project_config_path(&project_path);
+    match template {
+        Some(template) => {
+            validate_template(&template)?;
+            save_json_config(
+                &CommitTemplateSettings {
+                    template: Some(template),
+                },
+                path,
+            )
+        }
+        None if path.exists() => std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to remove {}: {}", path.display(), e)),
+        None => Ok(()),
+    }
+}
+
+/// Render a template (default: the project's effective one) against the current changes
+#[tauri::command]
+pub async fn preview_commit_message(
+    project_path: String,
+    template: Option<String>,
+    engine: Option<String>,
+    prompt_text: Option<String>,
+    prompt_index: Option<usize>,
+) -> Result<String, String> {
+    if let Some(template) = &template {
+        validate_template(template)?;
+    }
+    tokio::task::spawn_blocking(move || {
+        let template = template.or_else(|| effective_template(&project_path));
+        message_with(
+            template.as_deref(),
+            &project_path,
+            engine.as_deref().unwrap_or("claude"),
+            prompt_text.as_deref(),
+            prompt_index.unwrap_or(1),
+            None,
+        )
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_templates() {
+        let paths = vec![
+            "src/auth/login.rs".to_string(),
+            "src/auth/session.rs".to_string(),
+        ];
+        let context = MessageContext {
+            engine: "codex",
+            prompt: "Fix the login\nredirect",
+            index: 3,
+            paths: &paths,
+        };
+        assert_eq!(
+            render(CONVENTIONAL_TEMPLATE, &context),
+            "fix(auth): Fix the login redirect"
+        );
+        assert_eq!(
+            render("{prefix} {prompt} ({files} files) #{index}", &context),
+            "[Codex] Fix the login redirect (2 files) #3"
+        );
+        let context = MessageContext {
+            prompt: "call {type}()",
+            ..context
+        };
+        assert_eq!(
+            render(CONVENTIONAL_TEMPLATE, &context),
+            "chore(auth): call {type}()"
+        );
+
+        let root = vec!["README.md".to_string(), "src/main.rs".to_string()];
+        let context = MessageContext {
+            engine: "claude",
+            prompt: "",
+            index: 2,
+            paths: &root,
+        };
+        assert_eq!(
+            render(CONVENTIONAL_TEMPLATE, &context),
+            "chore: after prompt #2"
+        );
+        assert_eq!(
+            default_message("claude", "", 2),
+            "[Claude Code] After prompt #2"
+        );
+
+        assert_eq!(commit_type("add dark mode", &[]), "feat");
+        assert_eq!(
+            commit_type("anything", &["docs/guide.md".to_string()]),
+            "docs"
+        );
+        assert_eq!(commit_scope(&["packages/web/src/a.ts".to_string()]), "web");
+
+        assert!(validate_template(CONVENTIONAL_TEMPLATE).is_ok());
+        assert!(validate_template("{subject}").is_err());
+        assert!(validate_template("{type").is_err());
+        assert!(validate_template(" ").is_err());
+    }
+}
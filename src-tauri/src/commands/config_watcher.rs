@@ -0,0 +1,348 @@
+//! Hot-reload of workbench configuration
+//!
+//! Watches the workbench's configuration files (an allow-list of files in
+//! `~/.anycode` plus the workbench-owned files in the active Claude config
+//! directory) and reloads the state cached from them when they are edited by
+//! hand, so power users don't have to restart the app. Runtime state stored
+//! next to them (drafts, history, caches, session data) is not watched.
+//!
+//! Only files with in-memory state need a reload. The other configuration in
+//! `~/.anycode` (`forges.json`, `resource-limits.json`, `remote-projects.json`,
+//! `mcp-bridge.json`, ...) is read from disk on every use, so hand edits apply
+//! without the watcher. The one exception is the Gemini WSL detection, which
+//! probes WSL once and is kept until restart.
+//!
+//! Events:
+//! - `config-reloaded` - emitted with a [`ConfigReloadEvent`] describing what changed
+
+use notify::{RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{AppHandle, Emitter};
+use tokio::sync::mpsc;
+
+use super::{
+    auto_checkpoint, branch_protection, commit_metadata, commit_signing, commit_templates,
+    event_batcher, git_identity, profiling, tickets,
+};
+use crate::session_runner;
+
+/// Quiet period after a change before reloading, so a save counts once
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Files in `~/.anycode` cached in memory, with the function re-reading each
+const SETTINGS_FILES: &[(&str, fn())] = &[
+    ("auto-checkpoint.json", auto_checkpoint::reload_settings),
+    ("branch-protection.json", branch_protection::reload_settings),
+    ("commit-signing.json", commit_signing::reload_settings),
+    ("commit-tags.json", commit_metadata::reload_settings),
+    ("commit-template.json", commit_templates::reload_settings),
+    ("event-batching.json", event_batcher::reload_config),
+    ("git-identity.json", git_identity::reload_settings),
+    ("profiling.json", profiling::reload_config),
+    ("session-stream.json", session_runner::reload_settings),
+    ("tickets.json", tickets::reload_settings),
+];
+
+/// Other files in `~/.anycode` that in-memory state is built from
+///
+/// `claude-profiles.json` also holds the session-to-profile mappings.
+const ANYCODE_FILES: &[(&str, &str)] = &[
+    ("claude-profiles.json", "profiles"),
+    ("mcp-registry.json", "mcp"),
+    ("redaction.json", "redaction"),
+];
+
+/// Workbench-owned files in the Claude config directory
+const CLAUDE_DIR_FILES: &[(&str, &str)] = &[
+    // Provider env, whose secret values the redactor masks
+    ("settings.json", "profiles"),
+    ("translation_config.json", "translation"),
+];
+
+/// Kind of change detected for a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfigChangeKind {
+    Created,
+    Modified,
+    Removed,
+}
+
+/// A single changed configuration file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigChange {
+    pub path: String,
+    /// Logical area the file belongs to ("settings", "profiles", "mcp", "redaction", "translation")
+    pub category: String,
+    pub kind: ConfigChangeKind,
+}
+
+/// Payload of the `config-reloaded` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigReloadEvent {
+    pub changes: Vec<ConfigChange>,
+    /// Categories whose in-memory state was reloaded by the backend
+    pub reloaded: Vec<String>,
+}
+
+type FileStamp = (SystemTime, u64);
+
+/// The watched files and their categories
+fn watched_files(anycode_dir: &Path, claude_dir: Option<&Path>) -> HashMap<PathBuf, &'static str> {
+    let mut files: HashMap<PathBuf, &'static str> = SETTINGS_FILES
+        .iter()
+        .map(|(name, _)| (anycode_dir.join(name), "settings"))
+        .chain(
+            ANYCODE_FILES
+                .iter()
+                .map(|(name, category)| (anycode_dir.join(name), *category)),
+        )
+        .collect();
+    if let Some(claude_dir) = claude_dir {
+        for (name, category) in CLAUDE_DIR_FILES {
+            files.insert(claude_dir.join(name), *category);
+        }
+    }
+    files
+}
+
+/// Modification stamps of the watched files that exist
+fn snapshot_files(files: &HashMap<PathBuf, &'static str>) -> HashMap<PathBuf, FileStamp> {
+    files
+        .keys()
+        .filter_map(|path| Some((path.clone(), file_stamp(path)?)))
+        .collect()
+}
+
+fn file_stamp(path: &Path) -> Option<FileStamp> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// Diff two snapshots
+fn diff_snapshots(
+    before: &HashMap<PathBuf, FileStamp>,
+    after: &HashMap<PathBuf, FileStamp>,
+    files: &HashMap<PathBuf, &'static str>,
+) -> Vec<ConfigChange> {
+    let category = |path: &PathBuf| files.get(path).copied().unwrap_or("settings").to_string();
+    let mut changes = Vec::new();
+
+    for (path, stamp) in after {
+        let kind = match before.get(path) {
+            None => ConfigChangeKind::Created,
+            Some(previous) if previous != stamp => ConfigChangeKind::Modified,
+            Some(_) => continue,
+        };
+        changes.push(ConfigChange {
+            path: path.to_string_lossy().to_string(),
+            category: category(path),
+            kind,
+        });
+    }
+
+    for path in before.keys().filter(|p| !after.contains_key(*p)) {
+        changes.push(ConfigChange {
+            path: path.to_string_lossy().to_string(),
+            category: category(path),
+            kind: ConfigChangeKind::Removed,
+        });
+    }
+
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    changes
+}
+
+/// The function re-reading a settings file, by file name
+fn settings_reloader(path: &Path) -> Option<fn()> {
+    let name = path.file_name()?.to_str()?;
+    SETTINGS_FILES
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|(_, reload)| *reload)
+}
+
+/// Reload backend state that caches configuration in memory
+async fn reload_categories(changes: &[ConfigChange]) -> Vec<String> {
+    let mut reloaded = Vec::new();
+    let changed = |category: &str| changes.iter().any(|c| c.category == category);
+
+    let settings: Vec<fn()> = changes
+        .iter()
+        .filter(|c| c.category == "settings")
+        .filter_map(|c| settings_reloader(Path::new(&c.path)))
+        .collect();
+    if !settings.is_empty() {
+        tokio::task::spawn_blocking(move || settings.iter().for_each(|reload| reload()))
+            .await
+            .ok();
+        reloaded.push("settings".to_string());
+    }
+
+    // The active profile decides which Claude directory the translation config is read from
+    if changed("translation") || changed("profiles") {
+        super::translator::init_translation_service_with_saved_config().await;
+        reloaded.push("translation".to_string());
+    }
+
+    // Known secret values come from provider env and MCP server env blocks
+    if changed("redaction") || changed("profiles") || changed("mcp") {
+        tokio::task::spawn_blocking(crate::utils::redaction::reload)
+            .await
+            .ok();
+        reloaded.push("redaction".to_string());
+    }
+
+    reloaded
+}
+
+/// Point the watcher at `dir`, logging instead of failing when it can't be watched
+fn watch_dir(watcher: &mut RecommendedWatcher, dir: &Path) {
+    if let Err(e) = watcher.watch(dir, RecursiveMode::NonRecursive) {
+        log::warn!("[ConfigWatcher] Failed to watch {}: {}", dir.display(), e);
+    }
+}
+
+/// Start the configuration watcher loop
+pub async fn start_config_watcher(app: AppHandle) {
+    let Some(anycode_dir) = dirs::home_dir().map(|home| home.join(".anycode")) else {
+        log::warn!("[ConfigWatcher] Could not find home directory");
+        return;
+    };
+    if let Err(e) = std::fs::create_dir_all(&anycode_dir) {
+        log::warn!(
+            "[ConfigWatcher] Failed to create {}: {}",
+            anycode_dir.display(),
+            e
+        );
+        return;
+    }
+    let mut claude_dir = super::claude::get_claude_dir().ok();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut watcher =
+        match notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            let _ = tx.send(event);
+        }) {
+            Ok(watcher) => watcher,
+            Err(e) => {
+                log::warn!("[ConfigWatcher] Failed to create file watcher: {}", e);
+                return;
+            }
+        };
+    // Directories rather than files, so editors that save by renaming are seen
+    watch_dir(&mut watcher, &anycode_dir);
+    if let Some(dir) = &claude_dir {
+        watch_dir(&mut watcher, dir);
+    }
+
+    let mut files = watched_files(&anycode_dir, claude_dir.as_deref());
+    let mut previous = snapshot_files(&files);
+    log::info!(
+        "[ConfigWatcher] Watching {} configuration files",
+        files.len()
+    );
+
+    // Ends when the watcher is dropped and the channel closes
+    while rx.recv().await.is_some() {
+        while let Ok(Some(_)) = tokio::time::timeout(DEBOUNCE, rx.recv()).await {}
+
+        let current = snapshot_files(&files);
+        let changes = diff_snapshots(&previous, &current, &files);
+        previous = current;
+        if changes.is_empty() {
+            continue;
+        }
+
+        for change in &changes {
+            log::info!(
+                "[ConfigWatcher] {:?} {} ({})",
+                change.kind,
+                change.path,
+                change.category
+            );
+        }
+
+        let mut reloaded = reload_categories(&changes).await;
+
+        // Switching profiles can move the Claude config directory
+        if changes.iter().any(|c| c.category == "profiles") {
+            let current_dir = super::claude::get_claude_dir().ok();
+            if current_dir != claude_dir {
+                if let Some(dir) = &claude_dir {
+                    let _ = watcher.unwatch(dir);
+                }
+                if let Some(dir) = &current_dir {
+                    watch_dir(&mut watcher, dir);
+                }
+                claude_dir = current_dir;
+                files = watched_files(&anycode_dir, claude_dir.as_deref());
+                previous = snapshot_files(&files);
+            }
+            reloaded.push("profiles".to_string());
+        }
+
+        let event = ConfigReloadEvent { changes, reloaded };
+        if let Err(e) = app.emit("config-reloaded", &event) {
+            log::warn!("[ConfigWatcher] Failed to emit config-reloaded: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watched_files_skip_runtime_state() {
+        let anycode_dir = Path::new("/home/user/.anycode");
+        let files = watched_files(anycode_dir, Some(Path::new("/home/user/.claude")));
+
+        assert_eq!(
+            files.get(&anycode_dir.join("git-identity.json")),
+            Some(&"settings")
+        );
+        assert_eq!(
+            files.get(Path::new("/home/user/.claude/settings.json")),
+            Some(&"profiles")
+        );
+        for state in [
+            "prompt-drafts.json",
+            "prompt-history.json",
+            "worktrees.json",
+        ] {
+            assert!(!files.contains_key(&anycode_dir.join(state)));
+        }
+    }
+
+    #[test]
+    fn test_every_settings_file_has_a_reloader() {
+        let anycode_dir = Path::new("/home/user/.anycode");
+        let files = watched_files(anycode_dir, None);
+
+        for (name, _) in SETTINGS_FILES {
+            let path = anycode_dir.join(name);
+            assert_eq!(files.get(&path), Some(&"settings"));
+            assert!(settings_reloader(&path).is_some());
+        }
+        assert_eq!(
+            files.get(&anycode_dir.join("claude-profiles.json")),
+            Some(&"profiles")
+        );
+        assert!(settings_reloader(&anycode_dir.join("tickets.json")).is_some());
+        // Read from disk on every use, nothing to reload
+        for uncached in [
+            "forges.json",
+            "resource-limits.json",
+            "remote-projects.json",
+        ] {
+            assert!(!files.contains_key(&anycode_dir.join(uncached)));
+            assert!(settings_reloader(&anycode_dir.join(uncached)).is_none());
+        }
+    }
+}
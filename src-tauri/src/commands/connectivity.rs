@@ -0,0 +1,232 @@
+//! Connectivity monitor
+//!
+//! Periodically probes well-known "generate 204" endpoints to detect offline and
+//! captive-portal states. Network-dependent commands call [`ensure_online`] first
+//! so they fail fast with an `Offline:` error instead of hanging until a generic
+//! request timeout. Engines configured with a loopback or private-network
+//! endpoint (see [`is_local_endpoint`]) keep working offline.
+//!
+//! Events:
+//! - `connectivity-changed` - emitted with a [`ConnectivitySnapshot`] whenever the state changes
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::net::IpAddr;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Prefix of errors returned while offline, checked by the frontend
+pub const OFFLINE_ERROR_PREFIX: &str = "Offline";
+
+/// Probe interval while online
+const ONLINE_PROBE_INTERVAL_SECS: u64 = 30;
+/// Probe interval while offline, so recovery is noticed quickly
+const OFFLINE_PROBE_INTERVAL_SECS: u64 = 5;
+/// Timeout of a single probe request
+const PROBE_TIMEOUT_SECS: u64 = 5;
+
+/// Endpoints that answer with an empty 204 when the internet is reachable
+const PROBE_URLS: &[&str] = &[
+    "http://connectivitycheck.gstatic.com/generate_204",
+    "http://cp.cloudflare.com/generate_204",
+];
+
+/// Connectivity state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectivityState {
+    /// No probe has completed yet
+    Unknown,
+    Online,
+    Offline,
+    /// Requests are intercepted (hotel / airport login pages)
+    CaptivePortal,
+}
+
+/// Latest connectivity probe result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivitySnapshot {
+    pub state: ConnectivityState,
+    pub checked_at: Option<DateTime<Utc>>,
+    /// Error of the last failed probe, if any
+    pub detail: Option<String>,
+}
+
+lazy_static::lazy_static! {
+    static ref SNAPSHOT: RwLock<ConnectivitySnapshot> = RwLock::new(ConnectivitySnapshot {
+        state: ConnectivityState::Unknown,
+        checked_at: None,
+        detail: None,
+    });
+}
+
+/// Get the last known connectivity snapshot
+pub fn current_snapshot() -> ConnectivitySnapshot {
+    SNAPSHOT
+        .read()
+        .map(|snapshot| snapshot.clone())
+        .unwrap_or_else(|e| e.into_inner().clone())
+}
+
+/// Fail fast if the machine is known to be offline
+///
+/// Returns `Err("Offline: ...")` for offline and captive-portal states. An
+/// unknown state (monitor not yet run) is treated as online.
+pub fn ensure_online(operation: &str) -> Result<(), String> {
+    match current_snapshot().state {
+        ConnectivityState::Offline => Err(format!(
+            "{}: {} requires a network connection",
+            OFFLINE_ERROR_PREFIX, operation
+        )),
+        ConnectivityState::CaptivePortal => Err(format!(
+            "{}: {} requires a network connection (a captive portal is intercepting requests, sign in to the network first)",
+            OFFLINE_ERROR_PREFIX, operation
+        )),
+        ConnectivityState::Online | ConnectivityState::Unknown => Ok(()),
+    }
+}
+
+/// Whether a URL points at this machine or the local network
+///
+/// Covers loopback, private and link-local addresses as well as `localhost`
+/// and `.local` names; such endpoints stay reachable without internet access.
+pub fn is_local_endpoint(url: &str) -> bool {
+    let Ok(url) = reqwest::Url::parse(url.trim()) else {
+        return false;
+    };
+    let Some(host) = url.host_str() else {
+        return false;
+    };
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_loopback() || ip.is_private() || ip.is_link_local(),
+        Ok(IpAddr::V6(ip)) => {
+            let first = ip.segments()[0];
+            ip.is_loopback()
+                // Unique local (fc00::/7) and link-local (fe80::/10)
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || ip
+                    .to_ipv4_mapped()
+                    .is_some_and(|v4| v4.is_loopback() || v4.is_private())
+        }
+        Err(_) => {
+            let host = host.to_ascii_lowercase();
+            host == "localhost" || host.ends_with(".localhost") || host.ends_with(".local")
+        }
+    }
+}
+
+/// Probe the network once
+///
+/// Every probe URL is tried: one 204 means online, and a captive portal is only
+/// reported when no URL answered with 204 but at least one answered otherwise.
+async fn probe() -> (ConnectivityState, Option<String>) {
+    let client = match reqwest::Client::builder()
+        .timeout(Duration::from_secs(PROBE_TIMEOUT_SECS))
+        // A captive portal answers with a redirect to its login page
+        .redirect(reqwest::redirect::Policy::none())
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => return (ConnectivityState::Unknown, Some(e.to_string())),
+    };
+
+    let mut last_error = None;
+    let mut intercepted = None;
+    for url in PROBE_URLS {
+        match client.get(*url).send().await {
+            Ok(response) if response.status().as_u16() == 204 => {
+                return (ConnectivityState::Online, None);
+            }
+            Ok(response) => {
+                intercepted = Some(format!(
+                    "{} answered with status {}",
+                    url,
+                    response.status()
+                ));
+            }
+            Err(e) => {
+                last_error = Some(format!("{}: {}", url, e));
+            }
+        }
+    }
+
+    match intercepted {
+        Some(detail) => (ConnectivityState::CaptivePortal, Some(detail)),
+        None => (ConnectivityState::Offline, last_error),
+    }
+}
+
+/// Probe the network and publish the result, returning the new snapshot
+async fn refresh(app: &AppHandle) -> ConnectivitySnapshot {
+    let (state, detail) = probe().await;
+    let snapshot = ConnectivitySnapshot {
+        state,
+        checked_at: Some(Utc::now()),
+        detail,
+    };
+
+    let previous = {
+        let mut guard = SNAPSHOT.write().unwrap_or_else(|e| e.into_inner());
+        std::mem::replace(&mut *guard, snapshot.clone()).state
+    };
+
+    if previous != snapshot.state {
+        log::info!(
+            "[Connectivity] State changed: {:?} -> {:?}",
+            previous,
+            snapshot.state
+        );
+        if let Err(e) = app.emit("connectivity-changed", &snapshot) {
+            log::warn!("[Connectivity] Failed to emit connectivity-changed: {}", e);
+        }
+    }
+
+    snapshot
+}
+
+/// Start the background connectivity monitor
+pub async fn start_connectivity_monitor(app: AppHandle) {
+    loop {
+        let snapshot = refresh(&app).await;
+        let interval = match snapshot.state {
+            ConnectivityState::Online | ConnectivityState::Unknown => ONLINE_PROBE_INTERVAL_SECS,
+            ConnectivityState::Offline | ConnectivityState::CaptivePortal => {
+                OFFLINE_PROBE_INTERVAL_SECS
+            }
+        };
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+    }
+}
+
+/// Get the last known connectivity state
+#[tauri::command]
+pub async fn get_connectivity_status() -> Result<ConnectivitySnapshot, String> {
+    Ok(current_snapshot())
+}
+
+/// Probe the network immediately (e.g. after the user signed into a captive portal)
+#[tauri::command]
+pub async fn refresh_connectivity_status(app: AppHandle) -> Result<ConnectivitySnapshot, String> {
+    Ok(refresh(&app).await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_local_endpoint() {
+        assert!(is_local_endpoint("http://localhost:11434/v1"));
+        assert!(is_local_endpoint("http://127.0.0.1:8080"));
+        assert!(is_local_endpoint("https://192.168.1.20/api"));
+        assert!(is_local_endpoint("http://[::1]:3000"));
+        assert!(is_local_endpoint("http://gpu-box.local"));
+        assert!(!is_local_endpoint("https://api.anthropic.com"));
+        assert!(!is_local_endpoint("https://8.8.8.8"));
+        assert!(!is_local_endpoint("not a url"));
+    }
+}
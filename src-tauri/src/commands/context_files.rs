@@ -0,0 +1,352 @@
+//! Batched file reads for prompt context
+//!
+//! When a prompt @-mentions many files, `read_context_files` reads and hashes
+//! them concurrently in the backend, applies per-file and total size caps,
+//! skips binary files and paths matched by the project's exclusion policy
+//! (lockfiles, build outputs, ...), transcodes non-UTF-8 files to UTF-8, and returns both the per-file results and a single
+//! assembled context block, instead of the frontend issuing one fs call per file.
+
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncReadExt;
+
+use crate::commands::text_encoding::{self, EncodingReport};
+use crate::commands::{exclusion_policy, large_file_guard, monorepo, profiling};
+
+/// Files read at the same time
+const READ_CONCURRENCY: usize = 16;
+
+/// Default per-file cap
+const DEFAULT_MAX_FILE_BYTES: u64 = 256 * 1024;
+
+/// Default cap on the assembled payload
+const DEFAULT_MAX_TOTAL_BYTES: u64 = 2 * 1024 * 1024;
+
+/// Bytes inspected for NUL bytes when detecting binary files
+const BINARY_SNIFF_BYTES: usize = 8000;
+
+/// Outcome of reading one file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ContextFileStatus {
+    Included,
+    /// Included, but cut at the per-file cap
+    Truncated,
+    Binary,
+    /// Left out because the total budget was used up
+    OverBudget,
+    Missing,
+    /// Outside the package the session is scoped to
+    OutOfScope,
+    /// Over the attachment size limit; not read
+    TooLarge,
+    /// Matched by the project's exclusion policy; not read
+    Excluded,
+    Error,
+}
+
+/// One requested file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFile {
+    /// Path as requested
+    pub path: String,
+    pub absolute_path: String,
+    pub status: ContextFileStatus,
+    pub size: u64,
+    /// SHA-256 of the full file content
+    pub sha256: Option<String>,
+    pub content: Option<String>,
+    /// Set when the file was not UTF-8 and had to be transcoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encoding: Option<EncodingReport>,
+    pub error: Option<String>,
+}
+
+/// All requested files plus the assembled context block
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContextFilesPayload {
+    pub files: Vec<ContextFile>,
+    /// Included files wrapped in `<file path="...">` blocks, in request order
+    pub assembled: String,
+    pub total_bytes: u64,
+}
+
+fn resolve(project_path: &str, path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        path.to_path_buf()
+    } else {
+        Path::new(project_path).join(path)
+    }
+}
+
+/// Whether a requested path lies inside the package scope (if any)
+fn in_scope(project_path: &str, absolute: &Path, package_scope: Option<&str>) -> bool {
+    let Some(package) = package_scope else {
+        return true;
+    };
+    absolute
+        .strip_prefix(project_path)
+        .map(|relative| monorepo::is_in_package(&relative.to_string_lossy(), package))
+        .unwrap_or(false)
+}
+
+fn looks_binary(bytes: &[u8]) -> bool {
+    bytes[..bytes.len().min(BINARY_SNIFF_BYTES)].contains(&0)
+}
+
+/// Cut text to at most `max` bytes on a char boundary
+fn truncate_to(text: &mut String, max: usize) {
+    if text.len() > max {
+        let mut end = max;
+        while !text.is_char_boundary(end) {
+            end -= 1;
+        }
+        text.truncate(end);
+    }
+}
+
+async fn read_one(
+    path: String,
+    absolute: PathBuf,
+    max_file_bytes: u64,
+    max_attachment_bytes: u64,
+) -> ContextFile {
+    let mut file = ContextFile {
+        path,
+        absolute_path: absolute.to_string_lossy().to_string(),
+        status: ContextFileStatus::Error,
+        size: 0,
+        sha256: None,
+        content: None,
+        encoding: None,
+        error: None,
+    };
+
+    let mut handle = match tokio::fs::File::open(&absolute).await {
+        Ok(handle) => handle,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            file.status = ContextFileStatus::Missing;
+            return file;
+        }
+        Err(e) => {
+            file.error = Some(e.to_string());
+            return file;
+        }
+    };
+
+    // Refuse huge files before reading them
+    if let Ok(metadata) = handle.metadata().await {
+        file.size = metadata.len();
+        if let Some(error) =
+            large_file_guard::attachment_size_error(&file.path, file.size, max_attachment_bytes)
+        {
+            file.status = ContextFileStatus::TooLarge;
+            file.error = Some(error);
+            return file;
+        }
+    }
+
+    let mut bytes = Vec::new();
+    if let Err(e) = handle.read_to_end(&mut bytes).await {
+        file.error = Some(e.to_string());
+        return file;
+    }
+    file.size = bytes.len() as u64;
+    file.sha256 = Some(format!("{:x}", Sha256::digest(&bytes)));
+
+    if looks_binary(&bytes) {
+        file.status = ContextFileStatus::Binary;
+        return file;
+    }
+    let decoded = text_encoding::decode_text(&bytes);
+    let mut text = decoded.content;
+    if !decoded.report.is_utf8() {
+        file.encoding = Some(decoded.report);
+    }
+
+    file.status = if text.len() as u64 > max_file_bytes {
+        truncate_to(&mut text, max_file_bytes as usize);
+        ContextFileStatus::Truncated
+    } else {
+        ContextFileStatus::Included
+    };
+    file.content = Some(text);
+    file
+}
+
+/// Drop contents beyond the total budget and build the assembled block
+fn assemble(files: &mut [ContextFile], max_total_bytes: u64) -> (String, u64) {
+    let mut assembled = String::new();
+    let mut total = 0u64;
+    for file in files.iter_mut() {
+        let Some(content) = &file.content else {
+            continue;
+        };
+        if total + content.len() as u64 > max_total_bytes {
+            file.status = ContextFileStatus::OverBudget;
+            file.content = None;
+            continue;
+        }
+        total += content.len() as u64;
+
+        assembled.push_str(&format!("<file path=\"{}\">\n", file.path));
+        assembled.push_str(content);
+        if !content.ends_with('\n') {
+            assembled.push('\n');
+        }
+        if file.status == ContextFileStatus::Truncated {
+            assembled.push_str(&format!("[... truncated, {} bytes total]\n", file.size));
+        }
+        if let Some(report) = file.encoding.as_ref().filter(|r| r.lossy) {
+            assembled.push_str(&format!(
+                "[... {} characters could not be decoded from {}]\n",
+                report.replaced_chars, report.encoding
+            ));
+        }
+        assembled.push_str("</file>\n\n");
+    }
+    (assembled.trim_end().to_string(), total)
+}
+
+/// Read the files mentioned in a prompt concurrently and assemble them into one context block
+#[tauri::command]
+pub async fn read_context_files(
+    project_path: String,
+    paths: Vec<String>,
+    max_file_bytes: Option<u64>,
+    max_total_bytes: Option<u64>,
+    package_scope: Option<String>,
+) -> Result<ContextFilesPayload, String> {
+    let mut timer = profiling::CommandTimer::start("read_context_files");
+    let max_file_bytes = max_file_bytes.unwrap_or(DEFAULT_MAX_FILE_BYTES);
+    let max_total_bytes = max_total_bytes.unwrap_or(DEFAULT_MAX_TOTAL_BYTES);
+
+    let mut seen = std::collections::HashSet::new();
+    let requests: Vec<(String, PathBuf)> = paths
+        .into_iter()
+        .filter(|path| seen.insert(path.clone()))
+        .map(|path| {
+            let absolute = resolve(&project_path, &path);
+            (path, absolute)
+        })
+        .collect();
+
+    let package_scope = package_scope.as_deref();
+    let max_attachment_bytes = large_file_guard::load_config().max_attachment_bytes;
+    let policy = exclusion_policy::policy_for_project(&project_path);
+    let mut files: Vec<ContextFile> = stream::iter(requests)
+        .map(|(path, absolute)| {
+            let skipped = if !in_scope(&project_path, &absolute, package_scope) {
+                Some(ContextFileStatus::OutOfScope)
+            } else if policy.is_path_excluded(Path::new(&project_path), &absolute) {
+                Some(ContextFileStatus::Excluded)
+            } else {
+                None
+            };
+            async move {
+                match skipped {
+                    None => read_one(path, absolute, max_file_bytes, max_attachment_bytes).await,
+                    Some(status) => ContextFile {
+                        absolute_path: absolute.to_string_lossy().to_string(),
+                        path,
+                        status,
+                        size: 0,
+                        sha256: None,
+                        content: None,
+                        encoding: None,
+                        error: None,
+                    },
+                }
+            }
+        })
+        .buffered(READ_CONCURRENCY)
+        .collect()
+        .await;
+
+    let (assembled, total_bytes) = assemble(&mut files, max_total_bytes);
+    log::debug!(
+        "[ContextFiles] Read {} files for {} ({} bytes assembled)",
+        files.len(),
+        project_path,
+        total_bytes
+    );
+
+    let payload = ContextFilesPayload {
+        files,
+        assembled,
+        total_bytes,
+    };
+    timer.payload(&payload);
+    Ok(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_read_context_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.rs"), "fn main() {}\n").unwrap();
+        std::fs::write(dir.path().join("big.txt"), "x".repeat(100)).unwrap();
+        std::fs::write(dir.path().join("data.bin"), [0x7f, b'E', b'L', b'F', 0, 0]).unwrap();
+        std::fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+
+        let payload = read_context_files(
+            dir.path().to_string_lossy().to_string(),
+            vec![
+                "a.rs".to_string(),
+                "big.txt".to_string(),
+                "data.bin".to_string(),
+                "missing.rs".to_string(),
+                "package-lock.json".to_string(),
+                "a.rs".to_string(),
+            ],
+            Some(50),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        let statuses: Vec<_> = payload.files.iter().map(|f| f.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                ContextFileStatus::Included,
+                ContextFileStatus::Truncated,
+                ContextFileStatus::Binary,
+                ContextFileStatus::Missing,
+                ContextFileStatus::Excluded,
+            ]
+        );
+        assert!(payload
+            .assembled
+            .starts_with("<file path=\"a.rs\">\nfn main() {}\n</file>"));
+        assert_eq!(payload.total_bytes, 13 + 50);
+    }
+
+    #[test]
+    fn test_assemble_respects_total_budget() {
+        let file = |path: &str, content: &str| ContextFile {
+            path: path.to_string(),
+            absolute_path: path.to_string(),
+            status: ContextFileStatus::Included,
+            size: content.len() as u64,
+            sha256: None,
+            content: Some(content.to_string()),
+            encoding: None,
+            error: None,
+        };
+        let mut files = vec![file("a", "12345"), file("b", "123456"), file("c", "1")];
+        let (_, total) = assemble(&mut files, 6);
+        assert_eq!(total, 6);
+        assert_eq!(files[1].status, ContextFileStatus::OverBudget);
+        assert_eq!(files[2].status, ContextFileStatus::Included);
+    }
+}
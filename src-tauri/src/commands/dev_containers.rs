@@ -0,0 +1,440 @@
+//! Dev container execution mode
+//!
+//! Projects with a `.devcontainer/devcontainer.json` (or `.devcontainer.json`)
+//! can opt into running engines and project commands inside their dev
+//! container via `docker exec`, so AI edits are built and tested in the
+//! environment the team uses. The workspace is bind-mounted, so git keeps
+//! running on the host against the same files. The running container is found
+//! through the `devcontainer.local_folder` label set by the Dev Containers
+//! tooling, or set by hand. Per-project settings live in
+//! `~/.anycode/dev-containers.json`.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use tokio::process::Command;
+
+use crate::commands::claude::apply_no_window_async;
+use crate::commands::engine_safety::project_key;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Environment variables of engine commands forwarded into the container
+const FORWARDED_ENV_PREFIXES: &[&str] = &[
+    "ANTHROPIC_",
+    "CLAUDE_",
+    "CODEX_",
+    "OPENAI_",
+    "GEMINI_",
+    "GOOGLE_",
+];
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Per-project dev container settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerSettings {
+    /// Run engines and project commands inside the container
+    #[serde(default)]
+    pub enabled: bool,
+    /// Container name or id, overriding label-based lookup
+    #[serde(default)]
+    pub container: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DevContainerStore {
+    #[serde(default)]
+    projects: BTreeMap<String, DevContainerSettings>,
+}
+
+/// The parts of devcontainer.json the app uses
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerConfig {
+    pub name: Option<String>,
+    pub image: Option<String>,
+    pub docker_compose_file: Option<String>,
+    pub service: Option<String>,
+    /// Project directory inside the container
+    pub workspace_folder: String,
+    pub remote_user: Option<String>,
+}
+
+/// Dev container state of a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevContainerInfo {
+    pub config_path: String,
+    pub config: DevContainerConfig,
+    pub settings: DevContainerSettings,
+    /// Id of the running container, if any
+    pub container_id: Option<String>,
+}
+
+/// Output of a command run in the container
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContainerCommandOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("dev-containers.json"))
+}
+
+fn load_store() -> DevContainerStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn project_settings(project_path: &str) -> DevContainerSettings {
+    load_store()
+        .projects
+        .remove(&project_key(project_path))
+        .unwrap_or_default()
+}
+
+/// Locate the project's devcontainer.json
+fn find_config(project_path: &Path) -> Option<PathBuf> {
+    let dir = project_path.join(".devcontainer");
+    [
+        dir.join("devcontainer.json"),
+        project_path.join(".devcontainer.json"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())
+    .or_else(|| {
+        // .devcontainer/<name>/devcontainer.json (multiple configurations)
+        let mut nested: Vec<PathBuf> = std::fs::read_dir(&dir)
+            .ok()?
+            .flatten()
+            .map(|entry| entry.path().join("devcontainer.json"))
+            .filter(|path| path.is_file())
+            .collect();
+        nested.sort();
+        nested.into_iter().next()
+    })
+}
+
+/// Parse devcontainer.json (comments and trailing commas allowed)
+fn parse_config(content: &str, project_path: &Path) -> Result<DevContainerConfig, String> {
+    let value = jsonc_parser::parse_to_serde_value(content, &Default::default())
+        .map_err(|e| format!("Failed to parse devcontainer.json: {}", e))?
+        .unwrap_or(Value::Null);
+    let text = |key: &str| value.get(key).and_then(Value::as_str).map(str::to_string);
+    let folder_name = project_path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    Ok(DevContainerConfig {
+        name: text("name"),
+        image: text("image"),
+        docker_compose_file: match value.get("dockerComposeFile") {
+            Some(Value::Array(files)) => files.first().and_then(Value::as_str).map(str::to_string),
+            _ => text("dockerComposeFile"),
+        },
+        service: text("service"),
+        workspace_folder: text("workspaceFolder")
+            .map(|folder| folder.replace("${localWorkspaceFolderBasename}", &folder_name))
+            .unwrap_or_else(|| format!("/workspaces/{}", folder_name)),
+        remote_user: text("remoteUser").or_else(|| text("containerUser")),
+    })
+}
+
+fn load_config(project_path: &str) -> Result<Option<(PathBuf, DevContainerConfig)>, String> {
+    let root = Path::new(project_path);
+    let Some(path) = find_config(root) else {
+        return Ok(None);
+    };
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(Some((path, parse_config(&content, root)?)))
+}
+
+/// Id of the project's running container
+fn running_container(project_path: &str, settings: &DevContainerSettings) -> Option<String> {
+    let filter = match &settings.container {
+        Some(container) => format!("name={}", container),
+        None => format!("label=devcontainer.local_folder={}", project_path),
+    };
+    let mut cmd = std::process::Command::new("docker");
+    cmd.args(["ps", "-q", "--filter", &filter]);
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let output = cmd.output().ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// `docker exec` arguments running `program args` in the container's workspace
+fn exec_args(
+    container_id: &str,
+    config: &DevContainerConfig,
+    env: &[(String, String)],
+    program: &str,
+    args: &[String],
+) -> Vec<String> {
+    let mut exec = vec!["exec".to_string(), "-i".to_string()];
+    if let Some(user) = &config.remote_user {
+        exec.extend(["-u".to_string(), user.clone()]);
+    }
+    exec.extend(["-w".to_string(), config.workspace_folder.clone()]);
+    for (key, value) in env {
+        exec.extend(["-e".to_string(), format!("{}={}", key, value)]);
+    }
+    exec.push(container_id.to_string());
+    exec.push(program.to_string());
+    exec.extend(args.iter().cloned());
+    exec
+}
+
+/// Engine environment variables explicitly set on a host command
+fn forwarded_env(host_cmd: &Command) -> Vec<(String, String)> {
+    host_cmd
+        .as_std()
+        .get_envs()
+        .filter_map(|(key, value)| {
+            let key = key.to_string_lossy().to_string();
+            let value = value?.to_string_lossy().to_string();
+            // Host paths (CLAUDE_CONFIG_DIR, CODEX_PATH, ...) mean nothing in the container
+            let is_path = key.ends_with("_DIR") || key.ends_with("_PATH");
+            let forwarded = FORWARDED_ENV_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix));
+            (forwarded && !is_path).then_some((key, value))
+        })
+        .collect()
+}
+
+/// The equivalent of `host_cmd` (running `program`) inside the project's dev
+/// container, when the project uses container mode. Fails when container mode
+/// is on but the container is not running.
+pub fn container_command(
+    project_path: &str,
+    program: &str,
+    host_cmd: &Command,
+) -> Result<Option<Command>, String> {
+    let settings = project_settings(project_path);
+    if !settings.enabled {
+        return Ok(None);
+    }
+    let Some((_, config)) = load_config(project_path)? else {
+        return Ok(None);
+    };
+    let container_id = running_container(project_path, &settings).ok_or(
+        "Dev container mode is on but the project's container is not running. Start it first.",
+    )?;
+
+    let args: Vec<String> = host_cmd
+        .as_std()
+        .get_args()
+        .map(|arg| arg.to_string_lossy().to_string())
+        .collect();
+    let mut cmd = Command::new("docker");
+    cmd.args(exec_args(
+        &container_id,
+        &config,
+        &forwarded_env(host_cmd),
+        program,
+        &args,
+    ));
+    apply_no_window_async(&mut cmd);
+    log::info!(
+        "[DevContainer] Running {} in container {} ({})",
+        program,
+        container_id,
+        config.workspace_folder
+    );
+    Ok(Some(cmd))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Detect the project's dev container configuration and running container
+#[tauri::command]
+pub async fn detect_dev_container(
+    project_path: String,
+) -> Result<Option<DevContainerInfo>, String> {
+    let Some((config_path, config)) = load_config(&project_path)? else {
+        return Ok(None);
+    };
+    let settings = project_settings(&project_path);
+    let container_id = tokio::task::spawn_blocking({
+        let settings = settings.clone();
+        move || running_container(&project_path, &settings)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok(Some(DevContainerInfo {
+        config_path: config_path.to_string_lossy().to_string(),
+        config,
+        settings,
+        container_id,
+    }))
+}
+
+/// Turn container execution mode on or off for a project
+#[tauri::command]
+pub async fn update_dev_container_settings(
+    project_path: String,
+    settings: DevContainerSettings,
+) -> Result<(), String> {
+    if settings.enabled && load_config(&project_path)?.is_none() {
+        return Err("The project has no devcontainer.json".to_string());
+    }
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    store.projects.insert(project_key(&project_path), settings);
+    save_json_config(&store, store_path()?)
+}
+
+/// Build and start the dev container with the Dev Containers CLI
+#[tauri::command]
+pub async fn start_dev_container(project_path: String) -> Result<String, String> {
+    let mut cmd = Command::new("devcontainer");
+    cmd.args(["up", "--workspace-folder", &project_path])
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    apply_no_window_async(&mut cmd);
+    let output = cmd.output().await.map_err(|e| {
+        format!(
+            "Failed to run the Dev Containers CLI ({}). Install it with: npm install -g @devcontainers/cli",
+            e
+        )
+    })?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    // The CLI prints a JSON result with the container id as its last line
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .rev()
+        .find_map(|line| serde_json::from_str::<Value>(line).ok())
+        .and_then(|result| {
+            result
+                .get("containerId")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+        })
+        .ok_or_else(|| "The Dev Containers CLI did not report a container".to_string())
+}
+
+/// Run a shell command in the project's dev container (build, test, ...)
+#[tauri::command]
+pub async fn run_in_dev_container(
+    project_path: String,
+    command: String,
+) -> Result<ContainerCommandOutput, String> {
+    let (_, config) = load_config(&project_path)?.ok_or("The project has no devcontainer.json")?;
+    let settings = project_settings(&project_path);
+    let container_id = {
+        let project_path = project_path.clone();
+        tokio::task::spawn_blocking(move || running_container(&project_path, &settings))
+            .await
+            .map_err(|e| e.to_string())?
+    }
+    .ok_or("The project's dev container is not running")?;
+
+    let mut cmd = Command::new("docker");
+    cmd.args(exec_args(
+        &container_id,
+        &config,
+        &[],
+        "sh",
+        &["-lc".to_string(), command],
+    ))
+    .stdin(Stdio::null());
+    apply_no_window_async(&mut cmd);
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run docker: {}", e))?;
+    Ok(ContainerCommandOutput {
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_config() {
+        let content = r#"{
+            // Team environment
+            "name": "app",
+            "dockerComposeFile": ["docker-compose.yml"],
+            "service": "dev",
+            "workspaceFolder": "/work/${localWorkspaceFolderBasename}",
+            "remoteUser": "node",
+        }"#;
+        let config = parse_config(content, Path::new("/home/me/shop")).unwrap();
+        assert_eq!(config.workspace_folder, "/work/shop");
+        assert_eq!(
+            config.docker_compose_file.as_deref(),
+            Some("docker-compose.yml")
+        );
+        assert_eq!(config.remote_user.as_deref(), Some("node"));
+
+        let config = parse_config(r#"{"image": "rust:1"}"#, Path::new("/home/me/shop")).unwrap();
+        assert_eq!(config.workspace_folder, "/workspaces/shop");
+    }
+
+    #[test]
+    fn test_container_command_args() {
+        let config = DevContainerConfig {
+            workspace_folder: "/workspaces/shop".to_string(),
+            remote_user: Some("node".to_string()),
+            ..Default::default()
+        };
+        let mut host = Command::new("/usr/local/bin/claude");
+        host.arg("-p")
+            .env("ANTHROPIC_MODEL", "sonnet")
+            .env("CLAUDE_CONFIG_DIR", "/home/me/.claude")
+            .env("PATH", "/host/bin");
+        let args = exec_args(
+            "abc",
+            &config,
+            &forwarded_env(&host),
+            "claude",
+            &["-p".to_string()],
+        );
+        assert_eq!(
+            args,
+            vec![
+                "exec",
+                "-i",
+                "-u",
+                "node",
+                "-w",
+                "/workspaces/shop",
+                "-e",
+                "ANTHROPIC_MODEL=sonnet",
+                "abc",
+                "claude",
+                "-p"
+            ]
+        );
+    }
+}
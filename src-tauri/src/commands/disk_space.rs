@@ -0,0 +1,249 @@
+//! Disk space checks before heavy git operations
+//!
+//! Worktrees, bundles, clones and checkpoint commits can write as much as the
+//! repository (or the pending changes) weighs. Before starting one, the space
+//! needed is estimated from git and compared with what is free on the target
+//! volume, so the operation fails early with "required vs available" instead
+//! of git dying halfway and leaving a half-written state behind.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::large_file_guard::format_size;
+use crate::commands::profiling;
+use crate::commands::wsl_utils::git_command;
+
+/// Kept free on top of the estimate (index, temp packs, lock files)
+const HEADROOM_BYTES: u64 = 64 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum HeavyOperation {
+    Worktree,
+    Bundle,
+    Clone,
+    Checkpoint,
+}
+
+impl HeavyOperation {
+    fn describe(&self) -> &'static str {
+        match self {
+            HeavyOperation::Worktree => "create the worktree",
+            HeavyOperation::Bundle => "write the bundle",
+            HeavyOperation::Clone => "clone the repository",
+            HeavyOperation::Checkpoint => "create the checkpoint",
+        }
+    }
+}
+
+/// Free and total space of the volume holding a path
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpace {
+    pub path: String,
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+}
+
+/// Result of a pre-flight check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiskSpaceCheck {
+    pub operation: HeavyOperation,
+    pub target_path: String,
+    pub required_bytes: u64,
+    pub available_bytes: u64,
+    pub sufficient: bool,
+}
+
+/// The path itself, or its closest existing ancestor (the target of a clone does not exist yet)
+fn existing_ancestor(path: &Path) -> Option<PathBuf> {
+    path.ancestors().find(|p| p.exists()).map(Path::to_path_buf)
+}
+
+pub fn disk_space(path: &str) -> Result<DiskSpace, String> {
+    let existing = existing_ancestor(Path::new(path))
+        .ok_or_else(|| format!("No existing directory for {}", path))?;
+    let available_bytes = fs2::available_space(&existing)
+        .map_err(|e| format!("Failed to query free space for {}: {}", path, e))?;
+    let total_bytes = fs2::total_space(&existing)
+        .map_err(|e| format!("Failed to query volume size for {}: {}", path, e))?;
+    Ok(DiskSpace {
+        path: path.to_string(),
+        available_bytes,
+        total_bytes,
+    })
+}
+
+fn git_stdout(project_path: &str, args: &[&str]) -> Result<Vec<u8>, String> {
+    let mut cmd = git_command(project_path);
+    cmd.args(args);
+    let output = profiling::timed_output(&format!("git {}", args[0]), &mut cmd)
+        .map_err(|e| format!("Failed to run git {}: {}", args[0], e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(output.stdout)
+}
+
+/// Parse `git count-objects -v` into bytes of loose objects plus packs
+fn parse_count_objects(output: &str) -> u64 {
+    output
+        .lines()
+        .filter_map(|line| line.split_once(": "))
+        .filter(|(key, _)| matches!(*key, "size" | "size-pack"))
+        .filter_map(|(_, kib)| kib.trim().parse::<u64>().ok())
+        .sum::<u64>()
+        * 1024
+}
+
+/// Parse `git ls-tree -r -l` into the total blob size
+fn parse_ls_tree(output: &str) -> u64 {
+    output
+        .lines()
+        .filter_map(|line| line.split_whitespace().nth(3))
+        .filter_map(|size| size.parse::<u64>().ok())
+        .sum()
+}
+
+/// Size of the object store, what a bundle or clone copies
+pub fn object_store_bytes(project_path: &str) -> Result<u64, String> {
+    let stdout = git_stdout(project_path, &["count-objects", "-v"])?;
+    Ok(parse_count_objects(&String::from_utf8_lossy(&stdout)))
+}
+
+/// Size of the files a checkout of `revision` writes
+pub fn checkout_bytes(project_path: &str, revision: &str) -> Result<u64, String> {
+    let stdout = git_stdout(project_path, &["ls-tree", "-r", "-l", revision])?;
+    Ok(parse_ls_tree(&String::from_utf8_lossy(&stdout)))
+}
+
+/// Size of the changed and untracked files a checkpoint commit would store
+pub fn pending_change_bytes(project_path: &str, pathspec: Option<&str>) -> Result<u64, String> {
+    let mut args = vec!["status", "--porcelain", "-z", "--untracked-files=all"];
+    if let Some(pathspec) = pathspec {
+        args.extend(["--", pathspec]);
+    }
+    let stdout = git_stdout(project_path, &args)?;
+    let root = Path::new(project_path);
+    Ok(stdout
+        .split(|b| *b == 0)
+        // Entries are "XY path"; rename sources follow as bare paths and are skipped by the length check
+        .filter(|entry| entry.len() > 3 && entry[2] == b' ')
+        .filter_map(|entry| {
+            let path = String::from_utf8_lossy(&entry[3..]).to_string();
+            std::fs::metadata(root.join(path)).ok()
+        })
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum())
+}
+
+/// Space an operation on `project_path` needs
+pub fn required_bytes(
+    project_path: &str,
+    operation: HeavyOperation,
+    pathspec: Option<&str>,
+) -> Result<u64, String> {
+    let estimate = match operation {
+        HeavyOperation::Worktree => checkout_bytes(project_path, "HEAD")?,
+        HeavyOperation::Bundle | HeavyOperation::Clone => object_store_bytes(project_path)?,
+        HeavyOperation::Checkpoint => pending_change_bytes(project_path, pathspec)?,
+    };
+    Ok(estimate + HEADROOM_BYTES)
+}
+
+/// Fail if the volume of `target_path` has less than `required` bytes free
+pub fn ensure_disk_space(
+    target_path: &str,
+    required: u64,
+    operation: HeavyOperation,
+) -> Result<(), String> {
+    let space = disk_space(target_path)?;
+    if space.available_bytes < required {
+        return Err(format!(
+            "Not enough disk space to {} at {}: {} required, {} available ({} bytes required, {} bytes available)",
+            operation.describe(),
+            target_path,
+            format_size(required),
+            format_size(space.available_bytes),
+            required,
+            space.available_bytes
+        ));
+    }
+    Ok(())
+}
+
+/// Estimate and check in one step; `target_path` defaults to the project
+pub fn ensure_space_for(
+    project_path: &str,
+    operation: HeavyOperation,
+    target_path: Option<&str>,
+    pathspec: Option<&str>,
+) -> Result<(), String> {
+    let required = required_bytes(project_path, operation, pathspec)?;
+    ensure_disk_space(target_path.unwrap_or(project_path), required, operation)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_disk_space(path: String) -> Result<DiskSpace, String> {
+    tokio::task::spawn_blocking(move || disk_space(&path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Pre-flight check for a heavy operation, without running it
+#[tauri::command]
+pub async fn check_disk_space_for(
+    project_path: String,
+    operation: HeavyOperation,
+    target_path: Option<String>,
+) -> Result<DiskSpaceCheck, String> {
+    tokio::task::spawn_blocking(move || {
+        let target_path = target_path.unwrap_or_else(|| project_path.clone());
+        let required_bytes = required_bytes(&project_path, operation, None)?;
+        let available_bytes = disk_space(&target_path)?.available_bytes;
+        Ok(DiskSpaceCheck {
+            operation,
+            target_path,
+            required_bytes,
+            available_bytes,
+            sufficient: available_bytes >= required_bytes,
+        })
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_git_sizes() {
+        let count_objects = "count: 12\nsize: 48\nin-pack: 300\npacks: 1\nsize-pack: 1000\nprune-packable: 0\ngarbage: 0\nsize-garbage: 0\n";
+        assert_eq!(parse_count_objects(count_objects), 1048 * 1024);
+
+        let ls_tree = "100644 blob 3b18e512dba79e4c8300dd08aeb37f8e728b8dad      12\tREADME.md\n\
+                       100644 blob 9daeafb9864cf43055ae93beb0afd6c7d144bfa4    2048\tsrc/main.rs\n\
+                       160000 commit 5e1c309dae7f45e0f39b1bf3ac3cd9db12e7d689       -\tvendor/lib\n";
+        assert_eq!(parse_ls_tree(ls_tree), 2060);
+    }
+
+    #[test]
+    fn test_disk_space_of_missing_target_uses_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("clones/new-repo");
+        let space = disk_space(&target.to_string_lossy()).unwrap();
+        assert!(space.total_bytes >= space.available_bytes);
+        assert!(
+            ensure_disk_space(&target.to_string_lossy(), u64::MAX, HeavyOperation::Clone)
+                .unwrap_err()
+                .contains("required")
+        );
+    }
+}
@@ -0,0 +1,457 @@
+//! Engine CLI doctor
+//!
+//! `engine_doctor` diagnoses one engine CLI (Claude Code, Codex, Gemini):
+//! whether it is installed and which version, whether it has credentials, and
+//! the PATH problems that hide an installed CLI from the workbench — npm's
+//! global bin directory missing from PATH (the usual Windows failure), several
+//! installs shadowing each other, npm shims without Node.js. Every problem
+//! comes with a suggested fix. When a CLI disappears while a session is open
+//! the next spawn fails; that failure is reported on `engine-cli-missing` so
+//! the frontend can offer the doctor instead of a bare error.
+
+use serde::{Deserialize, Serialize};
+use std::ffi::OsStr;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+use super::onboarding::{self, CheckStatus, FixAction, OnboardingCheck};
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Emitted when an engine CLI cannot be started because its binary is gone
+pub const CLI_MISSING_EVENT: &str = "engine-cli-missing";
+
+/// Diagnosis of a single engine CLI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineDiagnosis {
+    pub engine: String,
+    /// Binary the workbench runs, when it could be resolved
+    pub binary_path: Option<String>,
+    pub version: Option<String>,
+    pub logged_in: bool,
+    /// Every match of the program on PATH, in lookup order
+    pub path_matches: Vec<String>,
+    pub checks: Vec<OnboardingCheck>,
+    /// Installed, logged in and no check failed
+    pub healthy: bool,
+}
+
+/// Payload of [`CLI_MISSING_EVENT`]
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct CliMissingEvent {
+    engine: String,
+    program: String,
+    project_path: String,
+    error: String,
+}
+
+/// What PATH holds for one program
+struct PathScan {
+    /// PATH directories in lookup order, without duplicates
+    dirs: Vec<PathBuf>,
+    /// Every match of the program, in lookup order
+    matches: Vec<PathBuf>,
+    /// Directory npm installs global CLIs into
+    npm_bin: Option<PathBuf>,
+    node_found: bool,
+}
+
+impl PathScan {
+    fn run(program: &str) -> Self {
+        let dirs = path_dirs(&std::env::var_os("PATH").unwrap_or_default());
+        Self {
+            matches: find_in_dirs(program, &dirs),
+            node_found: !find_in_dirs("node", &dirs).is_empty(),
+            npm_bin: npm_global_bin(),
+            dirs,
+        }
+    }
+
+    fn contains_dir(&self, dir: &Path) -> bool {
+        self.dirs.iter().any(|d| same_dir(d, dir))
+    }
+}
+
+fn normalize_dir(dir: &Path) -> String {
+    let dir = dir.to_string_lossy();
+    let dir = dir.trim_end_matches(['/', '\\']);
+    if cfg!(windows) {
+        dir.to_lowercase()
+    } else {
+        dir.to_string()
+    }
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    normalize_dir(a) == normalize_dir(b)
+}
+
+/// Split a PATH value, dropping empty and repeated entries
+fn path_dirs(path_var: &OsStr) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    for dir in std::env::split_paths(path_var) {
+        if !dir.as_os_str().is_empty() && !dirs.iter().any(|d| same_dir(d, &dir)) {
+            dirs.push(dir);
+        }
+    }
+    dirs
+}
+
+/// File names the program can have on disk (`claude.cmd`, `claude.exe`, ... on Windows)
+fn executable_names(program: &str) -> Vec<String> {
+    if !cfg!(windows) {
+        return vec![program.to_string()];
+    }
+    std::env::var("PATHEXT")
+        .unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string())
+        .split(';')
+        .filter(|ext| !ext.is_empty())
+        .map(|ext| format!("{}{}", program, ext.to_lowercase()))
+        .collect()
+}
+
+fn find_in_dirs(program: &str, dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let names = executable_names(program);
+    dirs.iter()
+        .flat_map(|dir| names.iter().map(move |name| dir.join(name)))
+        .filter(|path| path.is_file())
+        .collect()
+}
+
+/// Directory npm installs global CLIs into
+fn npm_global_bin() -> Option<PathBuf> {
+    let mut cmd = std::process::Command::new(if cfg!(windows) { "npm.cmd" } else { "npm" });
+    cmd.args(["prefix", "-g"]);
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let prefix = cmd
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+        .filter(|prefix| !prefix.is_empty())
+        .map(PathBuf::from);
+
+    if cfg!(windows) {
+        // Without npm on PATH fall back to the default global prefix
+        prefix.or_else(|| {
+            std::env::var_os("APPDATA").map(|appdata| PathBuf::from(appdata).join("npm"))
+        })
+    } else {
+        prefix.map(|prefix| prefix.join("bin"))
+    }
+}
+
+/// Whether the file is an npm-generated launcher that needs Node.js
+fn is_node_shim(path: &Path) -> bool {
+    let mut head = Vec::with_capacity(512);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(512).read_to_end(&mut head))
+        .map(|_| String::from_utf8_lossy(&head).contains("node"))
+        .unwrap_or(false)
+}
+
+fn add_to_path_fix(dir: &Path) -> FixAction {
+    if cfg!(windows) {
+        // `setx` truncates PATH at 1024 characters, append through PowerShell instead
+        FixAction::RunCommand {
+            label: "Add to user PATH (PowerShell, then restart the app)".to_string(),
+            command: format!(
+                "[Environment]::SetEnvironmentVariable('Path', [Environment]::GetEnvironmentVariable('Path', 'User') + ';{}', 'User')",
+                dir.display()
+            ),
+        }
+    } else {
+        FixAction::RunCommand {
+            label: "Add to PATH (then restart the app)".to_string(),
+            command: format!(
+                "echo 'export PATH=\"{}:$PATH\"' >> ~/.profile",
+                dir.display()
+            ),
+        }
+    }
+}
+
+fn choose_path_fix() -> FixAction {
+    FixAction::OpenSettings {
+        label: "Choose the CLI path".to_string(),
+        section: "engines".to_string(),
+    }
+}
+
+/// PATH checks for `program`; `resolved` is the binary the workbench runs
+fn path_checks(
+    engine: &str,
+    program: &str,
+    resolved: Option<&str>,
+    scan: &PathScan,
+) -> Vec<OnboardingCheck> {
+    let id = format!("{}-path", engine);
+    let title = format!("{} on PATH", program);
+    let mut checks = Vec::new();
+
+    match scan.matches.as_slice() {
+        [] => {
+            let npm_install = scan
+                .npm_bin
+                .as_ref()
+                .filter(|bin| !find_in_dirs(program, std::slice::from_ref(*bin)).is_empty());
+            checks.push(match npm_install {
+                Some(bin) if !scan.contains_dir(bin) => OnboardingCheck::new(
+                    &id,
+                    &title,
+                    CheckStatus::Fail,
+                    format!(
+                        "{} is installed in {}, which is not on PATH",
+                        program,
+                        bin.display()
+                    ),
+                )
+                .with_fix(add_to_path_fix(bin)),
+                _ => OnboardingCheck::new(
+                    &id,
+                    &title,
+                    CheckStatus::Warn,
+                    format!("{} was not found on PATH", program),
+                )
+                .with_fix(choose_path_fix()),
+            });
+        }
+        [first, rest @ ..] => {
+            let runs_other = resolved
+                .map(Path::new)
+                .filter(|resolved| resolved.is_absolute())
+                .filter(|resolved| !scan.matches.iter().any(|m| m == resolved));
+            let check = if let Some(resolved) = runs_other {
+                OnboardingCheck::new(
+                    &id,
+                    &title,
+                    CheckStatus::Warn,
+                    format!(
+                        "The workbench runs {} but your terminal runs {}",
+                        resolved.display(),
+                        first.display()
+                    ),
+                )
+                .with_fix(choose_path_fix())
+            } else if rest.is_empty() {
+                OnboardingCheck::new(&id, &title, CheckStatus::Pass, first.display().to_string())
+            } else {
+                let shadowed: Vec<String> = rest.iter().map(|p| p.display().to_string()).collect();
+                OnboardingCheck::new(
+                    &id,
+                    &title,
+                    CheckStatus::Warn,
+                    format!(
+                        "{} installs found; {} shadows {}",
+                        scan.matches.len(),
+                        first.display(),
+                        shadowed.join(", ")
+                    ),
+                )
+                .with_fix(choose_path_fix())
+            };
+            checks.push(check);
+
+            if !scan.node_found && is_node_shim(first) {
+                checks.push(
+                    OnboardingCheck::new(
+                        &format!("{}-node", engine),
+                        "Node.js",
+                        CheckStatus::Fail,
+                        format!(
+                            "{} is an npm launcher but node was not found on PATH",
+                            first.display()
+                        ),
+                    )
+                    .with_fix(FixAction::OpenUrl {
+                        label: "Install Node.js".to_string(),
+                        url: "https://nodejs.org/".to_string(),
+                    }),
+                );
+            }
+        }
+    }
+
+    checks
+}
+
+/// Report a failed engine CLI spawn; emits [`CLI_MISSING_EVENT`] when the binary is gone
+pub(crate) fn report_spawn_error(
+    app: &AppHandle,
+    engine: &str,
+    cmd: &tokio::process::Command,
+    project_path: &str,
+    error: &std::io::Error,
+) {
+    if error.kind() != std::io::ErrorKind::NotFound {
+        return;
+    }
+
+    let program = cmd.as_std().get_program().to_string_lossy().to_string();
+    log::warn!(
+        "[EngineDoctor] {} CLI is missing: {} ({})",
+        engine,
+        program,
+        error
+    );
+    let _ = app.emit(
+        CLI_MISSING_EVENT,
+        CliMissingEvent {
+            engine: engine.to_string(),
+            program,
+            project_path: project_path.to_string(),
+            error: error.to_string(),
+        },
+    );
+}
+
+/// Diagnose an engine CLI: installation, version, credentials and PATH
+#[tauri::command]
+pub async fn engine_doctor(engine: String, app: AppHandle) -> Result<EngineDiagnosis, String> {
+    let provider = crate::engines::require(&engine)?;
+    let cli = provider
+        .cli()
+        .ok_or_else(|| format!("{} does not use a CLI", provider.display_name()))?;
+    let engine = provider.id();
+    log::info!("[EngineDoctor] Diagnosing {}", engine);
+
+    // The availability checks cache their result, the doctor always probes again
+    let (installed, version, detail, binary_path) = match engine {
+        "claude" => {
+            let status = super::claude::check_claude_version(app.clone()).await?;
+            let detail = status.output.lines().next().unwrap_or_default().to_string();
+            let path = crate::claude_binary::find_claude_binary(&app).ok();
+            (status.is_installed, status.version, detail, path)
+        }
+        "codex" => {
+            let status = super::codex::config::do_check_codex_availability().await;
+            let detail = status
+                .error
+                .unwrap_or_else(|| "Codex CLI is available".to_string());
+            (status.available, status.version, detail, None)
+        }
+        "gemini" => {
+            let status =
+                tokio::task::spawn_blocking(super::gemini::session::do_check_gemini_installed)
+                    .await
+                    .map_err(|e| format!("Gemini check task failed: {}", e))?;
+            let detail = status
+                .error
+                .or_else(|| status.path.clone())
+                .unwrap_or_else(|| "Gemini CLI is available".to_string());
+            (status.installed, status.version, detail, status.path)
+        }
+        _ => return Err(format!("Unsupported engine: {}", engine)),
+    };
+
+    let mut checks = vec![onboarding::engine_install_check(
+        engine,
+        &format!("{} CLI", provider.display_name()),
+        installed,
+        version.clone(),
+        detail,
+    )];
+
+    let via_wsl = binary_path
+        .as_deref()
+        .map_or(false, |path| path.starts_with("WSL:"))
+        || version
+            .as_deref()
+            .map_or(false, |version| version.starts_with("WSL:"));
+    let path_matches = if via_wsl {
+        checks.push(OnboardingCheck::new(
+            &format!("{}-path", engine),
+            &format!("{} on PATH", cli.program),
+            CheckStatus::Pass,
+            "Runs inside WSL; the Windows PATH is not used",
+        ));
+        Vec::new()
+    } else {
+        let resolved = binary_path.clone();
+        let (path_checks, matches) = tokio::task::spawn_blocking(move || {
+            let scan = PathScan::run(cli.program);
+            (
+                path_checks(engine, cli.program, resolved.as_deref(), &scan),
+                scan.matches,
+            )
+        })
+        .await
+        .map_err(|e| format!("PATH check task failed: {}", e))?;
+        checks.extend(path_checks);
+        matches
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect()
+    };
+
+    let login = onboarding::login_check(engine, &format!("{} login", provider.display_name()));
+    let logged_in = login.status == CheckStatus::Pass;
+    checks.push(login);
+
+    let healthy = installed && logged_in && checks.iter().all(|c| c.status != CheckStatus::Fail);
+    log::info!(
+        "[EngineDoctor] {}: installed={}, logged_in={}, healthy={}",
+        engine,
+        installed,
+        logged_in,
+        healthy
+    );
+
+    Ok(EngineDiagnosis {
+        engine: engine.to_string(),
+        binary_path,
+        version,
+        logged_in,
+        path_matches,
+        checks,
+        healthy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_path_checks() {
+        let npm_bin = tempfile::tempdir().unwrap();
+        let other = tempfile::tempdir().unwrap();
+        let name = executable_names("codex").remove(0);
+        for dir in [npm_bin.path(), other.path()] {
+            std::fs::write(dir.join(&name), "#!/usr/bin/env node\n").unwrap();
+        }
+
+        // Installed by npm, but npm's bin directory is not on PATH
+        let scan = PathScan {
+            dirs: vec![other.path().join("empty")],
+            matches: Vec::new(),
+            npm_bin: Some(npm_bin.path().to_path_buf()),
+            node_found: true,
+        };
+        let checks = path_checks("codex", "codex", None, &scan);
+        assert_eq!(checks[0].status, CheckStatus::Fail);
+        assert!(matches!(checks[0].fix, Some(FixAction::RunCommand { .. })));
+
+        // Two installs, the first shadows the second, and Node.js is missing
+        let dirs = vec![npm_bin.path().to_path_buf(), other.path().to_path_buf()];
+        let scan = PathScan {
+            matches: find_in_dirs("codex", &dirs),
+            dirs,
+            npm_bin: None,
+            node_found: false,
+        };
+        assert_eq!(scan.matches.len(), 2);
+        let checks = path_checks("codex", "codex", None, &scan);
+        assert_eq!(checks[0].status, CheckStatus::Warn);
+        assert!(checks[0].detail.contains("shadows"));
+        assert_eq!(checks[1].id, "codex-node");
+        assert_eq!(checks[1].status, CheckStatus::Fail);
+    }
+}
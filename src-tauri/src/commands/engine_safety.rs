@@ -0,0 +1,546 @@
+//! Per-project engine safety policy
+//!
+//! Exposes each engine's native safety switches — Claude permission modes,
+//! Codex `--sandbox` / approval policy, Gemini approval mode and yolo — as typed
+//! settings stored per project in `~/.anycode/engine-safety.json`. The spawn
+//! layer translates them into CLI arguments; a project policy takes precedence
+//! over the per-run mode chosen in the UI (Claude's plan toggle still applies,
+//! as it is always the more restrictive choice). The safety flags every engine
+//! run was actually started with are recorded per session for auditing.
+//!
+//! Engines driven by the workbench (Ollama, OpenRouter) have no CLI to enforce
+//! a policy, so their MCP tool calls are approved here. Calls are allowed
+//! unless the project sets a policy: `ask` sends each call to the UI on
+//! `<engine>-tool-approval` and waits for [`respond_tool_approval`], `deny-all`
+//! refuses them.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+use crate::commands::claude::normalize_path_for_comparison;
+use crate::commands::codex::CodexExecutionMode;
+use crate::commands::permission_config::{ClaudePermissionConfig, PermissionMode};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Session records kept for auditing (oldest are dropped first)
+const MAX_SESSION_RECORDS: usize = 1000;
+
+/// Tool calls not answered within this time are denied
+const APPROVAL_TIMEOUT: Duration = Duration::from_secs(600);
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+    /// Tool calls awaiting the user's answer, by request id
+    static ref PENDING_APPROVALS: Mutex<HashMap<String, oneshot::Sender<bool>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Claude CLI `--permission-mode` values (plus the skip-permissions switch)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ClaudePermissionPolicy {
+    Default,
+    AcceptEdits,
+    Plan,
+    /// `--dangerously-skip-permissions`
+    BypassPermissions,
+}
+
+/// Codex `--sandbox` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodexSandboxPolicy {
+    ReadOnly,
+    WorkspaceWrite,
+    DangerFullAccess,
+}
+
+impl CodexSandboxPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::ReadOnly => "read-only",
+            Self::WorkspaceWrite => "workspace-write",
+            Self::DangerFullAccess => "danger-full-access",
+        }
+    }
+}
+
+/// Codex `approval_policy` values
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CodexApprovalPolicy {
+    Untrusted,
+    OnFailure,
+    OnRequest,
+    Never,
+}
+
+impl CodexApprovalPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Untrusted => "untrusted",
+            Self::OnFailure => "on-failure",
+            Self::OnRequest => "on-request",
+            Self::Never => "never",
+        }
+    }
+}
+
+/// Gemini CLI `--approval-mode` values (`yolo` maps to `--yolo`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GeminiApprovalPolicy {
+    Default,
+    AutoEdit,
+    Yolo,
+}
+
+impl GeminiApprovalPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::AutoEdit => "auto_edit",
+            Self::Yolo => "yolo",
+        }
+    }
+}
+
+/// Approval of MCP tool calls made by workbench-driven chat sessions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChatToolApprovalPolicy {
+    /// Ask the user for each call; needs a UI answering `<engine>-tool-approval`
+    Ask,
+    /// Run every call (the default)
+    #[serde(alias = "allowAll")]
+    AllowAll,
+    #[serde(alias = "denyAll")]
+    DenyAll,
+}
+
+impl ChatToolApprovalPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ask => "ask",
+            Self::AllowAll => "allow-all",
+            Self::DenyAll => "deny-all",
+        }
+    }
+}
+
+/// Safety settings of one project; `None` keeps the engine's normal behaviour
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSafetySettings {
+    pub claude_permission_mode: Option<ClaudePermissionPolicy>,
+    pub codex_sandbox: Option<CodexSandboxPolicy>,
+    pub codex_approval: Option<CodexApprovalPolicy>,
+    pub gemini_approval_mode: Option<GeminiApprovalPolicy>,
+    /// `None` allows every call
+    pub chat_tool_approval: Option<ChatToolApprovalPolicy>,
+}
+
+/// Safety flags an engine run was started with
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSafetyRecord {
+    pub session_id: String,
+    pub engine: String,
+    pub project_path: String,
+    pub flags: Vec<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SafetyStore {
+    /// Normalized project path → settings
+    #[serde(default)]
+    projects: BTreeMap<String, ProjectSafetySettings>,
+    #[serde(default)]
+    sessions: Vec<SessionSafetyRecord>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("engine-safety.json"))
+}
+
+fn load_store() -> SafetyStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(f: impl FnOnce(&mut SafetyStore) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+/// Key under which per-project settings are stored
+pub(crate) fn project_key(project_path: &str) -> String {
+    normalize_path_for_comparison(project_path)
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// Safety settings configured for a project
+pub fn project_settings(project_path: &str) -> ProjectSafetySettings {
+    load_store()
+        .projects
+        .remove(&project_key(project_path))
+        .unwrap_or_default()
+}
+
+/// Apply the project's Claude permission policy on top of the execution config
+pub fn apply_claude_policy(project_path: &str, permissions: &mut ClaudePermissionConfig) {
+    let Some(policy) = project_settings(project_path).claude_permission_mode else {
+        return;
+    };
+    log::info!("[Safety] Project Claude permission policy: {:?}", policy);
+    match policy {
+        ClaudePermissionPolicy::BypassPermissions => permissions.enable_dangerous_skip = true,
+        ClaudePermissionPolicy::Plan => *permissions = ClaudePermissionConfig::plan_mode(),
+        ClaudePermissionPolicy::Default | ClaudePermissionPolicy::AcceptEdits => {
+            permissions.enable_dangerous_skip = false;
+            permissions.permission_mode = if policy == ClaudePermissionPolicy::AcceptEdits {
+                PermissionMode::AcceptEdits
+            } else {
+                PermissionMode::Interactive
+            };
+        }
+    }
+}
+
+/// Codex safety arguments: the project policy, falling back to the per-run mode
+pub fn codex_args(project_path: &str, mode: &CodexExecutionMode) -> Vec<String> {
+    let settings = project_settings(project_path);
+    let mut args = Vec::new();
+
+    match settings.codex_sandbox {
+        Some(sandbox) => {
+            args.push("--sandbox".to_string());
+            args.push(sandbox.as_str().to_string());
+        }
+        None => match mode {
+            CodexExecutionMode::FullAuto => args.push("--full-auto".to_string()),
+            CodexExecutionMode::DangerFullAccess => {
+                args.push("--sandbox".to_string());
+                args.push("danger-full-access".to_string());
+            }
+            // Read-only is default
+            CodexExecutionMode::ReadOnly => {}
+        },
+    }
+
+    // `codex exec` has no approval flag; override the config.toml key instead
+    if let Some(approval) = settings.codex_approval {
+        args.push("-c".to_string());
+        args.push(format!("approval_policy={}", approval.as_str()));
+    }
+    args
+}
+
+/// Gemini approval mode configured for the project
+pub fn gemini_approval_mode(project_path: &str) -> Option<String> {
+    project_settings(project_path)
+        .gemini_approval_mode
+        .map(|mode| mode.as_str().to_string())
+}
+
+/// Tool-call approval policy of the project for workbench-driven sessions
+pub fn chat_tool_approval(project_path: &str) -> ChatToolApprovalPolicy {
+    project_settings(project_path)
+        .chat_tool_approval
+        .unwrap_or(ChatToolApprovalPolicy::AllowAll)
+}
+
+/// Tool call of a chat session waiting for the user's approval
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolApprovalRequest {
+    pub request_id: String,
+    pub session_id: String,
+    pub tool_use_id: String,
+    pub tool: String,
+    pub arguments: Value,
+}
+
+/// Removes the pending entry if the waiting session is cancelled
+struct PendingApproval(String);
+
+impl Drop for PendingApproval {
+    fn drop(&mut self) {
+        if let Ok(mut pending) = PENDING_APPROVALS.lock() {
+            pending.remove(&self.0);
+        }
+    }
+}
+
+/// Decide whether a chat session may run a tool call
+///
+/// Under the `ask` policy the call is emitted on `<engine>-tool-approval` (and
+/// `<engine>-tool-approval:<session_id>`) and denied when no answer arrives in
+/// time.
+pub async fn approve_tool_call(
+    app: &AppHandle,
+    engine: &str,
+    policy: ChatToolApprovalPolicy,
+    request: ToolApprovalRequest,
+) -> bool {
+    match policy {
+        ChatToolApprovalPolicy::AllowAll => return true,
+        ChatToolApprovalPolicy::DenyAll => return false,
+        ChatToolApprovalPolicy::Ask => {}
+    }
+
+    let (tx, rx) = oneshot::channel();
+    match PENDING_APPROVALS.lock() {
+        Ok(mut pending) => {
+            pending.insert(request.request_id.clone(), tx);
+        }
+        Err(_) => return false,
+    }
+    let _pending = PendingApproval(request.request_id.clone());
+
+    let channel = format!("{}-tool-approval", engine);
+    let _ = app.emit(&format!("{}:{}", channel, request.session_id), &request);
+    let _ = app.emit(&channel, &request);
+
+    match tokio::time::timeout(APPROVAL_TIMEOUT, rx).await {
+        Ok(Ok(approved)) => approved,
+        Ok(Err(_)) => false,
+        Err(_) => {
+            log::warn!(
+                "[Safety] Tool call {} was not answered in time, denying",
+                request.tool
+            );
+            false
+        }
+    }
+}
+
+/// Pick the safety-related flags (with their values) out of an argument list
+pub fn extract_safety_flags(args: &[String]) -> Vec<String> {
+    const SWITCHES: &[&str] = &[
+        "--dangerously-skip-permissions",
+        "--dangerously-bypass-approvals-and-sandbox",
+        "--full-auto",
+        "--yolo",
+    ];
+    const WITH_VALUE: &[&str] = &["--permission-mode", "--sandbox", "--approval-mode"];
+
+    let mut flags = Vec::new();
+    let mut iter = args.iter().peekable();
+    while let Some(arg) = iter.next() {
+        if SWITCHES.contains(&arg.as_str()) {
+            flags.push(arg.clone());
+        } else if WITH_VALUE.contains(&arg.as_str()) {
+            flags.push(arg.clone());
+            flags.extend(iter.next().cloned());
+        } else if arg == "-c"
+            && iter
+                .peek()
+                .is_some_and(|value| value.starts_with("approval_policy="))
+        {
+            flags.push(arg.clone());
+            flags.extend(iter.next().cloned());
+        }
+    }
+    flags
+}
+
+/// Safety flags of a prepared engine command
+pub fn command_safety_flags(cmd: &Command) -> Vec<String> {
+    let args: Vec<String> = cmd
+        .as_std()
+        .get_args()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    extract_safety_flags(&args)
+}
+
+/// Record the safety flags an engine session was started with
+pub fn record_session(engine: &str, session_id: &str, project_path: &str, flags: Vec<String>) {
+    let result = update_store(|store| {
+        store.sessions.push(SessionSafetyRecord {
+            session_id: session_id.to_string(),
+            engine: engine.to_string(),
+            project_path: project_path.to_string(),
+            flags,
+            recorded_at: Utc::now(),
+        });
+        let excess = store.sessions.len().saturating_sub(MAX_SESSION_RECORDS);
+        store.sessions.drain(..excess);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "[Safety] Failed to record safety flags for session {}: {}",
+            session_id,
+            e
+        );
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the safety settings of a project
+#[tauri::command]
+pub async fn get_project_safety_settings(
+    project_path: String,
+) -> Result<ProjectSafetySettings, String> {
+    Ok(project_settings(&project_path))
+}
+
+/// Update the safety settings of a project (all `None` removes the project entry)
+#[tauri::command]
+pub async fn update_project_safety_settings(
+    project_path: String,
+    settings: ProjectSafetySettings,
+) -> Result<(), String> {
+    log::info!(
+        "[Safety] Updating safety settings for {}: {:?}",
+        project_path,
+        settings
+    );
+    update_store(|store| {
+        let key = project_key(&project_path);
+        if settings == ProjectSafetySettings::default() {
+            store.projects.remove(&key);
+        } else {
+            store.projects.insert(key, settings);
+        }
+        Ok(())
+    })
+}
+
+/// Answer a pending tool-call approval of a chat session
+#[tauri::command]
+pub async fn respond_tool_approval(request_id: String, approved: bool) -> Result<(), String> {
+    let sender = PENDING_APPROVALS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&request_id)
+        .ok_or_else(|| format!("No pending tool approval: {}", request_id))?;
+    log::info!(
+        "[Safety] Tool approval {}: {}",
+        request_id,
+        if approved { "approved" } else { "denied" }
+    );
+    let _ = sender.send(approved);
+    Ok(())
+}
+
+/// Get the recorded safety flags of a session (one entry per run)
+#[tauri::command]
+pub async fn get_session_safety_records(
+    session_id: String,
+) -> Result<Vec<SessionSafetyRecord>, String> {
+    Ok(load_store()
+        .sessions
+        .into_iter()
+        .filter(|record| record.session_id == session_id)
+        .collect())
+}
+
+/// List recorded safety flags, newest first, optionally for one project
+#[tauri::command]
+pub async fn list_session_safety_records(
+    project_path: Option<String>,
+) -> Result<Vec<SessionSafetyRecord>, String> {
+    let project = project_path.as_deref().map(project_key);
+    Ok(load_store()
+        .sessions
+        .into_iter()
+        .rev()
+        .filter(|record| {
+            project
+                .as_ref()
+                .map_or(true, |key| &project_key(&record.project_path) == key)
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_extract_safety_flags() {
+        let claude = args(&[
+            "--resume",
+            "abc",
+            "--output-format",
+            "stream-json",
+            "--permission-mode",
+            "acceptEdits",
+        ]);
+        assert_eq!(
+            extract_safety_flags(&claude),
+            args(&["--permission-mode", "acceptEdits"])
+        );
+
+        let codex = args(&[
+            "exec",
+            "--json",
+            "--sandbox",
+            "workspace-write",
+            "-c",
+            "approval_policy=never",
+            "-c",
+            "model=\"gpt-5\"",
+            "-",
+        ]);
+        assert_eq!(
+            extract_safety_flags(&codex),
+            args(&[
+                "--sandbox",
+                "workspace-write",
+                "-c",
+                "approval_policy=never"
+            ])
+        );
+
+        let gemini = args(&["--output-format", "stream-json", "--yolo"]);
+        assert_eq!(extract_safety_flags(&gemini), args(&["--yolo"]));
+    }
+
+    #[test]
+    fn test_policy_serialization() {
+        let settings = ProjectSafetySettings {
+            claude_permission_mode: Some(ClaudePermissionPolicy::AcceptEdits),
+            codex_sandbox: Some(CodexSandboxPolicy::WorkspaceWrite),
+            codex_approval: Some(CodexApprovalPolicy::OnRequest),
+            gemini_approval_mode: Some(GeminiApprovalPolicy::AutoEdit),
+            chat_tool_approval: Some(ChatToolApprovalPolicy::AllowAll),
+        };
+        let json = serde_json::to_value(&settings).unwrap();
+        assert_eq!(json["claudePermissionMode"], "acceptEdits");
+        assert_eq!(json["codexSandbox"], "workspace-write");
+        assert_eq!(json["codexApproval"], "on-request");
+        assert_eq!(json["geminiApprovalMode"], "auto_edit");
+        assert_eq!(json["chatToolApproval"], "allow-all");
+        assert_eq!(
+            json["chatToolApproval"],
+            ChatToolApprovalPolicy::AllowAll.as_str()
+        );
+
+        let legacy: ChatToolApprovalPolicy = serde_json::from_str("\"denyAll\"").unwrap();
+        assert_eq!(legacy, ChatToolApprovalPolicy::DenyAll);
+    }
+}
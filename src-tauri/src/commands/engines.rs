@@ -0,0 +1,15 @@
+//! Registered engines
+//!
+//! Lets the frontend list the engines known to the backend (see `crate::engines`)
+//! instead of hard-coding them.
+
+use crate::engines::{self, EngineInfo};
+
+/// List the registered engines in display order
+#[tauri::command]
+pub async fn list_engines() -> Result<Vec<EngineInfo>, String> {
+    Ok(engines::all()
+        .iter()
+        .map(|engine| EngineInfo::of(*engine))
+        .collect())
+}
@@ -0,0 +1,241 @@
+//! Coalescing layer for high-frequency events
+//!
+//! Engine output streams can emit hundreds of events per second, one per line.
+//! When batching is enabled for an event type, events sent through [`emit`]
+//! are queued per channel and flushed once per frame (16–32 ms by default) as
+//! one event carrying an ordered array of payloads. The batched event name
+//! inserts `-batch` after the event type, so `claude-output:<session>`
+//! becomes `claude-output-batch:<session>` and existing single-event
+//! listeners are unaffected while batching is off.
+//!
+//! Batching is configured per event type in `~/.anycode/event-batching.json`.
+//! Typed session events (see `session_runner`) are batched unless turned off
+//! there; the other event types are sent one by one unless turned on.
+//! Call [`flush_all`] before emitting a terminal event (e.g. `*-complete`) so
+//! it cannot overtake queued output.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+const DEFAULT_FRAME_MS: u64 = 16;
+const MIN_FRAME_MS: u64 = 4;
+const MAX_FRAME_MS: u64 = 250;
+
+/// Event types routed through the batcher
+const BATCHABLE_EVENT_TYPES: &[&str] = &[
+    "claude-output",
+    "claude-error",
+    "codex-output",
+    "gemini-output",
+    "gemini-error",
+    "ollama-output",
+    "openrouter-output",
+    "remote-output",
+    "session-events",
+];
+
+/// Batching rule of an event type without a saved one
+fn default_rule(event_type: &str) -> BatchRule {
+    BatchRule {
+        enabled: event_type == "session-events",
+        ..BatchRule::default()
+    }
+}
+
+/// Batching rule for one event type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchRule {
+    pub enabled: bool,
+    /// Frame length in milliseconds
+    pub frame_ms: u64,
+}
+
+impl Default for BatchRule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frame_ms: DEFAULT_FRAME_MS,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventBatchingConfig {
+    /// Rules keyed by event type (the channel name before `:`)
+    pub rules: BTreeMap<String, BatchRule>,
+}
+
+impl Default for EventBatchingConfig {
+    fn default() -> Self {
+        Self {
+            rules: BATCHABLE_EVENT_TYPES
+                .iter()
+                .map(|t| (t.to_string(), default_rule(t)))
+                .collect(),
+        }
+    }
+}
+
+static CONFIG: Lazy<RwLock<EventBatchingConfig>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+/// Pending payloads per channel, in emit order
+static PENDING: Lazy<Mutex<HashMap<String, Vec<Value>>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("event-batching.json"))
+}
+
+/// Re-read the config after the file was edited outside the app
+pub(crate) fn reload_config() {
+    if let Ok(mut config) = CONFIG.write() {
+        *config = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+/// `claude-output:abc` -> (`claude-output`, `:abc`)
+fn split_channel(channel: &str) -> (&str, &str) {
+    match channel.find(':') {
+        Some(i) => channel.split_at(i),
+        None => (channel, ""),
+    }
+}
+
+/// Name of the event carrying a batch for `channel`
+pub fn batch_channel(channel: &str) -> String {
+    let (event_type, rest) = split_channel(channel);
+    format!("{}-batch{}", event_type, rest)
+}
+
+fn configured_rule(channel: &str) -> Option<BatchRule> {
+    let (event_type, _) = split_channel(channel);
+    let saved = CONFIG.read().ok()?.rules.get(event_type).copied();
+    saved.or_else(|| {
+        BATCHABLE_EVENT_TYPES
+            .contains(&event_type)
+            .then(|| default_rule(event_type))
+    })
+}
+
+fn rule_for(channel: &str) -> Option<BatchRule> {
+    configured_rule(channel).filter(|rule| rule.enabled)
+}
+
+/// Frame length configured for `channel`, whether or not its batching is on
+pub fn frame_length(channel: &str) -> Duration {
+    let frame_ms = configured_rule(channel).map_or(DEFAULT_FRAME_MS, |rule| rule.frame_ms);
+    Duration::from_millis(frame_ms)
+}
+
+fn flush_channel(app: &AppHandle, channel: &str) {
+    let batch = match PENDING.lock() {
+        Ok(mut pending) => pending.remove(channel),
+        Err(_) => None,
+    };
+    if let Some(batch) = batch.filter(|b| !b.is_empty()) {
+        if let Err(e) = app.emit(&batch_channel(channel), &batch) {
+            log::warn!("[EventBatcher] Failed to emit batch for {}: {}", channel, e);
+        }
+    }
+}
+
+/// Emit an event, queuing it into the current frame when its type is batched
+pub fn emit<S: Serialize + Clone>(app: &AppHandle, channel: &str, payload: S) -> tauri::Result<()> {
+    let Some(rule) = rule_for(channel) else {
+        return app.emit(channel, payload);
+    };
+
+    let value = serde_json::to_value(payload)?;
+    let starts_frame = match PENDING.lock() {
+        Ok(mut pending) => {
+            let queue = pending.entry(channel.to_string()).or_default();
+            queue.push(value);
+            queue.len() == 1
+        }
+        Err(_) => return Ok(()),
+    };
+
+    // The first event of a frame schedules the flush
+    if starts_frame {
+        let app = app.clone();
+        let channel = channel.to_string();
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(rule.frame_ms)).await;
+            flush_channel(&app, &channel);
+        });
+    }
+    Ok(())
+}
+
+/// Flush every pending batch now
+pub fn flush_all(app: &AppHandle) {
+    let channels: Vec<String> = match PENDING.lock() {
+        Ok(pending) => pending.keys().cloned().collect(),
+        Err(_) => return,
+    };
+    for channel in channels {
+        flush_channel(app, &channel);
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_event_batching_config() -> Result<EventBatchingConfig, String> {
+    CONFIG
+        .read()
+        .map(|config| config.clone())
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn update_event_batching_config(config: EventBatchingConfig) -> Result<(), String> {
+    for (event_type, rule) in &config.rules {
+        if !(MIN_FRAME_MS..=MAX_FRAME_MS).contains(&rule.frame_ms) {
+            return Err(format!(
+                "Frame length for {} must be between {} and {} ms",
+                event_type, MIN_FRAME_MS, MAX_FRAME_MS
+            ));
+        }
+    }
+    save_json_config(&config, config_path()?)?;
+    *CONFIG.write().map_err(|e| e.to_string())? = config;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_channel() {
+        assert_eq!(
+            batch_channel("claude-output:abc"),
+            "claude-output-batch:abc"
+        );
+        assert_eq!(batch_channel("codex-output"), "codex-output-batch");
+        assert_eq!(
+            batch_channel("remote-output:run:1"),
+            "remote-output-batch:run:1"
+        );
+    }
+
+    #[test]
+    fn test_default_rules() {
+        assert!(default_rule("session-events").enabled);
+        assert!(!default_rule("claude-output").enabled);
+        assert_eq!(default_rule("session-events").frame_ms, DEFAULT_FRAME_MS);
+    }
+}
@@ -0,0 +1,291 @@
+//! Exclusion policy for binary and generated files
+//!
+//! One list of glob patterns (lockfiles, minified bundles, build outputs,
+//! media) is consulted by file search, the file picker and context assembly,
+//! so engines are not handed `package-lock.json` or a `dist/` bundle. A
+//! project can add its own patterns and re-include paths the global list
+//! would drop. Settings live in `~/.anycode/exclusion-policy.json`.
+//!
+//! A pattern without `/` matches any path component (`dist` excludes every
+//! `dist` directory, `*.min.js` any minified file); a pattern with `/` is
+//! matched against the whole project-relative path.
+
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::engine_safety::project_key;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Built-in patterns, used until the global list is edited
+pub const DEFAULT_EXCLUSIONS: &[&str] = &[
+    // Lockfiles
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "Cargo.lock",
+    "poetry.lock",
+    "Pipfile.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+    // Minified bundles and source maps
+    "*.min.js",
+    "*.min.css",
+    "*.map",
+    // Build outputs and caches
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "out",
+    ".next",
+    ".nuxt",
+    "coverage",
+    "__pycache__",
+    "*.pyc",
+    // Media, archives and binaries
+    "*.png",
+    "*.jpg",
+    "*.jpeg",
+    "*.gif",
+    "*.webp",
+    "*.ico",
+    "*.mp3",
+    "*.mp4",
+    "*.mov",
+    "*.wav",
+    "*.pdf",
+    "*.zip",
+    "*.tar",
+    "*.gz",
+    "*.woff",
+    "*.woff2",
+    "*.ttf",
+    "*.exe",
+    "*.dll",
+    "*.so",
+    "*.dylib",
+];
+
+/// Per-project additions and exceptions
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectExclusions {
+    /// Excluded on top of the global list
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Kept even when an exclusion pattern matches
+    #[serde(default)]
+    pub include: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExclusionStore {
+    #[serde(default = "default_global")]
+    global: Vec<String>,
+    #[serde(default)]
+    projects: BTreeMap<String, ProjectExclusions>,
+}
+
+impl Default for ExclusionStore {
+    fn default() -> Self {
+        Self {
+            global: default_global(),
+            projects: BTreeMap::new(),
+        }
+    }
+}
+
+fn default_global() -> Vec<String> {
+    DEFAULT_EXCLUSIONS.iter().map(|p| p.to_string()).collect()
+}
+
+/// Settings that apply to a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExclusionSettings {
+    pub global: Vec<String>,
+    pub project: ProjectExclusions,
+}
+
+/// Compiled patterns for one project
+#[derive(Debug, Clone, Default)]
+pub struct ExclusionPolicy {
+    exclude: Vec<Pattern>,
+    include: Vec<Pattern>,
+}
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: false,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+fn matches_any(patterns: &[Pattern], relative: &str) -> bool {
+    patterns.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches_with(relative, MATCH_OPTIONS)
+        } else {
+            relative
+                .split('/')
+                .any(|part| pattern.matches_with(part, MATCH_OPTIONS))
+        }
+    })
+}
+
+impl ExclusionPolicy {
+    pub fn new(exclude: &[String], include: &[String]) -> Self {
+        let compile = |patterns: &[String]| -> Vec<Pattern> {
+            patterns
+                .iter()
+                .filter_map(|p| Pattern::new(p.trim().trim_end_matches('/')).ok())
+                .collect()
+        };
+        Self {
+            exclude: compile(exclude),
+            include: compile(include),
+        }
+    }
+
+    /// Whether a project-relative path (either separator) is excluded
+    pub fn is_excluded(&self, relative_path: &str) -> bool {
+        let relative = relative_path.replace('\\', "/");
+        let relative = relative.trim_start_matches("./").trim_matches('/');
+        if relative.is_empty() {
+            return false;
+        }
+        matches_any(&self.exclude, relative) && !matches_any(&self.include, relative)
+    }
+
+    /// Whether a path under `project_path` is excluded; paths outside the project never are
+    pub fn is_path_excluded(&self, project_path: &Path, path: &Path) -> bool {
+        match path.strip_prefix(project_path) {
+            Ok(relative) => self.is_excluded(&relative.to_string_lossy()),
+            Err(_) => false,
+        }
+    }
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("exclusion-policy.json"))
+}
+
+fn load_store() -> ExclusionStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn save_store(store: &ExclusionStore) -> Result<(), String> {
+    save_json_config(store, store_path()?)
+}
+
+/// The compiled policy for a project
+pub fn policy_for_project(project_path: &str) -> ExclusionPolicy {
+    let mut store = load_store();
+    let project = store
+        .projects
+        .remove(&project_key(project_path))
+        .unwrap_or_default();
+    let mut exclude = store.global;
+    exclude.extend(project.exclude);
+    ExclusionPolicy::new(&exclude, &project.include)
+}
+
+fn validate_patterns(patterns: &[String]) -> Result<Vec<String>, String> {
+    patterns
+        .iter()
+        .map(|p| p.trim())
+        .filter(|p| !p.is_empty())
+        .map(|p| {
+            Pattern::new(p.trim_end_matches('/'))
+                .map(|_| p.to_string())
+                .map_err(|e| format!("Invalid pattern '{}': {}", p, e))
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_exclusion_settings(project_path: String) -> Result<ExclusionSettings, String> {
+    let mut store = load_store();
+    Ok(ExclusionSettings {
+        project: store
+            .projects
+            .remove(&project_key(&project_path))
+            .unwrap_or_default(),
+        global: store.global,
+    })
+}
+
+/// Replace the global list; `None` restores the built-in defaults
+#[tauri::command]
+pub async fn update_global_exclusions(patterns: Option<Vec<String>>) -> Result<(), String> {
+    let global = match patterns {
+        Some(patterns) => validate_patterns(&patterns)?,
+        None => default_global(),
+    };
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    store.global = global;
+    save_store(&store)
+}
+
+#[tauri::command]
+pub async fn update_project_exclusions(
+    project_path: String,
+    exclusions: ProjectExclusions,
+) -> Result<(), String> {
+    let exclusions = ProjectExclusions {
+        exclude: validate_patterns(&exclusions.exclude)?,
+        include: validate_patterns(&exclusions.include)?,
+    };
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let key = project_key(&project_path);
+    if exclusions.exclude.is_empty() && exclusions.include.is_empty() {
+        store.projects.remove(&key);
+    } else {
+        store.projects.insert(key, exclusions);
+    }
+    save_store(&store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_policy() {
+        let policy = ExclusionPolicy::new(&default_global(), &[]);
+        assert!(policy.is_excluded("package-lock.json"));
+        assert!(policy.is_excluded("web/dist/app.js"));
+        assert!(policy.is_excluded("static\\vendor.min.js"));
+        assert!(policy.is_excluded("assets/Logo.PNG"));
+        assert!(!policy.is_excluded("src/main.rs"));
+        assert!(!policy.is_excluded("docs/distribution.md"));
+    }
+
+    #[test]
+    fn test_project_overrides() {
+        let policy = ExclusionPolicy::new(
+            &["dist".to_string(), "fixtures/*.json".to_string()],
+            &["dist/types.d.ts".to_string()],
+        );
+        assert!(policy.is_excluded("dist/index.js"));
+        assert!(!policy.is_excluded("dist/types.d.ts"));
+        assert!(policy.is_excluded("fixtures/users.json"));
+        assert!(!policy.is_excluded("src/fixtures/users.json"));
+        assert!(validate_patterns(&["[".to_string()]).is_err());
+    }
+}
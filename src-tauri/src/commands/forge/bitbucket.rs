@@ -0,0 +1,168 @@
+//! Bitbucket Cloud implementation of [`GitForge`]
+//!
+//! Tokens are either repository/workspace access tokens (sent as bearer
+//! tokens) or `username:app_password` pairs (sent with basic auth).
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::{
+    http_client, send_json, ForgeKind, GitForge, PullRequestInfo, PullRequestRequest, RepoRef,
+};
+use crate::commands::task_context::{AttachmentComment, TaskAttachment};
+
+const API_BASE: &str = "https://api.bitbucket.org/2.0";
+
+/// Comments fetched per issue (a single API page)
+const COMMENTS_PER_PAGE: usize = 100;
+
+pub struct BitbucketForge {
+    token: Option<String>,
+}
+
+impl BitbucketForge {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn repo_url(repo: &RepoRef) -> String {
+        format!("{}/repositories/{}/{}", API_BASE, repo.owner, repo.name)
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => match token.split_once(':') {
+                Some((username, app_password)) => builder.basic_auth(username, Some(app_password)),
+                None => builder.bearer_auth(token),
+            },
+            None => builder,
+        }
+    }
+}
+
+fn issue_to_attachment(
+    repo: &RepoRef,
+    number: u64,
+    issue: &Value,
+    comments: &Value,
+) -> TaskAttachment {
+    let reference = format!("{}#{}", repo.path(), number);
+    let comments = comments["values"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        // Comments without content are state changes
+        .filter(|c| c["content"]["raw"].as_str().is_some_and(|b| !b.is_empty()))
+        .map(|c| AttachmentComment {
+            author: c["user"]["display_name"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            body: c["content"]["raw"].as_str().unwrap_or_default().to_string(),
+            created_at: c["created_on"].as_str().map(String::from),
+        })
+        .collect();
+
+    let mut fields = BTreeMap::new();
+    for (label, key) in [
+        ("State", "state"),
+        ("Kind", "kind"),
+        ("Priority", "priority"),
+    ] {
+        if let Some(value) = issue[key].as_str() {
+            fields.insert(label.to_string(), value.to_string());
+        }
+    }
+    if let Some(reporter) = issue["reporter"]["display_name"].as_str() {
+        fields.insert("Author".to_string(), reporter.to_string());
+    }
+
+    TaskAttachment {
+        id: TaskAttachment::make_id("bitbucket", &reference),
+        source: "bitbucket".to_string(),
+        reference,
+        url: issue["links"]["html"]["href"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        title: issue["title"].as_str().unwrap_or_default().to_string(),
+        body: issue["content"]["raw"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        labels: issue["component"]["name"]
+            .as_str()
+            .map(|c| vec![c.to_string()])
+            .unwrap_or_default(),
+        comments,
+        fields,
+        acceptance_criteria: Vec::new(),
+        project_path: None,
+        linked_pr: None,
+        fetched_at: Utc::now(),
+    }
+}
+
+#[async_trait::async_trait]
+impl GitForge for BitbucketForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Bitbucket
+    }
+
+    async fn fetch_issue(&self, repo: &RepoRef, number: u64) -> Result<TaskAttachment, String> {
+        let client = http_client()?;
+        let issue_url = format!("{}/issues/{}", Self::repo_url(repo), number);
+        let issue = send_json(self.kind(), self.request(client.get(&issue_url))).await?;
+        let comments_url = format!("{}/comments?pagelen={}", issue_url, COMMENTS_PER_PAGE);
+        let comments = send_json(self.kind(), self.request(client.get(&comments_url))).await?;
+
+        Ok(issue_to_attachment(repo, number, &issue, &comments))
+    }
+
+    async fn create_pull_request(
+        &self,
+        repo: &RepoRef,
+        request: &PullRequestRequest,
+        source_branch: &str,
+    ) -> Result<PullRequestInfo, String> {
+        let url = format!("{}/pullrequests", Self::repo_url(repo));
+        let body = json!({
+            "title": request.title,
+            "description": request.body,
+            "source": { "branch": { "name": source_branch } },
+            "destination": { "branch": { "name": request.target_branch } },
+            "draft": request.draft,
+        });
+        let created = send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url)).json(&body),
+        )
+        .await?;
+
+        Ok(PullRequestInfo {
+            forge: self.kind(),
+            number: created["id"].as_u64().unwrap_or_default(),
+            url: created["links"]["html"]["href"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string(),
+        })
+    }
+
+    async fn comment_on_issue(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<(), String> {
+        let url = format!("{}/issues/{}/comments", Self::repo_url(repo), number);
+        send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url))
+                .json(&json!({ "content": { "raw": body } })),
+        )
+        .await
+        .map(|_| ())
+    }
+}
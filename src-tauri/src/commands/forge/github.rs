@@ -0,0 +1,172 @@
+//! GitHub implementation of [`GitForge`]
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::{
+    http_client, send_json, ForgeKind, GitForge, PullRequestInfo, PullRequestRequest, RepoRef,
+};
+use crate::commands::task_context::{AttachmentComment, TaskAttachment};
+
+/// Comments fetched per issue (a single API page)
+const COMMENTS_PER_PAGE: usize = 100;
+
+pub struct GithubForge {
+    token: Option<String>,
+}
+
+impl GithubForge {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    /// github.com uses api.github.com, GitHub Enterprise serves the API under /api/v3
+    fn api_base(repo: &RepoRef) -> String {
+        if repo.host.eq_ignore_ascii_case("github.com") {
+            "https://api.github.com".to_string()
+        } else {
+            format!("https://{}/api/v3", repo.host)
+        }
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        let builder = builder
+            .header("Accept", "application/vnd.github+json")
+            .header("X-GitHub-Api-Version", "2022-11-28");
+        match &self.token {
+            Some(token) => builder.bearer_auth(token),
+            None => builder,
+        }
+    }
+}
+
+fn issue_to_attachment(
+    repo: &RepoRef,
+    number: u64,
+    issue: &Value,
+    comments: &Value,
+) -> TaskAttachment {
+    let reference = format!("{}#{}", repo.path(), number);
+    let labels = issue["labels"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| l["name"].as_str().map(String::from))
+        .collect();
+    let comments = comments
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| AttachmentComment {
+            author: c["user"]["login"].as_str().unwrap_or("unknown").to_string(),
+            body: c["body"].as_str().unwrap_or_default().to_string(),
+            created_at: c["created_at"].as_str().map(String::from),
+        })
+        .collect();
+
+    let mut fields = BTreeMap::new();
+    if let Some(state) = issue["state"].as_str() {
+        fields.insert("State".to_string(), state.to_string());
+    }
+    if let Some(author) = issue["user"]["login"].as_str() {
+        fields.insert("Author".to_string(), author.to_string());
+    }
+
+    TaskAttachment {
+        id: TaskAttachment::make_id("github", &reference),
+        source: "github".to_string(),
+        reference,
+        url: issue["html_url"].as_str().unwrap_or_default().to_string(),
+        title: issue["title"].as_str().unwrap_or_default().to_string(),
+        body: issue["body"].as_str().unwrap_or_default().to_string(),
+        labels,
+        comments,
+        fields,
+        acceptance_criteria: Vec::new(),
+        project_path: None,
+        linked_pr: None,
+        fetched_at: Utc::now(),
+    }
+}
+
+#[async_trait::async_trait]
+impl GitForge for GithubForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Github
+    }
+
+    async fn fetch_issue(&self, repo: &RepoRef, number: u64) -> Result<TaskAttachment, String> {
+        let client = http_client()?;
+        let issue_url = format!(
+            "{}/repos/{}/{}/issues/{}",
+            Self::api_base(repo),
+            repo.owner,
+            repo.name,
+            number
+        );
+        let issue = send_json(self.kind(), self.request(client.get(&issue_url))).await?;
+        let comments = if issue["comments"].as_u64().unwrap_or(0) > 0 {
+            let url = format!("{}/comments?per_page={}", issue_url, COMMENTS_PER_PAGE);
+            send_json(self.kind(), self.request(client.get(&url))).await?
+        } else {
+            Value::Array(Vec::new())
+        };
+
+        Ok(issue_to_attachment(repo, number, &issue, &comments))
+    }
+
+    async fn create_pull_request(
+        &self,
+        repo: &RepoRef,
+        request: &PullRequestRequest,
+        source_branch: &str,
+    ) -> Result<PullRequestInfo, String> {
+        let url = format!(
+            "{}/repos/{}/{}/pulls",
+            Self::api_base(repo),
+            repo.owner,
+            repo.name
+        );
+        let body = json!({
+            "title": request.title,
+            "body": request.body,
+            "head": source_branch,
+            "base": request.target_branch,
+            "draft": request.draft,
+        });
+        let created = send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url)).json(&body),
+        )
+        .await?;
+
+        Ok(PullRequestInfo {
+            forge: self.kind(),
+            number: created["number"].as_u64().unwrap_or_default(),
+            url: created["html_url"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    async fn comment_on_issue(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<(), String> {
+        let url = format!(
+            "{}/repos/{}/{}/issues/{}/comments",
+            Self::api_base(repo),
+            repo.owner,
+            repo.name,
+            number
+        );
+        send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url))
+                .json(&json!({ "body": body })),
+        )
+        .await
+        .map(|_| ())
+    }
+}
@@ -0,0 +1,169 @@
+//! GitLab implementation of [`GitForge`]
+//!
+//! Issues are addressed by their project-scoped IID, projects by their
+//! URL-encoded full path. Pull requests are GitLab merge requests.
+
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+
+use super::{
+    http_client, send_json, ForgeKind, GitForge, PullRequestInfo, PullRequestRequest, RepoRef,
+};
+use crate::commands::task_context::{AttachmentComment, TaskAttachment};
+
+/// Notes fetched per issue (a single API page)
+const NOTES_PER_PAGE: usize = 100;
+
+pub struct GitlabForge {
+    token: Option<String>,
+}
+
+impl GitlabForge {
+    pub fn new(token: Option<String>) -> Self {
+        Self { token }
+    }
+
+    fn project_url(repo: &RepoRef) -> String {
+        format!(
+            "https://{}/api/v4/projects/{}",
+            repo.host,
+            urlencoding::encode(&repo.path())
+        )
+    }
+
+    fn request(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match &self.token {
+            Some(token) => builder.header("PRIVATE-TOKEN", token),
+            None => builder,
+        }
+    }
+}
+
+fn issue_to_attachment(
+    repo: &RepoRef,
+    number: u64,
+    issue: &Value,
+    notes: &Value,
+) -> TaskAttachment {
+    let reference = format!("{}#{}", repo.path(), number);
+    let labels = issue["labels"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|l| l.as_str().map(String::from))
+        .collect();
+    let comments = notes
+        .as_array()
+        .into_iter()
+        .flatten()
+        // System notes are activity entries ("changed the description"), not comments
+        .filter(|n| !n["system"].as_bool().unwrap_or(false))
+        .map(|n| AttachmentComment {
+            author: n["author"]["username"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            body: n["body"].as_str().unwrap_or_default().to_string(),
+            created_at: n["created_at"].as_str().map(String::from),
+        })
+        .collect();
+
+    let mut fields = BTreeMap::new();
+    if let Some(state) = issue["state"].as_str() {
+        fields.insert("State".to_string(), state.to_string());
+    }
+    if let Some(author) = issue["author"]["username"].as_str() {
+        fields.insert("Author".to_string(), author.to_string());
+    }
+    if let Some(milestone) = issue["milestone"]["title"].as_str() {
+        fields.insert("Milestone".to_string(), milestone.to_string());
+    }
+
+    TaskAttachment {
+        id: TaskAttachment::make_id("gitlab", &reference),
+        source: "gitlab".to_string(),
+        reference,
+        url: issue["web_url"].as_str().unwrap_or_default().to_string(),
+        title: issue["title"].as_str().unwrap_or_default().to_string(),
+        body: issue["description"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        labels,
+        comments,
+        fields,
+        acceptance_criteria: Vec::new(),
+        project_path: None,
+        linked_pr: None,
+        fetched_at: Utc::now(),
+    }
+}
+
+#[async_trait::async_trait]
+impl GitForge for GitlabForge {
+    fn kind(&self) -> ForgeKind {
+        ForgeKind::Gitlab
+    }
+
+    async fn fetch_issue(&self, repo: &RepoRef, number: u64) -> Result<TaskAttachment, String> {
+        let client = http_client()?;
+        let issue_url = format!("{}/issues/{}", Self::project_url(repo), number);
+        let issue = send_json(self.kind(), self.request(client.get(&issue_url))).await?;
+        let notes_url = format!(
+            "{}/notes?sort=asc&order_by=created_at&per_page={}",
+            issue_url, NOTES_PER_PAGE
+        );
+        let notes = send_json(self.kind(), self.request(client.get(&notes_url))).await?;
+
+        Ok(issue_to_attachment(repo, number, &issue, &notes))
+    }
+
+    async fn create_pull_request(
+        &self,
+        repo: &RepoRef,
+        request: &PullRequestRequest,
+        source_branch: &str,
+    ) -> Result<PullRequestInfo, String> {
+        let url = format!("{}/merge_requests", Self::project_url(repo));
+        // Draft merge requests are marked through the title prefix
+        let title = if request.draft {
+            format!("Draft: {}", request.title)
+        } else {
+            request.title.clone()
+        };
+        let body = json!({
+            "title": title,
+            "description": request.body,
+            "source_branch": source_branch,
+            "target_branch": request.target_branch,
+        });
+        let created = send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url)).json(&body),
+        )
+        .await?;
+
+        Ok(PullRequestInfo {
+            forge: self.kind(),
+            number: created["iid"].as_u64().unwrap_or_default(),
+            url: created["web_url"].as_str().unwrap_or_default().to_string(),
+        })
+    }
+
+    async fn comment_on_issue(
+        &self,
+        repo: &RepoRef,
+        number: u64,
+        body: &str,
+    ) -> Result<(), String> {
+        let url = format!("{}/issues/{}/notes", Self::project_url(repo), number);
+        send_json(
+            self.kind(),
+            self.request(http_client()?.post(&url))
+                .json(&json!({ "body": body })),
+        )
+        .await
+        .map(|_| ())
+    }
+}
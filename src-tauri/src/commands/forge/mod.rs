@@ -0,0 +1,776 @@
+//! Git forge integrations (GitHub, GitLab, Bitbucket)
+//!
+//! Issue import and pull/merge request creation are implemented per forge
+//! behind the [`GitForge`] trait. The forge of a project is detected from its
+//! `origin` remote; self-hosted instances can be mapped to a forge kind in
+//! `~/.anycode/forges.json`. Forge tokens are kept in the system keyring.
+//!
+//! Imported issues are stored as task attachments (see `task_context`).
+
+pub mod bitbucket;
+pub mod github;
+pub mod gitlab;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::task_context::{self, TaskAttachment};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// Supported forges
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    Github,
+    Gitlab,
+    Bitbucket,
+}
+
+impl ForgeKind {
+    pub const ALL: [ForgeKind; 3] = [ForgeKind::Github, ForgeKind::Gitlab, ForgeKind::Bitbucket];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ForgeKind::Github => "github",
+            ForgeKind::Gitlab => "gitlab",
+            ForgeKind::Bitbucket => "bitbucket",
+        }
+    }
+
+    fn display_name(&self) -> &'static str {
+        match self {
+            ForgeKind::Github => "GitHub",
+            ForgeKind::Gitlab => "GitLab",
+            ForgeKind::Bitbucket => "Bitbucket",
+        }
+    }
+
+    /// Host of the public instance
+    fn public_host(&self) -> &'static str {
+        match self {
+            ForgeKind::Github => "github.com",
+            ForgeKind::Gitlab => "gitlab.com",
+            ForgeKind::Bitbucket => "bitbucket.org",
+        }
+    }
+
+    fn token_env_vars(&self) -> &'static [&'static str] {
+        match self {
+            ForgeKind::Github => &["GITHUB_TOKEN", "GH_TOKEN"],
+            ForgeKind::Gitlab => &["GITLAB_TOKEN"],
+            ForgeKind::Bitbucket => &["BITBUCKET_TOKEN"],
+        }
+    }
+
+    /// The forge whose public instance is `host` or one of its subdomains
+    fn from_host(host: &str) -> Option<ForgeKind> {
+        let host = host.split(':').next().unwrap_or(host).to_lowercase();
+        ForgeKind::ALL.into_iter().find(|kind| {
+            let domain = kind.public_host();
+            host == domain
+                || host
+                    .strip_suffix(domain)
+                    .is_some_and(|sub| sub.ends_with('.'))
+        })
+    }
+}
+
+/// A repository on a forge
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoRef {
+    pub host: String,
+    /// Owner, workspace or (GitLab) group path including subgroups
+    pub owner: String,
+    pub name: String,
+}
+
+impl RepoRef {
+    pub fn path(&self) -> String {
+        format!("{}/{}", self.owner, self.name)
+    }
+}
+
+/// Pull/merge request to create
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestRequest {
+    pub title: String,
+    #[serde(default)]
+    pub body: String,
+    /// Defaults to the current branch
+    pub source_branch: Option<String>,
+    pub target_branch: String,
+    #[serde(default)]
+    pub draft: bool,
+}
+
+/// A created pull/merge request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestInfo {
+    pub forge: ForgeKind,
+    pub number: u64,
+    pub url: String,
+}
+
+/// Operations every forge implements
+#[async_trait::async_trait]
+pub trait GitForge: Send + Sync {
+    fn kind(&self) -> ForgeKind;
+
+    /// Fetch an issue with its comments as a task attachment
+    async fn fetch_issue(&self, repo: &RepoRef, number: u64) -> Result<TaskAttachment, String>;
+
+    /// Open a pull request (merge request on GitLab)
+    async fn create_pull_request(
+        &self,
+        repo: &RepoRef,
+        request: &PullRequestRequest,
+        source_branch: &str,
+    ) -> Result<PullRequestInfo, String>;
+
+    /// Post a comment on an issue
+    async fn comment_on_issue(&self, repo: &RepoRef, number: u64, body: &str)
+        -> Result<(), String>;
+}
+
+/// Build the forge implementation for a kind
+pub fn forge_for(kind: ForgeKind, token: Option<String>) -> Box<dyn GitForge> {
+    match kind {
+        ForgeKind::Github => Box::new(github::GithubForge::new(token)),
+        ForgeKind::Gitlab => Box::new(gitlab::GitlabForge::new(token)),
+        ForgeKind::Bitbucket => Box::new(bitbucket::BitbucketForge::new(token)),
+    }
+}
+
+// ============================================================================
+// Configuration and tokens
+// ============================================================================
+
+/// Stored forge settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgeConfig {
+    /// Tokens saved in plaintext by earlier versions, moved to the keyring on first use
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub tokens: HashMap<ForgeKind, String>,
+    /// Self-hosted hosts mapped to their forge kind, e.g. "git.example.com" -> gitlab
+    #[serde(default)]
+    pub hosts: HashMap<String, ForgeKind>,
+}
+
+/// Token availability of one forge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgeTokenStatus {
+    pub forge: ForgeKind,
+    pub configured: bool,
+    /// "keyring", "config", "env", "gh" or None
+    pub token_source: Option<String>,
+}
+
+/// Forge integration status of a project
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ForgeStatus {
+    /// Forge detected from the project's origin remote
+    pub detected: Option<ForgeKind>,
+    pub repository: Option<RepoRef>,
+    pub tokens: Vec<ForgeTokenStatus>,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("forges.json"))
+}
+
+fn load_config() -> ForgeConfig {
+    config_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn gh_cli_token() -> Option<String> {
+    let mut cmd = std::process::Command::new("gh");
+    cmd.args(["auth", "token"]);
+
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Token stored by earlier versions in `~/.anycode/github.json`
+fn legacy_github_token() -> Option<String> {
+    let path = ConfigPathBuilder::from_home_subdir(".anycode")
+        .ok()?
+        .build("github.json");
+    let config: Option<Value> = load_json_config(path).ok()?;
+    config?["token"].as_str().map(String::from)
+}
+
+/// Keyring entry holding the token of a forge
+fn token_key(kind: ForgeKind) -> String {
+    format!("forge-{}-token", kind.as_str())
+}
+
+/// Move tokens saved in plaintext by earlier versions into the keyring
+fn migrate_plaintext_tokens() -> Result<(), String> {
+    let mut config = load_config();
+    if config.tokens.is_empty() {
+        return Ok(());
+    }
+    for (kind, token) in &config.tokens {
+        if !token.trim().is_empty() {
+            keyring_store::set_secret(&token_key(*kind), token.trim())?;
+        }
+    }
+    config.tokens.clear();
+    save_json_config(&config, config_path()?)
+}
+
+/// Resolve the token of a forge and where it came from
+fn resolve_token(kind: ForgeKind) -> Option<(String, &'static str)> {
+    if let Err(e) = migrate_plaintext_tokens() {
+        log::warn!("[Forge] Failed to move tokens to the keyring: {}", e);
+    }
+    let stored = keyring_store::get_secret(&token_key(kind)).unwrap_or_else(|e| {
+        log::warn!("[Forge] {}", e);
+        None
+    });
+    // Left in the config only when moving it to the keyring failed
+    let config = load_config();
+
+    let resolved = if let Some(token) = stored.filter(|t| !t.trim().is_empty()) {
+        Some((token, "keyring"))
+    } else if let Some(token) = config.tokens.get(&kind).filter(|t| !t.trim().is_empty()) {
+        Some((token.clone(), "config"))
+    } else if let Some(token) = kind
+        .token_env_vars()
+        .iter()
+        .find_map(|key| std::env::var(key).ok().filter(|t| !t.trim().is_empty()))
+    {
+        Some((token, "env"))
+    } else if kind == ForgeKind::Github {
+        legacy_github_token()
+            .map(|t| (t, "config"))
+            .or_else(|| gh_cli_token().map(|t| (t, "gh")))
+    } else {
+        None
+    };
+
+    if let Some((token, _)) = &resolved {
+        crate::utils::redaction::register_secret(token);
+    }
+    resolved
+}
+
+async fn resolve_token_async(kind: ForgeKind) -> Result<Option<(String, &'static str)>, String> {
+    tokio::task::spawn_blocking(move || resolve_token(kind))
+        .await
+        .map_err(|e| format!("Token lookup failed: {}", e))
+}
+
+// ============================================================================
+// Remote and reference parsing
+// ============================================================================
+
+/// Parse a remote URL (https, ssh or scp-like) into forge kind and repository
+pub fn parse_remote_url(
+    url: &str,
+    hosts: &HashMap<String, ForgeKind>,
+) -> Option<(ForgeKind, RepoRef)> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+
+    let (host, path) = if let Some((scheme, rest)) = url.split_once("://") {
+        let rest = rest.rsplit_once('@').map(|(_, r)| r).unwrap_or(rest);
+        let (host, path) = rest.split_once('/')?;
+        // An http(s) port is the one the API is served on; an ssh port isn't
+        let host = if scheme.starts_with("http") {
+            host
+        } else {
+            host.split(':').next()?
+        };
+        (host.to_string(), path.to_string())
+    } else {
+        // scp-like syntax: git@host:owner/repo
+        let rest = url.rsplit_once('@').map(|(_, r)| r).unwrap_or(url);
+        let (host, path) = rest.split_once(':')?;
+        (host.to_string(), path.to_string())
+    };
+
+    let host_name = host.split(':').next().unwrap_or(&host).to_lowercase();
+    let kind = hosts
+        .get(&host.to_lowercase())
+        .or_else(|| hosts.get(&host_name))
+        .copied()
+        .or_else(|| ForgeKind::from_host(&host))?;
+
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+    let (owner, name) = match kind {
+        // GitLab groups can be nested; the repository path ends at "/-/"
+        ForgeKind::Gitlab => {
+            let end = segments
+                .iter()
+                .position(|s| *s == "-")
+                .unwrap_or(segments.len());
+            if end < 2 {
+                return None;
+            }
+            (segments[..end - 1].join("/"), segments[end - 1].to_string())
+        }
+        _ => {
+            if segments.len() < 2 {
+                return None;
+            }
+            (segments[0].to_string(), segments[1].to_string())
+        }
+    };
+
+    Some((kind, RepoRef { host, owner, name }))
+}
+
+/// Detect the forge of a project from its origin remote
+pub fn detect_project_forge(project_path: &str) -> Result<(ForgeKind, RepoRef), String> {
+    let remote = super::simple_git::git_remote_url(project_path, "origin")?;
+    parse_remote_url(&remote, &load_config().hosts)
+        .ok_or_else(|| format!("Origin remote is not a supported forge: {}", remote))
+}
+
+/// A parsed issue reference
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IssueRef {
+    pub forge: ForgeKind,
+    pub repo: RepoRef,
+    pub number: u64,
+}
+
+/// Parse an issue reference
+///
+/// Accepts issue URLs of any supported forge, `owner/repo#12` (on the
+/// project's forge, GitHub when unknown) and `#12` / `12` (resolved against
+/// the `origin` remote of `project_path`).
+pub fn parse_issue_reference(
+    reference: &str,
+    project_path: Option<&str>,
+) -> Result<IssueRef, String> {
+    let reference = reference.trim();
+    let invalid = || format!("Unrecognized issue reference: {}", reference);
+    let hosts = load_config().hosts;
+
+    if reference.contains("://") {
+        for marker in ["/-/issues/", "/issues/"] {
+            if let Some((repo_url, rest)) = reference.split_once(marker) {
+                let (forge, repo) = parse_remote_url(repo_url, &hosts).ok_or_else(invalid)?;
+                let number = rest
+                    .split(['/', '#', '?'])
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(invalid)?;
+                return Ok(IssueRef {
+                    forge,
+                    repo,
+                    number,
+                });
+            }
+        }
+        return Err(invalid());
+    }
+
+    let project_forge = project_path.map(detect_project_forge);
+
+    if let Some((repo_part, number)) = reference.split_once('#') {
+        let number: u64 = number.parse().map_err(|_| invalid())?;
+        if let Some((owner, name)) = repo_part.rsplit_once('/') {
+            let (forge, host) = match project_forge {
+                Some(Ok((forge, repo))) => (forge, repo.host),
+                _ => (ForgeKind::Github, "github.com".to_string()),
+            };
+            return Ok(IssueRef {
+                forge,
+                repo: RepoRef {
+                    host,
+                    owner: owner.to_string(),
+                    name: name.to_string(),
+                },
+                number,
+            });
+        }
+        if !repo_part.is_empty() {
+            return Err(invalid());
+        }
+    }
+
+    let number: u64 = reference
+        .trim_start_matches('#')
+        .parse()
+        .map_err(|_| invalid())?;
+    let (forge, repo) = project_forge
+        .ok_or_else(|| "A project is required to resolve a bare issue number".to_string())??;
+    Ok(IssueRef {
+        forge,
+        repo,
+        number,
+    })
+}
+
+// ============================================================================
+// Shared HTTP helpers
+// ============================================================================
+
+pub(crate) fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(concat!("any-code/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Send a request and parse the JSON response, mapping HTTP errors to messages
+pub(crate) async fn send_json(
+    kind: ForgeKind,
+    request: reqwest::RequestBuilder,
+) -> Result<Value, String> {
+    send_json_with(kind.display_name(), request).await
+}
+
+/// Same as [`send_json`], for services that aren't git forges
+pub(crate) async fn send_json_with(
+    name: &str,
+    request: reqwest::RequestBuilder,
+) -> Result<Value, String> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("{} request failed: {}", name, e))?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(match status.as_u16() {
+            401 => format!("{} rejected the token (401)", name),
+            403 | 429 => format!(
+                "{} rate limit or permission error ({}): {}",
+                name, status, body
+            ),
+            404 => format!(
+                "{} resource not found, or the token has no access to it",
+                name
+            ),
+            _ => format!("{} API error ({}): {}", name, status, body),
+        });
+    }
+
+    if status.as_u16() == 204 {
+        return Ok(Value::Null);
+    }
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid {} response: {}", name, e))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Report the detected forge of a project and which forge tokens are available
+#[tauri::command]
+pub async fn get_forge_status(project_path: Option<String>) -> Result<ForgeStatus, String> {
+    tokio::task::spawn_blocking(move || {
+        let detected = project_path
+            .as_deref()
+            .and_then(|path| detect_project_forge(path).ok());
+        let tokens = ForgeKind::ALL
+            .into_iter()
+            .map(|forge| {
+                let resolved = resolve_token(forge);
+                ForgeTokenStatus {
+                    forge,
+                    configured: resolved.is_some(),
+                    token_source: resolved.map(|(_, source)| source.to_string()),
+                }
+            })
+            .collect();
+
+        ForgeStatus {
+            detected: detected.as_ref().map(|(kind, _)| *kind),
+            repository: detected.map(|(_, repo)| repo),
+            tokens,
+        }
+    })
+    .await
+    .map_err(|e| format!("Forge status task failed: {}", e))
+}
+
+/// Store in the keyring (or clear, with `None`) the token of a forge
+#[tauri::command]
+pub async fn set_forge_token(forge: ForgeKind, token: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        match token
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+        {
+            Some(token) => keyring_store::set_secret(&token_key(forge), &token)?,
+            None => keyring_store::delete_secret(&token_key(forge))?,
+        }
+        let mut config = load_config();
+        if config.tokens.remove(&forge).is_some() {
+            save_json_config(&config, config_path()?)?;
+        }
+        Ok::<_, String>(())
+    })
+    .await
+    .map_err(|e| format!("Token storage task failed: {}", e))?
+}
+
+/// Map a self-hosted host to a forge kind (`None` removes the mapping)
+#[tauri::command]
+pub async fn set_forge_host(host: String, forge: Option<ForgeKind>) -> Result<(), String> {
+    let mut config = load_config();
+    let host = host.trim().to_lowercase();
+    match forge {
+        Some(forge) => config.hosts.insert(host, forge),
+        None => config.hosts.remove(&host),
+    };
+    save_json_config(&config, config_path()?)
+}
+
+/// Fetch an issue from the project's forge and store it as a task attachment
+///
+/// Re-importing an issue refreshes it while keeping any linked pull request.
+#[tauri::command]
+pub async fn import_issue(
+    reference: String,
+    project_path: Option<String>,
+) -> Result<TaskAttachment, String> {
+    super::connectivity::ensure_online("Importing an issue")?;
+
+    let parse_path = project_path.clone();
+    let issue_ref = tokio::task::spawn_blocking(move || {
+        parse_issue_reference(&reference, parse_path.as_deref())
+    })
+    .await
+    .map_err(|e| format!("Reference parsing failed: {}", e))??;
+    log::info!(
+        "[Forge] Importing {} issue {}#{}",
+        issue_ref.forge.as_str(),
+        issue_ref.repo.path(),
+        issue_ref.number
+    );
+
+    let token = resolve_token_async(issue_ref.forge).await?.map(|(t, _)| t);
+    let forge = forge_for(issue_ref.forge, token);
+    let mut attachment = forge.fetch_issue(&issue_ref.repo, issue_ref.number).await?;
+    attachment.project_path = project_path;
+    if let Ok(previous) = task_context::load_attachment(&attachment.id) {
+        attachment.linked_pr = previous.linked_pr;
+    }
+    task_context::save_attachment(&attachment)?;
+
+    log::info!(
+        "[Forge] Imported {} ({} comments)",
+        attachment.reference,
+        attachment.comments.len()
+    );
+    Ok(attachment)
+}
+
+async fn link_and_comment(
+    attachment: &mut TaskAttachment,
+    pr_url: &str,
+    post_comment: bool,
+) -> Result<(), String> {
+    if post_comment {
+        let issue_ref = parse_issue_reference(&attachment.url, None)?;
+        let (token, _) = resolve_token_async(issue_ref.forge).await?.ok_or_else(|| {
+            format!(
+                "A {} token is required to comment on issues",
+                issue_ref.forge.display_name()
+            )
+        })?;
+        forge_for(issue_ref.forge, Some(token))
+            .comment_on_issue(
+                &issue_ref.repo,
+                issue_ref.number,
+                &format!("Addressed in {}", pr_url),
+            )
+            .await?;
+    }
+
+    attachment.linked_pr = Some(pr_url.to_string());
+    task_context::save_attachment(attachment)
+}
+
+/// Open a pull/merge request for a project on its detected forge
+///
+/// When `attachment_id` is given, the request is linked to that imported issue
+/// and a comment pointing at it is posted on the issue.
+#[tauri::command]
+pub async fn create_pull_request(
+    project_path: String,
+    request: PullRequestRequest,
+    attachment_id: Option<String>,
+) -> Result<PullRequestInfo, String> {
+    super::connectivity::ensure_online("Creating a pull request")?;
+
+    let path = project_path.clone();
+    let ((kind, repo), current_branch) = tokio::task::spawn_blocking(move || {
+        Ok::<_, String>((
+            detect_project_forge(&path)?,
+            super::simple_git::git_current_branch(&path)?,
+        ))
+    })
+    .await
+    .map_err(|e| format!("Forge detection failed: {}", e))??;
+
+    let source_branch = request
+        .source_branch
+        .clone()
+        .filter(|b| !b.trim().is_empty())
+        .unwrap_or(current_branch);
+    if source_branch == request.target_branch {
+        return Err(format!(
+            "Source and target branch are both '{}'",
+            source_branch
+        ));
+    }
+
+    let (token, _) = resolve_token_async(kind).await?.ok_or_else(|| {
+        format!(
+            "A {} token is required to create pull requests",
+            kind.display_name()
+        )
+    })?;
+    let info = forge_for(kind, Some(token))
+        .create_pull_request(&repo, &request, &source_branch)
+        .await?;
+    log::info!(
+        "[Forge] Created {} for {}: {}",
+        kind.as_str(),
+        repo.path(),
+        info.url
+    );
+
+    if let Some(id) = attachment_id {
+        let mut attachment = task_context::load_attachment(&id)?;
+        if let Err(e) = link_and_comment(&mut attachment, &info.url, true).await {
+            log::warn!(
+                "[Forge] Pull request created but linking to {} failed: {}",
+                id,
+                e
+            );
+        }
+    }
+
+    Ok(info)
+}
+
+/// Link an existing pull request to an imported issue, optionally commenting on the issue
+#[tauri::command]
+pub async fn link_pull_request_to_issue(
+    attachment_id: String,
+    pr_url: String,
+    post_comment: bool,
+) -> Result<TaskAttachment, String> {
+    if post_comment {
+        super::connectivity::ensure_online("Commenting on an issue")?;
+    }
+    let mut attachment = task_context::load_attachment(&attachment_id)?;
+    link_and_comment(&mut attachment, &pr_url, post_comment).await?;
+    Ok(attachment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(host: &str, owner: &str, name: &str) -> RepoRef {
+        RepoRef {
+            host: host.to_string(),
+            owner: owner.to_string(),
+            name: name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_remote_url() {
+        let hosts = HashMap::new();
+        let github = Some((ForgeKind::Github, repo("github.com", "owner", "repo")));
+        assert_eq!(
+            parse_remote_url("git@github.com:owner/repo.git", &hosts),
+            github
+        );
+        assert_eq!(
+            parse_remote_url("https://github.com/owner/repo", &hosts),
+            github
+        );
+        assert_eq!(
+            parse_remote_url("ssh://git@github.com:22/owner/repo.git", &hosts),
+            github
+        );
+
+        assert_eq!(
+            parse_remote_url("https://gitlab.com/group/sub/repo.git", &hosts),
+            Some((ForgeKind::Gitlab, repo("gitlab.com", "group/sub", "repo")))
+        );
+        assert_eq!(
+            parse_remote_url("git@bitbucket.org:team/repo.git", &hosts),
+            Some((ForgeKind::Bitbucket, repo("bitbucket.org", "team", "repo")))
+        );
+        assert_eq!(
+            parse_remote_url("https://git.example.com/a/b", &hosts),
+            None
+        );
+
+        assert_eq!(
+            parse_remote_url("https://github.com.evil.io/owner/repo", &hosts),
+            None
+        );
+
+        let mut hosts = HashMap::new();
+        hosts.insert("git.example.com".to_string(), ForgeKind::Gitlab);
+        assert_eq!(
+            parse_remote_url("https://git.example.com/a/b", &hosts),
+            Some((ForgeKind::Gitlab, repo("git.example.com", "a", "b")))
+        );
+        assert_eq!(
+            parse_remote_url("https://git.example.com:8443/a/b", &hosts),
+            Some((ForgeKind::Gitlab, repo("git.example.com:8443", "a", "b")))
+        );
+    }
+
+    #[test]
+    fn test_parse_issue_reference_urls() {
+        let issue =
+            parse_issue_reference("https://gitlab.com/group/sub/repo/-/issues/7", None).unwrap();
+        assert_eq!(issue.forge, ForgeKind::Gitlab);
+        assert_eq!(issue.repo.path(), "group/sub/repo");
+        assert_eq!(issue.number, 7);
+
+        let issue = parse_issue_reference(
+            "https://github.com/owner/repo/issues/42#issuecomment-1",
+            None,
+        )
+        .unwrap();
+        assert_eq!(issue.forge, ForgeKind::Github);
+        assert_eq!(issue.number, 42);
+
+        let issue = parse_issue_reference("owner/repo#3", None).unwrap();
+        assert_eq!(issue.forge, ForgeKind::Github);
+        assert_eq!(issue.repo.path(), "owner/repo");
+
+        assert!(parse_issue_reference("#42", None).is_err());
+        assert!(parse_issue_reference("not an issue", None).is_err());
+    }
+}
@@ -285,6 +285,7 @@ pub fn list_session_files(project_path: &str) -> Result<Vec<GeminiSessionInfo>,
     let entries =
         fs::read_dir(&chats_dir).map_err(|e| format!("Failed to read chats directory: {}", e))?;
 
+    let titles = crate::commands::session_titles::load_index();
     let mut sessions = Vec::new();
 
     for entry in entries {
@@ -314,11 +315,17 @@ pub fn list_session_files(project_path: &str) -> Result<Vec<GeminiSessionInfo>,
                     }
                 }
 
+                let title = crate::commands::session_titles::title_for(
+                    &titles,
+                    "gemini",
+                    &detail.session_id,
+                );
                 sessions.push(GeminiSessionInfo {
                     session_id: detail.session_id,
                     file_name,
                     start_time: detail.start_time,
                     first_message,
+                    title,
                 });
             }
         }
@@ -400,7 +407,10 @@ pub async fn get_gemini_session_detail(
 /// Delete a Gemini session
 #[tauri::command]
 pub async fn delete_gemini_session(project_path: String, session_id: String) -> Result<(), String> {
-    delete_session(&project_path, &session_id)
+    delete_session(&project_path, &session_id)?;
+    crate::commands::session_titles::remove_title("gemini", &session_id);
+    crate::commands::session_bookmarks::remove_session_bookmarks("gemini", &session_id);
+    Ok(())
 }
 
 // ============================================================================
@@ -15,6 +15,7 @@ pub mod git_ops;
 pub mod parser;
 pub mod provider;
 pub mod session;
+pub mod settings;
 pub mod types;
 pub mod usage;
 
@@ -53,6 +54,9 @@ pub use provider::{
     switch_gemini_provider, test_gemini_provider_connection, update_gemini_provider_config,
 };
 
+// Re-export Gemini settings.json commands
+pub use settings::{get_gemini_settings, update_gemini_settings};
+
 // Re-export Gemini Usage Statistics commands
 pub use usage::get_gemini_usage_stats;
 
@@ -12,6 +12,7 @@ use std::fs;
 use std::path::PathBuf;
 
 use super::config::get_gemini_dir;
+use super::settings::get_gemini_settings_path;
 use crate::commands::wsl_utils;
 
 // ============================================================================
@@ -54,11 +55,6 @@ fn get_gemini_env_path() -> Result<PathBuf, String> {
     Ok(get_gemini_dir()?.join(".env"))
 }
 
-/// Get Gemini settings.json file path
-fn get_gemini_settings_path() -> Result<PathBuf, String> {
-    Ok(get_gemini_dir()?.join("settings.json"))
-}
-
 /// Get Gemini providers.json path (for custom presets storage)
 fn get_gemini_providers_path() -> Result<PathBuf, String> {
     let home = dirs::home_dir().ok_or("Failed to get home directory")?;
@@ -69,6 +65,16 @@ fn get_gemini_providers_path() -> Result<PathBuf, String> {
 // .env File Operations
 // ============================================================================
 
+/// Base URL from `~/.gemini/.env`, falling back to `GOOGLE_GEMINI_BASE_URL`
+pub fn configured_base_url() -> Option<String> {
+    get_gemini_env_path()
+        .and_then(|path| read_env_file(&path))
+        .ok()
+        .and_then(|env| env.get("GOOGLE_GEMINI_BASE_URL").cloned())
+        .or_else(|| std::env::var("GOOGLE_GEMINI_BASE_URL").ok())
+        .filter(|url| !url.trim().is_empty())
+}
+
 /// Read .env file and parse into HashMap
 fn read_env_file(path: &PathBuf) -> Result<HashMap<String, String>, String> {
     if !path.exists() {
@@ -121,45 +127,42 @@ fn write_env_file(path: &PathBuf, env_map: &HashMap<String, String>) -> Result<(
     fs::write(path, content).map_err(|e| format!("Failed to write .env file: {}", e))
 }
 
+/// Replace the API key in .env, keeping the other variables (used by key rotation)
+pub(crate) fn set_gemini_api_key(key: &str) -> Result<(), String> {
+    let env_path = get_gemini_env_path()?;
+    let mut env = read_env_file(&env_path)?;
+    // Keep whichever variable the current setup uses
+    let field = if env.contains_key("GOOGLE_API_KEY") && !env.contains_key("GEMINI_API_KEY") {
+        "GOOGLE_API_KEY"
+    } else {
+        "GEMINI_API_KEY"
+    };
+    env.insert(field.to_string(), key.to_string());
+    write_env_file(&env_path, &env)
+}
+
 // ============================================================================
 // settings.json Operations
 // ============================================================================
 
-/// Read settings.json
+/// Read settings.json (JSONC, comments allowed)
 fn read_settings_file(path: &PathBuf) -> Result<serde_json::Value, String> {
-    if !path.exists() {
-        return Ok(serde_json::json!({}));
-    }
-
-    let content =
-        fs::read_to_string(path).map_err(|e| format!("Failed to read settings.json: {}", e))?;
-
-    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings.json: {}", e))
+    super::settings::read_settings_value(path)
 }
 
-/// Write settings.json (preserves existing fields like mcpServers)
-fn write_settings_file(path: &PathBuf, settings: &serde_json::Value) -> Result<(), String> {
-    // Ensure parent directory exists
-    if let Some(parent) = path.parent() {
-        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
-    }
-
-    let content = serde_json::to_string_pretty(settings)
-        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
-
-    fs::write(path, content).map_err(|e| format!("Failed to write settings.json: {}", e))
-}
-
-/// Set selected auth type in settings.json
-fn set_auth_type_in_settings(settings: &mut serde_json::Value, auth_type: &str) {
-    // Ensure security.auth.selectedType structure exists
-    if settings.get("security").is_none() {
-        settings["security"] = serde_json::json!({});
-    }
-    if settings["security"].get("auth").is_none() {
-        settings["security"]["auth"] = serde_json::json!({});
-    }
-    settings["security"]["auth"]["selectedType"] = serde_json::Value::String(auth_type.to_string());
+/// Set selected auth type in settings.json, preserving mcpServers, comments and formatting
+fn write_auth_type(path: &PathBuf, auth_type: &str) -> Result<(), String> {
+    super::settings::edit_settings_file(path, |root, current| {
+        // Older Gemini CLI versions used a top-level `selectedAuthType`
+        let key_path: &[&str] = if current.get("selectedAuthType").is_some() {
+            &["selectedAuthType"]
+        } else {
+            &["security", "auth", "selectedType"]
+        };
+        let value = serde_json::Value::String(auth_type.to_string());
+        super::settings::set_property(root, key_path, Some(&value));
+        Ok(())
+    })
 }
 
 // ============================================================================
@@ -253,9 +256,6 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
             .map_err(|e| format!("Failed to create .gemini directory at {:?}: {}", gemini_dir, e))?;
     }
 
-    // Read existing settings to preserve mcpServers and other user configs
-    let mut settings = read_settings_file(&settings_path)?;
-
     // Determine if this is official (OAuth) or third-party (API Key)
     let is_official = config.is_official.unwrap_or(false)
         || config.category.as_deref() == Some("official")
@@ -276,7 +276,7 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
         write_env_file(&env_path, &HashMap::new())?;
 
         // Set auth type to oauth-personal
-        set_auth_type_in_settings(&mut settings, "oauth-personal");
+        write_auth_type(&settings_path, "oauth-personal")?;
     } else {
         // Third-party (API Key): Write env and set auth type to gemini-api-key
         log::info!("[Gemini Provider] Setting up for API Key mode");
@@ -285,17 +285,17 @@ pub async fn switch_gemini_provider(config: GeminiProviderConfig) -> Result<Stri
         write_env_file(&env_path, &config.env)?;
 
         // Set auth type to gemini-api-key
-        set_auth_type_in_settings(&mut settings, "gemini-api-key");
+        write_auth_type(&settings_path, "gemini-api-key")?;
     }
 
-    // Write settings.json
-    write_settings_file(&settings_path, &settings)?;
-
     log::info!(
         "[Gemini Provider] Successfully switched to: {}",
         config.name
     );
 
+    // Use the provider's key pool, if it has one
+    crate::commands::key_pools::on_provider_switched("gemini", &config.id);
+
     // Return success message with mode info
     let mode_info = if is_wsl_mode { " (WSL)" } else { "" };
     Ok(format!("成功切换到 Gemini 供应商: {}{}", config.name, mode_info))
@@ -468,11 +468,10 @@ pub async fn clear_gemini_provider_config() -> Result<String, String> {
     write_env_file(&env_path, &HashMap::new())?;
 
     // Reset auth type to OAuth in settings
-    let mut settings = read_settings_file(&settings_path)?;
-    set_auth_type_in_settings(&mut settings, "oauth-personal");
-    write_settings_file(&settings_path, &settings)?;
+    write_auth_type(&settings_path, "oauth-personal")?;
 
     log::info!("[Gemini Provider] Successfully cleared config");
+    crate::commands::key_pools::on_provider_cleared("gemini");
     Ok("成功清理 Gemini 配置，已切换回官方 OAuth 模式".to_string())
 }
 
@@ -483,6 +482,7 @@ pub async fn test_gemini_provider_connection(
     api_key: Option<String>,
 ) -> Result<String, String> {
     log::info!("[Gemini Provider] Testing connection to: {}", base_url);
+    crate::commands::connectivity::ensure_online("Provider connection test")?;
 
     // Simple connectivity test
     let client = reqwest::Client::builder()
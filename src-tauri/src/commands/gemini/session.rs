@@ -5,6 +5,7 @@
 
 use std::process::Stdio;
 
+use futures::future::join_all;
 use tauri::{AppHandle, Emitter, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
@@ -18,9 +19,13 @@ use super::parser::{
 };
 use super::types::{GeminiExecutionOptions, GeminiInstallStatus, GeminiProcessHandle, GeminiProcessState, GeminiSessionDetail, TokenUsage};
 use crate::claude_binary::detect_binary_for_tool;
-use crate::commands::claude::apply_no_window_async;
+use crate::commands::auto_checkpoint;
+use crate::commands::dev_containers;
+use crate::commands::event_batcher;
 use crate::commands::wsl_utils;
+use crate::process::interrupt::{self, Shutdown};
 use crate::process::JobObject;
+use crate::session_runner::{self, SessionRunner};
 
 // ============================================================================
 // Slash Command Detection
@@ -352,7 +357,7 @@ pub async fn check_gemini_installed() -> Result<GeminiInstallStatus, String> {
 }
 
 /// 实际执行 Gemini 安装检测（内部函数）
-fn do_check_gemini_installed() -> GeminiInstallStatus {
+pub(crate) fn do_check_gemini_installed() -> GeminiInstallStatus {
     match find_gemini_binary() {
         Ok(path) => {
             let is_wsl = path.starts_with("WSL:");
@@ -407,8 +412,35 @@ pub async fn execute_gemini(
         options.prompt.len()
     );
 
+    if crate::engines::require("gemini")?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting Gemini")?;
+    }
+
+    // Remember the model and approval mode used for this project
+    crate::commands::project_defaults::record_last_used(
+        &options.project_path,
+        "gemini",
+        crate::commands::project_defaults::EngineDefaults {
+            model: options.model.clone(),
+            reasoning_effort: None,
+            permission_mode: options.approval_mode.clone(),
+        },
+    );
+
     // Find Gemini binary
-    let gemini_path = find_gemini_binary()?;
+    let mut gemini_path = find_gemini_binary()?;
+    // Projects inside WSL must run the distro's Gemini CLI, not the Windows one
+    if !gemini_path.starts_with("WSL:") {
+        if let Some(location) = wsl_utils::detect_wsl_project(&options.project_path) {
+            let program = wsl_utils::wsl_engine_program("gemini", location.distro.as_deref());
+            log::info!(
+                "Project is inside WSL (distro: {:?}), using Gemini CLI in WSL: {:?}",
+                location.distro,
+                program
+            );
+            gemini_path = format!("WSL:{}", program.unwrap_or_default());
+        }
+    }
     let is_wsl = gemini_path.starts_with("WSL:");
 
     // Load configuration
@@ -431,11 +463,10 @@ pub async fn execute_gemini(
     args.push("--model".to_string());
     args.push(model.clone());
 
-    // Add approval mode
-    let approval_mode = options
-        .approval_mode
-        .as_ref()
-        .unwrap_or(&config.approval_mode);
+    // Add approval mode (the project's safety policy takes precedence)
+    let approval_mode = crate::commands::engine_safety::gemini_approval_mode(&options.project_path)
+        .or_else(|| options.approval_mode.clone())
+        .unwrap_or_else(|| config.approval_mode.clone());
     if approval_mode == "yolo" {
         args.push("--yolo".to_string());
     } else if approval_mode != "default" {
@@ -528,7 +559,11 @@ pub async fn execute_gemini(
             cmd.env(&key, &value);
         }
 
-        cmd
+        // Dev container mode: run Gemini inside the project's container
+        match dev_containers::container_command(&options.project_path, "gemini", &cmd)? {
+            Some(container_cmd) => container_cmd,
+            None => cmd,
+        }
     };
 
     // Execute process with prompt via stdin
@@ -550,46 +585,64 @@ pub async fn cancel_gemini(
 ) -> Result<(), String> {
     log::info!("cancel_gemini called for session: {:?}", session_id);
 
-    let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
-    let mut processes = state.processes.lock().await;
-
     if let Some(sid) = session_id {
         // Cancel specific session
-        if let Some(mut handle) = processes.remove(&sid) {
-            // Kill the process - JobObject will automatically terminate all child processes when dropped
-            handle
-                .child
-                .kill()
-                .await
-                .map_err(|e| format!("Failed to kill process: {}", e))?;
-            log::info!("Killed Gemini process for session: {} (PID: {})", sid, handle.pid);
-
-            // JobObject is dropped here, killing all child processes (MCP servers, node.exe, etc.)
-            drop(handle.job_object);
-
-            // Emit cancellation event
-            let _ = app_handle.emit(&format!("gemini-cancelled:{}", sid), true);
-            let _ = app_handle.emit("gemini-cancelled", true);
-        } else {
+        if stop_gemini(&app_handle, &sid).await.is_none() {
             log::warn!("No running process found for session: {}", sid);
         }
     } else {
         // Cancel all processes
-        for (sid, mut handle) in processes.drain() {
-            if let Err(e) = handle.child.kill().await {
-                log::error!("Failed to kill process for session {}: {}", sid, e);
-            } else {
-                log::info!("Killed Gemini process for session: {} (PID: {})", sid, handle.pid);
-            }
-            // JobObject is dropped here, killing all child processes
-            drop(handle.job_object);
-        }
-        let _ = app_handle.emit("gemini-cancelled", true);
+        stop_all_gemini(&app_handle).await;
     }
 
     Ok(())
 }
 
+/// Interrupt a running Gemini session and wait until its process tree is gone
+///
+/// Returns None when the session has no running process.
+pub(crate) async fn stop_gemini(app_handle: &AppHandle, session_id: &str) -> Option<Shutdown> {
+    let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
+    let handle = state.processes.lock().await.remove(session_id)?;
+    let outcome = shutdown_gemini_process(session_id, handle).await;
+
+    // Emit cancellation event
+    event_batcher::flush_all(app_handle);
+    let _ = app_handle.emit(&format!("gemini-cancelled:{}", session_id), true);
+    let _ = app_handle.emit("gemini-cancelled", true);
+    Some(outcome)
+}
+
+/// Interrupt every running Gemini session, returning how many were stopped
+pub(crate) async fn stop_all_gemini(app_handle: &AppHandle) -> usize {
+    let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
+    let handles: Vec<_> = state.processes.lock().await.drain().collect();
+    let count = handles.len();
+    join_all(
+        handles
+            .into_iter()
+            .map(|(sid, handle)| async move { shutdown_gemini_process(&sid, handle).await }),
+    )
+    .await;
+    let _ = app_handle.emit("gemini-cancelled", true);
+    count
+}
+
+async fn shutdown_gemini_process(session_id: &str, mut handle: GeminiProcessHandle) -> Shutdown {
+    log::info!(
+        "Interrupting Gemini process for session: {} (PID: {})",
+        session_id,
+        handle.pid
+    );
+    // The Job Object (Windows) or process group (Unix) also takes down MCP servers and node.exe
+    let outcome = interrupt::shutdown(handle.pid, handle.job_object.as_ref(), || {
+        interrupt::child_exited(&mut handle.child)
+    })
+    .await;
+    log::info!("Gemini session {} stopped: {:?}", session_id, outcome);
+    outcome
+}
+
 // ============================================================================
 // Process Execution
 // ============================================================================
@@ -628,18 +681,33 @@ async fn execute_gemini_process(
     cmd.stdout(Stdio::piped());
     cmd.stderr(Stdio::piped());
 
-    // Apply platform-specific no-window configuration
-    apply_no_window_async(&mut cmd);
+    // Run Gemini as the leader of its own process group, without a console window
+    interrupt::new_process_group(&mut cmd);
+    crate::process::limits::prepare_engine_command("gemini", &mut cmd);
+
+    let safety_flags = crate::commands::engine_safety::command_safety_flags(&cmd);
+    if let Some(prompt) = &prompt {
+        crate::commands::prompt_history::record_prompt(&project_path, "gemini", prompt);
+    }
 
     // Spawn process
     let mut child = cmd
         .spawn()
-        .map_err(|e| format!("Failed to spawn gemini: {}", e))?;
+        .map_err(|e| {
+            crate::commands::engine_doctor::report_spawn_error(
+                &app_handle,
+                "gemini",
+                &cmd,
+                &project_path,
+                &e,
+            );
+            format!("Failed to spawn gemini: {}", e)
+        })?;
 
     // 🔥 修复：只有非斜杠命令才通过 stdin 传递
     // 斜杠命令已经通过 -p 参数传递，避免重复
     if !use_p_flag {
-        if let Some(prompt_text) = prompt {
+        if let Some(prompt_text) = &prompt {
             if let Some(mut stdin) = child.stdin.take() {
                 use tokio::io::AsyncWriteExt;
 
@@ -675,6 +743,7 @@ async fn execute_gemini_process(
         .id()
         .ok_or("Failed to get process ID - process may have already exited")?;
     log::info!("[Gemini] Spawned process with PID: {}", pid);
+    crate::commands::key_pools::record_request("gemini");
 
     // Windows robustness: assign the process to a Job Object so *all* descendants are cleaned up
     // even if Gemini CLI spawns detached node.exe processes (MCP servers).
@@ -699,8 +768,17 @@ async fn execute_gemini_process(
     #[cfg(not(windows))]
     let job_object: Option<JobObject> = None;
 
+    // Apply configured CPU/memory ceilings before the engine starts its MCP servers
+    crate::process::limits::apply_to_engine("gemini", pid, job_object.as_ref());
+
     // Generate session ID
     let session_id = format!("gemini-{}", uuid::Uuid::new_v4());
+    crate::commands::engine_safety::record_session(
+        "gemini",
+        &session_id,
+        &project_path,
+        safety_flags,
+    );
 
     // Store process in state with PID and JobObject for proper cleanup
     let state: tauri::State<'_, GeminiProcessState> = app_handle.state();
@@ -735,9 +813,11 @@ async fn execute_gemini_process(
     }
 
     // Also emit as gemini-output for unified handling
-    let init_line = serde_json::to_string(&init_payload).unwrap_or_default();
-    let _ = app_handle.emit(&format!("gemini-output:{}", session_id), &init_line);
-    let _ = app_handle.emit("gemini-output", &init_line);
+    if session_runner::raw_output_enabled() {
+        let init_line = serde_json::to_string(&init_payload).unwrap_or_default();
+        let _ = event_batcher::emit(&app_handle, &format!("gemini-output:{}", session_id), &init_line);
+        let _ = event_batcher::emit(&app_handle, "gemini-output", &init_line);
+    }
 
     log::info!("Gemini session initialized with ID: {}", session_id);
 
@@ -756,6 +836,14 @@ async fn execute_gemini_process(
     // Spawn task to read stdout (JSONL events)
     let model_for_messages = model.clone();
     let project_path_for_usage = project_path.clone();
+    let mut session_events = SessionRunner::start(
+        &app_handle,
+        "gemini",
+        Some(session_id.clone()),
+        None,
+        &project_path,
+        prompt.as_deref(),
+    );
     tokio::spawn(async move {
         let mut reader = BufReader::new(stdout).lines();
         let mut real_cli_session_id_emitted = false;
@@ -955,21 +1043,40 @@ async fn execute_gemini_process(
 
             let unified_line = serde_json::to_string(&unified_message).unwrap_or(line.clone());
 
-            // Emit to session-specific channel
-            if let Err(e) = app_handle_stdout.emit(
-                &format!("gemini-output:{}", session_id_stdout),
-                &unified_line,
-            ) {
-                log::error!("Failed to emit gemini-output (session): {}", e);
+            if session_runner::raw_output_enabled() {
+                // Emit to session-specific channel
+                if let Err(e) = event_batcher::emit(
+                    &app_handle_stdout,
+                    &format!("gemini-output:{}", session_id_stdout),
+                    &unified_line,
+                ) {
+                    log::error!("Failed to emit gemini-output (session): {}", e);
+                }
+
+                // Also emit to global channel
+                if let Err(e) = event_batcher::emit(&app_handle_stdout, "gemini-output", &unified_line) {
+                    log::error!("Failed to emit gemini-output (global): {}", e);
+                }
             }
 
-            // Also emit to global channel
-            if let Err(e) = app_handle_stdout.emit("gemini-output", &unified_line) {
-                log::error!("Failed to emit gemini-output (global): {}", e);
+            // The unified format is Claude's stream-json dialect
+            if let Some(events) = session_events.as_mut() {
+                events.push_line(&unified_line).await;
             }
+
+            // Snapshot before file edits when auto-checkpoints are per edit
+            auto_checkpoint::on_engine_output(
+                &project_path_for_usage,
+                "gemini",
+                &session_id_stdout,
+                &unified_line,
+            );
         }
 
         log::info!("[Gemini] Stdout closed for session: {}", session_id_stdout);
+        if let Some(events) = session_events {
+            events.finish().await;
+        }
         // Signal that stdout is done (ignore send error if receiver dropped)
         let _ = stdout_done_tx.send(());
     });
@@ -981,6 +1088,7 @@ async fn execute_gemini_process(
         while let Ok(Some(line)) = reader.next_line().await {
             if !line.trim().is_empty() {
                 log::warn!("Gemini stderr: {}", line);
+                crate::commands::key_pools::observe_engine_error(&app_handle_stderr, "gemini", &line);
 
                 // Emit stderr as error event
                 let error_message = serde_json::json!({
@@ -997,9 +1105,12 @@ async fn execute_gemini_process(
 
                 let error_line = serde_json::to_string(&error_message).unwrap_or(line.clone());
 
-                let _ = app_handle_stderr
-                    .emit(&format!("gemini-error:{}", session_id_stderr), &error_line);
-                let _ = app_handle_stderr.emit("gemini-error", &error_line);
+                let _ = event_batcher::emit(
+                    &app_handle_stderr,
+                    &format!("gemini-error:{}", session_id_stderr),
+                    &error_line,
+                );
+                let _ = event_batcher::emit(&app_handle_stderr, "gemini-error", &error_line);
             }
         }
 
@@ -1089,11 +1200,13 @@ async fn execute_gemini_process(
 
         let complete_line = serde_json::to_string(&complete_payload).unwrap_or_default();
 
-        let _ = app_handle_complete.emit(
+        let _ = event_batcher::emit(
+            &app_handle_complete,
             &format!("gemini-output:{}", session_id_complete),
             &complete_line,
         );
-        let _ = app_handle_complete.emit("gemini-output", &complete_line);
+        let _ = event_batcher::emit(&app_handle_complete, "gemini-output", &complete_line);
+        event_batcher::flush_all(&app_handle_complete);
 
         let _ =
             app_handle_complete.emit(&format!("gemini-complete:{}", session_id_complete), success);
@@ -0,0 +1,490 @@
+//! Gemini settings.json Management
+//!
+//! Typed accessors for the parts of `~/.gemini/settings.json` the workbench
+//! manages (auth type, model, MCP servers, telemetry). Gemini CLI accepts
+//! comments in this file, so edits go through a JSONC CST that keeps comments,
+//! key order and indentation intact; only the touched properties change.
+
+use jsonc_parser::cst::{CstInputValue, CstObject, CstRootNode};
+use jsonc_parser::ParseOptions;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::config::get_gemini_dir;
+
+/// Auth types understood by Gemini CLI (`security.auth.selectedType`)
+pub const AUTH_TYPES: &[&str] = &[
+    "oauth-personal",
+    "gemini-api-key",
+    "vertex-ai",
+    "cloud-shell",
+];
+
+/// Telemetry targets understood by Gemini CLI
+pub const TELEMETRY_TARGETS: &[&str] = &["local", "gcp"];
+
+// ============================================================================
+// Type Definitions
+// ============================================================================
+
+/// Telemetry section of settings.json
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiTelemetrySettings {
+    pub enabled: Option<bool>,
+    pub target: Option<String>,
+    pub otlp_endpoint: Option<String>,
+    pub log_prompts: Option<bool>,
+}
+
+/// Settings read from settings.json
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSettings {
+    pub auth_type: Option<String>,
+    pub model: Option<String>,
+    /// MCP servers in the same JSON shape used by the MCP registry
+    pub mcp_servers: BTreeMap<String, Value>,
+    pub telemetry: GeminiTelemetrySettings,
+    /// Schema problems found in the file (wrong value types, unknown auth type, ...)
+    pub issues: Vec<String>,
+}
+
+/// Partial update of settings.json
+///
+/// `None` leaves a setting untouched and an empty string removes it.
+/// `telemetry` replaces the whole telemetry section (`None` fields are removed),
+/// `mcp_servers` replaces the whole server set.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GeminiSettingsUpdate {
+    pub auth_type: Option<String>,
+    pub model: Option<String>,
+    pub mcp_servers: Option<HashMap<String, Value>>,
+    pub telemetry: Option<GeminiTelemetrySettings>,
+}
+
+// ============================================================================
+// JSONC Helpers
+// ============================================================================
+
+/// Get Gemini settings.json file path
+pub fn get_gemini_settings_path() -> Result<PathBuf, String> {
+    Ok(get_gemini_dir()?.join("settings.json"))
+}
+
+/// Parse settings.json content (comments and trailing commas allowed)
+pub(crate) fn parse_settings_value(content: &str) -> Result<Value, String> {
+    let value = jsonc_parser::parse_to_serde_value(content, &ParseOptions::default())
+        .map_err(|e| format!("Failed to parse settings.json: {}", e))?;
+    Ok(value.unwrap_or_else(|| Value::Object(Default::default())))
+}
+
+/// Read settings.json as a JSON value (empty object when missing)
+pub(crate) fn read_settings_value(path: &Path) -> Result<Value, String> {
+    if !path.exists() {
+        return Ok(Value::Object(Default::default()));
+    }
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read settings.json: {}", e))?;
+    parse_settings_value(&content)
+}
+
+fn to_cst_value(value: &Value) -> CstInputValue {
+    match value {
+        Value::Null => CstInputValue::Null,
+        Value::Bool(b) => CstInputValue::Bool(*b),
+        Value::Number(n) => CstInputValue::Number(n.to_string()),
+        Value::String(s) => CstInputValue::String(s.clone()),
+        Value::Array(items) => CstInputValue::Array(items.iter().map(to_cst_value).collect()),
+        Value::Object(map) => CstInputValue::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), to_cst_value(v)))
+                .collect(),
+        ),
+    }
+}
+
+/// Set (or with `None`, remove) the property at `path`, creating parent objects as needed
+pub(crate) fn set_property(root: &CstObject, path: &[&str], value: Option<&Value>) {
+    let Some((last, parents)) = path.split_last() else {
+        return;
+    };
+
+    let mut object = root.clone();
+    for parent in parents {
+        object = match value {
+            Some(_) => object.object_value_or_set(parent),
+            None => match object.object_value(parent) {
+                Some(child) => child,
+                None => return,
+            },
+        };
+    }
+
+    match (object.get(last), value) {
+        (Some(prop), Some(value)) => prop.set_value(to_cst_value(value)),
+        (Some(prop), None) => prop.remove(),
+        (None, Some(value)) => {
+            object.append(last, to_cst_value(value));
+        }
+        (None, None) => {}
+    }
+}
+
+/// Apply `edit` to settings.json, keeping everything it doesn't touch byte-for-byte
+pub(crate) fn edit_settings_file(
+    path: &Path,
+    edit: impl FnOnce(&CstObject, &Value) -> Result<(), String>,
+) -> Result<(), String> {
+    let content = if path.exists() {
+        fs::read_to_string(path).map_err(|e| format!("Failed to read settings.json: {}", e))?
+    } else {
+        String::new()
+    };
+    let current = parse_settings_value(&content)?;
+    if !current.is_object() {
+        return Err("settings.json root must be an object".to_string());
+    }
+
+    let root = CstRootNode::parse(&content, &ParseOptions::default())
+        .map_err(|e| format!("Failed to parse settings.json: {}", e))?;
+    edit(&root.object_value_or_set(), &current)?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let mut output = root.to_string();
+    if !output.ends_with('\n') {
+        output.push('\n');
+    }
+    fs::write(path, output).map_err(|e| format!("Failed to write settings.json: {}", e))
+}
+
+// ============================================================================
+// Schema
+// ============================================================================
+
+fn string_at<'a>(root: &'a Value, path: &[&str]) -> Option<&'a str> {
+    path.iter()
+        .try_fold(root, |value, key| value.get(key))
+        .and_then(Value::as_str)
+}
+
+/// Check the managed sections for values Gemini CLI would reject
+fn validate_settings(root: &Value) -> Vec<String> {
+    let mut issues = Vec::new();
+    let mut expect = |path: &str, value: Option<&Value>, ok: fn(&Value) -> bool, kind: &str| {
+        if let Some(value) = value.filter(|v| !ok(v)) {
+            issues.push(format!("\"{}\" should be {}, found {}", path, kind, value));
+        }
+    };
+
+    expect(
+        "security.auth.selectedType",
+        root.pointer("/security/auth/selectedType"),
+        Value::is_string,
+        "a string",
+    );
+    expect(
+        "model",
+        root.get("model"),
+        |v| v.is_string() || v.is_object(),
+        "a string or an object",
+    );
+    expect(
+        "model.name",
+        root.pointer("/model/name"),
+        Value::is_string,
+        "a string",
+    );
+    expect(
+        "mcpServers",
+        root.get("mcpServers"),
+        Value::is_object,
+        "an object",
+    );
+    expect(
+        "telemetry",
+        root.get("telemetry"),
+        Value::is_object,
+        "an object",
+    );
+    expect(
+        "telemetry.enabled",
+        root.pointer("/telemetry/enabled"),
+        Value::is_boolean,
+        "a boolean",
+    );
+    expect(
+        "telemetry.logPrompts",
+        root.pointer("/telemetry/logPrompts"),
+        Value::is_boolean,
+        "a boolean",
+    );
+
+    if let Some(auth) = auth_type(root).filter(|a| !AUTH_TYPES.contains(a)) {
+        issues.push(format!("Unknown auth type \"{}\"", auth));
+    }
+    if let Some(servers) = root.get("mcpServers").and_then(Value::as_object) {
+        for (id, spec) in servers {
+            if let Err(e) = validate_server(id, spec) {
+                issues.push(e);
+            }
+        }
+    }
+    issues
+}
+
+/// An MCP server needs a command (stdio) or a url/httpUrl (remote)
+fn validate_server(id: &str, spec: &Value) -> Result<(), String> {
+    let has = |key: &str| {
+        spec.get(key)
+            .and_then(Value::as_str)
+            .is_some_and(|s| !s.trim().is_empty())
+    };
+    if !spec.is_object() {
+        return Err(format!("MCP server \"{}\" must be an object", id));
+    }
+    if !has("command") && !has("url") && !has("httpUrl") {
+        return Err(format!(
+            "MCP server \"{}\" needs a command, url or httpUrl",
+            id
+        ));
+    }
+    Ok(())
+}
+
+fn auth_type(root: &Value) -> Option<&str> {
+    // Older Gemini CLI versions used a top-level `selectedAuthType`
+    string_at(root, &["security", "auth", "selectedType"])
+        .or_else(|| string_at(root, &["selectedAuthType"]))
+}
+
+fn model(root: &Value) -> Option<&str> {
+    // Older Gemini CLI versions used a plain string `model`
+    string_at(root, &["model", "name"]).or_else(|| string_at(root, &["model"]))
+}
+
+fn parse_settings(root: &Value) -> GeminiSettings {
+    let telemetry = &root["telemetry"];
+    let mcp_servers = root
+        .get("mcpServers")
+        .and_then(Value::as_object)
+        .map(|servers| {
+            servers
+                .iter()
+                .map(|(id, spec)| {
+                    let mut spec = spec.clone();
+                    crate::gemini_mcp::from_gemini_server(&mut spec);
+                    (id.clone(), spec)
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    GeminiSettings {
+        auth_type: auth_type(root).map(String::from),
+        model: model(root).map(String::from),
+        mcp_servers,
+        telemetry: GeminiTelemetrySettings {
+            enabled: telemetry["enabled"].as_bool(),
+            target: telemetry["target"].as_str().map(String::from),
+            otlp_endpoint: telemetry["otlpEndpoint"].as_str().map(String::from),
+            log_prompts: telemetry["logPrompts"].as_bool(),
+        },
+        issues: validate_settings(root),
+    }
+}
+
+fn validate_update(update: &GeminiSettingsUpdate) -> Result<(), String> {
+    if let Some(auth) = update.auth_type.as_deref().map(str::trim) {
+        if !auth.is_empty() && !AUTH_TYPES.contains(&auth) {
+            return Err(format!(
+                "Invalid auth type: {}. Valid values are: {}",
+                auth,
+                AUTH_TYPES.join(", ")
+            ));
+        }
+    }
+    if let Some(telemetry) = &update.telemetry {
+        if let Some(target) = telemetry.target.as_deref() {
+            if !TELEMETRY_TARGETS.contains(&target) {
+                return Err(format!(
+                    "Invalid telemetry target: {}. Valid values are: {}",
+                    target,
+                    TELEMETRY_TARGETS.join(", ")
+                ));
+            }
+        }
+        if let Some(endpoint) = telemetry.otlp_endpoint.as_deref() {
+            reqwest::Url::parse(endpoint)
+                .map_err(|e| format!("Invalid OTLP endpoint {}: {}", endpoint, e))?;
+        }
+    }
+    Ok(())
+}
+
+/// Write the managed properties of an update into the CST
+fn apply_update(
+    root: &CstObject,
+    current: &Value,
+    update: &GeminiSettingsUpdate,
+) -> Result<(), String> {
+    let string_value = |s: &str| {
+        let s = s.trim();
+        (!s.is_empty()).then(|| Value::String(s.to_string()))
+    };
+
+    if let Some(auth) = &update.auth_type {
+        let value = string_value(auth);
+        if current.get("selectedAuthType").is_some() {
+            set_property(root, &["selectedAuthType"], value.as_ref());
+        } else {
+            set_property(root, &["security", "auth", "selectedType"], value.as_ref());
+        }
+    }
+
+    if let Some(model) = &update.model {
+        let value = string_value(model);
+        if current.get("model").is_some_and(Value::is_string) {
+            set_property(root, &["model"], value.as_ref());
+        } else {
+            set_property(root, &["model", "name"], value.as_ref());
+        }
+    }
+
+    if let Some(telemetry) = &update.telemetry {
+        let fields = [
+            ("enabled", telemetry.enabled.map(Value::Bool)),
+            ("target", telemetry.target.clone().map(Value::String)),
+            (
+                "otlpEndpoint",
+                telemetry.otlp_endpoint.clone().map(Value::String),
+            ),
+            ("logPrompts", telemetry.log_prompts.map(Value::Bool)),
+        ];
+        for (key, value) in &fields {
+            set_property(root, &["telemetry", key], value.as_ref());
+        }
+    }
+
+    if let Some(servers) = &update.mcp_servers {
+        write_mcp_servers(root, current, servers)?;
+    }
+    Ok(())
+}
+
+/// Replace the `mcpServers` set, only rewriting the entries that changed
+pub(crate) fn write_mcp_servers(
+    root: &CstObject,
+    current: &Value,
+    servers: &HashMap<String, Value>,
+) -> Result<(), String> {
+    let existing = current.get("mcpServers").and_then(Value::as_object);
+
+    if let Some(existing) = existing {
+        for id in existing.keys().filter(|id| !servers.contains_key(*id)) {
+            set_property(root, &["mcpServers", id.as_str()], None);
+        }
+    }
+
+    let mut ids: Vec<&String> = servers.keys().collect();
+    ids.sort();
+    for id in ids {
+        let spec = crate::gemini_mcp::to_gemini_server(id, &servers[id])?;
+        validate_server(id, &spec)?;
+        if existing.and_then(|e| e.get(id)) != Some(&spec) {
+            set_property(root, &["mcpServers", id.as_str()], Some(&spec));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read the workbench-managed settings from Gemini's settings.json
+#[tauri::command]
+pub async fn get_gemini_settings() -> Result<GeminiSettings, String> {
+    let root = read_settings_value(&get_gemini_settings_path()?)?;
+    Ok(parse_settings(&root))
+}
+
+/// Update settings in Gemini's settings.json, preserving comments and formatting
+#[tauri::command]
+pub async fn update_gemini_settings(
+    update: GeminiSettingsUpdate,
+) -> Result<GeminiSettings, String> {
+    log::info!("[Gemini] Updating settings.json");
+    validate_update(&update)?;
+
+    let path = get_gemini_settings_path()?;
+    edit_settings_file(&path, |root, current| apply_update(root, current, &update))?;
+
+    let root = read_settings_value(&path)?;
+    Ok(parse_settings(&root))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+  // Account
+  "security": { "auth": { "selectedType": "oauth-personal" } },
+  "model": "gemini-2.5-pro", // legacy layout
+  "mcpServers": {
+    "docs": { "command": "node", "args": ["docs.js"] }
+  }
+}"#;
+
+    fn apply(content: &str, update: &GeminiSettingsUpdate) -> String {
+        let current = parse_settings_value(content).unwrap();
+        let root = CstRootNode::parse(content, &ParseOptions::default()).unwrap();
+        apply_update(&root.object_value_or_set(), &current, update).unwrap();
+        root.to_string()
+    }
+
+    #[test]
+    fn test_update_preserves_comments_and_layout() {
+        let update = GeminiSettingsUpdate {
+            model: Some("gemini-2.5-flash".to_string()),
+            telemetry: Some(GeminiTelemetrySettings {
+                enabled: Some(false),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let output = apply(SAMPLE, &update);
+
+        assert!(output.contains("// Account"));
+        assert!(output.contains("\"model\": \"gemini-2.5-flash\", // legacy layout"));
+
+        let settings = parse_settings(&parse_settings_value(&output).unwrap());
+        assert_eq!(settings.auth_type.as_deref(), Some("oauth-personal"));
+        assert_eq!(settings.telemetry.enabled, Some(false));
+        assert!(settings.mcp_servers.contains_key("docs"));
+        assert!(settings.issues.is_empty());
+    }
+
+    #[test]
+    fn test_validation() {
+        let root = serde_json::json!({
+            "security": { "auth": { "selectedType": "password" } },
+            "telemetry": { "enabled": "yes" },
+            "mcpServers": { "broken": { "args": [] } }
+        });
+        assert_eq!(validate_settings(&root).len(), 3);
+
+        let update = GeminiSettingsUpdate {
+            auth_type: Some("password".to_string()),
+            ..Default::default()
+        };
+        assert!(validate_update(&update).is_err());
+    }
+}
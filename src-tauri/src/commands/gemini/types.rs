@@ -408,4 +408,7 @@ pub struct GeminiSessionInfo {
     pub file_name: String,
     pub start_time: String,
     pub first_message: Option<String>,
+    /// Generated or user-assigned title
+    #[serde(default)]
+    pub title: Option<String>,
 }
@@ -7,7 +7,7 @@
  * - Model-level statistics
  * - Per-project statistics
  */
-use chrono::NaiveDate;
+use chrono::{DateTime, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -32,6 +32,19 @@ pub struct GeminiSessionUsage {
     pub output_tokens: u64,
     pub start_time: String,
     pub first_message: Option<String>,
+    /// Per-message usage, so records are dated by when the tokens were spent
+    #[serde(skip)]
+    messages: Vec<MessageUsage>,
+}
+
+/// Tokens one message of a session used
+#[derive(Debug, Clone)]
+struct MessageUsage {
+    timestamp: DateTime<Utc>,
+    /// Model that answered, or the last one seen before
+    model: String,
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -198,6 +211,10 @@ fn parse_session_for_usage(
     let mut total_output_tokens: u64 = 0;
     let mut model = "gemini-3-flash".to_string();
     let mut first_message: Option<String> = None;
+    let mut messages: Vec<MessageUsage> = Vec::new();
+    let session_start = DateTime::parse_from_rfc3339(&detail.start_time)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc));
 
     for message in &detail.messages {
         // Extract model if available
@@ -207,11 +224,25 @@ fn parse_session_for_usage(
 
         // Extract tokens if available
         if let Some(tokens) = message.get("tokens").and_then(|v| v.as_object()) {
-            if let Some(input) = tokens.get("input").and_then(|v| v.as_u64()) {
-                total_input_tokens += input;
-            }
-            if let Some(output) = tokens.get("output").and_then(|v| v.as_u64()) {
-                total_output_tokens += output;
+            let input = tokens.get("input").and_then(|v| v.as_u64()).unwrap_or(0);
+            let output = tokens.get("output").and_then(|v| v.as_u64()).unwrap_or(0);
+            total_input_tokens += input;
+            total_output_tokens += output;
+
+            let timestamp = message
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+                .map(|dt| dt.with_timezone(&Utc))
+                .or(session_start);
+            match timestamp {
+                Some(timestamp) if input > 0 || output > 0 => messages.push(MessageUsage {
+                    timestamp,
+                    model: model.clone(),
+                    input_tokens: input,
+                    output_tokens: output,
+                }),
+                _ => {}
             }
         }
 
@@ -245,6 +276,7 @@ fn parse_session_for_usage(
         output_tokens: total_output_tokens,
         start_time: detail.start_time,
         first_message,
+        messages,
     })
 }
 
@@ -303,6 +335,34 @@ fn collect_all_sessions() -> Vec<GeminiSessionUsage> {
     sessions
 }
 
+/// A session's usage as engine-neutral records, one per message
+fn session_records(session: GeminiSessionUsage) -> Vec<crate::usage::UsageRecord> {
+    session
+        .messages
+        .into_iter()
+        .map(|message| crate::usage::UsageRecord {
+            engine: "gemini",
+            session_id: session.session_id.clone(),
+            project_path: session.project_path.clone(),
+            cost: calculate_cost(&message.model, message.input_tokens, message.output_tokens),
+            model: message.model,
+            timestamp: message.timestamp,
+            input_tokens: message.input_tokens,
+            output_tokens: message.output_tokens,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+        })
+        .collect()
+}
+
+/// Gemini usage as engine-neutral records, one per message
+pub(crate) fn usage_records() -> Vec<crate::usage::UsageRecord> {
+    collect_all_sessions()
+        .into_iter()
+        .flat_map(session_records)
+        .collect()
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -450,3 +510,46 @@ pub async fn get_gemini_usage_stats(
         sessions: filtered_sessions,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_usage_records_are_dated_per_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let session = serde_json::json!({
+            "sessionId": "s1",
+            "projectHash": "abc",
+            "startTime": "2025-11-01T23:00:00Z",
+            "lastUpdated": "2025-11-03T09:00:00Z",
+            "messages": [
+                {"type": "user", "content": "hi", "timestamp": "2025-11-01T23:00:00Z"},
+                {"type": "gemini", "model": "gemini-2.5-pro", "timestamp": "2025-11-01T23:00:05Z",
+                 "tokens": {"input": 100, "output": 10}},
+                {"type": "gemini", "model": "gemini-2.5-flash", "timestamp": "2025-11-03T09:00:00Z",
+                 "tokens": {"input": 200, "output": 20}}
+            ]
+        });
+        std::fs::write(&path, session.to_string()).unwrap();
+
+        let session = parse_session_for_usage(&path, "abc").unwrap();
+        assert_eq!((session.input_tokens, session.output_tokens), (300, 30));
+        let records = session_records(session);
+        let summary: Vec<(String, &str, u64)> = records
+            .iter()
+            .map(|r| {
+                let day = r.timestamp.format("%Y-%m-%d").to_string();
+                (day, r.model.as_str(), r.input_tokens)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("2025-11-01".to_string(), "gemini-2.5-pro", 100),
+                ("2025-11-03".to_string(), "gemini-2.5-flash", 200),
+            ]
+        );
+    }
+}
@@ -0,0 +1,3411 @@
+//! Git backends for simple_git
+//!
+//! `GitBackend` abstracts the repository operations the checkpoint/rewind
+//! machinery needs. `Git2Backend` runs them in-process with libgit2, so they
+//! work without a `git` binary in PATH and avoid a subprocess per status
+//! poll. `CliBackend` shells out to `git` as before and is used as the
+//! fallback where libgit2 would do the wrong thing: WSL projects (git must run
+//! inside the distro), repositories using sparse checkout, Git LFS filters or
+//! commit hooks (none is honored by libgit2), and anything libgit2 cannot open.
+//! When no `git` binary exists, libgit2 is always used.
+
+use chrono::{DateTime, Utc};
+use git2::{
+    build::CheckoutBuilder, ApplyLocation, BranchType, Diff, DiffFormat, DiffOptions, Email,
+    EmailCreateOptions, ErrorCode, IndexAddOption, ObjectType, Oid, Repository, ResetType,
+    Signature, Sort, StashApplyOptions, StashFlags, StatusOptions, WorktreeAddOptions,
+    WorktreeLockStatus, WorktreePruneOptions,
+};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Output, Stdio};
+
+use crate::commands::commit_metadata;
+use crate::commands::commit_signing;
+use crate::commands::engine_safety::project_key;
+use crate::commands::git_executor;
+use crate::commands::git_identity::{self, GitIdentity};
+use crate::commands::profiling;
+use crate::commands::wsl_utils::{self, git_command};
+
+/// A local branch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BranchInfo {
+    pub name: String,
+    /// Commit the branch points at
+    pub commit: String,
+    pub is_current: bool,
+}
+
+/// A tag and the commit it points at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TagInfo {
+    pub name: String,
+    pub commit: String,
+    /// Subject of an annotated tag's message; None for lightweight tags
+    pub message: Option<String>,
+}
+
+/// A commit copied onto another branch
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PickedCommit {
+    pub source: String,
+    pub commit: String,
+}
+
+/// A commit that could not be cherry-picked cleanly
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CherryPickConflict {
+    pub commit: String,
+    pub paths: Vec<String>,
+}
+
+/// Outcome of cherry-picking onto a branch; on a conflict nothing is picked
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CherryPickResult {
+    pub picked: Vec<PickedCommit>,
+    pub conflict: Option<CherryPickConflict>,
+}
+
+/// A submodule registered in `.gitmodules`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleInfo {
+    pub path: String,
+    pub url: Option<String>,
+    /// Commit the superproject's index points at
+    pub recorded_commit: Option<String>,
+    /// Commit checked out in the submodule; `None` when it is not initialized
+    pub checked_out_commit: Option<String>,
+    /// Checked out at a different commit than recorded, so `git add -A` would move the pointer
+    pub out_of_sync: bool,
+}
+
+impl SubmoduleInfo {
+    fn new(
+        path: String,
+        url: Option<String>,
+        recorded_commit: Option<String>,
+        checked_out_commit: Option<String>,
+    ) -> Self {
+        let out_of_sync = checked_out_commit.is_some() && checked_out_commit != recorded_commit;
+        Self {
+            path,
+            url,
+            recorded_commit,
+            checked_out_commit,
+            out_of_sync,
+        }
+    }
+}
+
+/// A working tree of a repository: the main checkout or a linked worktree
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeInfo {
+    pub path: String,
+    /// Checked-out commit (None when the worktree directory is gone)
+    pub head: Option<String>,
+    /// Checked-out branch, None when detached
+    pub branch: Option<String>,
+    pub is_main: bool,
+    pub locked: bool,
+    /// The worktree directory no longer exists; `git worktree prune` would drop it
+    pub prunable: bool,
+}
+
+/// An entry of the stash, newest first
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashEntry {
+    /// Position in `stash@{n}`
+    pub index: usize,
+    /// Stash commit, stable while the entry's index shifts
+    pub commit: String,
+    /// Message without git's `On <branch>:` prefix
+    pub message: String,
+    pub branch: Option<String>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A commit of the history, with its change counts
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    pub date: Option<DateTime<Utc>>,
+    pub subject: String,
+    /// Engine that made the commit, see [`commit_metadata::parse`]
+    pub engine: Option<String>,
+    pub session_id: Option<String>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl CommitInfo {
+    /// A commit without its change counts
+    fn new(
+        hash: String,
+        author_name: String,
+        author_email: String,
+        seconds: Option<i64>,
+        message: &str,
+    ) -> Self {
+        let metadata = commit_metadata::parse(message);
+        Self {
+            hash,
+            author_name,
+            author_email,
+            date: seconds.and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+            subject: message.lines().next().unwrap_or("").trim().to_string(),
+            engine: metadata.engine,
+            session_id: metadata.session_id,
+            files_changed: 0,
+            insertions: 0,
+            deletions: 0,
+        }
+    }
+}
+
+/// A path with uncommitted changes, with `git status --porcelain` codes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusEntry {
+    /// Relative to the repository root
+    pub path: String,
+    /// Index state: `A`, `M`, `D`, `R`, `T`, `U` (conflict), `?` (untracked) or ` `
+    pub staged: char,
+    /// Working tree state, same codes
+    pub unstaged: char,
+}
+
+/// The commit that last changed a line of a file
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BlameLine {
+    /// 1-based line number in the working tree file
+    pub line: usize,
+    /// `None` for lines that are not committed yet
+    pub commit: Option<String>,
+    pub author_name: String,
+    pub author_email: String,
+    pub date: Option<DateTime<Utc>>,
+    pub engine: Option<String>,
+    pub session_id: Option<String>,
+}
+
+impl BlameLine {
+    fn new(line: usize, commit: Option<&CommitInfo>) -> Self {
+        match commit {
+            Some(info) => Self {
+                line,
+                commit: Some(info.hash.clone()),
+                author_name: info.author_name.clone(),
+                author_email: info.author_email.clone(),
+                date: info.date,
+                engine: info.engine.clone(),
+                session_id: info.session_id.clone(),
+            },
+            None => Self {
+                line,
+                commit: None,
+                author_name: String::new(),
+                author_email: String::new(),
+                date: None,
+                engine: None,
+                session_id: None,
+            },
+        }
+    }
+}
+
+/// Which commits `log_page` returns; every given condition must match
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFilter {
+    /// Case-insensitive part of the author name or email
+    pub author: Option<String>,
+    /// An engine name, or `user` for commits without one
+    pub engine: Option<String>,
+    /// Only commits tagged with this session
+    #[serde(default)]
+    pub session_id: Option<String>,
+}
+
+impl LogFilter {
+    fn matches(&self, commit: &CommitInfo) -> bool {
+        if let Some(author) = &self.author {
+            let author = author.to_lowercase();
+            if !commit.author_name.to_lowercase().contains(&author)
+                && !commit.author_email.to_lowercase().contains(&author)
+            {
+                return false;
+            }
+        }
+        if let Some(session_id) = &self.session_id {
+            if commit.session_id.as_ref() != Some(session_id) {
+                return false;
+            }
+        }
+        match &self.engine {
+            Some(engine) => commit.engine.as_deref().unwrap_or("user") == engine,
+            None => true,
+        }
+    }
+}
+
+/// Resets that keep the working tree files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResetMode {
+    /// Keep the undone commits' changes staged
+    Soft,
+    /// Keep them as unstaged changes
+    Mixed,
+}
+
+/// Why reverting a range did not apply
+#[derive(Debug)]
+pub enum RevertFailure {
+    /// The changes conflict with later work; nothing was changed
+    Conflict(String),
+    Failed(String),
+}
+
+/// Repository operations used by simple_git
+pub trait GitBackend: Send + Sync {
+    fn name(&self) -> &'static str;
+    /// Create an empty repository
+    fn init(&self, project_path: &str) -> Result<(), String>;
+    /// Set the commit identity in the repository's local config
+    fn configure_identity(&self, project_path: &str, name: &str, email: &str)
+        -> Result<(), String>;
+    /// The identity git would commit with (local, global or system config), if any
+    fn configured_identity(&self, project_path: &str) -> Result<Option<GitIdentity>, String>;
+    fn current_commit(&self, project_path: &str) -> Result<String, String>;
+    fn current_branch(&self, project_path: &str) -> Result<String, String>;
+    fn remote_url(&self, project_path: &str, remote: &str) -> Result<String, String>;
+    /// Whether the working tree has staged, unstaged or untracked changes
+    fn has_uncommitted_changes(&self, project_path: &str) -> Result<bool, String>;
+    /// Paths with staged, unstaged or untracked changes, relative to the repository root
+    fn changed_paths(&self, project_path: &str) -> Result<Vec<String>, String>;
+    /// Uncommitted changes, or only those at or under `paths`
+    fn status(
+        &self,
+        project_path: &str,
+        paths: Option<&[String]>,
+    ) -> Result<Vec<StatusEntry>, String>;
+    /// Stage all changes, or only those under `pathspec`
+    fn stage_all(&self, project_path: &str, pathspec: Option<&str>) -> Result<(), String>;
+    /// Commit the index, even when it matches HEAD
+    fn commit(&self, project_path: &str, message: &str) -> Result<(), String>;
+    /// Like `commit`, with a committer other than the author
+    fn commit_as(
+        &self,
+        project_path: &str,
+        message: &str,
+        committer: &GitIdentity,
+    ) -> Result<(), String>;
+    /// Whether two commits have different trees
+    fn has_changes_between(&self, project_path: &str, from: &str, to: &str)
+        -> Result<bool, String>;
+    fn reset_hard(&self, project_path: &str, commit: &str) -> Result<(), String>;
+    /// Move HEAD (and with `Mixed`, the index) to `commit`, leaving the working tree alone
+    fn reset(&self, project_path: &str, commit: &str, mode: ResetMode) -> Result<(), String>;
+    /// Bring `paths` (files or directories) in the index and working tree back to
+    /// their state at `commit`; files that did not exist there are deleted
+    fn restore_paths(
+        &self,
+        project_path: &str,
+        commit: &str,
+        paths: &[String],
+    ) -> Result<(), String>;
+    /// Commits reachable from `to` but not from `from`
+    fn commit_count_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<usize, String>;
+    /// Subjects of the commits in `from..to`, newest first
+    fn log_between(&self, project_path: &str, from: &str, to: &str) -> Result<Vec<String>, String>;
+    /// Full messages of the commits in `from..to`, newest first
+    fn messages_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<String>, String>;
+    /// Commits reachable from HEAD matching `filter`, newest first, skipping `offset` of them
+    fn log_page(
+        &self,
+        project_path: &str,
+        offset: usize,
+        limit: usize,
+        filter: &LogFilter,
+    ) -> Result<Vec<CommitInfo>, String>;
+    /// Fill in the change counts of commits (against their first parent)
+    fn fill_stats(&self, project_path: &str, commits: &mut [CommitInfo]) -> Result<(), String>;
+    /// Commits recorded in the reflogs that no ref reaches any more, newest first, without stats
+    fn orphaned_commits(&self, project_path: &str) -> Result<Vec<CommitInfo>, String>;
+    /// Last commit of every line of `path` (repository-relative) as it is in the working tree
+    fn blame_file(&self, project_path: &str, path: &str) -> Result<Vec<BlameLine>, String>;
+    /// Stash uncommitted changes including untracked files, returning the stash commit
+    fn stash_save(&self, project_path: &str, message: &str) -> Result<String, String>;
+    /// Record uncommitted changes as a stash entry but leave the working tree and index as they are
+    fn stash_snapshot(&self, project_path: &str, message: &str) -> Result<String, String>;
+    fn stash_list(&self, project_path: &str) -> Result<Vec<StashEntry>, String>;
+    /// Apply `stash@{index}` and keep it; fails on conflicts with local changes
+    fn stash_apply(&self, project_path: &str, index: usize) -> Result<(), String>;
+    /// Apply `stash@{index}` and drop it if it applied cleanly
+    fn stash_pop(&self, project_path: &str, index: usize) -> Result<(), String>;
+    fn stash_drop(&self, project_path: &str, index: usize) -> Result<(), String>;
+    /// Undo the changes of `from..to` in the working tree and index, without committing
+    fn revert_range(&self, project_path: &str, from: &str, to: &str) -> Result<(), RevertFailure>;
+    fn submodules(&self, project_path: &str) -> Result<Vec<SubmoduleInfo>, String>;
+    /// Check out every submodule at its recorded commit, initializing them first with `init`
+    fn update_submodules(&self, project_path: &str, init: bool) -> Result<(), String>;
+    /// Stage `paths` (files or directories) as they are in the working tree, deletions included
+    fn stage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String>;
+    /// Reset the index entries of `paths` to HEAD, leaving the working tree alone
+    fn unstage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String>;
+    /// Copy `commits`, in order, onto the tip of `target_branch` (`cherry-pick -x`)
+    ///
+    /// All or nothing: when one conflicts the branch is left as it was. When the
+    /// branch is checked out, its working tree is updated and must be clean.
+    fn cherry_pick(
+        &self,
+        project_path: &str,
+        commits: &[String],
+        target_branch: &str,
+    ) -> Result<CherryPickResult, String>;
+    /// Object id of `path` in `commit`'s tree (for a submodule, the commit it points at)
+    fn tree_entry_id(
+        &self,
+        project_path: &str,
+        commit: &str,
+        path: &str,
+    ) -> Result<Option<String>, String>;
+    /// Number of parents of `commit` (0 for a root commit, 2 or more for a merge)
+    fn parent_count(&self, project_path: &str, commit: &str) -> Result<usize, String>;
+    fn list_branches(&self, project_path: &str) -> Result<Vec<BranchInfo>, String>;
+    /// Create a branch at `start_point` (HEAD when `None`)
+    fn create_branch(
+        &self,
+        project_path: &str,
+        name: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String>;
+    /// Check out a branch, refusing to overwrite conflicting local changes
+    fn switch_branch(&self, project_path: &str, name: &str) -> Result<(), String>;
+    /// Delete a branch; without `force`, only if it is merged into HEAD
+    fn delete_branch(&self, project_path: &str, name: &str, force: bool) -> Result<(), String>;
+    /// Tags pointing at commits, sorted by name
+    fn list_tags(&self, project_path: &str) -> Result<Vec<TagInfo>, String>;
+    /// Tag `commit` (HEAD when `None`); annotated when a message is given
+    fn create_tag(
+        &self,
+        project_path: &str,
+        name: &str,
+        commit: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<(), String>;
+    fn delete_tag(&self, project_path: &str, name: &str) -> Result<(), String>;
+    /// Create `branch` at `start_point` (HEAD when `None`) and check it out at `worktree_path`
+    fn add_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        branch: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String>;
+    /// All working trees, the main one first
+    fn list_worktrees(&self, project_path: &str) -> Result<Vec<WorktreeInfo>, String>;
+    /// Delete a linked worktree; without `force`, only if it is clean and unlocked
+    fn remove_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        force: bool,
+    ) -> Result<(), String>;
+    /// Unified diff (`git diff` format, renames detected) between two commits
+    fn diff_commits(&self, project_path: &str, from: &str, to: &str) -> Result<String, String>;
+    /// Unified diff of the working tree against HEAD, staged and untracked files included
+    fn diff_working_tree(&self, project_path: &str) -> Result<String, String>;
+    /// Unified diff of the unstaged changes to tracked file `path` (working tree against the index)
+    fn diff_unstaged(&self, project_path: &str, path: &str) -> Result<String, String>;
+    /// `git format-patch --stdout` mailbox of the non-merge commits in `from..to`, oldest first
+    fn format_patch(&self, project_path: &str, from: &str, to: &str) -> Result<String, String>;
+    /// Apply a mailbox or plain `git diff` to the working tree and index, all or nothing;
+    /// the working tree must be clean
+    fn apply_patch(&self, project_path: &str, patch: &str) -> Result<(), String>;
+    /// Apply a plain `git diff` to the index only, leaving the working tree alone
+    fn apply_to_index(&self, project_path: &str, patch: &str) -> Result<(), String>;
+}
+
+// ============================================================================
+// Backend selection
+// ============================================================================
+
+static CLI_AVAILABLE: Lazy<bool> = Lazy::new(|| {
+    let mut cmd = std::process::Command::new("git");
+    cmd.arg("--version");
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+    }
+    let available = cmd.output().map(|o| o.status.success()).unwrap_or(false);
+    if !available {
+        log::info!("[GitBackend] git not found in PATH, using libgit2 for all repositories");
+    }
+    available
+});
+
+/// Hooks run by `git commit`, which libgit2 never runs
+const COMMIT_HOOKS: &[&str] = &[
+    "pre-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+];
+
+/// Whether any `.gitattributes` of the repository (nested ones included) uses LFS
+fn uses_lfs(repo: &Repository) -> bool {
+    let mut files = vec![repo.path().join("info").join("attributes")];
+    if let Some(workdir) = repo.workdir() {
+        // The root file may not be committed yet
+        files.push(workdir.join(".gitattributes"));
+        if let Ok(index) = repo.index() {
+            files.extend(index.iter().filter_map(|entry| {
+                let path = String::from_utf8_lossy(&entry.path);
+                path.ends_with("/.gitattributes")
+                    .then(|| workdir.join(path.as_ref()))
+            }));
+        }
+    }
+    files.iter().any(|file| {
+        std::fs::read_to_string(file).is_ok_and(|attributes| attributes.contains("filter=lfs"))
+    })
+}
+
+/// Whether `git commit` would run a hook (from `core.hooksPath` or the hooks directory)
+fn has_commit_hooks(repo: &Repository, config: &git2::Config) -> bool {
+    let hooks_dir = match config.get_path("core.hooksPath") {
+        Ok(path) if path.is_relative() => match repo.workdir() {
+            Some(workdir) => workdir.join(path),
+            None => repo.path().join(path),
+        },
+        Ok(path) => path,
+        Err(_) => repo.commondir().join("hooks"),
+    };
+    COMMIT_HOOKS.iter().any(|hook| {
+        let Ok(metadata) = std::fs::metadata(hooks_dir.join(hook)) else {
+            return false;
+        };
+        // git skips hooks that are not executable
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        }
+        #[cfg(not(unix))]
+        {
+            metadata.is_file()
+        }
+    })
+}
+
+/// Features of a repository libgit2 does not honor
+fn needs_cli(project_path: &str) -> bool {
+    match Repository::discover(Path::new(project_path)) {
+        Ok(repo) => {
+            uses_lfs(&repo)
+                || repo
+                    .config()
+                    .map(|config| {
+                        config.get_bool("core.sparseCheckout").unwrap_or(false)
+                            // libgit2 cannot sign commits
+                            || commit_signing::required(&config)
+                            || has_commit_hooks(&repo, &config)
+                    })
+                    .unwrap_or(false)
+        }
+        // Not a repository yet (init) is fine; anything else libgit2 cannot open goes to the CLI
+        Err(e) => e.code() != ErrorCode::NotFound,
+    }
+}
+
+/// The backend to use for a project
+pub fn backend_for(project_path: &str) -> &'static dyn GitBackend {
+    if !*CLI_AVAILABLE {
+        return &Git2Backend;
+    }
+    if wsl_utils::detect_wsl_project(project_path).is_some() || needs_cli(project_path) {
+        &CliBackend
+    } else {
+        &Git2Backend
+    }
+}
+
+// ============================================================================
+// libgit2 backend
+// ============================================================================
+
+pub struct Git2Backend;
+
+fn open(project_path: &str) -> Result<Repository, String> {
+    // Like the git CLI, a subdirectory opens the enclosing repository
+    Repository::discover(project_path).map_err(|e| {
+        format!(
+            "Failed to open repository {}: {}",
+            project_path,
+            e.message()
+        )
+    })
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    repo.signature()
+        .or_else(|_| {
+            let fallback = git_identity::settings().fallback;
+            Signature::now(&fallback.name, &fallback.email)
+        })
+        .map_err(|e| format!("Failed to create signature: {}", e.message()))
+}
+
+/// Commit the index on top of HEAD
+fn commit_index(
+    repo: &Repository,
+    message: &str,
+    author: &Signature,
+    committer: &Signature,
+) -> Result<(), String> {
+    // Signing repositories are routed to the CLI; only reached when git is not installed
+    let config = repo.config().map_err(|e| e.message().to_string())?;
+    if commit_signing::required(&config) {
+        return Err(format!(
+            "{}: signed commits need the git command line, which was not found",
+            commit_signing::SIGNING_ERROR_PREFIX
+        ));
+    }
+    let mut index = repo.index().map_err(|e| e.message().to_string())?;
+    let tree_id = index
+        .write_tree()
+        .map_err(|e| format!("Git commit failed: {}", e.message()))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Git commit failed: {}", e.message()))?;
+    let parent = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let parents: Vec<&git2::Commit> = parent.iter().collect();
+    repo.commit(Some("HEAD"), author, committer, message, &tree, &parents)
+        .map(|_| ())
+        .map_err(|e| format!("Git commit failed: {}", e.message()))
+}
+
+fn resolve_commit<'r>(repo: &'r Repository, rev: &str) -> Result<git2::Commit<'r>, String> {
+    repo.revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Unknown revision {}: {}", rev, e.message()))
+}
+
+/// The repository owning the main working tree, also when opened from a linked worktree
+fn main_repository(repo: Repository) -> Result<Repository, String> {
+    if !repo.is_worktree() {
+        return Ok(repo);
+    }
+    Repository::open(repo.commondir())
+        .map_err(|e| format!("Failed to open main repository: {}", e.message()))
+}
+
+/// Render a diff as `git diff` would
+fn patch_text(diff: &mut Diff) -> Result<String, String> {
+    diff.find_similar(None)
+        .map_err(|e| format!("Failed to detect renames: {}", e.message()))?;
+    let mut patch = Vec::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), ' ' | '+' | '-') {
+            patch.push(line.origin() as u8);
+        }
+        patch.extend_from_slice(line.content());
+        true
+    })
+    .map_err(|e| format!("Git diff failed: {}", e.message()))?;
+    Ok(String::from_utf8_lossy(&patch).into_owned())
+}
+
+/// `On main: message` / `WIP on main: abc123 subject` -> (branch, message)
+fn parse_stash_subject(subject: &str) -> (Option<String>, String) {
+    let rest = subject
+        .strip_prefix("WIP on ")
+        .or_else(|| subject.strip_prefix("On "));
+    match rest.and_then(|rest| rest.split_once(": ")) {
+        Some((branch, message)) => (Some(branch.to_string()), message.to_string()),
+        None => (None, subject.to_string()),
+    }
+}
+
+fn stash_entry(index: usize, commit: String, subject: &str, seconds: Option<i64>) -> StashEntry {
+    let (branch, message) = parse_stash_subject(subject);
+    StashEntry {
+        index,
+        commit,
+        message,
+        branch,
+        timestamp: seconds.and_then(|seconds| DateTime::from_timestamp(seconds, 0)),
+    }
+}
+
+fn commit_info(commit: &git2::Commit) -> CommitInfo {
+    let author = commit.author();
+    CommitInfo::new(
+        commit.id().to_string(),
+        author.name().unwrap_or("").to_string(),
+        author.email().unwrap_or("").to_string(),
+        Some(commit.time().seconds()),
+        &String::from_utf8_lossy(commit.message_bytes()),
+    )
+}
+
+/// Change counts against the first parent, like `git log --stat`
+fn commit_stats(repo: &Repository, commit: &git2::Commit) -> Result<(usize, usize, usize), String> {
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let stats = commit
+        .tree()
+        .and_then(|tree| repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None))
+        .and_then(|diff| diff.stats())
+        .map_err(|e| format!("Failed to compute commit stats: {}", e.message()))?;
+    Ok((stats.files_changed(), stats.insertions(), stats.deletions()))
+}
+
+/// Paths with conflicts in an in-memory merge result
+fn conflict_paths(index: &git2::Index) -> Vec<String> {
+    let mut paths: Vec<String> = index
+        .conflicts()
+        .map(|conflicts| {
+            conflicts
+                .filter_map(Result::ok)
+                .filter_map(|conflict| conflict.our.or(conflict.their).or(conflict.ancestor))
+                .map(|entry| String::from_utf8_lossy(&entry.path).into_owned())
+                .collect()
+        })
+        .unwrap_or_default();
+    paths.sort();
+    paths.dedup();
+    paths
+}
+
+fn range_walk<'r>(repo: &'r Repository, from: &str, to: &str) -> Result<git2::Revwalk<'r>, String> {
+    let mut walk = repo.revwalk().map_err(|e| e.message().to_string())?;
+    walk.set_sorting(Sort::TIME)
+        .map_err(|e| e.message().to_string())?;
+    walk.push(resolve_commit(repo, to)?.id())
+        .map_err(|e| e.message().to_string())?;
+    walk.hide(resolve_commit(repo, from)?.id())
+        .map_err(|e| e.message().to_string())?;
+    Ok(walk)
+}
+
+impl GitBackend for Git2Backend {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+
+    fn init(&self, project_path: &str) -> Result<(), String> {
+        Repository::init(project_path)
+            .map(|_| ())
+            .map_err(|e| format!("Git init failed: {}", e.message()))
+    }
+
+    fn configure_identity(
+        &self,
+        project_path: &str,
+        name: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let mut config = repo.config().map_err(|e| e.message().to_string())?;
+        config
+            .set_str("user.name", name)
+            .and_then(|_| config.set_str("user.email", email))
+            .map_err(|e| format!("Failed to set git identity: {}", e.message()))
+    }
+
+    fn configured_identity(&self, project_path: &str) -> Result<Option<GitIdentity>, String> {
+        let repo = open(project_path)?;
+        let config = repo.config().map_err(|e| e.message().to_string())?;
+        let value = |key: &str| {
+            config
+                .get_string(key)
+                .ok()
+                .filter(|value| !value.trim().is_empty())
+        };
+        Ok(value("user.name")
+            .zip(value("user.email"))
+            .map(|(name, email)| GitIdentity { name, email }))
+    }
+
+    fn current_commit(&self, project_path: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let head = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| format!("Git rev-parse failed: {}", e.message()))?;
+        Ok(head.id().to_string())
+    }
+
+    fn current_branch(&self, project_path: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let head = repo
+            .head()
+            .map_err(|e| format!("Git rev-parse failed: {}", e.message()))?;
+        if !head.is_branch() {
+            return Err("HEAD is detached, no current branch".to_string());
+        }
+        head.shorthand()
+            .map(|name| name.to_string())
+            .ok_or_else(|| "Branch name is not valid UTF-8".to_string())
+    }
+
+    fn remote_url(&self, project_path: &str, remote: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let remote = repo
+            .find_remote(remote)
+            .map_err(|e| format!("Git remote get-url failed: {}", e.message()))?;
+        remote
+            .url()
+            .map(|url| url.to_string())
+            .ok_or_else(|| "Remote URL is not valid UTF-8".to_string())
+    }
+
+    fn has_uncommitted_changes(&self, project_path: &str) -> Result<bool, String> {
+        let repo = open(project_path)?;
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| format!("Failed to check status: {}", e.message()))?;
+        Ok(!statuses.is_empty())
+    }
+
+    fn changed_paths(&self, project_path: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .status(project_path, None)?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect())
+    }
+
+    fn status(
+        &self,
+        project_path: &str,
+        paths: Option<&[String]>,
+    ) -> Result<Vec<StatusEntry>, String> {
+        if paths.is_some_and(|paths| paths.is_empty()) {
+            return Ok(Vec::new());
+        }
+        let repo = open(project_path)?;
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        for path in paths.unwrap_or_default() {
+            options.pathspec(path);
+        }
+        let statuses = repo
+            .statuses(Some(&mut options))
+            .map_err(|e| format!("Failed to check status: {}", e.message()))?;
+        Ok(statuses
+            .iter()
+            .filter_map(|entry| {
+                let (staged, unstaged) = status_codes(entry.status());
+                Some(StatusEntry {
+                    path: entry.path()?.to_string(),
+                    staged,
+                    unstaged,
+                })
+            })
+            .collect())
+    }
+
+    fn stage_all(&self, project_path: &str, pathspec: Option<&str>) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let mut index = repo.index().map_err(|e| e.message().to_string())?;
+        let spec = [pathspec.unwrap_or("*")];
+        index
+            .add_all(spec.iter(), IndexAddOption::DEFAULT, None)
+            // add_all does not see deletions
+            .and_then(|_| index.update_all(spec.iter(), None))
+            .and_then(|_| index.write())
+            .map_err(|e| format!("Git add failed: {}", e.message()))
+    }
+
+    fn commit(&self, project_path: &str, message: &str) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let signature = signature(&repo)?;
+        commit_index(&repo, message, &signature, &signature)
+    }
+
+    fn commit_as(
+        &self,
+        project_path: &str,
+        message: &str,
+        committer: &GitIdentity,
+    ) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let author = signature(&repo)?;
+        let committer = Signature::now(&committer.name, &committer.email)
+            .map_err(|e| format!("Failed to create signature: {}", e.message()))?;
+        commit_index(&repo, message, &author, &committer)
+    }
+
+    fn has_changes_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, String> {
+        let repo = open(project_path)?;
+        let from_tree = resolve_commit(&repo, from)?.tree_id();
+        let to_tree = resolve_commit(&repo, to)?.tree_id();
+        Ok(from_tree != to_tree)
+    }
+
+    fn reset_hard(&self, project_path: &str, commit: &str) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let target = repo
+            .revparse_single(commit)
+            .and_then(|object| object.peel(ObjectType::Commit))
+            .map_err(|e| format!("Git reset failed: {}", e.message()))?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        repo.reset(&target, ResetType::Hard, Some(&mut checkout))
+            .map_err(|e| format!("Git reset failed: {}", e.message()))
+    }
+
+    fn reset(&self, project_path: &str, commit: &str, mode: ResetMode) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let target = repo
+            .revparse_single(commit)
+            .and_then(|object| object.peel(ObjectType::Commit))
+            .map_err(|e| format!("Git reset failed: {}", e.message()))?;
+        let kind = match mode {
+            ResetMode::Soft => ResetType::Soft,
+            ResetMode::Mixed => ResetType::Mixed,
+        };
+        repo.reset(&target, kind, None)
+            .map_err(|e| format!("Git reset failed: {}", e.message()))
+    }
+
+    fn restore_paths(
+        &self,
+        project_path: &str,
+        commit: &str,
+        paths: &[String],
+    ) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let tree = resolve_commit(&repo, commit)?
+            .tree()
+            .map_err(|e| format!("Git restore failed: {}", e.message()))?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| "Git restore failed: bare repository".to_string())?
+            .to_path_buf();
+        let mut index = repo.index().map_err(|e| e.message().to_string())?;
+        let in_scope = |path: &str| {
+            paths
+                .iter()
+                .any(|p| path == p || path.starts_with(&format!("{}/", p)))
+        };
+
+        // Tracked files added since `commit`; checkout only writes what the tree has
+        let tracked: Vec<String> = index
+            .iter()
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .filter(|path| in_scope(path))
+            .collect();
+        for path in paths {
+            let known = tree.get_path(Path::new(path)).is_ok()
+                || tracked
+                    .iter()
+                    .any(|t| t == path || t.starts_with(&format!("{}/", path)));
+            if !known {
+                return Err(format!(
+                    "Path not found at {} or in the index: {}",
+                    commit, path
+                ));
+            }
+        }
+        for path in tracked
+            .iter()
+            .filter(|t| tree.get_path(Path::new(t)).is_err())
+        {
+            index
+                .remove_path(Path::new(path))
+                .map_err(|e| format!("Git restore failed: {}", e.message()))?;
+            let file = workdir.join(path);
+            if file.exists() {
+                std::fs::remove_file(&file)
+                    .map_err(|e| format!("Failed to remove {}: {}", file.display(), e))?;
+            }
+        }
+        index
+            .write()
+            .map_err(|e| format!("Git restore failed: {}", e.message()))?;
+
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force();
+        for path in paths {
+            checkout.path(path);
+        }
+        repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+            .map_err(|e| format!("Git restore failed: {}", e.message()))
+    }
+
+    fn commit_count_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<usize, String> {
+        let repo = open(project_path)?;
+        let walk = range_walk(&repo, from, to)?;
+        Ok(walk.filter(|oid| oid.is_ok()).count())
+    }
+
+    fn log_between(&self, project_path: &str, from: &str, to: &str) -> Result<Vec<String>, String> {
+        let repo = open(project_path)?;
+        let walk = range_walk(&repo, from, to)?;
+        Ok(walk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| repo.find_commit(oid).ok())
+            .filter_map(|commit| commit.summary().map(|s| s.to_string()))
+            .filter(|summary| !summary.trim().is_empty())
+            .collect())
+    }
+
+    fn messages_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<String>, String> {
+        let repo = open(project_path)?;
+        let walk = range_walk(&repo, from, to)?;
+        Ok(walk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| repo.find_commit(oid).ok())
+            .map(|commit| String::from_utf8_lossy(commit.message_bytes()).to_string())
+            .collect())
+    }
+
+    fn log_page(
+        &self,
+        project_path: &str,
+        offset: usize,
+        limit: usize,
+        filter: &LogFilter,
+    ) -> Result<Vec<CommitInfo>, String> {
+        let repo = open(project_path)?;
+        if repo.head().is_err() {
+            // No commits yet
+            return Ok(Vec::new());
+        }
+        let mut walk = repo
+            .revwalk()
+            .map_err(|e| format!("Git log failed: {}", e.message()))?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+            .and_then(|_| walk.push_head())
+            .map_err(|e| format!("Git log failed: {}", e.message()))?;
+
+        let matching = walk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| repo.find_commit(oid).ok())
+            .map(|commit| {
+                let info = commit_info(&commit);
+                (commit, info)
+            })
+            .filter(|(_, info)| filter.matches(info));
+
+        let mut commits = Vec::new();
+        for (commit, mut info) in matching.skip(offset).take(limit) {
+            (info.files_changed, info.insertions, info.deletions) = commit_stats(&repo, &commit)?;
+            commits.push(info);
+        }
+        Ok(commits)
+    }
+
+    fn fill_stats(&self, project_path: &str, commits: &mut [CommitInfo]) -> Result<(), String> {
+        let repo = open(project_path)?;
+        for info in commits.iter_mut() {
+            let commit = resolve_commit(&repo, &info.hash)?;
+            (info.files_changed, info.insertions, info.deletions) = commit_stats(&repo, &commit)?;
+        }
+        Ok(())
+    }
+
+    fn orphaned_commits(&self, project_path: &str) -> Result<Vec<CommitInfo>, String> {
+        let repo = open(project_path)?;
+        let mut walk = repo
+            .revwalk()
+            .map_err(|e| format!("Git reflog failed: {}", e.message()))?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)
+            .map_err(|e| format!("Git reflog failed: {}", e.message()))?;
+
+        let mut names = vec!["HEAD".to_string()];
+        let references = repo
+            .references()
+            .map_err(|e| format!("Git reflog failed: {}", e.message()))?;
+        for reference in references.flatten() {
+            if let Some(name) = reference.name() {
+                names.push(name.to_string());
+            }
+            // Everything a ref still reaches is not lost
+            if let Ok(commit) = reference.peel_to_commit() {
+                let _ = walk.hide(commit.id());
+            }
+        }
+        if let Ok(head) = repo.head().and_then(|head| head.peel_to_commit()) {
+            let _ = walk.hide(head.id());
+        }
+        // The stash's reflog holds the stash entries, which aren't lost work
+        for name in names.iter().filter(|name| *name != "refs/stash") {
+            let Ok(reflog) = repo.reflog(name) else {
+                continue;
+            };
+            for entry in reflog.iter() {
+                for oid in [entry.id_old(), entry.id_new()] {
+                    if !oid.is_zero() && repo.find_commit(oid).is_ok() {
+                        let _ = walk.push(oid);
+                    }
+                }
+            }
+        }
+
+        Ok(walk
+            .filter_map(|oid| oid.ok())
+            .filter_map(|oid| repo.find_commit(oid).ok())
+            .map(|commit| commit_info(&commit))
+            .collect())
+    }
+
+    fn blame_file(&self, project_path: &str, path: &str) -> Result<Vec<BlameLine>, String> {
+        let repo = open(project_path)?;
+        let blame = repo
+            .blame_file(Path::new(path), None)
+            .map_err(|e| format!("Git blame failed: {}", e.message()))?;
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| "Git blame failed: bare repository".to_string())?;
+        // Blame the working tree content, so uncommitted lines show up as such
+        let buffered;
+        let blame = match std::fs::read(workdir.join(path)) {
+            Ok(content) => {
+                buffered = blame
+                    .blame_buffer(&content)
+                    .map_err(|e| format!("Git blame failed: {}", e.message()))?;
+                &buffered
+            }
+            Err(_) => &blame,
+        };
+
+        let mut commits: HashMap<Oid, CommitInfo> = HashMap::new();
+        let mut lines = Vec::new();
+        for hunk in blame.iter() {
+            let oid = hunk.final_commit_id();
+            if !oid.is_zero() && !commits.contains_key(&oid) {
+                if let Ok(commit) = repo.find_commit(oid) {
+                    commits.insert(oid, commit_info(&commit));
+                }
+            }
+            let start = hunk.final_start_line();
+            for line in start..start + hunk.lines_in_hunk() {
+                lines.push(BlameLine::new(line, commits.get(&oid)));
+            }
+        }
+        Ok(lines)
+    }
+
+    fn stash_save(&self, project_path: &str, message: &str) -> Result<String, String> {
+        let mut repo = open(project_path)?;
+        let signature = signature(&repo)?;
+        repo.stash_save(&signature, message, Some(StashFlags::INCLUDE_UNTRACKED))
+            .map(|oid| oid.to_string())
+            .map_err(|e| format!("Git stash failed: {}", e.message()))
+    }
+
+    fn stash_snapshot(&self, project_path: &str, message: &str) -> Result<String, String> {
+        let stash = self.stash_save(project_path, message)?;
+        let mut repo = open(project_path)?;
+        let mut options = StashApplyOptions::new();
+        options.reinstantiate_index();
+        repo.stash_apply(0, Some(&mut options))
+            .map_err(|e| format!("Git stash apply failed: {}", e.message()))?;
+        Ok(stash)
+    }
+
+    fn stash_list(&self, project_path: &str) -> Result<Vec<StashEntry>, String> {
+        let mut repo = open(project_path)?;
+        let mut stashes = Vec::new();
+        repo.stash_foreach(|index, subject, oid| {
+            stashes.push((index, *oid, subject.to_string()));
+            true
+        })
+        .map_err(|e| format!("Failed to list stashes: {}", e.message()))?;
+        Ok(stashes
+            .into_iter()
+            .map(|(index, oid, subject)| {
+                let seconds = repo.find_commit(oid).ok().map(|c| c.time().seconds());
+                stash_entry(index, oid.to_string(), &subject, seconds)
+            })
+            .collect())
+    }
+
+    fn stash_apply(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let mut repo = open(project_path)?;
+        repo.stash_apply(index, None)
+            .map_err(|e| format!("Git stash apply failed: {}", e.message()))
+    }
+
+    fn stash_pop(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let mut repo = open(project_path)?;
+        repo.stash_pop(index, None)
+            .map_err(|e| format!("Git stash pop failed: {}", e.message()))
+    }
+
+    fn stash_drop(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let mut repo = open(project_path)?;
+        repo.stash_drop(index)
+            .map_err(|e| format!("Git stash drop failed: {}", e.message()))
+    }
+
+    fn revert_range(&self, project_path: &str, from: &str, to: &str) -> Result<(), RevertFailure> {
+        let repo = open(project_path).map_err(RevertFailure::Failed)?;
+        let before = resolve_commit(&repo, from)
+            .and_then(|c| c.tree().map_err(|e| e.message().to_string()))
+            .map_err(RevertFailure::Failed)?;
+        let after = resolve_commit(&repo, to)
+            .and_then(|c| c.tree().map_err(|e| e.message().to_string()))
+            .map_err(RevertFailure::Failed)?;
+        // The range's net change, reversed
+        let diff = repo
+            .diff_tree_to_tree(Some(&after), Some(&before), None)
+            .map_err(|e| RevertFailure::Failed(e.message().to_string()))?;
+        repo.apply(&diff, ApplyLocation::Both, None).map_err(|e| {
+            if matches!(e.code(), ErrorCode::ApplyFail | ErrorCode::Conflict) {
+                RevertFailure::Conflict(e.message().to_string())
+            } else {
+                RevertFailure::Failed(e.message().to_string())
+            }
+        })
+    }
+
+    fn submodules(&self, project_path: &str) -> Result<Vec<SubmoduleInfo>, String> {
+        let repo = open(project_path)?;
+        let submodules = repo
+            .submodules()
+            .map_err(|e| format!("Failed to list submodules: {}", e.message()))?;
+        Ok(submodules
+            .iter()
+            .map(|submodule| {
+                SubmoduleInfo::new(
+                    submodule.path().to_string_lossy().replace('\\', "/"),
+                    submodule.url().map(str::to_string),
+                    submodule
+                        .index_id()
+                        .or_else(|| submodule.head_id())
+                        .map(|id| id.to_string()),
+                    submodule.workdir_id().map(|id| id.to_string()),
+                )
+            })
+            .collect())
+    }
+
+    fn update_submodules(&self, project_path: &str, init: bool) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let mut submodules = repo
+            .submodules()
+            .map_err(|e| format!("Failed to list submodules: {}", e.message()))?;
+        for submodule in submodules.iter_mut() {
+            submodule.update(init, None).map_err(|e| {
+                format!(
+                    "Failed to update submodule {}: {}",
+                    submodule.path().display(),
+                    e.message()
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    fn stage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let repo = open(project_path)?;
+        let mut index = repo.index().map_err(|e| e.message().to_string())?;
+        index
+            .add_all(paths.iter(), IndexAddOption::DEFAULT, None)
+            // add_all does not see deletions
+            .and_then(|_| index.update_all(paths.iter(), None))
+            .and_then(|_| index.write())
+            .map_err(|e| format!("Git add failed: {}", e.message()))
+    }
+
+    fn unstage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let repo = open(project_path)?;
+        let head = repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel(ObjectType::Commit).ok());
+        repo.reset_default(head.as_ref(), paths.iter().map(String::as_str))
+            .map_err(|e| format!("Git reset failed: {}", e.message()))
+    }
+
+    fn cherry_pick(
+        &self,
+        project_path: &str,
+        commits: &[String],
+        target_branch: &str,
+    ) -> Result<CherryPickResult, String> {
+        let repo = open(project_path)?;
+        let branch = repo
+            .find_branch(target_branch, BranchType::Local)
+            .map_err(|e| format!("Branch {} not found: {}", target_branch, e.message()))?;
+        let is_head = branch.is_head();
+        let reference = format!("refs/heads/{}", target_branch);
+        let mut tip = branch
+            .get()
+            .peel_to_commit()
+            .map_err(|e| e.message().to_string())?;
+        let committer = signature(&repo)?;
+
+        // Build the new commits first; the branch only moves once all of them applied
+        let mut picked = Vec::new();
+        for hash in commits {
+            let commit = resolve_commit(&repo, hash)?;
+            if commit.parent_count() > 1 {
+                return Err(format!(
+                    "{} is a merge commit and cannot be cherry-picked",
+                    hash
+                ));
+            }
+            let mut index = repo
+                .cherrypick_commit(&commit, &tip, 0, None)
+                .map_err(|e| format!("Git cherry-pick failed: {}", e.message()))?;
+            if index.has_conflicts() {
+                return Ok(CherryPickResult {
+                    picked: Vec::new(),
+                    conflict: Some(CherryPickConflict {
+                        commit: commit.id().to_string(),
+                        paths: conflict_paths(&index),
+                    }),
+                });
+            }
+            let tree = index
+                .write_tree_to(&repo)
+                .and_then(|id| repo.find_tree(id))
+                .map_err(|e| e.message().to_string())?;
+            let message = format!(
+                "{}\n\n(cherry picked from commit {})",
+                String::from_utf8_lossy(commit.message_bytes()).trim_end(),
+                commit.id()
+            );
+            let id = repo
+                .commit(None, &commit.author(), &committer, &message, &tree, &[&tip])
+                .map_err(|e| format!("Git commit failed: {}", e.message()))?;
+            tip = repo.find_commit(id).map_err(|e| e.message().to_string())?;
+            picked.push(PickedCommit {
+                source: commit.id().to_string(),
+                commit: id.to_string(),
+            });
+        }
+
+        repo.reference(&reference, tip.id(), true, "cherry-pick")
+            .map_err(|e| format!("Failed to update {}: {}", target_branch, e.message()))?;
+        if is_head {
+            repo.reset(tip.as_object(), ResetType::Hard, None)
+                .map_err(|e| format!("Git checkout failed: {}", e.message()))?;
+        }
+        Ok(CherryPickResult {
+            picked,
+            conflict: None,
+        })
+    }
+
+    fn tree_entry_id(
+        &self,
+        project_path: &str,
+        commit: &str,
+        path: &str,
+    ) -> Result<Option<String>, String> {
+        let repo = open(project_path)?;
+        let tree = resolve_commit(&repo, commit)?
+            .tree()
+            .map_err(|e| e.message().to_string())?;
+        Ok(tree
+            .get_path(Path::new(path))
+            .ok()
+            .map(|entry| entry.id().to_string()))
+    }
+
+    fn parent_count(&self, project_path: &str, commit: &str) -> Result<usize, String> {
+        let repo = open(project_path)?;
+        Ok(resolve_commit(&repo, commit)?.parent_count())
+    }
+
+    fn list_branches(&self, project_path: &str) -> Result<Vec<BranchInfo>, String> {
+        let repo = open(project_path)?;
+        let branches = repo
+            .branches(Some(BranchType::Local))
+            .map_err(|e| format!("Failed to list branches: {}", e.message()))?;
+        Ok(branches
+            .filter_map(|branch| branch.ok())
+            .filter_map(|(branch, _)| {
+                let name = branch.name().ok()??.to_string();
+                let commit = branch.get().peel_to_commit().ok()?.id().to_string();
+                Some(BranchInfo {
+                    name,
+                    commit,
+                    is_current: branch.is_head(),
+                })
+            })
+            .collect())
+    }
+
+    fn create_branch(
+        &self,
+        project_path: &str,
+        name: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let target = resolve_commit(&repo, start_point.unwrap_or("HEAD"))?;
+        repo.branch(name, &target, false)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to create branch {}: {}", name, e.message()))
+    }
+
+    fn switch_branch(&self, project_path: &str, name: &str) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let reference = format!("refs/heads/{}", name);
+        let target = repo
+            .revparse_single(&reference)
+            .map_err(|e| format!("Unknown branch {}: {}", name, e.message()))?;
+        let mut checkout = CheckoutBuilder::new();
+        checkout.safe();
+        repo.checkout_tree(&target, Some(&mut checkout))
+            .and_then(|_| repo.set_head(&reference))
+            .map_err(|e| format!("Failed to switch to {}: {}", name, e.message()))
+    }
+
+    fn delete_branch(&self, project_path: &str, name: &str, force: bool) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let mut branch = repo
+            .find_branch(name, BranchType::Local)
+            .map_err(|e| format!("Unknown branch {}: {}", name, e.message()))?;
+        if branch.is_head() {
+            return Err(format!("Cannot delete the current branch {}", name));
+        }
+        if !force {
+            let tip = branch
+                .get()
+                .peel_to_commit()
+                .map_err(|e| e.message().to_string())?
+                .id();
+            let head = resolve_commit(&repo, "HEAD")?.id();
+            let merged = tip == head
+                || repo
+                    .graph_descendant_of(head, tip)
+                    .map_err(|e| e.message().to_string())?;
+            if !merged {
+                return Err(format!("Branch {} is not fully merged", name));
+            }
+        }
+        branch
+            .delete()
+            .map_err(|e| format!("Failed to delete branch {}: {}", name, e.message()))
+    }
+
+    fn list_tags(&self, project_path: &str) -> Result<Vec<TagInfo>, String> {
+        let repo = open(project_path)?;
+        let names = repo
+            .tag_names(None)
+            .map_err(|e| format!("Failed to list tags: {}", e.message()))?;
+        let mut tags: Vec<TagInfo> = names
+            .iter()
+            .flatten()
+            .filter_map(|name| {
+                let object = repo.revparse_single(&format!("refs/tags/{}", name)).ok()?;
+                let commit = object.peel_to_commit().ok()?.id().to_string();
+                let message = object
+                    .as_tag()
+                    .and_then(|tag| tag.message())
+                    .and_then(|message| message.lines().next())
+                    .map(str::to_string);
+                Some(TagInfo {
+                    name: name.to_string(),
+                    commit,
+                    message,
+                })
+            })
+            .collect();
+        tags.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(tags)
+    }
+
+    fn create_tag(
+        &self,
+        project_path: &str,
+        name: &str,
+        commit: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let target = resolve_commit(&repo, commit.unwrap_or("HEAD"))?;
+        let result = match message {
+            Some(message) => repo.tag(name, target.as_object(), &signature(&repo)?, message, false),
+            None => repo.tag_lightweight(name, target.as_object(), false),
+        };
+        result
+            .map(|_| ())
+            .map_err(|e| format!("Failed to create tag {}: {}", name, e.message()))
+    }
+
+    fn delete_tag(&self, project_path: &str, name: &str) -> Result<(), String> {
+        open(project_path)?
+            .tag_delete(name)
+            .map_err(|e| format!("Failed to delete tag {}: {}", name, e.message()))
+    }
+
+    fn add_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        branch: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String> {
+        let repo = main_repository(open(project_path)?)?;
+        let name = Path::new(worktree_path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .ok_or_else(|| format!("Invalid worktree path: {}", worktree_path))?;
+        let target = resolve_commit(&repo, start_point.unwrap_or("HEAD"))?;
+        let mut created = repo
+            .branch(branch, &target, false)
+            .map_err(|e| format!("Failed to create branch {}: {}", branch, e.message()))?;
+
+        let mut options = WorktreeAddOptions::new();
+        options.reference(Some(created.get()));
+        if let Err(e) = repo.worktree(name, Path::new(worktree_path), Some(&options)) {
+            let _ = created.delete();
+            return Err(format!("Git worktree add failed: {}", e.message()));
+        }
+        Ok(())
+    }
+
+    fn list_worktrees(&self, project_path: &str) -> Result<Vec<WorktreeInfo>, String> {
+        let repo = main_repository(open(project_path)?)?;
+        let head_of = |worktree_repo: &Repository| {
+            let head = worktree_repo.head().ok();
+            let commit = head
+                .as_ref()
+                .and_then(|head| head.peel_to_commit().ok())
+                .map(|commit| commit.id().to_string());
+            let branch = head
+                .as_ref()
+                .filter(|head| head.is_branch())
+                .and_then(|head| head.shorthand().map(|name| name.to_string()));
+            (commit, branch)
+        };
+
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| "Repository has no working tree".to_string())?;
+        let (head, branch) = head_of(&repo);
+        let mut worktrees = vec![WorktreeInfo {
+            path: workdir
+                .to_string_lossy()
+                .trim_end_matches(['/', '\\'])
+                .to_string(),
+            head,
+            branch,
+            is_main: true,
+            locked: false,
+            prunable: false,
+        }];
+
+        let names = repo
+            .worktrees()
+            .map_err(|e| format!("Failed to list worktrees: {}", e.message()))?;
+        for name in names.iter().flatten() {
+            let Ok(worktree) = repo.find_worktree(name) else {
+                continue;
+            };
+            let prunable = worktree.validate().is_err();
+            let (head, branch) = if prunable {
+                (None, None)
+            } else {
+                Repository::open_from_worktree(&worktree)
+                    .map(|worktree_repo| head_of(&worktree_repo))
+                    .unwrap_or((None, None))
+            };
+            worktrees.push(WorktreeInfo {
+                path: worktree.path().to_string_lossy().to_string(),
+                head,
+                branch,
+                is_main: false,
+                locked: matches!(worktree.is_locked(), Ok(WorktreeLockStatus::Locked(_))),
+                prunable,
+            });
+        }
+        Ok(worktrees)
+    }
+
+    fn remove_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        let repo = main_repository(open(project_path)?)?;
+        let key = project_key(worktree_path);
+        let names = repo
+            .worktrees()
+            .map_err(|e| format!("Failed to list worktrees: {}", e.message()))?;
+        let worktree = names
+            .iter()
+            .flatten()
+            .filter_map(|name| repo.find_worktree(name).ok())
+            .find(|worktree| project_key(&worktree.path().to_string_lossy()) == key)
+            .ok_or_else(|| format!("Not a linked worktree: {}", worktree_path))?;
+
+        if !force {
+            if matches!(worktree.is_locked(), Ok(WorktreeLockStatus::Locked(_))) {
+                return Err(format!("Worktree {} is locked", worktree_path));
+            }
+            if worktree.validate().is_ok()
+                && self.has_uncommitted_changes(&worktree.path().to_string_lossy())?
+            {
+                return Err(format!(
+                    "Worktree {} has uncommitted changes",
+                    worktree_path
+                ));
+            }
+        }
+
+        let mut options = WorktreePruneOptions::new();
+        options.valid(true).locked(force).working_tree(true);
+        worktree
+            .prune(Some(&mut options))
+            .map_err(|e| format!("Git worktree remove failed: {}", e.message()))
+    }
+
+    fn diff_commits(&self, project_path: &str, from: &str, to: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let from_tree = resolve_commit(&repo, from)?
+            .tree()
+            .map_err(|e| e.message().to_string())?;
+        let to_tree = resolve_commit(&repo, to)?
+            .tree()
+            .map_err(|e| e.message().to_string())?;
+        let mut diff = repo
+            .diff_tree_to_tree(Some(&from_tree), Some(&to_tree), None)
+            .map_err(|e| format!("Git diff failed: {}", e.message()))?;
+        patch_text(&mut diff)
+    }
+
+    fn diff_working_tree(&self, project_path: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        // No HEAD yet: everything is new
+        let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+        let mut options = DiffOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .show_untracked_content(true);
+        let mut diff = repo
+            .diff_tree_to_workdir_with_index(head_tree.as_ref(), Some(&mut options))
+            .map_err(|e| format!("Git diff failed: {}", e.message()))?;
+        patch_text(&mut diff)
+    }
+
+    fn diff_unstaged(&self, project_path: &str, path: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let mut options = DiffOptions::new();
+        options.pathspec(path).disable_pathspec_match(true);
+        let mut diff = repo
+            .diff_index_to_workdir(None, Some(&mut options))
+            .map_err(|e| format!("Git diff failed: {}", e.message()))?;
+        patch_text(&mut diff)
+    }
+
+    fn format_patch(&self, project_path: &str, from: &str, to: &str) -> Result<String, String> {
+        let repo = open(project_path)?;
+        let mut walk = range_walk(&repo, from, to)?;
+        walk.set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+            .map_err(|e| e.message().to_string())?;
+        let mut patch = String::new();
+        for oid in walk {
+            let oid = oid.map_err(|e| e.message().to_string())?;
+            let commit = repo.find_commit(oid).map_err(|e| e.message().to_string())?;
+            // format-patch leaves merges out too
+            if commit.parent_count() > 1 {
+                continue;
+            }
+            let email = Email::from_commit(&commit, &mut EmailCreateOptions::new())
+                .map_err(|e| format!("Git format-patch failed: {}", e.message()))?;
+            patch.push_str(&String::from_utf8_lossy(email.as_slice()));
+        }
+        Ok(patch)
+    }
+
+    fn apply_patch(&self, project_path: &str, patch: &str) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let diffs = split_mbox(patch)
+            .into_iter()
+            .map(|chunk| {
+                Diff::from_buffer(chunk.as_bytes())
+                    .map_err(|e| format!("Invalid patch: {}", e.message()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Apply the whole series to HEAD's tree first, so a failing patch changes nothing
+        let head_tree = repo
+            .head()
+            .and_then(|head| head.peel_to_tree())
+            .map_err(|e| format!("Failed to read HEAD: {}", e.message()))?;
+        let mut tree = head_tree.clone();
+        for diff in &diffs {
+            let mut index = repo
+                .apply_to_tree(&tree, diff, None)
+                .map_err(|e| format!("Patch does not apply: {}", e.message()))?;
+            tree = index
+                .write_tree_to(&repo)
+                .and_then(|id| repo.find_tree(id))
+                .map_err(|e| e.message().to_string())?;
+        }
+        // then bring the index and workdir to the result in one step, which either
+        // applies entirely or (e.g. on local edits to a patched file) not at all
+        let combined = repo
+            .diff_tree_to_tree(Some(&head_tree), Some(&tree), None)
+            .map_err(|e| e.message().to_string())?;
+        repo.apply(&combined, ApplyLocation::Both, None)
+            .map_err(|e| format!("Git apply failed: {}", e.message()))
+    }
+
+    fn apply_to_index(&self, project_path: &str, patch: &str) -> Result<(), String> {
+        let repo = open(project_path)?;
+        let diff = Diff::from_buffer(patch.as_bytes())
+            .map_err(|e| format!("Invalid patch: {}", e.message()))?;
+        repo.apply(&diff, ApplyLocation::Index, None)
+            .map_err(|e| format!("Git apply failed: {}", e.message()))
+    }
+}
+
+// ============================================================================
+// git CLI backend
+// ============================================================================
+
+pub struct CliBackend;
+
+/// Run git and return its output, failing with `what` and stderr on a non-zero exit
+fn run_git(project_path: &str, args: &[&str], what: &str) -> Result<Output, String> {
+    let mut cmd = git_command(project_path);
+    cmd.args(args);
+    // Label by subcommand, skipping leading `-c key=value` overrides
+    let subcommand = args
+        .iter()
+        .enumerate()
+        .find(|(i, arg)| *arg != "-c" && (*i == 0 || args[i - 1] != "-c"))
+        .map_or(args[0], |(_, arg)| *arg);
+    let label = format!("git {}", subcommand);
+    let output = profiling::timed_subprocess(&label, || git_executor::output(&mut cmd))
+        .map_err(|e| format!("Failed to run {}: {}", label, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output)
+}
+
+/// A host path as git sees it (inside the distro for WSL projects)
+fn git_path(path: &str) -> String {
+    wsl_utils::detect_wsl_project(path)
+        .map(|location| location.wsl_path)
+        .unwrap_or_else(|| path.to_string())
+}
+
+/// A path reported by git, as the host sees it
+fn host_path(project_path: &str, path: &str) -> String {
+    match wsl_utils::detect_wsl_project(project_path) {
+        Some(location) if !path.starts_with("/mnt/") => match location.distro {
+            Some(distro) => wsl_utils::build_wsl_unc_path(path, &distro)
+                .to_string_lossy()
+                .to_string(),
+            None => path.to_string(),
+        },
+        Some(_) => wsl_utils::wsl_to_windows_path(path),
+        None => path.to_string(),
+    }
+}
+
+/// Parse `git worktree list --porcelain`
+fn parse_worktree_list(output: &str) -> Vec<WorktreeInfo> {
+    output
+        .split("\n\n")
+        .filter_map(|block| {
+            let mut info = WorktreeInfo {
+                path: String::new(),
+                head: None,
+                branch: None,
+                is_main: false,
+                locked: false,
+                prunable: false,
+            };
+            for line in block.lines() {
+                let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+                match key {
+                    "worktree" => info.path = value.to_string(),
+                    "HEAD" => info.head = Some(value.to_string()),
+                    "branch" => {
+                        info.branch = Some(value.trim_start_matches("refs/heads/").to_string())
+                    }
+                    "locked" => info.locked = true,
+                    "prunable" => info.prunable = true,
+                    _ => {}
+                }
+            }
+            (!info.path.is_empty()).then_some(info)
+        })
+        .enumerate()
+        .map(|(i, info)| WorktreeInfo {
+            is_main: i == 0,
+            ..info
+        })
+        .collect()
+}
+
+/// Porcelain codes of a libgit2 status
+fn status_codes(status: git2::Status) -> (char, char) {
+    if status.is_conflicted() {
+        return ('U', 'U');
+    }
+    if status.is_wt_new() {
+        return ('?', '?');
+    }
+    let staged = if status.is_index_new() {
+        'A'
+    } else if status.is_index_modified() {
+        'M'
+    } else if status.is_index_deleted() {
+        'D'
+    } else if status.is_index_renamed() {
+        'R'
+    } else if status.is_index_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    let unstaged = if status.is_wt_modified() {
+        'M'
+    } else if status.is_wt_deleted() {
+        'D'
+    } else if status.is_wt_renamed() {
+        'R'
+    } else if status.is_wt_typechange() {
+        'T'
+    } else {
+        ' '
+    };
+    (staged, unstaged)
+}
+
+/// Entries of `git status --porcelain -z`
+fn parse_porcelain_status(output: &str) -> Vec<StatusEntry> {
+    let mut result = Vec::new();
+    let mut entries = output.split('\0');
+    while let Some(entry) = entries.next() {
+        let mut codes = entry.chars();
+        let (Some(staged), Some(unstaged)) = (codes.next(), codes.next()) else {
+            continue;
+        };
+        let Some(path) = entry.get(3..).filter(|path| !path.is_empty()) else {
+            continue;
+        };
+        result.push(StatusEntry {
+            path: path.to_string(),
+            staged,
+            unstaged,
+        });
+        // Renames and copies are followed by their source path
+        if matches!(staged, 'R' | 'C') || matches!(unstaged, 'R' | 'C') {
+            entries.next();
+        }
+    }
+    result
+}
+
+/// Paths and commits from `git submodule status`; uninitialized submodules have no commit
+fn parse_submodule_status(output: &str) -> Vec<(String, Option<String>)> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (state, rest) = line.split_at(line.char_indices().nth(1)?.0);
+            let mut fields = rest.split(' ');
+            let commit = fields.next()?.to_string();
+            let path = fields.next()?.to_string();
+            Some((path, (state != "-").then_some(commit)))
+        })
+        .collect()
+}
+
+/// Submodule path → url from `git config -f .gitmodules --get-regexp`
+fn parse_gitmodules_urls(output: &str) -> HashMap<String, String> {
+    let mut paths: HashMap<&str, &str> = HashMap::new();
+    let mut urls: HashMap<&str, &str> = HashMap::new();
+    for line in output.lines() {
+        let Some((key, value)) = line.split_once(' ') else {
+            continue;
+        };
+        if let Some(name) = key
+            .strip_prefix("submodule.")
+            .and_then(|k| k.strip_suffix(".path"))
+        {
+            paths.insert(name, value);
+        } else if let Some(name) = key
+            .strip_prefix("submodule.")
+            .and_then(|k| k.strip_suffix(".url"))
+        {
+            urls.insert(name, value);
+        }
+    }
+    urls.into_iter()
+        .map(|(name, url)| {
+            let path = paths.get(name).copied().unwrap_or(name);
+            (path.to_string(), url.to_string())
+        })
+        .collect()
+}
+
+/// Whether a line starts a message in a `git format-patch` mailbox (`From <sha> <date>`)
+fn is_mbox_separator(line: &str) -> bool {
+    line.strip_prefix("From ")
+        .and_then(|rest| rest.split_whitespace().next())
+        .is_some_and(|id| id.len() == 40 && id.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+/// The patches of a mailbox, one per commit; plain diff output is a single patch
+pub(crate) fn split_mbox(patch: &str) -> Vec<&str> {
+    let mut starts = vec![0];
+    let mut offset = 0;
+    for line in patch.split_inclusive('\n') {
+        if offset > 0 && is_mbox_separator(line) {
+            starts.push(offset);
+        }
+        offset += line.len();
+    }
+    starts.push(patch.len());
+    starts
+        .windows(2)
+        .map(|range| &patch[range[0]..range[1]])
+        .filter(|chunk| !chunk.trim().is_empty())
+        .collect()
+}
+
+/// Line numbers and commits from `git blame --porcelain`; uncommitted lines have no commit
+fn parse_blame_porcelain(output: &str) -> Vec<(usize, Option<String>)> {
+    output
+        .lines()
+        .filter(|line| !line.starts_with('\t'))
+        .filter_map(|line| {
+            let mut fields = line.split(' ');
+            let hash = fields.next()?;
+            if hash.len() != 40 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+                return None;
+            }
+            let final_line = fields.nth(1)?.parse().ok()?;
+            let committed = hash.chars().any(|c| c != '0');
+            Some((final_line, committed.then(|| hash.to_string())))
+        })
+        .collect()
+}
+
+/// Parse `git log -z --format=%H%x1f%an%x1f%ae%x1f%ct%x1f%B`
+fn parse_log_records(output: &str) -> Vec<CommitInfo> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(5, '\x1f');
+            Some(CommitInfo::new(
+                fields.next()?.to_string(),
+                fields.next()?.to_string(),
+                fields.next()?.to_string(),
+                fields.next()?.parse::<i64>().ok(),
+                fields.next()?,
+            ))
+        })
+        .collect()
+}
+
+/// ` 3 files changed, 10 insertions(+), 2 deletions(-)` -> (3, 10, 2)
+fn parse_shortstat(line: &str) -> Option<(usize, usize, usize)> {
+    if !line.contains(" changed") {
+        return None;
+    }
+    let mut counts = (0, 0, 0);
+    for part in line.split(',') {
+        let mut words = part.split_whitespace();
+        let Some(count) = words.next().and_then(|n| n.parse::<usize>().ok()) else {
+            continue;
+        };
+        match words.next() {
+            Some(word) if word.starts_with("file") => counts.0 = count,
+            Some(word) if word.starts_with("insertion") => counts.1 = count,
+            Some(word) if word.starts_with("deletion") => counts.2 = count,
+            _ => {}
+        }
+    }
+    Some(counts)
+}
+
+/// `git commit` of the index with the signing settings applied; `overrides` are extra `-c` pairs
+fn cli_commit(project_path: &str, mut overrides: Vec<String>, message: &str) -> Result<(), String> {
+    overrides.extend(commit_signing::cli_overrides());
+    let mut args: Vec<&str> = overrides.iter().map(String::as_str).collect();
+    args.extend(["commit", "--allow-empty", "-m", message]);
+    run_git(project_path, &args, "Git commit")
+        .map(|_| ())
+        .map_err(commit_signing::classify_error)
+}
+
+/// Like [`run_git`], with `input` written to git's stdin
+fn run_git_with_input(
+    project_path: &str,
+    args: &[&str],
+    input: &str,
+    what: &str,
+) -> Result<Output, String> {
+    let mut cmd = git_command(project_path);
+    cmd.args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let label = format!("git {}", args[0]);
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", label, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input.as_bytes())
+            .map_err(|e| format!("Failed to write to {}: {}", label, e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", label, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "{} failed: {}",
+            what,
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(output)
+}
+
+/// `git apply <target> -` with `patch` on stdin
+fn cli_apply(project_path: &str, target: &str, patch: &str) -> Result<(), String> {
+    run_git_with_input(project_path, &["apply", target, "-"], patch, "Git apply").map(|_| ())
+}
+
+/// Cherry-pick `commits` one by one in the worktree at `worktree` (a path as git sees it)
+fn cli_pick_in(
+    project_path: &str,
+    worktree: &str,
+    commits: &[String],
+) -> Result<CherryPickResult, String> {
+    let in_worktree = |args: &[&str]| {
+        let mut full = vec!["-C", worktree];
+        full.extend(args);
+        full.into_iter().map(str::to_string).collect::<Vec<_>>()
+    };
+    let mut picked = Vec::new();
+    for hash in commits {
+        let source = stdout_line(&run_git(
+            project_path,
+            &["rev-parse", "--verify", &format!("{}^{{commit}}", hash)],
+            "Git rev-parse",
+        )?);
+        let mut args = commit_signing::cli_overrides();
+        args.extend(in_worktree(&[
+            "cherry-pick",
+            "-x",
+            "--allow-empty",
+            "--keep-redundant-commits",
+            &source,
+        ]));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        if let Err(e) = run_git(project_path, &args, "Git cherry-pick") {
+            let unmerged = in_worktree(&["diff", "--name-only", "--diff-filter=U"]);
+            let unmerged: Vec<&str> = unmerged.iter().map(String::as_str).collect();
+            let paths: Vec<String> = run_git(project_path, &unmerged, "Git diff")
+                .map(|output| {
+                    String::from_utf8_lossy(&output.stdout)
+                        .lines()
+                        .map(str::to_string)
+                        .collect()
+                })
+                .unwrap_or_default();
+            if paths.is_empty() {
+                return Err(commit_signing::classify_error(e));
+            }
+            return Ok(CherryPickResult {
+                picked: Vec::new(),
+                conflict: Some(CherryPickConflict {
+                    commit: source,
+                    paths,
+                }),
+            });
+        }
+        let head = in_worktree(&["rev-parse", "HEAD"]);
+        let head: Vec<&str> = head.iter().map(String::as_str).collect();
+        picked.push(PickedCommit {
+            source,
+            commit: stdout_line(&run_git(project_path, &head, "Git rev-parse")?),
+        });
+    }
+    Ok(CherryPickResult {
+        picked,
+        conflict: None,
+    })
+}
+
+fn stdout_line(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).trim().to_string()
+}
+
+impl GitBackend for CliBackend {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn init(&self, project_path: &str) -> Result<(), String> {
+        run_git(project_path, &["init"], "Git init").map(|_| ())
+    }
+
+    fn configure_identity(
+        &self,
+        project_path: &str,
+        name: &str,
+        email: &str,
+    ) -> Result<(), String> {
+        run_git(
+            project_path,
+            &["config", "--local", "user.name", name],
+            "Git config",
+        )?;
+        run_git(
+            project_path,
+            &["config", "--local", "user.email", email],
+            "Git config",
+        )
+        .map(|_| ())
+    }
+
+    fn configured_identity(&self, project_path: &str) -> Result<Option<GitIdentity>, String> {
+        // `git config --get` exits with 1 when the key is not set
+        let value = |key: &str| {
+            run_git(project_path, &["config", "--get", key], "Git config")
+                .ok()
+                .map(|output| stdout_line(&output))
+                .filter(|value| !value.is_empty())
+        };
+        Ok(value("user.name")
+            .zip(value("user.email"))
+            .map(|(name, email)| GitIdentity { name, email }))
+    }
+
+    fn current_commit(&self, project_path: &str) -> Result<String, String> {
+        let output = run_git(project_path, &["rev-parse", "HEAD"], "Git rev-parse")?;
+        Ok(stdout_line(&output))
+    }
+
+    fn current_branch(&self, project_path: &str) -> Result<String, String> {
+        let output = run_git(
+            project_path,
+            &["rev-parse", "--abbrev-ref", "HEAD"],
+            "Git rev-parse",
+        )?;
+        let branch = stdout_line(&output);
+        if branch == "HEAD" {
+            return Err("HEAD is detached, no current branch".to_string());
+        }
+        Ok(branch)
+    }
+
+    fn remote_url(&self, project_path: &str, remote: &str) -> Result<String, String> {
+        let output = run_git(
+            project_path,
+            &["remote", "get-url", remote],
+            "Git remote get-url",
+        )?;
+        Ok(stdout_line(&output))
+    }
+
+    fn has_uncommitted_changes(&self, project_path: &str) -> Result<bool, String> {
+        let output = run_git(project_path, &["status", "--porcelain"], "Git status")?;
+        Ok(!output.stdout.is_empty())
+    }
+
+    fn changed_paths(&self, project_path: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .status(project_path, None)?
+            .into_iter()
+            .map(|entry| entry.path)
+            .collect())
+    }
+
+    fn status(
+        &self,
+        project_path: &str,
+        paths: Option<&[String]>,
+    ) -> Result<Vec<StatusEntry>, String> {
+        let mut args = vec!["status", "--porcelain", "-z", "--untracked-files=all"];
+        if let Some(paths) = paths {
+            if paths.is_empty() {
+                return Ok(Vec::new());
+            }
+            args.push("--");
+            args.extend(paths.iter().map(String::as_str));
+        }
+        let output = run_git(project_path, &args, "Git status")?;
+        Ok(parse_porcelain_status(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+
+    fn stage_all(&self, project_path: &str, pathspec: Option<&str>) -> Result<(), String> {
+        let mut args = vec!["add", "-A"];
+        if let Some(pathspec) = pathspec {
+            args.extend(["--", pathspec]);
+        }
+        run_git(project_path, &args, "Git add").map(|_| ())
+    }
+
+    fn commit(&self, project_path: &str, message: &str) -> Result<(), String> {
+        cli_commit(project_path, Vec::new(), message)
+    }
+
+    fn commit_as(
+        &self,
+        project_path: &str,
+        message: &str,
+        committer: &GitIdentity,
+    ) -> Result<(), String> {
+        let overrides = vec![
+            "-c".to_string(),
+            format!("committer.name={}", committer.name),
+            "-c".to_string(),
+            format!("committer.email={}", committer.email),
+        ];
+        cli_commit(project_path, overrides, message)
+    }
+
+    fn has_changes_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<bool, String> {
+        let mut cmd = git_command(project_path);
+        cmd.args(["diff", "--quiet", from, to]);
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to diff commits: {}", e))?;
+        match output.status.code() {
+            Some(0) => Ok(false),
+            Some(1) => Ok(true),
+            _ => Err(format!(
+                "Git diff failed: {}",
+                String::from_utf8_lossy(&output.stderr)
+            )),
+        }
+    }
+
+    fn reset_hard(&self, project_path: &str, commit: &str) -> Result<(), String> {
+        run_git(project_path, &["reset", "--hard", commit], "Git reset").map(|_| ())
+    }
+
+    fn reset(&self, project_path: &str, commit: &str, mode: ResetMode) -> Result<(), String> {
+        let flag = match mode {
+            ResetMode::Soft => "--soft",
+            ResetMode::Mixed => "--mixed",
+        };
+        run_git(project_path, &["reset", flag, commit], "Git reset").map(|_| ())
+    }
+
+    fn restore_paths(
+        &self,
+        project_path: &str,
+        commit: &str,
+        paths: &[String],
+    ) -> Result<(), String> {
+        let source = format!("--source={}", commit);
+        let mut args = vec!["restore", source.as_str(), "--staged", "--worktree", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        run_git(project_path, &args, "Git restore").map(|_| ())
+    }
+
+    fn commit_count_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<usize, String> {
+        let range = format!("{}..{}", from, to);
+        let output = run_git(
+            project_path,
+            &["rev-list", "--count", &range],
+            "Git rev-list",
+        )?;
+        stdout_line(&output)
+            .parse::<usize>()
+            .map_err(|e| format!("Failed to parse commit count: {}", e))
+    }
+
+    fn log_between(&self, project_path: &str, from: &str, to: &str) -> Result<Vec<String>, String> {
+        let range = format!("{}..{}", from, to);
+        let output = run_git(project_path, &["log", "--format=%s", &range], "Git log")?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(|line| line.to_string())
+            .collect())
+    }
+
+    fn messages_between(
+        &self,
+        project_path: &str,
+        from: &str,
+        to: &str,
+    ) -> Result<Vec<String>, String> {
+        let range = format!("{}..{}", from, to);
+        let output = run_git(
+            project_path,
+            &["log", "-z", "--format=%B", &range],
+            "Git log",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .split('\0')
+            .filter(|message| !message.is_empty())
+            .map(|message| message.to_string())
+            .collect())
+    }
+
+    fn log_page(
+        &self,
+        project_path: &str,
+        offset: usize,
+        limit: usize,
+        filter: &LogFilter,
+    ) -> Result<Vec<CommitInfo>, String> {
+        if self.current_commit(project_path).is_err() {
+            // No commits yet
+            return Ok(Vec::new());
+        }
+        // Filter without stats (cheap even for long histories), then fetch stats for the page
+        let output = run_git(
+            project_path,
+            &["log", "-z", "--format=%H%x1f%an%x1f%ae%x1f%ct%x1f%B"],
+            "Git log",
+        )?;
+        let mut commits: Vec<CommitInfo> =
+            parse_log_records(&String::from_utf8_lossy(&output.stdout))
+                .into_iter()
+                .filter(|commit| filter.matches(commit))
+                .skip(offset)
+                .take(limit)
+                .collect();
+        self.fill_stats(project_path, &mut commits)?;
+        Ok(commits)
+    }
+
+    fn fill_stats(&self, project_path: &str, commits: &mut [CommitInfo]) -> Result<(), String> {
+        if commits.is_empty() {
+            return Ok(());
+        }
+        let mut args = vec![
+            "log",
+            "--no-walk=unsorted",
+            "--shortstat",
+            "--format=%x00%H",
+        ];
+        let hashes: Vec<String> = commits.iter().map(|c| c.hash.clone()).collect();
+        args.extend(hashes.iter().map(String::as_str));
+        let output = run_git(project_path, &args, "Git log")?;
+        let stats = String::from_utf8_lossy(&output.stdout).to_string();
+        let mut current = None;
+        for line in stats.lines() {
+            if let Some(hash) = line.strip_prefix('\0') {
+                current = commits.iter().position(|c| c.hash == hash);
+            } else if let (Some(index), Some(counts)) = (current, parse_shortstat(line)) {
+                let commit = &mut commits[index];
+                (commit.files_changed, commit.insertions, commit.deletions) = counts;
+            }
+        }
+        Ok(())
+    }
+
+    fn orphaned_commits(&self, project_path: &str) -> Result<Vec<CommitInfo>, String> {
+        if self.current_commit(project_path).is_err() {
+            return Ok(Vec::new());
+        }
+        // Every commit the reflogs mention, except the stash's: its entries are
+        // kept by `refs/stash` itself, and the stash commits aren't lost work
+        let reflogs = run_git(
+            project_path,
+            &["reflog", "show", "--all", "--format=%gD%x00%H"],
+            "Git reflog",
+        )?;
+        let mentioned: String = String::from_utf8_lossy(&reflogs.stdout)
+            .lines()
+            .filter_map(|line| line.split_once('\0'))
+            .filter(|(selector, _)| !selector.starts_with("refs/stash@"))
+            .map(|(_, hash)| format!("{}\n", hash))
+            .collect();
+        // ...minus what any ref (or HEAD) still reaches
+        let output = run_git_with_input(
+            project_path,
+            &[
+                "log",
+                "--stdin",
+                "--not",
+                "--all",
+                "-z",
+                "--format=%H%x1f%an%x1f%ae%x1f%ct%x1f%B",
+            ],
+            &mentioned,
+            "Git log",
+        )?;
+        Ok(parse_log_records(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    fn blame_file(&self, project_path: &str, path: &str) -> Result<Vec<BlameLine>, String> {
+        let output = run_git(
+            project_path,
+            &["blame", "--porcelain", "--", path],
+            "Git blame",
+        )?;
+        let line_commits = parse_blame_porcelain(&String::from_utf8_lossy(&output.stdout));
+
+        let mut hashes: Vec<&str> = line_commits
+            .iter()
+            .filter_map(|(_, hash)| hash.as_deref())
+            .collect();
+        hashes.sort_unstable();
+        hashes.dedup();
+        let mut commits: HashMap<String, CommitInfo> = HashMap::new();
+        if !hashes.is_empty() {
+            let mut args = vec![
+                "log",
+                "--no-walk=unsorted",
+                "-z",
+                "--format=%H%x1f%an%x1f%ae%x1f%ct%x1f%B",
+            ];
+            args.extend(hashes);
+            let output = run_git(project_path, &args, "Git log")?;
+            commits = parse_log_records(&String::from_utf8_lossy(&output.stdout))
+                .into_iter()
+                .map(|commit| (commit.hash.clone(), commit))
+                .collect();
+        }
+        Ok(line_commits
+            .iter()
+            .map(|(line, hash)| BlameLine::new(*line, hash.as_ref().and_then(|h| commits.get(h))))
+            .collect())
+    }
+
+    fn stash_save(&self, project_path: &str, message: &str) -> Result<String, String> {
+        run_git(
+            project_path,
+            &["stash", "push", "-u", "-m", message],
+            "Git stash",
+        )?;
+        let output = run_git(project_path, &["rev-parse", "stash@{0}"], "Git rev-parse")?;
+        Ok(stdout_line(&output))
+    }
+
+    fn stash_snapshot(&self, project_path: &str, message: &str) -> Result<String, String> {
+        let stash = self.stash_save(project_path, message)?;
+        run_git(
+            project_path,
+            &["stash", "apply", "--index", "stash@{0}"],
+            "Git stash apply",
+        )?;
+        Ok(stash)
+    }
+
+    fn stash_list(&self, project_path: &str) -> Result<Vec<StashEntry>, String> {
+        let output = run_git(
+            project_path,
+            &["stash", "list", "--format=%H%x00%ct%x00%gs"],
+            "Git stash list",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .enumerate()
+            .filter_map(|(index, line)| {
+                let mut fields = line.splitn(3, '\0');
+                let commit = fields.next()?.to_string();
+                let seconds = fields.next()?.parse::<i64>().ok();
+                Some(stash_entry(index, commit, fields.next()?, seconds))
+            })
+            .collect())
+    }
+
+    fn stash_apply(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let stash = format!("stash@{{{}}}", index);
+        run_git(project_path, &["stash", "apply", &stash], "Git stash apply").map(|_| ())
+    }
+
+    fn stash_pop(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let stash = format!("stash@{{{}}}", index);
+        run_git(project_path, &["stash", "pop", &stash], "Git stash pop").map(|_| ())
+    }
+
+    fn stash_drop(&self, project_path: &str, index: usize) -> Result<(), String> {
+        let stash = format!("stash@{{{}}}", index);
+        run_git(project_path, &["stash", "drop", &stash], "Git stash drop").map(|_| ())
+    }
+
+    fn revert_range(&self, project_path: &str, from: &str, to: &str) -> Result<(), RevertFailure> {
+        // --no-merges skips merge commits, which would need an ambiguous -m parent
+        let range = format!("{}..{}", from, to);
+        let mut cmd = git_command(project_path);
+        cmd.args(["revert", "--no-commit", "--no-merges", &range]);
+        let output = cmd
+            .output()
+            .map_err(|e| RevertFailure::Failed(format!("Failed to execute git revert: {}", e)))?;
+        if output.status.success() {
+            return Ok(());
+        }
+
+        let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+        if stderr.contains("conflict") || stderr.contains("CONFLICT") {
+            let _ = git_command(project_path)
+                .args(["revert", "--abort"])
+                .output();
+            return Err(RevertFailure::Conflict(stderr));
+        }
+        Err(RevertFailure::Failed(stderr))
+    }
+
+    fn submodules(&self, project_path: &str) -> Result<Vec<SubmoduleInfo>, String> {
+        let status = |cached: bool| {
+            let mut args = vec!["submodule", "status"];
+            if cached {
+                args.push("--cached");
+            }
+            run_git(project_path, &args, "Git submodule status")
+                .map(|output| parse_submodule_status(&String::from_utf8_lossy(&output.stdout)))
+        };
+        let checked_out = status(false)?;
+        let recorded = status(true)?;
+        // Exits with 1 when there is no .gitmodules
+        let urls = run_git(
+            project_path,
+            &[
+                "config",
+                "-f",
+                ".gitmodules",
+                "--get-regexp",
+                r"^submodule\..*\.(path|url)$",
+            ],
+            "Git config",
+        )
+        .map(|output| parse_gitmodules_urls(&String::from_utf8_lossy(&output.stdout)))
+        .unwrap_or_default();
+
+        Ok(checked_out
+            .into_iter()
+            .map(|(path, commit)| {
+                let recorded_commit = recorded
+                    .iter()
+                    .find(|(p, _)| *p == path)
+                    .and_then(|(_, commit)| commit.clone())
+                    .or_else(|| commit.clone());
+                let url = urls.get(&path).cloned();
+                SubmoduleInfo::new(path, url, recorded_commit, commit)
+            })
+            .collect())
+    }
+
+    fn update_submodules(&self, project_path: &str, init: bool) -> Result<(), String> {
+        let mut args = vec!["submodule", "update"];
+        if init {
+            args.push("--init");
+        }
+        run_git(project_path, &args, "Git submodule update").map(|_| ())
+    }
+
+    fn stage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String> {
+        if paths.is_empty() {
+            return Ok(());
+        }
+        let mut args = vec!["add", "-A", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        run_git(project_path, &args, "Git add").map(|_| ())
+    }
+
+    fn unstage_paths(&self, project_path: &str, paths: &[String]) -> Result<(), String> {
+        if paths.is_empty() || self.current_commit(project_path).is_err() {
+            return Ok(());
+        }
+        let mut args = vec!["reset", "-q", "HEAD", "--"];
+        args.extend(paths.iter().map(String::as_str));
+        run_git(project_path, &args, "Git reset").map(|_| ())
+    }
+
+    fn cherry_pick(
+        &self,
+        project_path: &str,
+        commits: &[String],
+        target_branch: &str,
+    ) -> Result<CherryPickResult, String> {
+        let reference = format!("refs/heads/{}", target_branch);
+        let old_tip = stdout_line(&run_git(
+            project_path,
+            &[
+                "rev-parse",
+                "--verify",
+                &format!("{}^{{commit}}", reference),
+            ],
+            "Git rev-parse",
+        )?);
+        let git_dir = stdout_line(&run_git(
+            project_path,
+            &["rev-parse", "--path-format=absolute", "--git-common-dir"],
+            "Git rev-parse",
+        )?);
+
+        // Pick in a scratch worktree so the current checkout is untouched
+        let scratch = format!("{}/anycode-cherry-pick", git_dir);
+        let _ = run_git(
+            project_path,
+            &["worktree", "remove", "--force", &scratch],
+            "Git worktree remove",
+        );
+        run_git(
+            project_path,
+            &["worktree", "add", "--detach", &scratch, &old_tip],
+            "Git worktree add",
+        )?;
+        let result = cli_pick_in(project_path, &scratch, commits);
+        if let Err(e) = run_git(
+            project_path,
+            &["worktree", "remove", "--force", &scratch],
+            "Git worktree remove",
+        ) {
+            log::warn!("Failed to remove cherry-pick worktree: {}", e);
+        }
+        let result = result?;
+        let Some(tip) = result.picked.last().map(|picked| picked.commit.clone()) else {
+            return Ok(result);
+        };
+
+        if self.current_branch(project_path).ok().as_deref() == Some(target_branch) {
+            run_git(project_path, &["merge", "--ff-only", &tip], "Git merge")?;
+        } else {
+            run_git(
+                project_path,
+                &["update-ref", &reference, &tip, &old_tip],
+                "Git update-ref",
+            )?;
+        }
+        Ok(result)
+    }
+
+    fn tree_entry_id(
+        &self,
+        project_path: &str,
+        commit: &str,
+        path: &str,
+    ) -> Result<Option<String>, String> {
+        let spec = format!("{}:{}", commit, path);
+        Ok(run_git(
+            project_path,
+            &["rev-parse", "--verify", "-q", &spec],
+            "Git rev-parse",
+        )
+        .ok()
+        .map(|output| stdout_line(&output)))
+    }
+
+    fn parent_count(&self, project_path: &str, commit: &str) -> Result<usize, String> {
+        // Prints the commit followed by its parents
+        let output = run_git(
+            project_path,
+            &["rev-list", "--parents", "-n", "1", commit, "--"],
+            "Git rev-list",
+        )?;
+        Ok(stdout_line(&output)
+            .split_whitespace()
+            .count()
+            .saturating_sub(1))
+    }
+
+    fn list_branches(&self, project_path: &str) -> Result<Vec<BranchInfo>, String> {
+        let output = run_git(
+            project_path,
+            &[
+                "for-each-ref",
+                "--format=%(refname:short)%00%(objectname)%00%(HEAD)",
+                "refs/heads",
+            ],
+            "Git for-each-ref",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.split('\0');
+                Some(BranchInfo {
+                    name: fields.next()?.to_string(),
+                    commit: fields.next()?.to_string(),
+                    is_current: fields.next()? == "*",
+                })
+            })
+            .collect())
+    }
+
+    fn create_branch(
+        &self,
+        project_path: &str,
+        name: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String> {
+        let mut args = vec!["branch", name];
+        if let Some(start_point) = start_point {
+            args.push(start_point);
+        }
+        run_git(project_path, &args, "Git branch").map(|_| ())
+    }
+
+    fn switch_branch(&self, project_path: &str, name: &str) -> Result<(), String> {
+        let reference = format!("refs/heads/{}", name);
+        run_git(
+            project_path,
+            &["rev-parse", "--verify", &reference],
+            "Git rev-parse",
+        )?;
+        run_git(project_path, &["checkout", name, "--"], "Git checkout").map(|_| ())
+    }
+
+    fn delete_branch(&self, project_path: &str, name: &str, force: bool) -> Result<(), String> {
+        let flag = if force { "-D" } else { "-d" };
+        run_git(project_path, &["branch", flag, name], "Git branch").map(|_| ())
+    }
+
+    fn list_tags(&self, project_path: &str) -> Result<Vec<TagInfo>, String> {
+        let output = run_git(
+            project_path,
+            &[
+                "for-each-ref",
+                "--sort=refname",
+                "--format=%(refname:strip=2)%00%(objecttype)%00%(objectname)%00%(*objectname)%00%(*objecttype)%00%(contents:subject)",
+                "refs/tags",
+            ],
+            "Git for-each-ref",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('\0').collect();
+                let [name, kind, object, peeled, peeled_kind, subject] = fields[..] else {
+                    return None;
+                };
+                // Annotated tags point at a tag object; only tags of commits are listed
+                let (commit, message) = match kind {
+                    "commit" => (object, None),
+                    "tag" if peeled_kind == "commit" => (peeled, Some(subject.to_string())),
+                    _ => return None,
+                };
+                Some(TagInfo {
+                    name: name.to_string(),
+                    commit: commit.to_string(),
+                    message,
+                })
+            })
+            .collect())
+    }
+
+    fn create_tag(
+        &self,
+        project_path: &str,
+        name: &str,
+        commit: Option<&str>,
+        message: Option<&str>,
+    ) -> Result<(), String> {
+        let mut args = vec!["tag"];
+        if let Some(message) = message {
+            args.extend(["-a", "-m", message]);
+        }
+        args.extend(["--", name, commit.unwrap_or("HEAD")]);
+        run_git(project_path, &args, "Git tag").map(|_| ())
+    }
+
+    fn delete_tag(&self, project_path: &str, name: &str) -> Result<(), String> {
+        run_git(project_path, &["tag", "-d", name], "Git tag").map(|_| ())
+    }
+
+    fn add_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        branch: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), String> {
+        let target = git_path(worktree_path);
+        let mut args = vec!["worktree", "add", "-b", branch, target.as_str()];
+        if let Some(start_point) = start_point {
+            args.push(start_point);
+        }
+        run_git(project_path, &args, "Git worktree add").map(|_| ())
+    }
+
+    fn list_worktrees(&self, project_path: &str) -> Result<Vec<WorktreeInfo>, String> {
+        let output = run_git(
+            project_path,
+            &["worktree", "list", "--porcelain"],
+            "Git worktree list",
+        )?;
+        Ok(
+            parse_worktree_list(&String::from_utf8_lossy(&output.stdout))
+                .into_iter()
+                .map(|info| WorktreeInfo {
+                    path: host_path(project_path, &info.path),
+                    ..info
+                })
+                .collect(),
+        )
+    }
+
+    fn remove_worktree(
+        &self,
+        project_path: &str,
+        worktree_path: &str,
+        force: bool,
+    ) -> Result<(), String> {
+        let target = git_path(worktree_path);
+        let mut args = vec!["worktree", "remove"];
+        if force {
+            // Twice to also remove a locked worktree
+            args.extend(["--force", "--force"]);
+        }
+        args.push(&target);
+        run_git(project_path, &args, "Git worktree remove").map(|_| ())
+    }
+
+    fn diff_commits(&self, project_path: &str, from: &str, to: &str) -> Result<String, String> {
+        let output = run_git(
+            project_path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "-M",
+                "--no-color",
+                "--no-ext-diff",
+                from,
+                to,
+            ],
+            "Git diff",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn diff_working_tree(&self, project_path: &str) -> Result<String, String> {
+        let output = run_git(
+            project_path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "-M",
+                "--no-color",
+                "--no-ext-diff",
+                "HEAD",
+            ],
+            "Git diff",
+        )?;
+        let mut patch = String::from_utf8_lossy(&output.stdout).into_owned();
+
+        // `git diff` leaves out untracked files; diff each against /dev/null
+        let untracked = run_git(
+            project_path,
+            &["ls-files", "--others", "--exclude-standard", "-z"],
+            "Git ls-files",
+        )?;
+        for file in String::from_utf8_lossy(&untracked.stdout)
+            .split('\0')
+            .filter(|file| !file.is_empty())
+        {
+            let mut cmd = git_command(project_path);
+            cmd.args([
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "--no-index",
+                "--no-color",
+                "--no-ext-diff",
+                "--",
+                "/dev/null",
+                file,
+            ]);
+            // Exits with 1 when the files differ, which they always do here
+            let output = profiling::timed_output("git diff", &mut cmd)
+                .map_err(|e| format!("Failed to run git diff: {}", e))?;
+            patch.push_str(&String::from_utf8_lossy(&output.stdout));
+        }
+        Ok(patch)
+    }
+
+    fn diff_unstaged(&self, project_path: &str, path: &str) -> Result<String, String> {
+        let output = run_git(
+            project_path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "diff",
+                "--no-color",
+                "--no-ext-diff",
+                "--",
+                path,
+            ],
+            "Git diff",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn format_patch(&self, project_path: &str, from: &str, to: &str) -> Result<String, String> {
+        let range = format!("{}..{}", from, to);
+        let output = run_git(
+            project_path,
+            &[
+                "-c",
+                "core.quotePath=false",
+                "format-patch",
+                "--stdout",
+                "--binary",
+                "--no-color",
+                &range,
+            ],
+            "Git format-patch",
+        )?;
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    fn apply_patch(&self, project_path: &str, patch: &str) -> Result<(), String> {
+        // `git apply` checks every patch before changing anything
+        cli_apply(project_path, "--index", patch)
+    }
+
+    fn apply_to_index(&self, project_path: &str, patch: &str) -> Result<(), String> {
+        cli_apply(project_path, "--cached", patch)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_needs_cli_for_hooks_and_nested_lfs() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let repo = Repository::init(dir.path()).unwrap();
+        assert!(!needs_cli(&path));
+
+        std::fs::create_dir_all(dir.path().join("assets")).unwrap();
+        std::fs::write(
+            dir.path().join("assets/.gitattributes"),
+            "*.png filter=lfs diff=lfs merge=lfs -text\n",
+        )
+        .unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("assets/.gitattributes")).unwrap();
+        index.write().unwrap();
+        assert!(needs_cli(&path));
+
+        let other = tempfile::tempdir().unwrap();
+        let other_path = other.path().to_string_lossy().to_string();
+        let repo = Repository::init(other.path()).unwrap();
+        let hooks = other.path().join(".husky");
+        std::fs::create_dir_all(&hooks).unwrap();
+        std::fs::write(hooks.join("pre-commit"), "#!/bin/sh\nexit 0\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(
+                hooks.join("pre-commit"),
+                std::fs::Permissions::from_mode(0o755),
+            )
+            .unwrap();
+        }
+        assert!(!needs_cli(&other_path));
+        repo.config()
+            .unwrap()
+            .set_str("core.hooksPath", ".husky")
+            .unwrap();
+        assert!(needs_cli(&other_path));
+    }
+
+    #[test]
+    fn test_git2_backend_commit_and_reset() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let first = backend.current_commit(&path).unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "new\n").unwrap();
+        assert!(backend.has_uncommitted_changes(&path).unwrap());
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "second").unwrap();
+        let second = backend.current_commit(&path).unwrap();
+
+        assert!(backend.has_changes_between(&path, &first, &second).unwrap());
+        assert_eq!(
+            backend
+                .commit_count_between(&path, &first, &second)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            backend.log_between(&path, &first, &second).unwrap(),
+            vec!["second".to_string()]
+        );
+
+        backend.revert_range(&path, &first, &second).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+        backend.commit(&path, "revert").unwrap();
+        assert!(!backend.has_uncommitted_changes(&path).unwrap());
+        assert!(!backend.has_changes_between(&path, &first, "HEAD").unwrap());
+
+        let main_branch = backend.current_branch(&path).unwrap();
+        backend
+            .create_branch(&path, "session/a", Some(&first))
+            .unwrap();
+        backend.switch_branch(&path, "session/a").unwrap();
+        let branches = backend.list_branches(&path).unwrap();
+        assert!(branches
+            .iter()
+            .any(|b| b.name == "session/a" && b.is_current && b.commit == first));
+        assert!(backend.delete_branch(&path, "session/a", false).is_err());
+
+        backend.switch_branch(&path, &main_branch).unwrap();
+        let before_reset = backend.current_commit(&path).unwrap();
+        backend.reset(&path, &second, ResetMode::Soft).unwrap();
+        assert_eq!(backend.current_commit(&path).unwrap(), second);
+        // The index and working tree still hold the revert, which differs from `second`
+        assert!(backend.has_uncommitted_changes(&path).unwrap());
+        backend
+            .reset(&path, &before_reset, ResetMode::Mixed)
+            .unwrap();
+        assert!(!backend.has_uncommitted_changes(&path).unwrap());
+
+        backend.reset_hard(&path, &first).unwrap();
+        assert_eq!(backend.current_commit(&path).unwrap(), first);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(!dir.path().join("b.txt").exists());
+    }
+
+    #[test]
+    fn test_git2_backend_worktrees() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo_dir = dir.path().join("repo");
+        std::fs::create_dir(&repo_dir).unwrap();
+        let path = repo_dir.to_string_lossy().to_string();
+        let backend = Git2Backend;
+
+        backend.init(&path).unwrap();
+        std::fs::write(repo_dir.join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+
+        let worktree_dir = dir.path().join("claude-1234");
+        let worktree_path = worktree_dir.to_string_lossy().to_string();
+        backend
+            .add_worktree(&path, &worktree_path, "anycode/claude/1234", None)
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(worktree_dir.join("a.txt")).unwrap(),
+            "one\n"
+        );
+
+        // Listing from the linked worktree still starts with the main checkout
+        let worktrees = backend.list_worktrees(&worktree_path).unwrap();
+        assert_eq!(worktrees.len(), 2);
+        assert!(worktrees[0].is_main);
+        assert_eq!(project_key(&worktrees[0].path), project_key(&path));
+        assert_eq!(worktrees[1].branch.as_deref(), Some("anycode/claude/1234"));
+
+        std::fs::write(worktree_dir.join("a.txt"), "changed\n").unwrap();
+        assert!(backend
+            .remove_worktree(&path, &worktree_path, false)
+            .is_err());
+        backend
+            .remove_worktree(&path, &worktree_path, true)
+            .unwrap();
+        assert!(!worktree_dir.exists());
+        assert_eq!(backend.list_worktrees(&path).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_git2_backend_restore_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::create_dir(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/main.rs"), "one\n").unwrap();
+        std::fs::write(dir.path().join("other.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let first = backend.current_commit(&path).unwrap();
+
+        std::fs::write(dir.path().join("src/main.rs"), "two\n").unwrap();
+        std::fs::write(dir.path().join("src/new.rs"), "new\n").unwrap();
+        std::fs::write(dir.path().join("other.txt"), "two\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "second").unwrap();
+
+        backend
+            .restore_paths(&path, &first, &["src".to_string()])
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/main.rs")).unwrap(),
+            "one\n"
+        );
+        assert!(!dir.path().join("src/new.rs").exists());
+        // Everything outside the restored paths is untouched
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("other.txt")).unwrap(),
+            "two\n"
+        );
+        backend.commit(&path, "restore src").unwrap();
+        assert!(!backend.has_uncommitted_changes(&path).unwrap());
+
+        assert!(backend
+            .restore_paths(&path, &first, &["missing.txt".to_string()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_git2_backend_stash() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "edited\n").unwrap();
+        let first_stash = backend.stash_save(&path, "keep me").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "untracked\n").unwrap();
+        backend.stash_save(&path, "second").unwrap();
+        assert!(!backend.has_uncommitted_changes(&path).unwrap());
+
+        let stashes = backend.stash_list(&path).unwrap();
+        assert_eq!(stashes.len(), 2);
+        assert_eq!(stashes[0].message, "second");
+        assert_eq!((stashes[1].index, &stashes[1].commit), (1, &first_stash));
+        assert!(stashes[1].branch.is_some() && stashes[1].timestamp.is_some());
+
+        // A snapshot keeps the working tree as it was
+        std::fs::write(dir.path().join("a.txt"), "snapshot\n").unwrap();
+        backend.stash_snapshot(&path, "snapshot").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "snapshot\n"
+        );
+        backend.reset_hard(&path, "HEAD").unwrap();
+        backend.stash_drop(&path, 0).unwrap();
+
+        backend.stash_pop(&path, 1).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "edited\n"
+        );
+        backend.stash_drop(&path, 0).unwrap();
+        assert!(backend.stash_list(&path).unwrap().is_empty());
+        assert!(!dir.path().join("new.txt").exists());
+    }
+
+    #[test]
+    fn test_git2_backend_log_page() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        assert!(backend
+            .log_page(&path, 0, 10, &LogFilter::default())
+            .unwrap()
+            .is_empty());
+        backend
+            .configure_identity(&path, "Dev", "dev@example.com")
+            .unwrap();
+        for (i, subject) in [
+            "initial",
+            "[Codex] fix prompt #0",
+            "[Claude Code] test prompt #1",
+        ]
+        .iter()
+        .enumerate()
+        {
+            std::fs::write(dir.path().join("a.txt"), "x\n".repeat(i + 1)).unwrap();
+            backend.stage_all(&path, None).unwrap();
+            backend.commit(&path, subject).unwrap();
+        }
+
+        let all = backend
+            .log_page(&path, 0, 10, &LogFilter::default())
+            .unwrap();
+        assert_eq!(all.len(), 3);
+        let page = backend
+            .log_page(&path, 1, 1, &LogFilter::default())
+            .unwrap();
+        assert_eq!(page, vec![all[1].clone()]);
+        assert_eq!(page[0].engine.as_deref(), Some("codex"));
+        assert_eq!((page[0].files_changed, page[0].insertions), (1, 1));
+        assert_eq!(all[2].insertions, 1);
+
+        let filter = LogFilter {
+            author: Some("EXAMPLE.com".to_string()),
+            engine: Some("user".to_string()),
+            ..LogFilter::default()
+        };
+        let user = backend.log_page(&path, 0, 10, &filter).unwrap();
+        assert_eq!(user.len(), 1);
+        assert_eq!(user[0].subject, "initial");
+
+        assert!(backend.orphaned_commits(&path).unwrap().is_empty());
+        backend.reset_hard(&path, &all[2].hash).unwrap();
+        let mut orphaned = backend.orphaned_commits(&path).unwrap();
+        let hashes: Vec<&str> = orphaned.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec![all[0].hash.as_str(), all[1].hash.as_str()]);
+        backend.fill_stats(&path, &mut orphaned).unwrap();
+        assert_eq!(orphaned[0], all[0]);
+    }
+
+    #[test]
+    fn test_git2_backend_blame_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "initial").unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\nTWO\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend
+            .commit(
+                &path,
+                &commit_metadata::tag_message("[Codex] p prompt #0", "codex", Some("s-1")),
+            )
+            .unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\nTWO\nthree\n").unwrap();
+
+        let lines = backend.blame_file(&path, "a.txt").unwrap();
+        let engines: Vec<(usize, Option<&str>, bool)> = lines
+            .iter()
+            .map(|l| (l.line, l.engine.as_deref(), l.commit.is_some()))
+            .collect();
+        assert_eq!(
+            engines,
+            vec![(1, None, true), (2, Some("codex"), true), (3, None, false)]
+        );
+        assert_eq!(lines[1].session_id.as_deref(), Some("s-1"));
+    }
+
+    #[test]
+    fn test_git2_backend_patch_round_trip() {
+        let setup = || {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().to_string_lossy().to_string();
+            Git2Backend.init(&path).unwrap();
+            std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+            std::fs::write(dir.path().join("c.txt"), "c\n").unwrap();
+            Git2Backend.stage_all(&path, None).unwrap();
+            Git2Backend.commit(&path, "initial").unwrap();
+            (dir, path)
+        };
+        let backend = Git2Backend;
+        let (source_dir, source) = setup();
+        let base = backend.current_commit(&source).unwrap();
+        std::fs::write(source_dir.path().join("a.txt"), "one\nTWO\n").unwrap();
+        backend.stage_all(&source, None).unwrap();
+        backend.commit(&source, "Change two").unwrap();
+        std::fs::write(source_dir.path().join("a.txt"), "one\nTWO\nthree\n").unwrap();
+        std::fs::write(source_dir.path().join("b.txt"), "new\n").unwrap();
+        std::fs::write(source_dir.path().join("c.txt"), "C\n").unwrap();
+        backend.stage_all(&source, None).unwrap();
+        backend.commit(&source, "Add three").unwrap();
+
+        let patch = backend.format_patch(&source, &base, "HEAD").unwrap();
+        assert_eq!(split_mbox(&patch).len(), 2);
+        assert!(patch.contains("Subject: [PATCH] Change two"));
+
+        // A local edit the second patch conflicts with leaves the first one unapplied too
+        let (dirty_dir, dirty) = setup();
+        std::fs::write(dirty_dir.path().join("c.txt"), "local\n").unwrap();
+        assert!(backend.apply_patch(&dirty, &patch).is_err());
+        assert_eq!(
+            std::fs::read_to_string(dirty_dir.path().join("a.txt")).unwrap(),
+            "one\ntwo\n"
+        );
+
+        let (target_dir, target) = setup();
+        backend.apply_patch(&target, &patch).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(target_dir.path().join("a.txt")).unwrap(),
+            "one\nTWO\nthree\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(target_dir.path().join("b.txt")).unwrap(),
+            "new\n"
+        );
+        let staged: Vec<char> = backend
+            .status(&target, None)
+            .unwrap()
+            .iter()
+            .map(|e| e.staged)
+            .collect();
+        assert_eq!(staged, vec!['M', 'A', 'M']);
+    }
+
+    #[test]
+    fn test_parse_porcelain_status() {
+        let entries = parse_porcelain_status("M  a.rs\0R  new.rs\0old.rs\0?? dir/b.txt\0 D c.md\0");
+        let codes: Vec<(&str, char, char)> = entries
+            .iter()
+            .map(|e| (e.path.as_str(), e.staged, e.unstaged))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("a.rs", 'M', ' '),
+                ("new.rs", 'R', ' '),
+                ("dir/b.txt", '?', '?'),
+                ("c.md", ' ', 'D'),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_submodule_status() {
+        let output = format!(
+            " {a} libs/core (v1.2)\n+{b} vendor/ui (heads/main)\n-{c} docs/theme\n",
+            a = "a".repeat(40),
+            b = "b".repeat(40),
+            c = "c".repeat(40)
+        );
+        assert_eq!(
+            parse_submodule_status(&output),
+            vec![
+                ("libs/core".to_string(), Some("a".repeat(40))),
+                ("vendor/ui".to_string(), Some("b".repeat(40))),
+                ("docs/theme".to_string(), None),
+            ]
+        );
+        let urls = parse_gitmodules_urls(
+            "submodule.core.path libs/core\nsubmodule.core.url https://example.com/core.git\n",
+        );
+        assert_eq!(
+            urls.get("libs/core").map(String::as_str),
+            Some("https://example.com/core.git")
+        );
+    }
+
+    #[test]
+    fn test_parse_blame_porcelain() {
+        let hash = "a".repeat(40);
+        let zero = "0".repeat(40);
+        let output = format!(
+            "{hash} 1 1 2\nauthor Dev\nsummary x\nfilename a.txt\n\tone\n\
+             {hash} 2 2\n\ttwo\n{zero} 3 3 1\nauthor Not Committed Yet\n\t{zero} 9 9\n"
+        );
+        assert_eq!(
+            parse_blame_porcelain(&output),
+            vec![(1, Some(hash.clone())), (2, Some(hash)), (3, None)]
+        );
+    }
+
+    #[test]
+    fn test_parse_shortstat() {
+        assert_eq!(
+            parse_shortstat(" 3 files changed, 10 insertions(+), 2 deletions(-)"),
+            Some((3, 10, 2))
+        );
+        assert_eq!(
+            parse_shortstat(" 1 file changed, 1 deletion(-)"),
+            Some((1, 0, 1))
+        );
+    }
+
+    #[test]
+    fn test_parse_stash_subject() {
+        assert_eq!(
+            parse_stash_subject("On main: [Claude Workbench] Auto-stash"),
+            (
+                Some("main".to_string()),
+                "[Claude Workbench] Auto-stash".to_string()
+            )
+        );
+        assert_eq!(
+            parse_stash_subject("WIP on feature/x: 1a2b3c4 subject"),
+            (Some("feature/x".to_string()), "1a2b3c4 subject".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "worktree /home/u/app\nHEAD 1111\nbranch refs/heads/main\n\n\
+                      worktree /home/u/app.worktrees/codex-ab12\nHEAD 2222\nbranch refs/heads/anycode/codex/ab12\nlocked\n\n\
+                      worktree /tmp/gone\nHEAD 3333\ndetached\nprunable gitdir file points to non-existent location\n";
+        let worktrees = parse_worktree_list(output);
+        assert_eq!(worktrees.len(), 3);
+        assert!(worktrees[0].is_main);
+        assert_eq!(worktrees[0].branch.as_deref(), Some("main"));
+        assert_eq!(worktrees[1].branch.as_deref(), Some("anycode/codex/ab12"));
+        assert!(worktrees[1].locked && !worktrees[1].is_main);
+        assert!(worktrees[2].prunable);
+        assert_eq!(worktrees[2].branch, None);
+    }
+}
@@ -0,0 +1,25 @@
+//! Line-level blame
+//!
+//! Tells for every line of a file which commit last changed it, and through
+//! the commit's trailers (see [`commit_metadata`](crate::commands::commit_metadata))
+//! which engine and session, so the editor can color AI-written lines apart
+//! from hand-written ones. Lines changed in the working tree but not committed
+//! come back without a commit.
+
+use crate::commands::git_backend::{backend_for, BlameLine};
+use crate::commands::git_executor::GitOperation;
+use crate::commands::profiling::CommandTimer;
+use crate::commands::simple_git::normalize_restore_path;
+
+/// Blame of a repository-relative file as it currently is on disk
+#[tauri::command]
+pub async fn git_blame_file(project_path: String, path: String) -> Result<Vec<BlameLine>, String> {
+    let path = normalize_restore_path(&path)?;
+    let mut timer = CommandTimer::start("git_blame_file");
+    let lines = GitOperation::new(&project_path, "git blame")
+        .shared()
+        .run(move || backend_for(&project_path).blame_file(&project_path, &path))
+        .await?;
+    timer.payload(&lines);
+    Ok(lines)
+}
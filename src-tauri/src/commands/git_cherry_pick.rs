@@ -0,0 +1,121 @@
+//! Cherry-picking checkpoints onto another branch
+//!
+//! An experimental engine session often produces a few good commits among
+//! many. Those can be copied onto another branch (typically the user's real
+//! feature branch) without checking it out. Each copy records the commit it
+//! came from, and a conflict in any of them leaves the branch untouched and
+//! reports the conflicting files instead.
+
+use crate::commands::git_backend::{backend_for, CherryPickResult, GitBackend};
+use crate::commands::git_executor::GitOperation;
+
+fn cherry_pick(
+    backend: &dyn GitBackend,
+    project_path: &str,
+    commits: &[String],
+    target_branch: &str,
+) -> Result<CherryPickResult, String> {
+    if commits.is_empty() {
+        return Err("No commits selected".to_string());
+    }
+    let on_target = backend.current_branch(project_path).ok().as_deref() == Some(target_branch);
+    if on_target && backend.has_uncommitted_changes(project_path)? {
+        return Err(format!(
+            "Commit or stash uncommitted changes before cherry-picking onto {}",
+            target_branch
+        ));
+    }
+
+    let result = backend.cherry_pick(project_path, commits, target_branch)?;
+    match &result.conflict {
+        Some(conflict) => log::warn!(
+            "[CherryPick] {} conflicts on {} in {} file(s); {} left unchanged",
+            conflict.commit,
+            target_branch,
+            conflict.paths.len(),
+            target_branch
+        ),
+        None => log::info!(
+            "[CherryPick] Picked {} commit(s) onto {}",
+            result.picked.len(),
+            target_branch
+        ),
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Copy `commits`, in the given order, onto `target_branch`
+#[tauri::command]
+pub async fn git_cherry_pick(
+    project_path: String,
+    commits: Vec<String>,
+    target_branch: String,
+) -> Result<CherryPickResult, String> {
+    GitOperation::new(&project_path, "git cherry-pick")
+        .run(move || {
+            cherry_pick(
+                backend_for(&project_path),
+                &project_path,
+                &commits,
+                &target_branch,
+            )
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::Git2Backend;
+
+    #[test]
+    fn test_cherry_pick_onto_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        let commit = |file: &str, content: &str, message: &str| {
+            std::fs::write(dir.path().join(file), content).unwrap();
+            backend.stage_all(&path, None).unwrap();
+            backend.commit(&path, message).unwrap();
+            backend.current_commit(&path).unwrap()
+        };
+
+        commit("a.txt", "one\n", "initial");
+        backend.create_branch(&path, "feature", None).unwrap();
+        let bad = commit("a.txt", "broken\n", "bad idea");
+        let good = commit("b.txt", "good\n", "good idea");
+
+        let result = cherry_pick(&backend, &path, &[good.clone()], "feature").unwrap();
+        assert!(result.conflict.is_none());
+        assert_eq!(result.picked.len(), 1);
+        assert_eq!(result.picked[0].source, good);
+        let feature = &result.picked[0].commit;
+        assert!(backend
+            .tree_entry_id(&path, feature, "b.txt")
+            .unwrap()
+            .is_some());
+        assert_eq!(
+            backend.tree_entry_id(&path, feature, "a.txt").unwrap(),
+            backend
+                .tree_entry_id(&path, &format!("{}~1", bad), "a.txt")
+                .unwrap()
+        );
+        // The checked-out branch is untouched
+        assert_eq!(backend.current_commit(&path).unwrap(), good);
+
+        backend.switch_branch(&path, "feature").unwrap();
+        commit("a.txt", "other\n", "diverge");
+        let tip = backend.current_commit(&path).unwrap();
+        let result = cherry_pick(&backend, &path, &[bad.clone()], "feature").unwrap();
+        let conflict = result.conflict.unwrap();
+        assert_eq!(conflict.commit, bad);
+        assert_eq!(conflict.paths, vec!["a.txt".to_string()]);
+        assert!(result.picked.is_empty());
+        assert_eq!(backend.current_commit(&path).unwrap(), tip);
+    }
+}
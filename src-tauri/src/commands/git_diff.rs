@@ -0,0 +1,362 @@
+//! Structured diffs for checkpoint previews
+//!
+//! Before rolling back, the UI shows exactly what will change. The active
+//! [`GitBackend`](crate::commands::git_backend::GitBackend) renders a unified
+//! diff, which is parsed here into per-file entries (change kind, rename
+//! source, binary flag, line counts) with their hunks and numbered lines.
+//! Very large files keep their counts but have their lines cut off, so a
+//! regenerated lockfile cannot blow up the response.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::git_backend::backend_for;
+use crate::commands::git_executor::GitOperation;
+use crate::commands::profiling::CommandTimer;
+
+/// Lines kept per file; the rest only count towards additions/deletions
+const MAX_LINES_PER_FILE: usize = 5000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileChangeKind {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiffLineKind {
+    Context,
+    Added,
+    Removed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffLine {
+    pub kind: DiffLineKind,
+    pub content: String,
+    /// Line number before the change (None for added lines)
+    pub old_line: Option<u32>,
+    /// Line number after the change (None for removed lines)
+    pub new_line: Option<u32>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffHunk {
+    /// The `@@ -a,b +c,d @@ context` line
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FileDiff {
+    pub path: String,
+    /// Previous path of a renamed file
+    pub old_path: Option<String>,
+    pub change: FileChangeKind,
+    pub binary: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    /// Lines beyond the per-file limit were dropped from the hunks
+    pub truncated: bool,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredDiff {
+    pub files: Vec<FileDiff>,
+    pub files_changed: usize,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+/// Strip the `a/` / `b/` prefix (and quotes) from a header path; None for /dev/null
+fn header_path(value: &str) -> Option<String> {
+    let value = value.trim_end_matches('\t').trim_matches('"');
+    if value == "/dev/null" {
+        return None;
+    }
+    let path = value
+        .strip_prefix("a/")
+        .or_else(|| value.strip_prefix("b/"))
+        .unwrap_or(value);
+    Some(path.to_string())
+}
+
+/// `-12,3` -> (12, 3); a missing count means 1
+fn parse_range(range: &str) -> (u32, u32) {
+    let range = &range[1..];
+    match range.split_once(',') {
+        Some((start, count)) => (start.parse().unwrap_or(0), count.parse().unwrap_or(0)),
+        None => (range.parse().unwrap_or(0), 1),
+    }
+}
+
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let mut parts = line.strip_prefix("@@ ")?.split_whitespace();
+    let (old_start, old_lines) = parse_range(parts.next()?);
+    let (new_start, new_lines) = parse_range(parts.next()?);
+    Some(DiffHunk {
+        header: line.to_string(),
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+/// Parse `git diff` output
+pub fn parse_unified_diff(patch: &str) -> StructuredDiff {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut kept_lines = 0;
+    let (mut old_line, mut new_line) = (0u32, 0u32);
+
+    for line in patch.lines() {
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            // Fallback path; the ---/+++ and rename lines below are unambiguous
+            let path = rest
+                .rsplit_once(" b/")
+                .map(|(_, b)| b.to_string())
+                .unwrap_or_else(|| rest.to_string());
+            files.push(FileDiff {
+                path,
+                old_path: None,
+                change: FileChangeKind::Modified,
+                binary: false,
+                additions: 0,
+                deletions: 0,
+                truncated: false,
+                hunks: Vec::new(),
+            });
+            kept_lines = 0;
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+
+        if file.hunks.is_empty() {
+            if line.starts_with("new file mode") {
+                file.change = FileChangeKind::Added;
+            } else if line.starts_with("deleted file mode") {
+                file.change = FileChangeKind::Deleted;
+            } else if let Some(from) = line.strip_prefix("rename from ") {
+                file.change = FileChangeKind::Renamed;
+                file.old_path = Some(from.to_string());
+            } else if let Some(to) = line.strip_prefix("rename to ") {
+                file.path = to.to_string();
+            } else if line.starts_with("Binary files ") || line == "GIT binary patch" {
+                file.binary = true;
+            } else if let Some(old) = line.strip_prefix("--- ") {
+                if header_path(old).is_none() {
+                    file.change = FileChangeKind::Added;
+                }
+            } else if let Some(new) = line.strip_prefix("+++ ") {
+                match header_path(new) {
+                    Some(path) => file.path = path,
+                    None => file.change = FileChangeKind::Deleted,
+                }
+            }
+        }
+
+        if line.starts_with("@@ ") {
+            if let Some(hunk) = parse_hunk_header(line) {
+                old_line = hunk.old_start;
+                new_line = hunk.new_start;
+                file.hunks.push(hunk);
+            }
+            continue;
+        }
+        let Some(hunk) = file.hunks.last_mut() else {
+            continue;
+        };
+        let (kind, content) = match line.as_bytes().first() {
+            Some(b'+') => (DiffLineKind::Added, &line[1..]),
+            Some(b'-') => (DiffLineKind::Removed, &line[1..]),
+            Some(b' ') => (DiffLineKind::Context, &line[1..]),
+            // Empty context lines lose their leading space in some tools
+            None => (DiffLineKind::Context, ""),
+            // "\ No newline at end of file"
+            _ => continue,
+        };
+        let numbers = match kind {
+            DiffLineKind::Added => {
+                file.additions += 1;
+                new_line += 1;
+                (None, Some(new_line - 1))
+            }
+            DiffLineKind::Removed => {
+                file.deletions += 1;
+                old_line += 1;
+                (Some(old_line - 1), None)
+            }
+            DiffLineKind::Context => {
+                old_line += 1;
+                new_line += 1;
+                (Some(old_line - 1), Some(new_line - 1))
+            }
+        };
+        if kept_lines >= MAX_LINES_PER_FILE {
+            file.truncated = true;
+            continue;
+        }
+        kept_lines += 1;
+        hunk.lines.push(DiffLine {
+            kind,
+            content: content.to_string(),
+            old_line: numbers.0,
+            new_line: numbers.1,
+        });
+    }
+
+    StructuredDiff {
+        files_changed: files.len(),
+        additions: files.iter().map(|f| f.additions).sum(),
+        deletions: files.iter().map(|f| f.deletions).sum(),
+        files,
+    }
+}
+
+pub(crate) fn validate_revision(revision: &str) -> Result<(), String> {
+    if revision.is_empty() || revision.starts_with('-') {
+        return Err(format!("Invalid revision: {}", revision));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Per-file diff between two commits, e.g. a checkpoint and HEAD
+#[tauri::command]
+pub async fn git_diff_commits(
+    project_path: String,
+    from: String,
+    to: String,
+) -> Result<StructuredDiff, String> {
+    validate_revision(&from)?;
+    validate_revision(&to)?;
+    let mut timer = CommandTimer::start("git_diff_commits");
+    let diff = GitOperation::new(&project_path, "git diff")
+        .shared()
+        .run(move || {
+            backend_for(&project_path)
+                .diff_commits(&project_path, &from, &to)
+                .map(|patch| parse_unified_diff(&patch))
+        })
+        .await?;
+    timer.payload(&diff);
+    Ok(diff)
+}
+
+/// Per-file diff of uncommitted changes (staged, unstaged and untracked) against HEAD
+#[tauri::command]
+pub async fn git_diff_working_tree(project_path: String) -> Result<StructuredDiff, String> {
+    let mut timer = CommandTimer::start("git_diff_working_tree");
+    let diff = GitOperation::new(&project_path, "git diff")
+        .shared()
+        .run(move || {
+            backend_for(&project_path)
+                .diff_working_tree(&project_path)
+                .map(|patch| parse_unified_diff(&patch))
+        })
+        .await?;
+    timer.payload(&diff);
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::{Git2Backend, GitBackend};
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let patch = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ mod a;
+ fn one() {}
+-fn two() {}
++fn two() -> u8 { 2 }
+ fn three() {}
+\\ No newline at end of file
+diff --git a/old name.txt b/new name.txt
+similarity index 100%
+rename from old name.txt
+rename to new name.txt
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let diff = parse_unified_diff(patch);
+        assert_eq!(diff.files_changed, 4);
+        assert_eq!((diff.additions, diff.deletions), (1, 2));
+
+        let lib = &diff.files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert_eq!(lib.change, FileChangeKind::Modified);
+        let hunk = &lib.hunks[0];
+        assert_eq!((hunk.old_start, hunk.new_lines), (1, 3));
+        assert_eq!(hunk.lines.len(), 4);
+        assert_eq!(hunk.lines[2].kind, DiffLineKind::Added);
+        assert_eq!(hunk.lines[2].new_line, Some(2));
+        assert_eq!(hunk.lines[3].old_line, Some(3));
+
+        let renamed = &diff.files[1];
+        assert_eq!(renamed.change, FileChangeKind::Renamed);
+        assert_eq!(renamed.path, "new name.txt");
+        assert_eq!(renamed.old_path.as_deref(), Some("old name.txt"));
+
+        assert!(diff.files[2].binary);
+        assert_eq!(diff.files[2].change, FileChangeKind::Added);
+        assert_eq!(diff.files[3].change, FileChangeKind::Deleted);
+        assert_eq!(diff.files[3].path, "gone.txt");
+    }
+
+    #[test]
+    fn test_git2_working_tree_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\ntwo\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let first = backend.current_commit(&path).unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "one\n2\n").unwrap();
+        std::fs::write(dir.path().join("new.txt"), "fresh\n").unwrap();
+        let diff = parse_unified_diff(&backend.diff_working_tree(&path).unwrap());
+        assert_eq!(diff.files_changed, 2);
+        let new_file = diff.files.iter().find(|f| f.path == "new.txt").unwrap();
+        assert_eq!(new_file.change, FileChangeKind::Added);
+        assert_eq!(new_file.additions, 1);
+
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "second").unwrap();
+        let diff = parse_unified_diff(&backend.diff_commits(&path, &first, "HEAD").unwrap());
+        assert_eq!((diff.additions, diff.deletions), (2, 1));
+    }
+}
@@ -0,0 +1,393 @@
+//! Repository health check (`git_repo_doctor`)
+//!
+//! Finds the breakage that makes workbench git operations fail: no git
+//! binary, a stale `index.lock` left by a killed git process, a detached HEAD,
+//! a merge, rebase, cherry-pick or revert left half-done, and corrupt or
+//! missing objects. Issues with a safe fix can be repaired with
+//! `git_repo_repair`; corrupt objects only get a diagnosis, since repairing
+//! them needs a fresh fetch or clone.
+
+use chrono::{DateTime, Utc};
+use git2::{Repository, RepositoryState};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::commands::git_backend::{backend_for, GitBackend};
+use crate::commands::git_executor::{GitOperation, LONG_TIMEOUT};
+use crate::commands::simple_git::{ensure_git_repo, validate_branch_name};
+use crate::commands::wsl_utils::git_command;
+
+/// A lock file older than this is taken to be left behind by a dead process
+const STALE_LOCK_AGE: Duration = Duration::from_secs(60);
+
+/// Lines of `git fsck` output kept in the report
+const MAX_FSCK_LINES: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RepoIssueKind {
+    GitMissing,
+    NotARepository,
+    StaleIndexLock,
+    DetachedHead,
+    MergeInProgress,
+    RebaseInProgress,
+    CherryPickInProgress,
+    RevertInProgress,
+    CorruptObjects,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoIssue {
+    pub kind: RepoIssueKind,
+    pub detail: String,
+    /// What `git_repo_repair` would do; None when it has to be fixed by hand
+    pub fix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoHealthReport {
+    pub checked_at: DateTime<Utc>,
+    pub issues: Vec<RepoIssue>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepoRepairResult {
+    pub repaired: Vec<RepoIssueKind>,
+    /// Fixes that were attempted and failed
+    pub errors: Vec<String>,
+    /// The repository after the repair
+    pub report: RepoHealthReport,
+}
+
+fn issue(kind: RepoIssueKind, detail: String, fix: Option<&str>) -> RepoIssue {
+    RepoIssue {
+        kind,
+        detail,
+        fix: fix.map(str::to_string),
+    }
+}
+
+fn git_version(project_path: &str) -> Option<String> {
+    let mut cmd = git_command(project_path);
+    cmd.arg("--version");
+    let output = cmd.output().ok().filter(|output| output.status.success())?;
+    Some(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn index_lock(repo: &Repository) -> PathBuf {
+    repo.path().join("index.lock")
+}
+
+/// Age of the index lock when it is old enough to be stale
+fn stale_lock_age(lock: &Path) -> Option<Duration> {
+    let modified = std::fs::metadata(lock).ok()?.modified().ok()?;
+    let age = SystemTime::now().duration_since(modified).ok()?;
+    (age >= STALE_LOCK_AGE).then_some(age)
+}
+
+/// The operation left half-done, with the command that abandons it
+fn pending_operation(state: RepositoryState) -> Option<(RepoIssueKind, &'static str)> {
+    match state {
+        RepositoryState::Merge => Some((RepoIssueKind::MergeInProgress, "merge")),
+        RepositoryState::Rebase
+        | RepositoryState::RebaseInteractive
+        | RepositoryState::RebaseMerge
+        | RepositoryState::ApplyMailbox
+        | RepositoryState::ApplyMailboxOrRebase => {
+            Some((RepoIssueKind::RebaseInProgress, "rebase"))
+        }
+        RepositoryState::CherryPick | RepositoryState::CherryPickSequence => {
+            Some((RepoIssueKind::CherryPickInProgress, "cherry-pick"))
+        }
+        RepositoryState::Revert | RepositoryState::RevertSequence => {
+            Some((RepoIssueKind::RevertInProgress, "revert"))
+        }
+        _ => None,
+    }
+}
+
+/// Problems reported by `git fsck`, without dangling objects (those are normal)
+fn fsck_errors(project_path: &str) -> Result<Vec<String>, String> {
+    let mut cmd = git_command(project_path);
+    cmd.args([
+        "fsck",
+        "--no-progress",
+        "--no-dangling",
+        "--connectivity-only",
+    ]);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git fsck: {}", e))?;
+    if output.status.success() {
+        return Ok(Vec::new());
+    }
+    let mut lines: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .chain(String::from_utf8_lossy(&output.stderr).lines())
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+        .collect();
+    if lines.is_empty() {
+        lines.push(format!("git fsck exited with {}", output.status));
+    }
+    Ok(lines)
+}
+
+fn diagnose(project_path: &str) -> RepoHealthReport {
+    let mut issues = Vec::new();
+    let git_available = git_version(project_path).is_some();
+    if !git_available {
+        issues.push(issue(
+            RepoIssueKind::GitMissing,
+            "The git executable could not be run; install git and make sure it is on PATH"
+                .to_string(),
+            None,
+        ));
+    }
+
+    let repo = match Repository::discover(project_path) {
+        Ok(repo) => repo,
+        Err(e) => {
+            issues.push(issue(
+                RepoIssueKind::NotARepository,
+                e.message().to_string(),
+                Some("Initialize a repository with an initial commit"),
+            ));
+            return RepoHealthReport {
+                checked_at: Utc::now(),
+                issues,
+            };
+        }
+    };
+
+    if let Some(age) = stale_lock_age(&index_lock(&repo)) {
+        issues.push(issue(
+            RepoIssueKind::StaleIndexLock,
+            format!(
+                "index.lock has been there for {}s; a git process was probably killed",
+                age.as_secs()
+            ),
+            Some("Delete the lock file"),
+        ));
+    }
+
+    if let Some((kind, operation)) = pending_operation(repo.state()) {
+        issues.push(issue(
+            kind,
+            format!("A {} was started and never finished", operation),
+            Some("Abort it, going back to the state before it started"),
+        ));
+    }
+
+    if repo.head_detached().unwrap_or(false) {
+        let head = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string())
+            .unwrap_or_default();
+        issues.push(issue(
+            RepoIssueKind::DetachedHead,
+            format!(
+                "HEAD is detached at {}; new commits would not be on any branch",
+                &head[..8.min(head.len())]
+            ),
+            Some("Check out a branch at the same commit, creating one if needed"),
+        ));
+    }
+
+    if git_available {
+        match fsck_errors(project_path) {
+            Ok(errors) if !errors.is_empty() => issues.push(issue(
+                RepoIssueKind::CorruptObjects,
+                errors
+                    .into_iter()
+                    .take(MAX_FSCK_LINES)
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                None,
+            )),
+            Ok(_) => {}
+            Err(e) => log::warn!("[GitDoctor] {}", e),
+        }
+    }
+
+    RepoHealthReport {
+        checked_at: Utc::now(),
+        issues,
+    }
+}
+
+/// Attach HEAD to a branch at its commit: an existing one if any, else a new one
+fn attach_head(backend: &dyn GitBackend, project_path: &str) -> Result<String, String> {
+    let head = backend.current_commit(project_path)?;
+    let branches = backend.list_branches(project_path)?;
+    let name = match branches.iter().find(|branch| branch.commit == head) {
+        Some(branch) => branch.name.clone(),
+        None => {
+            let base = format!("anycode/detached-{}", &head[..8.min(head.len())]);
+            let name = std::iter::once(base.clone())
+                .chain((2..).map(|n| format!("{}-{}", base, n)))
+                .find(|name| !branches.iter().any(|branch| &branch.name == name))
+                .unwrap_or(base);
+            validate_branch_name(&name)?;
+            backend.create_branch(project_path, &name, None)?;
+            name
+        }
+    };
+    backend.switch_branch(project_path, &name)?;
+    Ok(name)
+}
+
+fn abort_operation(project_path: &str, operation: &str) -> Result<(), String> {
+    let mut cmd = git_command(project_path);
+    cmd.args([operation, "--abort"]);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git {} --abort: {}", operation, e))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!(
+            "git {} --abort failed: {}",
+            operation,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn repair_issue(project_path: &str, kind: RepoIssueKind) -> Result<(), String> {
+    match kind {
+        RepoIssueKind::NotARepository => ensure_git_repo(project_path),
+        RepoIssueKind::StaleIndexLock => {
+            let repo = Repository::discover(project_path).map_err(|e| e.to_string())?;
+            let lock = index_lock(&repo);
+            // Only while it is still stale; a live git process may have taken it meanwhile
+            if stale_lock_age(&lock).is_none() {
+                return Err("index.lock is in use".to_string());
+            }
+            std::fs::remove_file(&lock)
+                .map_err(|e| format!("Failed to delete {}: {}", lock.display(), e))
+        }
+        RepoIssueKind::DetachedHead => {
+            // Aborting a rebase may have put HEAD back on its branch already
+            let repo = Repository::discover(project_path).map_err(|e| e.to_string())?;
+            if !repo.head_detached().unwrap_or(false) {
+                return Ok(());
+            }
+            let branch = attach_head(backend_for(project_path), project_path)?;
+            log::info!(
+                "[GitDoctor] Attached HEAD to {} in {}",
+                branch,
+                project_path
+            );
+            Ok(())
+        }
+        RepoIssueKind::MergeInProgress => abort_operation(project_path, "merge"),
+        RepoIssueKind::RebaseInProgress => abort_operation(project_path, "rebase"),
+        RepoIssueKind::CherryPickInProgress => abort_operation(project_path, "cherry-pick"),
+        RepoIssueKind::RevertInProgress => abort_operation(project_path, "revert"),
+        RepoIssueKind::GitMissing | RepoIssueKind::CorruptObjects => {
+            Err(format!("{:?} has to be fixed by hand", kind))
+        }
+    }
+}
+
+fn repair(project_path: &str, kinds: &[RepoIssueKind]) -> RepoRepairResult {
+    let found = diagnose(project_path);
+    let mut repaired = Vec::new();
+    let mut errors = Vec::new();
+    // In report order: the lock goes first, since the other fixes need the index
+    for issue in &found.issues {
+        if issue.fix.is_none() || !kinds.contains(&issue.kind) {
+            continue;
+        }
+        match repair_issue(project_path, issue.kind) {
+            Ok(()) => {
+                log::info!("[GitDoctor] Repaired {:?} in {}", issue.kind, project_path);
+                repaired.push(issue.kind);
+            }
+            Err(e) => errors.push(format!("{:?}: {}", issue.kind, e)),
+        }
+    }
+    RepoRepairResult {
+        repaired,
+        errors,
+        report: diagnose(project_path),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Check a repository for common breakage
+#[tauri::command]
+pub async fn git_repo_doctor(project_path: String) -> Result<RepoHealthReport, String> {
+    GitOperation::new(&project_path, "git fsck")
+        .shared()
+        .timeout(LONG_TIMEOUT)
+        .run(move || Ok(diagnose(&project_path)))
+        .await
+}
+
+/// Apply the safe fixes for the given issues, if they are still present
+#[tauri::command]
+pub async fn git_repo_repair(
+    project_path: String,
+    issues: Vec<RepoIssueKind>,
+) -> Result<RepoRepairResult, String> {
+    GitOperation::new(&project_path, "repository repair")
+        .timeout(LONG_TIMEOUT)
+        .run(move || Ok(repair(&project_path, &issues)))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::Git2Backend;
+
+    #[test]
+    fn test_detached_head_and_stale_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "initial").unwrap();
+        let head = backend.current_commit(&path).unwrap();
+        let main = backend.current_branch(&path).unwrap();
+
+        let repo = Repository::open(&path).unwrap();
+        repo.set_head_detached(git2::Oid::from_str(&head).unwrap())
+            .unwrap();
+        let lock = index_lock(&repo);
+        std::fs::write(&lock, "").unwrap();
+        let old = SystemTime::now() - STALE_LOCK_AGE * 2;
+        std::fs::File::options()
+            .write(true)
+            .open(&lock)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let kinds: Vec<RepoIssueKind> = diagnose(&path)
+            .issues
+            .iter()
+            .map(|issue| issue.kind)
+            .collect();
+        assert!(kinds.contains(&RepoIssueKind::DetachedHead));
+        assert!(kinds.contains(&RepoIssueKind::StaleIndexLock));
+
+        std::fs::remove_file(&lock).unwrap();
+        assert_eq!(attach_head(&backend, &path).unwrap(), main);
+        assert!(!repo.head_detached().unwrap());
+    }
+}
@@ -0,0 +1,396 @@
+//! Git operation executor
+//!
+//! Git commands run off the Tauri threads, one repository at a time: every
+//! operation takes the repository's lock, shared for reads (log, diff, blame)
+//! and exclusive for anything that writes, so writes queue up in arrival order
+//! and never interleave. Each running operation has a timeout and can be
+//! cancelled; either kills the git processes it started, so a hung network
+//! command no longer blocks the repository. In-process (libgit2) work cannot be
+//! interrupted, but the caller gets its answer right away and the repository
+//! stays locked until that work ends.
+//!
+//! Events:
+//! - `git-operation` - emitted with a [`GitOperationEvent`] whenever an
+//!   operation is queued, starts or ends
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::io;
+use std::process::{Command, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::sync::{Notify, RwLock};
+
+use crate::commands::engine_safety::project_key;
+use crate::process::ledger::terminate_pid;
+
+/// Timeout of operations that don't set their own
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+/// Timeout of operations that may go to the network or check out a lot of files
+pub const LONG_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Prefix of the error returned for cancelled operations, checked by the frontend
+pub const CANCELLED_ERROR_PREFIX: &str = "Cancelled";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OperationState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitOperationEvent {
+    pub id: u64,
+    pub project_path: String,
+    /// What the operation does, e.g. `git log`
+    pub label: String,
+    pub state: OperationState,
+    pub queued_at: DateTime<Utc>,
+    pub error: Option<String>,
+}
+
+/// An operation in flight, as the executor tracks it
+struct Operation {
+    info: Mutex<GitOperationEvent>,
+    cancelled: AtomicBool,
+    cancel: Notify,
+    /// Git processes currently running for the operation
+    pids: Mutex<Vec<u32>>,
+}
+
+impl Operation {
+    fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// Stop the operation's processes; the waiting caller is woken when `notify` is set
+    fn cancel(&self, notify: bool) {
+        self.cancelled.store(true, Ordering::SeqCst);
+        let pids = self
+            .pids
+            .lock()
+            .map(|pids| pids.clone())
+            .unwrap_or_default();
+        for pid in pids {
+            if let Err(e) = terminate_pid(pid) {
+                log::debug!("[GitExecutor] Failed to stop git process {}: {}", pid, e);
+            }
+        }
+        if notify {
+            self.cancel.notify_one();
+        }
+    }
+
+    fn snapshot(&self) -> Option<GitOperationEvent> {
+        self.info.lock().ok().map(|info| info.clone())
+    }
+}
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+static OPERATIONS: Lazy<Mutex<HashMap<u64, Arc<Operation>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+/// One lock per repository, by project key
+static REPOSITORIES: Lazy<Mutex<HashMap<String, Arc<RwLock<()>>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+thread_local! {
+    /// The operation the current blocking thread works for
+    static CURRENT: RefCell<Option<Arc<Operation>>> = const { RefCell::new(None) };
+}
+
+/// Let the executor emit `git-operation` events
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+fn repository_lock(project_path: &str) -> Arc<RwLock<()>> {
+    let mut repositories = REPOSITORIES.lock().unwrap_or_else(|e| e.into_inner());
+    repositories
+        .entry(project_key(project_path))
+        .or_insert_with(|| Arc::new(RwLock::new(())))
+        .clone()
+}
+
+fn set_state(operation: &Operation, state: OperationState, error: Option<String>) {
+    let Some(event) = operation.info.lock().ok().map(|mut info| {
+        info.state = state;
+        info.error = error;
+        info.clone()
+    }) else {
+        return;
+    };
+    if let Some(app) = APP.get() {
+        if let Err(e) = app.emit("git-operation", &event) {
+            log::debug!("[GitExecutor] Failed to emit git-operation: {}", e);
+        }
+    }
+}
+
+/// Run a git subprocess to completion, stopping it if the current operation is cancelled
+///
+/// Outside an operation this is `cmd.output()`.
+pub fn output(cmd: &mut Command) -> io::Result<Output> {
+    let Some(operation) = CURRENT.with(|current| current.borrow().clone()) else {
+        return cmd.output();
+    };
+    if operation.is_cancelled() {
+        return Err(io::Error::new(
+            io::ErrorKind::Interrupted,
+            "operation cancelled",
+        ));
+    }
+
+    let child = cmd
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let pid = child.id();
+    if let Ok(mut pids) = operation.pids.lock() {
+        pids.push(pid);
+    }
+    // Cancelled between the check above and the registration
+    if operation.is_cancelled() {
+        let _ = terminate_pid(pid);
+    }
+    let output = child.wait_with_output();
+    if let Ok(mut pids) = operation.pids.lock() {
+        pids.retain(|p| *p != pid);
+    }
+    output
+}
+
+/// A git operation on one repository, see the module docs
+pub struct GitOperation {
+    project_path: String,
+    label: String,
+    shared: bool,
+    timeout: Duration,
+}
+
+impl GitOperation {
+    pub fn new(project_path: &str, label: &str) -> Self {
+        Self {
+            project_path: project_path.to_string(),
+            label: label.to_string(),
+            shared: false,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Read-only: may run alongside other reads of the repository
+    pub fn shared(mut self) -> Self {
+        self.shared = true;
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Queue `work`, run it on a blocking thread once the repository is free, and wait for it
+    pub async fn run<T, F>(self, work: F) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> Result<T, String> + Send + 'static,
+    {
+        let id = NEXT_ID.fetch_add(1, Ordering::SeqCst);
+        let operation = Arc::new(Operation {
+            info: Mutex::new(GitOperationEvent {
+                id,
+                project_path: self.project_path.clone(),
+                label: self.label.clone(),
+                state: OperationState::Queued,
+                queued_at: Utc::now(),
+                error: None,
+            }),
+            cancelled: AtomicBool::new(false),
+            cancel: Notify::new(),
+            pids: Mutex::new(Vec::new()),
+        });
+        if let Ok(mut operations) = OPERATIONS.lock() {
+            operations.insert(id, operation.clone());
+        }
+        set_state(&operation, OperationState::Queued, None);
+
+        let result = tokio::select! {
+            result = execute(&self.project_path, &operation, self.shared, self.timeout, work) => result,
+            _ = operation.cancel.notified() => Err((
+                OperationState::Cancelled,
+                format!("{}: {} was cancelled", CANCELLED_ERROR_PREFIX, self.label),
+            )),
+        };
+
+        if let Ok(mut operations) = OPERATIONS.lock() {
+            operations.remove(&id);
+        }
+        match result {
+            Ok(value) => {
+                set_state(&operation, OperationState::Succeeded, None);
+                Ok(value)
+            }
+            Err((state, error)) => {
+                if state != OperationState::Failed {
+                    log::warn!(
+                        "[GitExecutor] {} in {}: {}",
+                        self.label,
+                        self.project_path,
+                        error
+                    );
+                }
+                set_state(&operation, state, Some(error.clone()));
+                Err(error)
+            }
+        }
+    }
+}
+
+async fn execute<T, F>(
+    project_path: &str,
+    operation: &Arc<Operation>,
+    shared: bool,
+    timeout: Duration,
+    work: F,
+) -> Result<T, (OperationState, String)>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+{
+    let lock = repository_lock(project_path);
+    // The guard travels with the work, so the repository stays locked until it really ends
+    let (read_guard, write_guard) = if shared {
+        (Some(lock.read_owned().await), None)
+    } else {
+        (None, Some(lock.write_owned().await))
+    };
+    set_state(operation, OperationState::Running, None);
+
+    let current = operation.clone();
+    let task = tokio::task::spawn_blocking(move || {
+        let _guards = (read_guard, write_guard);
+        CURRENT.with(|slot| *slot.borrow_mut() = Some(current));
+        let result = work();
+        CURRENT.with(|slot| *slot.borrow_mut() = None);
+        result
+    });
+    match tokio::time::timeout(timeout, task).await {
+        Ok(Ok(Ok(value))) => Ok(value),
+        Ok(Ok(Err(e))) => Err((OperationState::Failed, e)),
+        Ok(Err(e)) => Err((OperationState::Failed, e.to_string())),
+        Err(_) => {
+            operation.cancel(false);
+            Err((
+                OperationState::TimedOut,
+                format!("Timed out after {}s", timeout.as_secs()),
+            ))
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Git operations queued or running, oldest first
+#[tauri::command]
+pub async fn list_git_operations() -> Result<Vec<GitOperationEvent>, String> {
+    let mut operations: Vec<GitOperationEvent> = OPERATIONS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .values()
+        .filter_map(|operation| operation.snapshot())
+        .collect();
+    operations.sort_by_key(|operation| operation.id);
+    Ok(operations)
+}
+
+/// Cancel a queued or running operation; false when it already ended
+#[tauri::command]
+pub async fn cancel_git_operation(id: u64) -> Result<bool, String> {
+    let operation = OPERATIONS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&id)
+        .cloned();
+    match operation {
+        Some(operation) => {
+            operation.cancel(true);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_run_one_at_a_time() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let running = Arc::new(AtomicU64::new(0));
+        let overlaps = Arc::new(AtomicU64::new(0));
+
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let (running, overlaps) = (running.clone(), overlaps.clone());
+                GitOperation::new(&path, "write").run(move || {
+                    if running.fetch_add(1, Ordering::SeqCst) > 0 {
+                        overlaps.fetch_add(1, Ordering::SeqCst);
+                    }
+                    std::thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                    Ok(())
+                })
+            })
+            .collect();
+        for result in futures::future::join_all(tasks).await {
+            result.unwrap();
+        }
+        assert_eq!(overlaps.load(Ordering::SeqCst), 0);
+    }
+
+    #[tokio::test]
+    async fn test_timeout_and_cancel() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+
+        let error = GitOperation::new(&path, "slow")
+            .timeout(Duration::from_millis(20))
+            .run(|| {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(())
+            })
+            .await
+            .unwrap_err();
+        assert!(error.starts_with("Timed out"));
+
+        let slow = tokio::spawn(GitOperation::new(&path, "queued").run(|| Ok(())));
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let id = list_git_operations()
+            .await
+            .unwrap()
+            .into_iter()
+            .find(|operation| operation.project_path == path && operation.label == "queued")
+            .unwrap()
+            .id;
+        assert!(cancel_git_operation(id).await.unwrap());
+        let result = slow.await.unwrap();
+        assert!(result.unwrap_err().starts_with(CANCELLED_ERROR_PREFIX));
+        assert!(!cancel_git_operation(id).await.unwrap());
+    }
+}
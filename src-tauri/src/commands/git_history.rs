@@ -0,0 +1,55 @@
+//! Paginated commit history
+//!
+//! The history panel loads commits a page at a time as it scrolls, so
+//! repositories with thousands of commits stay responsive. Each commit comes
+//! with its author, date, change counts and the engine and session that
+//! created it (see [`commit_metadata`](crate::commands::commit_metadata)).
+//! Filtering happens before paging, so offsets count matching commits only.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::git_backend::{backend_for, CommitInfo, LogFilter};
+use crate::commands::git_executor::GitOperation;
+use crate::commands::profiling::CommandTimer;
+
+/// Upper bound for `limit`; change counts are computed per returned commit
+const MAX_PAGE_SIZE: usize = 500;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogPage {
+    pub commits: Vec<CommitInfo>,
+    pub offset: usize,
+    /// More matching commits follow this page
+    pub has_more: bool,
+}
+
+/// A page of the history reachable from HEAD, newest first
+#[tauri::command]
+pub async fn git_log(
+    project_path: String,
+    offset: usize,
+    limit: usize,
+    filter: Option<LogFilter>,
+) -> Result<LogPage, String> {
+    let limit = limit.clamp(1, MAX_PAGE_SIZE);
+    let filter = filter.unwrap_or_default();
+    let mut timer = CommandTimer::start("git_log");
+    let page = GitOperation::new(&project_path, "git log")
+        .shared()
+        .run(move || {
+            // One extra commit tells whether another page exists
+            let mut commits =
+                backend_for(&project_path).log_page(&project_path, offset, limit + 1, &filter)?;
+            let has_more = commits.len() > limit;
+            commits.truncate(limit);
+            Ok::<_, String>(LogPage {
+                commits,
+                offset,
+                has_more,
+            })
+        })
+        .await?;
+    timer.payload(&page);
+    Ok(page)
+}
@@ -0,0 +1,171 @@
+//! Commit identity
+//!
+//! Commits are authored by whoever git is configured for: the repository's
+//! own `user.name`/`user.email`, or else the user's global config. Only when
+//! neither is set does the workbench write a fallback identity, and then only
+//! to the repository's local config.
+//!
+//! Optionally AI commits get a distinct committer, so `git log --format=%cn`
+//! separates them from hand-made commits while authorship stays with the user.
+//!
+//! Settings live in `~/.anycode/git-identity.json`.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+use crate::commands::git_backend::backend_for;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitIdentity {
+    pub name: String,
+    pub email: String,
+}
+
+impl GitIdentity {
+    fn validate(&self) -> Result<(), String> {
+        let invalid =
+            |value: &str| value.trim().is_empty() || value.contains(['\n', '\r', '<', '>']);
+        if invalid(&self.name) || invalid(&self.email) {
+            return Err(format!(
+                "Invalid git identity: {} <{}>",
+                self.name, self.email
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitIdentitySettings {
+    /// Written to a repository's local config when git has no identity at all
+    #[serde(default = "default_fallback")]
+    pub fallback: GitIdentity,
+    /// Committer for AI commits; `None` commits as the author
+    #[serde(default)]
+    pub ai_committer: Option<GitIdentity>,
+}
+
+impl Default for GitIdentitySettings {
+    fn default() -> Self {
+        Self {
+            fallback: default_fallback(),
+            ai_committer: None,
+        }
+    }
+}
+
+fn default_fallback() -> GitIdentity {
+    GitIdentity {
+        name: "Claude Workbench".to_string(),
+        email: "ai@claude.workbench".to_string(),
+    }
+}
+
+static SETTINGS: Lazy<RwLock<GitIdentitySettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("git-identity.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+pub fn settings() -> GitIdentitySettings {
+    SETTINGS
+        .read()
+        .map(|settings| settings.clone())
+        .unwrap_or_default()
+}
+
+/// Make sure git can commit in the repository, without overriding an existing identity
+pub fn ensure_identity(project_path: &str) -> Result<(), String> {
+    let backend = backend_for(project_path);
+    if backend.configured_identity(project_path)?.is_some() {
+        return Ok(());
+    }
+    let fallback = settings().fallback;
+    log::info!(
+        "No git identity configured for {}, using {} <{}> locally",
+        project_path,
+        fallback.name,
+        fallback.email
+    );
+    backend.configure_identity(project_path, &fallback.name, &fallback.email)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_git_identity_settings() -> Result<GitIdentitySettings, String> {
+    Ok(settings())
+}
+
+#[tauri::command]
+pub async fn update_git_identity_settings(settings: GitIdentitySettings) -> Result<(), String> {
+    settings.fallback.validate()?;
+    if let Some(committer) = &settings.ai_committer {
+        committer.validate()?;
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+/// The identity git commits with in a project, if one is configured
+#[tauri::command]
+pub async fn get_git_identity(project_path: String) -> Result<Option<GitIdentity>, String> {
+    tokio::task::spawn_blocking(move || {
+        backend_for(&project_path).configured_identity(&project_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::{Git2Backend, GitBackend};
+
+    #[test]
+    fn test_identity() {
+        assert!(default_fallback().validate().is_ok());
+        let identity = |name: &str, email: &str| GitIdentity {
+            name: name.to_string(),
+            email: email.to_string(),
+        };
+        assert!(identity(" ", "a@b.c").validate().is_err());
+        assert!(identity("A <evil>", "a@b.c").validate().is_err());
+        assert!(identity("A", "a@b.c\n").validate().is_err());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        backend
+            .configure_identity(&path, "Jane Doe", "jane@example.com")
+            .unwrap();
+        assert_eq!(
+            backend.configured_identity(&path).unwrap(),
+            Some(identity("Jane Doe", "jane@example.com"))
+        );
+
+        // An existing identity is left alone
+        ensure_identity(&path).unwrap();
+        assert_eq!(
+            backend.configured_identity(&path).unwrap(),
+            Some(identity("Jane Doe", "jane@example.com"))
+        );
+    }
+}
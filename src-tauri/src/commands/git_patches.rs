@@ -0,0 +1,87 @@
+//! Patch export and import
+//!
+//! An AI session's commits can be handed to a colleague, or moved to another
+//! checkout, without pushing: they are exported as a standard
+//! `git format-patch` mailbox (readable by `git am` as well) and applied
+//! elsewhere to the working tree and index for review before committing.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::commands::git_backend::{backend_for, split_mbox};
+use crate::commands::git_executor::GitOperation;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchExport {
+    pub path: String,
+    /// Commits in the patch (merges are left out)
+    pub commits: usize,
+    pub bytes: usize,
+}
+
+fn export_patch(
+    project_path: &str,
+    from: &str,
+    to: &str,
+    out_path: &str,
+) -> Result<PatchExport, String> {
+    let patch = backend_for(project_path).format_patch(project_path, from, to)?;
+    if patch.trim().is_empty() {
+        return Err(format!("No commits to export between {} and {}", from, to));
+    }
+    if let Some(parent) = Path::new(out_path).parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(out_path, &patch).map_err(|e| format!("Failed to write patch: {}", e))?;
+    log::info!("[Patches] Exported {}..{} to {}", from, to, out_path);
+    Ok(PatchExport {
+        path: out_path.to_string(),
+        commits: split_mbox(&patch).len(),
+        bytes: patch.len(),
+    })
+}
+
+fn apply_patch(project_path: &str, patch: &str) -> Result<(), String> {
+    if patch.trim().is_empty() {
+        return Err("Patch is empty".to_string());
+    }
+    let backend = backend_for(project_path);
+    if backend.has_uncommitted_changes(project_path)? {
+        return Err("Commit or stash uncommitted changes before applying a patch".to_string());
+    }
+    backend.apply_patch(project_path, patch)?;
+    log::info!(
+        "[Patches] Applied {} patch(es) to {}",
+        split_mbox(patch).len(),
+        project_path
+    );
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Write the commits in `from..to` to `out_path` as a `format-patch` mailbox
+#[tauri::command]
+pub async fn git_export_patch(
+    project_path: String,
+    from: String,
+    to: String,
+    out_path: String,
+) -> Result<PatchExport, String> {
+    GitOperation::new(&project_path, "git format-patch")
+        .shared()
+        .run(move || export_patch(&project_path, &from, &to, &out_path))
+        .await
+}
+
+/// Apply `format-patch` or `git diff` output to a clean working tree and its index
+#[tauri::command]
+pub async fn git_apply_patch(project_path: String, patch: String) -> Result<(), String> {
+    GitOperation::new(&project_path, "git apply")
+        .run(move || apply_patch(&project_path, &patch))
+        .await
+}
@@ -0,0 +1,134 @@
+//! Recovery of lost commits
+//!
+//! A hard reset, or a branch deleted with `force`, leaves commits that no ref
+//! reaches any more. Until git's garbage collection prunes them they are still
+//! in the object store, and the reflogs still name them. This lists the ones
+//! the workbench created (recognized through
+//! [`commit_metadata`](crate::commands::commit_metadata)) and puts one back on
+//! a recovery branch, from where it can be inspected, merged or cherry-picked.
+
+use crate::commands::git_backend::{backend_for, CommitInfo};
+use crate::commands::git_executor::GitOperation;
+use crate::commands::simple_git::validate_branch_name;
+
+/// Commits listed at most; the newest are the likely ones to recover
+const MAX_RECOVERABLE: usize = 100;
+
+/// Lost commits created by an engine or the workbench, newest first
+///
+/// `include_user_commits` also lists commits without an engine tag.
+#[tauri::command]
+pub async fn git_recover_commits(
+    project_path: String,
+    include_user_commits: Option<bool>,
+) -> Result<Vec<CommitInfo>, String> {
+    let include_user_commits = include_user_commits.unwrap_or(false);
+    GitOperation::new(&project_path, "git fsck")
+        .shared()
+        .run(move || {
+            let backend = backend_for(&project_path);
+            let mut commits: Vec<CommitInfo> = backend
+                .orphaned_commits(&project_path)?
+                .into_iter()
+                .filter(|commit| include_user_commits || commit.engine.is_some())
+                .take(MAX_RECOVERABLE)
+                .collect();
+            backend.fill_stats(&project_path, &mut commits)?;
+            Ok(commits)
+        })
+        .await
+}
+
+/// Create a branch at a lost commit, `anycode/recovery/<commit>` unless named;
+/// returns the branch name
+#[tauri::command]
+pub async fn git_restore_orphaned_commit(
+    project_path: String,
+    commit: String,
+    branch: Option<String>,
+) -> Result<String, String> {
+    if commit.is_empty() || commit.starts_with('-') {
+        return Err(format!("Invalid commit: {}", commit));
+    }
+    let branch = branch
+        .filter(|name| !name.trim().is_empty())
+        .unwrap_or_else(|| format!("anycode/recovery/{}", &commit[..8.min(commit.len())]));
+    validate_branch_name(&branch)?;
+
+    GitOperation::new(&project_path, "git branch")
+        .run(move || {
+            backend_for(&project_path).create_branch(&project_path, &branch, Some(&commit))?;
+            log::info!(
+                "[Recovery] Restored {} onto branch {} in {}",
+                commit,
+                branch,
+                project_path
+            );
+            Ok(branch)
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::commit_metadata;
+
+    #[tokio::test]
+    async fn test_recover_and_restore_lost_commit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = backend_for(&path);
+        backend.init(&path).unwrap();
+        let write = |contents: &str| std::fs::write(dir.path().join("a.txt"), contents).unwrap();
+
+        write("one\n");
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+        let base = backend.current_commit(&path).unwrap();
+
+        // Older stash entries are only in the stash's reflog, yet not lost
+        for contents in ["wip 1\n", "wip 2\n"] {
+            write(contents);
+            backend.stash_save(&path, "wip").unwrap();
+        }
+        let stashes: Vec<String> = backend
+            .stash_list(&path)
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.commit)
+            .collect();
+        assert_eq!(stashes.len(), 2);
+
+        write("two\n");
+        backend.stage_all(&path, None).unwrap();
+        let message = commit_metadata::tag_message("Engine change", "codex", None);
+        backend.commit(&path, &message).unwrap();
+        let lost = backend.current_commit(&path).unwrap();
+        backend.reset_hard(&path, &base).unwrap();
+
+        let recoverable = git_recover_commits(path.clone(), None).await.unwrap();
+        assert_eq!(recoverable.len(), 1);
+        assert_eq!(recoverable[0].hash, lost);
+        assert_eq!(recoverable[0].engine.as_deref(), Some("codex"));
+        let everything = git_recover_commits(path.clone(), Some(true)).await.unwrap();
+        assert!(everything.iter().any(|commit| commit.hash == lost));
+        assert!(everything
+            .iter()
+            .all(|commit| !stashes.contains(&commit.hash)));
+
+        let branch = git_restore_orphaned_commit(path.clone(), lost.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(branch, format!("anycode/recovery/{}", &lost[..8]));
+        assert!(backend
+            .list_branches(&path)
+            .unwrap()
+            .iter()
+            .any(|info| info.name == branch && info.commit == lost));
+        assert!(git_recover_commits(path.clone(), None)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
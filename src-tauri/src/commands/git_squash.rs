@@ -0,0 +1,193 @@
+//! Squashing a session's commits
+//!
+//! Every prompt of a session leaves a commit behind. Before the work is
+//! shared, the session's commits can be squashed into a single commit carrying
+//! the same engine and session trailers. This rewrites history, so it is only
+//! done while the session's commits are the latest on the branch and nothing
+//! else is mixed in between; the checkpoint timeline is updated to match.
+//!
+//! Rewinding to an individual prompt of a squashed session is no longer
+//! possible afterwards, since its commits are gone from the branch.
+
+use serde::{Deserialize, Serialize};
+
+use crate::commands::checkpoints;
+use crate::commands::commit_metadata;
+use crate::commands::git_backend::{backend_for, GitBackend, LogFilter, ResetMode};
+use crate::commands::git_executor::GitOperation;
+
+/// Most commits a session squash considers
+const MAX_SESSION_COMMITS: usize = 1000;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SquashResult {
+    /// The commit replacing the session's commits
+    pub commit: String,
+    /// The commit the squashed commit sits on
+    pub base: String,
+    pub squashed_commits: usize,
+}
+
+fn squash_session(
+    backend: &dyn GitBackend,
+    project_path: &str,
+    session_id: &str,
+    message: &str,
+) -> Result<SquashResult, String> {
+    if message.trim().is_empty() {
+        return Err("Commit message is empty".to_string());
+    }
+    if backend.has_uncommitted_changes(project_path)? {
+        return Err("Commit or stash uncommitted changes before squashing".to_string());
+    }
+
+    let filter = LogFilter {
+        session_id: Some(session_id.to_string()),
+        ..LogFilter::default()
+    };
+    let commits = backend.log_page(project_path, 0, MAX_SESSION_COMMITS, &filter)?;
+    if commits.len() < 2 {
+        return Err(format!(
+            "Nothing to squash: session {} has {} commit(s) on this branch",
+            session_id,
+            commits.len()
+        ));
+    }
+
+    // Everything between the oldest session commit's parent and HEAD must belong to the session
+    let oldest = &commits[commits.len() - 1];
+    let base = format!("{}~1", oldest.hash);
+    let in_range = backend.commit_count_between(project_path, &base, "HEAD")?;
+    if in_range != commits.len() {
+        return Err(format!(
+            "Session commits are mixed with {} other commit(s); only the latest commits can be squashed",
+            in_range.saturating_sub(commits.len())
+        ));
+    }
+
+    let head = backend.current_commit(project_path)?;
+    let engine = commits[0].engine.as_deref().unwrap_or("workbench");
+    let message = commit_metadata::tag_message(message.trim(), engine, Some(session_id));
+
+    backend.reset(project_path, &base, ResetMode::Soft)?;
+    let base = backend.current_commit(project_path)?;
+    if let Err(e) = backend.commit(project_path, &message) {
+        // Put the original commits back
+        if let Err(restore_error) = backend.reset(project_path, &head, ResetMode::Soft) {
+            log::error!("[Squash] Failed to restore {}: {}", head, restore_error);
+        }
+        return Err(e);
+    }
+    let commit = backend.current_commit(project_path)?;
+
+    let replaced: Vec<String> = commits.iter().map(|c| c.hash.clone()).collect();
+    checkpoints::collapse_checkpoints(project_path, &replaced, &base, &commit);
+    log::info!(
+        "[Squash] Squashed {} commits of session {} into {}",
+        commits.len(),
+        session_id,
+        commit
+    );
+    Ok(SquashResult {
+        commit,
+        base,
+        squashed_commits: commits.len(),
+    })
+}
+
+/// Squash a session's commits once it has more than `max_commits` on the branch
+///
+/// The squashed commit keeps the subject of the session's first commit.
+pub fn squash_over_limit(
+    project_path: &str,
+    session_id: &str,
+    max_commits: usize,
+) -> Result<Option<SquashResult>, String> {
+    let backend = backend_for(project_path);
+    let filter = LogFilter {
+        session_id: Some(session_id.to_string()),
+        ..LogFilter::default()
+    };
+    let commits = backend.log_page(project_path, 0, MAX_SESSION_COMMITS, &filter)?;
+    if commits.len() <= max_commits.max(1) {
+        return Ok(None);
+    }
+    let subject = commits[commits.len() - 1].subject.clone();
+    squash_session(backend, project_path, session_id, &subject).map(Some)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Squash all commits of a session into one commit with `message`
+#[tauri::command]
+pub async fn git_squash_session_commits(
+    project_path: String,
+    session_id: String,
+    message: String,
+) -> Result<SquashResult, String> {
+    GitOperation::new(&project_path, "squash session")
+        .run(move || {
+            squash_session(
+                backend_for(&project_path),
+                &project_path,
+                &session_id,
+                &message,
+            )
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::Git2Backend;
+
+    #[test]
+    fn test_squash_session() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        let commit = |n: usize, message: &str| {
+            std::fs::write(dir.path().join("a.txt"), "x\n".repeat(n)).unwrap();
+            backend.stage_all(&path, None).unwrap();
+            backend.commit(&path, message).unwrap();
+        };
+        let tagged = |n: usize, session: &str| {
+            commit_metadata::tag_message(
+                &format!("[Codex] p prompt #{}", n),
+                "codex",
+                Some(session),
+            )
+        };
+
+        commit(1, "initial");
+        commit(2, &tagged(0, "s-1"));
+        commit(3, "manual fix");
+        commit(4, &tagged(1, "s-1"));
+        commit(5, &tagged(2, "s-1"));
+        let err = squash_session(&backend, &path, "s-1", "Session work").unwrap_err();
+        assert!(err.contains("mixed with 1 other"));
+
+        commit(6, &tagged(0, "s-2"));
+        commit(7, &tagged(1, "s-2"));
+        let head = backend.current_commit(&path).unwrap();
+        let result = squash_session(&backend, &path, "s-2", "Session work").unwrap();
+        assert_eq!(result.squashed_commits, 2);
+        assert!(!backend
+            .has_changes_between(&path, &head, &result.commit)
+            .unwrap());
+
+        let log = backend
+            .log_page(&path, 0, 10, &LogFilter::default())
+            .unwrap();
+        assert_eq!(log.len(), 6);
+        assert_eq!(log[0].subject, "Session work");
+        assert_eq!(log[0].engine.as_deref(), Some("codex"));
+        assert_eq!(log[0].session_id.as_deref(), Some("s-2"));
+        assert!(squash_session(&backend, &path, "s-2", "again").is_err());
+    }
+}
@@ -0,0 +1,218 @@
+//! Interactive staging
+//!
+//! AI commits stage everything (`git add -A`). To review the changes and keep
+//! only what they approve, the user can instead stage whole files, or single
+//! hunks of a file (applied to the index with `git apply --cached`), and then
+//! commit just the index. Hunks are numbered as in [`git_diff_unstaged`], the
+//! file's working tree against the index, so they stay valid while other hunks
+//! of the same file are already staged.
+
+use crate::commands::git_backend::{backend_for, GitBackend};
+use crate::commands::git_diff::{parse_unified_diff, StructuredDiff};
+use crate::commands::git_executor::GitOperation;
+use crate::commands::secret_scanner;
+use crate::commands::simple_git::normalize_restore_path;
+
+fn normalize_paths(paths: &[String]) -> Result<Vec<String>, String> {
+    if paths.is_empty() {
+        return Err("No paths given".to_string());
+    }
+    paths
+        .iter()
+        .map(|path| normalize_restore_path(path))
+        .collect()
+}
+
+/// Keep only the hunks at `selected` (0-based) of a single-file patch
+fn select_hunks(patch: &str, selected: &[usize]) -> Result<String, String> {
+    let mut header = String::new();
+    let mut hunks: Vec<String> = Vec::new();
+    for line in patch.split_inclusive('\n') {
+        if line.starts_with("@@ ") {
+            hunks.push(line.to_string());
+        } else if let Some(hunk) = hunks.last_mut() {
+            hunk.push_str(line);
+        } else {
+            header.push_str(line);
+        }
+    }
+    if hunks.is_empty() {
+        return Err("No unstaged text changes".to_string());
+    }
+    if let Some(index) = selected.iter().find(|&&index| index >= hunks.len()) {
+        return Err(format!(
+            "Hunk {} not found ({} unstaged)",
+            index,
+            hunks.len()
+        ));
+    }
+    let mut patch = header;
+    for (index, hunk) in hunks.iter().enumerate() {
+        if selected.contains(&index) {
+            patch.push_str(hunk);
+        }
+    }
+    Ok(patch)
+}
+
+fn stage_hunks(
+    backend: &dyn GitBackend,
+    project_path: &str,
+    path: &str,
+    hunks: &[usize],
+) -> Result<(), String> {
+    let patch = backend.diff_unstaged(project_path, path)?;
+    let patch = select_hunks(&patch, hunks).map_err(|e| format!("{}: {}", path, e))?;
+    backend.apply_to_index(project_path, &patch)?;
+    log::info!("[Staging] Staged {} hunk(s) of {}", hunks.len(), path);
+    Ok(())
+}
+
+/// Commit the index as it is; returns the new commit
+fn commit_staged(project_path: &str, message: &str) -> Result<String, String> {
+    let backend = backend_for(project_path);
+    let has_staged = backend
+        .status(project_path, None)?
+        .iter()
+        .any(|entry| !matches!(entry.staged, ' ' | '?'));
+    if !has_staged {
+        return Err("Nothing staged to commit".to_string());
+    }
+    secret_scanner::check_staged(project_path)?;
+    backend.commit(project_path, message)?;
+    let commit = backend.current_commit(project_path)?;
+    log::info!("[Staging] Committed staged changes as {}", commit);
+    Ok(commit)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Stage `paths` (files or directories), deletions included
+#[tauri::command]
+pub async fn git_stage_paths(project_path: String, paths: Vec<String>) -> Result<(), String> {
+    let paths = normalize_paths(&paths)?;
+    GitOperation::new(&project_path, "git add")
+        .run(move || backend_for(&project_path).stage_paths(&project_path, &paths))
+        .await
+}
+
+/// Take `paths` out of the index again, keeping their changes in the working tree
+#[tauri::command]
+pub async fn git_unstage_paths(project_path: String, paths: Vec<String>) -> Result<(), String> {
+    let paths = normalize_paths(&paths)?;
+    GitOperation::new(&project_path, "git reset")
+        .run(move || backend_for(&project_path).unstage_paths(&project_path, &paths))
+        .await
+}
+
+/// Unstaged changes of tracked file `path`, hunk by hunk
+#[tauri::command]
+pub async fn git_diff_unstaged(
+    project_path: String,
+    path: String,
+) -> Result<StructuredDiff, String> {
+    let path = normalize_restore_path(&path)?;
+    GitOperation::new(&project_path, "git diff")
+        .shared()
+        .run(move || {
+            backend_for(&project_path)
+                .diff_unstaged(&project_path, &path)
+                .map(|patch| parse_unified_diff(&patch))
+        })
+        .await
+}
+
+/// Stage only the hunks at `hunks` of `path`, numbered as in `git_diff_unstaged`
+#[tauri::command]
+pub async fn git_stage_hunks(
+    project_path: String,
+    path: String,
+    hunks: Vec<usize>,
+) -> Result<(), String> {
+    let path = normalize_restore_path(&path)?;
+    if hunks.is_empty() {
+        return Err("No hunks given".to_string());
+    }
+    GitOperation::new(&project_path, "git apply --cached")
+        .run(move || stage_hunks(backend_for(&project_path), &project_path, &path, &hunks))
+        .await
+}
+
+/// Commit only what is staged, leaving the rest of the working tree uncommitted
+#[tauri::command]
+pub async fn git_commit_staged(project_path: String, message: String) -> Result<String, String> {
+    if message.trim().is_empty() {
+        return Err("Commit message is empty".to_string());
+    }
+    GitOperation::new(&project_path, "git commit")
+        .run(move || commit_staged(&project_path, &message))
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::Git2Backend;
+
+    #[test]
+    fn test_stage_paths_and_hunks() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        let lines: Vec<String> = (1..=20).map(|n| format!("line {}\n", n)).collect();
+        std::fs::write(dir.path().join("a.txt"), lines.concat()).unwrap();
+        std::fs::write(dir.path().join("gone.txt"), "bye\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "first").unwrap();
+
+        // Two hunks far apart, plus a new and a deleted file
+        let mut changed = lines.clone();
+        changed[0] = "first line\n".to_string();
+        changed[19] = "last line\n".to_string();
+        std::fs::write(dir.path().join("a.txt"), changed.concat()).unwrap();
+        std::fs::remove_file(dir.path().join("gone.txt")).unwrap();
+        std::fs::write(dir.path().join("new.txt"), "fresh\n").unwrap();
+
+        backend
+            .stage_paths(&path, &["gone.txt".to_string(), "new.txt".to_string()])
+            .unwrap();
+        assert_eq!(
+            parse_unified_diff(&backend.diff_unstaged(&path, "a.txt").unwrap()).files[0]
+                .hunks
+                .len(),
+            2
+        );
+        stage_hunks(&backend, &path, "a.txt", &[1]).unwrap();
+
+        let remaining = parse_unified_diff(&backend.diff_unstaged(&path, "a.txt").unwrap());
+        assert_eq!(remaining.files[0].hunks.len(), 1);
+        assert_eq!(remaining.files[0].hunks[0].old_start, 1);
+
+        let mut status = backend.status(&path, None).unwrap();
+        status.sort_by(|a, b| a.path.cmp(&b.path));
+        let codes: Vec<(&str, char, char)> = status
+            .iter()
+            .map(|entry| (entry.path.as_str(), entry.staged, entry.unstaged))
+            .collect();
+        assert_eq!(
+            codes,
+            vec![
+                ("a.txt", 'M', 'M'),
+                ("gone.txt", 'D', ' '),
+                ("new.txt", 'A', ' ')
+            ]
+        );
+
+        assert!(stage_hunks(&backend, &path, "a.txt", &[3]).is_err());
+        backend
+            .unstage_paths(&path, &["new.txt".to_string()])
+            .unwrap();
+        let new_file = backend
+            .status(&path, Some(&["new.txt".to_string()]))
+            .unwrap();
+        assert_eq!(new_file[0].staged, '?');
+    }
+}
@@ -1,5 +1,4 @@
 use serde::{Deserialize, Serialize};
-use std::process::Command as StdCommand;
 
 /// Git 代码变更统计
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -23,8 +22,7 @@ pub async fn get_git_diff_stats(
     let to_ref = to_commit.unwrap_or_else(|| "HEAD".to_string());
 
     // 使用 git diff --numstat 获取统计
-    let mut cmd = StdCommand::new("git");
-    cmd.current_dir(&project_path);
+    let mut cmd = crate::commands::wsl_utils::git_command(&project_path);
     cmd.args(&["diff", "--numstat", &from_commit, &to_ref]);
 
     #[cfg(target_os = "windows")]
@@ -33,8 +31,7 @@ pub async fn get_git_diff_stats(
         cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
     }
 
-    let output = cmd
-        .output()
+    let output = crate::commands::profiling::timed_output("git diff --numstat", &mut cmd)
         .map_err(|e| format!("Failed to execute git diff: {}", e))?;
 
     if !output.status.success() {
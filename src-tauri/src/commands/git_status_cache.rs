@@ -0,0 +1,446 @@
+//! Cached, incremental git status
+//!
+//! `git status` walks the whole working tree, which takes seconds in multi-GB
+//! monorepos. The first `git_status_cached` call for a project computes the
+//! full status once and starts a filesystem watcher; later calls only re-check
+//! the paths the watcher reported since. Changes to HEAD, the index or refs
+//! (commits, checkouts, resets) and bursts of more than
+//! `MAX_INCREMENTAL_PATHS` paths make the next call rescan everything.
+//!
+//! Paths the repository ignores are dropped, and an index write only counts
+//! when the staged content changed: `git status` itself rewrites the index to
+//! refresh its stat cache, which would otherwise trigger a rescan after every
+//! scan. Scans run without holding the project table lock.
+//!
+//! Events:
+//! - `git-status-changed` - emitted with a [`StatusChangedEvent`] when watched files change
+
+use notify::{EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+use crate::commands::engine_safety::project_key;
+use crate::commands::git_backend::{backend_for, StatusEntry};
+
+/// Watcher events closer together than this are reported as one batch
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Above this many changed paths a full rescan is cheaper than checking each one
+const MAX_INCREMENTAL_PATHS: usize = 1000;
+
+/// Changed paths listed in one `git-status-changed` event
+const MAX_EVENT_PATHS: usize = 100;
+
+/// Files under `.git` whose change invalidates the whole status (the index is
+/// handled separately, see [`index_fingerprint`])
+const GIT_STATE_FILES: &[&str] = &["HEAD", "packed-refs", "MERGE_HEAD", "info/exclude"];
+
+static PROJECTS: Lazy<Mutex<HashMap<String, WatchedProject>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+struct WatchedProject {
+    project_path: String,
+    entries: BTreeMap<String, StatusEntry>,
+    /// Repository-relative paths changed since the last refresh
+    pending: BTreeSet<String>,
+    needs_rescan: bool,
+    /// Dropping the watcher ends its event thread
+    _watcher: RecommendedWatcher,
+}
+
+/// Status work a refresh needs, taken out of the project under the lock
+enum Refresh {
+    Full,
+    Paths(Vec<String>),
+    Nothing,
+}
+
+impl WatchedProject {
+    /// Claim the outstanding work; changes reported meanwhile queue up again
+    fn take_refresh(&mut self) -> Refresh {
+        if self.needs_rescan {
+            self.needs_rescan = false;
+            self.pending.clear();
+            Refresh::Full
+        } else if !self.pending.is_empty() {
+            Refresh::Paths(std::mem::take(&mut self.pending).into_iter().collect())
+        } else {
+            Refresh::Nothing
+        }
+    }
+
+    fn apply(&mut self, refresh: &Refresh, entries: Vec<StatusEntry>) {
+        let entries = entries.into_iter().map(|e| (e.path.clone(), e));
+        match refresh {
+            Refresh::Full => self.entries = entries.collect(),
+            Refresh::Paths(paths) => {
+                // A changed path may be a directory; drop everything below it before re-adding
+                self.entries.retain(|path, _| {
+                    !paths
+                        .iter()
+                        .any(|p| path == p || path.starts_with(&format!("{}/", p)))
+                });
+                self.entries.extend(entries);
+            }
+            Refresh::Nothing => {}
+        }
+    }
+}
+
+/// Bring a watched project's cache up to date, running git without the lock
+///
+/// Returns None when the project is not watched.
+fn refresh(key: &str) -> Result<Option<StatusSource>, String> {
+    let (project_path, work) = {
+        let mut projects = PROJECTS.lock().map_err(|e| e.to_string())?;
+        let Some(project) = projects.get_mut(key) else {
+            return Ok(None);
+        };
+        (project.project_path.clone(), project.take_refresh())
+    };
+    let (source, scanned) = match &work {
+        Refresh::Full => (
+            StatusSource::Full,
+            backend_for(&project_path).status(&project_path, None),
+        ),
+        Refresh::Paths(paths) => (
+            StatusSource::Incremental,
+            backend_for(&project_path).status(&project_path, Some(paths)),
+        ),
+        Refresh::Nothing => return Ok(Some(StatusSource::Cached)),
+    };
+
+    let mut projects = PROJECTS.lock().map_err(|e| e.to_string())?;
+    let Some(project) = projects.get_mut(key) else {
+        return Ok(None);
+    };
+    match scanned {
+        Ok(entries) => {
+            project.apply(&work, entries);
+            Ok(Some(source))
+        }
+        Err(e) => {
+            project.needs_rescan = true;
+            Err(e)
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum StatusSource {
+    /// Computed from scratch
+    Full,
+    /// Only paths changed since the last call were checked
+    Incremental,
+    /// Nothing changed since the last call
+    Cached,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedStatus {
+    pub entries: Vec<StatusEntry>,
+    pub source: StatusSource,
+}
+
+/// Payload of the `git-status-changed` event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StatusChangedEvent {
+    pub project_path: String,
+    /// Repository-relative paths (at most `MAX_EVENT_PATHS`)
+    pub paths: Vec<String>,
+    /// HEAD or the index changed; the whole status will be recomputed
+    pub rescan: bool,
+}
+
+/// What a filesystem change means for the status
+#[derive(Debug, PartialEq)]
+enum Change {
+    Path(String),
+    /// `.git/index` was written; only matters if the staged content changed
+    Index,
+    Rescan,
+    Ignore,
+}
+
+fn classify(workdir: &Path, path: &Path) -> Change {
+    let Ok(relative) = path.strip_prefix(workdir) else {
+        return Change::Ignore;
+    };
+    let relative = relative.to_string_lossy().replace('\\', "/");
+    match relative.strip_prefix(".git/") {
+        Some("index") => Change::Index,
+        Some(git_path) => {
+            if GIT_STATE_FILES.contains(&git_path) || git_path.starts_with("refs/") {
+                Change::Rescan
+            } else {
+                Change::Ignore
+            }
+        }
+        None if relative.is_empty() || relative == ".git" => Change::Ignore,
+        None => Change::Path(relative),
+    }
+}
+
+/// Hash of the staged entries, leaving out the stat data `git status` refreshes
+fn index_fingerprint(repo: &git2::Repository) -> Option<u64> {
+    let mut index = repo.index().ok()?;
+    index.read(false).ok()?;
+    let mut hasher = DefaultHasher::new();
+    for entry in index.iter() {
+        entry.path.hash(&mut hasher);
+        entry.id.as_bytes().hash(&mut hasher);
+        entry.mode.hash(&mut hasher);
+        entry.flags.hash(&mut hasher);
+    }
+    Some(hasher.finish())
+}
+
+fn repository_workdir(project_path: &str) -> Result<PathBuf, String> {
+    let repo = git2::Repository::discover(project_path)
+        .map_err(|e| format!("Failed to open repository: {}", e.message()))?;
+    repo.workdir()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Bare repositories have no status".to_string())
+}
+
+fn start_watcher(
+    app: AppHandle,
+    key: String,
+    workdir: PathBuf,
+) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)
+        .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+    watcher
+        .watch(&workdir, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", workdir.display(), e))?;
+    std::thread::spawn(move || forward_events(app, key, workdir, rx));
+    Ok(watcher)
+}
+
+/// Whether `path` matches an ignore rule and git doesn't track it anyway
+///
+/// Tracked files stay in the status whatever the ignore rules say.
+fn is_untracked_ignored(repo: &git2::Repository, path: &str) -> bool {
+    if !repo.is_path_ignored(Path::new(path)).unwrap_or(false) {
+        return false;
+    }
+    repo.index()
+        .map(|index| index.get_path(Path::new(path), 0).is_none())
+        .unwrap_or(false)
+}
+
+/// Collect watcher events into the project's pending set and announce them, batch by batch
+fn forward_events(
+    app: AppHandle,
+    key: String,
+    workdir: PathBuf,
+    rx: Receiver<notify::Result<notify::Event>>,
+) {
+    // Used for ignore rules and the index fingerprint; without it every change counts
+    let repo = git2::Repository::open(&workdir).ok();
+    let mut last_index = repo.as_ref().and_then(index_fingerprint);
+
+    // Ends when the watcher is dropped and the channel disconnects
+    while let Ok(first) = rx.recv() {
+        let mut batch = vec![first];
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            batch.push(event);
+        }
+
+        let mut paths = BTreeSet::new();
+        let mut rescan = false;
+        let mut index_written = false;
+        for event in batch.into_iter().filter_map(Result::ok) {
+            if matches!(event.kind, EventKind::Access(_)) {
+                continue;
+            }
+            for path in &event.paths {
+                match classify(&workdir, path) {
+                    // New ignore rules can change the status of any path
+                    Change::Path(path) if path.rsplit('/').next() == Some(".gitignore") => {
+                        rescan = true;
+                    }
+                    Change::Path(path) => {
+                        if !repo
+                            .as_ref()
+                            .is_some_and(|repo| is_untracked_ignored(repo, &path))
+                        {
+                            paths.insert(path);
+                        }
+                    }
+                    Change::Index => index_written = true,
+                    Change::Rescan => rescan = true,
+                    Change::Ignore => {}
+                }
+            }
+        }
+        if index_written {
+            let fingerprint = repo.as_ref().and_then(index_fingerprint);
+            if fingerprint.is_none() || fingerprint != last_index {
+                rescan = true;
+            }
+            last_index = fingerprint;
+        }
+        if paths.is_empty() && !rescan {
+            continue;
+        }
+
+        let project_path = {
+            let Ok(mut projects) = PROJECTS.lock() else {
+                return;
+            };
+            let Some(project) = projects.get_mut(&key) else {
+                return;
+            };
+            project.pending.extend(paths.iter().cloned());
+            if rescan || project.pending.len() > MAX_INCREMENTAL_PATHS {
+                project.needs_rescan = true;
+                project.pending.clear();
+            }
+            project.project_path.clone()
+        };
+        let event = StatusChangedEvent {
+            project_path,
+            paths: paths.into_iter().take(MAX_EVENT_PATHS).collect(),
+            rescan,
+        };
+        if let Err(e) = app.emit("git-status-changed", &event) {
+            log::warn!("[StatusCache] Failed to emit status change: {}", e);
+        }
+    }
+}
+
+/// Status of a project, starting to watch it on first use
+fn cached_status(app: &AppHandle, project_path: &str) -> Result<CachedStatus, String> {
+    let key = project_key(project_path);
+    let source = match refresh(&key)? {
+        Some(source) => source,
+        None => {
+            // Watch before the first scan, so no change between the two is missed
+            let workdir = repository_workdir(project_path)?;
+            let watcher = start_watcher(app.clone(), key.clone(), workdir)?;
+            PROJECTS
+                .lock()
+                .map_err(|e| e.to_string())?
+                .entry(key.clone())
+                .or_insert(WatchedProject {
+                    project_path: project_path.to_string(),
+                    entries: BTreeMap::new(),
+                    pending: BTreeSet::new(),
+                    needs_rescan: true,
+                    _watcher: watcher,
+                });
+            log::info!("[StatusCache] Watching {}", project_path);
+            refresh(&key)?.unwrap_or(StatusSource::Full)
+        }
+    };
+    let entries = PROJECTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .get(&key)
+        .map(|project| project.entries.values().cloned().collect())
+        .unwrap_or_default();
+    Ok(CachedStatus { entries, source })
+}
+
+/// Whether the project has uncommitted changes
+///
+/// A watched project reporting changes answers from the cache. A clean cache is
+/// confirmed with a real status, since the watcher may not have delivered the
+/// latest writes yet.
+pub fn has_uncommitted_changes(project_path: &str) -> Result<bool, String> {
+    let key = project_key(project_path);
+    let cached_dirty = matches!(refresh(&key), Ok(Some(_)))
+        && PROJECTS
+            .lock()
+            .ok()
+            .and_then(|projects| Some(!projects.get(&key)?.entries.is_empty()))
+            .unwrap_or(false);
+    if cached_dirty {
+        return Ok(true);
+    }
+    backend_for(project_path).has_uncommitted_changes(project_path)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Uncommitted changes of a project, from the watcher-maintained cache
+#[tauri::command]
+pub async fn git_status_cached(
+    app: AppHandle,
+    project_path: String,
+) -> Result<CachedStatus, String> {
+    tokio::task::spawn_blocking(move || cached_status(&app, &project_path))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// Stop watching a project and drop its cached status
+#[tauri::command]
+pub async fn git_status_unwatch(project_path: String) -> Result<(), String> {
+    let removed = PROJECTS
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(&project_key(&project_path));
+    if removed.is_some() {
+        log::info!("[StatusCache] Stopped watching {}", project_path);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_changes() {
+        let workdir = Path::new("/repo");
+        assert_eq!(
+            classify(workdir, Path::new("/repo/src/main.rs")),
+            Change::Path("src/main.rs".to_string())
+        );
+        assert_eq!(
+            classify(workdir, Path::new("/repo/.git/index")),
+            Change::Index
+        );
+        assert_eq!(
+            classify(workdir, Path::new("/repo/.git/refs/heads/main")),
+            Change::Rescan
+        );
+        assert_eq!(
+            classify(workdir, Path::new("/repo/.git/objects/ab/cdef")),
+            Change::Ignore
+        );
+        assert_eq!(classify(workdir, Path::new("/elsewhere/a")), Change::Ignore);
+    }
+
+    #[test]
+    fn test_tracked_ignored_paths_are_kept() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join(".gitignore"), "*.log\n").unwrap();
+        std::fs::write(dir.path().join("tracked.log"), "a").unwrap();
+        std::fs::write(dir.path().join("untracked.log"), "b").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("tracked.log")).unwrap();
+        index.write().unwrap();
+
+        assert!(!is_untracked_ignored(&repo, "tracked.log"));
+        assert!(is_untracked_ignored(&repo, "untracked.log"));
+        assert!(!is_untracked_ignored(&repo, "main.rs"));
+    }
+}
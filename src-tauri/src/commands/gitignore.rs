@@ -0,0 +1,268 @@
+//! .gitignore management
+//!
+//! Checkpoints stage everything (`git add -A`), so in a fresh repository
+//! dependency folders, build output and engine scratch files would end up in
+//! every AI commit. When the workbench initializes a repository without a
+//! `.gitignore`, it writes a template covering the common cases.
+//!
+//! Patterns can be added to two places:
+//! - `.gitignore`, shared with everyone using the repository
+//! - the repository's `info/exclude`, which only affects this checkout and
+//!   keeps paths out of AI commits without touching any tracked file
+//!
+//! Ignoring only applies to untracked files; a file that is already committed
+//! keeps being picked up until it is removed from the index.
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+const TEMPLATE_HEADER: &str = "# Generated by Claude Workbench";
+
+/// Written to fresh repositories without a .gitignore
+const GITIGNORE_TEMPLATE: &[&str] = &[
+    "# Dependencies",
+    "node_modules/",
+    ".pnpm-store/",
+    "vendor/bundle/",
+    ".venv/",
+    "venv/",
+    "",
+    "# Build output and caches",
+    "target/",
+    "dist/",
+    "build/",
+    "out/",
+    ".next/",
+    ".nuxt/",
+    ".turbo/",
+    ".cache/",
+    "coverage/",
+    "__pycache__/",
+    "*.pyc",
+    "",
+    "# Logs and local environment",
+    "*.log",
+    ".env",
+    ".env.local",
+    "",
+    "# Editor and OS files",
+    ".idea/",
+    ".DS_Store",
+    "Thumbs.db",
+    "*.swp",
+    "",
+    "# AI engine scratch files",
+    ".anycode/",
+    ".claude/settings.local.json",
+    "*.orig",
+    "*.rej",
+];
+
+/// Where ignore patterns are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum IgnoreTarget {
+    /// The project's `.gitignore`
+    #[default]
+    Gitignore,
+    /// The repository's `info/exclude`, local to this checkout
+    LocalExclude,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GitignoreInfo {
+    pub gitignore_exists: bool,
+    /// Patterns in `.gitignore`, without comments and blank lines
+    pub gitignore: Vec<String>,
+    /// Patterns in `info/exclude`
+    pub local_exclude: Vec<String>,
+}
+
+fn gitignore_path(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".gitignore")
+}
+
+/// `info/exclude` of the repository containing `project_path` (shared by its worktrees)
+fn exclude_path(project_path: &str) -> Result<PathBuf, String> {
+    let repo = git2::Repository::discover(project_path)
+        .map_err(|e| format!("Failed to open repository: {}", e.message()))?;
+    Ok(repo.commondir().join("info").join("exclude"))
+}
+
+fn target_path(project_path: &str, target: IgnoreTarget) -> Result<PathBuf, String> {
+    match target {
+        IgnoreTarget::Gitignore => Ok(gitignore_path(project_path)),
+        IgnoreTarget::LocalExclude => exclude_path(project_path),
+    }
+}
+
+fn read_patterns(path: &Path) -> Vec<String> {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn validate_patterns(patterns: &[String]) -> Result<Vec<String>, String> {
+    let patterns: Vec<String> = patterns
+        .iter()
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty())
+        .collect();
+    if let Some(invalid) = patterns
+        .iter()
+        .find(|p| p.starts_with('#') || p.contains(['\n', '\r']))
+    {
+        return Err(format!("Invalid ignore pattern: {}", invalid));
+    }
+    if patterns.is_empty() {
+        return Err("No patterns given".to_string());
+    }
+    Ok(patterns)
+}
+
+/// Append the patterns a file does not have yet; returns those added
+fn append_patterns(path: &Path, patterns: &[String]) -> Result<Vec<String>, String> {
+    let existing = std::fs::read_to_string(path).unwrap_or_default();
+    let present = read_patterns(path);
+    let mut added: Vec<String> = Vec::new();
+    for pattern in patterns {
+        if !present.contains(pattern) && !added.contains(pattern) {
+            added.push(pattern.clone());
+        }
+    }
+    if added.is_empty() {
+        return Ok(added);
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    writeln!(file, "{}{}", separator, added.join("\n"))
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(added)
+}
+
+/// Add patterns to the repository's `info/exclude`; returns those added
+pub fn exclude_locally(project_path: &str, patterns: &[String]) -> Result<Vec<String>, String> {
+    append_patterns(&exclude_path(project_path)?, patterns)
+}
+
+/// Write the template when the project has no .gitignore; returns whether it was written
+pub fn ensure_gitignore(project_path: &str) -> Result<bool, String> {
+    let path = gitignore_path(project_path);
+    if path.exists() {
+        return Ok(false);
+    }
+    let content = format!("{}\n{}\n", TEMPLATE_HEADER, GITIGNORE_TEMPLATE.join("\n"));
+    std::fs::write(&path, content)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    log::info!("Created .gitignore template in {}", project_path);
+    Ok(true)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn gitignore_list(project_path: String) -> Result<GitignoreInfo, String> {
+    let gitignore = gitignore_path(&project_path);
+    Ok(GitignoreInfo {
+        gitignore_exists: gitignore.exists(),
+        gitignore: read_patterns(&gitignore),
+        local_exclude: exclude_path(&project_path)
+            .map(|path| read_patterns(&path))
+            .unwrap_or_default(),
+    })
+}
+
+/// Add ignore patterns (default: to .gitignore); returns the ones that were not there yet
+#[tauri::command]
+pub async fn gitignore_add_patterns(
+    project_path: String,
+    patterns: Vec<String>,
+    target: Option<IgnoreTarget>,
+) -> Result<Vec<String>, String> {
+    let patterns = validate_patterns(&patterns)?;
+    let path = target_path(&project_path, target.unwrap_or_default())?;
+    let added = append_patterns(&path, &patterns)?;
+    log::info!(
+        "Added {} ignore pattern(s) to {}",
+        added.len(),
+        path.display()
+    );
+    Ok(added)
+}
+
+/// Remove ignore patterns, keeping comments and every other line as they are
+#[tauri::command]
+pub async fn gitignore_remove_patterns(
+    project_path: String,
+    patterns: Vec<String>,
+    target: Option<IgnoreTarget>,
+) -> Result<(), String> {
+    let patterns = validate_patterns(&patterns)?;
+    let path = target_path(&project_path, target.unwrap_or_default())?;
+    let Ok(existing) = std::fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let kept: Vec<&str> = existing
+        .lines()
+        .filter(|line| !patterns.iter().any(|p| p == line.trim()))
+        .collect();
+    let mut content = kept.join("\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    std::fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_and_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        git2::Repository::init(dir.path()).unwrap();
+
+        assert!(ensure_gitignore(&path).unwrap());
+        assert!(!ensure_gitignore(&path).unwrap());
+        let gitignore = read_patterns(&gitignore_path(&path));
+        assert!(gitignore.contains(&"node_modules/".to_string()));
+
+        let added = append_patterns(
+            &gitignore_path(&path),
+            &["node_modules/".to_string(), "secrets/".to_string()],
+        )
+        .unwrap();
+        assert_eq!(added, vec!["secrets/".to_string()]);
+
+        let added = exclude_locally(&path, &["scratch/".to_string()]).unwrap();
+        assert_eq!(added, vec!["scratch/".to_string()]);
+        assert!(exclude_locally(&path, &["scratch/".to_string()])
+            .unwrap()
+            .is_empty());
+        assert!(read_patterns(&exclude_path(&path).unwrap()).contains(&"scratch/".to_string()));
+
+        assert!(validate_patterns(&["a\nb".to_string()]).is_err());
+        assert!(validate_patterns(&["  ".to_string()]).is_err());
+    }
+}
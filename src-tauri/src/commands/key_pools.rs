@@ -0,0 +1,590 @@
+//! API key pools with rotation and failover
+//!
+//! For custom / OpenAI-compatible providers several API keys can be registered
+//! per provider preset. The keys themselves live in the system keyring; the
+//! pool metadata (labels, usage counters, exhaustion state) is stored in
+//! `~/.anycode/key-pools.json`. When an engine reports an auth or quota error
+//! the active key is marked exhausted and the next usable key is written into
+//! the engine's configuration, so the next run picks it up.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// How long a key hitting its quota is skipped
+const QUOTA_COOLDOWN_MINUTES: i64 = 60;
+
+/// Error bursts (several stderr lines for one failure) only rotate once
+const ROTATION_DEBOUNCE_SECS: i64 = 30;
+
+const SUPPORTED_ENGINES: &[&str] = &["claude", "codex", "gemini"];
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Why a key stopped working
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyFailure {
+    /// Rejected credentials; the key stays disabled until reset
+    Auth,
+    /// Rate limit or quota exhausted; the key is retried after a cooldown
+    Quota,
+}
+
+/// Availability of a pooled key
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum KeyState {
+    Available,
+    CoolingDown,
+    Invalid,
+}
+
+/// Per-key usage accounting
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyUsage {
+    pub requests: u64,
+    pub failures: u64,
+    pub last_used: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+}
+
+/// A key registered in a pool (the secret itself is in the keyring)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PooledKey {
+    pub id: String,
+    pub label: String,
+    /// Last characters of the key, for display
+    pub hint: String,
+    pub state: KeyState,
+    pub exhausted_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub usage: KeyUsage,
+    pub added_at: DateTime<Utc>,
+}
+
+impl PooledKey {
+    fn is_usable(&self, now: DateTime<Utc>) -> bool {
+        match self.state {
+            KeyState::Available => true,
+            KeyState::CoolingDown => self.exhausted_until.map_or(true, |until| until <= now),
+            KeyState::Invalid => false,
+        }
+    }
+}
+
+/// Keys of one provider preset
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPool {
+    pub engine: String,
+    pub provider_id: String,
+    pub keys: Vec<PooledKey>,
+    pub active_key: Option<String>,
+    pub last_rotation: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct KeyPoolStore {
+    #[serde(default)]
+    pools: Vec<KeyPool>,
+    /// Engine → provider preset currently switched to
+    #[serde(default)]
+    active_providers: HashMap<String, String>,
+}
+
+/// Pool state returned by the status command
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyPoolStatus {
+    #[serde(flatten)]
+    pub pool: KeyPool,
+    /// Whether this pool belongs to the engine's active provider
+    pub in_use: bool,
+    pub exhausted: bool,
+}
+
+/// Event emitted as `key-pool-rotated`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeyRotationEvent {
+    pub engine: String,
+    pub provider_id: String,
+    pub failure: KeyFailure,
+    pub from_key: Option<String>,
+    /// `None` when every key in the pool is exhausted
+    pub to_key: Option<String>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("key-pools.json"))
+}
+
+fn load_store() -> KeyPoolStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(f: impl FnOnce(&mut KeyPoolStore) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+fn secret_name(key_id: &str) -> String {
+    format!("key-pool-{}", key_id)
+}
+
+fn validate_engine(engine: &str) -> Result<(), String> {
+    if SUPPORTED_ENGINES.contains(&engine) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Key pools are not supported for engine: {}",
+            engine
+        ))
+    }
+}
+
+/// Classify engine error output as an auth or quota failure
+pub fn classify_error(text: &str) -> Option<KeyFailure> {
+    let text = text.to_lowercase();
+    const QUOTA: &[&str] = &[
+        "429",
+        "insufficient_quota",
+        "quota exceeded",
+        "exceeded your current quota",
+        "rate limit",
+        "rate_limit",
+        "resource_exhausted",
+        "credit balance is too low",
+    ];
+    const AUTH: &[&str] = &[
+        "401",
+        "invalid api key",
+        "invalid_api_key",
+        "incorrect api key",
+        "invalid x-api-key",
+        "authentication_error",
+        "api key not valid",
+        "unauthorized",
+    ];
+
+    if QUOTA.iter().any(|p| text.contains(p)) {
+        Some(KeyFailure::Quota)
+    } else if AUTH.iter().any(|p| text.contains(p)) {
+        Some(KeyFailure::Auth)
+    } else {
+        None
+    }
+}
+
+/// Write a key into the engine's configuration
+fn apply_key(engine: &str, key: &str) -> Result<(), String> {
+    crate::utils::redaction::register_secret(key);
+    match engine {
+        "claude" => crate::commands::provider::set_active_api_key(key),
+        "codex" => crate::commands::codex::config::set_codex_api_key(key),
+        "gemini" => crate::commands::gemini::provider::set_gemini_api_key(key),
+        _ => Err(format!(
+            "Key pools are not supported for engine: {}",
+            engine
+        )),
+    }
+}
+
+/// Pick the next usable key after `current`, wrapping around
+fn next_usable_key(pool: &KeyPool, now: DateTime<Utc>) -> Option<String> {
+    let start = pool
+        .active_key
+        .as_ref()
+        .and_then(|id| pool.keys.iter().position(|k| &k.id == id))
+        .map_or(0, |i| i + 1);
+    (0..pool.keys.len())
+        .map(|offset| &pool.keys[(start + offset) % pool.keys.len()])
+        .find(|k| k.is_usable(now) && Some(&k.id) != pool.active_key.as_ref())
+        .map(|k| k.id.clone())
+}
+
+/// Activate a key: read it from the keyring and write it into the engine config
+fn activate(pool: &mut KeyPool, key_id: &str) -> Result<(), String> {
+    let secret = keyring_store::get_secret(&secret_name(key_id))?
+        .ok_or_else(|| "Key is missing from the keyring".to_string())?;
+    apply_key(&pool.engine, &secret)?;
+    if let Some(key) = pool.keys.iter_mut().find(|k| k.id == key_id) {
+        key.state = KeyState::Available;
+        key.exhausted_until = None;
+    }
+    pool.active_key = Some(key_id.to_string());
+    Ok(())
+}
+
+fn active_pool<'a>(store: &'a mut KeyPoolStore, engine: &str) -> Option<&'a mut KeyPool> {
+    let provider_id = store.active_providers.get(engine)?.clone();
+    store
+        .pools
+        .iter_mut()
+        .find(|p| p.engine == engine && p.provider_id == provider_id)
+}
+
+/// Remember the provider preset an engine was switched to and apply its pool's active key
+pub fn on_provider_switched(engine: &str, provider_id: &str) {
+    let result = update_store(|store| {
+        store
+            .active_providers
+            .insert(engine.to_string(), provider_id.to_string());
+        if let Some(pool) = active_pool(store, engine) {
+            if let Some(key_id) = pool.active_key.clone() {
+                activate(pool, &key_id)?;
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[Key Pools] Failed to apply key pool for {}: {}", engine, e);
+    }
+}
+
+/// Forget the engine's provider preset (back to official auth, no rotation)
+pub fn on_provider_cleared(engine: &str) {
+    let result = update_store(|store| {
+        store.active_providers.remove(engine);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[Key Pools] Failed to clear active provider: {}", e);
+    }
+}
+
+/// Count a request against the engine's active key
+pub fn record_request(engine: &str) {
+    if !store_path().map(|p| p.exists()).unwrap_or(false) {
+        return;
+    }
+    let result = update_store(|store| {
+        if let Some(pool) = active_pool(store, engine) {
+            let active = pool.active_key.clone();
+            if let Some(key) = pool
+                .keys
+                .iter_mut()
+                .find(|k| Some(&k.id) == active.as_ref())
+            {
+                key.usage.requests += 1;
+                key.usage.last_used = Some(Utc::now());
+            }
+        }
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::debug!("[Key Pools] Failed to record request: {}", e);
+    }
+}
+
+/// Mark the active key as failed and fail over to the next usable key
+fn rotate_on_failure(
+    engine: &str,
+    failure: KeyFailure,
+    message: &str,
+) -> Result<Option<KeyRotationEvent>, String> {
+    update_store(|store| {
+        let Some(pool) = active_pool(store, engine) else {
+            return Ok(None);
+        };
+        let now = Utc::now();
+        if pool
+            .last_rotation
+            .is_some_and(|t| now - t < Duration::seconds(ROTATION_DEBOUNCE_SECS))
+        {
+            return Ok(None);
+        }
+
+        let from_key = pool.active_key.clone();
+        if let Some(key) = pool
+            .keys
+            .iter_mut()
+            .find(|k| Some(&k.id) == from_key.as_ref())
+        {
+            key.usage.failures += 1;
+            key.usage.last_error = Some(message.chars().take(300).collect());
+            match failure {
+                KeyFailure::Auth => key.state = KeyState::Invalid,
+                KeyFailure::Quota => {
+                    key.state = KeyState::CoolingDown;
+                    key.exhausted_until = Some(now + Duration::minutes(QUOTA_COOLDOWN_MINUTES));
+                }
+            }
+        }
+        pool.last_rotation = Some(now);
+
+        let mut to_key = None;
+        while let Some(candidate) = next_usable_key(pool, now) {
+            match activate(pool, &candidate) {
+                Ok(()) => {
+                    to_key = Some(candidate);
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("[Key Pools] Skipping key {}: {}", candidate, e);
+                    if let Some(key) = pool.keys.iter_mut().find(|k| k.id == candidate) {
+                        key.state = KeyState::Invalid;
+                        key.usage.last_error = Some(e);
+                    }
+                }
+            }
+        }
+
+        Ok(Some(KeyRotationEvent {
+            engine: engine.to_string(),
+            provider_id: pool.provider_id.clone(),
+            failure,
+            from_key,
+            to_key,
+        }))
+    })
+}
+
+/// Inspect engine error output and rotate keys on auth/quota failures
+pub fn observe_engine_error(app: &AppHandle, engine: &str, text: &str) {
+    let Some(failure) = classify_error(text) else {
+        return;
+    };
+    if !load_store().active_providers.contains_key(engine) {
+        return;
+    }
+    match rotate_on_failure(engine, failure, text) {
+        Ok(Some(event)) => {
+            match &event.to_key {
+                Some(key) => log::warn!(
+                    "[Key Pools] {:?} failure on {}, rotated to key {}",
+                    failure,
+                    engine,
+                    key
+                ),
+                None => log::warn!(
+                    "[Key Pools] {:?} failure on {}, every key in the pool is exhausted",
+                    failure,
+                    engine
+                ),
+            }
+            let _ = app.emit("key-pool-rotated", &event);
+        }
+        Ok(None) => {}
+        Err(e) => log::warn!("[Key Pools] Rotation failed: {}", e),
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Show every pool (optionally of one engine) with key usage and exhaustion state
+#[tauri::command]
+pub async fn get_key_pool_status(engine: Option<String>) -> Result<Vec<KeyPoolStatus>, String> {
+    let store = load_store();
+    let now = Utc::now();
+    Ok(store
+        .pools
+        .iter()
+        .filter(|p| engine.as_deref().map_or(true, |e| p.engine == e))
+        .map(|pool| {
+            let mut pool = pool.clone();
+            for key in &mut pool.keys {
+                if key.state == KeyState::CoolingDown && key.is_usable(now) {
+                    key.state = KeyState::Available;
+                    key.exhausted_until = None;
+                }
+            }
+            KeyPoolStatus {
+                in_use: store.active_providers.get(&pool.engine) == Some(&pool.provider_id),
+                exhausted: !pool.keys.iter().any(|k| k.is_usable(now)),
+                pool,
+            }
+        })
+        .collect())
+}
+
+/// Add a key to a provider's pool; the first key of a pool becomes active
+#[tauri::command]
+pub async fn add_pool_key(
+    engine: String,
+    provider_id: String,
+    label: String,
+    key: String,
+) -> Result<PooledKey, String> {
+    validate_engine(&engine)?;
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("API key is empty".to_string());
+    }
+
+    let pooled = PooledKey {
+        id: uuid::Uuid::new_v4().to_string(),
+        label: label.trim().to_string(),
+        hint: key
+            .chars()
+            .rev()
+            .take(4)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect(),
+        state: KeyState::Available,
+        exhausted_until: None,
+        usage: KeyUsage::default(),
+        added_at: Utc::now(),
+    };
+    keyring_store::set_secret(&secret_name(&pooled.id), &key)?;
+
+    let result = update_store(|store| {
+        let index = match store
+            .pools
+            .iter()
+            .position(|p| p.engine == engine && p.provider_id == provider_id)
+        {
+            Some(index) => index,
+            None => {
+                store.pools.push(KeyPool {
+                    engine: engine.clone(),
+                    provider_id: provider_id.clone(),
+                    keys: Vec::new(),
+                    active_key: None,
+                    last_rotation: None,
+                });
+                store.pools.len() - 1
+            }
+        };
+        let pool = &mut store.pools[index];
+        pool.keys.push(pooled.clone());
+        if pool.active_key.is_none() {
+            pool.active_key = Some(pooled.id.clone());
+        }
+        Ok(pooled.clone())
+    });
+    if result.is_err() {
+        let _ = keyring_store::delete_secret(&secret_name(&pooled.id));
+    }
+    result
+}
+
+/// Remove a key from its pool and the keyring
+#[tauri::command]
+pub async fn remove_pool_key(key_id: String) -> Result<(), String> {
+    update_store(|store| {
+        for pool in &mut store.pools {
+            pool.keys.retain(|k| k.id != key_id);
+            if pool.active_key.as_deref() == Some(key_id.as_str()) {
+                pool.active_key = pool.keys.first().map(|k| k.id.clone());
+            }
+        }
+        store.pools.retain(|p| !p.keys.is_empty());
+        Ok(())
+    })?;
+    keyring_store::delete_secret(&secret_name(&key_id))
+}
+
+/// Clear the exhausted/invalid state of a key
+#[tauri::command]
+pub async fn reset_pool_key(key_id: String) -> Result<(), String> {
+    update_store(|store| {
+        let key = store
+            .pools
+            .iter_mut()
+            .flat_map(|p| p.keys.iter_mut())
+            .find(|k| k.id == key_id)
+            .ok_or_else(|| format!("Key not found: {}", key_id))?;
+        key.state = KeyState::Available;
+        key.exhausted_until = None;
+        key.usage.last_error = None;
+        Ok(())
+    })
+}
+
+/// Make a specific key (or, without `key_id`, the next usable key) active for its engine
+#[tauri::command]
+pub async fn rotate_pool_key(
+    engine: String,
+    provider_id: String,
+    key_id: Option<String>,
+) -> Result<String, String> {
+    update_store(|store| {
+        let in_use = store.active_providers.get(&engine) == Some(&provider_id);
+        let pool = store
+            .pools
+            .iter_mut()
+            .find(|p| p.engine == engine && p.provider_id == provider_id)
+            .ok_or_else(|| format!("No key pool for {} provider {}", engine, provider_id))?;
+        let key_id = match key_id {
+            Some(id) => id,
+            None => next_usable_key(pool, Utc::now())
+                .ok_or_else(|| "Every key in the pool is exhausted".to_string())?,
+        };
+        if !pool.keys.iter().any(|k| k.id == key_id) {
+            return Err(format!("Key not found: {}", key_id));
+        }
+        // Only touch the engine config when this provider is the one in use
+        if in_use {
+            activate(pool, &key_id)?;
+        } else {
+            pool.active_key = Some(key_id.clone());
+        }
+        Ok(key_id)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(id: &str, state: KeyState) -> PooledKey {
+        PooledKey {
+            id: id.to_string(),
+            label: id.to_string(),
+            hint: String::new(),
+            state,
+            exhausted_until: None,
+            usage: KeyUsage::default(),
+            added_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_classify_error() {
+        assert_eq!(
+            classify_error("Error: 429 You exceeded your current quota"),
+            Some(KeyFailure::Quota)
+        );
+        assert_eq!(
+            classify_error("{\"type\":\"authentication_error\",\"message\":\"invalid x-api-key\"}"),
+            Some(KeyFailure::Auth)
+        );
+        assert_eq!(classify_error("Compiling project..."), None);
+    }
+
+    #[test]
+    fn test_next_usable_key_skips_exhausted() {
+        let pool = KeyPool {
+            engine: "codex".to_string(),
+            provider_id: "relay".to_string(),
+            keys: vec![
+                key("a", KeyState::Available),
+                key("b", KeyState::Invalid),
+                key("c", KeyState::Available),
+            ],
+            active_key: Some("a".to_string()),
+            last_rotation: None,
+        };
+        assert_eq!(next_usable_key(&pool, Utc::now()).as_deref(), Some("c"));
+    }
+}
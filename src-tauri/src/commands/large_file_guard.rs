@@ -0,0 +1,397 @@
+//! Large-file guard
+//!
+//! Configurable thresholds, stored in `~/.anycode/large-file-guard.json`:
+//! files above `max_attachment_bytes` are refused when attached to a prompt
+//! (before they are read). When the app auto-commits, staged files above
+//! `commit_warning_bytes`, and newly added binary files above
+//! `binary_threshold_bytes` (build outputs, model weights), are taken back out
+//! of the commit, or only reported in `warn` mode. With `route_to_lfs` they are
+//! tracked with Git LFS instead, where the repository has LFS set up.
+
+use std::io::Read;
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::commands::git_backend::backend_for;
+use crate::commands::wsl_utils::git_command;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+const DEFAULT_MAX_ATTACHMENT_BYTES: u64 = 20 * 1024 * 1024;
+const DEFAULT_COMMIT_WARNING_BYTES: u64 = 50 * 1024 * 1024;
+const DEFAULT_BINARY_THRESHOLD_BYTES: u64 = 1024 * 1024;
+
+/// Bytes inspected for a NUL byte, as git does to tell binary files apart
+const BINARY_PROBE_BYTES: usize = 8000;
+
+/// What happens to flagged files when the app auto-commits
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CommitAction {
+    /// Commit them and log a warning
+    Warn,
+    /// Leave them out of the commit
+    #[default]
+    Exclude,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeFileGuardConfig {
+    /// Largest file that can be attached to a prompt
+    #[serde(default = "default_max_attachment_bytes")]
+    pub max_attachment_bytes: u64,
+    /// Staged files above this size are flagged on commit
+    #[serde(default = "default_commit_warning_bytes")]
+    pub commit_warning_bytes: u64,
+    /// Newly added binary files above this size are flagged on commit
+    #[serde(default = "default_binary_threshold_bytes")]
+    pub binary_threshold_bytes: u64,
+    #[serde(default)]
+    pub commit_action: CommitAction,
+    /// Track flagged files with Git LFS instead, where the repository has LFS set up
+    #[serde(default)]
+    pub route_to_lfs: bool,
+}
+
+fn default_max_attachment_bytes() -> u64 {
+    DEFAULT_MAX_ATTACHMENT_BYTES
+}
+
+fn default_commit_warning_bytes() -> u64 {
+    DEFAULT_COMMIT_WARNING_BYTES
+}
+
+fn default_binary_threshold_bytes() -> u64 {
+    DEFAULT_BINARY_THRESHOLD_BYTES
+}
+
+impl Default for LargeFileGuardConfig {
+    fn default() -> Self {
+        Self {
+            max_attachment_bytes: DEFAULT_MAX_ATTACHMENT_BYTES,
+            commit_warning_bytes: DEFAULT_COMMIT_WARNING_BYTES,
+            binary_threshold_bytes: DEFAULT_BINARY_THRESHOLD_BYTES,
+            commit_action: CommitAction::default(),
+            route_to_lfs: false,
+        }
+    }
+}
+
+/// A staged file over the commit threshold
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LargeStagedFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GuardReason {
+    TooLarge,
+    Binary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum GuardOutcome {
+    /// Committed as is
+    Warned,
+    /// Left out of the commit; still in the working tree
+    Excluded,
+    /// Committed as a Git LFS pointer
+    Lfs,
+}
+
+/// A staged file the guard acted on during an auto-commit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuardedFile {
+    pub path: String,
+    pub size: u64,
+    pub reason: GuardReason,
+    pub outcome: GuardOutcome,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("large-file-guard.json"))
+}
+
+pub fn load_config() -> LargeFileGuardConfig {
+    config_path().and_then(load_json_config).unwrap_or_default()
+}
+
+/// Human-readable size, e.g. `2.1 GB`
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Error for a file over the attachment limit
+pub fn attachment_size_error(path: &str, size: u64, limit: u64) -> Option<String> {
+    (size > limit).then(|| {
+        format!(
+            "{} is {}, over the {} attachment limit",
+            path,
+            format_size(size),
+            format_size(limit)
+        )
+    })
+}
+
+/// Staged added or modified files: (path, newly added, size in the working tree)
+fn staged_files(project_path: &str) -> Result<Vec<(String, bool, u64)>, String> {
+    let mut cmd = git_command(project_path);
+    cmd.args([
+        "diff",
+        "--cached",
+        "--name-status",
+        "--diff-filter=AM",
+        "-z",
+    ]);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to list staged files: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let fields: Vec<&[u8]> = output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|field| !field.is_empty())
+        .collect();
+    Ok(fields
+        .chunks(2)
+        .filter_map(|pair| {
+            let [status, name] = pair else {
+                return None;
+            };
+            let path = String::from_utf8_lossy(name).to_string();
+            let size = std::fs::metadata(Path::new(project_path).join(&path))
+                .ok()?
+                .len();
+            Some((path, status.starts_with(b"A"), size))
+        })
+        .collect())
+}
+
+/// Staged files (added or modified) larger than `threshold`
+pub fn large_staged_files(
+    project_path: &str,
+    threshold: u64,
+) -> Result<Vec<LargeStagedFile>, String> {
+    let mut files: Vec<LargeStagedFile> = staged_files(project_path)?
+        .into_iter()
+        .filter(|(_, _, size)| *size > threshold)
+        .map(|(path, _, size)| LargeStagedFile { path, size })
+        .collect();
+    files.sort_by(|a, b| b.size.cmp(&a.size));
+    Ok(files)
+}
+
+/// Whether a file's first bytes contain a NUL byte
+fn is_binary(path: &Path) -> bool {
+    let mut probe = Vec::with_capacity(BINARY_PROBE_BYTES);
+    std::fs::File::open(path)
+        .and_then(|file| file.take(BINARY_PROBE_BYTES as u64).read_to_end(&mut probe))
+        .map(|_| probe.contains(&0))
+        .unwrap_or(false)
+}
+
+/// Why a staged file should not be committed as is, if it should not
+fn flag(
+    config: &LargeFileGuardConfig,
+    full_path: &Path,
+    added: bool,
+    size: u64,
+) -> Option<GuardReason> {
+    if size > config.commit_warning_bytes {
+        Some(GuardReason::TooLarge)
+    } else if added && size > config.binary_threshold_bytes && is_binary(full_path) {
+        Some(GuardReason::Binary)
+    } else {
+        None
+    }
+}
+
+/// Whether the repository has the Git LFS filter configured
+fn lfs_configured(project_path: &str) -> bool {
+    let mut cmd = git_command(project_path);
+    cmd.args(["config", "--get", "filter.lfs.clean"]);
+    cmd.output()
+        .map(|output| output.status.success() && !output.stdout.is_empty())
+        .unwrap_or(false)
+}
+
+/// Track `paths` with Git LFS and stage them again as pointers
+fn track_with_lfs(project_path: &str, paths: &[String]) -> Result<(), String> {
+    let run = |args: Vec<&str>, what: &str| -> Result<(), String> {
+        let mut cmd = git_command(project_path);
+        cmd.args(args);
+        let output = cmd
+            .output()
+            .map_err(|e| format!("Failed to run {}: {}", what, e))?;
+        if output.status.success() {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} failed: {}",
+                what,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ))
+        }
+    };
+    let mut track = vec!["lfs", "track", "--filename", "--"];
+    track.extend(paths.iter().map(String::as_str));
+    run(track, "git lfs track")?;
+    let mut add = vec!["add", "--", ".gitattributes"];
+    add.extend(paths.iter().map(String::as_str));
+    run(add, "git add")
+}
+
+/// Check staged files before an auto-commit and act on the flagged ones
+///
+/// Failures are logged and never stop the commit; the files acted on are returned.
+pub fn guard_staged_files(project_path: &str) -> Vec<GuardedFile> {
+    let config = load_config();
+    let staged = match staged_files(project_path) {
+        Ok(staged) => staged,
+        Err(e) => {
+            log::debug!("[LargeFileGuard] Could not check staged files: {}", e);
+            return Vec::new();
+        }
+    };
+    let flagged: Vec<(String, u64, GuardReason)> = staged
+        .into_iter()
+        .filter_map(|(path, added, size)| {
+            let reason = flag(&config, &Path::new(project_path).join(&path), added, size)?;
+            Some((path, size, reason))
+        })
+        .collect();
+    if flagged.is_empty() {
+        return Vec::new();
+    }
+
+    let paths: Vec<String> = flagged.iter().map(|(path, _, _)| path.clone()).collect();
+    let mut outcome = match config.commit_action {
+        CommitAction::Warn => GuardOutcome::Warned,
+        CommitAction::Exclude => GuardOutcome::Excluded,
+    };
+    if config.route_to_lfs && lfs_configured(project_path) {
+        match track_with_lfs(project_path, &paths) {
+            Ok(()) => outcome = GuardOutcome::Lfs,
+            Err(e) => log::warn!("[LargeFileGuard] Could not track files with Git LFS: {}", e),
+        }
+    }
+    if outcome == GuardOutcome::Excluded {
+        if let Err(e) = backend_for(project_path).unstage_paths(project_path, &paths) {
+            log::warn!(
+                "[LargeFileGuard] Failed to leave files out of the commit: {}",
+                e
+            );
+            outcome = GuardOutcome::Warned;
+        }
+    }
+
+    flagged
+        .into_iter()
+        .map(|(path, size, reason)| {
+            log::warn!(
+                "[LargeFileGuard] {} ({}, {:?}): {:?}; consider adding it to .gitignore or tracking it with Git LFS",
+                path,
+                format_size(size),
+                reason,
+                outcome
+            );
+            GuardedFile {
+                path,
+                size,
+                reason,
+                outcome,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_large_file_guard_config() -> Result<LargeFileGuardConfig, String> {
+    Ok(load_config())
+}
+
+#[tauri::command]
+pub async fn update_large_file_guard_config(config: LargeFileGuardConfig) -> Result<(), String> {
+    if config.max_attachment_bytes == 0
+        || config.commit_warning_bytes == 0
+        || config.binary_threshold_bytes == 0
+    {
+        return Err("Size thresholds must be greater than zero".to_string());
+    }
+    save_json_config(&config, config_path()?)
+}
+
+/// Staged files of a project over the commit warning threshold
+#[tauri::command]
+pub async fn check_large_staged_files(
+    project_path: String,
+) -> Result<Vec<LargeStagedFile>, String> {
+    let threshold = load_config().commit_warning_bytes;
+    tokio::task::spawn_blocking(move || large_staged_files(&project_path, threshold))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(50 * 1024 * 1024), "50.0 MB");
+        assert_eq!(format_size(2_254_857_830), "2.1 GB");
+        assert_eq!(
+            attachment_size_error("data.csv", 30 * 1024 * 1024, 20 * 1024 * 1024).as_deref(),
+            Some("data.csv is 30.0 MB, over the 20.0 MB attachment limit")
+        );
+        assert!(attachment_size_error("a.rs", 10, 20).is_none());
+    }
+
+    #[test]
+    fn test_flag_large_and_binary_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let binary = dir.path().join("model.bin");
+        let text = dir.path().join("data.csv");
+        std::fs::write(&binary, [1u8, 0, 2, 0]).unwrap();
+        std::fs::write(&text, "a,b\n").unwrap();
+        let config = LargeFileGuardConfig {
+            commit_warning_bytes: 100,
+            binary_threshold_bytes: 2,
+            ..LargeFileGuardConfig::default()
+        };
+
+        assert_eq!(flag(&config, &binary, true, 4), Some(GuardReason::Binary));
+        // Binaries already in the repository may change
+        assert_eq!(flag(&config, &binary, false, 4), None);
+        assert_eq!(flag(&config, &text, true, 4), None);
+        assert_eq!(
+            flag(&config, &text, false, 101),
+            Some(GuardReason::TooLarge)
+        );
+    }
+}
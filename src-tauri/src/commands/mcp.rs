@@ -37,7 +37,7 @@ use log::{error, info};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use tauri::AppHandle;
 
@@ -937,7 +937,7 @@ pub async fn mcp_get_unified_servers() -> Result<std::collections::HashMap<Strin
 /// 获取指定引擎的 MCP 服务器列表
 ///
 /// # 参数
-/// - `engine`: 引擎名称（"claude" | "codex" | "gemini"）
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
 ///
 /// # 返回
 /// - Ok(HashMap<String, Value>): 该引擎的 MCP 服务器映射
@@ -954,7 +954,7 @@ pub async fn mcp_get_engine_servers(
 /// 在指定引擎中添加或更新 MCP 服务器
 ///
 /// # 参数
-/// - `engine`: 引擎名称（"claude" | "codex" | "gemini"）
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
 /// - `id`: 服务器 ID
 /// - `server_spec`: 服务器规范（JSON）
 #[tauri::command]
@@ -968,12 +968,13 @@ pub async fn mcp_upsert_engine_server(
     // 验证服务器规范
     crate::mcp::validate_server_spec(&server_spec)?;
 
-    // 保存到注册表（启用状态）
-    crate::mcp::registry::upsert_server(&id, &id, &server_spec, true)?;
-
-    // 同步到引擎配置文件
+    // 保存到注册表（在该引擎中启用）
     let app_type = crate::mcp::AppType::from_str(&engine)?;
-    crate::mcp::sync_server_to_app(&id, &server_spec, &app_type)?;
+    crate::mcp::registry::upsert_server(&id, &id, &server_spec, Some((&app_type, true)))?;
+
+    // 同步到引擎配置文件（带上注册表中单独禁用的工具）
+    let engine_spec = registry_engine_spec(&id, server_spec)?;
+    crate::mcp::sync_server_to_app(&id, &engine_spec, &app_type)?;
 
     Ok(format!("成功在 {} 引擎中配置 MCP 服务器 '{}'", engine, id))
 }
@@ -981,7 +982,7 @@ pub async fn mcp_upsert_engine_server(
 /// 从指定引擎中删除 MCP 服务器（永久删除，同时从注册表中移除）
 ///
 /// # 参数
-/// - `engine`: 引擎名称（"claude" | "codex" | "gemini"）
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
 /// - `id`: 服务器 ID
 #[tauri::command]
 pub async fn mcp_delete_engine_server(engine: String, id: String) -> Result<String, String> {
@@ -1003,7 +1004,7 @@ pub async fn mcp_delete_engine_server(engine: String, id: String) -> Result<Stri
 /// 切换指定引擎中 MCP 服务器的启用状态
 ///
 /// # 参数
-/// - `engine`: 引擎名称（"claude" | "codex" | "gemini"）
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
 /// - `id`: 服务器 ID
 /// - `server_spec`: 服务器规范（JSON）
 /// - `enabled`: 启用状态
@@ -1011,6 +1012,7 @@ pub async fn mcp_delete_engine_server(engine: String, id: String) -> Result<Stri
 /// # 说明
 /// - 当 enabled=true 时，将服务器添加到引擎配置文件
 /// - 当 enabled=false 时，从引擎配置文件中移除服务器（但保留在注册表中）
+/// - 其它引擎中的启用状态不受影响
 #[tauri::command]
 pub async fn mcp_toggle_engine_server(
     engine: String,
@@ -1025,13 +1027,14 @@ pub async fn mcp_toggle_engine_server(
 
     let app_type = crate::mcp::AppType::from_str(&engine)?;
 
-    // 始终将服务器保存到注册表（确保禁用后不会丢失）
-    crate::mcp::registry::upsert_server(&id, &id, &server_spec, enabled)?;
+    // 始终将服务器保存到注册表（确保禁用后不会丢失），只更新该引擎的启用状态
+    crate::mcp::registry::upsert_server(&id, &id, &server_spec, Some((&app_type, enabled)))?;
 
     if enabled {
         // 启用：添加到配置文件
         crate::mcp::validate_server_spec(&server_spec)?;
-        crate::mcp::sync_server_to_app(&id, &server_spec, &app_type)?;
+        let engine_spec = registry_engine_spec(&id, server_spec)?;
+        crate::mcp::sync_server_to_app(&id, &engine_spec, &app_type)?;
         Ok(format!(
             "已在 {} 引擎中启用 MCP 服务器 '{}'",
             engine, id
@@ -1046,6 +1049,70 @@ pub async fn mcp_toggle_engine_server(
     }
 }
 
+/// 注册表条目写入引擎配置时的规范（不在注册表中时原样使用 `server_spec`）
+fn registry_engine_spec(
+    id: &str,
+    server_spec: serde_json::Value,
+) -> Result<serde_json::Value, String> {
+    match crate::mcp::registry::get_server(id)? {
+        Some(entry) => entry.engine_spec(),
+        None => Ok(server_spec),
+    }
+}
+
+/// 启用或禁用 MCP 服务器中的单个工具
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `tool`: 工具名称（来自 `mcp_list_tools`）
+/// - `enabled`: 是否启用
+/// - `project_path`: 给出且服务器在该项目的注册表中时修改项目注册表
+///
+/// # 说明
+/// - 修改后立即同步到已启用该服务器的引擎：Codex 写入 `disabled_tools`，
+///   Gemini 写入 `excludeTools`，Claude 写入 settings.json 的 `permissions.deny`
+#[tauri::command]
+pub async fn mcp_set_tool_enabled(
+    id: String,
+    tool: String,
+    enabled: bool,
+    project_path: Option<String>,
+) -> Result<Vec<String>, String> {
+    info!(
+        "{} MCP 服务器 '{}' 的工具 '{}'",
+        if enabled { "启用" } else { "禁用" },
+        id,
+        tool
+    );
+
+    if tool.trim().is_empty() {
+        return Err("工具名称不能为空".to_string());
+    }
+
+    let (scope, entry) =
+        crate::mcp::registry::set_tool_enabled(project_path.as_deref(), &id, &tool, enabled)?;
+
+    match (scope, project_path.as_deref()) {
+        (crate::mcp::registry::RegistryScope::Project, Some(project_path)) => {
+            crate::mcp::sync_project_registry(project_path, &[])?;
+        }
+        _ => {
+            // 只更新当前已启用该服务器的引擎
+            let spec = entry.engine_spec()?;
+            for app_type in AppType::ALL {
+                let active = crate::mcp::import_from_app(&app_type)
+                    .map(|servers| servers.contains_key(&id))
+                    .unwrap_or(false);
+                if active {
+                    crate::mcp::sync_server_to_app(&id, &spec, &app_type)?;
+                }
+            }
+        }
+    }
+
+    Ok(entry.disabled_tools)
+}
+
 /// 带启用状态的 MCP 服务器条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpServerWithStatus {
@@ -1055,12 +1122,14 @@ pub struct McpServerWithStatus {
     pub spec: serde_json::Value,
     /// 是否启用
     pub enabled: bool,
+    /// 最近一次健康检查的结果
+    pub health: Option<crate::mcp::health::ServerHealth>,
 }
 
 /// 获取指定引擎的 MCP 服务器列表（包含禁用的服务器）
 ///
 /// # 参数
-/// - `engine`: 引擎名称（"claude" | "codex" | "gemini"）
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
 ///
 /// # 返回
 /// - Ok(Vec<McpServerWithStatus>): 该引擎的 MCP 服务器列表（包含启用状态）
@@ -1071,9 +1140,835 @@ pub async fn mcp_get_engine_servers_with_status(
     info!("获取 {} 引擎的 MCP 服务器列表（含状态）", engine);
 
     let servers = crate::mcp::registry::get_engine_servers_with_status(&engine)?;
+    let registry = crate::mcp::registry::read_registry()?;
 
     Ok(servers
         .into_iter()
-        .map(|(id, spec, enabled)| McpServerWithStatus { id, spec, enabled })
+        .map(|(id, spec, enabled)| {
+            let health = registry
+                .servers
+                .get(&id)
+                .and_then(|entry| entry.health.clone());
+            McpServerWithStatus {
+                id,
+                spec,
+                enabled,
+                health,
+            }
+        })
         .collect())
 }
+
+/// 查找注册表条目：项目注册表 → 全局注册表
+fn find_server_entry(
+    id: &str,
+    project_path: Option<&str>,
+) -> Result<Option<crate::mcp::registry::RegistryEntry>, String> {
+    if let Some(project_path) = project_path {
+        let entry = crate::mcp::registry::read_project_registry(project_path)?
+            .servers
+            .remove(id);
+        if entry.is_some() {
+            return Ok(entry);
+        }
+    }
+    crate::mcp::registry::get_server(id)
+}
+
+/// 查找服务器配置：项目注册表 → 全局注册表 → 引擎配置（密钥已替换为真实值）
+fn find_server_spec(id: &str, project_path: Option<&str>) -> Result<serde_json::Value, String> {
+    let entry = find_server_entry(id, project_path)?;
+    let mut spec = entry.map(|entry| entry.launch_spec()).transpose()?;
+    if spec.is_none() {
+        spec = AppType::ALL
+            .iter()
+            .find_map(|app| crate::mcp::import_from_app(app).ok()?.remove(id));
+    }
+    spec.ok_or_else(|| format!("未找到 MCP 服务器 '{}'", id))
+}
+
+/// 测试 MCP 服务器：启动（或连接）服务器并完成 initialize 握手
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `project_path`: 给出时优先查找该项目的注册表
+///
+/// # 说明
+/// - 返回协议版本、工具数量和延迟；连接失败时 `healthy` 为 false 并附带错误信息
+/// - 结果保存在注册表条目中，供 UI 显示健康状态
+/// - 不在注册表中的服务器（只存在于引擎配置中）也可以测试，但结果不会保存
+#[tauri::command]
+pub async fn mcp_test_server(
+    id: String,
+    project_path: Option<String>,
+) -> Result<crate::mcp::health::ServerHealth, String> {
+    info!("测试 MCP 服务器: {}", id);
+
+    let spec = find_server_spec(&id, project_path.as_deref())?;
+
+    let health = crate::mcp::health::check_server(&spec).await;
+    if health.healthy {
+        info!(
+            "MCP 服务器 '{}' 可用: 协议 {:?}，{:?} 个工具，{:?} ms",
+            id, health.protocol_version, health.tool_count, health.latency_ms
+        );
+    } else {
+        log::warn!(
+            "MCP 服务器 '{}' 不可用: {}",
+            id,
+            health.error.as_deref().unwrap_or("未知错误")
+        );
+    }
+
+    if let Err(e) = crate::mcp::registry::set_server_health(project_path.as_deref(), &id, &health) {
+        error!("保存 MCP 服务器 '{}' 的健康状态失败: {}", id, e);
+    }
+
+    Ok(health)
+}
+
+/// 获取服务器的工具和资源列表：默认使用注册表中的缓存，没有缓存或 `refresh` 时连接服务器
+async fn introspect_server(
+    id: &str,
+    project_path: Option<&str>,
+    refresh: bool,
+) -> Result<crate::mcp::health::ServerIntrospection, String> {
+    if !refresh {
+        let mut cached = None;
+        if let Some(project_path) = project_path {
+            cached = crate::mcp::registry::read_project_registry(project_path)?
+                .servers
+                .remove(id)
+                .and_then(|entry| entry.introspection);
+        }
+        if cached.is_none() {
+            cached = crate::mcp::registry::get_server(id)?.and_then(|entry| entry.introspection);
+        }
+        if let Some(cached) = cached {
+            return Ok(cached);
+        }
+    }
+
+    info!("内省 MCP 服务器: {}", id);
+    let spec = find_server_spec(id, project_path)?;
+    let introspection = crate::mcp::health::introspect(&spec).await?;
+    info!(
+        "MCP 服务器 '{}' 提供 {} 个工具、{} 个资源",
+        id,
+        introspection.tools.len(),
+        introspection.resources.len()
+    );
+
+    if let Err(e) = crate::mcp::registry::set_server_introspection(project_path, id, &introspection) {
+        error!("缓存 MCP 服务器 '{}' 的工具列表失败: {}", id, e);
+    }
+    Ok(introspection)
+}
+
+/// 列出 MCP 服务器提供的工具（名称、描述和参数 schema）
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `project_path`: 给出时优先查找该项目的注册表
+/// - `refresh`: 为 true 时忽略缓存，重新连接服务器
+///
+/// # 说明
+/// - 结果缓存在注册表条目中；修改服务器配置后缓存失效
+#[tauri::command]
+pub async fn mcp_list_tools(
+    id: String,
+    project_path: Option<String>,
+    refresh: Option<bool>,
+) -> Result<Vec<crate::mcp::health::McpTool>, String> {
+    introspect_server(&id, project_path.as_deref(), refresh.unwrap_or(false))
+        .await
+        .map(|introspection| introspection.tools)
+}
+
+/// 列出 MCP 服务器提供的资源，参数与缓存规则同 `mcp_list_tools`
+#[tauri::command]
+pub async fn mcp_list_resources(
+    id: String,
+    project_path: Option<String>,
+    refresh: Option<bool>,
+) -> Result<Vec<crate::mcp::health::McpResource>, String> {
+    introspect_server(&id, project_path.as_deref(), refresh.unwrap_or(false))
+        .await
+        .map(|introspection| introspection.resources)
+}
+
+// ============================================================================
+// 项目级 MCP 注册表
+// ============================================================================
+
+/// 获取项目中生效的 MCP 服务器（全局与项目注册表合并，标注作用域）
+#[tauri::command]
+pub async fn mcp_get_project_servers(
+    project_path: String,
+) -> Result<Vec<crate::mcp::registry::ScopedEntry>, String> {
+    crate::mcp::registry::resolve_project_servers(&project_path)
+}
+
+/// 在项目注册表中添加或更新 MCP 服务器
+///
+/// # 参数
+/// - `project_path`: 项目路径
+/// - `id`: 服务器 ID
+/// - `server_spec`: 服务器规范（JSON）
+/// - `enabled`: 在该项目中是否启用
+#[tauri::command]
+pub async fn mcp_upsert_project_server(
+    project_path: String,
+    id: String,
+    server_spec: serde_json::Value,
+    enabled: bool,
+) -> Result<String, String> {
+    info!("在项目 {} 中添加/更新 MCP 服务器: {}", project_path, id);
+
+    crate::mcp::validate_server_spec(&server_spec)?;
+    crate::mcp::registry::upsert_project_server(&project_path, &id, &id, &server_spec, enabled)?;
+    crate::mcp::sync_project_registry(&project_path, &[])?;
+
+    Ok(format!("成功在项目中配置 MCP 服务器 '{}'", id))
+}
+
+/// 将全局 MCP 服务器降级为项目服务器
+///
+/// 服务器会从所有引擎的全局配置中移除，只写入该项目的 .mcp.json
+#[tauri::command]
+pub async fn mcp_demote_server(project_path: String, id: String) -> Result<String, String> {
+    info!("将 MCP 服务器 '{}' 降级到项目: {}", id, project_path);
+
+    crate::mcp::registry::demote_server(&project_path, &id)?;
+
+    for app in AppType::ALL {
+        let configured = crate::mcp::import_from_app(&app)
+            .map(|servers| servers.contains_key(&id))
+            .unwrap_or(false);
+        if configured {
+            if let Err(e) = crate::mcp::remove_server_from_app(&id, &app) {
+                log::warn!("从 {} 全局配置中移除 MCP 服务器 '{}' 失败: {}", app.as_str(), id, e);
+            }
+        }
+    }
+    crate::mcp::sync_project_registry(&project_path, &[])?;
+
+    Ok(format!("MCP 服务器 '{}' 现在只在该项目中可用", id))
+}
+
+/// 将项目 MCP 服务器提升为全局服务器
+///
+/// 服务器会从项目的 .mcp.json 中移除，之后需要在各引擎中启用
+#[tauri::command]
+pub async fn mcp_promote_server(project_path: String, id: String) -> Result<String, String> {
+    info!("将项目 {} 的 MCP 服务器 '{}' 提升为全局", project_path, id);
+
+    crate::mcp::registry::promote_server(&project_path, &id)?;
+    crate::mcp::sync_project_registry(&project_path, &[&id])?;
+
+    Ok(format!("MCP 服务器 '{}' 已移到全局注册表", id))
+}
+
+/// 设置 MCP 服务器在项目中的启用状态（项目服务器或全局服务器均可）
+#[tauri::command]
+pub async fn mcp_set_project_server_enabled(
+    project_path: String,
+    id: String,
+    enabled: bool,
+) -> Result<String, String> {
+    info!(
+        "设置项目 {} 中 MCP 服务器 '{}' 的状态: {}",
+        project_path, id, enabled
+    );
+
+    crate::mcp::registry::set_project_server_enabled(&project_path, &id, enabled)?;
+    crate::mcp::sync_project_registry(&project_path, &[])?;
+
+    Ok(format!(
+        "已在项目中{} MCP 服务器 '{}'",
+        if enabled { "启用" } else { "禁用" },
+        id
+    ))
+}
+
+/// 列出 MCP 注册表的自动备份（最新的在前）
+#[tauri::command]
+pub async fn list_registry_backups() -> Result<Vec<crate::mcp::registry::RegistryBackup>, String> {
+    crate::mcp::registry::list_backups()
+}
+
+/// 从备份恢复 MCP 注册表
+///
+/// # 参数
+/// - `name`: 备份文件名（来自 `list_registry_backups`）
+///
+/// # 说明
+/// - 当前注册表会先被备份，因此恢复操作本身也可以撤销
+/// - 只恢复注册表，引擎配置文件需要再次同步
+#[tauri::command]
+pub async fn restore_registry_backup(name: String) -> Result<String, String> {
+    info!("从备份恢复 MCP 注册表: {}", name);
+
+    let count = crate::mcp::registry::restore_backup(&name)?;
+
+    Ok(format!("已从备份 '{}' 恢复 {} 个 MCP 服务器", name, count))
+}
+
+// ============================================================================
+// MCP 服务器进程监督
+// ============================================================================
+
+/// 启动 stdio MCP 服务器并监督其进程，崩溃后自动重启
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `project_path`: 给出时优先查找该项目的注册表，并作为进程的工作目录
+///
+/// # 说明
+/// - 状态变化通过 `mcp-server-state` 事件通知前端
+/// - 该项目中的引擎会话通过桥接连接这个进程
+#[tauri::command]
+pub async fn mcp_supervisor_start(
+    id: String,
+    project_path: Option<String>,
+) -> Result<crate::mcp::supervisor::SupervisedServer, String> {
+    info!("启动受监督的 MCP 服务器: {}", id);
+
+    let launch = server_launch(&id, project_path.as_deref())?;
+    crate::mcp::supervisor::start(launch, project_path.as_deref().map(Path::new))
+}
+
+/// 服务器的启动配置（不在注册表中的服务器使用默认的启动策略和资源限制）
+fn server_launch(
+    id: &str,
+    project_path: Option<&str>,
+) -> Result<crate::mcp::supervisor::ServerLaunch, String> {
+    match find_server_entry(id, project_path)? {
+        Some(entry) => crate::mcp::supervisor::ServerLaunch::from_entry(&entry),
+        None => Ok(crate::mcp::supervisor::ServerLaunch::from_spec(
+            id,
+            find_server_spec(id, project_path)?,
+        )),
+    }
+}
+
+/// 使用受监督的 MCP 服务器：未运行时按启动策略启动，运行中则刷新空闲计时
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `project_path`: 给出时优先查找该项目的注册表，并作为进程的工作目录
+///
+/// # 说明
+/// - 启动策略为 `manual` 且未运行的服务器返回错误
+/// - 设置了空闲超时的服务器在没有引擎会话连接、且超过该时间未被使用后自动停止
+#[tauri::command]
+pub async fn mcp_supervisor_acquire(
+    id: String,
+    project_path: Option<String>,
+) -> Result<crate::mcp::supervisor::SupervisedServer, String> {
+    let launch = server_launch(&id, project_path.as_deref())?;
+    crate::mcp::supervisor::acquire(launch, project_path.as_deref().map(Path::new))
+}
+
+/// 设置 MCP 服务器的启动策略（eager / on-first-use / manual）和空闲超时
+///
+/// # 参数
+/// - `id`: 服务器 ID（全局注册表）
+/// - `startup`: 启动配置，例如 `{ "policy": "on-first-use", "idleTimeoutSecs": 600 }`
+///
+/// # 说明
+/// - 对之后启动的进程生效，正在运行的服务器需要重启
+#[tauri::command]
+pub async fn mcp_set_server_launch_options(
+    id: String,
+    startup: crate::mcp::supervisor::StartupConfig,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    info!("设置 MCP 服务器 '{}' 的启动配置: {:?}", id, startup);
+
+    crate::mcp::registry::set_startup_config(&id, startup)
+}
+
+/// 设置 MCP 服务器进程的资源限制（内存上限、优先级、启动目录、环境变量白名单）
+///
+/// # 参数
+/// - `id`: 服务器 ID（全局注册表）
+/// - `sandbox`: 例如 `{ "maxMemoryMb": 512, "nice": 10, "launchRoot": "/path/to/project", "envAllowlist": ["LANG"] }`
+///
+/// # 说明
+/// - 由进程监督执行，对之后启动的进程生效，正在运行的服务器需要重启
+/// - 全局的 MCP 资源限制同时生效；限制无法生效时（例如 macOS 上的内存上限）服务器不会启动
+#[tauri::command]
+pub async fn mcp_set_server_sandbox(
+    id: String,
+    sandbox: crate::mcp::sandbox::SandboxConfig,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    info!("设置 MCP 服务器 '{}' 的资源限制: {:?}", id, sandbox);
+
+    crate::mcp::registry::set_sandbox_config(&id, sandbox)
+}
+
+/// 停止受监督的 MCP 服务器（所有工作目录中的实例），连接的引擎会话随之断开
+#[tauri::command]
+pub async fn mcp_supervisor_stop(id: String) -> Result<String, String> {
+    info!("停止受监督的 MCP 服务器: {}", id);
+
+    crate::mcp::supervisor::stop(&id).await?;
+
+    Ok(format!("已停止 MCP 服务器 '{}'", id))
+}
+
+/// 重启受监督的 MCP 服务器的所有实例（重新读取配置）
+#[tauri::command]
+pub async fn mcp_supervisor_restart(
+    id: String,
+    project_path: Option<String>,
+) -> Result<Vec<crate::mcp::supervisor::SupervisedServer>, String> {
+    info!("重启受监督的 MCP 服务器: {}", id);
+
+    let launch = server_launch(&id, project_path.as_deref())?;
+    crate::mcp::supervisor::restart(launch, project_path.as_deref().map(Path::new)).await
+}
+
+/// 列出受监督的 MCP 服务器及其 PID、运行时长和重启次数
+#[tauri::command]
+pub async fn mcp_supervisor_list() -> Result<Vec<crate::mcp::supervisor::SupervisedServer>, String> {
+    Ok(crate::mcp::supervisor::list())
+}
+
+/// 读取 MCP 服务器的日志（stderr 和进程生命周期）
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `tail`: 返回的行数，默认 200
+///
+/// # 说明
+/// - 新日志通过 `mcp-server-log` 事件实时推送
+#[tauri::command]
+pub async fn mcp_get_server_logs(id: String, tail: Option<usize>) -> Result<Vec<String>, String> {
+    crate::mcp::logs::read_tail(&id, tail.unwrap_or(crate::mcp::logs::DEFAULT_TAIL))
+}
+
+// ============================================================================
+// MCP 配置组
+// ============================================================================
+
+/// 列出 MCP 配置组
+#[tauri::command]
+pub async fn mcp_list_profiles() -> Result<Vec<crate::mcp::registry::McpProfile>, String> {
+    crate::mcp::registry::list_profiles()
+}
+
+/// 创建或更新 MCP 配置组
+///
+/// # 参数
+/// - `name`: 配置组名称（同名时覆盖）
+/// - `servers`: 组内服务器 ID，必须已在注册表中
+/// - `description`: 说明（可选）
+#[tauri::command]
+pub async fn mcp_create_profile(
+    name: String,
+    servers: Vec<String>,
+    description: Option<String>,
+) -> Result<crate::mcp::registry::McpProfile, String> {
+    info!("保存 MCP 配置组: {}", name);
+
+    crate::mcp::registry::create_profile(&name, description, &servers)
+}
+
+/// 删除 MCP 配置组
+#[tauri::command]
+pub async fn mcp_delete_profile(name: String) -> Result<String, String> {
+    info!("删除 MCP 配置组: {}", name);
+
+    crate::mcp::registry::delete_profile(&name)?;
+
+    Ok(format!("已删除配置组 '{}'", name))
+}
+
+/// 将 MCP 配置组应用到引擎
+///
+/// # 参数
+/// - `name`: 配置组名称
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
+///
+/// # 说明
+/// - 引擎的 MCP 配置被整体替换为组内的服务器
+/// - 引擎中原有的服务器都保留在注册表中，可以随时切换回来
+#[tauri::command]
+pub async fn mcp_apply_profile(name: String, engine: String) -> Result<String, String> {
+    info!("将 MCP 配置组 '{}' 应用到 {} 引擎", name, engine);
+
+    let count = crate::mcp::registry::apply_profile(&name, &engine)?;
+
+    Ok(format!(
+        "已将配置组 '{}' 应用到 {} 引擎（{} 个服务器）",
+        name, engine, count
+    ))
+}
+
+/// 从其他工具的配置导入 MCP 服务器
+///
+/// # 参数
+/// - `source`: 来源（"claude-desktop" | "cursor" | "windsurf" | "vscode"）
+/// - `dry_run`: 为 true 时只预览，不写入注册表
+/// - `config_path`: 配置文件路径（可选，默认为该工具的标准位置）
+///
+/// # 说明
+/// - 与注册表中已有服务器相同（同 ID 或同命令/URL）或冲突的服务器不会导入
+/// - 导入的服务器为禁用状态，需要再为各引擎单独启用
+#[tauri::command]
+pub async fn mcp_import_external(
+    source: String,
+    dry_run: Option<bool>,
+    config_path: Option<String>,
+) -> Result<crate::mcp::external::ExternalImport, String> {
+    info!("从外部工具导入 MCP 服务器: {}", source);
+
+    let source = crate::mcp::external::ExternalSource::from_str(&source)?;
+    crate::mcp::external::import_external(
+        source,
+        config_path.map(PathBuf::from),
+        dry_run.unwrap_or(false),
+    )
+}
+
+/// 导出 MCP 配置包（注册表、配置组和各引擎启用的服务器）
+///
+/// # 参数
+/// - `path`: 配置包保存路径
+/// - `include_secrets`: 为 false 时清空 env/headers 中的密钥，并在配置包中列出
+#[tauri::command]
+pub async fn mcp_export_bundle(
+    path: String,
+    include_secrets: bool,
+) -> Result<crate::mcp::bundle::McpBundle, String> {
+    info!("导出 MCP 配置包: {}", path);
+
+    crate::mcp::bundle::export_bundle(&PathBuf::from(path), include_secrets)
+}
+
+/// 导入 MCP 配置包
+///
+/// # 参数
+/// - `path`: 配置包路径
+/// - `overwrite`: 为 true 时覆盖注册表中同 ID 的服务器和同名配置组
+/// - `sync_engines`: 为 true 时把配置包中各引擎启用的服务器添加到本机的引擎配置
+///
+/// # 说明
+/// - 返回仍需填写的密钥；缺少密钥的服务器不会同步到引擎
+#[tauri::command]
+pub async fn mcp_import_bundle(
+    path: String,
+    overwrite: Option<bool>,
+    sync_engines: Option<bool>,
+) -> Result<crate::mcp::bundle::BundleImport, String> {
+    info!("导入 MCP 配置包: {}", path);
+
+    crate::mcp::bundle::import_bundle(
+        &PathBuf::from(path),
+        overwrite.unwrap_or(false),
+        sync_engines.unwrap_or(false),
+    )
+}
+
+/// 将 MCP 服务器配置中的 env/headers 字段标记为密钥
+///
+/// # 参数
+/// - `id`: 服务器 ID（全局注册表）
+/// - `block`: "env" 或 "headers"
+/// - `key`: 字段名
+/// - `value`: 新的密钥值（可选，默认使用配置中当前的值）
+///
+/// # 说明
+/// - 值保存到系统钥匙串，注册表中只保留占位符；同步到引擎和启动服务器时再替换为真实值
+#[tauri::command]
+pub async fn mcp_mark_secret(
+    id: String,
+    block: String,
+    key: String,
+    value: Option<String>,
+) -> Result<String, String> {
+    info!("将 MCP 服务器 '{}' 的 {}.{} 标记为密钥", id, block, key);
+
+    crate::mcp::registry::mark_secret(&id, &block, &key, value.as_deref())?;
+
+    Ok(format!("已将 {}.{} 保存到系统钥匙串", block, key))
+}
+
+/// 取消密钥标记，值写回注册表并从系统钥匙串删除
+#[tauri::command]
+pub async fn mcp_unmark_secret(id: String, block: String, key: String) -> Result<String, String> {
+    info!("取消 MCP 服务器 '{}' 的 {}.{} 的密钥标记", id, block, key);
+
+    crate::mcp::registry::unmark_secret(&id, &block, &key)?;
+
+    Ok(format!("已取消 {}.{} 的密钥标记", block, key))
+}
+
+/// 检测注册表与引擎配置的差异
+///
+/// # 参数
+/// - `engine`: 引擎类型（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
+///
+/// # 说明
+/// - 列出直接修改引擎配置（例如 ~/.claude.json）后与注册表不一致的服务器
+#[tauri::command]
+pub async fn mcp_detect_drift(engine: String) -> Result<crate::mcp::drift::DriftReport, String> {
+    info!("检测 {} 引擎与注册表的差异", engine);
+
+    crate::mcp::drift::detect_drift(&engine)
+}
+
+/// 协调注册表与引擎配置
+///
+/// # 参数
+/// - `engine`: 引擎类型（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
+/// - `strategy`: 协调方式（"keep-registry" | "keep-engine" | "merge"）
+///
+/// # 说明
+/// - 只在引擎中存在的服务器总会保存到注册表
+/// - 返回协调后的差异，正常情况下为空
+#[tauri::command]
+pub async fn mcp_reconcile_drift(
+    engine: String,
+    strategy: String,
+) -> Result<crate::mcp::drift::DriftReport, String> {
+    info!("协调 {} 引擎与注册表: {}", engine, strategy);
+
+    let strategy = crate::mcp::drift::ReconcileStrategy::from_str(&strategy)?;
+    crate::mcp::drift::reconcile(&engine, strategy)
+}
+
+/// 验证 MCP 服务器规范
+///
+/// # 参数
+/// - `spec`: 服务器规范（JSON）
+///
+/// # 说明
+/// - 返回所有字段级错误（为空表示有效），供添加服务器的表单逐字段显示
+#[tauri::command]
+pub async fn mcp_validate_spec(
+    spec: serde_json::Value,
+) -> Result<Vec<crate::mcp::SpecError>, String> {
+    Ok(crate::mcp::validate_spec_fields(&spec))
+}
+
+/// 获取 MCP 服务器目录
+///
+/// # 参数
+/// - `remote_url`: 远程目录地址（可选）；获取成功后缓存，同 ID 的条目覆盖内置条目
+///
+/// # 说明
+/// - 远程目录获取失败时返回内置目录和之前的缓存，并在 `remoteError` 中说明原因
+#[tauri::command]
+pub async fn mcp_get_catalog(
+    remote_url: Option<String>,
+) -> Result<crate::mcp::catalog::CatalogListing, String> {
+    crate::mcp::catalog::list_catalog(remote_url.as_deref()).await
+}
+
+/// 从目录安装 MCP 服务器
+///
+/// # 参数
+/// - `id`: 目录中的服务器 ID
+/// - `inputs`: 用户填写的参数（参数名 -> 值）
+///
+/// # 说明
+/// - 自动生成 npx/uvx/docker 命令或远程地址，标记为密钥的参数保存到系统钥匙串
+/// - 安装的服务器为禁用状态，需要再为各引擎单独启用
+#[tauri::command]
+pub async fn mcp_install_from_catalog(
+    id: String,
+    inputs: Option<HashMap<String, String>>,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    info!("从目录安装 MCP 服务器: {}", id);
+
+    crate::mcp::catalog::install(&id, &inputs.unwrap_or_default())
+}
+
+/// 检查注册表中 npx/uvx 服务器的更新
+///
+/// # 说明
+/// - 查询 npm / PyPI 上的最新版本；只有锁定了版本的服务器会提示更新
+/// - 单个服务器查询失败不影响其它服务器，原因记录在结果的 `error` 中
+#[tauri::command]
+pub async fn mcp_check_updates() -> Result<Vec<crate::mcp::versions::UpdateCheck>, String> {
+    info!("检查 MCP 服务器更新");
+
+    crate::mcp::versions::check_updates().await
+}
+
+/// 更新服务器锁定的版本（用户确认后调用）
+///
+/// # 参数
+/// - `id`: 服务器 ID（全局注册表）
+/// - `version`: 目标版本（可选，默认为最新版本）
+///
+/// # 说明
+/// - 已启用该服务器的引擎会同步更新配置
+#[tauri::command]
+pub async fn mcp_update_server(
+    id: String,
+    version: Option<String>,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    info!("更新 MCP 服务器 '{}' 的版本", id);
+
+    let entry = crate::mcp::versions::update_server(&id, version.as_deref()).await?;
+
+    // 只更新当前已启用该服务器的引擎
+    let spec = entry.engine_spec()?;
+    for app_type in AppType::ALL {
+        let active = crate::mcp::import_from_app(&app_type)
+            .map(|servers| servers.contains_key(&id))
+            .unwrap_or(false);
+        if active {
+            crate::mcp::sync_server_to_app(&id, &spec, &app_type)?;
+        }
+    }
+
+    Ok(entry)
+}
+
+/// 将注册表同步到引擎，并检测启用的服务器之间的工具名冲突
+///
+/// # 参数
+/// - `engine`: 引擎名称（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
+/// - `apply_prefixes`: 为 true 时给冲突中的 stdio 服务器自动设置工具前缀（默认 false）
+///
+/// # 说明
+/// - 使用缓存的工具列表，没有缓存的服务器会被连接一次
+/// - 冲突、自动设置的前缀和无法检测的服务器都记录在返回结果中
+#[tauri::command]
+pub async fn mcp_sync_engine(
+    engine: String,
+    apply_prefixes: Option<bool>,
+) -> Result<crate::mcp::conflicts::SyncReport, String> {
+    info!("同步 MCP 注册表到 {} 引擎并检测工具冲突", engine);
+
+    crate::mcp::conflicts::sync_engine(&engine, apply_prefixes.unwrap_or(false)).await
+}
+
+/// 设置或清除服务器的工具前缀
+///
+/// # 参数
+/// - `id`: 服务器 ID（全局注册表）
+/// - `prefix`: 工具前缀（为空时清除）
+///
+/// # 说明
+/// - 只有 stdio 服务器支持前缀
+/// - 已启用该服务器的引擎会同步更新配置
+#[tauri::command]
+pub async fn mcp_set_tool_prefix(
+    id: String,
+    prefix: Option<String>,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    let prefix = prefix.filter(|prefix| !prefix.is_empty());
+    info!("设置 MCP 服务器 '{}' 的工具前缀: {:?}", id, prefix);
+
+    let entry = crate::mcp::registry::set_tool_prefix(&id, prefix.as_deref())?;
+
+    let spec = entry.engine_spec()?;
+    for app_type in AppType::ALL {
+        if entry.is_enabled_for(&app_type) {
+            crate::mcp::sync_server_to_app(&id, &spec, &app_type)?;
+        }
+    }
+
+    Ok(entry)
+}
+
+/// 获取 MCP 注册表的变更记录（最新的在前）
+///
+/// # 参数
+/// - `server_id`: 给出时只返回该服务器的记录
+/// - `limit`: 最多返回的记录数（默认 200）
+///
+/// # 说明
+/// - 记录每次写入全局注册表时的新增、删除、修改和启用状态变化，以及变化前后的字段值
+#[tauri::command]
+pub async fn mcp_get_audit_log(
+    server_id: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<crate::mcp::audit::AuditRecord>, String> {
+    crate::mcp::audit::read_log(
+        server_id.as_deref(),
+        limit.unwrap_or(crate::mcp::audit::DEFAULT_LIMIT),
+    )
+}
+
+/// 搜索注册表中的 MCP 服务器
+///
+/// # 参数
+/// - `query`: 按 ID、名称、描述、标签和命令模糊匹配，多个词以空格分隔，为空时返回全部
+/// - `tags`: 给出时只返回包含所有这些标签的服务器
+///
+/// # 返回
+/// 按相关度排序的匹配结果
+#[tauri::command]
+pub async fn mcp_search_servers(
+    query: Option<String>,
+    tags: Option<Vec<String>>,
+) -> Result<Vec<crate::mcp::search::SearchHit>, String> {
+    let registry = crate::mcp::registry::read_registry()?;
+    Ok(crate::mcp::search::search(
+        &registry,
+        query.as_deref().unwrap_or_default(),
+        &tags.unwrap_or_default(),
+    ))
+}
+
+/// 获取注册表中使用的所有标签及对应的服务器数量，用于分组显示
+#[tauri::command]
+pub async fn mcp_list_server_tags() -> Result<std::collections::BTreeMap<String, usize>, String> {
+    let registry = crate::mcp::registry::read_registry()?;
+    Ok(crate::mcp::search::tag_counts(&registry))
+}
+
+/// 设置 MCP 服务器的描述和标签
+///
+/// # 参数
+/// - `id`: 服务器 ID
+/// - `description`: 描述，为空时清除
+/// - `tags`: 标签（保存为小写并去重）
+#[tauri::command]
+pub async fn mcp_set_server_metadata(
+    id: String,
+    description: Option<String>,
+    tags: Vec<String>,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    info!("设置 MCP 服务器 '{}' 的描述和标签: {:?}", id, tags);
+
+    crate::mcp::registry::set_server_metadata(&id, description, &tags)
+}
+
+/// 将内置的工作台 MCP 服务器添加到注册表，并在指定引擎中启用
+///
+/// # 参数
+/// - `engines`: 要启用的引擎（"claude" | "codex" | "gemini" | "ollama" | "openrouter"）
+///
+/// # 说明
+/// - 服务器由本应用以 stdio 方式提供，工具可读取项目文件、列出检查点和查看 git diff
+/// - 应用路径变化（例如重新安装到其它位置）后需要再次调用
+#[tauri::command]
+pub async fn mcp_install_workbench_server(
+    engines: Vec<String>,
+) -> Result<crate::mcp::registry::RegistryEntry, String> {
+    use crate::mcp::workbench::SERVER_ID;
+    info!("安装工作台 MCP 服务器: {:?}", engines);
+
+    let spec = crate::mcp::workbench::server_spec()?;
+    crate::mcp::registry::upsert_server(SERVER_ID, "AnyCode Workbench", &spec, None)?;
+    for engine in &engines {
+        let app_type = AppType::from_str(engine)?;
+        crate::mcp::registry::set_server_enabled(SERVER_ID, &app_type, true)?;
+    }
+
+    let entry = crate::mcp::registry::get_server(SERVER_ID)?
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", SERVER_ID))?;
+    let engine_spec = entry.engine_spec()?;
+    for engine in &engines {
+        crate::mcp::sync_server_to_app(SERVER_ID, &engine_spec, &AppType::from_str(engine)?)?;
+    }
+
+    Ok(entry)
+}
@@ -1,22 +1,88 @@
 pub mod acemcp;
+pub mod agent_packs;
+pub mod auto_checkpoint;
+pub mod branch_protection;
+pub mod checkpoints;
 pub mod claude;
+pub mod claude_profiles;
 pub mod clipboard;
 pub mod codex; // OpenAI Codex integration
+pub mod commit_metadata;
+pub mod commit_signing;
+pub mod commit_templates;
+pub mod config_watcher;
+pub mod connectivity;
 pub mod context_commands;
+pub mod context_files;
 pub mod context_manager;
+pub mod dev_containers;
+pub mod disk_space;
+pub mod doctor;
+pub mod engine_doctor;
+pub mod engine_safety;
+pub mod engines;
 pub mod enhanced_hooks;
+pub mod event_batcher;
+pub mod exclusion_policy;
 pub mod extensions;
 pub mod file_operations;
+pub mod forge;
 pub mod gemini; // Google Gemini CLI integration
+pub mod git_backend;
+pub mod git_blame;
+pub mod git_cherry_pick;
+pub mod git_diff;
+pub mod git_doctor;
+pub mod git_executor;
+pub mod git_history;
+pub mod git_identity;
+pub mod git_patches;
+pub mod git_recovery;
+pub mod git_squash;
+pub mod git_staging;
+pub mod git_status_cache;
 pub mod git_stats;
+pub mod gitignore;
+pub mod key_pools;
+pub mod large_file_guard;
 pub mod mcp;
+pub mod monorepo;
+pub mod ollama; // Ollama / OpenAI-compatible local models
+pub mod onboarding;
+pub mod openrouter; // OpenRouter hosted models
 pub mod permission_config;
+pub mod process_reaper;
+pub mod profiling;
+pub mod project_defaults;
+pub mod project_settings;
+pub mod prompt_cache;
+pub mod prompt_drafts;
+pub mod prompt_history;
 pub mod prompt_tracker;
 pub mod provider;
+pub mod redaction;
+pub mod remote_projects;
+pub mod resource_limits;
+pub mod screenshot;
+pub mod secret_scanner;
+pub mod session_bookmarks;
+pub mod session_cancel;
+pub mod session_history;
+pub mod session_search;
+pub mod session_stream;
+pub mod session_titles;
 pub mod simple_git;
+pub mod snapshots;
+pub mod speech_to_text;
 pub mod storage;
+pub mod submodules;
+pub mod task_context;
+pub mod text_encoding;
+pub mod tickets;
+pub mod token_counter;
 pub mod translator;
 pub mod url_utils; // API URL 规范化工具
 pub mod usage;
 pub mod window; // 多窗口管理
+pub mod worktrees;
 pub mod wsl_utils; // WSL 兼容性工具
@@ -0,0 +1,464 @@
+//! Monorepo packages and package-scoped sessions
+//!
+//! `detect_workspace_packages` recognizes pnpm workspaces, npm/yarn
+//! `workspaces`, Cargo workspaces and Nx projects (Turbo repos are npm/pnpm
+//! workspaces with a `turbo.json`). A session can then be scoped to one
+//! package: context assembly, file search and the per-prompt auto-commit only
+//! touch that package's directory, and the checkout can be narrowed to it with
+//! git sparse-checkout. Scopes are stored per session in
+//! `~/.anycode/package-scopes.json`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::wsl_utils::git_command;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Depth searched for Nx `project.json` files
+const NX_SEARCH_DEPTH: usize = 4;
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WorkspaceKind {
+    Pnpm,
+    /// `workspaces` in package.json (npm, yarn, bun)
+    Npm,
+    Cargo,
+    Nx,
+    Turbo,
+}
+
+/// A package of the workspace
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspacePackage {
+    pub name: String,
+    /// Directory relative to the workspace root, with `/` separators
+    pub path: String,
+    pub kind: WorkspaceKind,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceLayout {
+    pub kinds: Vec<WorkspaceKind>,
+    pub packages: Vec<WorkspacePackage>,
+}
+
+/// The package a session is restricted to
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageScope {
+    pub project_path: String,
+    /// Package directory relative to the project root
+    pub package_path: String,
+    pub set_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ScopeStore {
+    #[serde(default)]
+    sessions: BTreeMap<String, PackageScope>,
+}
+
+// ============================================================================
+// Detection
+// ============================================================================
+
+fn read_json(path: &Path) -> Option<Value> {
+    serde_json::from_str(&std::fs::read_to_string(path).ok()?).ok()
+}
+
+fn relative_path(root: &Path, dir: &Path) -> Option<String> {
+    let relative = dir.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Expand workspace globs (`!` patterns exclude) to directories containing `manifest`
+fn expand_globs(root: &Path, patterns: &[String], manifest: &str) -> Vec<PathBuf> {
+    let matches = |pattern: &str| -> BTreeSet<PathBuf> {
+        let full = root.join(pattern.trim_end_matches('/'));
+        glob::glob(&full.to_string_lossy())
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter(|dir| dir.join(manifest).is_file())
+            .filter(|dir| !dir.components().any(|c| c.as_os_str() == "node_modules"))
+            .collect()
+    };
+    let excluded: BTreeSet<PathBuf> = patterns
+        .iter()
+        .filter_map(|p| p.strip_prefix('!'))
+        .flat_map(|p| matches(p))
+        .collect();
+    patterns
+        .iter()
+        .filter(|p| !p.starts_with('!'))
+        .flat_map(|p| matches(p))
+        .filter(|dir| !excluded.contains(dir))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn npm_package(root: &Path, dir: &Path, kind: WorkspaceKind) -> Option<WorkspacePackage> {
+    let path = relative_path(root, dir)?;
+    let name = read_json(&dir.join("package.json"))
+        .and_then(|pkg| pkg.get("name")?.as_str().map(str::to_string))
+        .unwrap_or_else(|| path.clone());
+    Some(WorkspacePackage { name, path, kind })
+}
+
+fn pnpm_packages(root: &Path) -> Option<Vec<WorkspacePackage>> {
+    let content = std::fs::read_to_string(root.join("pnpm-workspace.yaml")).ok()?;
+    let yaml: serde_yaml::Value = serde_yaml::from_str(&content).ok()?;
+    let patterns: Vec<String> = yaml
+        .get("packages")?
+        .as_sequence()?
+        .iter()
+        .filter_map(|p| p.as_str().map(str::to_string))
+        .collect();
+    Some(
+        expand_globs(root, &patterns, "package.json")
+            .iter()
+            .filter_map(|dir| npm_package(root, dir, WorkspaceKind::Pnpm))
+            .collect(),
+    )
+}
+
+fn npm_packages(root: &Path) -> Option<Vec<WorkspacePackage>> {
+    let manifest = read_json(&root.join("package.json"))?;
+    // `workspaces` is either a list or `{ packages: [...] }` (yarn classic)
+    let workspaces = manifest.get("workspaces")?;
+    let list = workspaces
+        .as_array()
+        .or_else(|| workspaces.get("packages")?.as_array())?;
+    let patterns: Vec<String> = list
+        .iter()
+        .filter_map(|p| p.as_str().map(str::to_string))
+        .collect();
+    Some(
+        expand_globs(root, &patterns, "package.json")
+            .iter()
+            .filter_map(|dir| npm_package(root, dir, WorkspaceKind::Npm))
+            .collect(),
+    )
+}
+
+fn cargo_packages(root: &Path) -> Option<Vec<WorkspacePackage>> {
+    let content = std::fs::read_to_string(root.join("Cargo.toml")).ok()?;
+    let manifest: toml::Value = toml::from_str(&content).ok()?;
+    let workspace = manifest.get("workspace")?;
+    let list = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|v| v.as_array())
+            .into_iter()
+            .flatten()
+            .filter_map(|p| p.as_str().map(str::to_string))
+            .collect()
+    };
+    let mut patterns = list("members");
+    patterns.extend(list("exclude").into_iter().map(|p| format!("!{}", p)));
+
+    Some(
+        expand_globs(root, &patterns, "Cargo.toml")
+            .iter()
+            .filter_map(|dir| {
+                let path = relative_path(root, dir)?;
+                let name = std::fs::read_to_string(dir.join("Cargo.toml"))
+                    .ok()
+                    .and_then(|c| toml::from_str::<toml::Value>(&c).ok())
+                    .and_then(|m| m.get("package")?.get("name")?.as_str().map(str::to_string))
+                    .unwrap_or_else(|| path.clone());
+                Some(WorkspacePackage {
+                    name,
+                    path,
+                    kind: WorkspaceKind::Cargo,
+                })
+            })
+            .collect(),
+    )
+}
+
+fn nx_packages(root: &Path) -> Option<Vec<WorkspacePackage>> {
+    if !root.join("nx.json").is_file() {
+        return None;
+    }
+    let packages = walkdir::WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(NX_SEARCH_DEPTH)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name != "node_modules" && !name.starts_with('.')
+        })
+        .flatten()
+        .filter(|entry| entry.file_name() == "project.json")
+        .filter_map(|entry| {
+            let dir = entry.path().parent()?;
+            let path = relative_path(root, dir).filter(|path| !path.is_empty())?;
+            let name = read_json(entry.path())
+                .and_then(|p| p.get("name")?.as_str().map(str::to_string))
+                .unwrap_or_else(|| path.clone());
+            Some(WorkspacePackage {
+                name,
+                path,
+                kind: WorkspaceKind::Nx,
+            })
+        })
+        .collect();
+    Some(packages)
+}
+
+/// Detect the workspace layout of a project
+pub fn detect_layout(root: &Path) -> WorkspaceLayout {
+    let mut layout = WorkspaceLayout::default();
+    let detectors: [(WorkspaceKind, fn(&Path) -> Option<Vec<WorkspacePackage>>); 4] = [
+        (WorkspaceKind::Pnpm, pnpm_packages),
+        (WorkspaceKind::Npm, npm_packages),
+        (WorkspaceKind::Cargo, cargo_packages),
+        (WorkspaceKind::Nx, nx_packages),
+    ];
+    for (kind, detect) in detectors {
+        if let Some(packages) = detect(root) {
+            layout.kinds.push(kind);
+            for package in packages {
+                // The same directory may be listed by several tools (e.g. Nx on pnpm)
+                if !layout.packages.iter().any(|p| p.path == package.path) {
+                    layout.packages.push(package);
+                }
+            }
+        }
+    }
+    if root.join("turbo.json").is_file() {
+        layout.kinds.push(WorkspaceKind::Turbo);
+    }
+    layout.packages.sort_by(|a, b| a.path.cmp(&b.path));
+    layout
+}
+
+// ============================================================================
+// Session scopes
+// ============================================================================
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("package-scopes.json"))
+}
+
+fn load_store() -> ScopeStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+/// Reject package paths that are absolute or leave the project
+fn validate_package_path(package_path: &str) -> Result<String, String> {
+    let trimmed = package_path.trim().trim_matches('/');
+    let valid = !trimmed.is_empty()
+        && Path::new(trimmed)
+            .components()
+            .all(|c| matches!(c, Component::Normal(_)));
+    if valid {
+        Ok(trimmed.replace('\\', "/"))
+    } else {
+        Err(format!("Invalid package path: {}", package_path))
+    }
+}
+
+/// Package directory (relative to the project) a session is scoped to
+pub fn session_package_scope(session_id: &str) -> Option<String> {
+    load_store()
+        .sessions
+        .get(session_id)
+        .map(|scope| scope.package_path.clone())
+}
+
+/// Whether a project-relative path lies inside the package directory
+pub fn is_in_package(relative_path: &str, package_path: &str) -> bool {
+    let path = relative_path.replace('\\', "/");
+    let path = path.trim_start_matches("./");
+    path == package_path || path.starts_with(&format!("{}/", package_path))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Detect workspace tools and packages of a project
+#[tauri::command]
+pub async fn detect_workspace_packages(project_path: String) -> Result<WorkspaceLayout, String> {
+    tokio::task::spawn_blocking(move || detect_layout(Path::new(&project_path)))
+        .await
+        .map_err(|e| e.to_string())
+}
+
+/// Scope a session to a package directory, or clear the scope with `None`
+#[tauri::command]
+pub async fn set_session_package_scope(
+    session_id: String,
+    project_path: String,
+    package_path: Option<String>,
+) -> Result<Option<PackageScope>, String> {
+    let scope = match package_path {
+        Some(path) => {
+            let package_path = validate_package_path(&path)?;
+            if !Path::new(&project_path).join(&package_path).is_dir() {
+                return Err(format!("Package directory not found: {}", package_path));
+            }
+            Some(PackageScope {
+                project_path,
+                package_path,
+                set_at: Utc::now(),
+            })
+        }
+        None => None,
+    };
+
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    match &scope {
+        Some(scope) => store.sessions.insert(session_id, scope.clone()),
+        None => store.sessions.remove(&session_id),
+    };
+    save_json_config(&store, store_path()?)?;
+    Ok(scope)
+}
+
+#[tauri::command]
+pub async fn get_session_package_scope(session_id: String) -> Result<Option<PackageScope>, String> {
+    Ok(load_store().sessions.remove(&session_id))
+}
+
+/// Narrow the working tree to the given packages with cone-mode sparse-checkout
+/// (files at the repository root stay checked out)
+#[tauri::command]
+pub async fn apply_package_sparse_checkout(
+    project_path: String,
+    package_paths: Vec<String>,
+) -> Result<(), String> {
+    if package_paths.is_empty() {
+        return Err("Select at least one package".to_string());
+    }
+    let paths = package_paths
+        .iter()
+        .map(|path| validate_package_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut cmd = git_command(&project_path);
+    cmd.args(["sparse-checkout", "set", "--cone", "--"])
+        .args(&paths);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git sparse-checkout: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git sparse-checkout failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    log::info!(
+        "[Monorepo] Sparse checkout of {:?} in {}",
+        paths,
+        project_path
+    );
+    Ok(())
+}
+
+/// Restore the full working tree
+#[tauri::command]
+pub async fn disable_sparse_checkout(project_path: String) -> Result<(), String> {
+    let mut cmd = git_command(&project_path);
+    cmd.args(["sparse-checkout", "disable"]);
+    let output = cmd
+        .output()
+        .map_err(|e| format!("Failed to run git sparse-checkout: {}", e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "git sparse-checkout failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(root: &Path, path: &str, content: &str) {
+        let path = root.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, content).unwrap();
+    }
+
+    #[test]
+    fn test_detect_pnpm_and_cargo_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        write(
+            root,
+            "pnpm-workspace.yaml",
+            "packages:\n  - 'packages/*'\n  - '!packages/legacy'\n",
+        );
+        write(root, "packages/ui/package.json", r#"{"name": "@acme/ui"}"#);
+        write(
+            root,
+            "packages/legacy/package.json",
+            r#"{"name": "legacy"}"#,
+        );
+        write(root, "turbo.json", "{}");
+        write(
+            root,
+            "Cargo.toml",
+            "[workspace]\nmembers = [\"crates/*\"]\n",
+        );
+        write(
+            root,
+            "crates/core/Cargo.toml",
+            "[package]\nname = \"acme-core\"\n",
+        );
+
+        let layout = detect_layout(root);
+        assert_eq!(
+            layout.kinds,
+            vec![
+                WorkspaceKind::Pnpm,
+                WorkspaceKind::Cargo,
+                WorkspaceKind::Turbo
+            ]
+        );
+        let names: Vec<_> = layout
+            .packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.path.as_str()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![("acme-core", "crates/core"), ("@acme/ui", "packages/ui")]
+        );
+    }
+
+    #[test]
+    fn test_package_paths() {
+        assert_eq!(
+            validate_package_path("packages/ui/").unwrap(),
+            "packages/ui"
+        );
+        assert!(validate_package_path("../other").is_err());
+        assert!(validate_package_path("").is_err());
+        assert!(is_in_package("packages/ui/src/a.ts", "packages/ui"));
+        assert!(is_in_package("./packages/ui", "packages/ui"));
+        assert!(!is_in_package("packages/ui-kit/a.ts", "packages/ui"));
+    }
+}
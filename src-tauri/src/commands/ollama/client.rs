@@ -0,0 +1,500 @@
+//! HTTP client for Ollama and OpenAI-compatible chat endpoints
+//!
+//! Both protocols stream the reply: Ollama as one JSON object per line,
+//! OpenAI-compatible servers as server-sent events whose tool calls arrive in
+//! fragments keyed by index. [`StreamDecoder`] turns either stream into a
+//! [`ChatTurn`]; [`ChatMessage`] is the protocol-neutral history entry that is
+//! converted to the endpoint's wire format on every request.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use super::config::{OllamaApi, OllamaConfig};
+
+/// Timeout for connecting and for non-streaming requests
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Model offered by the endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelInfo {
+    pub id: String,
+    /// Size on disk in bytes (Ollama only)
+    pub size: Option<u64>,
+    pub modified_at: Option<String>,
+}
+
+/// Tool call requested by the model
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCall {
+    pub id: String,
+    pub name: String,
+    pub arguments: Value,
+}
+
+/// Token counts reported by the endpoint
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// One model reply
+#[derive(Debug, Clone, Default)]
+pub struct ChatTurn {
+    pub content: String,
+    pub tool_calls: Vec<ToolCall>,
+    pub usage: Usage,
+}
+
+/// Conversation history entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatMessage {
+    /// `system`, `user`, `assistant` or `tool`
+    pub role: String,
+    pub content: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ToolCall>,
+    /// For `tool` messages: the call being answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn user(content: &str) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: content.to_string(),
+            tool_calls: Vec::new(),
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+
+    pub fn assistant(turn: &ChatTurn) -> Self {
+        Self {
+            role: "assistant".to_string(),
+            content: turn.content.clone(),
+            tool_calls: turn.tool_calls.clone(),
+            tool_call_id: None,
+            tool_name: None,
+        }
+    }
+
+    pub fn tool_result(call: &ToolCall, content: &str) -> Self {
+        Self {
+            role: "tool".to_string(),
+            content: content.to_string(),
+            tool_calls: Vec::new(),
+            tool_call_id: Some(call.id.clone()),
+            tool_name: Some(call.name.clone()),
+        }
+    }
+
+    /// The message in the endpoint's request format
+    fn to_wire(&self, api: OllamaApi) -> Value {
+        let mut message = json!({ "role": self.role, "content": self.content });
+        match api {
+            OllamaApi::Ollama => {
+                if !self.tool_calls.is_empty() {
+                    message["tool_calls"] = self
+                        .tool_calls
+                        .iter()
+                        .map(|call| {
+                            json!({ "function": { "name": call.name, "arguments": call.arguments } })
+                        })
+                        .collect();
+                }
+                if let Some(name) = &self.tool_name {
+                    message["tool_name"] = json!(name);
+                }
+            }
+            OllamaApi::OpenAi => {
+                if !self.tool_calls.is_empty() {
+                    message["tool_calls"] = self
+                        .tool_calls
+                        .iter()
+                        .map(|call| {
+                            json!({
+                                "id": call.id,
+                                "type": "function",
+                                "function": {
+                                    "name": call.name,
+                                    "arguments": call.arguments.to_string()
+                                }
+                            })
+                        })
+                        .collect();
+                }
+                if let Some(id) = &self.tool_call_id {
+                    message["tool_call_id"] = json!(id);
+                }
+            }
+        }
+        message
+    }
+}
+
+/// Function definition offered to the model (same shape for both protocols)
+pub fn tool_definition(name: &str, description: Option<&str>, schema: &Value) -> Value {
+    let parameters = if schema.is_object() {
+        schema.clone()
+    } else {
+        json!({ "type": "object", "properties": {} })
+    };
+    json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description.unwrap_or_default(),
+            "parameters": parameters
+        }
+    })
+}
+
+/// Tool call whose arguments may still be arriving
+#[derive(Default)]
+struct PartialCall {
+    id: Option<String>,
+    name: String,
+    arguments: String,
+}
+
+/// Accumulates a streamed reply line by line
+pub struct StreamDecoder {
+    api: OllamaApi,
+    content: String,
+    calls: Vec<PartialCall>,
+    usage: Usage,
+}
+
+fn stream_error(value: &Value) -> Option<String> {
+    let error = value.get("error")?;
+    Some(
+        error
+            .get("message")
+            .and_then(Value::as_str)
+            .or_else(|| error.as_str())
+            .unwrap_or("unknown error")
+            .to_string(),
+    )
+}
+
+impl StreamDecoder {
+    pub fn new(api: OllamaApi) -> Self {
+        Self {
+            api,
+            content: String::new(),
+            calls: Vec::new(),
+            usage: Usage::default(),
+        }
+    }
+
+    /// Feed one line of the stream; returns the text it adds to the reply
+    pub fn feed_line(&mut self, line: &str) -> Result<Option<String>, String> {
+        let line = line.trim();
+        let payload = match self.api {
+            OllamaApi::Ollama => line,
+            OllamaApi::OpenAi => match line.strip_prefix("data:") {
+                Some(data) => data.trim(),
+                None => return Ok(None),
+            },
+        };
+        if payload.is_empty() || payload == "[DONE]" {
+            return Ok(None);
+        }
+
+        let value: Value = serde_json::from_str(payload)
+            .map_err(|e| format!("Invalid stream chunk: {} ({})", e, payload))?;
+        if let Some(error) = stream_error(&value) {
+            return Err(error);
+        }
+        let delta = match self.api {
+            OllamaApi::Ollama => self.feed_ollama(&value),
+            OllamaApi::OpenAi => self.feed_openai(&value),
+        };
+        Ok(delta.filter(|text| !text.is_empty()).map(|text| {
+            self.content.push_str(&text);
+            text
+        }))
+    }
+
+    fn feed_ollama(&mut self, value: &Value) -> Option<String> {
+        let message = &value["message"];
+        // Ollama sends each tool call complete, usually in a single chunk
+        for call in message["tool_calls"].as_array().into_iter().flatten() {
+            self.calls.push(PartialCall {
+                id: None,
+                name: call["function"]["name"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                arguments: call["function"]["arguments"].to_string(),
+            });
+        }
+        if value["done"].as_bool() == Some(true) {
+            self.usage = Usage {
+                input_tokens: value["prompt_eval_count"].as_u64().unwrap_or_default(),
+                output_tokens: value["eval_count"].as_u64().unwrap_or_default(),
+            };
+        }
+        message["content"].as_str().map(str::to_string)
+    }
+
+    fn feed_openai(&mut self, value: &Value) -> Option<String> {
+        if let Some(usage) = value.get("usage").filter(|usage| usage.is_object()) {
+            self.usage = Usage {
+                input_tokens: usage["prompt_tokens"].as_u64().unwrap_or_default(),
+                output_tokens: usage["completion_tokens"].as_u64().unwrap_or_default(),
+            };
+        }
+        let delta = &value["choices"][0]["delta"];
+        for fragment in delta["tool_calls"].as_array().into_iter().flatten() {
+            let index = fragment["index"].as_u64().unwrap_or_default() as usize;
+            if self.calls.len() <= index {
+                self.calls.resize_with(index + 1, PartialCall::default);
+            }
+            let call = &mut self.calls[index];
+            if let Some(id) = fragment["id"].as_str() {
+                call.id = Some(id.to_string());
+            }
+            if let Some(name) = fragment["function"]["name"].as_str() {
+                call.name.push_str(name);
+            }
+            if let Some(arguments) = fragment["function"]["arguments"].as_str() {
+                call.arguments.push_str(arguments);
+            }
+        }
+        delta["content"].as_str().map(str::to_string)
+    }
+
+    /// The complete reply
+    pub fn finish(self) -> ChatTurn {
+        let tool_calls = self
+            .calls
+            .into_iter()
+            .filter(|call| !call.name.is_empty())
+            .map(|call| {
+                let arguments = match call.arguments.trim() {
+                    "" | "null" => json!({}),
+                    raw => serde_json::from_str(raw).unwrap_or_else(|_| json!(raw)),
+                };
+                ToolCall {
+                    id: call
+                        .id
+                        .unwrap_or_else(|| format!("call_{}", uuid::Uuid::new_v4().simple())),
+                    name: call.name,
+                    arguments,
+                }
+            })
+            .collect();
+        ChatTurn {
+            content: self.content,
+            tool_calls,
+            usage: self.usage,
+        }
+    }
+}
+
+fn endpoint(config: &OllamaConfig, path: &str) -> String {
+    let base = config.base_url.trim().trim_end_matches('/');
+    match config.api {
+        OllamaApi::Ollama => format!("{}/api/{}", base, path),
+        OllamaApi::OpenAi if base.ends_with("/v1") => format!("{}/{}", base, path),
+        OllamaApi::OpenAi => format!("{}/v1/{}", base, path),
+    }
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .connect_timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+async fn send(
+    request: reqwest::RequestBuilder,
+    api_key: Option<&str>,
+) -> Result<reqwest::Response, String> {
+    let request = match api_key {
+        Some(key) => request.bearer_auth(key),
+        None => request,
+    };
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Failed to reach the model server: {}", e))?;
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|value| stream_error(&value))
+            .unwrap_or(body);
+        return Err(format!(
+            "Model server returned {}: {}",
+            status,
+            message.trim()
+        ));
+    }
+    Ok(response)
+}
+
+/// Models listed by `/api/tags` (Ollama) or `/v1/models` (OpenAI-compatible)
+pub fn parse_models(api: OllamaApi, value: &Value) -> Vec<ModelInfo> {
+    let mut models: Vec<ModelInfo> = match api {
+        OllamaApi::Ollama => value["models"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| {
+                Some(ModelInfo {
+                    id: model["name"].as_str()?.to_string(),
+                    size: model["size"].as_u64(),
+                    modified_at: model["modified_at"].as_str().map(str::to_string),
+                })
+            })
+            .collect(),
+        OllamaApi::OpenAi => value["data"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|model| {
+                Some(ModelInfo {
+                    id: model["id"].as_str()?.to_string(),
+                    size: None,
+                    modified_at: None,
+                })
+            })
+            .collect(),
+    };
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+/// List the models available on the endpoint
+pub async fn list_models(
+    config: &OllamaConfig,
+    api_key: Option<&str>,
+) -> Result<Vec<ModelInfo>, String> {
+    let path = match config.api {
+        OllamaApi::Ollama => "tags",
+        OllamaApi::OpenAi => "models",
+    };
+    let request = http_client()?
+        .get(endpoint(config, path))
+        .timeout(REQUEST_TIMEOUT);
+    let value: Value = send(request, api_key)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid model list: {}", e))?;
+    Ok(parse_models(config.api, &value))
+}
+
+/// Send the conversation and stream the reply, calling `on_text` for each text delta
+pub async fn chat(
+    config: &OllamaConfig,
+    api_key: Option<&str>,
+    model: &str,
+    messages: &[ChatMessage],
+    tools: &[Value],
+    mut on_text: impl FnMut(&str),
+) -> Result<ChatTurn, String> {
+    let mut body = json!({
+        "model": model,
+        "messages": messages.iter().map(|m| m.to_wire(config.api)).collect::<Vec<_>>(),
+        "stream": true
+    });
+    if !tools.is_empty() {
+        body["tools"] = json!(tools);
+    }
+    let path = match config.api {
+        OllamaApi::Ollama => "chat",
+        OllamaApi::OpenAi => {
+            body["stream_options"] = json!({ "include_usage": true });
+            "chat/completions"
+        }
+    };
+
+    let request = http_client()?.post(endpoint(config, path)).json(&body);
+    let mut response = send(request, api_key).await?;
+
+    let mut decoder = StreamDecoder::new(config.api);
+    let mut buffer: Vec<u8> = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| format!("Model stream interrupted: {}", e))?
+    {
+        buffer.extend_from_slice(&chunk);
+        // Only complete lines are decoded, so multi-byte characters are never split
+        while let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
+            let line: Vec<u8> = buffer.drain(..=pos).collect();
+            if let Some(text) = decoder.feed_line(&String::from_utf8_lossy(&line))? {
+                on_text(&text);
+            }
+        }
+    }
+    if let Some(text) = decoder.feed_line(&String::from_utf8_lossy(&buffer))? {
+        on_text(&text);
+    }
+    Ok(decoder.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_decoding() {
+        let mut ollama = StreamDecoder::new(OllamaApi::Ollama);
+        for line in [
+            r#"{"message":{"role":"assistant","content":"Let me "},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":"look","tool_calls":[{"function":{"name":"mcp__fs__read","arguments":{"path":"a.txt"}}}]},"done":false}"#,
+            r#"{"message":{"role":"assistant","content":""},"done":true,"prompt_eval_count":12,"eval_count":5}"#,
+        ] {
+            ollama.feed_line(line).unwrap();
+        }
+        let turn = ollama.finish();
+        assert_eq!(turn.content, "Let me look");
+        assert_eq!(turn.tool_calls.len(), 1);
+        assert_eq!(turn.tool_calls[0].name, "mcp__fs__read");
+        assert_eq!(turn.tool_calls[0].arguments, json!({ "path": "a.txt" }));
+        assert_eq!(turn.usage.input_tokens, 12);
+
+        let mut openai = StreamDecoder::new(OllamaApi::OpenAi);
+        for line in [
+            ": keep-alive",
+            r#"data: {"choices":[{"delta":{"content":"Hi"}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"id":"call_1","function":{"name":"search","arguments":"{\"q\":"}}]}}]}"#,
+            r#"data: {"choices":[{"delta":{"tool_calls":[{"index":0,"function":{"arguments":"\"rust\"}"}}]}}]}"#,
+            r#"data: {"choices":[],"usage":{"prompt_tokens":7,"completion_tokens":3}}"#,
+            "data: [DONE]",
+        ] {
+            openai.feed_line(line).unwrap();
+        }
+        let turn = openai.finish();
+        assert_eq!(turn.content, "Hi");
+        assert_eq!(turn.tool_calls[0].id, "call_1");
+        assert_eq!(turn.tool_calls[0].arguments, json!({ "q": "rust" }));
+        assert_eq!(turn.usage.output_tokens, 3);
+
+        let wire = ChatMessage::tool_result(&turn.tool_calls[0], "ok").to_wire(OllamaApi::OpenAi);
+        assert_eq!(wire["tool_call_id"], json!("call_1"));
+        let wire = ChatMessage::assistant(&turn).to_wire(OllamaApi::OpenAi);
+        assert_eq!(
+            wire["tool_calls"][0]["function"]["arguments"],
+            json!(r#"{"q":"rust"}"#)
+        );
+
+        assert!(StreamDecoder::new(OllamaApi::Ollama)
+            .feed_line(r#"{"error":"model not found"}"#)
+            .is_err());
+    }
+}
@@ -0,0 +1,131 @@
+//! Ollama engine settings
+//!
+//! Stored in `~/.anycode/ollama.json`; the optional API key (for
+//! OpenAI-compatible servers that require one) lives in the keyring.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use super::client::{self, ModelInfo};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// Keyring entry of the endpoint API key
+const API_KEY_SECRET: &str = "ollama-api-key";
+
+/// Protocol spoken by the endpoint
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OllamaApi {
+    /// Ollama's native `/api/chat`
+    Ollama,
+    /// OpenAI-compatible `/v1/chat/completions` (LM Studio, llama.cpp, vLLM, ...)
+    OpenAi,
+}
+
+/// Ollama engine configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaConfig {
+    #[serde(default = "default_base_url")]
+    pub base_url: String,
+    #[serde(default = "default_api")]
+    pub api: OllamaApi,
+    /// Model used when a request does not name one
+    pub default_model: Option<String>,
+    /// Maximum model/tool round trips per prompt
+    #[serde(default = "default_max_tool_rounds")]
+    pub max_tool_rounds: u32,
+}
+
+fn default_base_url() -> String {
+    "http://localhost:11434".to_string()
+}
+
+fn default_api() -> OllamaApi {
+    OllamaApi::Ollama
+}
+
+fn default_max_tool_rounds() -> u32 {
+    8
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            base_url: default_base_url(),
+            api: default_api(),
+            default_model: None,
+            max_tool_rounds: default_max_tool_rounds(),
+        }
+    }
+}
+
+/// Configuration plus whether an API key is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OllamaSettings {
+    #[serde(flatten)]
+    pub config: OllamaConfig,
+    pub has_api_key: bool,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("ollama.json"))
+}
+
+pub fn load_config() -> OllamaConfig {
+    config_path().and_then(load_json_config).unwrap_or_default()
+}
+
+pub fn api_key() -> Option<String> {
+    keyring_store::get_secret(API_KEY_SECRET).ok().flatten()
+}
+
+/// Model to use: the requested one, else the configured default
+pub fn resolve_model(config: &OllamaConfig, model: Option<&str>) -> Result<String, String> {
+    model
+        .or(config.default_model.as_deref())
+        .map(str::trim)
+        .filter(|model| !model.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| "No model selected; choose one in the engine settings".to_string())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the Ollama configuration
+#[tauri::command]
+pub async fn get_ollama_settings() -> Result<OllamaSettings, String> {
+    Ok(OllamaSettings {
+        config: load_config(),
+        has_api_key: api_key().is_some(),
+    })
+}
+
+/// Update the configuration; `api_key` stores (or, when empty, removes) the API key
+#[tauri::command]
+pub async fn update_ollama_settings(
+    config: OllamaConfig,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    reqwest::Url::parse(&config.base_url)
+        .map_err(|e| format!("Invalid base URL '{}': {}", config.base_url, e))?;
+    if let Some(key) = api_key {
+        let key = key.trim();
+        if key.is_empty() {
+            keyring_store::delete_secret(API_KEY_SECRET)?;
+        } else {
+            keyring_store::set_secret(API_KEY_SECRET, key)?;
+        }
+    }
+    save_json_config(&config, config_path()?)
+}
+
+/// List the models available on the configured endpoint
+#[tauri::command]
+pub async fn list_ollama_models() -> Result<Vec<ModelInfo>, String> {
+    client::list_models(&load_config(), api_key().as_deref()).await
+}
@@ -0,0 +1,23 @@
+//! Ollama / local model integration
+//!
+//! Runs prompts against a local Ollama server or any OpenAI-compatible
+//! endpoint (LM Studio, llama.cpp, vLLM), so the workbench can be used fully
+//! offline.
+//!
+//! ## Features
+//!
+//! - **Model Listing**: `/api/tags` or `/v1/models`
+//! - **Streaming Chat**: Replies are streamed as ClaudeStreamMessage lines
+//! - **Tool Calls**: MCP tools are offered to the model as functions and
+//!   executed by the workbench
+//! - **Sessions**: History is stored locally and can be resumed
+
+pub mod client;
+pub mod config;
+pub mod session;
+
+// Re-export Tauri commands
+pub use config::{get_ollama_settings, list_ollama_models, update_ollama_settings};
+pub use session::{
+    cancel_ollama, delete_ollama_session, execute_ollama, get_ollama_session, list_ollama_sessions,
+};
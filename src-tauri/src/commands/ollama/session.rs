@@ -0,0 +1,696 @@
+//! Chat sessions driven by the workbench
+//!
+//! Used by the engines that only offer a chat endpoint (Ollama, OpenRouter).
+//! The workbench drives the conversation itself: each prompt is streamed from
+//! the model, the tool calls it makes are executed against the MCP servers in
+//! `~/.anycode/<engine>-mcp.json` (started in the project directory), and the
+//! results are sent back until the model answers without calling a tool. Each
+//! call first goes through the project's tool approval policy (see
+//! [`crate::commands::engine_safety`]). Output is emitted on
+//! `<engine>-output` in the unified ClaudeStreamMessage format. History is kept
+//! in `~/.anycode/<engine>/sessions/<id>.json` so sessions can be resumed.
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter};
+use tokio::task::AbortHandle;
+
+use super::client::{self, ChatMessage, ToolCall, Usage};
+use super::config::{self, OllamaConfig};
+use crate::commands::engine_safety::{self, ChatToolApprovalPolicy, ToolApprovalRequest};
+use crate::commands::event_batcher;
+use crate::mcp::health::{McpClient, ToolCallResult};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Length of the session title derived from the first prompt
+const TITLE_CHARS: usize = 80;
+
+lazy_static::lazy_static! {
+    /// Running sessions by id, with the engine running them
+    static ref RUNNING: Mutex<HashMap<String, (&'static str, AbortHandle)>> =
+        Mutex::new(HashMap::new());
+}
+
+/// A chat endpoint and the engine it belongs to
+pub struct ChatEngine {
+    /// Engine id; names the events, the session directory and the MCP config
+    pub id: &'static str,
+    pub config: OllamaConfig,
+    pub api_key: Option<String>,
+}
+
+/// Options for starting a prompt
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatExecutionOptions {
+    pub project_path: String,
+    pub prompt: String,
+    pub model: Option<String>,
+    /// Resume this session instead of starting a new one
+    pub session_id: Option<String>,
+}
+
+/// Stored conversation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatSession {
+    pub id: String,
+    pub project_path: String,
+    pub model: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub messages: Vec<ChatMessage>,
+}
+
+/// Session list entry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatSessionSummary {
+    pub id: String,
+    pub model: String,
+    /// Start of the first prompt
+    pub title: String,
+    pub updated_at: i64,
+    pub message_count: usize,
+}
+
+fn sessions_dir(engine: &str) -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?
+        .build(engine)
+        .join("sessions"))
+}
+
+fn session_path(engine: &str, session_id: &str) -> Result<PathBuf, String> {
+    // Session ids are UUIDs; anything else could escape the sessions directory
+    uuid::Uuid::parse_str(session_id).map_err(|_| format!("Invalid session id: {}", session_id))?;
+    Ok(sessions_dir(engine)?.join(format!("{}.json", session_id)))
+}
+
+/// Load a stored session with its full history
+pub fn load_session(engine: &str, session_id: &str) -> Result<ChatSession, String> {
+    let path = session_path(engine, session_id)?;
+    if !path.exists() {
+        return Err(format!("{} session not found: {}", engine, session_id));
+    }
+    load_json_config(path)
+}
+
+fn save_session(engine: &str, session: &ChatSession) -> Result<(), String> {
+    save_json_config(session, session_path(engine, &session.id)?)
+}
+
+// ============================================================================
+// MCP Tools
+// ============================================================================
+
+/// Function name offered to the model, in the `mcp__<server>__<tool>` form Claude uses
+fn function_name(server: &str, tool: &str) -> String {
+    let server: String = server
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    format!("mcp__{}__{}", server, tool)
+}
+
+/// Connections to the configured MCP servers and the functions they provide
+struct McpTools {
+    clients: Vec<McpClient>,
+    /// Function name -> (client index, tool name)
+    routes: HashMap<String, (usize, String)>,
+    definitions: Vec<Value>,
+}
+
+impl McpTools {
+    /// Connect to every configured server; servers that fail are skipped
+    ///
+    /// Stdio servers without their own `cwd` are started in the project, so
+    /// relative paths and the shared server they attach to are the project's.
+    async fn connect(engine: &str, project_path: &str) -> Self {
+        let servers = crate::chat_mcp::read_mcp_servers_map(engine).unwrap_or_else(|e| {
+            log::warn!("[{}] Failed to read MCP config: {}", engine, e);
+            HashMap::new()
+        });
+        let connections = join_all(servers.into_iter().map(|(id, mut spec)| async move {
+            if spec.get("command").is_some() && spec.get("cwd").is_none() {
+                spec["cwd"] = json!(project_path);
+            }
+            let client = McpClient::connect(&spec).await;
+            (id, spec, client)
+        }))
+        .await;
+
+        let mut tools = Self {
+            clients: Vec::new(),
+            routes: HashMap::new(),
+            definitions: Vec::new(),
+        };
+        for (id, spec, client) in connections {
+            let client = match client {
+                Ok(client) => client,
+                Err(e) => {
+                    log::warn!("[{}] MCP server '{}' unavailable: {}", engine, id, e);
+                    continue;
+                }
+            };
+            let disabled: Vec<&str> = spec["disabledTools"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+                .collect();
+            for tool in client
+                .tools
+                .iter()
+                .filter(|tool| !disabled.contains(&tool.name.as_str()))
+            {
+                let name = function_name(&id, &tool.name);
+                tools.definitions.push(client::tool_definition(
+                    &name,
+                    tool.description.as_deref(),
+                    &tool.input_schema,
+                ));
+                tools
+                    .routes
+                    .insert(name, (tools.clients.len(), tool.name.clone()));
+            }
+            tools.clients.push(client);
+        }
+        tools
+    }
+
+    fn provides(&self, name: &str) -> bool {
+        self.routes.contains_key(name)
+    }
+
+    async fn call(&mut self, call: &ToolCall) -> ToolCallResult {
+        let Some((index, tool)) = self.routes.get(&call.name) else {
+            return ToolCallResult {
+                content: format!("Unknown tool: {}", call.name),
+                is_error: true,
+            };
+        };
+        self.clients[*index]
+            .call_tool(tool, call.arguments.clone())
+            .await
+            .unwrap_or_else(|e| ToolCallResult {
+                content: e,
+                is_error: true,
+            })
+    }
+
+    async fn close(self) {
+        join_all(self.clients.into_iter().map(McpClient::close)).await;
+    }
+}
+
+// ============================================================================
+// Output
+// ============================================================================
+
+/// Emits unified stream lines for one session
+struct Output {
+    app: AppHandle,
+    engine: &'static str,
+    session_id: String,
+}
+
+impl Output {
+    /// Metadata key tagging the lines, e.g. `ollamaMetadata`
+    fn metadata_key(&self) -> String {
+        format!("{}Metadata", self.engine)
+    }
+
+    fn emit(&self, event_type: &str, mut payload: Value) {
+        let key = self.metadata_key();
+        payload[&key]["provider"] = json!(self.engine);
+        payload[&key]["eventType"] = json!(event_type);
+        let line = payload.to_string();
+        let channel = format!("{}-output", self.engine);
+        let _ = event_batcher::emit(
+            &self.app,
+            &format!("{}:{}", channel, self.session_id),
+            &line,
+        );
+        let _ = event_batcher::emit(&self.app, &channel, &line);
+    }
+
+    fn text(&self, text: &str) {
+        let mut payload = json!({
+            "type": "assistant",
+            "message": { "content": [{ "type": "text", "text": text }], "role": "assistant" }
+        });
+        payload[self.metadata_key()]["delta"] = json!(true);
+        self.emit("message", payload);
+    }
+
+    fn tool_use(&self, call: &ToolCall) {
+        self.emit(
+            "tool_use",
+            json!({
+                "type": "assistant",
+                "message": {
+                    "content": [{
+                        "type": "tool_use",
+                        "id": call.id,
+                        "name": call.name,
+                        "input": call.arguments
+                    }],
+                    "role": "assistant"
+                }
+            }),
+        );
+    }
+
+    fn tool_result(&self, call: &ToolCall, result: &ToolCallResult) {
+        self.emit(
+            "tool_result",
+            json!({
+                "type": "user",
+                "message": {
+                    "content": [{
+                        "type": "tool_result",
+                        "tool_use_id": call.id,
+                        "content": result.content,
+                        "is_error": result.is_error
+                    }],
+                    "role": "user"
+                }
+            }),
+        );
+    }
+}
+
+// ============================================================================
+// Execution
+// ============================================================================
+
+fn system_message(project_path: &str) -> ChatMessage {
+    ChatMessage {
+        role: "system".to_string(),
+        content: format!(
+            "You are a coding assistant working in the project at {}. \
+             Use the available tools to inspect or change files when needed.",
+            project_path
+        ),
+        tool_calls: Vec::new(),
+        tool_call_id: None,
+        tool_name: None,
+    }
+}
+
+/// Run a tool call if the project's approval policy allows it
+async fn run_tool_call(
+    out: &Output,
+    tools: &mut McpTools,
+    policy: ChatToolApprovalPolicy,
+    call: &ToolCall,
+) -> ToolCallResult {
+    if tools.provides(&call.name) {
+        let request = ToolApprovalRequest {
+            request_id: uuid::Uuid::new_v4().to_string(),
+            session_id: out.session_id.clone(),
+            tool_use_id: call.id.clone(),
+            tool: call.name.clone(),
+            arguments: call.arguments.clone(),
+        };
+        if !engine_safety::approve_tool_call(&out.app, out.engine, policy, request).await {
+            log::info!("[{}] Tool call {} denied", out.engine, call.name);
+            return ToolCallResult {
+                content: format!("The user denied the call to {}", call.name),
+                is_error: true,
+            };
+        }
+    }
+    tools.call(call).await
+}
+
+/// Alternate model replies and tool calls until the model stops calling tools
+async fn run_turns(
+    out: &Output,
+    session: &mut ChatSession,
+    config: &OllamaConfig,
+    api_key: Option<&str>,
+    tools: &mut McpTools,
+) -> Result<Usage, String> {
+    let policy = engine_safety::chat_tool_approval(&session.project_path);
+    let mut usage = Usage::default();
+    for _ in 0..config.max_tool_rounds.max(1) {
+        let messages: Vec<ChatMessage> = std::iter::once(system_message(&session.project_path))
+            .chain(session.messages.iter().cloned())
+            .collect();
+        let turn = client::chat(
+            config,
+            api_key,
+            &session.model,
+            &messages,
+            &tools.definitions,
+            |text| out.text(text),
+        )
+        .await?;
+        usage.input_tokens += turn.usage.input_tokens;
+        usage.output_tokens += turn.usage.output_tokens;
+        session.messages.push(ChatMessage::assistant(&turn));
+        if turn.tool_calls.is_empty() {
+            return Ok(usage);
+        }
+
+        for call in &turn.tool_calls {
+            out.tool_use(call);
+            let result = run_tool_call(out, tools, policy, call).await;
+            out.tool_result(call, &result);
+            session
+                .messages
+                .push(ChatMessage::tool_result(call, &result.content));
+        }
+        // Keep completed rounds if the session is cancelled
+        session.updated_at = chrono::Utc::now().timestamp();
+        if let Err(e) = save_session(out.engine, session) {
+            log::warn!(
+                "[{}] Failed to save session {}: {}",
+                out.engine,
+                session.id,
+                e
+            );
+        }
+    }
+    Err(format!(
+        "Stopped after {} tool rounds without a final answer",
+        config.max_tool_rounds
+    ))
+}
+
+async fn run_session(out: Output, mut session: ChatSession, prompt: String, engine: ChatEngine) {
+    session.messages.push(ChatMessage::user(&prompt));
+    let mut tools = McpTools::connect(engine.id, &session.project_path).await;
+    let outcome = run_turns(
+        &out,
+        &mut session,
+        &engine.config,
+        engine.api_key.as_deref(),
+        &mut tools,
+    )
+    .await;
+    tools.close().await;
+
+    session.updated_at = chrono::Utc::now().timestamp();
+    if let Err(e) = save_session(engine.id, &session) {
+        log::warn!(
+            "[{}] Failed to save session {}: {}",
+            engine.id,
+            session.id,
+            e
+        );
+    }
+
+    let success = outcome.is_ok();
+    let usage = match outcome {
+        Ok(usage) => usage,
+        Err(e) => {
+            log::error!("[{}] Session {} failed: {}", engine.id, session.id, e);
+            out.emit(
+                "error",
+                json!({ "type": "system", "subtype": "error", "error": { "message": e } }),
+            );
+            Usage::default()
+        }
+    };
+    out.emit(
+        "result",
+        json!({
+            "type": "result",
+            "subtype": if success { "success" } else { "error" },
+            "status": if success { "success" } else { "error" },
+            "session_id": session.id,
+            "usage": usage
+        }),
+    );
+
+    if let Ok(mut running) = RUNNING.lock() {
+        running.remove(&session.id);
+    }
+    event_batcher::flush_all(&out.app);
+    let _ = out
+        .app
+        .emit(&format!("{}-complete:{}", engine.id, session.id), success);
+    let _ = out.app.emit(&format!("{}-complete", engine.id), success);
+}
+
+/// Send a prompt, streaming the reply on `<engine>-output`
+///
+/// Resumes `options.session_id` when given; `options.model` switches the
+/// session to another model from this prompt on.
+pub fn start(
+    engine: ChatEngine,
+    options: ChatExecutionOptions,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    log::info!(
+        "[{}] start: project_path={}, model={:?}, session_id_present={}, prompt_len={}",
+        engine.id,
+        options.project_path,
+        options.model,
+        options.session_id.is_some(),
+        options.prompt.len()
+    );
+
+    if crate::engines::require(engine.id)?.requires_network() {
+        crate::commands::connectivity::ensure_online("Starting a chat session")?;
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let mut session = match &options.session_id {
+        Some(id) => load_session(engine.id, id)?,
+        None => ChatSession {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_path: options.project_path.clone(),
+            created_at: now,
+            updated_at: now,
+            ..Default::default()
+        },
+    };
+    session.model = config::resolve_model(
+        &engine.config,
+        options
+            .model
+            .as_deref()
+            .or(Some(session.model.as_str()).filter(|m| !m.is_empty())),
+    )?;
+
+    crate::commands::project_defaults::record_last_used(
+        &options.project_path,
+        engine.id,
+        crate::commands::project_defaults::EngineDefaults {
+            model: Some(session.model.clone()),
+            reasoning_effort: None,
+            permission_mode: None,
+        },
+    );
+
+    let mut running = RUNNING.lock().map_err(|e| e.to_string())?;
+    if running.contains_key(&session.id) {
+        return Err(format!("Session {} is already running", session.id));
+    }
+
+    let out = Output {
+        app: app_handle,
+        engine: engine.id,
+        session_id: session.id.clone(),
+    };
+    out.emit(
+        "session_init",
+        json!({
+            "type": "system",
+            "subtype": "init",
+            "session_id": session.id,
+            "model": session.model,
+            "project_path": session.project_path
+        }),
+    );
+
+    engine_safety::record_session(
+        engine.id,
+        &session.id,
+        &session.project_path,
+        vec![
+            "--tool-approval".to_string(),
+            engine_safety::chat_tool_approval(&session.project_path)
+                .as_str()
+                .to_string(),
+        ],
+    );
+
+    let (engine_id, session_id) = (engine.id, session.id.clone());
+    let task = tokio::spawn(run_session(out, session, options.prompt, engine));
+    running.insert(session_id, (engine_id, task.abort_handle()));
+    Ok(())
+}
+
+/// Cancel a running session of the engine (all of its sessions when `session_id` is None)
+pub fn cancel(
+    engine: &str,
+    session_id: Option<String>,
+    app_handle: &AppHandle,
+) -> Result<(), String> {
+    log::info!("[{}] cancel called for session: {:?}", engine, session_id);
+
+    let cancelled: Vec<(String, AbortHandle)> = {
+        let mut running = RUNNING.lock().map_err(|e| e.to_string())?;
+        let ids: Vec<String> = match session_id {
+            Some(sid) => vec![sid],
+            None => running
+                .iter()
+                .filter(|(_, (owner, _))| *owner == engine)
+                .map(|(sid, _)| sid.clone())
+                .collect(),
+        };
+        ids.into_iter()
+            .filter_map(|sid| {
+                let (_, handle) = running.remove(&sid)?;
+                Some((sid, handle))
+            })
+            .collect()
+    };
+    // Aborting drops the MCP connections, which stops their server processes
+    for (sid, handle) in &cancelled {
+        handle.abort();
+        log::info!("[{}] Cancelled session: {}", engine, sid);
+    }
+
+    event_batcher::flush_all(app_handle);
+    for (sid, _) in &cancelled {
+        let _ = app_handle.emit(&format!("{}-cancelled:{}", engine, sid), true);
+    }
+    let _ = app_handle.emit(&format!("{}-cancelled", engine), true);
+    Ok(())
+}
+
+/// Engine running the given session, if it is running
+pub fn running_engine(session_id: &str) -> Option<&'static str> {
+    let running = RUNNING.lock().ok()?;
+    running.get(session_id).map(|(engine, _)| *engine)
+}
+
+/// The stored sessions of a project, most recent first
+pub fn list_sessions(engine: &str, project_path: &str) -> Result<Vec<ChatSessionSummary>, String> {
+    let dir = sessions_dir(engine)?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries = std::fs::read_dir(&dir).map_err(|e| format!("Failed to read sessions: {}", e))?;
+    let mut sessions: Vec<ChatSessionSummary> = entries
+        .flatten()
+        .filter_map(|entry| load_json_config::<ChatSession>(entry.path()).ok())
+        .filter(|session| session.project_path == project_path)
+        .map(|session| ChatSessionSummary {
+            title: session
+                .messages
+                .iter()
+                .find(|m| m.role == "user")
+                .map(|m| m.content.chars().take(TITLE_CHARS).collect())
+                .unwrap_or_default(),
+            message_count: session.messages.len(),
+            id: session.id,
+            model: session.model,
+            updated_at: session.updated_at,
+        })
+        .collect();
+    sessions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(sessions)
+}
+
+/// Switch a stored session to another model for its next prompts
+pub fn set_session_model(engine: &str, session_id: &str, model: &str) -> Result<(), String> {
+    let model = model.trim();
+    if model.is_empty() {
+        return Err("Model must not be empty".to_string());
+    }
+    let mut session = load_session(engine, session_id)?;
+    session.model = model.to_string();
+    session.updated_at = chrono::Utc::now().timestamp();
+    save_session(engine, &session)
+}
+
+/// Delete a stored session
+pub fn delete_session(engine: &str, session_id: &str) -> Result<(), String> {
+    let path = session_path(engine, session_id)?;
+    if path.exists() {
+        std::fs::remove_file(&path).map_err(|e| format!("Failed to delete session: {}", e))?;
+    }
+    crate::commands::session_titles::remove_title(engine, session_id);
+    crate::commands::session_bookmarks::remove_session_bookmarks(engine, session_id);
+    Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+fn ollama() -> ChatEngine {
+    ChatEngine {
+        id: "ollama",
+        config: config::load_config(),
+        api_key: config::api_key(),
+    }
+}
+
+/// Send a prompt to the local model, streaming the reply on `ollama-output`
+#[tauri::command]
+pub async fn execute_ollama(
+    options: ChatExecutionOptions,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    start(ollama(), options, app_handle)
+}
+
+/// Cancel a running Ollama session (all sessions when `session_id` is None)
+#[tauri::command]
+pub async fn cancel_ollama(
+    session_id: Option<String>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    cancel("ollama", session_id, &app_handle)
+}
+
+/// List the stored sessions of a project, most recent first
+#[tauri::command]
+pub async fn list_ollama_sessions(project_path: String) -> Result<Vec<ChatSessionSummary>, String> {
+    list_sessions("ollama", &project_path)
+}
+
+/// Load a stored session with its full history
+#[tauri::command]
+pub async fn get_ollama_session(session_id: String) -> Result<ChatSession, String> {
+    load_session("ollama", &session_id)
+}
+
+/// Delete a stored session
+#[tauri::command]
+pub async fn delete_ollama_session(session_id: String) -> Result<(), String> {
+    delete_session("ollama", &session_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_function_name() {
+        assert_eq!(function_name("github", "search"), "mcp__github__search");
+        assert_eq!(
+            function_name("@scope/fs server", "read"),
+            "mcp___scope_fs_server__read"
+        );
+        assert!(session_path("ollama", "../../etc/passwd").is_err());
+    }
+}
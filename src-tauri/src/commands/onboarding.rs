@@ -0,0 +1,360 @@
+//! First-run onboarding environment checks
+//!
+//! `run_onboarding_checks` verifies the tools and permissions the workbench
+//! depends on and returns a checklist the setup wizard renders, with a fix-it
+//! action attached to every failing item.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::process::Command;
+use tauri::{AppHandle, Manager};
+
+use crate::engines::EngineCli;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+/// Outcome of a single check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Pass,
+    /// Optional or degraded, the app still works
+    Warn,
+    /// Required and missing
+    Fail,
+}
+
+/// Action the wizard can offer to fix a failing check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub enum FixAction {
+    /// Open a download or documentation page
+    OpenUrl { label: String, url: String },
+    /// Show a command the user should run in a terminal
+    RunCommand { label: String, command: String },
+    /// Navigate to a settings page inside the app
+    OpenSettings { label: String, section: String },
+}
+
+/// A single checklist item
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingCheck {
+    pub id: String,
+    pub title: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    pub version: Option<String>,
+    pub fix: Option<FixAction>,
+}
+
+/// Full onboarding checklist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OnboardingReport {
+    pub checks: Vec<OnboardingCheck>,
+    /// True when no check failed
+    pub ready: bool,
+}
+
+impl OnboardingCheck {
+    pub(crate) fn new(
+        id: &str,
+        title: &str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: id.to_string(),
+            title: title.to_string(),
+            status,
+            detail: detail.into(),
+            version: None,
+            fix: None,
+        }
+    }
+
+    pub(crate) fn with_version(mut self, version: Option<String>) -> Self {
+        self.version = version;
+        self
+    }
+
+    pub(crate) fn with_fix(mut self, fix: FixAction) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Run `<program> --version` and return the first line of output
+pub(crate) fn probe_tool_version(program: &str) -> Option<String> {
+    let mut cmd = Command::new(program);
+    cmd.arg("--version");
+
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+
+    let output = cmd.output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .next()
+        .map(|line| line.trim().to_string())
+        .filter(|line| !line.is_empty())
+}
+
+fn check_tool(
+    id: &str,
+    title: &str,
+    program: &str,
+    required: bool,
+    install_url: &str,
+) -> OnboardingCheck {
+    match probe_tool_version(program) {
+        Some(version) => OnboardingCheck::new(
+            id,
+            title,
+            CheckStatus::Pass,
+            format!("{} is available", program),
+        )
+        .with_version(Some(version)),
+        None => {
+            let status = if required {
+                CheckStatus::Fail
+            } else {
+                CheckStatus::Warn
+            };
+            OnboardingCheck::new(
+                id,
+                title,
+                status,
+                format!("{} was not found on PATH", program),
+            )
+            .with_fix(FixAction::OpenUrl {
+                label: format!("Install {}", program),
+                url: install_url.to_string(),
+            })
+        }
+    }
+}
+
+/// Verify a directory can be created and written to
+fn check_writable(id: &str, title: &str, dir: &Path) -> OnboardingCheck {
+    let probe = dir.join(".anycode-write-test");
+    let result = std::fs::create_dir_all(dir)
+        .and_then(|_| std::fs::write(&probe, b"ok"))
+        .and_then(|_| std::fs::remove_file(&probe));
+
+    match result {
+        Ok(_) => OnboardingCheck::new(id, title, CheckStatus::Pass, dir.display().to_string()),
+        Err(e) => OnboardingCheck::new(
+            id,
+            title,
+            CheckStatus::Fail,
+            format!("Cannot write to {}: {}", dir.display(), e),
+        )
+        .with_fix(FixAction::RunCommand {
+            label: "Fix directory permissions".to_string(),
+            command: if cfg!(windows) {
+                format!("icacls \"{}\" /grant %USERNAME%:F /T", dir.display())
+            } else {
+                format!("chmod -R u+rw \"{}\"", dir.display())
+            },
+        }),
+    }
+}
+
+fn env_is_set(key: &str) -> bool {
+    std::env::var(key)
+        .map(|v| !v.trim().is_empty())
+        .unwrap_or(false)
+}
+
+fn file_contains(path: &Path, needle: &str) -> bool {
+    std::fs::read_to_string(path)
+        .map(|content| content.contains(needle))
+        .unwrap_or(false)
+}
+
+/// Best-effort detection of whether an engine CLI has credentials
+fn engine_logged_in(engine: &str) -> bool {
+    let Some(home) = dirs::home_dir() else {
+        return false;
+    };
+
+    match engine {
+        "claude" => {
+            env_is_set("ANTHROPIC_API_KEY")
+                || env_is_set("ANTHROPIC_AUTH_TOKEN")
+                || home.join(".claude").join(".credentials.json").exists()
+                // macOS keeps the token in the keychain, the account marker stays in ~/.claude.json
+                || file_contains(&home.join(".claude.json"), "\"oauthAccount\"")
+                // Third-party provider configured through the provider switcher
+                || file_contains(&home.join(".claude").join("settings.json"), "ANTHROPIC_AUTH_TOKEN")
+                || file_contains(&home.join(".claude").join("settings.json"), "ANTHROPIC_API_KEY")
+        }
+        "codex" => env_is_set("OPENAI_API_KEY") || home.join(".codex").join("auth.json").exists(),
+        "gemini" => {
+            env_is_set("GEMINI_API_KEY")
+                || env_is_set("GOOGLE_API_KEY")
+                || home.join(".gemini").join("oauth_creds.json").exists()
+                || file_contains(&home.join(".gemini").join(".env"), "GEMINI_API_KEY")
+        }
+        _ => false,
+    }
+}
+
+/// CLI of a registered engine
+fn engine_cli(engine: &str) -> Option<EngineCli> {
+    crate::engines::get(engine).and_then(|provider| provider.cli())
+}
+
+pub(crate) fn login_check(engine: &str, title: &str) -> OnboardingCheck {
+    let id = format!("{}-login", engine);
+    if engine_logged_in(engine) {
+        return OnboardingCheck::new(&id, title, CheckStatus::Pass, "Credentials found");
+    }
+
+    let check = OnboardingCheck::new(
+        &id,
+        title,
+        CheckStatus::Warn,
+        "No credentials detected (WSL installs are not inspected)",
+    );
+    match engine_cli(engine) {
+        Some(cli) => check.with_fix(FixAction::RunCommand {
+            label: "Log in".to_string(),
+            command: cli.login_command.to_string(),
+        }),
+        None => check,
+    }
+}
+
+pub(crate) fn engine_install_check(
+    engine: &str,
+    title: &str,
+    installed: bool,
+    version: Option<String>,
+    detail: String,
+) -> OnboardingCheck {
+    let id = format!("{}-installed", engine);
+    if installed {
+        return OnboardingCheck::new(&id, title, CheckStatus::Pass, detail).with_version(version);
+    }
+
+    // Each engine is optional on its own, the wizard decides if at least one is required
+    let check = OnboardingCheck::new(&id, title, CheckStatus::Warn, detail);
+    match engine_cli(engine) {
+        Some(cli) => check.with_fix(FixAction::RunCommand {
+            label: "Install".to_string(),
+            command: cli.install_command.to_string(),
+        }),
+        None => check,
+    }
+}
+
+/// Run all onboarding checks
+#[tauri::command]
+pub async fn run_onboarding_checks(app: AppHandle) -> Result<OnboardingReport, String> {
+    log::info!("[Onboarding] Running environment checks");
+
+    let mut checks = tokio::task::spawn_blocking(|| {
+        vec![
+            check_tool("git", "Git", "git", true, "https://git-scm.com/downloads"),
+            check_tool("node", "Node.js", "node", false, "https://nodejs.org/"),
+            check_tool(
+                "uv",
+                "uv (Python MCP servers)",
+                "uv",
+                false,
+                "https://docs.astral.sh/uv/",
+            ),
+        ]
+    })
+    .await
+    .map_err(|e| format!("Tool check task failed: {}", e))?;
+
+    // Engine CLIs
+    let claude = super::claude::check_claude_version(app.clone()).await?;
+    checks.push(engine_install_check(
+        "claude",
+        "Claude Code CLI",
+        claude.is_installed,
+        claude.version,
+        claude.output.lines().next().unwrap_or_default().to_string(),
+    ));
+    checks.push(login_check("claude", "Claude login"));
+
+    let codex = super::codex::check_codex_availability().await?;
+    checks.push(engine_install_check(
+        "codex",
+        "Codex CLI",
+        codex.available,
+        codex.version,
+        codex
+            .error
+            .unwrap_or_else(|| "Codex CLI is available".to_string()),
+    ));
+    checks.push(login_check("codex", "Codex login"));
+
+    let gemini = super::gemini::check_gemini_installed().await?;
+    checks.push(engine_install_check(
+        "gemini",
+        "Gemini CLI",
+        gemini.installed,
+        gemini.version,
+        gemini
+            .error
+            .or(gemini.path)
+            .unwrap_or_else(|| "Gemini CLI is available".to_string()),
+    ));
+    checks.push(login_check("gemini", "Gemini login"));
+
+    if !checks
+        .iter()
+        .any(|c| c.id.ends_with("-installed") && c.status == CheckStatus::Pass)
+    {
+        checks.push(
+            OnboardingCheck::new(
+                "any-engine",
+                "At least one engine",
+                CheckStatus::Fail,
+                "No engine CLI is installed",
+            )
+            .with_fix(FixAction::OpenSettings {
+                label: "Configure engine paths".to_string(),
+                section: "engines".to_string(),
+            }),
+        );
+    }
+
+    // Data directories
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    checks.push(check_writable(
+        "data-dir",
+        "App data directory",
+        &app_data_dir,
+    ));
+    if let Some(home) = dirs::home_dir() {
+        checks.push(check_writable(
+            "anycode-dir",
+            "Workbench config directory",
+            &home.join(".anycode"),
+        ));
+    }
+
+    let ready = checks.iter().all(|c| c.status != CheckStatus::Fail);
+    log::info!(
+        "[Onboarding] {} checks completed, ready={}",
+        checks.len(),
+        ready
+    );
+
+    Ok(OnboardingReport { checks, ready })
+}
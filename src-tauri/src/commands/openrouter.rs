@@ -0,0 +1,271 @@
+//! OpenRouter engine
+//!
+//! Routes prompts to any model hosted on OpenRouter through its
+//! OpenAI-compatible API. Sessions, streaming and MCP tool calls reuse the
+//! workbench-driven chat sessions of the Ollama integration
+//! (`commands::ollama::session`); each session keeps its own model, which can
+//! be switched between prompts. The configuration lives in
+//! `~/.anycode/openrouter.json`, the API key in the keyring.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::PathBuf;
+use std::time::Duration;
+use tauri::AppHandle;
+
+use super::ollama::config::{OllamaApi, OllamaConfig};
+use super::ollama::session::{
+    self, ChatEngine, ChatExecutionOptions, ChatSession, ChatSessionSummary,
+};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// Keyring entry of the OpenRouter API key
+const API_KEY_SECRET: &str = "openrouter-api-key";
+
+/// OpenAI-compatible API root (`/v1/...` is appended)
+const BASE_URL: &str = "https://openrouter.ai/api";
+
+/// Timeout for the model catalog request
+const CATALOG_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// OpenRouter configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterConfig {
+    /// Model for new sessions, e.g. `anthropic/claude-sonnet-4`
+    pub default_model: Option<String>,
+    /// Maximum model/tool round trips per prompt
+    #[serde(default = "default_max_tool_rounds")]
+    pub max_tool_rounds: u32,
+}
+
+fn default_max_tool_rounds() -> u32 {
+    8
+}
+
+impl Default for OpenRouterConfig {
+    fn default() -> Self {
+        Self {
+            default_model: None,
+            max_tool_rounds: default_max_tool_rounds(),
+        }
+    }
+}
+
+/// Configuration plus whether an API key is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterSettings {
+    #[serde(flatten)]
+    pub config: OpenRouterConfig,
+    pub has_api_key: bool,
+}
+
+/// Model in the OpenRouter catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenRouterModel {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub context_length: Option<u64>,
+    /// USD per million input tokens
+    pub prompt_price: Option<f64>,
+    /// USD per million output tokens
+    pub completion_price: Option<f64>,
+    /// Whether the model accepts tool definitions (needed for MCP tools)
+    pub supports_tools: bool,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("openrouter.json"))
+}
+
+fn load_config() -> OpenRouterConfig {
+    config_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn api_key() -> Option<String> {
+    keyring_store::get_secret(API_KEY_SECRET).ok().flatten()
+}
+
+/// The chat endpoint for OpenRouter sessions and one-off prompts
+pub(crate) fn chat_engine() -> Result<ChatEngine, String> {
+    let config = load_config();
+    Ok(ChatEngine {
+        id: "openrouter",
+        config: OllamaConfig {
+            base_url: BASE_URL.to_string(),
+            api: OllamaApi::OpenAi,
+            default_model: config.default_model,
+            max_tool_rounds: config.max_tool_rounds,
+        },
+        api_key: Some(api_key().ok_or("No OpenRouter API key configured")?),
+    })
+}
+
+/// OpenRouter prices are USD per token, as strings
+fn price_per_million(value: &Value) -> Option<f64> {
+    let per_token: f64 = value.as_str()?.parse().ok()?;
+    // Negative prices mark variable-priced routers
+    (per_token >= 0.0).then_some(per_token * 1_000_000.0)
+}
+
+/// Models listed by `/api/v1/models`, sorted by id
+fn parse_models(value: &Value) -> Vec<OpenRouterModel> {
+    let mut models: Vec<OpenRouterModel> = value["data"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|model| {
+            let id = model["id"].as_str()?.to_string();
+            Some(OpenRouterModel {
+                name: model["name"].as_str().unwrap_or(&id).to_string(),
+                description: model["description"].as_str().map(str::to_string),
+                context_length: model["context_length"].as_u64(),
+                prompt_price: price_per_million(&model["pricing"]["prompt"]),
+                completion_price: price_per_million(&model["pricing"]["completion"]),
+                supports_tools: model["supported_parameters"]
+                    .as_array()
+                    .map_or(false, |params| {
+                        params.iter().any(|p| p.as_str() == Some("tools"))
+                    }),
+                id,
+            })
+        })
+        .collect();
+    models.sort_by(|a, b| a.id.cmp(&b.id));
+    models
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the OpenRouter configuration
+#[tauri::command]
+pub async fn get_openrouter_settings() -> Result<OpenRouterSettings, String> {
+    Ok(OpenRouterSettings {
+        config: load_config(),
+        has_api_key: api_key().is_some(),
+    })
+}
+
+/// Update the configuration; `api_key` stores (or, when empty, removes) the API key
+#[tauri::command]
+pub async fn update_openrouter_settings(
+    config: OpenRouterConfig,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    if let Some(key) = api_key {
+        let key = key.trim();
+        if key.is_empty() {
+            keyring_store::delete_secret(API_KEY_SECRET)?;
+        } else {
+            keyring_store::set_secret(API_KEY_SECRET, key)?;
+        }
+    }
+    save_json_config(&config, config_path()?)
+}
+
+/// List the models available on OpenRouter with their pricing and context length
+#[tauri::command]
+pub async fn list_openrouter_models() -> Result<Vec<OpenRouterModel>, String> {
+    crate::commands::connectivity::ensure_online("Listing OpenRouter models")?;
+
+    let mut request = reqwest::Client::new()
+        .get(format!("{}/v1/models", BASE_URL))
+        .timeout(CATALOG_TIMEOUT);
+    if let Some(key) = api_key() {
+        request = request.bearer_auth(key);
+    }
+    let value: Value = request
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to fetch OpenRouter models: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid OpenRouter model list: {}", e))?;
+    Ok(parse_models(&value))
+}
+
+/// Send a prompt to an OpenRouter model, streaming the reply on `openrouter-output`
+#[tauri::command]
+pub async fn execute_openrouter(
+    options: ChatExecutionOptions,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    session::start(chat_engine()?, options, app_handle)
+}
+
+/// Cancel a running OpenRouter session (all sessions when `session_id` is None)
+#[tauri::command]
+pub async fn cancel_openrouter(
+    session_id: Option<String>,
+    app_handle: AppHandle,
+) -> Result<(), String> {
+    session::cancel("openrouter", session_id, &app_handle)
+}
+
+/// List the stored sessions of a project, most recent first
+#[tauri::command]
+pub async fn list_openrouter_sessions(
+    project_path: String,
+) -> Result<Vec<ChatSessionSummary>, String> {
+    session::list_sessions("openrouter", &project_path)
+}
+
+/// Load a stored session with its full history
+#[tauri::command]
+pub async fn get_openrouter_session(session_id: String) -> Result<ChatSession, String> {
+    session::load_session("openrouter", &session_id)
+}
+
+/// Delete a stored session
+#[tauri::command]
+pub async fn delete_openrouter_session(session_id: String) -> Result<(), String> {
+    session::delete_session("openrouter", &session_id)
+}
+
+/// Switch a session to another model for its next prompts
+#[tauri::command]
+pub async fn set_openrouter_session_model(session_id: String, model: String) -> Result<(), String> {
+    session::set_session_model("openrouter", &session_id, &model)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_models() {
+        let models = parse_models(&json!({
+            "data": [
+                {
+                    "id": "openai/gpt-4o",
+                    "name": "OpenAI: GPT-4o",
+                    "context_length": 128000,
+                    "pricing": { "prompt": "0.0000025", "completion": "0.00001" },
+                    "supported_parameters": ["tools", "temperature"]
+                },
+                {
+                    "id": "openrouter/auto",
+                    "pricing": { "prompt": "-1", "completion": "-1" }
+                },
+                { "name": "missing id" }
+            ]
+        }));
+
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].id, "openai/gpt-4o");
+        assert_eq!(models[0].context_length, Some(128000));
+        assert!((models[0].prompt_price.unwrap() - 2.5).abs() < 1e-9);
+        assert!(models[0].supports_tools);
+        assert_eq!(models[1].name, "openrouter/auto");
+        assert_eq!(models[1].prompt_price, None);
+        assert!(!models[1].supports_tools);
+    }
+}
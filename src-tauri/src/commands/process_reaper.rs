@@ -0,0 +1,112 @@
+//! Orphan process reaper
+//!
+//! Processes recorded in the process ledger that outlive a crashed run show up
+//! here on the next launch. The user can terminate them (optionally cleaning the
+//! lock files they may hold) or adopt them back into the process registry.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::process::ledger::{self, OrphanProcess};
+use crate::process::ProcessRegistryState;
+
+/// Result of terminating an orphan process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminateOrphanResult {
+    pub pid: u32,
+    pub removed_locks: Vec<String>,
+}
+
+/// Scan the ledger once on startup and notify the frontend about survivors
+pub async fn scan_orphans_on_startup(app: AppHandle) {
+    let live_pids = app
+        .state::<ProcessRegistryState>()
+        .0
+        .get_tracked_pids()
+        .unwrap_or_default();
+
+    let orphans = match tokio::task::spawn_blocking(move || ledger::scan_orphans(&live_pids)).await
+    {
+        Ok(Ok(orphans)) => orphans,
+        Ok(Err(e)) => {
+            log::warn!("[Reaper] Failed to scan process ledger: {}", e);
+            return;
+        }
+        Err(e) => {
+            log::warn!("[Reaper] Ledger scan task panicked: {}", e);
+            return;
+        }
+    };
+
+    if orphans.is_empty() {
+        log::debug!("[Reaper] No orphan processes from previous runs");
+        return;
+    }
+
+    log::warn!(
+        "[Reaper] Found {} orphan process(es) from a previous run",
+        orphans.len()
+    );
+    if let Err(e) = app.emit("orphan-processes-detected", &orphans) {
+        log::warn!("[Reaper] Failed to emit orphan-processes-detected: {}", e);
+    }
+}
+
+/// List processes from previous runs that are still alive
+#[tauri::command]
+pub async fn list_orphan_processes(
+    registry: tauri::State<'_, ProcessRegistryState>,
+) -> Result<Vec<OrphanProcess>, String> {
+    let live_pids = registry.0.get_tracked_pids()?;
+    tokio::task::spawn_blocking(move || ledger::scan_orphans(&live_pids))
+        .await
+        .map_err(|e| format!("Ledger scan task failed: {}", e))?
+}
+
+/// Terminate an orphan process, optionally removing the stale locks it left behind
+#[tauri::command]
+pub async fn terminate_orphan_process(
+    registry: tauri::State<'_, ProcessRegistryState>,
+    pid: u32,
+    remove_locks: bool,
+) -> Result<TerminateOrphanResult, String> {
+    let live_pids = registry.0.get_tracked_pids()?;
+
+    tokio::task::spawn_blocking(move || {
+        let orphan = ledger::find_orphan(pid, &live_pids)?
+            .ok_or_else(|| format!("Process {} is not a known orphan", pid))?;
+
+        log::info!("[Reaper] Terminating orphan process {}", pid);
+        ledger::terminate_pid(pid)?;
+
+        let removed_locks = if remove_locks {
+            ledger::remove_stale_locks(&orphan.stale_locks)
+        } else {
+            Vec::new()
+        };
+
+        Ok(TerminateOrphanResult { pid, removed_locks })
+    })
+    .await
+    .map_err(|e| format!("Terminate task failed: {}", e))?
+}
+
+/// Adopt an orphan process back into the process registry
+///
+/// Returns the new run_id assigned to the process.
+#[tauri::command]
+pub async fn adopt_orphan_process(
+    registry: tauri::State<'_, ProcessRegistryState>,
+    pid: u32,
+) -> Result<i64, String> {
+    let live_pids = registry.0.get_tracked_pids()?;
+    let orphan = tokio::task::spawn_blocking(move || ledger::find_orphan(pid, &live_pids))
+        .await
+        .map_err(|e| format!("Ledger scan task failed: {}", e))??
+        .ok_or_else(|| format!("Process {} is not a known orphan", pid))?;
+
+    let run_id = registry.0.adopt_process(orphan.info)?;
+    log::info!("[Reaper] Adopted orphan process {} as run_id {}", pid, run_id);
+    Ok(run_id)
+}
@@ -0,0 +1,300 @@
+//! Opt-in profiling of backend commands
+//!
+//! When enabled (`~/.anycode/profiling.json`), instrumented commands record
+//! their latency and response payload size, and instrumented subprocesses
+//! (git, mostly) their wall time and output size, into an in-memory metrics
+//! store holding the most recent samples. `get_performance_report`
+//! aggregates them per operation and lists the slowest calls, so a "the app
+//! is slow on my repo" report can come with numbers.
+//!
+//! Disabled profiling costs one atomic load per instrumented call.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Samples kept in the metrics store
+const MAX_SAMPLES: usize = 5000;
+
+/// Slowest samples listed in a report by default
+const DEFAULT_SLOWEST_LIMIT: usize = 20;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilingConfig {
+    #[serde(default)]
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SampleKind {
+    Command,
+    Subprocess,
+}
+
+/// One recorded operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceSample {
+    pub kind: SampleKind,
+    /// Command name, or the subprocess label (e.g. `git diff`)
+    pub name: String,
+    pub duration_ms: f64,
+    /// Serialized response size (commands) or stdout + stderr size (subprocesses)
+    pub payload_bytes: Option<u64>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Aggregate for one operation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationStats {
+    pub kind: SampleKind,
+    pub name: String,
+    pub count: usize,
+    pub avg_ms: f64,
+    pub p95_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+    pub max_payload_bytes: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PerformanceReport {
+    pub enabled: bool,
+    pub sample_count: usize,
+    pub since: Option<DateTime<Utc>>,
+    /// Per-operation statistics, slowest p95 first
+    pub operations: Vec<OperationStats>,
+    /// Individual slowest samples
+    pub slowest: Vec<PerformanceSample>,
+}
+
+static ENABLED: Lazy<AtomicBool> = Lazy::new(|| {
+    let config: ProfilingConfig = config_path().and_then(load_json_config).unwrap_or_default();
+    AtomicBool::new(config.enabled)
+});
+
+static SAMPLES: Lazy<Mutex<VecDeque<PerformanceSample>>> =
+    Lazy::new(|| Mutex::new(VecDeque::with_capacity(MAX_SAMPLES)));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("profiling.json"))
+}
+
+/// Re-read the config after the file was edited outside the app
+pub(crate) fn reload_config() {
+    let config: ProfilingConfig = config_path().and_then(load_json_config).unwrap_or_default();
+    ENABLED.store(config.enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn record(kind: SampleKind, name: &str, started: Instant, payload_bytes: Option<u64>) {
+    let sample = PerformanceSample {
+        kind,
+        name: name.to_string(),
+        duration_ms: started.elapsed().as_secs_f64() * 1000.0,
+        payload_bytes,
+        recorded_at: Utc::now(),
+    };
+    if let Ok(mut samples) = SAMPLES.lock() {
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+}
+
+/// Times a command from creation until drop
+pub struct CommandTimer {
+    name: &'static str,
+    started: Option<Instant>,
+    payload_bytes: Option<u64>,
+}
+
+impl CommandTimer {
+    pub fn start(name: &'static str) -> Self {
+        Self {
+            name,
+            started: is_enabled().then(Instant::now),
+            payload_bytes: None,
+        }
+    }
+
+    /// Record the serialized size of the response (only computed while profiling)
+    pub fn payload<T: Serialize>(&mut self, value: &T) {
+        if self.started.is_some() {
+            self.payload_bytes = serde_json::to_vec(value).ok().map(|v| v.len() as u64);
+        }
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        if let Some(started) = self.started {
+            record(SampleKind::Command, self.name, started, self.payload_bytes);
+        }
+    }
+}
+
+/// Run a subprocess to completion, recording its wall time under `label`
+pub fn timed_output(label: &str, cmd: &mut Command) -> std::io::Result<Output> {
+    timed_subprocess(label, || cmd.output())
+}
+
+/// Like [`timed_output`], for subprocesses started by `run`
+pub fn timed_subprocess(
+    label: &str,
+    run: impl FnOnce() -> std::io::Result<Output>,
+) -> std::io::Result<Output> {
+    if !is_enabled() {
+        return run();
+    }
+    let started = Instant::now();
+    let output = run();
+    let payload_bytes = output
+        .as_ref()
+        .ok()
+        .map(|o| (o.stdout.len() + o.stderr.len()) as u64);
+    record(SampleKind::Subprocess, label, started, payload_bytes);
+    output
+}
+
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+    sorted[rank.min(sorted.len() - 1)]
+}
+
+fn build_report(
+    enabled: bool,
+    samples: &[PerformanceSample],
+    slowest_limit: usize,
+) -> PerformanceReport {
+    let mut grouped: BTreeMap<(SampleKind, &str), Vec<&PerformanceSample>> = BTreeMap::new();
+    for sample in samples {
+        grouped
+            .entry((sample.kind, sample.name.as_str()))
+            .or_default()
+            .push(sample);
+    }
+
+    let mut operations: Vec<OperationStats> = grouped
+        .into_iter()
+        .map(|((kind, name), group)| {
+            let mut durations: Vec<f64> = group.iter().map(|s| s.duration_ms).collect();
+            durations.sort_by(|a, b| a.total_cmp(b));
+            let total_ms: f64 = durations.iter().sum();
+            OperationStats {
+                kind,
+                name: name.to_string(),
+                count: durations.len(),
+                avg_ms: total_ms / durations.len() as f64,
+                p95_ms: percentile(&durations, 0.95),
+                max_ms: durations.last().copied().unwrap_or_default(),
+                total_ms,
+                max_payload_bytes: group.iter().filter_map(|s| s.payload_bytes).max(),
+            }
+        })
+        .collect();
+    operations.sort_by(|a, b| b.p95_ms.total_cmp(&a.p95_ms));
+
+    let mut slowest = samples.to_vec();
+    slowest.sort_by(|a, b| b.duration_ms.total_cmp(&a.duration_ms));
+    slowest.truncate(slowest_limit);
+
+    PerformanceReport {
+        enabled,
+        sample_count: samples.len(),
+        since: samples.iter().map(|s| s.recorded_at).min(),
+        operations,
+        slowest,
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn set_profiling_enabled(enabled: bool) -> Result<(), String> {
+    save_json_config(&ProfilingConfig { enabled }, config_path()?)?;
+    ENABLED.store(enabled, Ordering::Relaxed);
+    log::info!(
+        "[Profiling] {}",
+        if enabled { "Enabled" } else { "Disabled" }
+    );
+    Ok(())
+}
+
+/// Aggregated latency and payload statistics, slowest operations first
+#[tauri::command]
+pub async fn get_performance_report(
+    slowest_limit: Option<usize>,
+) -> Result<PerformanceReport, String> {
+    let samples: Vec<PerformanceSample> = SAMPLES
+        .lock()
+        .map_err(|e| e.to_string())?
+        .iter()
+        .cloned()
+        .collect();
+    Ok(build_report(
+        is_enabled(),
+        &samples,
+        slowest_limit.unwrap_or(DEFAULT_SLOWEST_LIMIT),
+    ))
+}
+
+#[tauri::command]
+pub async fn clear_performance_samples() -> Result<(), String> {
+    SAMPLES.lock().map_err(|e| e.to_string())?.clear();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report() {
+        let sample = |kind, name: &str, duration_ms, payload_bytes| PerformanceSample {
+            kind,
+            name: name.to_string(),
+            duration_ms,
+            payload_bytes,
+            recorded_at: Utc::now(),
+        };
+        let samples = vec![
+            sample(SampleKind::Command, "search_files", 40.0, Some(2_000)),
+            sample(SampleKind::Command, "search_files", 60.0, Some(5_000)),
+            sample(SampleKind::Subprocess, "git status", 900.0, Some(100)),
+            sample(SampleKind::Command, "list_projects", 5.0, None),
+        ];
+
+        let report = build_report(true, &samples, 2);
+        assert_eq!(report.sample_count, 4);
+        assert_eq!(report.operations[0].name, "git status");
+        let search = &report.operations[1];
+        assert_eq!(search.count, 2);
+        assert_eq!(search.avg_ms, 50.0);
+        assert_eq!(search.max_ms, 60.0);
+        assert_eq!(search.max_payload_bytes, Some(5_000));
+        assert_eq!(report.slowest.len(), 2);
+        assert_eq!(report.slowest[1].duration_ms, 60.0);
+    }
+}
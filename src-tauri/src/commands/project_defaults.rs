@@ -0,0 +1,224 @@
+//! Per-project engine defaults
+//!
+//! Remembers, for each project, the engine last used and the model, reasoning
+//! effort and permission mode last used with each engine, so reopening a
+//! project restores them instead of the global defaults. Engine runs update the
+//! record automatically; a pinned record is only changed explicitly. Stored in
+//! `~/.anycode/project-defaults.json`.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::commands::engine_safety::project_key;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+const SUPPORTED_ENGINES: &[&str] = &["claude", "codex", "gemini", "ollama", "openrouter"];
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Model and mode choices for one engine; `None` means "not chosen yet"
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineDefaults {
+    pub model: Option<String>,
+    pub reasoning_effort: Option<String>,
+    pub permission_mode: Option<String>,
+}
+
+impl EngineDefaults {
+    /// Overlay the fields set in `other`
+    fn merge(&mut self, other: EngineDefaults) {
+        if other.model.is_some() {
+            self.model = other.model;
+        }
+        if other.reasoning_effort.is_some() {
+            self.reasoning_effort = other.reasoning_effort;
+        }
+        if other.permission_mode.is_some() {
+            self.permission_mode = other.permission_mode;
+        }
+    }
+}
+
+/// Defaults of one project
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDefaults {
+    /// Engine to open the project with
+    pub engine: Option<String>,
+    /// Engine → last used (or pinned) choices
+    #[serde(default)]
+    pub engines: BTreeMap<String, EngineDefaults>,
+    /// When pinned, engine runs no longer overwrite these defaults
+    #[serde(default)]
+    pub pinned: bool,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Explicit update of a project's defaults
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectDefaultsUpdate {
+    pub engine: Option<String>,
+    /// Choices for `engine`; only the fields that are set are changed
+    pub defaults: Option<EngineDefaults>,
+    pub pinned: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct ProjectDefaultsStore {
+    #[serde(default)]
+    projects: BTreeMap<String, ProjectDefaults>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("project-defaults.json"))
+}
+
+fn load_store() -> ProjectDefaultsStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(
+    f: impl FnOnce(&mut ProjectDefaultsStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+fn validate_engine(engine: &str) -> Result<(), String> {
+    if SUPPORTED_ENGINES.contains(&engine) {
+        Ok(())
+    } else {
+        Err(format!("Unknown engine: {}", engine))
+    }
+}
+
+/// The choices last used (or pinned) for an engine in a project
+pub fn engine_defaults(project_path: &str, engine: &str) -> EngineDefaults {
+    load_store()
+        .projects
+        .remove(&project_key(project_path))
+        .and_then(|mut project| project.engines.remove(engine))
+        .unwrap_or_default()
+}
+
+/// Record the choices an engine run was started with (skipped for pinned projects)
+pub fn record_last_used(project_path: &str, engine: &str, defaults: EngineDefaults) {
+    let key = project_key(project_path);
+    let current = load_store().projects.remove(&key).unwrap_or_default();
+    if current.pinned {
+        return;
+    }
+    let mut updated = current.clone();
+    updated.engine = Some(engine.to_string());
+    updated
+        .engines
+        .entry(engine.to_string())
+        .or_default()
+        .merge(defaults);
+    // Avoid rewriting the file on every run with unchanged choices
+    if updated == current {
+        return;
+    }
+
+    let result = update_store(|store| {
+        updated.updated_at = Some(Utc::now());
+        store.projects.insert(key, updated);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!(
+            "[Project Defaults] Failed to record defaults for {}: {}",
+            project_path,
+            e
+        );
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the engine, model and mode a project should open with
+#[tauri::command]
+pub async fn get_project_defaults(project_path: String) -> Result<ProjectDefaults, String> {
+    Ok(load_store()
+        .projects
+        .remove(&project_key(&project_path))
+        .unwrap_or_default())
+}
+
+/// Update (and optionally pin or unpin) a project's defaults
+#[tauri::command]
+pub async fn update_project_defaults(
+    project_path: String,
+    update: ProjectDefaultsUpdate,
+) -> Result<ProjectDefaults, String> {
+    if let Some(engine) = &update.engine {
+        validate_engine(engine)?;
+    }
+    if update.defaults.is_some() && update.engine.is_none() {
+        return Err("An engine is required when updating model or mode defaults".to_string());
+    }
+
+    update_store(|store| {
+        let project = store
+            .projects
+            .entry(project_key(&project_path))
+            .or_default();
+        if let Some(engine) = update.engine {
+            if let Some(defaults) = update.defaults {
+                project
+                    .engines
+                    .entry(engine.clone())
+                    .or_default()
+                    .merge(defaults);
+            }
+            project.engine = Some(engine);
+        }
+        if let Some(pinned) = update.pinned {
+            project.pinned = pinned;
+        }
+        project.updated_at = Some(Utc::now());
+        Ok(project.clone())
+    })
+}
+
+/// Forget a project's defaults so it opens with the global defaults again
+#[tauri::command]
+pub async fn clear_project_defaults(project_path: String) -> Result<(), String> {
+    update_store(|store| {
+        store.projects.remove(&project_key(&project_path));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merge_keeps_unset_fields() {
+        let mut defaults = EngineDefaults {
+            model: Some("opus".to_string()),
+            reasoning_effort: Some("high".to_string()),
+            permission_mode: Some("plan".to_string()),
+        };
+        defaults.merge(EngineDefaults {
+            model: Some("sonnet".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(defaults.model.as_deref(), Some("sonnet"));
+        assert_eq!(defaults.reasoning_effort.as_deref(), Some("high"));
+        assert_eq!(defaults.permission_mode.as_deref(), Some("plan"));
+    }
+}
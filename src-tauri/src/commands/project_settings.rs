@@ -0,0 +1,142 @@
+//! Per-project git settings
+//!
+//! Stored in `<project>/.anycode/settings.json`, so they can be committed and
+//! shared with the rest of the team. Every field is optional; an unset field
+//! falls back to the app-wide behavior:
+//!
+//! - `autoCommit`: commit after each prompt (on by default)
+//! - `commitPrefix`: put in front of every AI commit subject, e.g. `[ai]`
+//! - `protectedBranches`: branches AI commits must not land on; replaces the
+//!   global branch protection list, and an empty list turns protection off
+//! - `maxCommitsBeforeSquash`: once a session has more commits than this on
+//!   the branch, they are squashed into one
+//! - `undoBackend`: `git` (default), or `snapshots` to keep checkpoints as
+//!   file snapshots and never create a repository, see [`super::snapshots`]
+
+use glob::Pattern;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Where checkpoints and rewinds keep the project's history
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum UndoBackend {
+    Git,
+    Snapshots,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_commit: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub commit_prefix: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protected_branches: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_commits_before_squash: Option<usize>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub undo_backend: Option<UndoBackend>,
+}
+
+impl ProjectSettings {
+    pub fn auto_commit_enabled(&self) -> bool {
+        self.auto_commit.unwrap_or(true)
+    }
+
+    /// `message` with the project's commit prefix in front of its subject
+    pub fn prefixed_message(&self, message: &str) -> String {
+        match self.commit_prefix.as_deref().map(str::trim) {
+            Some(prefix) if !prefix.is_empty() && !message.starts_with(prefix) => {
+                format!("{} {}", prefix, message)
+            }
+            _ => message.to_string(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if let Some(pattern) = self
+            .protected_branches
+            .iter()
+            .flatten()
+            .find(|pattern| pattern.trim().is_empty() || Pattern::new(pattern).is_err())
+        {
+            return Err(format!("Invalid protected branch pattern: '{}'", pattern));
+        }
+        if self.max_commits_before_squash.is_some_and(|max| max < 1) {
+            return Err("Max commits before squash must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn config_path(project_path: &str) -> PathBuf {
+    ConfigPathBuilder::new(Path::new(project_path).join(".anycode")).build("settings.json")
+}
+
+/// The project's settings; defaults when the file is missing or unreadable
+pub fn load(project_path: &str) -> ProjectSettings {
+    match load_json_config(config_path(project_path)) {
+        Ok(settings) => settings,
+        Err(e) => {
+            log::warn!(
+                "[ProjectSettings] Ignoring settings of {}: {}",
+                project_path,
+                e
+            );
+            ProjectSettings::default()
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_project_settings(project_path: String) -> Result<ProjectSettings, String> {
+    Ok(load(&project_path))
+}
+
+#[tauri::command]
+pub async fn update_project_settings(
+    project_path: String,
+    settings: ProjectSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    save_json_config(&settings, config_path(&project_path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_settings_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        assert_eq!(load(&path), ProjectSettings::default());
+        assert!(load(&path).auto_commit_enabled());
+
+        let settings = ProjectSettings {
+            auto_commit: Some(false),
+            commit_prefix: Some("[ai]".to_string()),
+            ..ProjectSettings::default()
+        };
+        save_json_config(&settings, config_path(&path)).unwrap();
+        let loaded = load(&path);
+        assert_eq!(loaded, settings);
+        assert!(!loaded.auto_commit_enabled());
+        assert_eq!(loaded.prefixed_message("Fix it"), "[ai] Fix it");
+        assert_eq!(loaded.prefixed_message("[ai] Fix it"), "[ai] Fix it");
+
+        let invalid = ProjectSettings {
+            max_commits_before_squash: Some(0),
+            ..ProjectSettings::default()
+        };
+        assert!(invalid.validate().is_err());
+    }
+}
@@ -0,0 +1,357 @@
+//! Prompt/result cache for one-off engine requests
+//!
+//! Non-interactive requests such as "write a commit message for this diff" are
+//! often repeated with identical input. When enabled (it is off by default),
+//! `run_cached_prompt` answers such repeats from a cache keyed by engine, model,
+//! the whitespace-normalized prompt and a hash of the attached context, instead
+//! of spending tokens again. Entries expire after a TTL; callers can bypass the
+//! cache per request. Config and entries live in `~/.anycode/prompt-cache.json`.
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Cache configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCacheConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_ttl_secs")]
+    pub ttl_secs: i64,
+    #[serde(default = "default_max_entries")]
+    pub max_entries: usize,
+}
+
+fn default_ttl_secs() -> i64 {
+    3600
+}
+
+fn default_max_entries() -> usize {
+    200
+}
+
+impl Default for PromptCacheConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            ttl_secs: default_ttl_secs(),
+            max_entries: default_max_entries(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    engine: String,
+    model: Option<String>,
+    result: String,
+    created_at: DateTime<Utc>,
+    #[serde(default)]
+    hits: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PromptCacheStore {
+    #[serde(default)]
+    config: PromptCacheConfig,
+    /// Cache key → entry
+    #[serde(default)]
+    entries: HashMap<String, CacheEntry>,
+}
+
+/// A one-off engine request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedPromptRequest {
+    pub engine: String,
+    pub model: Option<String>,
+    pub prompt: String,
+    /// Material the prompt works on (e.g. a diff); appended to the prompt and hashed into the key
+    pub context: Option<String>,
+    /// Skip the cache lookup (the fresh result is still stored)
+    #[serde(default)]
+    pub bypass_cache: bool,
+}
+
+/// Reply to a one-off engine request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedPromptResult {
+    pub text: String,
+    /// Whether the reply was served from the cache
+    pub cached: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Cache statistics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptCacheStats {
+    pub config: PromptCacheConfig,
+    pub entries: usize,
+    pub expired: usize,
+    pub total_hits: u64,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("prompt-cache.json"))
+}
+
+fn load_store() -> PromptCacheStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(
+    f: impl FnOnce(&mut PromptCacheStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+/// Collapse whitespace so formatting-only differences hit the same entry
+fn normalize_prompt(prompt: &str) -> String {
+    prompt.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn sha256_hex(data: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(data.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Cache key for (engine, model, normalized prompt, context hash)
+fn cache_key(request: &CachedPromptRequest) -> String {
+    let context_hash = request
+        .context
+        .as_deref()
+        .map(sha256_hex)
+        .unwrap_or_default();
+    sha256_hex(&format!(
+        "{}\0{}\0{}\0{}",
+        request.engine,
+        request.model.as_deref().unwrap_or(""),
+        normalize_prompt(&request.prompt),
+        context_hash
+    ))
+}
+
+fn is_expired(entry: &CacheEntry, config: &PromptCacheConfig, now: DateTime<Utc>) -> bool {
+    now - entry.created_at > Duration::seconds(config.ttl_secs)
+}
+
+/// Drop expired entries, then the oldest ones beyond the size limit
+fn prune(store: &mut PromptCacheStore, now: DateTime<Utc>) {
+    let config = store.config.clone();
+    store
+        .entries
+        .retain(|_, entry| !is_expired(entry, &config, now));
+
+    let excess = store.entries.len().saturating_sub(config.max_entries);
+    if excess > 0 {
+        let mut by_age: Vec<(String, DateTime<Utc>)> = store
+            .entries
+            .iter()
+            .map(|(key, entry)| (key.clone(), entry.created_at))
+            .collect();
+        by_age.sort_by_key(|(_, created_at)| *created_at);
+        for (key, _) in by_age.into_iter().take(excess) {
+            store.entries.remove(&key);
+        }
+    }
+}
+
+/// Look up a fresh cached reply and count the hit
+fn lookup(key: &str) -> Option<CachedPromptResult> {
+    let store = load_store();
+    let entry = store.entries.get(key)?;
+    if is_expired(entry, &store.config, Utc::now()) {
+        return None;
+    }
+    let result = CachedPromptResult {
+        text: entry.result.clone(),
+        cached: true,
+        created_at: entry.created_at,
+    };
+
+    let _ = update_store(|store| {
+        if let Some(entry) = store.entries.get_mut(key) {
+            entry.hits += 1;
+        }
+        Ok(())
+    });
+    Some(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Run a one-off engine prompt, answering identical recent requests from the cache
+#[tauri::command]
+pub async fn run_cached_prompt(
+    app: AppHandle,
+    request: CachedPromptRequest,
+) -> Result<CachedPromptResult, String> {
+    let enabled = load_store().config.enabled;
+    let key = cache_key(&request);
+
+    if enabled && !request.bypass_cache {
+        if let Some(result) = lookup(&key) {
+            log::info!("[PromptCache] Cache hit for {} request", request.engine);
+            return Ok(result);
+        }
+    }
+
+    if crate::engines::require(&request.engine)?.requires_network() {
+        super::connectivity::ensure_online("Engine request")?;
+    }
+    let prompt = match &request.context {
+        Some(context) => format!("{}\n\n{}", request.prompt, context),
+        None => request.prompt.clone(),
+    };
+    let text = super::session_titles::run_engine_prompt(
+        &app,
+        &request.engine,
+        request.model.as_deref(),
+        &prompt,
+    )
+    .await?
+    .trim()
+    .to_string();
+
+    let now = Utc::now();
+    if enabled && !text.is_empty() {
+        let entry = CacheEntry {
+            engine: request.engine.clone(),
+            model: request.model.clone(),
+            result: text.clone(),
+            created_at: now,
+            hits: 0,
+        };
+        let stored = update_store(|store| {
+            store.entries.insert(key, entry);
+            prune(store, now);
+            Ok(())
+        });
+        if let Err(e) = stored {
+            log::warn!("[PromptCache] Failed to store result: {}", e);
+        }
+    }
+
+    Ok(CachedPromptResult {
+        text,
+        cached: false,
+        created_at: now,
+    })
+}
+
+/// Get cache configuration and statistics
+#[tauri::command]
+pub async fn get_prompt_cache_stats() -> Result<PromptCacheStats, String> {
+    let store = load_store();
+    let now = Utc::now();
+    Ok(PromptCacheStats {
+        entries: store.entries.len(),
+        expired: store
+            .entries
+            .values()
+            .filter(|entry| is_expired(entry, &store.config, now))
+            .count(),
+        total_hits: store.entries.values().map(|entry| entry.hits).sum(),
+        config: store.config,
+    })
+}
+
+/// Update the cache configuration (disabling it also clears the cache)
+#[tauri::command]
+pub async fn update_prompt_cache_config(config: PromptCacheConfig) -> Result<(), String> {
+    if config.ttl_secs <= 0 {
+        return Err("Cache TTL must be positive".to_string());
+    }
+    update_store(|store| {
+        if !config.enabled {
+            store.entries.clear();
+        }
+        store.config = config;
+        prune(store, Utc::now());
+        Ok(())
+    })
+}
+
+/// Remove all cached results
+#[tauri::command]
+pub async fn clear_prompt_cache() -> Result<(), String> {
+    update_store(|store| {
+        store.entries.clear();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(prompt: &str, context: Option<&str>) -> CachedPromptRequest {
+        CachedPromptRequest {
+            engine: "claude".to_string(),
+            model: Some("sonnet".to_string()),
+            prompt: prompt.to_string(),
+            context: context.map(String::from),
+            bypass_cache: false,
+        }
+    }
+
+    #[test]
+    fn test_cache_key_normalizes_whitespace() {
+        assert_eq!(
+            cache_key(&request("Write a commit message", Some("diff"))),
+            cache_key(&request("  Write a\ncommit   message ", Some("diff")))
+        );
+        assert_ne!(
+            cache_key(&request("Write a commit message", Some("diff"))),
+            cache_key(&request("Write a commit message", Some("other diff")))
+        );
+    }
+
+    #[test]
+    fn test_prune_drops_expired_and_oldest() {
+        let now = Utc::now();
+        let entry = |age_secs: i64| CacheEntry {
+            engine: "claude".to_string(),
+            model: None,
+            result: String::new(),
+            created_at: now - Duration::seconds(age_secs),
+            hits: 0,
+        };
+        let mut store = PromptCacheStore {
+            config: PromptCacheConfig {
+                enabled: true,
+                ttl_secs: 100,
+                max_entries: 1,
+            },
+            entries: HashMap::new(),
+        };
+        store.entries.insert("old".to_string(), entry(50));
+        store.entries.insert("new".to_string(), entry(10));
+        store.entries.insert("expired".to_string(), entry(500));
+
+        prune(&mut store, now);
+        assert_eq!(store.entries.keys().collect::<Vec<_>>(), vec!["new"]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Prompt draft autosave
+//!
+//! The prompt box saves its in-progress text, @-referenced files and attached
+//! images per project, so a crash or an accidental close does not lose a long
+//! prompt. Drafts are stored in `~/.anycode/prompt-drafts.json`; attached
+//! images usually live in the temp directory, so they are copied to
+//! `~/.anycode/draft-assets/<project>/` to survive a restart.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::engine_safety::project_key;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Draft content sent by the prompt box
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDraftInput {
+    pub text: String,
+    /// @-referenced file paths
+    #[serde(default)]
+    pub file_references: Vec<String>,
+    /// Attached image file paths
+    #[serde(default)]
+    pub images: Vec<String>,
+}
+
+/// A saved draft
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptDraft {
+    pub project_path: String,
+    pub text: String,
+    #[serde(default)]
+    pub file_references: Vec<String>,
+    /// Image paths inside the draft asset directory
+    #[serde(default)]
+    pub images: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct DraftStore {
+    /// Normalized project path → draft
+    #[serde(default)]
+    drafts: BTreeMap<String, PromptDraft>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("prompt-drafts.json"))
+}
+
+fn load_store() -> DraftStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(f: impl FnOnce(&mut DraftStore) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+/// Directory holding a project's draft images
+fn assets_dir(key: &str) -> Result<PathBuf, String> {
+    let hash = format!("{:x}", Sha256::digest(key.as_bytes()));
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?
+        .build("draft-assets")
+        .join(&hash[..16]))
+}
+
+/// Copy attached images into the asset directory (already copied ones are kept as is)
+fn persist_images(images: &[String], dir: &Path) -> Result<Vec<String>, String> {
+    let mut persisted = Vec::new();
+    for image in images {
+        let source = Path::new(image);
+        if source.starts_with(dir) {
+            persisted.push(image.clone());
+            continue;
+        }
+        let Some(file_name) = source.file_name() else {
+            continue;
+        };
+        if !source.is_file() {
+            log::warn!("[Drafts] Attached image no longer exists: {}", image);
+            continue;
+        }
+        std::fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create draft asset directory: {}", e))?;
+        let target = dir.join(file_name);
+        std::fs::copy(source, &target)
+            .map_err(|e| format!("Failed to copy draft image {}: {}", image, e))?;
+        persisted.push(target.to_string_lossy().to_string());
+    }
+    Ok(persisted)
+}
+
+/// Delete draft images that are no longer attached
+fn remove_stale_images(dir: &Path, keep: &[String]) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !keep.iter().any(|kept| Path::new(kept) == path) {
+            let _ = std::fs::remove_file(&path);
+        }
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Save the project's draft; an empty draft removes it
+#[tauri::command]
+pub async fn save_prompt_draft(
+    project_path: String,
+    draft: PromptDraftInput,
+) -> Result<Option<PromptDraft>, String> {
+    let key = project_key(&project_path);
+    let dir = assets_dir(&key)?;
+
+    if draft.text.trim().is_empty() && draft.file_references.is_empty() && draft.images.is_empty() {
+        clear_prompt_draft(project_path).await?;
+        return Ok(None);
+    }
+
+    let images = persist_images(&draft.images, &dir)?;
+    remove_stale_images(&dir, &images);
+    let saved = PromptDraft {
+        project_path,
+        text: draft.text,
+        file_references: draft.file_references,
+        images,
+        updated_at: Utc::now(),
+    };
+    update_store(|store| {
+        store.drafts.insert(key, saved.clone());
+        Ok(())
+    })?;
+    Ok(Some(saved))
+}
+
+/// Get the project's saved draft
+#[tauri::command]
+pub async fn get_prompt_draft(project_path: String) -> Result<Option<PromptDraft>, String> {
+    Ok(load_store().drafts.remove(&project_key(&project_path)))
+}
+
+/// List all saved drafts, newest first
+#[tauri::command]
+pub async fn list_prompt_drafts() -> Result<Vec<PromptDraft>, String> {
+    let mut drafts: Vec<PromptDraft> = load_store().drafts.into_values().collect();
+    drafts.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+    Ok(drafts)
+}
+
+/// Discard the project's draft (after the prompt was sent, or by hand)
+#[tauri::command]
+pub async fn clear_prompt_draft(project_path: String) -> Result<(), String> {
+    let key = project_key(&project_path);
+    let dir = assets_dir(&key)?;
+    if dir.exists() {
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+    update_store(|store| {
+        store.drafts.remove(&key);
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_persist_images_copies_once_and_prunes() {
+        let temp = tempfile::tempdir().unwrap();
+        let source = temp.path().join("clipboard.png");
+        std::fs::write(&source, b"png").unwrap();
+        let dir = temp.path().join("assets");
+
+        let images = vec![
+            source.to_string_lossy().to_string(),
+            temp.path().join("gone.png").to_string_lossy().to_string(),
+        ];
+        let persisted = persist_images(&images, &dir).unwrap();
+        assert_eq!(persisted.len(), 1);
+        assert!(persisted[0].starts_with(&*dir.to_string_lossy()));
+
+        // Persisted paths are kept as they are
+        assert_eq!(persist_images(&persisted, &dir).unwrap(), persisted);
+
+        remove_stale_images(&dir, &[]);
+        assert!(!Path::new(&persisted[0]).exists());
+    }
+}
@@ -0,0 +1,232 @@
+//! Prompt history
+//!
+//! Every prompt submitted to an engine is appended to
+//! `~/.anycode/prompt-history.json` with its project, engine and timestamp, so
+//! the up-arrow history of the prompt box survives restarts. Re-submitting the
+//! prompt that is already the project's latest entry only bumps that entry.
+//! The history can be listed per project or globally and searched.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::commands::engine_safety::project_key;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Entries kept before the oldest are dropped
+const MAX_ENTRIES: usize = 5000;
+
+/// Default number of entries returned by list/search
+const DEFAULT_LIMIT: usize = 100;
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A submitted prompt
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptHistoryEntry {
+    pub id: String,
+    pub prompt: String,
+    pub project_path: Option<String>,
+    pub engine: Option<String>,
+    /// Last time the prompt was submitted
+    pub submitted_at: DateTime<Utc>,
+    /// How often it was submitted in a row
+    #[serde(default = "default_use_count")]
+    pub use_count: u32,
+}
+
+fn default_use_count() -> u32 {
+    1
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct PromptHistoryStore {
+    /// Oldest first
+    #[serde(default)]
+    entries: Vec<PromptHistoryEntry>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("prompt-history.json"))
+}
+
+fn load_store() -> PromptHistoryStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(
+    f: impl FnOnce(&mut PromptHistoryStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+fn in_project(entry: &PromptHistoryEntry, project: Option<&str>) -> bool {
+    match project {
+        None => true,
+        Some(key) => entry.project_path.as_deref().map(project_key).as_deref() == Some(key),
+    }
+}
+
+/// Append a prompt, merging it into the project's latest entry when identical
+fn push_entry(store: &mut PromptHistoryStore, project_path: &str, engine: &str, prompt: &str) {
+    let key = project_key(project_path);
+    let now = Utc::now();
+    if let Some(last) = store
+        .entries
+        .iter_mut()
+        .rev()
+        .find(|entry| in_project(entry, Some(&key)))
+    {
+        if last.prompt == prompt {
+            last.submitted_at = now;
+            last.use_count += 1;
+            last.engine = Some(engine.to_string());
+            return;
+        }
+    }
+
+    store.entries.push(PromptHistoryEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        prompt: prompt.to_string(),
+        project_path: Some(project_path.to_string()),
+        engine: Some(engine.to_string()),
+        submitted_at: now,
+        use_count: 1,
+    });
+    let excess = store.entries.len().saturating_sub(MAX_ENTRIES);
+    store.entries.drain(..excess);
+}
+
+/// Record a submitted prompt
+pub fn record_prompt(project_path: &str, engine: &str, prompt: &str) {
+    let prompt = prompt.trim();
+    if prompt.is_empty() {
+        return;
+    }
+    let result = update_store(|store| {
+        push_entry(store, project_path, engine, prompt);
+        Ok(())
+    });
+    if let Err(e) = result {
+        log::warn!("[PromptHistory] Failed to record prompt: {}", e);
+    }
+}
+
+/// Newest-first entries of a project (or all projects), optionally filtered
+fn query(
+    store: PromptHistoryStore,
+    project_path: Option<&str>,
+    terms: &[String],
+    limit: usize,
+) -> Vec<PromptHistoryEntry> {
+    let project = project_path.map(project_key);
+    let mut seen = HashSet::new();
+    store
+        .entries
+        .into_iter()
+        .rev()
+        .filter(|entry| in_project(entry, project.as_deref()))
+        .filter(|entry| {
+            let prompt = entry.prompt.to_lowercase();
+            terms.iter().all(|term| prompt.contains(term))
+        })
+        // Across projects the same prompt may appear several times; keep the newest
+        .filter(|entry| seen.insert(entry.prompt.clone()))
+        .take(limit)
+        .collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get recent prompts, newest first (all projects when `project_path` is omitted)
+#[tauri::command]
+pub async fn get_prompt_history(
+    project_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<PromptHistoryEntry>, String> {
+    Ok(query(
+        load_store(),
+        project_path.as_deref(),
+        &[],
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
+
+/// Search prompts containing every word of `query` (case-insensitive), newest first
+#[tauri::command]
+pub async fn search_prompt_history(
+    query: String,
+    project_path: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<PromptHistoryEntry>, String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| term.to_lowercase())
+        .collect();
+    Ok(self::query(
+        load_store(),
+        project_path.as_deref(),
+        &terms,
+        limit.unwrap_or(DEFAULT_LIMIT),
+    ))
+}
+
+/// Delete one history entry
+#[tauri::command]
+pub async fn delete_prompt_history_entry(id: String) -> Result<(), String> {
+    update_store(|store| {
+        store.entries.retain(|entry| entry.id != id);
+        Ok(())
+    })
+}
+
+/// Clear the history of a project, or all history when `project_path` is omitted
+#[tauri::command]
+pub async fn clear_prompt_history(project_path: Option<String>) -> Result<(), String> {
+    let project = project_path.as_deref().map(project_key);
+    update_store(|store| {
+        store
+            .entries
+            .retain(|entry| project.is_some() && !in_project(entry, project.as_deref()));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_consecutive_duplicates_are_merged() {
+        let mut store = PromptHistoryStore::default();
+        push_entry(&mut store, "/work/app", "claude", "run the tests");
+        push_entry(&mut store, "/work/app", "claude", "run the tests");
+        push_entry(&mut store, "/work/lib", "codex", "fix the build");
+        // Still the latest prompt of /work/app
+        push_entry(&mut store, "/work/app", "codex", "run the tests");
+        push_entry(&mut store, "/work/app", "claude", "explain this");
+        push_entry(&mut store, "/work/app", "claude", "run the tests");
+
+        assert_eq!(store.entries.len(), 4);
+        assert_eq!(store.entries[0].use_count, 3);
+
+        let app = query(store.clone(), Some("/work/app"), &[], 10);
+        let prompts: Vec<_> = app.iter().map(|e| e.prompt.as_str()).collect();
+        assert_eq!(prompts, vec!["run the tests", "explain this"]);
+
+        let found = query(store, None, &["build".to_string()], 10);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].project_path.as_deref(), Some("/work/lib"));
+    }
+}
@@ -6,9 +6,16 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use super::auto_checkpoint;
+use super::checkpoints;
 use super::claude::get_claude_dir;
+use super::commit_metadata;
+use super::commit_templates;
+use super::git_executor::GitOperation;
 use super::permission_config::ClaudeExecutionConfig;
+use super::monorepo;
 use super::simple_git;
+use super::snapshots;
 
 /// Rewind mode for reverting prompts
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -168,23 +175,6 @@ fn get_git_record(
     Ok(records.get(&prompt_index).cloned())
 }
 
-fn build_prompt_commit_message(
-    prefix: &str,
-    prompt_text: Option<&str>,
-    prompt_index: usize,
-) -> String {
-    let prompt_text = prompt_text.unwrap_or("");
-    let sanitized = prompt_text.replace('\n', " ").replace('\r', " ");
-    let sanitized = sanitized.trim();
-    let truncated: String = sanitized.chars().take(80).collect();
-
-    if truncated.is_empty() {
-        return format!("{prefix} After prompt #{prompt_index}");
-    }
-
-    format!("{prefix} {truncated} prompt #{prompt_index}")
-}
-
 /// Truncate git records (remove records for prompts after the specified index)
 fn truncate_git_records(
     session_id: &str,
@@ -530,14 +520,31 @@ pub async fn record_prompt_sent(
         return Ok(prompt_index);
     }
 
-    // Ensure Git repository is initialized
-    simple_git::ensure_git_repo(&project_path)
-        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
-
-    // IMPORTANT: Always get the LATEST commit
-    // This ensures we start from the correct state even if previous prompt made no changes
-    let commit_before = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+    let commit_before = {
+        let project_path = project_path.clone();
+        let session_id = session_id.clone();
+        GitOperation::new(&project_path, "record prompt")
+            .run(move || {
+                Ok(if snapshots::enabled(&project_path) {
+                    // Snapshot projects never get a Git repository; a snapshot id stands in for the commit
+                    snapshots::take_snapshot(&project_path)
+                        .map_err(|e| format!("Failed to snapshot project: {}", e))?
+                } else {
+                    // Ensure Git repository is initialized
+                    simple_git::ensure_git_repo(&project_path)
+                        .map_err(|e| format!("Failed to ensure Git repo: {}", e))?;
+
+                    // Snapshot uncommitted work first, so the prompt's starting point includes it
+                    auto_checkpoint::before_run(&project_path, "claude", &session_id);
+
+                    // IMPORTANT: Always get the LATEST commit
+                    // This ensures we start from the correct state even if previous prompt made no changes
+                    simple_git::git_current_commit(&project_path)
+                        .map_err(|e| format!("Failed to get current commit: {}", e))?
+                })
+            })
+            .await?
+    };
 
     log::info!("[Record Prompt] Current git commit: {}", commit_before);
 
@@ -596,36 +603,74 @@ pub async fn mark_prompt_completed(
         return Ok(());
     }
 
-    // Auto-commit any changes made by AI
-    // This ensures each prompt has a distinct git state
-    let commit_message =
-        build_prompt_commit_message("[Claude Code]", prompt_text.as_deref(), prompt_index);
-    match simple_git::git_commit_changes(&project_path, &commit_message) {
-        Ok(true) => {
-            log::info!("Auto-committed changes after prompt #{}", prompt_index);
-        }
-        Ok(false) => {
-            log::debug!("No changes to commit after prompt #{}", prompt_index);
-        }
-        Err(e) => {
-            log::warn!(
-                "Failed to auto-commit after prompt #{}: {}",
-                prompt_index,
-                e
-            );
-            // Continue anyway, don't fail the whole operation
-        }
-    }
+    let commit_after = {
+        let project_path = project_path.clone();
+        let session_id = session_id.clone();
+        let prompt_text = prompt_text.clone();
+        GitOperation::new(&project_path, "auto-commit")
+            .run(move || {
+                Ok(if snapshots::enabled(&project_path) {
+                    snapshots::take_snapshot(&project_path)
+                        .map_err(|e| format!("Failed to snapshot project: {}", e))?
+                } else {
+                    // Auto-commit any changes made by AI
+                    // This ensures each prompt has a distinct git state
+                    let package_scope = monorepo::session_package_scope(&session_id);
+                    let commit_message = commit_metadata::tag_message(
+                        &commit_templates::prompt_commit_message(
+                            &project_path,
+                            "claude",
+                            prompt_text.as_deref(),
+                            prompt_index,
+                            package_scope.as_deref(),
+                        ),
+                        "claude",
+                        Some(&session_id),
+                    );
+                    match simple_git::git_commit_changes_in(
+                        &project_path,
+                        &commit_message,
+                        package_scope.as_deref(),
+                    ) {
+                        Ok(outcome) if outcome.committed => {
+                            log::info!("Auto-committed changes after prompt #{}", prompt_index);
+                        }
+                        Ok(_) => {
+                            log::debug!("No changes to commit after prompt #{}", prompt_index);
+                        }
+                        Err(e) => {
+                            log::warn!(
+                                "Failed to auto-commit after prompt #{}: {}",
+                                prompt_index,
+                                e
+                            );
+                            // Continue anyway, don't fail the whole operation
+                        }
+                    }
 
-    // Get current commit (state after AI completion and auto-commit)
-    let commit_after = simple_git::git_current_commit(&project_path)
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
+                    // Get current commit (state after AI completion and auto-commit)
+                    simple_git::git_current_commit(&project_path)
+                        .map_err(|e| format!("Failed to get current commit: {}", e))?
+                })
+            })
+            .await?
+    };
 
     // 🔧 FIX: Load existing git record using prompt_index (not hash!)
     let mut git_record = get_git_record(&session_id, &project_id, prompt_index)
         .map_err(|e| format!("Failed to get git record: {}", e))?
         .ok_or_else(|| format!("Git record not found for prompt #{}", prompt_index))?;
 
+    checkpoints::record_checkpoint(
+        &project_path,
+        "claude",
+        &session_id,
+        prompt_index,
+        prompt_text.as_deref(),
+        &git_record.commit_before,
+        &commit_after,
+    );
+
     // Update commit_after
     git_record.commit_after = Some(commit_after.clone());
 
@@ -697,6 +742,26 @@ pub async fn revert_to_prompt(
         _ => {}
     }
 
+    // Snapshot projects have no commits to revert: put the files back as they were
+    if let (RewindMode::CodeOnly | RewindMode::Both, Some(record)) = (&mode, &git_record) {
+        if snapshots::enabled(&project_path) {
+            let restore = snapshots::restore_snapshot(&project_path, &record.commit_before)?;
+            log::info!(
+                "[Snapshot Revert] Restored state before prompt #{} ({} written, {} deleted)",
+                prompt_index,
+                restore.restored,
+                restore.removed
+            );
+            if mode == RewindMode::Both {
+                truncate_session_to_prompt(&session_id, &project_id, prompt_index)
+                    .map_err(|e| format!("Failed to truncate session: {}", e))?;
+                truncate_git_records(&session_id, &project_id, &prompts, prompt_index)
+                    .map_err(|e| format!("Failed to truncate git records: {}", e))?;
+            }
+            return Ok(prompt.text.clone());
+        }
+    }
+
     // Execute revert based on mode
     match mode {
         RewindMode::ConversationOnly => {
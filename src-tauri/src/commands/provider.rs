@@ -52,6 +52,22 @@ fn get_legacy_providers_path() -> Result<PathBuf, String> {
     Ok(home_dir.join(".claude").join("providers.json"))
 }
 
+/// Claude 请求的 API 地址（settings.json 的 env 优先，其次是进程环境变量）
+pub(crate) fn configured_base_url() -> Option<String> {
+    let from_settings = get_settings_path()
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|settings| {
+            settings["env"]["ANTHROPIC_BASE_URL"]
+                .as_str()
+                .map(str::to_string)
+        });
+    from_settings
+        .or_else(|| std::env::var("ANTHROPIC_BASE_URL").ok())
+        .filter(|url| !url.trim().is_empty())
+}
+
 // 读取settings.json文件
 fn load_settings() -> Result<Value, String> {
     let settings_path = get_settings_path()?;
@@ -91,6 +107,30 @@ fn save_settings(settings: &Value) -> Result<(), String> {
     Ok(())
 }
 
+/// 仅替换 settings.json env 中当前使用的 API 凭据（用于多密钥轮换）
+pub(crate) fn set_active_api_key(key: &str) -> Result<(), String> {
+    let mut settings = load_settings()?;
+    let env_obj = settings
+        .as_object_mut()
+        .ok_or("settings.json格式错误")?
+        .entry("env")
+        .or_insert_with(|| serde_json::json!({}))
+        .as_object_mut()
+        .ok_or("env字段格式错误")?;
+
+    // 沿用当前配置使用的字段，默认使用 ANTHROPIC_AUTH_TOKEN
+    let field = if env_obj.contains_key("ANTHROPIC_API_KEY")
+        && !env_obj.contains_key("ANTHROPIC_AUTH_TOKEN")
+    {
+        "ANTHROPIC_API_KEY"
+    } else {
+        "ANTHROPIC_AUTH_TOKEN"
+    };
+    env_obj.insert(field.to_string(), Value::String(key.to_string()));
+
+    save_settings(&settings)
+}
+
 // 从遗留的providers.json加载预设配置
 fn load_legacy_providers() -> Result<Vec<ProviderConfig>, String> {
     let legacy_path = get_legacy_providers_path()?;
@@ -413,6 +453,9 @@ pub async fn switch_provider_config(
 
     log::info!("代理商配置切换完成: {}", config.name);
 
+    // 若该代理商配置了多密钥池，写入池中当前密钥
+    super::key_pools::on_provider_switched("claude", &config.id);
+
     Ok(format!(
         "✅ 已成功切换到 {} ({})\n\n配置已写入 ~/.claude/settings.json，即时生效！",
         config.name, config.description
@@ -483,6 +526,7 @@ pub async fn clear_provider_config(_app: AppHandle) -> Result<String, String> {
     save_settings(&settings)?;
 
     log::info!("代理商配置清理完成");
+    super::key_pools::on_provider_cleared("claude");
 
     Ok("✅ 已清理所有ANTHROPIC环境变量和apiKeyHelper配置\n\n配置已从 ~/.claude/settings.json 中移除！".to_string())
 }
@@ -529,6 +573,7 @@ pub async fn query_provider_usage(
     use reqwest::Client;
 
     log::info!("开始查询 API Key 用量: {}", base_url);
+    crate::commands::connectivity::ensure_online("API Key 用量查询")?;
 
     // 规范化基础 URL
     let normalized_base = normalize_base_url(&base_url);
@@ -0,0 +1,32 @@
+//! Secret redaction settings and export helper
+//!
+//! Exposes the redaction layer in [`crate::utils::redaction`] to the frontend:
+//! pattern configuration, and a helper the session exporter runs exported
+//! content through before it is written to disk.
+
+use crate::utils::redaction::{self, RedactionConfig};
+
+/// Get the redaction configuration
+#[tauri::command]
+pub async fn get_redaction_config() -> Result<RedactionConfig, String> {
+    redaction::load_config()
+}
+
+/// Update the redaction configuration (invalid patterns are rejected)
+#[tauri::command]
+pub async fn update_redaction_config(config: RedactionConfig) -> Result<(), String> {
+    log::info!(
+        "[Redaction] Updating config: enabled={}, {} custom patterns",
+        config.enabled,
+        config.custom_patterns.len()
+    );
+    tokio::task::spawn_blocking(move || redaction::save_config(&config))
+        .await
+        .map_err(|e| format!("Redaction task failed: {}", e))?
+}
+
+/// Redact secrets from content before it is exported (sessions, logs, reports)
+#[tauri::command]
+pub async fn redact_export_content(content: String) -> Result<String, String> {
+    Ok(redaction::redact(&content))
+}
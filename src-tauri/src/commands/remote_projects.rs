@@ -0,0 +1,644 @@
+//! Remote projects over SSH
+//!
+//! A remote project is a directory on another host. Git, engine and file
+//! operations run there through the system `ssh` client, so the user's
+//! `~/.ssh/config`, agent and known hosts apply as usual. Connections are
+//! pooled with OpenSSH multiplexing (one `ControlMaster` per host, kept alive
+//! for a while after the last command), and a per-host semaphore keeps the
+//! number of concurrent sessions under the server's `MaxSessions`.
+//! Registered projects are stored in `~/.anycode/remote-projects.json`.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Command;
+use tokio::sync::{oneshot, Semaphore};
+
+use crate::commands::claude::apply_no_window_async;
+use crate::commands::event_batcher;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Concurrent sessions per host (OpenSSH's default `MaxSessions` is 10)
+const MAX_SESSIONS_PER_HOST: usize = 8;
+
+/// How long an idle master connection is kept open
+const CONTROL_PERSIST: &str = "10m";
+
+const CONNECT_TIMEOUT_SECS: u32 = 15;
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+static HOST_SLOTS: Lazy<Mutex<HashMap<String, Arc<Semaphore>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Cancel handles of streaming commands, by run id
+static RUNNING: Lazy<Mutex<HashMap<String, oneshot::Sender<()>>>> =
+    Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// A project living on a remote host
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteProject {
+    pub id: String,
+    pub name: String,
+    /// Host name or `~/.ssh/config` alias
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<String>,
+    /// Absolute project directory on the host
+    pub remote_path: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Fields the user enters when registering a remote project
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteProjectInput {
+    pub name: Option<String>,
+    pub host: String,
+    pub user: Option<String>,
+    pub port: Option<u16>,
+    pub identity_file: Option<String>,
+    pub remote_path: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct RemoteProjectStore {
+    #[serde(default)]
+    projects: Vec<RemoteProject>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConnectionStatus {
+    pub connected: bool,
+    pub latency_ms: u64,
+    /// `uname -sm` of the host
+    pub platform: Option<String>,
+    pub git_version: Option<String>,
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteCommandOutput {
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteOutputLine {
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteFileEntry {
+    pub name: String,
+    /// Path relative to the project root
+    pub path: String,
+    pub is_directory: bool,
+    pub size: u64,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("remote-projects.json"))
+}
+
+fn load_store() -> RemoteProjectStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(
+    f: impl FnOnce(&mut RemoteProjectStore) -> Result<T, String>,
+) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+fn find_project(id: &str) -> Result<RemoteProject, String> {
+    load_store()
+        .projects
+        .into_iter()
+        .find(|project| project.id == id)
+        .ok_or_else(|| format!("Remote project not found: {}", id))
+}
+
+// ============================================================================
+// SSH plumbing
+// ============================================================================
+
+/// Quote a string for a POSIX shell
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r#"'\''"#))
+}
+
+/// Map a project-relative path to a path on the host, refusing to leave the project
+fn remote_file_path(project: &RemoteProject, relative: &str) -> Result<String, String> {
+    let mut parts = Vec::new();
+    for part in relative.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => return Err(format!("Path escapes the project: {}", relative)),
+            part => parts.push(part),
+        }
+    }
+    let root = project.remote_path.trim_end_matches('/');
+    Ok(if parts.is_empty() {
+        root.to_string()
+    } else {
+        format!("{}/{}", root, parts.join("/"))
+    })
+}
+
+/// Refuse hosts and users ssh would read as options or split into several arguments
+fn validate_ssh_target(host: &str, user: Option<&str>) -> Result<(), String> {
+    for (label, value) in [("Host", Some(host)), ("User", user)] {
+        let Some(value) = value else {
+            continue;
+        };
+        if value.starts_with('-') {
+            return Err(format!("{} must not start with '-': {}", label, value));
+        }
+        if value.chars().any(char::is_whitespace) {
+            return Err(format!("{} must not contain whitespace: {}", label, value));
+        }
+    }
+    Ok(())
+}
+
+fn destination(project: &RemoteProject) -> Result<String, String> {
+    let user = project.user.as_deref().filter(|user| !user.is_empty());
+    validate_ssh_target(&project.host, user)?;
+    Ok(match user {
+        Some(user) => format!("{}@{}", user, project.host),
+        None => project.host.clone(),
+    })
+}
+
+/// Control socket directory for multiplexed connections
+fn control_dir() -> Result<PathBuf, String> {
+    let dir = ConfigPathBuilder::from_home_subdir(".anycode")?.build("ssh");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create SSH control directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Options shared by every invocation for a project
+fn ssh_options(project: &RemoteProject) -> Result<Vec<String>, String> {
+    let mut args = vec![
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+    ];
+    // OpenSSH for Windows does not support connection multiplexing
+    if !cfg!(target_os = "windows") {
+        let control_path = control_dir()?.join("cm-%C");
+        args.extend([
+            "-o".to_string(),
+            "ControlMaster=auto".to_string(),
+            "-o".to_string(),
+            format!("ControlPath={}", control_path.to_string_lossy()),
+            "-o".to_string(),
+            format!("ControlPersist={}", CONTROL_PERSIST),
+        ]);
+    }
+    if let Some(port) = project.port {
+        args.extend(["-p".to_string(), port.to_string()]);
+    }
+    if let Some(identity) = project.identity_file.as_ref().filter(|i| !i.is_empty()) {
+        args.extend(["-i".to_string(), identity.clone()]);
+    }
+    Ok(args)
+}
+
+/// `ssh` command running `script` in the project directory through a login shell,
+/// so tools installed via the user's profile (nvm, pyenv, ...) are on PATH
+fn ssh_command(project: &RemoteProject, script: &str) -> Result<Command, String> {
+    let full_script = format!("cd {} && {}", shell_quote(&project.remote_path), script);
+    let mut cmd = Command::new("ssh");
+    cmd.args(ssh_options(project)?)
+        .arg("--")
+        .arg(destination(project)?)
+        .arg(format!("bash -lc {}", shell_quote(&full_script)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    apply_no_window_async(&mut cmd);
+    Ok(cmd)
+}
+
+/// Shell command line for a program and its arguments
+fn command_line(program: &str, args: &[String]) -> String {
+    std::iter::once(program)
+        .chain(args.iter().map(String::as_str))
+        .map(shell_quote)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn host_slots(project: &RemoteProject) -> Arc<Semaphore> {
+    let key = format!(
+        "{}@{}:{}",
+        project.user.as_deref().unwrap_or_default(),
+        project.host,
+        project.port.unwrap_or(22)
+    );
+    let mut slots = HOST_SLOTS.lock().unwrap_or_else(|e| e.into_inner());
+    slots
+        .entry(key)
+        .or_insert_with(|| Arc::new(Semaphore::new(MAX_SESSIONS_PER_HOST)))
+        .clone()
+}
+
+/// Run a script on the host and collect its output
+async fn run_script(
+    project: &RemoteProject,
+    script: &str,
+    stdin: Option<&[u8]>,
+) -> Result<RemoteCommandOutput, String> {
+    let slots = host_slots(project);
+    let _permit = slots.acquire().await.map_err(|e| e.to_string())?;
+
+    let mut cmd = ssh_command(project, script)?;
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to start ssh: {}", e))?;
+    if let (Some(input), Some(mut pipe)) = (stdin, child.stdin.take()) {
+        pipe.write_all(input)
+            .await
+            .map_err(|e| format!("Failed to send data over ssh: {}", e))?;
+    }
+    let output = child
+        .wait_with_output()
+        .await
+        .map_err(|e| format!("ssh failed: {}", e))?;
+    Ok(RemoteCommandOutput {
+        exit_code: output.status.code(),
+        stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+        stderr: String::from_utf8_lossy(&output.stderr).to_string(),
+    })
+}
+
+fn ensure_success(output: RemoteCommandOutput) -> Result<RemoteCommandOutput, String> {
+    if output.exit_code == Some(0) {
+        Ok(output)
+    } else {
+        Err(output.stderr.trim().to_string())
+    }
+}
+
+/// Parse `find -printf '%y\t%s\t%P\n'` output
+fn parse_listing(output: &str, base: &str) -> Vec<RemoteFileEntry> {
+    let base = base.trim_matches('/');
+    let mut entries: Vec<RemoteFileEntry> = output
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, '\t');
+            let kind = fields.next()?;
+            let size = fields.next()?.parse().unwrap_or(0);
+            let name = fields.next()?.to_string();
+            Some(RemoteFileEntry {
+                path: if base.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{}/{}", base, name)
+                },
+                name,
+                is_directory: kind == "d",
+                size,
+            })
+        })
+        .collect();
+    entries.sort_by(|a, b| {
+        b.is_directory
+            .cmp(&a.is_directory)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    entries
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn list_remote_projects() -> Result<Vec<RemoteProject>, String> {
+    Ok(load_store().projects)
+}
+
+/// Register a remote project
+#[tauri::command]
+pub async fn add_remote_project(input: RemoteProjectInput) -> Result<RemoteProject, String> {
+    if input.host.trim().is_empty() {
+        return Err("Host is required".to_string());
+    }
+    let user = input.user.filter(|u| !u.is_empty());
+    validate_ssh_target(input.host.trim(), user.as_deref())?;
+    if !input.remote_path.starts_with('/') {
+        return Err("Remote path must be absolute".to_string());
+    }
+    let remote_path = input.remote_path.trim_end_matches('/').to_string();
+    let project = RemoteProject {
+        id: uuid::Uuid::new_v4().to_string(),
+        name: input
+            .name
+            .filter(|n| !n.trim().is_empty())
+            .unwrap_or_else(|| {
+                remote_path
+                    .rsplit('/')
+                    .next()
+                    .unwrap_or(&remote_path)
+                    .to_string()
+            }),
+        host: input.host.trim().to_string(),
+        user,
+        port: input.port,
+        identity_file: input.identity_file.filter(|i| !i.is_empty()),
+        remote_path,
+        created_at: Utc::now(),
+    };
+    update_store(|store| {
+        store.projects.push(project.clone());
+        Ok(())
+    })?;
+    Ok(project)
+}
+
+#[tauri::command]
+pub async fn remove_remote_project(id: String) -> Result<(), String> {
+    if let Ok(project) = find_project(&id) {
+        let _ = disconnect(&project).await;
+    }
+    update_store(|store| {
+        store.projects.retain(|project| project.id != id);
+        Ok(())
+    })
+}
+
+/// Open (or reuse) the connection and check the project directory and git
+#[tauri::command]
+pub async fn test_remote_connection(id: String) -> Result<RemoteConnectionStatus, String> {
+    let project = find_project(&id)?;
+    let started = Instant::now();
+    let output = run_script(
+        &project,
+        "uname -sm; git --version 2>/dev/null || true",
+        None,
+    )
+    .await?;
+    let latency_ms = started.elapsed().as_millis() as u64;
+
+    if output.exit_code != Some(0) {
+        return Ok(RemoteConnectionStatus {
+            connected: false,
+            latency_ms,
+            platform: None,
+            git_version: None,
+            message: Some(output.stderr.trim().to_string()),
+        });
+    }
+    let mut lines = output.stdout.lines();
+    Ok(RemoteConnectionStatus {
+        connected: true,
+        latency_ms,
+        platform: lines.next().map(str::to_string),
+        git_version: lines.next().map(str::to_string),
+        message: None,
+    })
+}
+
+/// Close the pooled connection of a project's host
+#[tauri::command]
+pub async fn disconnect_remote_project(id: String) -> Result<(), String> {
+    disconnect(&find_project(&id)?).await
+}
+
+async fn disconnect(project: &RemoteProject) -> Result<(), String> {
+    if cfg!(target_os = "windows") {
+        return Ok(());
+    }
+    let mut cmd = Command::new("ssh");
+    cmd.args(ssh_options(project)?)
+        .args(["-O", "exit", "--"])
+        .arg(destination(project)?)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    // Fails when no master is running, which is fine
+    let _ = cmd.status().await;
+    Ok(())
+}
+
+/// Run git in the remote project and return its output
+#[tauri::command]
+pub async fn run_remote_git(id: String, args: Vec<String>) -> Result<RemoteCommandOutput, String> {
+    let project = find_project(&id)?;
+    run_script(&project, &command_line("git", &args), None).await
+}
+
+/// Run a program (git, an engine CLI, a build tool) in the remote project,
+/// streaming output as `remote-output:{run_id}` and finishing with
+/// `remote-complete:{run_id}` (the exit code)
+#[tauri::command]
+pub async fn run_remote_command(
+    app: AppHandle,
+    id: String,
+    run_id: String,
+    program: String,
+    args: Vec<String>,
+) -> Result<Option<i32>, String> {
+    let project = find_project(&id)?;
+    let slots = host_slots(&project);
+    let _permit = slots.acquire().await.map_err(|e| e.to_string())?;
+
+    let mut child = ssh_command(&project, &command_line(&program, &args))?
+        .spawn()
+        .map_err(|e| format!("Failed to start ssh: {}", e))?;
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    if let Ok(mut running) = RUNNING.lock() {
+        running.insert(run_id.clone(), cancel_tx);
+    }
+
+    let event = format!("remote-output:{}", run_id);
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(app.clone(), event.clone(), "stdout", stdout));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(app.clone(), event, "stderr", stderr));
+    }
+
+    let status = tokio::select! {
+        status = child.wait() => Some(status),
+        _ = cancel_rx => None,
+    };
+    let exit_code = match status {
+        Some(status) => status.map_err(|e| format!("ssh failed: {}", e))?.code(),
+        None => {
+            let _ = child.kill().await;
+            None
+        }
+    };
+    for reader in readers {
+        let _ = reader.await;
+    }
+    if let Ok(mut running) = RUNNING.lock() {
+        running.remove(&run_id);
+    }
+    event_batcher::flush_all(&app);
+    let _ = app.emit(&format!("remote-complete:{}", run_id), exit_code);
+    Ok(exit_code)
+}
+
+fn forward_lines<R>(
+    app: AppHandle,
+    event: String,
+    stream: &'static str,
+    pipe: R,
+) -> tokio::task::JoinHandle<()>
+where
+    R: tokio::io::AsyncRead + Send + Unpin + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(pipe).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let _ = event_batcher::emit(
+                &app,
+                &event,
+                RemoteOutputLine {
+                    stream: stream.to_string(),
+                    line,
+                },
+            );
+        }
+    })
+}
+
+/// Stop a command started with `run_remote_command`
+#[tauri::command]
+pub async fn cancel_remote_command(run_id: String) -> Result<(), String> {
+    let sender = RUNNING.lock().map_err(|e| e.to_string())?.remove(&run_id);
+    if let Some(sender) = sender {
+        let _ = sender.send(());
+    }
+    Ok(())
+}
+
+/// List a directory of the remote project (`path` is relative to the project root)
+#[tauri::command]
+pub async fn list_remote_directory(
+    id: String,
+    path: Option<String>,
+) -> Result<Vec<RemoteFileEntry>, String> {
+    let project = find_project(&id)?;
+    let relative = path.unwrap_or_default();
+    let dir = remote_file_path(&project, &relative)?;
+    let script = format!(
+        "find {} -mindepth 1 -maxdepth 1 -printf '%y\\t%s\\t%P\\n'",
+        shell_quote(&dir)
+    );
+    let output = ensure_success(run_script(&project, &script, None).await?)?;
+    Ok(parse_listing(&output.stdout, &relative))
+}
+
+#[tauri::command]
+pub async fn read_remote_file(id: String, path: String) -> Result<String, String> {
+    let project = find_project(&id)?;
+    let file = remote_file_path(&project, &path)?;
+    let output = run_script(&project, &format!("cat {}", shell_quote(&file)), None).await?;
+    Ok(ensure_success(output)?.stdout)
+}
+
+/// Write a file in the remote project, creating parent directories
+#[tauri::command]
+pub async fn write_remote_file(id: String, path: String, content: String) -> Result<(), String> {
+    let project = find_project(&id)?;
+    let file = remote_file_path(&project, &path)?;
+    let script = format!(
+        "mkdir -p \"$(dirname {file})\" && cat > {file}",
+        file = shell_quote(&file)
+    );
+    ensure_success(run_script(&project, &script, Some(content.as_bytes())).await?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn project() -> RemoteProject {
+        RemoteProject {
+            id: "p".to_string(),
+            name: "app".to_string(),
+            host: "devbox".to_string(),
+            user: Some("me".to_string()),
+            port: None,
+            identity_file: None,
+            remote_path: "/home/me/app/".to_string(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_shell_quoting() {
+        assert_eq!(shell_quote("it's"), r#"'it'\''s'"#);
+        assert_eq!(
+            command_line(
+                "git",
+                &["commit".to_string(), "-m".to_string(), "a b".to_string()]
+            ),
+            "'git' 'commit' '-m' 'a b'"
+        );
+    }
+
+    #[test]
+    fn test_ssh_target_validation() {
+        assert_eq!(destination(&project()).unwrap(), "me@devbox");
+        assert!(validate_ssh_target("-oProxyCommand=touch /tmp/x", None).is_err());
+        assert!(validate_ssh_target("devbox", Some("-lroot")).is_err());
+        assert!(validate_ssh_target("dev box", None).is_err());
+        assert!(validate_ssh_target("devbox", Some("me")).is_ok());
+    }
+
+    #[test]
+    fn test_remote_file_path() {
+        let project = project();
+        assert_eq!(remote_file_path(&project, "").unwrap(), "/home/me/app");
+        assert_eq!(
+            remote_file_path(&project, "src\\main.rs").unwrap(),
+            "/home/me/app/src/main.rs"
+        );
+        assert!(remote_file_path(&project, "../secrets").is_err());
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let entries = parse_listing("f\t12\tmain.rs\nd\t4096\tutils\n", "src");
+        assert_eq!(entries[0].name, "utils");
+        assert!(entries[0].is_directory);
+        assert_eq!(entries[1].path, "src/main.rs");
+        assert_eq!(entries[1].size, 12);
+    }
+}
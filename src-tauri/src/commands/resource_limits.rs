@@ -0,0 +1,28 @@
+//! Resource limit configuration and per-process usage reporting
+
+use crate::process::limits::{self, ProcessUsage, ResourceLimitsConfig};
+
+/// Get the resource limit configuration
+#[tauri::command]
+pub async fn get_resource_limits() -> Result<ResourceLimitsConfig, String> {
+    limits::load_config()
+}
+
+/// Update the resource limit configuration
+///
+/// New limits apply to engines spawned after the update; running engines keep
+/// the limits they were started with.
+#[tauri::command]
+pub async fn update_resource_limits(config: ResourceLimitsConfig) -> Result<(), String> {
+    limits::save_config(&config)?;
+    log::info!("[Limits] Resource limits updated: {:?}", config);
+    Ok(())
+}
+
+/// Get current memory/CPU usage of engine processes and their MCP servers
+#[tauri::command]
+pub async fn get_process_resource_usage() -> Result<Vec<ProcessUsage>, String> {
+    tokio::task::spawn_blocking(limits::collect_usage)
+        .await
+        .map_err(|e| format!("Failed to collect process usage: {}", e))
+}
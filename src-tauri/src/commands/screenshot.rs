@@ -0,0 +1,197 @@
+//! Screenshot capture for visual bug reports
+//!
+//! Captures a whole screen or a region of it through the OS capture APIs
+//! (via `xcap`) and saves it as PNG in the session's asset directory,
+//! `~/.anycode/session-assets/<session id>/`, so it can be attached to the next
+//! engine prompt like any other image.
+
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use xcap::Monitor;
+
+/// A display that can be captured
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenInfo {
+    pub id: u32,
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub is_primary: bool,
+}
+
+/// Region to capture, in physical pixels of the virtual desktop
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CaptureRegion {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// A saved screenshot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScreenshotResult {
+    pub file_path: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Asset directory of a session (`unsorted` before the session has an id)
+pub fn session_assets_dir(session_id: Option<&str>) -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Could not find home directory")?;
+    let name = session_id
+        .filter(|id| !id.is_empty())
+        .map(|id| id.replace(['/', '\\', ':'], "_"))
+        .unwrap_or_else(|| "unsorted".to_string());
+    let dir = home.join(".anycode").join("session-assets").join(name);
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create asset directory: {}", e))?;
+    Ok(dir)
+}
+
+fn screen_info(monitor: &Monitor) -> ScreenInfo {
+    ScreenInfo {
+        id: monitor.id(),
+        name: monitor.name().to_string(),
+        x: monitor.x(),
+        y: monitor.y(),
+        width: monitor.width(),
+        height: monitor.height(),
+        is_primary: monitor.is_primary(),
+    }
+}
+
+/// Cut a region (in desktop coordinates) out of a capture of `screen`
+fn crop_region(
+    image: &RgbaImage,
+    screen: &ScreenInfo,
+    region: CaptureRegion,
+) -> Result<RgbaImage, String> {
+    // Intersect the region with the screen, relative to the screen's origin
+    let rel_x = region.x as i64 - screen.x as i64;
+    let rel_y = region.y as i64 - screen.y as i64;
+    let left = rel_x.max(0);
+    let top = rel_y.max(0);
+    let right = (rel_x + region.width as i64).min(image.width() as i64);
+    let bottom = (rel_y + region.height as i64).min(image.height() as i64);
+    if right <= left || bottom <= top {
+        return Err("The selected region is outside the screen".to_string());
+    }
+    Ok(image::imageops::crop_imm(
+        image,
+        left as u32,
+        top as u32,
+        (right - left) as u32,
+        (bottom - top) as u32,
+    )
+    .to_image())
+}
+
+fn capture(
+    session_id: Option<String>,
+    monitor_id: Option<u32>,
+    region: Option<CaptureRegion>,
+) -> Result<ScreenshotResult, String> {
+    let monitors = Monitor::all().map_err(|e| format!("Failed to list screens: {}", e))?;
+    let monitor = match (monitor_id, region) {
+        (Some(id), _) => monitors.into_iter().find(|m| m.id() == id),
+        (None, Some(region)) => Monitor::from_point(region.x, region.y).ok(),
+        (None, None) => monitors.into_iter().find(|m| m.is_primary()),
+    }
+    .ok_or("Screen not found")?;
+    let screen = screen_info(&monitor);
+
+    let mut image = monitor
+        .capture_image()
+        .map_err(|e| format!("Failed to capture screen: {}", e))?;
+    if let Some(region) = region {
+        image = crop_region(&image, &screen, region)?;
+    }
+
+    let file_name = format!(
+        "screenshot_{}.png",
+        chrono::Utc::now().format("%Y%m%d_%H%M%S_%3f")
+    );
+    let path = session_assets_dir(session_id.as_deref())?.join(file_name);
+    image
+        .save(&path)
+        .map_err(|e| format!("Failed to save screenshot: {}", e))?;
+    log::info!(
+        "[Screenshot] Captured {}x{} from '{}' to {:?}",
+        image.width(),
+        image.height(),
+        screen.name,
+        path
+    );
+
+    Ok(ScreenshotResult {
+        file_path: path.to_string_lossy().to_string(),
+        width: image.width(),
+        height: image.height(),
+    })
+}
+
+/// List the screens that can be captured
+#[tauri::command]
+pub async fn list_screens() -> Result<Vec<ScreenInfo>, String> {
+    tokio::task::spawn_blocking(|| {
+        Monitor::all()
+            .map(|monitors| monitors.iter().map(screen_info).collect())
+            .map_err(|e| format!("Failed to list screens: {}", e))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// Capture a screen (the primary one by default) or a region of it into the session's assets
+#[tauri::command]
+pub async fn capture_screenshot(
+    session_id: Option<String>,
+    monitor_id: Option<u32>,
+    region: Option<CaptureRegion>,
+) -> Result<ScreenshotResult, String> {
+    tokio::task::spawn_blocking(move || capture(session_id, monitor_id, region))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crop_region_clamps_to_screen() {
+        let image = RgbaImage::new(100, 80);
+        let screen = ScreenInfo {
+            id: 1,
+            name: "test".to_string(),
+            x: 1000,
+            y: 0,
+            width: 100,
+            height: 80,
+            is_primary: false,
+        };
+        let region = CaptureRegion {
+            x: 1050,
+            y: 40,
+            width: 200,
+            height: 20,
+        };
+        let cropped = crop_region(&image, &screen, region).unwrap();
+        assert_eq!((cropped.width(), cropped.height()), (50, 20));
+
+        let outside = CaptureRegion {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        assert!(crop_region(&image, &screen, outside).is_err());
+    }
+}
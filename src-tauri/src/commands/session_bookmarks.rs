@@ -0,0 +1,319 @@
+//! Message bookmarks and pinned context
+//!
+//! Users can bookmark individual messages or tool results of a session and pin
+//! some of them. Bookmarks are stored per session in
+//! `~/.anycode/bookmarks/<engine>/<session_id>.json`, and
+//! `build_pinned_context` assembles the pinned items into a block the frontend
+//! prepends to the next prompt.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Default size budget of the pinned context block
+const DEFAULT_CONTEXT_CHARS: usize = 8000;
+
+/// What kind of item was bookmarked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BookmarkKind {
+    Message,
+    ToolResult,
+}
+
+/// A bookmarked message or tool result
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Bookmark {
+    pub id: String,
+    pub kind: BookmarkKind,
+    /// Index of the message in the session history
+    pub message_index: usize,
+    /// Engine-specific message UUID, when the history provides one
+    pub message_id: Option<String>,
+    /// "user", "assistant" or the tool name for tool results
+    pub role: String,
+    /// Snapshot of the bookmarked content, so it survives history rewinds
+    pub content: String,
+    pub note: Option<String>,
+    /// Pinned items are included in the context block for future turns
+    pub pinned: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bookmarks of a single session
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionBookmarks {
+    #[serde(default)]
+    pub bookmarks: Vec<Bookmark>,
+}
+
+/// Fields accepted when creating a bookmark
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBookmark {
+    pub kind: BookmarkKind,
+    pub message_index: usize,
+    pub message_id: Option<String>,
+    pub role: String,
+    pub content: String,
+    pub note: Option<String>,
+    #[serde(default)]
+    pub pinned: bool,
+}
+
+fn bookmarks_path(engine: &str, session_id: &str) -> Result<PathBuf, String> {
+    // Both end up in the path: only registered engine ids and plain file names
+    let engine = crate::engines::require(engine)?.id();
+    if session_id.is_empty() || session_id.contains(['/', '\\']) || session_id.contains("..") {
+        return Err(format!("Invalid session id: {}", session_id));
+    }
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?
+        .build("bookmarks")
+        .join(engine)
+        .join(format!("{}.json", session_id)))
+}
+
+fn load_bookmarks(engine: &str, session_id: &str) -> Result<SessionBookmarks, String> {
+    load_json_config(bookmarks_path(engine, session_id)?)
+}
+
+fn save_bookmarks(
+    engine: &str,
+    session_id: &str,
+    bookmarks: &SessionBookmarks,
+) -> Result<(), String> {
+    let path = bookmarks_path(engine, session_id)?;
+    if bookmarks.bookmarks.is_empty() {
+        if path.exists() {
+            std::fs::remove_file(&path)
+                .map_err(|e| format!("Failed to remove bookmarks file: {}", e))?;
+        }
+        return Ok(());
+    }
+    save_json_config(bookmarks, path)
+}
+
+/// Remove all bookmarks of a session (called when the session is deleted)
+pub fn remove_session_bookmarks(engine: &str, session_id: &str) {
+    if let Ok(path) = bookmarks_path(engine, session_id) {
+        if path.exists() {
+            if let Err(e) = std::fs::remove_file(&path) {
+                log::warn!(
+                    "[Bookmarks] Failed to remove bookmarks for {}: {}",
+                    session_id,
+                    e
+                );
+            }
+        }
+    }
+}
+
+/// Render pinned bookmarks as a context block, newest pins dropped first when over budget
+fn render_context(bookmarks: &[Bookmark], max_chars: usize) -> String {
+    let mut pinned: Vec<&Bookmark> = bookmarks.iter().filter(|b| b.pinned).collect();
+    if pinned.is_empty() {
+        return String::new();
+    }
+    pinned.sort_by_key(|b| b.message_index);
+
+    let header = "<pinned-context>\nThe user pinned the following items from earlier in this conversation:\n";
+    let footer = "</pinned-context>\n";
+    let mut block = String::from(header);
+
+    for bookmark in pinned {
+        let mut item = match bookmark.kind {
+            BookmarkKind::Message => {
+                format!("\n[{} message #{}]", bookmark.role, bookmark.message_index)
+            }
+            BookmarkKind::ToolResult => {
+                format!("\n[{} result #{}]", bookmark.role, bookmark.message_index)
+            }
+        };
+        if let Some(note) = bookmark.note.as_deref().filter(|n| !n.trim().is_empty()) {
+            item.push_str(&format!(" Note: {}", note.trim()));
+        }
+        item.push('\n');
+        item.push_str(bookmark.content.trim());
+        item.push('\n');
+
+        if block.chars().count() + item.chars().count() + footer.len() > max_chars {
+            log::info!(
+                "[Bookmarks] Pinned context truncated at message #{}",
+                bookmark.message_index
+            );
+            break;
+        }
+        block.push_str(&item);
+    }
+
+    block.push_str(footer);
+    block
+}
+
+/// List bookmarks of a session
+#[tauri::command]
+pub async fn list_session_bookmarks(
+    engine: String,
+    session_id: String,
+) -> Result<Vec<Bookmark>, String> {
+    Ok(load_bookmarks(&engine, &session_id)?.bookmarks)
+}
+
+/// Bookmark a message or tool result
+#[tauri::command]
+pub async fn add_session_bookmark(
+    engine: String,
+    session_id: String,
+    bookmark: NewBookmark,
+) -> Result<Bookmark, String> {
+    let mut stored = load_bookmarks(&engine, &session_id)?;
+
+    // Bookmarking the same item twice returns the existing bookmark
+    if let Some(existing) = stored
+        .bookmarks
+        .iter()
+        .find(|b| b.message_index == bookmark.message_index && b.kind == bookmark.kind)
+    {
+        return Ok(existing.clone());
+    }
+
+    let created = Bookmark {
+        id: uuid::Uuid::new_v4().to_string(),
+        kind: bookmark.kind,
+        message_index: bookmark.message_index,
+        message_id: bookmark.message_id,
+        role: bookmark.role,
+        content: bookmark.content,
+        note: bookmark.note,
+        pinned: bookmark.pinned,
+        created_at: Utc::now(),
+    };
+    stored.bookmarks.push(created.clone());
+    stored.bookmarks.sort_by_key(|b| b.message_index);
+    save_bookmarks(&engine, &session_id, &stored)?;
+
+    log::info!(
+        "[Bookmarks] Bookmarked message #{} in {} session {}",
+        created.message_index,
+        engine,
+        session_id
+    );
+    Ok(created)
+}
+
+/// Update the note and/or pinned flag of a bookmark
+#[tauri::command]
+pub async fn update_session_bookmark(
+    engine: String,
+    session_id: String,
+    bookmark_id: String,
+    note: Option<String>,
+    pinned: Option<bool>,
+) -> Result<Bookmark, String> {
+    let mut stored = load_bookmarks(&engine, &session_id)?;
+    let bookmark = stored
+        .bookmarks
+        .iter_mut()
+        .find(|b| b.id == bookmark_id)
+        .ok_or_else(|| format!("Bookmark not found: {}", bookmark_id))?;
+
+    if let Some(note) = note {
+        bookmark.note = Some(note).filter(|n| !n.trim().is_empty());
+    }
+    if let Some(pinned) = pinned {
+        bookmark.pinned = pinned;
+    }
+    let updated = bookmark.clone();
+
+    save_bookmarks(&engine, &session_id, &stored)?;
+    Ok(updated)
+}
+
+/// Remove a bookmark
+#[tauri::command]
+pub async fn remove_session_bookmark(
+    engine: String,
+    session_id: String,
+    bookmark_id: String,
+) -> Result<(), String> {
+    let mut stored = load_bookmarks(&engine, &session_id)?;
+    let before = stored.bookmarks.len();
+    stored.bookmarks.retain(|b| b.id != bookmark_id);
+    if stored.bookmarks.len() == before {
+        return Err(format!("Bookmark not found: {}", bookmark_id));
+    }
+    save_bookmarks(&engine, &session_id, &stored)
+}
+
+/// Assemble pinned items of a session into a context block for the next turn
+///
+/// Returns an empty string when nothing is pinned.
+#[tauri::command]
+pub async fn build_pinned_context(
+    engine: String,
+    session_id: String,
+    max_chars: Option<usize>,
+) -> Result<String, String> {
+    let stored = load_bookmarks(&engine, &session_id)?;
+    Ok(render_context(
+        &stored.bookmarks,
+        max_chars.unwrap_or(DEFAULT_CONTEXT_CHARS),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bookmark(index: usize, pinned: bool, content: &str) -> Bookmark {
+        Bookmark {
+            id: index.to_string(),
+            kind: BookmarkKind::Message,
+            message_index: index,
+            message_id: None,
+            role: "assistant".to_string(),
+            content: content.to_string(),
+            note: None,
+            pinned,
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_render_context_only_pinned_in_order() {
+        let bookmarks = vec![
+            bookmark(7, true, "use sqlite"),
+            bookmark(2, true, "design decision"),
+            bookmark(4, false, "not pinned"),
+        ];
+        let block = render_context(&bookmarks, DEFAULT_CONTEXT_CHARS);
+        assert!(block.starts_with("<pinned-context>"));
+        assert!(!block.contains("not pinned"));
+        assert!(block.find("design decision").unwrap() < block.find("use sqlite").unwrap());
+    }
+
+    #[test]
+    fn test_bookmarks_path_rejects_traversal() {
+        assert!(bookmarks_path("../../etc", "abc").is_err());
+        assert!(bookmarks_path("claude", "../abc").is_err());
+        let path = bookmarks_path("Codex", "abc").unwrap();
+        assert!(path.ends_with("bookmarks/codex/abc.json"));
+    }
+
+    #[test]
+    fn test_render_context_empty_and_budget() {
+        assert_eq!(render_context(&[bookmark(1, false, "x")], 1000), "");
+
+        let bookmarks = vec![
+            bookmark(1, true, "short"),
+            bookmark(2, true, &"y".repeat(500)),
+        ];
+        let block = render_context(&bookmarks, 300);
+        assert!(block.contains("short"));
+        assert!(!block.contains("yyyy"));
+        assert!(block.ends_with("</pinned-context>\n"));
+    }
+}
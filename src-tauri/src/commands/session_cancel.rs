@@ -0,0 +1,113 @@
+//! Session cancellation
+//!
+//! `cancel_session` stops a session whichever engine runs it. CLI engines are
+//! interrupted (SIGINT, or CTRL_BREAK on Windows) and get
+//! [`GRACE_PERIOD`](crate::process::interrupt::GRACE_PERIOD) to save their
+//! session before the rest of their process tree is killed; the command only
+//! returns once nothing of the session is left running. [`shutdown_all`] does
+//! the same for every session when the app exits, so closing the app no longer
+//! leaves engines behind.
+
+use futures::future::join_all;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::{claude, codex, gemini, ollama};
+use crate::process::interrupt::Shutdown;
+use crate::process::ProcessRegistryState;
+
+/// Engines whose sessions run in-process rather than as a CLI
+const CHAT_ENGINES: [&str; 2] = ["ollama", "openrouter"];
+
+/// A session stopped by `cancel_session`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CancelledSession {
+    pub engine: String,
+    /// How the engine process went away; None for in-process engines
+    pub shutdown: Option<Shutdown>,
+}
+
+/// Engine currently running the session
+async fn running_engine(app: &AppHandle, session_id: &str) -> Result<Option<&'static str>, String> {
+    let registry = app.state::<ProcessRegistryState>();
+    if registry.0.get_claude_session_by_id(session_id)?.is_some() {
+        return Ok(Some("claude"));
+    }
+    let codex = app.state::<codex::CodexProcessState>();
+    if codex.processes.lock().await.contains_key(session_id) {
+        return Ok(Some("codex"));
+    }
+    let gemini = app.state::<gemini::GeminiProcessState>();
+    if gemini.processes.lock().await.contains_key(session_id) {
+        return Ok(Some("gemini"));
+    }
+    Ok(ollama::session::running_engine(session_id))
+}
+
+/// Interrupt a running session and wait until its processes are gone
+///
+/// Returns None when no engine is running the session.
+#[tauri::command]
+pub async fn cancel_session(
+    session_id: String,
+    app: AppHandle,
+) -> Result<Option<CancelledSession>, String> {
+    let Some(engine) = running_engine(&app, &session_id).await? else {
+        log::warn!("Session {} is not running, nothing to cancel", session_id);
+        return Ok(None);
+    };
+    log::info!("Cancelling {} session {}", engine, session_id);
+
+    let shutdown = match engine {
+        "claude" => claude::cancel_claude(&app, Some(session_id)).await,
+        "codex" => codex::session::stop_codex(&app, &session_id).await,
+        "gemini" => gemini::session::stop_gemini(&app, &session_id).await,
+        _ => {
+            ollama::session::cancel(engine, Some(session_id), &app)?;
+            None
+        }
+    };
+    Ok(Some(CancelledSession {
+        engine: engine.to_string(),
+        shutdown,
+    }))
+}
+
+/// Stop every running session, waiting for engines to exit; called when the app exits
+pub async fn shutdown_all(app: &AppHandle) {
+    let registry = app.state::<ProcessRegistryState>().0.clone();
+    let run_ids: Vec<i64> = registry
+        .get_running_processes()
+        .unwrap_or_default()
+        .iter()
+        .map(|info| info.run_id)
+        .collect();
+
+    let (registered, codex, gemini) = tokio::join!(
+        join_all(
+            run_ids
+                .iter()
+                .map(|run_id| registry.interrupt_process(*run_id))
+        ),
+        codex::session::stop_all_codex(app),
+        gemini::session::stop_all_gemini(app),
+    );
+    for engine in CHAT_ENGINES {
+        if let Err(e) = ollama::session::cancel(engine, None, app) {
+            log::warn!("Failed to cancel {} sessions: {}", engine, e);
+        }
+    }
+
+    let forced = registered
+        .iter()
+        .filter(|outcome| matches!(outcome, Ok(Some(Shutdown::Forced))))
+        .count();
+    log::info!(
+        "Stopped {} registered processes ({} killed), {} Codex and {} Gemini sessions",
+        registered.len(),
+        forced,
+        codex,
+        gemini
+    );
+}
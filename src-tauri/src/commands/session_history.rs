@@ -0,0 +1,124 @@
+//! Session history
+//!
+//! Every engine run is recorded in `~/.anycode/sessions.db` (see
+//! [`crate::session_store`]). These commands list and load the recorded
+//! sessions and resume one with the engine that ran it, so a run cut short by
+//! closing the app can be picked up after a restart. Resumed runs use the
+//! model and mode last chosen for the project, falling back to the ones the
+//! session was recorded with.
+
+use tauri::AppHandle;
+
+use crate::commands::project_defaults::engine_defaults;
+use crate::commands::session_titles;
+use crate::commands::{claude, codex, gemini};
+use crate::session_store::{self, SessionDetail, StoredSession};
+
+/// Sessions listed when no limit is given
+const DEFAULT_LIMIT: usize = 100;
+
+/// Prompt sent when resuming without one
+const RESUME_PROMPT: &str = "Continue where you left off.";
+
+/// Recorded sessions, most recently updated first
+#[tauri::command]
+pub async fn list_sessions(
+    project_path: Option<String>,
+    engine: Option<String>,
+    limit: Option<usize>,
+) -> Result<Vec<StoredSession>, String> {
+    let mut sessions = session_store::list_sessions(
+        project_path.as_deref(),
+        engine.as_deref(),
+        limit.unwrap_or(DEFAULT_LIMIT),
+    )?;
+    let titles = session_titles::load_index();
+    for session in &mut sessions {
+        if let Some(title) = session_titles::title_for(&titles, &session.engine, &session.id) {
+            session.title = Some(title);
+        }
+    }
+    Ok(sessions)
+}
+
+/// A recorded session with its prompts, responses, tool calls and checkpoints
+#[tauri::command]
+pub async fn get_session(session_id: String) -> Result<SessionDetail, String> {
+    session_store::get_session(&session_id)?
+        .ok_or_else(|| format!("Session {} not found", session_id))
+}
+
+/// Resume a recorded session with the engine that ran it
+///
+/// Gemini can only resume its latest session, so a Gemini session is resumed
+/// exactly only if no other Gemini session was started in the project since.
+#[tauri::command]
+pub async fn resume_session(
+    session_id: String,
+    prompt: Option<String>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let session = session_store::get_session(&session_id)?
+        .ok_or_else(|| format!("Session {} not found", session_id))?
+        .session;
+    let prompt = prompt
+        .filter(|prompt| !prompt.trim().is_empty())
+        .unwrap_or_else(|| RESUME_PROMPT.to_string());
+    let defaults = engine_defaults(&session.project_path, &session.engine);
+    let model = defaults.model.or(session.model);
+    log::info!("Resuming {} session {}", session.engine, session.id);
+
+    match session.engine.as_str() {
+        "claude" => {
+            let plan_mode = defaults.permission_mode.as_deref() == Some("plan");
+            claude::resume_claude_code(
+                app,
+                session.project_path,
+                session.id,
+                prompt,
+                model.unwrap_or_else(|| "sonnet".to_string()),
+                Some(plan_mode),
+                None,
+                None,
+            )
+            .await
+        }
+        "codex" => {
+            let mode = match defaults.permission_mode.as_deref() {
+                Some("full-auto") => codex::CodexExecutionMode::FullAuto,
+                Some("danger-full-access") => codex::CodexExecutionMode::DangerFullAccess,
+                _ => codex::CodexExecutionMode::ReadOnly,
+            };
+            let options = codex::CodexExecutionOptions {
+                project_path: session.project_path,
+                prompt,
+                mode,
+                model,
+                json: true,
+                output_schema: None,
+                output_file: None,
+                skip_git_repo_check: false,
+                api_key: None,
+                session_id: None,
+                resume_last: false,
+            };
+            codex::resume_codex(session.id, options, app).await
+        }
+        "gemini" => {
+            let mut options = gemini::types::GeminiExecutionOptions {
+                project_path: session.project_path,
+                prompt,
+                session_id: Some(session.id),
+                ..Default::default()
+            };
+            if model.is_some() {
+                options.model = model;
+            }
+            if defaults.permission_mode.is_some() {
+                options.approval_mode = defaults.permission_mode;
+            }
+            gemini::execute_gemini(options, app).await
+        }
+        engine => Err(format!("Resuming {} sessions is not supported", engine)),
+    }
+}
@@ -0,0 +1,466 @@
+//! Session history search
+//!
+//! Full-text search over session transcripts of all engines. Searches are
+//! scoped to one project by default; the global mode spans every project on
+//! disk and returns project-qualified results, optionally filtered by engine,
+//! model and the file paths a session touched.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeSet, HashMap};
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use super::claude::normalize_path_for_comparison;
+
+/// Default number of results returned
+const DEFAULT_LIMIT: usize = 50;
+
+/// Characters of context shown on each side of the first match
+const SNIPPET_CONTEXT_CHARS: usize = 80;
+
+/// Keys in tool inputs that carry file paths
+const PATH_KEYS: &[&str] = &["file_path", "path", "notebook_path", "absolute_path"];
+
+/// Search options
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchOptions {
+    /// Search all projects instead of `project_path` only
+    #[serde(default)]
+    pub global: bool,
+    /// Project to search when `global` is false
+    pub project_path: Option<String>,
+    /// Restrict to these engines ("claude", "codex", "gemini")
+    pub engines: Option<Vec<String>>,
+    /// Case-insensitive substring of the model name
+    pub model: Option<String>,
+    /// Case-insensitive substring of a file path the session touched
+    pub file_path: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// A matching session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSearchHit {
+    pub engine: String,
+    pub session_id: String,
+    /// Project the session belongs to (None for Gemini sessions of unknown projects)
+    pub project_path: Option<String>,
+    pub title: Option<String>,
+    pub model: Option<String>,
+    /// ISO timestamp of the last message
+    pub last_activity: Option<String>,
+    /// Text around the first match
+    pub snippet: String,
+    pub match_count: usize,
+    pub files_touched: Vec<String>,
+}
+
+/// Text and metadata extracted from one transcript
+#[derive(Debug, Default)]
+struct SessionDigest {
+    session_id: String,
+    project_path: Option<String>,
+    model: Option<String>,
+    last_activity: Option<String>,
+    texts: Vec<String>,
+    files: BTreeSet<String>,
+}
+
+fn collect_paths(input: &Value, files: &mut BTreeSet<String>) {
+    if let Some(obj) = input.as_object() {
+        for key in PATH_KEYS {
+            if let Some(path) = obj.get(*key).and_then(|v| v.as_str()) {
+                files.insert(path.to_string());
+            }
+        }
+    }
+}
+
+/// Pull text and file paths out of a Claude/Codex style content value
+fn collect_content(content: &Value, digest: &mut SessionDigest) {
+    match content {
+        Value::String(text) => digest.texts.push(text.clone()),
+        Value::Array(items) => {
+            for item in items {
+                match item.get("type").and_then(|t| t.as_str()) {
+                    Some("text") | Some("input_text") | Some("output_text") => {
+                        if let Some(text) = item.get("text").and_then(|t| t.as_str()) {
+                            digest.texts.push(text.to_string());
+                        }
+                    }
+                    Some("tool_use") => {
+                        if let Some(input) = item.get("input") {
+                            collect_paths(input, &mut digest.files);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Files from a Codex apply_patch body ("*** Update File: src/main.rs")
+fn collect_patch_paths(patch: &str, files: &mut BTreeSet<String>) {
+    for line in patch.lines() {
+        for prefix in ["*** Update File: ", "*** Add File: ", "*** Delete File: "] {
+            if let Some(path) = line.strip_prefix(prefix) {
+                files.insert(path.trim().to_string());
+            }
+        }
+    }
+}
+
+fn read_jsonl(path: &Path) -> impl Iterator<Item = Value> {
+    std::fs::File::open(path)
+        .ok()
+        .map(|file| BufReader::new(file).lines())
+        .into_iter()
+        .flatten()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<Value>(&line).ok())
+}
+
+fn digest_claude_session(path: &Path) -> Option<SessionDigest> {
+    let mut digest = SessionDigest {
+        session_id: path.file_stem()?.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+
+    for entry in read_jsonl(path) {
+        if digest.project_path.is_none() {
+            digest.project_path = entry.get("cwd").and_then(|v| v.as_str()).map(String::from);
+        }
+        if let Some(ts) = entry.get("timestamp").and_then(|v| v.as_str()) {
+            digest.last_activity = Some(ts.to_string());
+        }
+        let Some(message) = entry.get("message") else {
+            continue;
+        };
+        if let Some(model) = message.get("model").and_then(|v| v.as_str()) {
+            digest.model = Some(model.to_string());
+        }
+        if let Some(content) = message.get("content") {
+            collect_content(content, &mut digest);
+        }
+    }
+
+    Some(digest)
+}
+
+fn digest_codex_session(path: &Path) -> Option<SessionDigest> {
+    let mut digest = SessionDigest::default();
+
+    for event in read_jsonl(path) {
+        if let Some(ts) = event.get("timestamp").and_then(|v| v.as_str()) {
+            digest.last_activity = Some(ts.to_string());
+        }
+        let payload = &event["payload"];
+        match event.get("type").and_then(|t| t.as_str()) {
+            Some("session_meta") => {
+                if let Some(id) = payload["id"].as_str() {
+                    digest.session_id = id.to_string();
+                }
+                digest.project_path = payload["cwd"].as_str().map(String::from);
+            }
+            Some("turn_context") => {
+                if let Some(model) = payload["model"].as_str() {
+                    digest.model = Some(model.to_string());
+                }
+            }
+            Some("response_item") => match payload["type"].as_str() {
+                Some("message") => {
+                    if let Some(content) = payload.get("content") {
+                        collect_content(content, &mut digest);
+                    }
+                }
+                Some("function_call") | Some("custom_tool_call") => {
+                    let args = payload["arguments"]
+                        .as_str()
+                        .or_else(|| payload["input"].as_str())
+                        .unwrap_or_default();
+                    collect_patch_paths(args, &mut digest.files);
+                    if let Ok(parsed) = serde_json::from_str::<Value>(args) {
+                        collect_paths(&parsed, &mut digest.files);
+                    }
+                }
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    if digest.session_id.is_empty() {
+        return None;
+    }
+    Some(digest)
+}
+
+fn digest_gemini_session(
+    path: &Path,
+    known_projects: &HashMap<String, String>,
+) -> Option<SessionDigest> {
+    let content = std::fs::read_to_string(path).ok()?;
+    let session: Value = serde_json::from_str(&content).ok()?;
+
+    let mut digest = SessionDigest {
+        session_id: session["sessionId"].as_str()?.to_string(),
+        last_activity: session["lastUpdated"].as_str().map(String::from),
+        project_path: session["projectHash"]
+            .as_str()
+            .and_then(|hash| known_projects.get(hash).cloned()),
+        ..Default::default()
+    };
+
+    for message in session["messages"].as_array().into_iter().flatten() {
+        if let Some(text) = message["content"].as_str() {
+            digest.texts.push(text.to_string());
+        }
+        if let Some(model) = message["model"].as_str() {
+            digest.model = Some(model.to_string());
+        }
+        for call in message["toolCalls"].as_array().into_iter().flatten() {
+            collect_paths(&call["args"], &mut digest.files);
+        }
+    }
+
+    Some(digest)
+}
+
+/// Collect session files of an engine
+fn engine_session_files(engine: &str) -> Vec<std::path::PathBuf> {
+    let root = match engine {
+        "claude" => dirs::home_dir().map(|h| h.join(".claude").join("projects")),
+        "codex" => super::codex::get_codex_sessions_dir().ok(),
+        "gemini" => super::gemini::config::get_gemini_dir()
+            .ok()
+            .map(|d| d.join("tmp")),
+        _ => None,
+    };
+    let Some(root) = root.filter(|r| r.exists()) else {
+        return Vec::new();
+    };
+
+    let (extension, max_depth) = match engine {
+        "claude" => ("jsonl", 2),
+        "codex" => ("jsonl", 4),
+        _ => ("json", 3),
+    };
+
+    walkdir::WalkDir::new(root)
+        .max_depth(max_depth)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .map(|e| e.into_path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some(extension))
+        .filter(|p| {
+            let name = p
+                .file_name()
+                .map(|n| n.to_string_lossy())
+                .unwrap_or_default();
+            match engine {
+                // Subagent transcripts are part of their parent session
+                "claude" => !name.starts_with("agent-"),
+                "gemini" => p
+                    .parent()
+                    .and_then(|d| d.file_name())
+                    .is_some_and(|d| d == "chats"),
+                _ => true,
+            }
+        })
+        .collect()
+}
+
+/// Case-insensitive match count and snippet around the first match
+fn match_texts(texts: &[String], query: &str) -> Option<(usize, String)> {
+    let query = query.to_lowercase();
+    let mut count = 0;
+    let mut snippet = None;
+
+    for text in texts {
+        let lower = text.to_lowercase();
+        let matches = lower.matches(&query).count();
+        if matches == 0 {
+            continue;
+        }
+        count += matches;
+        if snippet.is_none() {
+            // Work in chars so multi-byte text is never split
+            let chars: Vec<char> = text.chars().collect();
+            let lower_chars: Vec<char> = lower.chars().collect();
+            let query_chars: Vec<char> = query.chars().collect();
+            let start = lower_chars
+                .windows(query_chars.len().max(1))
+                .position(|w| w == query_chars.as_slice())
+                .unwrap_or(0)
+                .min(chars.len());
+            let from = start.saturating_sub(SNIPPET_CONTEXT_CHARS);
+            let to = (start + query_chars.len() + SNIPPET_CONTEXT_CHARS).min(chars.len());
+            let mut s: String = chars[from..to].iter().collect();
+            s = s.split_whitespace().collect::<Vec<_>>().join(" ");
+            if from > 0 {
+                s = format!("…{}", s);
+            }
+            if to < chars.len() {
+                s.push('…');
+            }
+            snippet = Some(s);
+        }
+    }
+
+    snippet.map(|s| (count, s))
+}
+
+fn search_blocking(
+    query: String,
+    options: SessionSearchOptions,
+) -> Result<Vec<SessionSearchHit>, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+
+    let scope = if options.global {
+        None
+    } else {
+        let project = options.project_path.as_deref().ok_or_else(|| {
+            "project_path is required unless global search is enabled".to_string()
+        })?;
+        Some(normalize_path_for_comparison(project))
+    };
+
+    let engines: Vec<String> = options
+        .engines
+        .clone()
+        .filter(|e| !e.is_empty())
+        .unwrap_or_else(|| vec!["claude".into(), "codex".into(), "gemini".into()]);
+    let model_filter = options.model.as_deref().map(str::to_lowercase);
+    let file_filter = options.file_path.as_deref().map(str::to_lowercase);
+    let limit = options.limit.unwrap_or(DEFAULT_LIMIT);
+
+    let mut digests: Vec<(String, SessionDigest)> = Vec::new();
+    for engine in engines.iter().filter(|e| e.as_str() != "gemini") {
+        for path in engine_session_files(engine) {
+            let digest = match engine.as_str() {
+                "claude" => digest_claude_session(&path),
+                "codex" => digest_codex_session(&path),
+                _ => None,
+            };
+            if let Some(digest) = digest {
+                digests.push((engine.clone(), digest));
+            }
+        }
+    }
+
+    if engines.iter().any(|e| e == "gemini") {
+        // Gemini stores sessions under a hash of the project path; resolve it
+        // against every project path the other engines (or the scope) know about
+        let mut known_projects: HashMap<String, String> = digests
+            .iter()
+            .filter_map(|(_, d)| d.project_path.clone())
+            .map(|p| (super::gemini::config::hash_project_path(&p), p))
+            .collect();
+        if let Some(project) = options.project_path.as_deref() {
+            known_projects.insert(
+                super::gemini::config::hash_project_path(project),
+                project.to_string(),
+            );
+        }
+        for path in engine_session_files("gemini") {
+            if let Some(digest) = digest_gemini_session(&path, &known_projects) {
+                digests.push(("gemini".to_string(), digest));
+            }
+        }
+    }
+
+    let titles = super::session_titles::load_index();
+    let mut hits: Vec<SessionSearchHit> = digests
+        .into_iter()
+        .filter(|(_, d)| match (&scope, &d.project_path) {
+            (None, _) => true,
+            (Some(scope), Some(path)) => normalize_path_for_comparison(path) == *scope,
+            (Some(_), None) => false,
+        })
+        .filter(|(_, d)| match &model_filter {
+            Some(filter) => d
+                .model
+                .as_deref()
+                .is_some_and(|m| m.to_lowercase().contains(filter)),
+            None => true,
+        })
+        .filter(|(_, d)| match &file_filter {
+            Some(filter) => d.files.iter().any(|f| f.to_lowercase().contains(filter)),
+            None => true,
+        })
+        .filter_map(|(engine, d)| {
+            let (match_count, snippet) = match_texts(&d.texts, &query)?;
+            Some(SessionSearchHit {
+                title: super::session_titles::title_for(&titles, &engine, &d.session_id),
+                engine,
+                session_id: d.session_id,
+                project_path: d.project_path,
+                model: d.model,
+                last_activity: d.last_activity,
+                snippet,
+                match_count,
+                files_touched: d.files.into_iter().collect(),
+            })
+        })
+        .collect();
+
+    // Most recent first (ISO timestamps sort lexicographically)
+    hits.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+    hits.truncate(limit);
+    Ok(hits)
+}
+
+/// Search session history, within one project or globally across all projects
+#[tauri::command]
+pub async fn search_session_history(
+    query: String,
+    options: Option<SessionSearchOptions>,
+) -> Result<Vec<SessionSearchHit>, String> {
+    let options = options.unwrap_or_default();
+    log::info!(
+        "[SessionSearch] Searching '{}' (global: {})",
+        query,
+        options.global
+    );
+
+    tokio::task::spawn_blocking(move || search_blocking(query, options))
+        .await
+        .map_err(|e| format!("Search task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match_texts_snippet_and_count() {
+        let texts = vec![
+            "nothing here".to_string(),
+            "The Bug was in the parser, the bug again".to_string(),
+        ];
+        let (count, snippet) = match_texts(&texts, "bug").unwrap();
+        assert_eq!(count, 2);
+        assert!(snippet.contains("Bug was in the parser"));
+        assert!(match_texts(&texts, "missing").is_none());
+    }
+
+    #[test]
+    fn test_collect_patch_paths() {
+        let mut files = BTreeSet::new();
+        collect_patch_paths(
+            "*** Begin Patch\n*** Update File: src/main.rs\n@@\n*** Add File: src/new.rs\n",
+            &mut files,
+        );
+        assert!(files.contains("src/main.rs"));
+        assert!(files.contains("src/new.rs"));
+    }
+}
@@ -0,0 +1,20 @@
+//! Typed session event stream settings
+
+use crate::session_runner::{self, SessionStreamSettings};
+
+/// Get the session stream settings
+#[tauri::command]
+pub async fn get_session_stream_settings() -> Result<SessionStreamSettings, String> {
+    Ok(session_runner::settings())
+}
+
+/// Update the session stream settings
+///
+/// Raw output switches immediately; typed events apply to engine processes
+/// started after the update.
+#[tauri::command]
+pub async fn update_session_stream_settings(settings: SessionStreamSettings) -> Result<(), String> {
+    session_runner::save_settings(settings)?;
+    log::info!("[SessionRunner] Stream settings updated: {:?}", settings);
+    Ok(())
+}
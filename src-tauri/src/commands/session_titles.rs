@@ -0,0 +1,366 @@
+//! Automatic conversation titles
+//!
+//! When a run of a session completes, the session runner asks the session's
+//! engine CLI for a short title of the run's prompt and reply (falling back to
+//! a local heuristic when the CLI is unavailable or the machine is offline)
+//! and stores it in `~/.anycode/session-titles.json`, unless the session has
+//! a title already. The frontend can do the same with `ensure_session_title`.
+//! Session listings of all three engines read their `title` from this index.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::AppHandle;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Number of messages (user + assistant) required before a title is generated
+const MIN_MESSAGES_FOR_TITLE: usize = 2;
+
+/// Maximum title length in characters
+const MAX_TITLE_CHARS: usize = 60;
+
+/// Conversation excerpt sent to the engine is capped to keep the request cheap
+const MAX_EXCERPT_CHARS: usize = 2000;
+
+lazy_static::lazy_static! {
+    static ref INDEX_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// Where a title came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TitleSource {
+    /// Generated by the session's engine
+    Engine,
+    /// Derived locally from the first message
+    Heuristic,
+    /// Set by the user, never overwritten automatically
+    Manual,
+}
+
+/// A stored session title
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionTitle {
+    pub title: String,
+    pub source: TitleSource,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// Title index, keyed by `<engine>:<session_id>`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionTitleIndex {
+    #[serde(default)]
+    pub titles: HashMap<String, SessionTitle>,
+}
+
+fn index_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("session-titles.json"))
+}
+
+fn index_key(engine: &str, session_id: &str) -> String {
+    format!("{}:{}", engine, session_id)
+}
+
+/// Load the title index
+pub fn load_index() -> SessionTitleIndex {
+    index_path().and_then(load_json_config).unwrap_or_default()
+}
+
+/// Look up the title of a session in a loaded index
+pub fn title_for(index: &SessionTitleIndex, engine: &str, session_id: &str) -> Option<String> {
+    index
+        .titles
+        .get(&index_key(engine, session_id))
+        .map(|entry| entry.title.clone())
+}
+
+fn update_index<F>(f: F) -> Result<(), String>
+where
+    F: FnOnce(&mut SessionTitleIndex),
+{
+    let _guard = INDEX_LOCK
+        .lock()
+        .map_err(|e| format!("Title index lock poisoned: {}", e))?;
+    let path = index_path()?;
+    let mut index: SessionTitleIndex = load_json_config(&path)?;
+    f(&mut index);
+    save_json_config(&index, &path)
+}
+
+fn store_title(engine: &str, session_id: &str, title: SessionTitle) -> Result<(), String> {
+    update_index(|index| {
+        index.titles.insert(index_key(engine, session_id), title);
+    })
+}
+
+/// Remove a session's title (called when the session is deleted)
+pub fn remove_title(engine: &str, session_id: &str) {
+    if let Err(e) = update_index(|index| {
+        index.titles.remove(&index_key(engine, session_id));
+    }) {
+        log::warn!(
+            "[SessionTitles] Failed to remove title for {}: {}",
+            session_id,
+            e
+        );
+    }
+}
+
+/// Normalize a candidate title: first non-empty line, no quotes or markdown, bounded length
+fn clean_title(raw: &str) -> Option<String> {
+    let line = raw
+        .lines()
+        .map(|l| l.trim())
+        .find(|l| !l.is_empty() && !l.starts_with("```"))?;
+
+    let line = line
+        .trim_start_matches(|c: char| c == '#' || c == '*' || c == '-' || c.is_whitespace())
+        .trim_start_matches("Title:")
+        .trim()
+        .trim_matches(|c: char| matches!(c, '"' | '\'' | '`' | '*' | '“' | '”' | '「' | '」'))
+        .trim_end_matches(['.', '。'])
+        .trim();
+
+    if line.is_empty() {
+        return None;
+    }
+
+    if line.chars().count() <= MAX_TITLE_CHARS {
+        return Some(line.to_string());
+    }
+
+    // Cut on a word boundary when there is one reasonably close to the limit
+    let truncated: String = line.chars().take(MAX_TITLE_CHARS).collect();
+    let cut = match truncated.rfind(' ') {
+        Some(pos) if pos > MAX_TITLE_CHARS / 2 => truncated[..pos].to_string(),
+        _ => truncated,
+    };
+    Some(format!("{}…", cut.trim_end()))
+}
+
+/// Derive a title from the first user message without calling an engine
+pub fn heuristic_title(messages: &[String]) -> Option<String> {
+    let first = messages.first()?;
+
+    // Skip fenced code so a pasted snippet doesn't become the title
+    let mut in_code = false;
+    let prose: Vec<&str> = first
+        .lines()
+        .filter(|line| {
+            if line.trim_start().starts_with("```") {
+                in_code = !in_code;
+                return false;
+            }
+            !in_code
+        })
+        .collect();
+    let text = prose.join(" ");
+
+    // First sentence
+    let sentence = text
+        .split_inclusive(['.', '?', '!', '。', '？', '！'])
+        .next()
+        .unwrap_or(&text);
+
+    let collapsed = sentence.split_whitespace().collect::<Vec<_>>().join(" ");
+    clean_title(&collapsed)
+}
+
+fn build_excerpt(messages: &[String]) -> String {
+    let mut excerpt = String::new();
+    for (i, message) in messages.iter().enumerate() {
+        let role = if i % 2 == 0 { "User" } else { "Assistant" };
+        excerpt.push_str(&format!("{}: {}\n\n", role, message.trim()));
+        if excerpt.chars().count() >= MAX_EXCERPT_CHARS {
+            break;
+        }
+    }
+    excerpt.chars().take(MAX_EXCERPT_CHARS).collect()
+}
+
+fn title_prompt(messages: &[String]) -> String {
+    format!(
+        "Write a short title (at most 8 words) for the following conversation. \
+         Use the language of the conversation. Reply with the title only, \
+         no quotes and no punctuation at the end.\n\n{}",
+        build_excerpt(messages)
+    )
+}
+
+/// Run a one-off, non-interactive prompt through an engine and return its reply
+pub(crate) async fn run_engine_prompt(
+    app: &AppHandle,
+    engine: &str,
+    model: Option<&str>,
+    prompt: &str,
+) -> Result<String, String> {
+    crate::engines::require(engine)?
+        .run_prompt(app, model, prompt)
+        .await
+}
+
+/// Ask the engine for a title
+async fn engine_title(
+    app: &AppHandle,
+    engine: &str,
+    messages: &[String],
+) -> Result<String, String> {
+    if crate::engines::require(engine)?.requires_network() {
+        super::connectivity::ensure_online("Title generation")?;
+    }
+
+    let reply = run_engine_prompt(app, engine, None, &title_prompt(messages)).await?;
+    clean_title(&reply).ok_or_else(|| format!("{} returned an empty title", engine))
+}
+
+/// Generate a title, preferring the engine and falling back to the heuristic
+async fn generate_title(
+    app: &AppHandle,
+    engine: &str,
+    messages: &[String],
+) -> Result<SessionTitle, String> {
+    let (title, source) = match engine_title(app, engine, messages).await {
+        Ok(title) => (title, TitleSource::Engine),
+        Err(e) => {
+            log::warn!(
+                "[SessionTitles] Engine title generation failed, using heuristic: {}",
+                e
+            );
+            let title = heuristic_title(messages)
+                .ok_or_else(|| "Not enough content to generate a title".to_string())?;
+            (title, TitleSource::Heuristic)
+        }
+    };
+
+    Ok(SessionTitle {
+        title,
+        source,
+        generated_at: Utc::now(),
+    })
+}
+
+/// Get all stored titles for an engine, keyed by session ID
+#[tauri::command]
+pub async fn get_session_titles(engine: String) -> Result<HashMap<String, SessionTitle>, String> {
+    let prefix = format!("{}:", engine);
+    Ok(load_index()
+        .titles
+        .into_iter()
+        .filter_map(|(key, title)| key.strip_prefix(&prefix).map(|id| (id.to_string(), title)))
+        .collect())
+}
+
+/// Generate a title for a session if it has enough turns and no title yet
+///
+/// `messages` are the session's text messages in order, starting with the
+/// first user prompt. Returns the existing or newly generated title, or
+/// `None` when the session is still too short.
+pub(crate) async fn ensure_title(
+    app: &AppHandle,
+    engine: &str,
+    session_id: &str,
+    messages: &[String],
+) -> Result<Option<SessionTitle>, String> {
+    if let Some(existing) = load_index()
+        .titles
+        .get(&index_key(engine, session_id))
+        .cloned()
+    {
+        return Ok(Some(existing));
+    }
+
+    if messages.len() < MIN_MESSAGES_FOR_TITLE {
+        return Ok(None);
+    }
+
+    let title = generate_title(app, engine, messages).await?;
+    log::info!(
+        "[SessionTitles] {} session {} titled '{}' ({:?})",
+        engine,
+        session_id,
+        title.title,
+        title.source
+    );
+    store_title(engine, session_id, title.clone())?;
+    Ok(Some(title))
+}
+
+/// Generate a title for a session if it has enough turns and no title yet
+#[tauri::command]
+pub async fn ensure_session_title(
+    app: AppHandle,
+    engine: String,
+    session_id: String,
+    messages: Vec<String>,
+) -> Result<Option<SessionTitle>, String> {
+    ensure_title(&app, &engine, &session_id, &messages).await
+}
+
+/// Regenerate a session title, replacing any existing one (including manual titles)
+#[tauri::command]
+pub async fn regenerate_session_title(
+    app: AppHandle,
+    engine: String,
+    session_id: String,
+    messages: Vec<String>,
+) -> Result<SessionTitle, String> {
+    let title = generate_title(&app, &engine, &messages).await?;
+    store_title(&engine, &session_id, title.clone())?;
+    Ok(title)
+}
+
+/// Set a session title by hand
+#[tauri::command]
+pub async fn set_session_title(
+    engine: String,
+    session_id: String,
+    title: String,
+) -> Result<SessionTitle, String> {
+    let title = clean_title(&title).ok_or_else(|| "Title cannot be empty".to_string())?;
+    let entry = SessionTitle {
+        title,
+        source: TitleSource::Manual,
+        generated_at: Utc::now(),
+    };
+    store_title(&engine, &session_id, entry.clone())?;
+    Ok(entry)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clean_title() {
+        assert_eq!(
+            clean_title("\n\"Fix login redirect bug.\"\n"),
+            Some("Fix login redirect bug".to_string())
+        );
+        assert_eq!(
+            clean_title("Title: Refactor parser"),
+            Some("Refactor parser".to_string())
+        );
+        assert_eq!(clean_title("   \n"), None);
+
+        let long = "word ".repeat(30);
+        let cleaned = clean_title(&long).unwrap();
+        assert!(cleaned.chars().count() <= MAX_TITLE_CHARS + 1);
+        assert!(cleaned.ends_with('…'));
+    }
+
+    #[test]
+    fn test_heuristic_title_skips_code() {
+        let messages = vec![
+            "```rust\nfn main() {}\n```\nWhy does this not compile? It used to.".to_string(),
+            "Because...".to_string(),
+        ];
+        assert_eq!(
+            heuristic_title(&messages),
+            Some("Why does this not compile?".to_string())
+        );
+    }
+}
@@ -1,9 +1,22 @@
 use log;
 use std::path::Path;
-use std::process::Command;
 
-#[cfg(target_os = "windows")]
-use std::os::windows::process::CommandExt;
+use crate::commands::branch_protection;
+use crate::commands::commit_metadata;
+use crate::commands::disk_space::{self, HeavyOperation};
+use crate::commands::git_backend::{
+    backend_for, BranchInfo, ResetMode, RevertFailure, StashEntry, TagInfo,
+};
+use crate::commands::git_diff::{parse_unified_diff, StructuredDiff};
+use crate::commands::git_executor::{GitOperation, LONG_TIMEOUT};
+use crate::commands::git_identity;
+use crate::commands::git_squash;
+use crate::commands::git_status_cache;
+use crate::commands::gitignore;
+use crate::commands::large_file_guard::{self, GuardedFile};
+use crate::commands::project_settings;
+use crate::commands::secret_scanner;
+use crate::commands::submodules;
 
 /// Check if a directory is a Git repository
 pub fn is_git_repo(project_path: &str) -> bool {
@@ -23,87 +36,50 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
         return Ok(());
     }
 
+    let backend = backend_for(project_path);
+
     // Need to initialize or create first commit
     if !has_git_dir {
-        log::info!("Initializing Git repository at: {}", project_path);
-
-        let mut cmd = Command::new("git");
-        cmd.args(["init"]);
-        cmd.current_dir(project_path);
-
-        #[cfg(target_os = "windows")]
-        cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-        let init_output = cmd
-            .output()
-            .map_err(|e| format!("Failed to init git: {}", e))?;
-
-        if !init_output.status.success() {
-            return Err(format!(
-                "Git init failed: {}",
-                String::from_utf8_lossy(&init_output.stderr)
-            ));
-        }
+        log::info!(
+            "Initializing Git repository at: {} (backend: {})",
+            project_path,
+            backend.name()
+        );
+        backend.init(project_path)?;
     } else {
         log::info!("Git repository exists but has no commits, creating initial commit");
     }
 
-    // Configure Git user if not set (needed for commits)
-    let mut config_name = Command::new("git");
-    config_name.args(["config", "user.name", "Claude Workbench"]);
-    config_name.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    config_name.creation_flags(0x08000000);
-    let _ = config_name.output();
-
-    let mut config_email = Command::new("git");
-    config_email.args(["config", "user.email", "ai@claude.workbench"]);
-    config_email.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    config_email.creation_flags(0x08000000);
-    let _ = config_email.output();
+    // Re-select now that the repository exists (e.g. signing needs the CLI)
+    let backend = backend_for(project_path);
+
+    // Configure Git user if not set (needed for commits); an existing identity is kept
+    if let Err(e) = git_identity::ensure_identity(project_path) {
+        log::warn!("Failed to configure git identity: {}", e);
+    }
+
+    // Keep dependencies and build output out of the initial commit and every checkpoint
+    if let Err(e) = gitignore::ensure_gitignore(project_path) {
+        log::warn!("Failed to create .gitignore: {}", e);
+    }
 
     // CRITICAL: Add all existing files first to preserve user code!
     log::info!("Adding all existing files to git staging area...");
-    let mut add_cmd = Command::new("git");
-    add_cmd.args(["add", "-A"]);
-    add_cmd.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    add_cmd.creation_flags(0x08000000);
-
-    let add_output = add_cmd
-        .output()
-        .map_err(|e| format!("Failed to add files: {}", e))?;
-
-    if !add_output.status.success() {
-        let stderr = String::from_utf8_lossy(&add_output.stderr);
-        log::warn!("Git add warning: {}", stderr);
+    if let Err(e) = backend.stage_all(project_path, None) {
+        log::warn!("Git add warning: {}", e);
         // Continue anyway, might just be no files to add
     }
 
-    // Create initial commit with all current files
-    // Use --allow-empty as fallback in case there are no files
-    let mut commit_cmd = Command::new("git");
-    commit_cmd.args([
-        "commit",
-        "--allow-empty",
-        "-m",
-        "[Claude Workbench] Initial commit - preserving existing code",
-    ]);
-    commit_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    commit_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let commit_output = commit_cmd
-        .output()
-        .map_err(|e| format!("Failed to create initial commit: {}", e))?;
-
-    if !commit_output.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_output.stderr);
-        log::error!("Git commit failed: {}", stderr);
-        return Err(format!("Failed to create initial commit: {}", stderr));
-    }
+    // Create initial commit with all current files (empty if there are no files)
+    backend
+        .commit(
+            project_path,
+            "[Claude Workbench] Initial commit - preserving existing code",
+        )
+        .map_err(|e| {
+            log::error!("Git commit failed: {}", e);
+            format!("Failed to create initial commit: {}", e)
+        })?;
 
     log::info!("Git repository initialized successfully with initial commit (all existing files preserved)");
     Ok(())
@@ -111,75 +87,107 @@ pub fn ensure_git_repo(project_path: &str) -> Result<(), String> {
 
 /// Get current HEAD commit hash
 pub fn git_current_commit(project_path: &str) -> Result<String, String> {
-    let mut cmd = Command::new("git");
-    cmd.args(["rev-parse", "HEAD"]);
-    cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to get current commit: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git rev-parse failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+    backend_for(project_path).current_commit(project_path)
+}
 
-    let commit = String::from_utf8(output.stdout)
-        .map_err(|e| format!("Invalid UTF-8 in commit hash: {}", e))?
-        .trim()
-        .to_string();
+/// Get the name of the current branch
+pub fn git_current_branch(project_path: &str) -> Result<String, String> {
+    backend_for(project_path).current_branch(project_path)
+}
+
+/// Get the URL of a remote (e.g. "origin")
+pub fn git_remote_url(project_path: &str, remote: &str) -> Result<String, String> {
+    backend_for(project_path).remote_url(project_path, remote)
+}
 
-    Ok(commit)
+/// Result of an AI commit
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommitOutcome {
+    pub committed: bool,
+    /// Branch created for the commit because the current one is protected
+    pub redirected_to: Option<String>,
+    /// Large or binary files the guard left out of the commit, routed to LFS or warned about
+    #[serde(default)]
+    pub guarded_files: Vec<GuardedFile>,
 }
 
 /// Commit all changes with a message
-/// Returns: Ok(true) if committed, Ok(false) if no changes, Err if failed
-pub fn git_commit_changes(project_path: &str, message: &str) -> Result<bool, String> {
-    // Stage all changes
-    let mut add_cmd = Command::new("git");
-    add_cmd.args(["add", "-A"]);
-    add_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    add_cmd.creation_flags(0x08000000);
-
-    let add_output = add_cmd
-        .output()
-        .map_err(|e| format!("Failed to git add: {}", e))?;
-
-    if !add_output.status.success() {
-        return Err(format!(
-            "Git add failed: {}",
-            String::from_utf8_lossy(&add_output.stderr)
-        ));
+pub fn git_commit_changes(project_path: &str, message: &str) -> Result<CommitOutcome, String> {
+    git_commit_changes_in(project_path, message, None)
+}
+
+/// Commit changes, staging only `pathspec` when given (e.g. a monorepo package)
+pub fn git_commit_changes_in(
+    project_path: &str,
+    message: &str,
+    pathspec: Option<&str>,
+) -> Result<CommitOutcome, String> {
+    let project = project_settings::load(project_path);
+    if !project.auto_commit_enabled() {
+        log::info!("Auto-commit is off for {}, leaving changes uncommitted", project_path);
+        return Ok(CommitOutcome::default());
+    }
+    let message = &project.prefixed_message(message);
+
+    // Fail before staging rather than leave a half-written object store
+    match disk_space::required_bytes(project_path, HeavyOperation::Checkpoint, pathspec) {
+        Ok(required) => {
+            disk_space::ensure_disk_space(project_path, required, HeavyOperation::Checkpoint)?
+        }
+        Err(e) => log::debug!("Could not estimate checkpoint size: {}", e),
+    }
+
+    let backend = backend_for(project_path);
+
+    // Stage all changes (or only those under the pathspec)
+    backend.stage_all(project_path, pathspec)?;
+
+    // Leave submodule pointers alone unless the project opted in
+    if let Err(e) = submodules::unstage_for_commit(project_path) {
+        log::warn!("Failed to keep submodules out of the commit: {}", e);
     }
 
+    // Keep huge files and build artifacts out of history
+    let guarded_files = large_file_guard::guard_staged_files(project_path);
+
+    // Keep credentials out of history
+    secret_scanner::check_staged(project_path)?;
+
+    // Protected branches get a session branch instead of the commit; only
+    // branch off once every check has passed
+    let original_branch = backend.current_branch(project_path).ok();
+    let redirected_to = branch_protection::redirect_commit(backend, project_path, message)?;
+
     // Commit changes (always create a commit, even if empty)
-    let mut commit_cmd = Command::new("git");
-    commit_cmd.args(["commit", "--allow-empty", "-m", message]);
-    commit_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    commit_cmd.creation_flags(0x08000000);
-
-    let commit_output = commit_cmd
-        .output()
-        .map_err(|e| format!("Failed to git commit: {}", e))?;
-
-    if !commit_output.status.success() {
-        return Err(format!(
-            "Git commit failed: {}",
-            String::from_utf8_lossy(&commit_output.stderr)
-        ));
+    let committed = match git_identity::settings().ai_committer {
+        Some(committer) => backend.commit_as(project_path, message, &committer),
+        None => backend.commit(project_path, message),
+    };
+    if let Err(e) = committed {
+        if let (Some(branch), Some(original)) = (&redirected_to, &original_branch) {
+            branch_protection::abandon_redirect(backend, project_path, original, branch);
+        }
+        return Err(e);
     }
 
     log::info!("Committed changes: {}", message);
-    Ok(true)
+
+    // Keep long sessions down to one commit when the project asks for it
+    if let (Some(max_commits), Some(session_id)) = (
+        project.max_commits_before_squash,
+        commit_metadata::parse(message).session_id,
+    ) {
+        if let Err(e) = git_squash::squash_over_limit(project_path, &session_id, max_commits) {
+            log::warn!("Failed to squash commits of session {}: {}", session_id, e);
+        }
+    }
+
+    Ok(CommitOutcome {
+        committed: true,
+        redirected_to,
+        guarded_files,
+    })
 }
 
 /// Check if two commits have different tree contents
@@ -189,29 +197,7 @@ pub fn git_has_changes_between_commits(
     commit_before: &str,
     commit_after: &str,
 ) -> Result<bool, String> {
-    let mut diff_cmd = Command::new("git");
-    diff_cmd.args(["diff", "--quiet", commit_before, commit_after]);
-    diff_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    diff_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let diff_output = diff_cmd
-        .output()
-        .map_err(|e| format!("Failed to diff commits: {}", e))?;
-
-    if diff_output.status.success() {
-        return Ok(false);
-    }
-
-    if diff_output.status.code() == Some(1) {
-        return Ok(true);
-    }
-
-    Err(format!(
-        "Git diff failed: {}",
-        String::from_utf8_lossy(&diff_output.stderr)
-    ))
+    backend_for(project_path).has_changes_between(project_path, commit_before, commit_after)
 }
 
 /// Reset repository to a specific commit
@@ -220,23 +206,7 @@ pub fn git_has_changes_between_commits(
 pub fn git_reset_hard(project_path: &str, commit: &str) -> Result<(), String> {
     log::info!("Resetting repository to commit: {}", commit);
 
-    let mut cmd = Command::new("git");
-    cmd.args(["reset", "--hard", commit]);
-    cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to reset: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git reset failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
+    backend_for(project_path).reset_hard(project_path, commit)?;
 
     log::info!("Successfully reset to commit: {}", commit);
     Ok(())
@@ -388,72 +358,32 @@ pub fn git_revert_range(
         commit_count
     );
 
-    // Try to revert the range
-    // Using --no-commit to stage all reverts, then commit once
-    // Using --no-merges to skip merge commits (they require -m parameter which is ambiguous)
-    let mut revert_cmd = Command::new("git");
-    revert_cmd.args([
-        "revert",
-        "--no-commit",
-        "--no-merges", // Skip merge commits to avoid "commit is a merge but no -m option" error
-        &format!("{}..{}", commit_before, commit_after),
-    ]);
-    revert_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    revert_cmd.creation_flags(0x08000000);
-
-    let revert_output = revert_cmd
-        .output()
-        .map_err(|e| format!("Failed to execute git revert: {}", e))?;
-
-    // Check for conflicts
-    if !revert_output.status.success() {
-        let stderr = String::from_utf8_lossy(&revert_output.stderr);
-
-        // Check if it's a conflict error
-        if stderr.contains("conflict") || stderr.contains("CONFLICT") {
-            log::warn!("[Precise Revert] Conflicts detected, attempting to abort");
-
-            // Abort the revert
-            let mut abort_cmd = Command::new("git");
-            abort_cmd.args(["revert", "--abort"]);
-            abort_cmd.current_dir(project_path);
-            #[cfg(target_os = "windows")]
-            abort_cmd.creation_flags(0x08000000);
-            let _ = abort_cmd.output();
+    let backend = backend_for(project_path);
 
+    // Undo the range in the working tree and index, then commit once
+    match backend.revert_range(project_path, commit_before, commit_after) {
+        Ok(()) => {}
+        Err(RevertFailure::Conflict(details)) => {
+            log::warn!("[Precise Revert] Conflicts detected, revert aborted");
             return Ok(RevertResult {
                 success: false,
                 commits_reverted: 0,
                 new_commit: None,
                 message: format!(
                     "撤回时发生冲突，无法自动完成。建议手动处理或使用'仅删除对话'模式。\n详情: {}",
-                    stderr.lines().take(3).collect::<Vec<_>>().join("\n")
+                    details.lines().take(3).collect::<Vec<_>>().join("\n")
                 ),
                 has_conflicts: true,
             });
         }
-
-        // Other error
-        return Err(format!("Git revert failed: {}", stderr));
+        Err(RevertFailure::Failed(e)) => return Err(format!("Git revert failed: {}", e)),
     }
 
     // Check if there are staged changes to commit
-    let mut status_cmd = Command::new("git");
-    status_cmd.args(["status", "--porcelain"]);
-    status_cmd.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    status_cmd.creation_flags(0x08000000);
-
-    let status_output = status_cmd
-        .output()
+    let has_changes = backend
+        .has_uncommitted_changes(project_path)
         .map_err(|e| format!("Failed to check status: {}", e))?;
 
-    let has_changes = !String::from_utf8_lossy(&status_output.stdout)
-        .trim()
-        .is_empty();
-
     if !has_changes {
         log::info!("[Precise Revert] No changes after revert (already at target state)");
         return Ok(RevertResult {
@@ -466,21 +396,10 @@ pub fn git_revert_range(
     }
 
     // Commit the reverted changes
-    let mut commit_cmd = Command::new("git");
-    commit_cmd.args(["commit", "-m", message]);
-    commit_cmd.current_dir(project_path);
-    #[cfg(target_os = "windows")]
-    commit_cmd.creation_flags(0x08000000);
-
-    let commit_output = commit_cmd
-        .output()
+    backend
+        .commit(project_path, message)
         .map_err(|e| format!("Failed to commit revert: {}", e))?;
 
-    if !commit_output.status.success() {
-        let stderr = String::from_utf8_lossy(&commit_output.stderr);
-        return Err(format!("Failed to commit revert: {}", stderr));
-    }
-
     // Get the new commit hash
     let new_commit = git_current_commit(project_path).ok();
 
@@ -501,7 +420,7 @@ pub fn git_revert_range(
 
 /// Tauri command wrapper for precise revert
 #[tauri::command]
-pub fn precise_revert_code(
+pub async fn precise_revert_code(
     project_path: String,
     commit_before: String,
     commit_after: String,
@@ -514,60 +433,86 @@ pub fn precise_revert_code(
         &commit_after[..8.min(commit_after.len())]
     );
 
-    git_revert_range(&project_path, &commit_before, &commit_after, &message)
+    GitOperation::new(&project_path, "git revert")
+        .run(move || git_revert_range(&project_path, &commit_before, &commit_after, &message))
+        .await
 }
 
-/// Save uncommitted changes to stash
-pub fn git_stash_save(project_path: &str, message: &str) -> Result<(), String> {
-    // Check if there are uncommitted changes
-    let mut status_cmd = Command::new("git");
-    status_cmd.args(["status", "--porcelain"]);
-    status_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    status_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
+/// Prefix of stash messages created by the workbench
+pub const WORKBENCH_STASH_MARKER: &str = "[Claude Workbench]";
 
-    let status_output = status_cmd
-        .output()
-        .map_err(|e| format!("Failed to check status: {}", e))?;
+/// Save uncommitted changes to stash
+/// Returns the stash commit, or None if there was nothing to stash
+pub fn git_stash_save(project_path: &str, message: &str) -> Result<Option<String>, String> {
+    let backend = backend_for(project_path);
 
-    if status_output.stdout.is_empty() {
+    // Check if there are uncommitted changes
+    if !git_status_cache::has_uncommitted_changes(project_path)? {
         log::debug!("No uncommitted changes to stash");
-        return Ok(()); // No changes to stash
+        return Ok(None); // No changes to stash
     }
 
     log::info!("Stashing uncommitted changes: {}", message);
 
-    let mut stash_cmd = Command::new("git");
-    stash_cmd.args(["stash", "save", "-u", message]);
-    stash_cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    stash_cmd.creation_flags(0x08000000); // CREATE_NO_WINDOW
-
-    let output = stash_cmd
-        .output()
-        .map_err(|e| format!("Failed to stash: {}", e))?;
+    let message = format!("{} {}", WORKBENCH_STASH_MARKER, message);
+    match backend.stash_save(project_path, &message) {
+        Ok(stash) => Ok(Some(stash)),
+        Err(e) => {
+            log::warn!("Git stash warning: {}", e);
+            Ok(None)
+        }
+    }
+}
 
-    if !output.status.success() {
+/// Pop the stash created before an operation, if it is still the newest one
+fn restore_workbench_stash(project_path: &str, stash: &str) {
+    let backend = backend_for(project_path);
+    let on_top = backend
+        .stash_list(project_path)
+        .map(|stashes| stashes.first().is_some_and(|s| s.commit == stash))
+        .unwrap_or(false);
+    if !on_top {
         log::warn!(
-            "Git stash warning: {}",
-            String::from_utf8_lossy(&output.stderr)
+            "Auto-stash {} is no longer the newest stash, leaving it in place",
+            stash
         );
+        return;
+    }
+    match backend.stash_pop(project_path, 0) {
+        Ok(()) => log::info!("Restored auto-stashed changes"),
+        Err(e) => log::warn!(
+            "Failed to restore auto-stash, it is kept in the stash list: {}",
+            e
+        ),
     }
+}
 
+/// Roll back to `commit` after a failed operation and restore the changes stashed before it
+pub fn git_reset_hard_and_restore(
+    project_path: &str,
+    commit: &str,
+    auto_stash: Option<&str>,
+) -> Result<(), String> {
+    git_reset_hard(project_path, commit)?;
+    if let Some(stash) = auto_stash {
+        restore_workbench_stash(project_path, stash);
+    }
     Ok(())
 }
 
 /// Tauri command: Check and initialize Git repository
 #[tauri::command]
-pub fn check_and_init_git(project_path: String) -> Result<bool, String> {
-    let was_not_initialized = !is_git_repo(&project_path);
+pub async fn check_and_init_git(project_path: String) -> Result<bool, String> {
+    GitOperation::new(&project_path, "git init")
+        .run(move || {
+            let was_not_initialized = !is_git_repo(&project_path);
 
-    // Always call ensure_git_repo - it will check for commits too
-    ensure_git_repo(&project_path)?;
+            // Always call ensure_git_repo - it will check for commits too
+            ensure_git_repo(&project_path)?;
 
-    Ok(was_not_initialized)
+            Ok(was_not_initialized)
+        })
+        .await
 }
 
 // ============================================================================
@@ -592,36 +537,36 @@ pub struct ResetSafetyInfo {
     pub safe_to_proceed: bool,
     /// Warning message if not safe
     pub warning: Option<String>,
+    /// Submodules whose pointer or checkout the reset would change
+    #[serde(default)]
+    pub submodules_affected: Vec<String>,
+    /// Number of files that differ between HEAD and the target commit
+    #[serde(default)]
+    pub files_changed: usize,
+    /// Files that differ between HEAD and the target commit, at most 50
+    #[serde(default)]
+    pub changed_files: Vec<String>,
+    /// Lines in HEAD that are not in the target commit
+    #[serde(default)]
+    pub lines_lost: usize,
+    /// Lines of the target commit that come back
+    #[serde(default)]
+    pub lines_restored: usize,
+    /// Uncommitted changes exist (they are stashed before a reset)
+    #[serde(default)]
+    pub has_uncommitted_changes: bool,
 }
 
+/// Changed files listed in a reset safety check
+const MAX_RESET_CHANGED_FILES: usize = 50;
+
 /// Count commits between two references
 pub fn git_commit_count_between(
     project_path: &str,
     from_commit: &str,
     to_commit: &str,
 ) -> Result<usize, String> {
-    let mut cmd = Command::new("git");
-    cmd.args(["rev-list", "--count", &format!("{}..{}", from_commit, to_commit)]);
-    cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000);
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to count commits: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git rev-list failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let count_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-    count_str
-        .parse::<usize>()
-        .map_err(|e| format!("Failed to parse commit count: {}", e))
+    backend_for(project_path).commit_count_between(project_path, from_commit, to_commit)
 }
 
 /// Get commit messages between two references
@@ -630,44 +575,25 @@ pub fn git_log_between(
     from_commit: &str,
     to_commit: &str,
 ) -> Result<Vec<String>, String> {
-    let mut cmd = Command::new("git");
-    cmd.args([
-        "log",
-        "--oneline",
-        "--format=%s",
-        &format!("{}..{}", from_commit, to_commit),
-    ]);
-    cmd.current_dir(project_path);
-
-    #[cfg(target_os = "windows")]
-    cmd.creation_flags(0x08000000);
-
-    let output = cmd
-        .output()
-        .map_err(|e| format!("Failed to get git log: {}", e))?;
-
-    if !output.status.success() {
-        return Err(format!(
-            "Git log failed: {}",
-            String::from_utf8_lossy(&output.stderr)
-        ));
-    }
-
-    let log_str = String::from_utf8_lossy(&output.stdout);
-    let messages: Vec<String> = log_str
-        .lines()
-        .filter(|line| !line.trim().is_empty())
-        .map(|s| s.to_string())
-        .collect();
-
-    Ok(messages)
+    backend_for(project_path).log_between(project_path, from_commit, to_commit)
 }
 
 /// Check if a reset operation is safe
 /// This prevents accidentally reverting to a much older version when
 /// multiple engines or user manual commits are involved
 #[tauri::command]
-pub fn check_reset_safety(
+pub async fn check_reset_safety(
+    project_path: String,
+    target_commit: String,
+    current_engine: String,
+) -> Result<ResetSafetyInfo, String> {
+    GitOperation::new(&project_path, "reset safety check")
+        .shared()
+        .run(move || reset_safety(project_path, target_commit, current_engine))
+        .await
+}
+
+fn reset_safety(
     project_path: String,
     target_commit: String,
     current_engine: String,
@@ -679,6 +605,11 @@ pub fn check_reset_safety(
     );
 
     let current_head = git_current_commit(&project_path)?;
+    let has_uncommitted_changes = git_status_cache::has_uncommitted_changes(&project_path)
+        .unwrap_or_else(|e| {
+            log::warn!("[Reset Safety] Could not check for uncommitted changes: {}", e);
+            false
+        });
 
     // If target is same as HEAD, it's safe
     if current_head == target_commit {
@@ -689,14 +620,38 @@ pub fn check_reset_safety(
             commits_summary: vec![],
             safe_to_proceed: true,
             warning: None,
+            submodules_affected: vec![],
+            files_changed: 0,
+            changed_files: vec![],
+            lines_lost: 0,
+            lines_restored: 0,
+            has_uncommitted_changes,
         });
     }
 
+    // What the reset does to the files: target..HEAD additions are lost, deletions come back
+    let impact = backend_for(&project_path)
+        .diff_commits(&project_path, &target_commit, &current_head)
+        .map(|patch| parse_unified_diff(&patch))
+        .unwrap_or_else(|e| {
+            log::warn!("[Reset Safety] Could not diff against {}: {}", target_commit, e);
+            StructuredDiff::default()
+        });
+
     // Count commits between target and HEAD
     let commits_to_lose = git_commit_count_between(&project_path, &target_commit, &current_head)?;
 
     // Get commit messages to analyze
-    let commits_summary = git_log_between(&project_path, &target_commit, &current_head)?;
+    let messages = backend_for(&project_path).messages_between(
+        &project_path,
+        &target_commit,
+        &current_head,
+    )?;
+    let commits_summary: Vec<String> = messages
+        .iter()
+        .map(|message| message.lines().next().unwrap_or("").trim().to_string())
+        .filter(|subject| !subject.is_empty())
+        .collect();
 
     // Analyze commits for other engines and user commits
     let mut has_other_engine_commits = false;
@@ -704,38 +659,38 @@ pub fn check_reset_safety(
     let mut other_engine_count = 0;
     let mut user_commit_count = 0;
 
-    for msg in &commits_summary {
-        let msg_lower = msg.to_lowercase();
-
-        // Check for other engine commits
-        let is_claude = msg.contains("[Claude") || msg.contains("[Claude Code]");
-        let is_codex = msg.contains("[Codex]");
-        let is_gemini = msg.contains("[Gemini]");
-        let is_workbench = msg.contains("[Claude Workbench]");
+    for message in &messages {
+        // Engine from the commit trailers, or the configurable subject prefixes
+        let engine = commit_metadata::parse(message).engine;
 
-        let is_current_engine = match current_engine.as_str() {
-            "claude" => is_claude || is_workbench,
-            "codex" => is_codex,
-            "gemini" => is_gemini,
-            _ => false,
+        let is_current_engine = match engine.as_deref() {
+            // Workbench commits (initial commit, rollbacks) count as Claude's own
+            Some("workbench") => current_engine == "claude",
+            Some(engine) => engine == current_engine,
+            None => false,
         };
 
-        let is_any_engine = is_claude || is_codex || is_gemini || is_workbench;
-
-        if is_any_engine && !is_current_engine {
+        if engine.is_some() && !is_current_engine {
             has_other_engine_commits = true;
             other_engine_count += 1;
         }
 
         // Check for user commits (no engine marker)
-        if !is_any_engine && !msg_lower.contains("merge") {
+        let subject = message.lines().next().unwrap_or("").to_lowercase();
+        if engine.is_none() && !subject.contains("merge") {
             has_user_commits = true;
             user_commit_count += 1;
         }
     }
 
+    let submodules_affected =
+        submodules::affected_between(&project_path, &target_commit, &current_head);
+
     // Determine if safe to proceed
-    let safe_to_proceed = !has_other_engine_commits && !has_user_commits && commits_to_lose <= 5;
+    let safe_to_proceed = !has_other_engine_commits
+        && !has_user_commits
+        && commits_to_lose <= 5
+        && submodules_affected.is_empty();
 
     // Generate warning message
     let warning = if !safe_to_proceed {
@@ -762,6 +717,14 @@ pub fn check_reset_safety(
             ));
         }
 
+        if !submodules_affected.is_empty() {
+            warnings.push(format!(
+                "检测到 {} 个子模块的指针将被改变：{}",
+                submodules_affected.len(),
+                submodules_affected.join(", ")
+            ));
+        }
+
         Some(warnings.join("；"))
     } else {
         None
@@ -782,6 +745,414 @@ pub fn check_reset_safety(
         commits_summary: commits_summary.into_iter().take(10).collect(), // Limit to 10 for display
         safe_to_proceed,
         warning,
+        submodules_affected,
+        files_changed: impact.files_changed,
+        changed_files: impact
+            .files
+            .into_iter()
+            .take(MAX_RESET_CHANGED_FILES)
+            .map(|file| file.path)
+            .collect(),
+        lines_lost: impact.additions,
+        lines_restored: impact.deletions,
+        has_uncommitted_changes,
     })
 }
 
+
+// ============================================================================
+// Non-destructive Rollback (检测到其他引擎或用户提交时，替代 reset --hard)
+// ============================================================================
+
+fn validate_revision(commit: &str) -> Result<(), String> {
+    if commit.is_empty() || commit.starts_with('-') {
+        return Err(format!("Invalid commit: {}", commit));
+    }
+    Ok(())
+}
+
+/// Move HEAD to `commit`, keeping the undone changes staged
+#[tauri::command]
+pub async fn git_reset_soft(project_path: String, commit: String) -> Result<(), String> {
+    validate_revision(&commit)?;
+    GitOperation::new(&project_path, "git reset --soft")
+        .run(move || {
+            log::info!("Soft reset to commit: {}", commit);
+            backend_for(&project_path).reset(&project_path, &commit, ResetMode::Soft)
+        })
+        .await
+}
+
+/// Move HEAD and the index to `commit`, keeping the undone changes in the working tree
+#[tauri::command]
+pub async fn git_reset_mixed(project_path: String, commit: String) -> Result<(), String> {
+    validate_revision(&commit)?;
+    GitOperation::new(&project_path, "git reset --mixed")
+        .run(move || {
+            log::info!("Mixed reset to commit: {}", commit);
+            backend_for(&project_path).reset(&project_path, &commit, ResetMode::Mixed)
+        })
+        .await
+}
+
+/// Undo a single commit with a new commit, keeping everything after it
+#[tauri::command]
+pub async fn git_revert_commit(
+    project_path: String,
+    commit: String,
+) -> Result<RevertResult, String> {
+    validate_revision(&commit)?;
+    GitOperation::new(&project_path, "git revert")
+        .run(move || {
+            match backend_for(&project_path).parent_count(&project_path, &commit)? {
+                0 => {
+                    return Err(format!(
+                        "Cannot revert the root commit {}: it has no parent to revert to",
+                        commit
+                    ))
+                }
+                1 => {}
+                _ => {
+                    return Err(format!(
+                        "Cannot revert merge commit {}: it has more than one parent, revert the merged commits instead",
+                        commit
+                    ))
+                }
+            }
+            let parent = format!("{}~1", commit);
+            let subject = git_log_between(&project_path, &parent, &commit)
+                .ok()
+                .and_then(|subjects| subjects.into_iter().next())
+                .unwrap_or_else(|| commit.clone());
+            let message = format!("[Revert] Revert \"{}\"", subject);
+
+            git_revert_range(&project_path, &parent, &commit, &message)
+        })
+        .await
+}
+
+/// Normalize a repository-relative path; rejects anything that could leave the repository
+pub(crate) fn normalize_restore_path(path: &str) -> Result<String, String> {
+    let normalized = path.trim().replace('\\', "/");
+    let normalized = normalized.trim_start_matches("./").trim_end_matches('/');
+    if normalized.is_empty()
+        || normalized.starts_with('/')
+        || normalized.starts_with('-')
+        || normalized.contains(':')
+        || normalized.split('/').any(|part| part == "..")
+    {
+        return Err(format!("Invalid path: {}", path));
+    }
+    Ok(normalized.to_string())
+}
+
+/// Restore only `paths` (files or directories) from `commit`, leaving every other change in place
+///
+/// The restored files end up modified in the index and working tree, ready to be committed;
+/// files that did not exist at `commit` are deleted.
+#[tauri::command]
+pub async fn git_restore_files(
+    project_path: String,
+    commit: String,
+    paths: Vec<String>,
+) -> Result<(), String> {
+    validate_revision(&commit)?;
+    if paths.is_empty() {
+        return Err("No paths to restore".to_string());
+    }
+    let paths = paths
+        .iter()
+        .map(|path| normalize_restore_path(path))
+        .collect::<Result<Vec<_>, _>>()?;
+    GitOperation::new(&project_path, "git restore")
+        .run(move || {
+            log::info!("Restoring {} path(s) from commit: {}", paths.len(), commit);
+            backend_for(&project_path).restore_paths(&project_path, &commit, &paths)
+        })
+        .await
+}
+
+// ============================================================================
+// Stash Management (恢复自动暂存的更改)
+// ============================================================================
+
+/// A stash entry, marked when the workbench created it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StashInfo {
+    #[serde(flatten)]
+    pub entry: StashEntry,
+    pub created_by_workbench: bool,
+}
+
+/// List stashes, newest first
+#[tauri::command]
+pub async fn git_stash_list(project_path: String) -> Result<Vec<StashInfo>, String> {
+    GitOperation::new(&project_path, "git stash list")
+        .shared()
+        .run(move || {
+            Ok(backend_for(&project_path)
+                .stash_list(&project_path)?
+                .into_iter()
+                .map(|entry| StashInfo {
+                    created_by_workbench: entry.message.starts_with(WORKBENCH_STASH_MARKER),
+                    entry,
+                })
+                .collect())
+        })
+        .await
+}
+
+/// Apply `stash@{index}`, keeping it in the list
+#[tauri::command]
+pub async fn git_stash_apply(project_path: String, index: usize) -> Result<(), String> {
+    GitOperation::new(&project_path, "git stash apply")
+        .run(move || {
+            backend_for(&project_path).stash_apply(&project_path, index)?;
+            log::info!("Applied stash@{{{}}} in {}", index, project_path);
+            Ok(())
+        })
+        .await
+}
+
+/// Apply `stash@{index}` and remove it from the list
+#[tauri::command]
+pub async fn git_stash_pop(project_path: String, index: usize) -> Result<(), String> {
+    GitOperation::new(&project_path, "git stash pop")
+        .run(move || {
+            backend_for(&project_path).stash_pop(&project_path, index)?;
+            log::info!("Popped stash@{{{}}} in {}", index, project_path);
+            Ok(())
+        })
+        .await
+}
+
+/// Discard `stash@{index}`
+#[tauri::command]
+pub async fn git_stash_drop(project_path: String, index: usize) -> Result<(), String> {
+    GitOperation::new(&project_path, "git stash drop")
+        .run(move || {
+            backend_for(&project_path).stash_drop(&project_path, index)?;
+            log::info!("Dropped stash@{{{}}} in {}", index, project_path);
+            Ok(())
+        })
+        .await
+}
+
+// ============================================================================
+// Branch Management (每个会话可使用独立分支)
+// ============================================================================
+
+pub(crate) fn validate_branch_name(name: &str) -> Result<(), String> {
+    let valid = !name.starts_with('-') && git2::Branch::name_is_valid(name).unwrap_or(false);
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid branch name: {}", name))
+    }
+}
+
+/// List local branches
+#[tauri::command]
+pub async fn git_list_branches(project_path: String) -> Result<Vec<BranchInfo>, String> {
+    GitOperation::new(&project_path, "git branch --list")
+        .shared()
+        .run(move || backend_for(&project_path).list_branches(&project_path))
+        .await
+}
+
+/// Create a branch at `start_point` (HEAD by default), optionally switching to it
+#[tauri::command]
+pub async fn git_create_branch(
+    project_path: String,
+    name: String,
+    start_point: Option<String>,
+    switch_to: Option<bool>,
+) -> Result<(), String> {
+    validate_branch_name(&name)?;
+    GitOperation::new(&project_path, "git branch")
+        .run(move || {
+            let backend = backend_for(&project_path);
+            backend.create_branch(&project_path, &name, start_point.as_deref())?;
+            log::info!("Created branch {} in {}", name, project_path);
+
+            if switch_to.unwrap_or(false) {
+                backend.switch_branch(&project_path, &name)?;
+                log::info!("Switched to branch {}", name);
+            }
+            Ok(())
+        })
+        .await
+}
+
+/// Switch to an existing branch; fails if local changes would be overwritten
+#[tauri::command]
+pub async fn git_switch_branch(project_path: String, name: String) -> Result<(), String> {
+    validate_branch_name(&name)?;
+    GitOperation::new(&project_path, "git switch")
+        .timeout(LONG_TIMEOUT)
+        .run(move || {
+            backend_for(&project_path).switch_branch(&project_path, &name)?;
+            log::info!("Switched to branch {} in {}", name, project_path);
+            Ok(())
+        })
+        .await
+}
+
+/// Delete a branch; unmerged branches need `force`
+#[tauri::command]
+pub async fn git_delete_branch(
+    project_path: String,
+    name: String,
+    force: Option<bool>,
+) -> Result<(), String> {
+    validate_branch_name(&name)?;
+    GitOperation::new(&project_path, "git branch --delete")
+        .run(move || {
+            backend_for(&project_path).delete_branch(
+                &project_path,
+                &name,
+                force.unwrap_or(false),
+            )?;
+            log::info!("Deleted branch {} in {}", name, project_path);
+            Ok(())
+        })
+        .await
+}
+
+// ============================================================================
+// Tag Management
+// ============================================================================
+
+pub(crate) fn validate_tag_name(name: &str) -> Result<(), String> {
+    let valid = !name.starts_with('-')
+        && git2::Reference::is_valid_name(&format!("refs/tags/{}", name));
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("Invalid tag name: {}", name))
+    }
+}
+
+/// List tags with the commits they point at
+#[tauri::command]
+pub async fn git_tag_list(project_path: String) -> Result<Vec<TagInfo>, String> {
+    GitOperation::new(&project_path, "git tag --list")
+        .shared()
+        .run(move || backend_for(&project_path).list_tags(&project_path))
+        .await
+}
+
+/// Tag `commit` (HEAD by default); a message makes it an annotated tag
+#[tauri::command]
+pub async fn git_tag_create(
+    project_path: String,
+    name: String,
+    commit: Option<String>,
+    message: Option<String>,
+) -> Result<(), String> {
+    validate_tag_name(&name)?;
+    let message = message.filter(|m| !m.trim().is_empty());
+    GitOperation::new(&project_path, "git tag")
+        .run(move || {
+            backend_for(&project_path).create_tag(
+                &project_path,
+                &name,
+                commit.as_deref(),
+                message.as_deref(),
+            )?;
+            log::info!("Created tag {} in {}", name, project_path);
+            Ok(())
+        })
+        .await
+}
+
+/// Delete a tag
+#[tauri::command]
+pub async fn git_tag_delete(project_path: String, name: String) -> Result<(), String> {
+    validate_tag_name(&name)?;
+    GitOperation::new(&project_path, "git tag --delete")
+        .run(move || {
+            backend_for(&project_path).delete_tag(&project_path, &name)?;
+            log::info!("Deleted tag {} in {}", name, project_path);
+            Ok(())
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn commit_file(project_path: &str, contents: &str, message: &str) -> String {
+        std::fs::write(Path::new(project_path).join("a.txt"), contents).unwrap();
+        let backend = backend_for(project_path);
+        backend.stage_all(project_path, None).unwrap();
+        backend.commit(project_path, message).unwrap();
+        git_current_commit(project_path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reset_and_revert_commands() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        backend_for(&path).init(&path).unwrap();
+        let root = commit_file(&path, "one\n", "first");
+        let second = commit_file(&path, "two\n", "second");
+        let repo = git2::Repository::open(dir.path()).unwrap();
+        let status = || repo.status_file(Path::new("a.txt")).unwrap();
+
+        git_reset_soft(path.clone(), root.clone()).await.unwrap();
+        assert_eq!(git_current_commit(&path).unwrap(), root);
+        assert_eq!(status(), git2::Status::INDEX_MODIFIED);
+        git_reset_soft(path.clone(), second.clone()).await.unwrap();
+        assert_eq!(status(), git2::Status::CURRENT);
+
+        git_reset_mixed(path.clone(), root.clone()).await.unwrap();
+        assert_eq!(git_current_commit(&path).unwrap(), root);
+        assert_eq!(status(), git2::Status::WT_MODIFIED);
+        git_reset_mixed(path.clone(), second.clone()).await.unwrap();
+        assert_eq!(status(), git2::Status::CURRENT);
+        assert!(git_reset_mixed(path.clone(), "--hard".to_string())
+            .await
+            .is_err());
+
+        let result = git_revert_commit(path.clone(), second.clone())
+            .await
+            .unwrap();
+        assert!(result.success);
+        assert_eq!(result.commits_reverted, 1);
+        assert_eq!(result.new_commit, git_current_commit(&path).ok());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "one\n"
+        );
+
+        let error = git_revert_commit(path.clone(), root.clone())
+            .await
+            .unwrap_err();
+        assert!(error.contains("root commit"), "{}", error);
+
+        // A merge of the root commit into HEAD
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        let other = repo
+            .find_commit(git2::Oid::from_str(&root).unwrap())
+            .unwrap();
+        let signature = git2::Signature::now("Test", "test@example.com").unwrap();
+        let merge = repo
+            .commit(
+                Some("HEAD"),
+                &signature,
+                &signature,
+                "merge",
+                &head.tree().unwrap(),
+                &[&head, &other],
+            )
+            .unwrap();
+        let error = git_revert_commit(path.clone(), merge.to_string())
+            .await
+            .unwrap_err();
+        assert!(error.contains("merge commit"), "{}", error);
+        assert_eq!(git_current_commit(&path).unwrap(), merge.to_string());
+    }
+}
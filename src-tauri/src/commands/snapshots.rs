@@ -0,0 +1,580 @@
+//! Snapshot undo backend
+//!
+//! For projects that should never be turned into a git repository, the
+//! checkpoint and rewind features can run on file snapshots instead (the
+//! `undoBackend` project setting). A snapshot is a manifest mapping every file
+//! of the project to a content-addressed copy, stored under
+//! `<project>/.anycode/snapshots`:
+//!
+//! - `objects/<aa>/<sha256 rest>`: file contents, stored once however many
+//!   snapshots share them
+//! - `manifests/<id>.json`: the file list, named after its own hash, so an
+//!   unchanged project yields the same id again
+//! - `index.json`: size, mtime and hash of each file at the last snapshot, so
+//!   files left alone since aren't read and hashed again
+//!
+//! Snapshot ids take the place of commit hashes in prompt records and
+//! checkpoints. Restoring writes back changed and deleted files and removes
+//! files created since, after snapshotting the current state so the restore
+//! can be undone too. Objects no manifest refers to (left behind by an
+//! interrupted snapshot) are pruned after a restore. Dependency and build directories (`node_modules`,
+//! `target`, ...) and files over 50 MB are left out, and never touched.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::Metadata;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::commands::git_diff::{parse_unified_diff, StructuredDiff};
+use crate::commands::project_settings::{self, UndoBackend};
+use crate::commands::simple_git::normalize_restore_path;
+
+/// Directories never snapshotted, at any depth
+const EXCLUDED_DIRS: &[&str] = &[
+    ".git",
+    ".anycode",
+    "node_modules",
+    "target",
+    ".venv",
+    "__pycache__",
+    ".next",
+];
+
+const MAX_FILE_BYTES: u64 = 50 * 1024 * 1024;
+
+/// Files modified this recently are hashed again even when size and mtime
+/// match the index, as a write within the mtime granularity wouldn't show
+const RACY_WINDOW: Duration = Duration::from_secs(2);
+
+/// Project path -> content hash of the file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+struct Manifest {
+    files: BTreeMap<String, String>,
+    /// Files with the executable bit set; skipped when empty so manifests
+    /// written before modes were recorded keep their ids
+    #[serde(default, skip_serializing_if = "BTreeSet::is_empty")]
+    executable: BTreeSet<String>,
+}
+
+/// What a file looked like when it was last hashed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexEntry {
+    size: u64,
+    modified: SystemTime,
+    hash: String,
+}
+
+/// Project path -> the file as of the last snapshot
+type Index = BTreeMap<String, IndexEntry>;
+
+/// What restoring a snapshot changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotRestore {
+    /// Files written back
+    pub restored: usize,
+    /// Files created after the snapshot, now deleted
+    pub removed: usize,
+    /// Snapshot of the state before the restore
+    pub backup: String,
+}
+
+/// Whether the project keeps its undo history in snapshots rather than git
+pub fn enabled(project_path: &str) -> bool {
+    project_settings::load(project_path).undo_backend == Some(UndoBackend::Snapshots)
+}
+
+fn store_root(project_path: &str) -> PathBuf {
+    Path::new(project_path).join(".anycode").join("snapshots")
+}
+
+fn object_path(root: &Path, hash: &str) -> PathBuf {
+    root.join("objects").join(&hash[..2]).join(&hash[2..])
+}
+
+fn manifest_path(root: &Path, id: &str) -> PathBuf {
+    root.join("manifests").join(format!("{}.json", id))
+}
+
+fn index_path(root: &Path) -> PathBuf {
+    root.join("index.json")
+}
+
+fn hash_bytes(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+fn is_hash(value: &str) -> bool {
+    value.len() == 64 && value.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+/// Write through a temporary file, so a crash never leaves a truncated object
+fn write_atomic(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut temp = path.as_os_str().to_owned();
+    temp.push(".anycode-tmp");
+    let temp = PathBuf::from(temp);
+    std::fs::write(&temp, bytes)
+        .map_err(|e| format!("Failed to write {}: {}", temp.display(), e))?;
+    std::fs::rename(&temp, path).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &Metadata) -> bool {
+    false
+}
+
+/// Set or clear the executable bits, for whoever may read the file
+#[cfg(unix)]
+fn set_executable(path: &Path, executable: bool) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = std::fs::metadata(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?
+        .permissions();
+    let mode = permissions.mode();
+    let mode = if executable {
+        mode | ((mode & 0o444) >> 2)
+    } else {
+        mode & !0o111
+    };
+    if mode != permissions.mode() {
+        permissions.set_mode(mode);
+        std::fs::set_permissions(path, permissions)
+            .map_err(|e| format!("Failed to set mode of {}: {}", path.display(), e))?;
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path, _executable: bool) -> Result<(), String> {
+    Ok(())
+}
+
+/// Files a snapshot covers, by project path (`/`-separated)
+fn project_files(project_path: &str) -> BTreeMap<String, (PathBuf, Metadata)> {
+    let root = Path::new(project_path);
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_dir()
+                || !EXCLUDED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let metadata = entry.metadata().ok()?;
+            if metadata.len() > MAX_FILE_BYTES {
+                return None;
+            }
+            let relative = entry.path().strip_prefix(root).ok()?;
+            let relative = relative.to_string_lossy().replace('\\', "/");
+            Some((relative, (entry.into_path(), metadata)))
+        })
+        .collect()
+}
+
+fn load_manifest(project_path: &str, id: &str) -> Result<Manifest, String> {
+    if !is_hash(id) {
+        return Err(format!("Invalid snapshot: {}", id));
+    }
+    let path = manifest_path(&store_root(project_path), id);
+    let content = std::fs::read(&path).map_err(|_| format!("Snapshot not found: {}", id))?;
+    serde_json::from_slice(&content).map_err(|e| format!("Snapshot {} is corrupt: {}", id, e))
+}
+
+fn read_object(project_path: &str, hash: &str) -> Result<Vec<u8>, String> {
+    if !is_hash(hash) {
+        return Err(format!("Invalid object: {}", hash));
+    }
+    std::fs::read(object_path(&store_root(project_path), hash))
+        .map_err(|e| format!("Snapshot object {} is missing: {}", hash, e))
+}
+
+fn load_index(root: &Path) -> Index {
+    std::fs::read(index_path(root))
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default()
+}
+
+/// The hash the index recorded for a file, if it is unchanged since
+fn indexed_hash<'a>(
+    index: &'a Index,
+    root: &Path,
+    path: &str,
+    metadata: &Metadata,
+) -> Option<&'a str> {
+    let entry = index.get(path)?;
+    let unchanged = entry.size == metadata.len()
+        && metadata.modified().is_ok_and(|m| m == entry.modified)
+        && is_hash(&entry.hash)
+        && object_path(root, &entry.hash).exists();
+    unchanged.then_some(entry.hash.as_str())
+}
+
+/// Snapshot the project as it is on disk; returns the snapshot id
+pub fn take_snapshot(project_path: &str) -> Result<String, String> {
+    let root = store_root(project_path);
+    let started = SystemTime::now();
+    let previous = load_index(&root);
+    let mut index = Index::new();
+    let mut manifest = Manifest::default();
+    let mut hashed = 0;
+    for (path, (full_path, metadata)) in project_files(project_path) {
+        let hash = match indexed_hash(&previous, &root, &path, &metadata) {
+            Some(hash) => hash.to_string(),
+            None => {
+                let bytes = match std::fs::read(&full_path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        log::debug!("[Snapshots] Skipping unreadable {}: {}", path, e);
+                        continue;
+                    }
+                };
+                hashed += 1;
+                let hash = hash_bytes(&bytes);
+                let object = object_path(&root, &hash);
+                if !object.exists() {
+                    write_atomic(&object, &bytes)?;
+                }
+                hash
+            }
+        };
+        if let Ok(modified) = metadata.modified() {
+            let settled = started
+                .duration_since(modified)
+                .is_ok_and(|age| age >= RACY_WINDOW);
+            if settled {
+                let entry = IndexEntry {
+                    size: metadata.len(),
+                    modified,
+                    hash: hash.clone(),
+                };
+                index.insert(path.clone(), entry);
+            }
+        }
+        if is_executable(&metadata) {
+            manifest.executable.insert(path.clone());
+        }
+        manifest.files.insert(path, hash);
+    }
+
+    let content = serde_json::to_vec(&manifest).map_err(|e| e.to_string())?;
+    let id = hash_bytes(&content);
+    let path = manifest_path(&root, &id);
+    if !path.exists() {
+        write_atomic(&path, &content)?;
+    }
+    // Only a cache: a lost index means the next snapshot hashes everything
+    match serde_json::to_vec(&index) {
+        Ok(content) => {
+            if let Err(e) = write_atomic(&index_path(&root), &content) {
+                log::debug!("[Snapshots] Could not save the index: {}", e);
+            }
+        }
+        Err(e) => log::debug!("[Snapshots] Could not save the index: {}", e),
+    }
+    log::debug!(
+        "[Snapshots] Snapshot {} of {} file(s) in {} ({} hashed)",
+        &id[..8],
+        manifest.files.len(),
+        project_path,
+        hashed
+    );
+    Ok(id)
+}
+
+/// Delete objects no manifest refers to; returns how many were removed
+fn prune_objects(project_path: &str) -> Result<usize, String> {
+    let root = store_root(project_path);
+    let manifests = std::fs::read_dir(root.join("manifests"))
+        .map_err(|e| format!("Failed to list snapshots: {}", e))?;
+    let mut referenced = BTreeSet::new();
+    for entry in manifests.filter_map(|entry| entry.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(id) = name.strip_suffix(".json").filter(|id| is_hash(id)) else {
+            continue;
+        };
+        // An unreadable manifest could refer to anything, so prune nothing
+        let manifest = load_manifest(project_path, id)?;
+        referenced.extend(manifest.files.into_values());
+    }
+
+    let mut removed = 0;
+    let objects = root.join("objects");
+    for entry in WalkDir::new(&objects)
+        .min_depth(2)
+        .max_depth(2)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+    {
+        let Ok(relative) = entry.path().strip_prefix(&objects) else {
+            continue;
+        };
+        let hash = relative.to_string_lossy().replace(['/', '\\'], "");
+        if referenced.contains(&hash) {
+            continue;
+        }
+        match std::fs::remove_file(entry.path()) {
+            Ok(()) => removed += 1,
+            Err(e) => log::debug!(
+                "[Snapshots] Could not prune {}: {}",
+                entry.path().display(),
+                e
+            ),
+        }
+    }
+    Ok(removed)
+}
+
+/// Bring the project back to snapshot `id`
+pub fn restore_snapshot(project_path: &str, id: &str) -> Result<SnapshotRestore, String> {
+    let target = load_manifest(project_path, id)?;
+    let backup = take_snapshot(project_path)?;
+    let current = load_manifest(project_path, &backup)?;
+    let root = Path::new(project_path);
+
+    let mut restored = 0;
+    for (path, hash) in &target.files {
+        let executable = target.executable.contains(path);
+        let same_content = current.files.get(path) == Some(hash);
+        if same_content && current.executable.contains(path) == executable {
+            continue;
+        }
+        let full_path = root.join(normalize_restore_path(path)?);
+        if !same_content {
+            write_atomic(&full_path, &read_object(project_path, hash)?)?;
+        }
+        set_executable(&full_path, executable)?;
+        restored += 1;
+    }
+    let mut removed = 0;
+    for path in current.files.keys() {
+        if target.files.contains_key(path) {
+            continue;
+        }
+        let full_path = root.join(normalize_restore_path(path)?);
+        std::fs::remove_file(&full_path)
+            .map_err(|e| format!("Failed to delete {}: {}", full_path.display(), e))?;
+        removed += 1;
+    }
+    match prune_objects(project_path) {
+        Ok(0) => {}
+        Ok(pruned) => log::debug!("[Snapshots] Pruned {} unreferenced object(s)", pruned),
+        Err(e) => log::warn!("[Snapshots] Could not prune objects: {}", e),
+    }
+
+    log::info!(
+        "[Snapshots] Restored {} in {}: {} written, {} deleted (backup {})",
+        &id[..8],
+        project_path,
+        restored,
+        removed,
+        &backup[..8]
+    );
+    Ok(SnapshotRestore {
+        restored,
+        removed,
+        backup,
+    })
+}
+
+/// A `git diff`-style patch of one file; None stands for a missing side
+fn file_patch(path: &str, old: Option<&[u8]>, new: Option<&[u8]>) -> Result<String, String> {
+    let side = |prefix: &str, content: Option<&[u8]>| match content {
+        Some(_) => format!("{}/{}", prefix, path),
+        None => "/dev/null".to_string(),
+    };
+    let mut patch = format!(
+        "diff --git a/{0} b/{0}\n--- {1}\n+++ {2}\n",
+        path,
+        side("a", old),
+        side("b", new)
+    );
+    let (old, new) = (old.unwrap_or_default(), new.unwrap_or_default());
+    if old.contains(&0) || new.contains(&0) {
+        patch.push_str(&format!("Binary files a/{0} and b/{0} differ\n", path));
+        return Ok(patch);
+    }
+    let mut hunks =
+        git2::Patch::from_buffers(old, None, new, None, None).map_err(|e| e.to_string())?;
+    let text = hunks.to_buf().map_err(|e| e.to_string())?;
+    let text = String::from_utf8_lossy(&text);
+    if let Some(start) = text.find("@@ ") {
+        patch.push_str(&text[start..]);
+    }
+    Ok(patch)
+}
+
+/// Changes from snapshot `from` to snapshot `to`, or to the files on disk
+pub fn diff(project_path: &str, from: &str, to: Option<&str>) -> Result<StructuredDiff, String> {
+    let to = match to {
+        Some(to) => to.to_string(),
+        None => take_snapshot(project_path)?,
+    };
+    let old = load_manifest(project_path, from)?;
+    let new = load_manifest(project_path, &to)?;
+    let paths: BTreeSet<&String> = old.files.keys().chain(new.files.keys()).collect();
+
+    let mut patch = String::new();
+    for path in paths {
+        let (old_hash, new_hash) = (old.files.get(path), new.files.get(path));
+        if old_hash == new_hash {
+            continue;
+        }
+        let old_bytes = old_hash
+            .map(|hash| read_object(project_path, hash))
+            .transpose()?;
+        let new_bytes = new_hash
+            .map(|hash| read_object(project_path, hash))
+            .transpose()?;
+        patch.push_str(&file_patch(
+            path,
+            old_bytes.as_deref(),
+            new_bytes.as_deref(),
+        )?);
+    }
+    Ok(parse_unified_diff(&patch))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_diff::FileChangeKind;
+
+    #[test]
+    fn test_snapshot_restore_and_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::create_dir_all(dir.path().join("node_modules/dep")).unwrap();
+        std::fs::write(dir.path().join("src/a.txt"), "one\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "keep\n").unwrap();
+        std::fs::write(dir.path().join("node_modules/dep/index.js"), "x").unwrap();
+
+        let before = take_snapshot(&path).unwrap();
+        assert_eq!(take_snapshot(&path).unwrap(), before);
+        assert!(!dir.path().join(".git").exists());
+
+        std::fs::write(dir.path().join("src/a.txt"), "two\n").unwrap();
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        std::fs::write(dir.path().join("c.txt"), "new\n").unwrap();
+
+        let changes = diff(&path, &before, None).unwrap();
+        let summary: Vec<(&str, FileChangeKind)> = changes
+            .files
+            .iter()
+            .map(|file| (file.path.as_str(), file.change))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("b.txt", FileChangeKind::Deleted),
+                ("c.txt", FileChangeKind::Added),
+                ("src/a.txt", FileChangeKind::Modified),
+            ]
+        );
+        assert_eq!((changes.additions, changes.deletions), (2, 2));
+
+        let restore = restore_snapshot(&path, &before).unwrap();
+        assert_eq!((restore.restored, restore.removed), (2, 1));
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("src/a.txt")).unwrap(),
+            "one\n"
+        );
+        assert!(dir.path().join("b.txt").exists());
+        assert!(!dir.path().join("c.txt").exists());
+        assert!(dir.path().join("node_modules/dep/index.js").exists());
+        assert_eq!(take_snapshot(&path).unwrap(), before);
+
+        // The restore itself can be undone
+        restore_snapshot(&path, &restore.backup).unwrap();
+        assert!(dir.path().join("c.txt").exists());
+        assert!(load_manifest(&path, "../etc").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_snapshot_keeps_modes_and_prunes_objects() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, "#!/bin/sh\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+        let mode = || std::fs::metadata(&script).unwrap().permissions().mode() & 0o777;
+
+        let before = take_snapshot(&path).unwrap();
+        let manifest = load_manifest(&path, &before).unwrap();
+        assert!(manifest.executable.contains("run.sh"));
+
+        std::fs::write(&script, "echo changed\n").unwrap();
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        restore_snapshot(&path, &before).unwrap();
+        assert_eq!(std::fs::read_to_string(&script).unwrap(), "#!/bin/sh\n");
+        assert_eq!(mode(), 0o755);
+
+        // A mode change alone is restored too
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o644)).unwrap();
+        let restore = restore_snapshot(&path, &before).unwrap();
+        assert_eq!(restore.restored, 1);
+        assert_eq!(mode(), 0o755);
+
+        // Objects left behind without a manifest are pruned
+        let root = store_root(&path);
+        let orphan = hash_bytes(b"orphan");
+        write_atomic(&object_path(&root, &orphan), b"orphan").unwrap();
+        assert_eq!(prune_objects(&path).unwrap(), 1);
+        assert!(!object_path(&root, &orphan).exists());
+        assert!(read_object(&path, &manifest.files["run.sh"]).is_ok());
+    }
+
+    #[test]
+    fn test_snapshot_reuses_indexed_hashes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let file = dir.path().join("a.txt");
+        std::fs::write(&file, "one\n").unwrap();
+        let old = SystemTime::now() - Duration::from_secs(60);
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+
+        let first = take_snapshot(&path).unwrap();
+        let root = store_root(&path);
+        assert!(load_index(&root).contains_key("a.txt"));
+
+        // Same size and mtime: the indexed hash is trusted without reading the file
+        std::fs::write(&file, "two\n").unwrap();
+        std::fs::File::options()
+            .write(true)
+            .open(&file)
+            .unwrap()
+            .set_modified(old)
+            .unwrap();
+        assert_eq!(take_snapshot(&path).unwrap(), first);
+
+        // A fresh mtime is hashed again
+        std::fs::write(&file, "two\n").unwrap();
+        assert_ne!(take_snapshot(&path).unwrap(), first);
+        assert!(!load_index(&root).contains_key("a.txt"));
+    }
+}
@@ -0,0 +1,428 @@
+//! Speech-to-text input for the prompt box
+//!
+//! `start_voice_recording` captures the default microphone (via `cpal`) on a
+//! dedicated thread; `stop_voice_recording` stops it, converts the audio to
+//! 16 kHz mono WAV and transcribes it with the configured backend: a local
+//! whisper.cpp CLI or an OpenAI-compatible `/audio/transcriptions` API. The
+//! configuration lives in `~/.anycode/speech.json`, the API key in the keyring.
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// Keyring entry of the transcription API key
+const API_KEY_SECRET: &str = "speech-api-key";
+
+/// Recordings are stopped automatically after this long
+const MAX_RECORDING_SECS: u64 = 300;
+
+/// Sample rate whisper models expect
+const TARGET_SAMPLE_RATE: u32 = 16_000;
+
+lazy_static::lazy_static! {
+    static ref RECORDING: Mutex<Option<Recording>> = Mutex::new(None);
+}
+
+/// Transcription backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SpeechBackend {
+    /// OpenAI-compatible transcription API
+    Api,
+    /// Local whisper.cpp command line
+    WhisperCpp,
+}
+
+/// Speech-to-text configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechConfig {
+    pub backend: SpeechBackend,
+    #[serde(default = "default_api_base_url")]
+    pub api_base_url: String,
+    #[serde(default = "default_api_model")]
+    pub api_model: String,
+    /// Spoken language (ISO 639-1); `None` lets the model detect it
+    pub language: Option<String>,
+    /// whisper.cpp executable (defaults to `whisper-cli` on PATH)
+    pub whisper_binary: Option<String>,
+    /// whisper.cpp model file (ggml)
+    pub whisper_model_path: Option<String>,
+}
+
+fn default_api_base_url() -> String {
+    "https://api.openai.com/v1".to_string()
+}
+
+fn default_api_model() -> String {
+    "whisper-1".to_string()
+}
+
+impl Default for SpeechConfig {
+    fn default() -> Self {
+        Self {
+            backend: SpeechBackend::Api,
+            api_base_url: default_api_base_url(),
+            api_model: default_api_model(),
+            language: None,
+            whisper_binary: None,
+            whisper_model_path: None,
+        }
+    }
+}
+
+/// Configuration plus whether an API key is stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechSettings {
+    #[serde(flatten)]
+    pub config: SpeechConfig,
+    pub has_api_key: bool,
+}
+
+/// Result of a recording
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transcription {
+    pub text: String,
+    pub duration_secs: f32,
+}
+
+/// Captured audio, interleaved
+struct RecordedAudio {
+    samples: Vec<f32>,
+    sample_rate: u32,
+    channels: u16,
+}
+
+struct Recording {
+    stop_tx: mpsc::Sender<()>,
+    handle: JoinHandle<Result<RecordedAudio, String>>,
+    started_at: Instant,
+}
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("speech.json"))
+}
+
+fn load_config() -> SpeechConfig {
+    config_path().and_then(load_json_config).unwrap_or_default()
+}
+
+/// Record from the default input device until a stop signal arrives
+fn record_until_stopped(stop_rx: mpsc::Receiver<()>) -> Result<RecordedAudio, String> {
+    let device = cpal::default_host()
+        .default_input_device()
+        .ok_or("No microphone found")?;
+    let supported = device
+        .default_input_config()
+        .map_err(|e| format!("Failed to query microphone: {}", e))?;
+    let sample_rate = supported.sample_rate().0;
+    let channels = supported.channels();
+    let sample_format = supported.sample_format();
+    let config: cpal::StreamConfig = supported.into();
+
+    let samples = Arc::new(Mutex::new(Vec::<f32>::new()));
+    let on_error = |e: cpal::StreamError| log::error!("[Speech] Input stream error: {}", e);
+    let sink = samples.clone();
+    let stream = match sample_format {
+        cpal::SampleFormat::F32 => device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &_| sink.lock().unwrap().extend_from_slice(data),
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::I16 => device.build_input_stream(
+            &config,
+            move |data: &[i16], _: &_| {
+                sink.lock()
+                    .unwrap()
+                    .extend(data.iter().map(|s| *s as f32 / i16::MAX as f32))
+            },
+            on_error,
+            None,
+        ),
+        cpal::SampleFormat::U16 => device.build_input_stream(
+            &config,
+            move |data: &[u16], _: &_| {
+                sink.lock()
+                    .unwrap()
+                    .extend(data.iter().map(|s| (*s as f32 - 32768.0) / 32768.0))
+            },
+            on_error,
+            None,
+        ),
+        other => return Err(format!("Unsupported sample format: {:?}", other)),
+    }
+    .map_err(|e| format!("Failed to open microphone: {}", e))?;
+    stream
+        .play()
+        .map_err(|e| format!("Failed to start recording: {}", e))?;
+
+    // Either an explicit stop or the time limit ends the recording
+    let _ = stop_rx.recv_timeout(Duration::from_secs(MAX_RECORDING_SECS));
+    drop(stream);
+
+    let samples = std::mem::take(&mut *samples.lock().unwrap());
+    Ok(RecordedAudio {
+        samples,
+        sample_rate,
+        channels,
+    })
+}
+
+/// Downmix to mono and resample (linear interpolation) to `TARGET_SAMPLE_RATE`
+fn to_mono_16k(audio: &RecordedAudio) -> Vec<f32> {
+    let channels = audio.channels.max(1) as usize;
+    let mono: Vec<f32> = audio
+        .samples
+        .chunks(channels)
+        .map(|frame| frame.iter().sum::<f32>() / frame.len() as f32)
+        .collect();
+    if audio.sample_rate == TARGET_SAMPLE_RATE || mono.is_empty() {
+        return mono;
+    }
+
+    let ratio = audio.sample_rate as f64 / TARGET_SAMPLE_RATE as f64;
+    let out_len = (mono.len() as f64 / ratio) as usize;
+    (0..out_len)
+        .map(|i| {
+            let pos = i as f64 * ratio;
+            let index = pos as usize;
+            let frac = (pos - index as f64) as f32;
+            let a = mono[index];
+            let b = mono.get(index + 1).copied().unwrap_or(a);
+            a + (b - a) * frac
+        })
+        .collect()
+}
+
+fn write_wav(path: &Path, samples: &[f32]) -> Result<(), String> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: TARGET_SAMPLE_RATE,
+        bits_per_sample: 16,
+        sample_format: hound::SampleFormat::Int,
+    };
+    let mut writer = hound::WavWriter::create(path, spec)
+        .map_err(|e| format!("Failed to write audio: {}", e))?;
+    for sample in samples {
+        writer
+            .write_sample((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)
+            .map_err(|e| format!("Failed to write audio: {}", e))?;
+    }
+    writer
+        .finalize()
+        .map_err(|e| format!("Failed to write audio: {}", e))
+}
+
+async fn transcribe_with_api(config: &SpeechConfig, wav: &Path) -> Result<String, String> {
+    crate::commands::connectivity::ensure_online("Speech transcription")?;
+    let api_key =
+        keyring_store::get_secret(API_KEY_SECRET)?.ok_or("No transcription API key configured")?;
+    let bytes = std::fs::read(wav).map_err(|e| format!("Failed to read audio: {}", e))?;
+
+    let file = reqwest::multipart::Part::bytes(bytes)
+        .file_name("recording.wav")
+        .mime_str("audio/wav")
+        .map_err(|e| e.to_string())?;
+    let mut form = reqwest::multipart::Form::new()
+        .part("file", file)
+        .text("model", config.api_model.clone());
+    if let Some(language) = &config.language {
+        form = form.text("language", language.clone());
+    }
+
+    let url = format!(
+        "{}/audio/transcriptions",
+        config.api_base_url.trim_end_matches('/')
+    );
+    let response = reqwest::Client::new()
+        .post(&url)
+        .bearer_auth(api_key)
+        .multipart(form)
+        .send()
+        .await
+        .map_err(|e| format!("Transcription request failed: {}", e))?;
+    let status = response.status();
+    let body: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid transcription response: {}", e))?;
+    if !status.is_success() {
+        return Err(format!(
+            "Transcription failed ({}): {}",
+            status,
+            body["error"]["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    Ok(body["text"].as_str().unwrap_or_default().to_string())
+}
+
+async fn transcribe_with_whisper_cpp(config: &SpeechConfig, wav: &Path) -> Result<String, String> {
+    let model = config
+        .whisper_model_path
+        .as_deref()
+        .ok_or("No whisper.cpp model configured")?;
+    let binary = config.whisper_binary.as_deref().unwrap_or("whisper-cli");
+
+    let mut cmd = tokio::process::Command::new(binary);
+    cmd.arg("-m")
+        .arg(model)
+        .arg("-f")
+        .arg(wav)
+        .args(["-nt", "-np"]);
+    if let Some(language) = &config.language {
+        cmd.args(["-l", language]);
+    }
+    crate::commands::claude::apply_no_window_async(&mut cmd);
+
+    let output = cmd
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", binary, e))?;
+    if !output.status.success() {
+        return Err(format!(
+            "whisper.cpp failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" "))
+}
+
+/// Take the running recording and wait for its thread to hand over the audio
+async fn finish_recording() -> Result<(RecordedAudio, f32), String> {
+    let recording = RECORDING
+        .lock()
+        .map_err(|e| e.to_string())?
+        .take()
+        .ok_or("No recording in progress")?;
+    let _ = recording.stop_tx.send(());
+    let duration = recording.started_at.elapsed().as_secs_f32();
+    let audio = tokio::task::spawn_blocking(move || recording.handle.join())
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|_| "Recording thread panicked".to_string())??;
+    Ok((audio, duration))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the speech-to-text configuration
+#[tauri::command]
+pub async fn get_speech_settings() -> Result<SpeechSettings, String> {
+    Ok(SpeechSettings {
+        config: load_config(),
+        has_api_key: keyring_store::get_secret(API_KEY_SECRET)
+            .ok()
+            .flatten()
+            .is_some(),
+    })
+}
+
+/// Update the configuration; `api_key` stores (or, when empty, removes) the API key
+#[tauri::command]
+pub async fn update_speech_settings(
+    config: SpeechConfig,
+    api_key: Option<String>,
+) -> Result<(), String> {
+    if let Some(key) = api_key {
+        let key = key.trim();
+        if key.is_empty() {
+            keyring_store::delete_secret(API_KEY_SECRET)?;
+        } else {
+            keyring_store::set_secret(API_KEY_SECRET, key)?;
+        }
+    }
+    save_json_config(&config, config_path()?)
+}
+
+/// Start recording from the default microphone
+#[tauri::command]
+pub async fn start_voice_recording() -> Result<(), String> {
+    let mut recording = RECORDING.lock().map_err(|e| e.to_string())?;
+    if recording.is_some() {
+        return Err("A recording is already in progress".to_string());
+    }
+
+    let (stop_tx, stop_rx) = mpsc::channel();
+    // cpal streams are not Send, so the stream lives on its own thread
+    let handle = std::thread::spawn(move || record_until_stopped(stop_rx));
+    *recording = Some(Recording {
+        stop_tx,
+        handle,
+        started_at: Instant::now(),
+    });
+    log::info!("[Speech] Recording started");
+    Ok(())
+}
+
+/// Stop recording and return the transcribed text
+#[tauri::command]
+pub async fn stop_voice_recording() -> Result<Transcription, String> {
+    let (audio, duration_secs) = finish_recording().await?;
+    let samples = to_mono_16k(&audio);
+    if samples.is_empty() {
+        return Err("No audio was recorded".to_string());
+    }
+
+    let wav = tempfile::Builder::new()
+        .suffix(".wav")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    write_wav(wav.path(), &samples)?;
+
+    let config = load_config();
+    let text = match config.backend {
+        SpeechBackend::Api => transcribe_with_api(&config, wav.path()).await?,
+        SpeechBackend::WhisperCpp => transcribe_with_whisper_cpp(&config, wav.path()).await?,
+    };
+    log::info!(
+        "[Speech] Transcribed {:.1}s of audio ({} chars)",
+        duration_secs,
+        text.len()
+    );
+
+    Ok(Transcription {
+        text: text.trim().to_string(),
+        duration_secs,
+    })
+}
+
+/// Stop recording and discard the audio
+#[tauri::command]
+pub async fn cancel_voice_recording() -> Result<(), String> {
+    finish_recording().await.map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_mono_16k() {
+        // One second of 48 kHz stereo
+        let audio = RecordedAudio {
+            samples: vec![0.5; 48_000 * 2],
+            sample_rate: 48_000,
+            channels: 2,
+        };
+        let mono = to_mono_16k(&audio);
+        assert_eq!(mono.len(), 16_000);
+        assert!(mono.iter().all(|s| (*s - 0.5).abs() < f32::EPSILON));
+    }
+}
@@ -0,0 +1,197 @@
+//! Submodule awareness
+//!
+//! AI commits stage everything with `git add -A`, which also records whatever
+//! commit each submodule happens to have checked out. After a reset or a
+//! branch switch the submodules are usually behind, so the next AI commit
+//! would silently move their pointers. By default submodule changes are kept
+//! out of AI commits; a project can opt in to committing them.
+//!
+//! The opt-ins are stored in `~/.anycode/submodules.json`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::engine_safety::project_key;
+use crate::commands::git_backend::{backend_for, SubmoduleInfo};
+use crate::commands::git_executor::{GitOperation, LONG_TIMEOUT};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SubmoduleStore {
+    /// Projects whose AI commits include submodule pointer changes
+    #[serde(default)]
+    included_projects: BTreeSet<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SubmoduleOverview {
+    pub submodules: Vec<SubmoduleInfo>,
+    pub excluded_from_ai_commits: bool,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("submodules.json"))
+}
+
+fn load_store() -> SubmoduleStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(f: impl FnOnce(&mut SubmoduleStore) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+/// Whether AI commits in the project leave submodule pointers alone
+pub fn excluded_from_commits(project_path: &str) -> bool {
+    !load_store()
+        .included_projects
+        .contains(&project_key(project_path))
+}
+
+/// Submodules of the project; empty without a `.gitmodules`
+pub fn list(project_path: &str) -> Vec<SubmoduleInfo> {
+    if !Path::new(project_path).join(".gitmodules").exists() {
+        return Vec::new();
+    }
+    backend_for(project_path)
+        .submodules(project_path)
+        .unwrap_or_else(|e| {
+            log::warn!("[Submodules] Failed to list submodules: {}", e);
+            Vec::new()
+        })
+}
+
+/// Take staged submodule pointer changes back out of the index, unless the project opted in
+pub fn unstage_for_commit(project_path: &str) -> Result<(), String> {
+    if !excluded_from_commits(project_path) {
+        return Ok(());
+    }
+    let paths: Vec<String> = list(project_path)
+        .into_iter()
+        .map(|submodule| submodule.path)
+        .collect();
+    if paths.is_empty() {
+        return Ok(());
+    }
+    log::debug!(
+        "[Submodules] Keeping {} submodule(s) out of the commit",
+        paths.len()
+    );
+    backend_for(project_path).unstage_paths(project_path, &paths)
+}
+
+/// Submodules whose pointer differs between `from` and `to`, or whose checkout is out of sync
+pub fn affected_between(project_path: &str, from: &str, to: &str) -> Vec<String> {
+    let backend = backend_for(project_path);
+    let pointer = |commit: &str, path: &str| {
+        backend
+            .tree_entry_id(project_path, commit, path)
+            .ok()
+            .flatten()
+    };
+    list(project_path)
+        .into_iter()
+        .filter(|submodule| {
+            submodule.out_of_sync || pointer(from, &submodule.path) != pointer(to, &submodule.path)
+        })
+        .map(|submodule| submodule.path)
+        .collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn git_list_submodules(project_path: String) -> Result<SubmoduleOverview, String> {
+    tokio::task::spawn_blocking(move || SubmoduleOverview {
+        excluded_from_ai_commits: excluded_from_commits(&project_path),
+        submodules: list(&project_path),
+    })
+    .await
+    .map_err(|e| e.to_string())
+}
+
+/// Check out every submodule at its recorded commit (`init` also initializes new ones)
+#[tauri::command]
+pub async fn git_update_submodules(project_path: String, init: Option<bool>) -> Result<(), String> {
+    GitOperation::new(&project_path, "git submodule update")
+        .timeout(LONG_TIMEOUT)
+        .run(move || {
+            backend_for(&project_path).update_submodules(&project_path, init.unwrap_or(true))
+        })
+        .await
+}
+
+#[tauri::command]
+pub async fn git_set_submodules_excluded(
+    project_path: String,
+    excluded: bool,
+) -> Result<(), String> {
+    let key = project_key(&project_path);
+    update_store(|store| {
+        if excluded {
+            store.included_projects.remove(&key);
+        } else {
+            store.included_projects.insert(key);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::git_backend::{Git2Backend, GitBackend};
+
+    #[test]
+    fn test_unstage_paths_keeps_working_tree() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_string_lossy().to_string();
+        let backend = Git2Backend;
+        backend.init(&path).unwrap();
+        std::fs::write(dir.path().join("a.txt"), "one\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend.commit(&path, "initial").unwrap();
+        let initial = backend.current_commit(&path).unwrap();
+
+        std::fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        std::fs::write(dir.path().join("b.txt"), "new\n").unwrap();
+        backend.stage_all(&path, None).unwrap();
+        backend
+            .unstage_paths(&path, &["a.txt".to_string()])
+            .unwrap();
+        backend.commit(&path, "only b").unwrap();
+
+        let head = backend.current_commit(&path).unwrap();
+        assert_eq!(
+            backend.tree_entry_id(&path, &initial, "a.txt").unwrap(),
+            backend.tree_entry_id(&path, &head, "a.txt").unwrap()
+        );
+        assert!(backend
+            .tree_entry_id(&path, &head, "b.txt")
+            .unwrap()
+            .is_some());
+        assert!(backend
+            .tree_entry_id(&path, &initial, "b.txt")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("a.txt")).unwrap(),
+            "two\n"
+        );
+        assert!(list(&path).is_empty());
+    }
+}
@@ -0,0 +1,262 @@
+//! Task attachments
+//!
+//! Issues and tickets imported from external trackers are stored as task
+//! attachments in `~/.anycode/tasks/<id>.json`. `build_task_prompt` formats an
+//! attachment into the opening prompt of a new session.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Maximum characters of the description included in the prompt
+const MAX_BODY_CHARS: usize = 12000;
+
+/// Only the most recent comments are included in the prompt
+const MAX_PROMPT_COMMENTS: usize = 20;
+
+/// A comment on an imported issue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AttachmentComment {
+    pub author: String,
+    pub body: String,
+    pub created_at: Option<String>,
+}
+
+/// An imported issue/ticket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskAttachment {
+    /// Stable id derived from source and reference, e.g. `github-owner-repo-12`
+    pub id: String,
+    /// Tracker the attachment came from ("github", ...)
+    pub source: String,
+    /// Human-readable reference, e.g. `owner/repo#12`
+    pub reference: String,
+    pub url: String,
+    pub title: String,
+    pub body: String,
+    #[serde(default)]
+    pub labels: Vec<String>,
+    #[serde(default)]
+    pub comments: Vec<AttachmentComment>,
+    /// Additional tracker-specific fields (state, assignee, ...)
+    #[serde(default)]
+    pub fields: BTreeMap<String, String>,
+    /// Acceptance criteria, one item per entry
+    #[serde(default)]
+    pub acceptance_criteria: Vec<String>,
+    /// Project the attachment was imported for
+    pub project_path: Option<String>,
+    /// Pull request created for this task, once linked
+    pub linked_pr: Option<String>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+impl TaskAttachment {
+    /// Build the attachment id from a source and reference
+    pub fn make_id(source: &str, reference: &str) -> String {
+        let slug: String = reference
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+        let slug = slug
+            .split('-')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        format!("{}-{}", source, slug.to_lowercase())
+    }
+}
+
+fn tasks_dir() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("tasks"))
+}
+
+fn attachment_path(id: &str) -> Result<PathBuf, String> {
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid task attachment id: {}", id));
+    }
+    Ok(tasks_dir()?.join(format!("{}.json", id)))
+}
+
+/// Save (or replace) an attachment
+pub fn save_attachment(attachment: &TaskAttachment) -> Result<(), String> {
+    save_json_config(attachment, attachment_path(&attachment.id)?)
+}
+
+/// Load an attachment by id
+pub fn load_attachment(id: &str) -> Result<TaskAttachment, String> {
+    let path = attachment_path(id)?;
+    if !path.exists() {
+        return Err(format!("Task attachment not found: {}", id));
+    }
+    load_json_config(path)
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let mut truncated: String = text.chars().take(max).collect();
+    truncated.push_str("\n\n[... truncated ...]");
+    truncated
+}
+
+/// Format an attachment as prompt context
+pub fn format_attachment(attachment: &TaskAttachment, instructions: Option<&str>) -> String {
+    let mut prompt = String::new();
+
+    match instructions.map(str::trim).filter(|i| !i.is_empty()) {
+        Some(instructions) => {
+            prompt.push_str(instructions);
+            prompt.push_str("\n\n");
+        }
+        None => prompt.push_str("Work on the following task.\n\n"),
+    }
+
+    prompt.push_str(&format!(
+        "<task source=\"{}\" reference=\"{}\">\n",
+        attachment.source, attachment.reference
+    ));
+    prompt.push_str(&format!("# {}\n", attachment.title));
+    prompt.push_str(&format!("URL: {}\n", attachment.url));
+    if !attachment.labels.is_empty() {
+        prompt.push_str(&format!("Labels: {}\n", attachment.labels.join(", ")));
+    }
+    for (key, value) in &attachment.fields {
+        prompt.push_str(&format!("{}: {}\n", key, value));
+    }
+
+    let body = attachment.body.trim();
+    if !body.is_empty() {
+        prompt.push('\n');
+        prompt.push_str(&truncate_chars(body, MAX_BODY_CHARS));
+        prompt.push('\n');
+    }
+
+    if !attachment.acceptance_criteria.is_empty() {
+        prompt.push_str("\n## Acceptance criteria\n");
+        for criterion in &attachment.acceptance_criteria {
+            prompt.push_str(&format!("- {}\n", criterion));
+        }
+    }
+
+    if !attachment.comments.is_empty() {
+        prompt.push_str("\n## Comments\n");
+        let skip = attachment
+            .comments
+            .len()
+            .saturating_sub(MAX_PROMPT_COMMENTS);
+        if skip > 0 {
+            prompt.push_str(&format!("({} earlier comments omitted)\n", skip));
+        }
+        for comment in attachment.comments.iter().skip(skip) {
+            match &comment.created_at {
+                Some(date) => prompt.push_str(&format!("\n**{}** ({}):\n", comment.author, date)),
+                None => prompt.push_str(&format!("\n**{}**:\n", comment.author)),
+            }
+            prompt.push_str(comment.body.trim());
+            prompt.push('\n');
+        }
+    }
+
+    prompt.push_str("</task>\n");
+    prompt
+}
+
+/// List stored attachments, optionally only those of one project
+#[tauri::command]
+pub async fn list_task_attachments(
+    project_path: Option<String>,
+) -> Result<Vec<TaskAttachment>, String> {
+    let dir = tasks_dir()?;
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let entries =
+        std::fs::read_dir(&dir).map_err(|e| format!("Failed to read tasks directory: {}", e))?;
+    let mut attachments: Vec<TaskAttachment> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+        .filter_map(|path| load_json_config::<TaskAttachment>(&path).ok())
+        .filter(|a| match &project_path {
+            Some(project) => a.project_path.as_deref() == Some(project.as_str()),
+            None => true,
+        })
+        .collect();
+
+    attachments.sort_by(|a, b| b.fetched_at.cmp(&a.fetched_at));
+    Ok(attachments)
+}
+
+/// Get a stored attachment
+#[tauri::command]
+pub async fn get_task_attachment(id: String) -> Result<TaskAttachment, String> {
+    load_attachment(&id)
+}
+
+/// Delete a stored attachment
+#[tauri::command]
+pub async fn delete_task_attachment(id: String) -> Result<(), String> {
+    let path = attachment_path(&id)?;
+    if path.exists() {
+        std::fs::remove_file(&path)
+            .map_err(|e| format!("Failed to delete task attachment: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Format an attachment into the opening prompt of a new session
+#[tauri::command]
+pub async fn build_task_prompt(id: String, instructions: Option<String>) -> Result<String, String> {
+    let attachment = load_attachment(&id)?;
+    Ok(format_attachment(&attachment, instructions.as_deref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn attachment() -> TaskAttachment {
+        TaskAttachment {
+            id: TaskAttachment::make_id("github", "Owner/Repo#12"),
+            source: "github".to_string(),
+            reference: "Owner/Repo#12".to_string(),
+            url: "https://github.com/Owner/Repo/issues/12".to_string(),
+            title: "Crash on startup".to_string(),
+            body: "Steps to reproduce".to_string(),
+            labels: vec!["bug".to_string()],
+            comments: vec![AttachmentComment {
+                author: "alice".to_string(),
+                body: "Also happens on Linux".to_string(),
+                created_at: None,
+            }],
+            fields: BTreeMap::new(),
+            acceptance_criteria: vec!["Starts without crashing".to_string()],
+            project_path: None,
+            linked_pr: None,
+            fetched_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_make_id() {
+        assert_eq!(attachment().id, "github-owner-repo-12");
+    }
+
+    #[test]
+    fn test_format_attachment() {
+        let prompt = format_attachment(&attachment(), None);
+        assert!(prompt.contains("# Crash on startup"));
+        assert!(prompt.contains("Labels: bug"));
+        assert!(prompt.contains("## Acceptance criteria\n- Starts without crashing"));
+        assert!(prompt.contains("**alice**:\nAlso happens on Linux"));
+        assert!(prompt.trim_end().ends_with("</task>"));
+    }
+}
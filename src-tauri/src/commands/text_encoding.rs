@@ -0,0 +1,146 @@
+//! Encoding detection for non-UTF-8 text files
+//!
+//! Legacy codebases (GBK, Shift-JIS, Latin-1, ...) are decoded with the
+//! encoding `chardetng` guesses and transcoded to UTF-8 instead of being
+//! rejected or shown as mojibake. Every decode reports the encoding used and
+//! how many bytes could not be mapped, so callers can mark lossy content.
+//! Used by context assembly, and exposed for diff views and export through
+//! `read_text_file` and `read_file_at_revision`.
+
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use serde::{Deserialize, Serialize};
+
+use crate::commands::wsl_utils::git_command;
+
+/// How a byte buffer was turned into UTF-8
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EncodingReport {
+    /// Encoding label, e.g. `UTF-8`, `GBK`, `Shift_JIS`, `windows-1252`
+    pub encoding: String,
+    /// Some bytes were not valid in the encoding and became U+FFFD
+    pub lossy: bool,
+    /// Number of U+FFFD replacement characters introduced
+    pub replaced_chars: usize,
+}
+
+impl EncodingReport {
+    pub fn is_utf8(&self) -> bool {
+        self.encoding == UTF_8.name()
+    }
+}
+
+/// Decoded text plus its encoding report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DecodedText {
+    pub content: String,
+    #[serde(flatten)]
+    pub report: EncodingReport,
+}
+
+fn detect(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    detector.guess(None, true)
+}
+
+/// Decode bytes as UTF-8 when valid, otherwise with the detected encoding
+pub fn decode_text(bytes: &[u8]) -> DecodedText {
+    let without_bom = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+    if let Ok(text) = std::str::from_utf8(without_bom) {
+        return DecodedText {
+            content: text.to_string(),
+            report: EncodingReport {
+                encoding: UTF_8.name().to_string(),
+                lossy: false,
+                replaced_chars: 0,
+            },
+        };
+    }
+
+    let encoding = detect(bytes);
+    // `decode` strips a BOM if present
+    let (content, used, had_errors) = encoding.decode(bytes);
+    let replaced_chars = if had_errors {
+        content.matches('\u{FFFD}').count()
+    } else {
+        0
+    };
+    DecodedText {
+        content: content.into_owned(),
+        report: EncodingReport {
+            encoding: used.name().to_string(),
+            lossy: had_errors,
+            replaced_chars,
+        },
+    }
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read a text file in whatever encoding it uses, transcoded to UTF-8
+#[tauri::command]
+pub async fn read_text_file(path: String) -> Result<DecodedText, String> {
+    let bytes = tokio::fs::read(&path)
+        .await
+        .map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    Ok(decode_text(&bytes))
+}
+
+/// Read a file as of a git revision (for diff views), transcoded to UTF-8
+#[tauri::command]
+pub async fn read_file_at_revision(
+    project_path: String,
+    revision: String,
+    path: String,
+) -> Result<DecodedText, String> {
+    if revision.starts_with('-') {
+        return Err(format!("Invalid revision: {}", revision));
+    }
+    let spec = format!("{}:{}", revision, path.replace('\\', "/"));
+    let output = tokio::task::spawn_blocking(move || {
+        git_command(&project_path).args(["show", &spec]).output()
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| format!("Failed to run git show: {}", e))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+    Ok(decode_text(&output.stdout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_utf8_and_bom() {
+        let decoded = decode_text("héllo".as_bytes());
+        assert_eq!(decoded.content, "héllo");
+        assert!(decoded.report.is_utf8());
+
+        let decoded = decode_text(b"\xEF\xBB\xBFfn main() {}");
+        assert_eq!(decoded.content, "fn main() {}");
+        assert!(!decoded.report.lossy);
+    }
+
+    #[test]
+    fn test_decode_gbk() {
+        let (gbk, _, _) = encoding_rs::GBK.encode("// 中文注释：读取配置文件并初始化数据库连接");
+        let decoded = decode_text(&gbk);
+        assert_eq!(
+            decoded.content,
+            "// 中文注释：读取配置文件并初始化数据库连接"
+        );
+        assert_eq!(decoded.report.encoding, "GBK");
+        assert!(!decoded.report.lossy);
+    }
+}
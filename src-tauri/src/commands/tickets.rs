@@ -0,0 +1,609 @@
+//! Jira and Linear ticket import (read-only)
+//!
+//! Fetches a ticket by key or URL (summary, description, acceptance criteria,
+//! comments) and stores it as a task attachment for prompt context. Non-secret
+//! settings live in `~/.anycode/tickets.json`; API tokens are kept in the system
+//! keyring. Fetched tickets are cached in memory for a few minutes so repeated
+//! prompt building doesn't hit the tracker every time.
+
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::forge::{http_client, send_json_with};
+use super::task_context::{self, AttachmentComment, TaskAttachment};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use crate::utils::keyring_store;
+
+/// How long a fetched ticket is served from the cache
+const CACHE_TTL: Duration = Duration::from_secs(10 * 60);
+
+const JIRA_TOKEN_KEY: &str = "jira-api-token";
+const LINEAR_TOKEN_KEY: &str = "linear-api-key";
+const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+lazy_static::lazy_static! {
+    static ref CACHE: Mutex<HashMap<String, (Instant, TaskAttachment)>> = Mutex::new(HashMap::new());
+}
+
+/// Supported trackers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TicketProvider {
+    Jira,
+    Linear,
+}
+
+impl TicketProvider {
+    fn as_str(&self) -> &'static str {
+        match self {
+            TicketProvider::Jira => "jira",
+            TicketProvider::Linear => "linear",
+        }
+    }
+}
+
+/// Jira connection settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JiraSettings {
+    /// e.g. https://example.atlassian.net
+    pub base_url: String,
+    /// Account email for Jira Cloud; leave empty to use a Data Center personal access token
+    #[serde(default)]
+    pub email: String,
+    /// Custom field holding acceptance criteria (e.g. "customfield_10050")
+    pub acceptance_criteria_field: Option<String>,
+}
+
+/// Non-secret ticket integration settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketSettings {
+    pub jira: Option<JiraSettings>,
+}
+
+/// Configuration state of the trackers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TicketIntegrationStatus {
+    pub jira: Option<JiraSettings>,
+    pub jira_token_stored: bool,
+    pub linear_token_stored: bool,
+}
+
+fn settings_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("tickets.json"))
+}
+
+fn load_settings() -> TicketSettings {
+    settings_path()
+        .and_then(load_json_config)
+        .unwrap_or_default()
+}
+
+/// Drop fetched tickets after the settings were edited outside the app, as
+/// they may come from a tracker that is no longer configured
+pub(crate) fn reload_settings() {
+    if let Ok(mut cache) = CACHE.lock() {
+        cache.clear();
+    }
+}
+
+// ============================================================================
+// Reference parsing
+// ============================================================================
+
+/// Whether `key` looks like a ticket key: PROJECT-123
+fn is_ticket_key(key: &str) -> bool {
+    key.split_once('-').is_some_and(|(project, number)| {
+        !project.is_empty()
+            && project
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_')
+            && number.parse::<u64>().is_ok()
+    })
+}
+
+/// The ticket key in a Linear or Jira URL
+fn key_from_url(url: &reqwest::Url) -> Option<(TicketProvider, String)> {
+    let host = url.host_str()?.to_ascii_lowercase();
+    let segments: Vec<&str> = url.path_segments()?.collect();
+    let after = |marker: &str| {
+        segments
+            .iter()
+            .position(|segment| *segment == marker)
+            .and_then(|index| segments.get(index + 1))
+            .map(|key| key.to_string())
+    };
+
+    // https://linear.app/<team>/issue/ENG-123/slug
+    if host == "linear.app" || host.ends_with(".linear.app") {
+        return Some((TicketProvider::Linear, after("issue")?));
+    }
+    // https://<site>/browse/ABC-123 or .../issues/ABC-123 or ?selectedIssue=ABC-123
+    let key = url
+        .query_pairs()
+        .find(|(name, _)| name == "selectedIssue")
+        .map(|(_, value)| value.into_owned())
+        .or_else(|| after("browse"))
+        .or_else(|| after("issues"))?;
+    Some((TicketProvider::Jira, key))
+}
+
+/// Parse a ticket key or URL into provider and key
+fn parse_ticket_reference(
+    reference: &str,
+    provider: Option<TicketProvider>,
+    settings: &TicketSettings,
+) -> Result<(TicketProvider, String), String> {
+    let reference = reference.trim();
+    let invalid = || format!("Unrecognized ticket reference: {}", reference);
+
+    if reference.contains("://") {
+        let url = reqwest::Url::parse(reference).map_err(|_| invalid())?;
+        let (provider, key) = key_from_url(&url).ok_or_else(invalid)?;
+        if !is_ticket_key(&key) {
+            return Err(invalid());
+        }
+        return Ok((provider, key.to_uppercase()));
+    }
+
+    if !is_ticket_key(reference) {
+        return Err(invalid());
+    }
+    let provider = match provider {
+        Some(provider) => provider,
+        None if settings.jira.is_some() => TicketProvider::Jira,
+        None => TicketProvider::Linear,
+    };
+    Ok((provider, reference.to_uppercase()))
+}
+
+/// Whether a description line is a section heading (Markdown, Jira wiki markup or a bold/colon label)
+fn is_heading(line: &str) -> bool {
+    let wiki_heading = line.len() > 3
+        && line.starts_with('h')
+        && line.as_bytes()[1].is_ascii_digit()
+        && line[2..].starts_with(". ");
+    // A single "#" is a numbered list item in Jira wiki markup, so only "##" and deeper count
+    line.starts_with("##")
+        || wiki_heading
+        || (line.len() > 4 && line.starts_with("**") && line.ends_with("**"))
+        || (line.ends_with(':') && !line.starts_with(['-', '*', '+']))
+}
+
+/// Pull list items out of an "Acceptance criteria" section of a description
+fn extract_acceptance_criteria(description: &str) -> Vec<String> {
+    let mut criteria = Vec::new();
+    let mut in_section = false;
+
+    for line in description.lines().map(str::trim) {
+        if is_heading(line) {
+            let title = line
+                .trim_start_matches(['#', '*', ' '])
+                .trim_start_matches(|c: char| c == 'h' || c.is_ascii_digit())
+                .trim_start_matches(['.', ' '])
+                .trim_end_matches([':', '*', ' '])
+                .to_lowercase();
+            if in_section {
+                break;
+            }
+            in_section = title.starts_with("acceptance criteria");
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+
+        // "- [ ] item", "* item", "# item" (Jira wiki) or "1. item"
+        let item = line
+            .trim_start_matches(['-', '*', '#', '+'])
+            .trim_start()
+            .trim_start_matches("[ ]")
+            .trim_start_matches("[x]")
+            .trim();
+        let item = match item.split_once(". ") {
+            Some((n, rest)) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => rest,
+            _ => item,
+        };
+        if !item.is_empty() {
+            criteria.push(item.to_string());
+        }
+    }
+
+    criteria
+}
+
+// ============================================================================
+// Jira
+// ============================================================================
+
+async fn fetch_jira(
+    key: &str,
+    settings: &JiraSettings,
+    token: &str,
+) -> Result<TaskAttachment, String> {
+    let base = settings.base_url.trim_end_matches('/');
+    let mut fields = vec![
+        "summary",
+        "description",
+        "status",
+        "issuetype",
+        "priority",
+        "labels",
+        "assignee",
+        "comment",
+    ];
+    if let Some(field) = &settings.acceptance_criteria_field {
+        fields.push(field);
+    }
+    // API v2 returns descriptions as wiki markup text (v3 uses Atlassian document format)
+    let url = format!(
+        "{}/rest/api/2/issue/{}?fields={}",
+        base,
+        key,
+        fields.join(",")
+    );
+
+    let request = http_client()?
+        .get(&url)
+        .header("Accept", "application/json");
+    let request = if settings.email.trim().is_empty() {
+        request.bearer_auth(token)
+    } else {
+        request.basic_auth(settings.email.trim(), Some(token))
+    };
+    let issue = send_json_with("Jira", request).await?;
+    let f = &issue["fields"];
+
+    let description = f["description"].as_str().unwrap_or_default().to_string();
+    let acceptance_criteria = settings
+        .acceptance_criteria_field
+        .as_ref()
+        .and_then(|field| f[field.as_str()].as_str())
+        .map(|text| {
+            let items = extract_acceptance_criteria(&format!("Acceptance criteria:\n{}", text));
+            if items.is_empty() {
+                vec![text.trim().to_string()]
+            } else {
+                items
+            }
+        })
+        .unwrap_or_else(|| extract_acceptance_criteria(&description));
+
+    let mut extra = BTreeMap::new();
+    for (label, value) in [
+        ("Type", &f["issuetype"]["name"]),
+        ("Status", &f["status"]["name"]),
+        ("Priority", &f["priority"]["name"]),
+        ("Assignee", &f["assignee"]["displayName"]),
+    ] {
+        if let Some(value) = value.as_str() {
+            extra.insert(label.to_string(), value.to_string());
+        }
+    }
+
+    let comments = f["comment"]["comments"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| AttachmentComment {
+            author: c["author"]["displayName"]
+                .as_str()
+                .unwrap_or("unknown")
+                .to_string(),
+            body: c["body"].as_str().unwrap_or_default().to_string(),
+            created_at: c["created"].as_str().map(String::from),
+        })
+        .collect();
+
+    Ok(TaskAttachment {
+        id: TaskAttachment::make_id("jira", key),
+        source: "jira".to_string(),
+        reference: key.to_string(),
+        url: format!("{}/browse/{}", base, key),
+        title: f["summary"].as_str().unwrap_or_default().to_string(),
+        body: description,
+        labels: f["labels"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l.as_str().map(String::from))
+            .collect(),
+        comments,
+        fields: extra,
+        acceptance_criteria,
+        project_path: None,
+        linked_pr: None,
+        fetched_at: Utc::now(),
+    })
+}
+
+// ============================================================================
+// Linear
+// ============================================================================
+
+const LINEAR_ISSUE_QUERY: &str = r#"
+query Issue($id: String!) {
+  issue(id: $id) {
+    identifier
+    title
+    description
+    url
+    priorityLabel
+    state { name }
+    assignee { name }
+    labels { nodes { name } }
+    comments { nodes { body createdAt user { name } } }
+  }
+}"#;
+
+async fn fetch_linear(key: &str, token: &str) -> Result<TaskAttachment, String> {
+    let request = http_client()?
+        .post(LINEAR_API_URL)
+        // Personal API keys are sent as-is, OAuth tokens need the Bearer prefix
+        .header("Authorization", token)
+        .json(&json!({ "query": LINEAR_ISSUE_QUERY, "variables": { "id": key } }));
+    let response = send_json_with("Linear", request).await?;
+
+    if let Some(error) = response["errors"].as_array().and_then(|e| e.first()) {
+        return Err(format!(
+            "Linear API error: {}",
+            error["message"].as_str().unwrap_or("unknown error")
+        ));
+    }
+    let issue = &response["data"]["issue"];
+    if issue.is_null() {
+        return Err(format!("Linear issue not found: {}", key));
+    }
+
+    let description = issue["description"]
+        .as_str()
+        .unwrap_or_default()
+        .to_string();
+    let mut extra = BTreeMap::new();
+    for (label, value) in [
+        ("Status", &issue["state"]["name"]),
+        ("Priority", &issue["priorityLabel"]),
+        ("Assignee", &issue["assignee"]["name"]),
+    ] {
+        if let Some(value) = value.as_str() {
+            extra.insert(label.to_string(), value.to_string());
+        }
+    }
+
+    let mut comments: Vec<AttachmentComment> = issue["comments"]["nodes"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|c| AttachmentComment {
+            author: c["user"]["name"].as_str().unwrap_or("unknown").to_string(),
+            body: c["body"].as_str().unwrap_or_default().to_string(),
+            created_at: c["createdAt"].as_str().map(String::from),
+        })
+        .collect();
+    comments.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+
+    let reference = issue["identifier"].as_str().unwrap_or(key).to_string();
+    Ok(TaskAttachment {
+        id: TaskAttachment::make_id("linear", &reference),
+        source: "linear".to_string(),
+        url: issue["url"].as_str().unwrap_or_default().to_string(),
+        reference,
+        title: issue["title"].as_str().unwrap_or_default().to_string(),
+        acceptance_criteria: extract_acceptance_criteria(&description),
+        body: description,
+        labels: issue["labels"]["nodes"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|l| l["name"].as_str().map(String::from))
+            .collect(),
+        comments,
+        fields: extra,
+        project_path: None,
+        linked_pr: None,
+        fetched_at: Utc::now(),
+    })
+}
+
+fn cached(cache_key: &str) -> Option<TaskAttachment> {
+    let cache = CACHE.lock().ok()?;
+    cache
+        .get(cache_key)
+        .filter(|(fetched, _)| fetched.elapsed() < CACHE_TTL)
+        .map(|(_, attachment)| attachment.clone())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get the configuration state of Jira and Linear
+#[tauri::command]
+pub async fn get_ticket_integrations() -> Result<TicketIntegrationStatus, String> {
+    tokio::task::spawn_blocking(|| {
+        Ok(TicketIntegrationStatus {
+            jira: load_settings().jira,
+            jira_token_stored: keyring_store::get_secret(JIRA_TOKEN_KEY)?.is_some(),
+            linear_token_stored: keyring_store::get_secret(LINEAR_TOKEN_KEY)?.is_some(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Keyring task failed: {}", e))?
+}
+
+/// Configure Jira; `token` is stored in the keyring (`None` keeps the stored token)
+#[tauri::command]
+pub async fn configure_jira(
+    settings: Option<JiraSettings>,
+    token: Option<String>,
+) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let mut stored = load_settings();
+        match settings {
+            Some(settings) => stored.jira = Some(settings),
+            None => {
+                stored.jira = None;
+                keyring_store::delete_secret(JIRA_TOKEN_KEY)?;
+            }
+        }
+        if let Some(token) = token.filter(|t| !t.trim().is_empty()) {
+            keyring_store::set_secret(JIRA_TOKEN_KEY, token.trim())?;
+        }
+        save_json_config(&stored, settings_path()?)
+    })
+    .await
+    .map_err(|e| format!("Keyring task failed: {}", e))?
+}
+
+/// Store (or remove, with `None`) the Linear API key in the keyring
+#[tauri::command]
+pub async fn configure_linear(api_key: Option<String>) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || match api_key.filter(|k| !k.trim().is_empty()) {
+        Some(key) => keyring_store::set_secret(LINEAR_TOKEN_KEY, key.trim()),
+        None => keyring_store::delete_secret(LINEAR_TOKEN_KEY),
+    })
+    .await
+    .map_err(|e| format!("Keyring task failed: {}", e))?
+}
+
+/// Fetch a Jira/Linear ticket by key or URL and store it as a task attachment
+///
+/// Bare keys go to `provider`, or Jira when it is configured. Results are
+/// served from a short-lived cache unless `force_refresh` is set.
+#[tauri::command]
+pub async fn import_ticket(
+    reference: String,
+    provider: Option<TicketProvider>,
+    project_path: Option<String>,
+    force_refresh: Option<bool>,
+) -> Result<TaskAttachment, String> {
+    let settings = load_settings();
+    let (provider, key) = parse_ticket_reference(&reference, provider, &settings)?;
+    let cache_key = format!("{}:{}", provider.as_str(), key);
+
+    let mut attachment = match cached(&cache_key).filter(|_| !force_refresh.unwrap_or(false)) {
+        Some(attachment) => {
+            log::debug!("[Tickets] Serving {} from cache", cache_key);
+            attachment
+        }
+        None => {
+            super::connectivity::ensure_online("Importing a ticket")?;
+            log::info!("[Tickets] Fetching {} ticket {}", provider.as_str(), key);
+
+            let token_key = match provider {
+                TicketProvider::Jira => JIRA_TOKEN_KEY,
+                TicketProvider::Linear => LINEAR_TOKEN_KEY,
+            };
+            let token = tokio::task::spawn_blocking(move || keyring_store::get_secret(token_key))
+                .await
+                .map_err(|e| format!("Keyring task failed: {}", e))??
+                .ok_or_else(|| format!("No {} credentials stored", provider.as_str()))?;
+
+            let attachment = match provider {
+                TicketProvider::Jira => {
+                    let jira = settings
+                        .jira
+                        .as_ref()
+                        .ok_or_else(|| "Jira is not configured".to_string())?;
+                    fetch_jira(&key, jira, &token).await?
+                }
+                TicketProvider::Linear => fetch_linear(&key, &token).await?,
+            };
+
+            if let Ok(mut cache) = CACHE.lock() {
+                cache.insert(cache_key, (Instant::now(), attachment.clone()));
+            }
+            attachment
+        }
+    };
+
+    attachment.project_path = project_path;
+    if let Ok(previous) = task_context::load_attachment(&attachment.id) {
+        attachment.linked_pr = previous.linked_pr;
+    }
+    task_context::save_attachment(&attachment)?;
+    Ok(attachment)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ticket_reference() {
+        let settings = TicketSettings::default();
+        assert_eq!(
+            parse_ticket_reference(
+                "https://linear.app/acme/issue/eng-42/fix-login",
+                None,
+                &settings
+            )
+            .unwrap(),
+            (TicketProvider::Linear, "ENG-42".to_string())
+        );
+        assert_eq!(
+            parse_ticket_reference("https://acme.atlassian.net/browse/ABC-7", None, &settings)
+                .unwrap(),
+            (TicketProvider::Jira, "ABC-7".to_string())
+        );
+        assert_eq!(
+            parse_ticket_reference("abc-7", Some(TicketProvider::Jira), &settings).unwrap(),
+            (TicketProvider::Jira, "ABC-7".to_string())
+        );
+        assert_eq!(
+            parse_ticket_reference(
+                "https://acme.atlassian.net/jira/software/projects/ABC/boards/1?selectedIssue=abc-9",
+                None,
+                &settings
+            )
+            .unwrap(),
+            (TicketProvider::Jira, "ABC-9".to_string())
+        );
+        assert!(parse_ticket_reference("not a ticket", None, &settings).is_err());
+        // Keys taken from URLs get the same check as bare keys
+        for url in [
+            "https://acme.atlassian.net/browse/../secure",
+            "https://acme.atlassian.net/browse/ABC-7x",
+            "https://linear.app/acme/issue/",
+        ] {
+            assert!(
+                parse_ticket_reference(url, None, &settings).is_err(),
+                "{}",
+                url
+            );
+        }
+        // A Linear path on another host is not a Linear ticket
+        assert_eq!(
+            parse_ticket_reference(
+                "https://linear.app.example.com/browse/ABC-1",
+                None,
+                &settings
+            )
+            .unwrap(),
+            (TicketProvider::Jira, "ABC-1".to_string())
+        );
+        assert!(parse_ticket_reference(
+            "https://example.com/linear.app/issue/ENG-1",
+            None,
+            &settings
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_extract_acceptance_criteria() {
+        let description = "Some context.\n\n## Acceptance Criteria\n- [ ] Users can log in\n- Errors are shown\n1. Works offline\n\n## Notes\n- not a criterion";
+        assert_eq!(
+            extract_acceptance_criteria(description),
+            vec!["Users can log in", "Errors are shown", "Works offline"]
+        );
+        assert!(extract_acceptance_criteria("No criteria here").is_empty());
+    }
+}
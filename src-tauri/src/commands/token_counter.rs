@@ -0,0 +1,207 @@
+//! Token counting for text and files
+//!
+//! `count_tokens` counts tokens the way the target model family does: OpenAI
+//! models use their tiktoken encodings (o200k for GPT-4o/GPT-5/o-series/Codex,
+//! cl100k for older GPT-4/3.5). Claude and Gemini tokenizers are not
+//! available locally, so their counts are approximated with cl100k and flagged
+//! as such. Counts are cached by content hash, so the live counter and repeated
+//! estimates of unchanged files stay cheap.
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Mutex;
+use tiktoken_rs::CoreBPE;
+
+/// Cached counts kept before the cache is reset
+const MAX_CACHE_ENTRIES: usize = 4096;
+
+/// Characters per token used when no tokenizer can be loaded
+const FALLBACK_CHARS_PER_TOKEN: usize = 4;
+
+static O200K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::o200k_base().ok());
+static CL100K: Lazy<Option<CoreBPE>> = Lazy::new(|| tiktoken_rs::cl100k_base().ok());
+static CACHE: Lazy<Mutex<HashMap<String, usize>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// Tokenizer used for a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Tokenizer {
+    O200k,
+    Cl100k,
+}
+
+/// How a count was obtained
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CountMethod {
+    /// The model's own tokenizer
+    Exact,
+    /// A similar tokenizer (Claude, Gemini)
+    Approximate,
+    /// Character-based estimate (tokenizer unavailable)
+    Estimate,
+}
+
+/// Count for one text or file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCountItem {
+    /// `"text"` or the file path
+    pub source: String,
+    pub tokens: usize,
+    pub error: Option<String>,
+}
+
+/// Result of `count_tokens`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCount {
+    pub model: String,
+    pub tokenizer: Tokenizer,
+    pub method: CountMethod,
+    pub total: usize,
+    pub items: Vec<TokenCountItem>,
+}
+
+/// Pick the tokenizer for a model and whether it is the model's own
+pub fn tokenizer_for(model: &str) -> (Tokenizer, CountMethod) {
+    let model = model.to_lowercase();
+    let is_openai = model.contains("codex")
+        || ["gpt-", "o1", "o3", "o4", "chatgpt"]
+            .iter()
+            .any(|prefix| model.starts_with(prefix));
+    if !is_openai {
+        return (Tokenizer::Cl100k, CountMethod::Approximate);
+    }
+    if model.starts_with("gpt-4-") || model == "gpt-4" || model.starts_with("gpt-3.5") {
+        (Tokenizer::Cl100k, CountMethod::Exact)
+    } else {
+        (Tokenizer::O200k, CountMethod::Exact)
+    }
+}
+
+fn encode_len(tokenizer: Tokenizer, text: &str) -> Option<usize> {
+    let bpe = match tokenizer {
+        Tokenizer::O200k => O200K.as_ref(),
+        Tokenizer::Cl100k => CL100K.as_ref(),
+    }?;
+    Some(bpe.encode_ordinary(text).len())
+}
+
+/// Count tokens of `text` for `model`, using the content-hash cache
+pub fn count_text_tokens(model: &str, text: &str) -> (usize, CountMethod) {
+    let (tokenizer, method) = tokenizer_for(model);
+    if text.is_empty() {
+        return (0, method);
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{:?}\0", tokenizer).as_bytes());
+    hasher.update(text.as_bytes());
+    let key = format!("{:x}", hasher.finalize());
+    if let Some(count) = CACHE.lock().ok().and_then(|cache| cache.get(&key).copied()) {
+        return (count, method);
+    }
+
+    let Some(count) = encode_len(tokenizer, text) else {
+        let estimate = text.chars().count().div_ceil(FALLBACK_CHARS_PER_TOKEN);
+        return (estimate, CountMethod::Estimate);
+    };
+    if let Ok(mut cache) = CACHE.lock() {
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(key, count);
+    }
+    (count, method)
+}
+
+fn count_all(model: String, text: Option<String>, paths: Vec<String>) -> TokenCount {
+    let (tokenizer, mut method) = tokenizer_for(&model);
+    let mut items = Vec::new();
+    let mut record = |source: String, result: Result<String, String>| match result {
+        Ok(content) => {
+            let (tokens, used) = count_text_tokens(&model, &content);
+            if used == CountMethod::Estimate {
+                method = CountMethod::Estimate;
+            }
+            items.push(TokenCountItem {
+                source,
+                tokens,
+                error: None,
+            });
+        }
+        Err(error) => items.push(TokenCountItem {
+            source,
+            tokens: 0,
+            error: Some(error),
+        }),
+    };
+
+    if let Some(text) = text {
+        record("text".to_string(), Ok(text));
+    }
+    for path in paths {
+        let content = std::fs::read(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| String::from_utf8(bytes).map_err(|_| "Binary file".to_string()));
+        record(path, content);
+    }
+
+    TokenCount {
+        total: items.iter().map(|item| item.tokens).sum(),
+        model,
+        tokenizer,
+        method,
+        items,
+    }
+}
+
+/// Count tokens of a text and/or files with the tokenizer of the given model
+#[tauri::command]
+pub async fn count_tokens(
+    model: String,
+    text: Option<String>,
+    paths: Option<Vec<String>>,
+) -> Result<TokenCount, String> {
+    let paths = paths.unwrap_or_default();
+    tokio::task::spawn_blocking(move || count_all(model, text, paths))
+        .await
+        .map_err(|e| format!("Token counting failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenizer_for() {
+        assert_eq!(
+            tokenizer_for("gpt-5.1-codex-max"),
+            (Tokenizer::O200k, CountMethod::Exact)
+        );
+        assert_eq!(
+            tokenizer_for("gpt-4-turbo"),
+            (Tokenizer::Cl100k, CountMethod::Exact)
+        );
+        assert_eq!(
+            tokenizer_for("sonnet"),
+            (Tokenizer::Cl100k, CountMethod::Approximate)
+        );
+        assert_eq!(
+            tokenizer_for("gemini-2.5-pro"),
+            (Tokenizer::Cl100k, CountMethod::Approximate)
+        );
+    }
+
+    #[test]
+    fn test_count_text_tokens() {
+        assert_eq!(count_text_tokens("gpt-4o", "").0, 0);
+        let (count, _) = count_text_tokens("gpt-4o", "Hello, world!");
+        assert!(count > 0 && count < 10);
+        // Second call is served from the cache
+        assert_eq!(count_text_tokens("gpt-4o", "Hello, world!").0, count);
+    }
+}
@@ -1,7 +1,7 @@
 // Simplified usage tracking from opcode project
 // Source: https://github.com/meistrari/opcode
 
-use chrono::{DateTime, Local, NaiveDate};
+use chrono::{DateTime, Local, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json;
 use std::collections::{HashMap, HashSet};
@@ -9,6 +9,8 @@ use std::fs;
 use std::path::PathBuf;
 use tauri::command;
 
+use crate::usage::{UsageGroupBy, UsageRange, UsageRecord, UsageReport};
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UsageEntry {
     timestamp: String,
@@ -416,7 +418,7 @@ fn get_all_usage_entries(claude_path: &PathBuf) -> Vec<UsageEntry> {
 }
 
 #[command]
-pub fn get_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
+pub fn get_claude_usage_stats(days: Option<u32>) -> Result<UsageStats, String> {
     let claude_path = dirs::home_dir()
         .ok_or("Failed to get home directory")?
         .join(".claude");
@@ -834,3 +836,52 @@ pub fn get_session_stats(
 
     Ok(by_session)
 }
+
+/// Claude usage as engine-neutral records, one per message
+pub(crate) fn usage_records() -> Vec<UsageRecord> {
+    let Some(home) = dirs::home_dir() else {
+        return Vec::new();
+    };
+    get_all_usage_entries(&home.join(".claude"))
+        .into_iter()
+        .filter_map(|entry| {
+            let timestamp = DateTime::parse_from_rfc3339(&entry.timestamp).ok()?;
+            Some(UsageRecord {
+                engine: "claude",
+                session_id: entry.session_id,
+                project_path: entry.project_path,
+                model: entry.model,
+                timestamp: timestamp.with_timezone(&Utc),
+                input_tokens: entry.input_tokens,
+                output_tokens: entry.output_tokens,
+                cache_creation_tokens: entry.cache_creation_tokens,
+                cache_read_tokens: entry.cache_read_tokens,
+                cost: entry.cost,
+            })
+        })
+        .collect()
+}
+
+/// Token usage and cost of all engines, grouped by project, engine or day
+///
+/// Defaults to all time, grouped by day.
+#[command]
+pub async fn get_usage_stats(
+    range: Option<UsageRange>,
+    group_by: Option<UsageGroupBy>,
+) -> Result<UsageReport, String> {
+    let records = tokio::task::spawn_blocking(|| {
+        let mut records = usage_records();
+        records.extend(super::codex::usage::usage_records());
+        records.extend(super::gemini::usage::usage_records());
+        records
+    })
+    .await
+    .map_err(|e| format!("Failed to collect usage: {}", e))?;
+
+    Ok(crate::usage::aggregate(
+        &records,
+        range.unwrap_or(UsageRange::All),
+        group_by.unwrap_or(UsageGroupBy::Day),
+    ))
+}
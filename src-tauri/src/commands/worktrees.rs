@@ -0,0 +1,294 @@
+//! Per-session git worktrees
+//!
+//! Engines running against the same checkout at the same time overwrite each
+//! other's edits. A session can instead get its own linked worktree: a
+//! separate checkout on a session branch (`anycode/<engine>/<session>`),
+//! created next to the repository in `<repo>.worktrees/<engine>-<session>`.
+//! All worktrees share the main repository's object store, so finished work
+//! is merged back like any other branch.
+//!
+//! Which session owns which worktree is recorded in `~/.anycode/worktrees.json`,
+//! so a worktree can be mapped back to its main repository and engine even
+//! after its directory has been deleted.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::commands::disk_space::{self, HeavyOperation};
+use crate::commands::engine_safety::project_key;
+use crate::commands::git_backend::{backend_for, WorktreeInfo};
+use crate::commands::git_executor::{GitOperation, LONG_TIMEOUT};
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Characters of the session id used in directory and branch names
+const SESSION_SLUG_LEN: usize = 12;
+
+lazy_static::lazy_static! {
+    static ref STORE_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A worktree created for an engine session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionWorktree {
+    pub worktree_path: String,
+    /// Main working tree of the repository
+    pub main_repo: String,
+    pub branch: String,
+    pub engine: String,
+    pub session_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A worktree as listed for a project, with its session when it has one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WorktreeEntry {
+    #[serde(flatten)]
+    pub worktree: WorktreeInfo,
+    pub main_repo: String,
+    pub session: Option<SessionWorktree>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct WorktreeStore {
+    #[serde(default)]
+    worktrees: Vec<SessionWorktree>,
+}
+
+fn store_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("worktrees.json"))
+}
+
+fn load_store() -> WorktreeStore {
+    store_path().and_then(load_json_config).unwrap_or_default()
+}
+
+fn update_store<T>(f: impl FnOnce(&mut WorktreeStore) -> Result<T, String>) -> Result<T, String> {
+    let _guard = STORE_LOCK.lock().map_err(|e| e.to_string())?;
+    let mut store = load_store();
+    let result = f(&mut store)?;
+    save_json_config(&store, store_path()?)?;
+    Ok(result)
+}
+
+/// Lowercase alphanumerics and dashes, for directory and branch names
+fn slug(value: &str, max_len: usize) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-')
+        .map(|c| c.to_ascii_lowercase())
+        .take(max_len)
+        .collect()
+}
+
+/// Main working tree of the repository containing `path` (itself when it is the main one)
+pub fn main_repo_path(path: &str) -> Result<String, String> {
+    backend_for(path)
+        .list_worktrees(path)?
+        .into_iter()
+        .find(|worktree| worktree.is_main)
+        .map(|worktree| worktree.path)
+        .ok_or_else(|| format!("No main working tree found for {}", path))
+}
+
+/// `<parent>/<repo>.worktrees/<engine>-<session>`
+fn session_worktree_path(
+    main_repo: &str,
+    engine: &str,
+    session_id: &str,
+) -> Result<PathBuf, String> {
+    let main = Path::new(main_repo);
+    let repo_name = main
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid repository path: {}", main_repo))?;
+    let parent = main
+        .parent()
+        .ok_or_else(|| format!("Repository has no parent directory: {}", main_repo))?;
+    Ok(parent
+        .join(format!("{}.worktrees", repo_name))
+        .join(format!("{}-{}", engine, slug(session_id, SESSION_SLUG_LEN))))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Create an isolated checkout for an engine session, on a new session branch
+#[tauri::command]
+pub async fn git_worktree_create(
+    project_path: String,
+    engine: String,
+    session_id: String,
+    start_point: Option<String>,
+) -> Result<SessionWorktree, String> {
+    let engine = slug(&engine, 32);
+    if engine.is_empty() || slug(&session_id, SESSION_SLUG_LEN).is_empty() {
+        return Err("Engine and session id are required".to_string());
+    }
+    if start_point.as_deref().is_some_and(|s| s.starts_with('-')) {
+        return Err("Invalid start point".to_string());
+    }
+
+    GitOperation::new(&project_path, "git worktree add")
+        .timeout(LONG_TIMEOUT)
+        .run(move || {
+            let main_repo = main_repo_path(&project_path)?;
+            let target = session_worktree_path(&main_repo, &engine, &session_id)?;
+            if target.exists() {
+                return Err(format!("Worktree already exists: {}", target.display()));
+            }
+            let worktree_path = target.to_string_lossy().to_string();
+            let branch = format!("anycode/{}/{}", engine, slug(&session_id, SESSION_SLUG_LEN));
+
+            disk_space::ensure_space_for(
+                &main_repo,
+                HeavyOperation::Worktree,
+                Some(&worktree_path),
+                None,
+            )?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+            }
+            backend_for(&main_repo).add_worktree(
+                &main_repo,
+                &worktree_path,
+                &branch,
+                start_point.as_deref(),
+            )?;
+            log::info!(
+                "[Worktree] Created {} on {} for {} session {}",
+                worktree_path,
+                branch,
+                engine,
+                session_id
+            );
+
+            let worktree = SessionWorktree {
+                worktree_path,
+                main_repo,
+                branch,
+                engine,
+                session_id,
+                created_at: Utc::now(),
+            };
+            let record = worktree.clone();
+            update_store(move |store| {
+                store.worktrees.push(record);
+                Ok(())
+            })?;
+            Ok(worktree)
+        })
+        .await
+}
+
+/// Worktrees of the repository containing `project_path`, main checkout first
+#[tauri::command]
+pub async fn git_worktree_list(project_path: String) -> Result<Vec<WorktreeEntry>, String> {
+    GitOperation::new(&project_path, "git worktree list")
+        .shared()
+        .run(move || {
+            let worktrees = backend_for(&project_path).list_worktrees(&project_path)?;
+            let main_repo = worktrees
+                .iter()
+                .find(|worktree| worktree.is_main)
+                .map(|worktree| worktree.path.clone())
+                .unwrap_or_else(|| project_path.clone());
+            let sessions = load_store().worktrees;
+            Ok(worktrees
+                .into_iter()
+                .map(|worktree| {
+                    let key = project_key(&worktree.path);
+                    let session = sessions
+                        .iter()
+                        .find(|s| project_key(&s.worktree_path) == key)
+                        .cloned();
+                    WorktreeEntry {
+                        worktree,
+                        main_repo: main_repo.clone(),
+                        session,
+                    }
+                })
+                .collect())
+        })
+        .await
+}
+
+/// Remove a linked worktree, optionally deleting its branch too
+#[tauri::command]
+pub async fn git_worktree_remove(
+    worktree_path: String,
+    force: Option<bool>,
+    delete_branch: Option<bool>,
+) -> Result<(), String> {
+    let force = force.unwrap_or(false);
+    GitOperation::new(&worktree_path, "git worktree remove")
+        .run(move || {
+            let key = project_key(&worktree_path);
+            let session = load_store()
+                .worktrees
+                .into_iter()
+                .find(|s| project_key(&s.worktree_path) == key);
+
+            // A deleted worktree directory can only be mapped back through the registry
+            let main_repo = match &session {
+                Some(session) => session.main_repo.clone(),
+                None => main_repo_path(&worktree_path)?,
+            };
+            if project_key(&main_repo) == key {
+                return Err("Cannot remove the main working tree".to_string());
+            }
+
+            let backend = backend_for(&main_repo);
+            let branch = match &session {
+                Some(session) => Some(session.branch.clone()),
+                None => backend
+                    .list_worktrees(&main_repo)?
+                    .into_iter()
+                    .find(|worktree| project_key(&worktree.path) == key)
+                    .and_then(|worktree| worktree.branch),
+            };
+
+            backend.remove_worktree(&main_repo, &worktree_path, force)?;
+            log::info!("[Worktree] Removed {}", worktree_path);
+
+            if delete_branch.unwrap_or(false) {
+                if let Some(branch) = branch {
+                    backend.delete_branch(&main_repo, &branch, force)?;
+                    log::info!("[Worktree] Deleted branch {}", branch);
+                }
+            }
+
+            update_store(|store| {
+                store
+                    .worktrees
+                    .retain(|s| project_key(&s.worktree_path) != key);
+                Ok(())
+            })
+        })
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session_worktree_path() {
+        let path = session_worktree_path(
+            "/home/u/projects/app",
+            "claude",
+            "9F2C41D0-77AB-4E1B-9C1E-1234567890AB",
+        )
+        .unwrap();
+        assert_eq!(
+            path,
+            PathBuf::from("/home/u/projects/app.worktrees/claude-9f2c41d0-77a")
+        );
+        assert_eq!(slug("rollout/../x y", 32), "rolloutxy");
+    }
+}
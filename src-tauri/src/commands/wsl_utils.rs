@@ -2024,6 +2024,123 @@ pub fn build_wsl_command_async(
     cmd
 }
 
+// ============================================================================
+// WSL 项目路由
+// ============================================================================
+
+/// 位于 WSL 文件系统内的项目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WslProjectLocation {
+    /// 所在发行版（Linux 绝对路径时为 None，即默认发行版）
+    pub distro: Option<String>,
+    /// WSL 内的项目路径
+    pub wsl_path: String,
+}
+
+/// 解析 WSL 项目路径
+///
+/// - `\\wsl$\<distro>\...` 或 `\\wsl.localhost\<distro>\...` -> 对应发行版
+/// - /home/user/proj 等 Linux 绝对路径（如 wslpath 的结果）-> 默认发行版
+/// - `/mnt/<drive>/...` 实际位于 Windows 盘符，不视为 WSL 项目
+pub fn parse_wsl_project_path(project_path: &str) -> Option<WslProjectLocation> {
+    if let Some((distro, wsl_path)) = try_parse_wsl_unc_path(project_path) {
+        return Some(WslProjectLocation {
+            distro: Some(distro),
+            wsl_path,
+        });
+    }
+    if project_path.starts_with('/') && !project_path.starts_with("/mnt/") {
+        return Some(WslProjectLocation {
+            distro: None,
+            wsl_path: project_path.to_string(),
+        });
+    }
+    None
+}
+
+/// 检测项目是否位于 WSL 内（仅 Windows，其他平台始终返回 None）
+pub fn detect_wsl_project(project_path: &str) -> Option<WslProjectLocation> {
+    if cfg!(target_os = "windows") {
+        parse_wsl_project_path(project_path)
+    } else {
+        None
+    }
+}
+
+/// 构建项目的 git 命令
+///
+/// WSL 项目通过 `wsl.exe` 调用发行版内的 git，避免 Windows git 操作 Linux 检出
+/// （换行符、文件权限、符号链接都会出错）；其他项目直接在项目目录执行 git。
+pub fn git_command(project_path: &str) -> std::process::Command {
+    #[cfg(target_os = "windows")]
+    if let Some(location) = detect_wsl_project(project_path) {
+        let mut cmd = Command::new("wsl");
+        if let Some(ref distro) = location.distro {
+            cmd.arg("-d").arg(distro);
+        }
+        cmd.arg("--cd").arg(&location.wsl_path).arg("--").arg("git");
+        cmd.creation_flags(CREATE_NO_WINDOW);
+        return cmd;
+    }
+
+    let mut cmd = std::process::Command::new("git");
+    cmd.current_dir(project_path);
+    #[cfg(target_os = "windows")]
+    cmd.creation_flags(CREATE_NO_WINDOW);
+    cmd
+}
+
+/// WSL 项目所在发行版中引擎 CLI（claude / codex / gemini）的路径，按发行版缓存
+#[cfg(target_os = "windows")]
+pub fn wsl_engine_program(engine: &str, distro: Option<&str>) -> Option<String> {
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    static CACHE: OnceLock<Mutex<HashMap<(String, String), Option<String>>>> = OnceLock::new();
+    let key = (engine.to_string(), distro.unwrap_or_default().to_string());
+    let cache = CACHE.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Some(program) = cache.lock().ok().and_then(|c| c.get(&key).cloned()) {
+        return program;
+    }
+
+    let program = match engine {
+        "claude" => check_wsl_claude(distro),
+        "codex" => check_wsl_codex(distro),
+        "gemini" => check_wsl_gemini(distro),
+        _ => None,
+    };
+    info!(
+        "[WSL] {} in distro {:?} for WSL project: {:?}",
+        engine, distro, program
+    );
+    if let Ok(mut cache) = cache.lock() {
+        cache.insert(key, program.clone());
+    }
+    program
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn wsl_engine_program(_engine: &str, _distro: Option<&str>) -> Option<String> {
+    None
+}
+
+/// 通过 WSLENV 把环境变量传入 WSL（wsl.exe 默认不转发 Windows 环境变量）
+pub fn forward_env_to_wsl(cmd: &mut tokio::process::Command, names: &[&str]) {
+    let mut entries: Vec<String> = std::env::var("WSLENV")
+        .unwrap_or_default()
+        .split(':')
+        .filter(|entry| !entry.is_empty())
+        .map(str::to_string)
+        .collect();
+    for name in names {
+        if !entries.iter().any(|entry| entry.split('/').next() == Some(name)) {
+            entries.push(name.to_string());
+        }
+    }
+    cmd.env("WSLENV", entries.join(":"));
+}
+
 // ============================================================================
 // 测试
 // ============================================================================
@@ -2061,6 +2178,23 @@ mod tests {
         assert_eq!(wsl_to_windows_path("/mnt/c"), "C:\\"); // 边界情况
     }
 
+    #[test]
+    fn test_parse_wsl_project_path() {
+        assert_eq!(
+            parse_wsl_project_path(r"\\wsl$\Ubuntu\home\me\app"),
+            Some(WslProjectLocation {
+                distro: Some("Ubuntu".to_string()),
+                wsl_path: "/home/me/app".to_string(),
+            })
+        );
+        assert_eq!(
+            parse_wsl_project_path("/home/me/app").map(|l| l.distro),
+            Some(None)
+        );
+        assert_eq!(parse_wsl_project_path("/mnt/c/Projects/app"), None);
+        assert_eq!(parse_wsl_project_path("C:\\Projects\\app"), None);
+    }
+
     #[test]
     fn test_build_wsl_unc_path() {
         let path = build_wsl_unc_path("/root/.codex/sessions", "Debian");
@@ -0,0 +1,78 @@
+//! Claude Code
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use super::{EngineCli, EngineProvider};
+use crate::session_runner::StreamDialect;
+
+pub struct ClaudeEngine;
+
+#[async_trait::async_trait]
+impl EngineProvider for ClaudeEngine {
+    fn id(&self) -> &'static str {
+        "claude"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Claude Code"
+    }
+
+    fn commit_tag(&self) -> &'static str {
+        "[Claude Code]"
+    }
+
+    fn mcp_config_path(&self) -> PathBuf {
+        crate::claude_mcp::user_config_path()
+    }
+
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String> {
+        crate::mcp::import_from_claude()
+    }
+
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String> {
+        crate::mcp::sync_servers_to_claude(servers)
+    }
+
+    fn upsert_mcp_server(&self, id: &str, spec: &Value) -> Result<(), String> {
+        crate::mcp::sync_single_server_to_claude(id, spec)
+    }
+
+    fn remove_mcp_server(&self, id: &str) -> Result<(), String> {
+        crate::mcp::remove_server_from_claude(id)
+    }
+
+    fn cli(&self) -> Option<EngineCli> {
+        Some(EngineCli {
+            program: "claude",
+            install_command: "npm install -g @anthropic-ai/claude-code",
+            login_command: "claude /login",
+        })
+    }
+
+    fn stream_dialect(&self) -> Option<StreamDialect> {
+        Some(StreamDialect::Unified)
+    }
+
+    fn api_endpoint(&self) -> Option<String> {
+        crate::commands::provider::configured_base_url()
+    }
+
+    async fn run_prompt(
+        &self,
+        app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        let binary = crate::claude_binary::find_claude_binary(app)?;
+        let mut cmd = Command::new(binary);
+        if let Some(model) = model {
+            cmd.args(["--model", model]);
+        }
+        cmd.args(["-p", "--output-format", "text", "--no-session-persistence"]);
+        super::cli::run_prompt(self.id(), cmd, prompt, None).await
+    }
+}
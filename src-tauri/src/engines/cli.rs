@@ -0,0 +1,90 @@
+//! Running engine CLIs for one-off prompts
+//!
+//! The prompt is written to the CLI's stdin, never passed as an argument, so
+//! conversation excerpts do not show up in process listings. The engines are
+//! told not to persist these runs; they also run in a scratch directory that
+//! the session listings skip, for CLIs that record them anyway.
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::commands::claude::apply_no_window_async;
+
+/// Timeout for one-off engine requests (titles, cached prompts)
+const PROMPT_TIMEOUT_SECS: u64 = 45;
+
+fn prompt_dir() -> PathBuf {
+    std::env::temp_dir().join("anycode-prompts")
+}
+
+/// Whether a project path recorded by an engine is the one-off prompt directory
+pub fn is_prompt_dir(project_path: &str) -> bool {
+    let dir = prompt_dir();
+    let path = Path::new(project_path);
+    path.starts_with(&dir)
+        || dir
+            .canonicalize()
+            .is_ok_and(|canonical| path.starts_with(canonical))
+}
+
+/// Run a non-interactive CLI invocation with `prompt` on stdin and return its reply
+///
+/// The reply is read from `reply_file` when the engine writes it there,
+/// otherwise from stdout.
+pub(super) async fn run_prompt(
+    engine: &str,
+    mut cmd: Command,
+    prompt: &str,
+    reply_file: Option<&Path>,
+) -> Result<String, String> {
+    // Run outside any project so the engine has nothing to explore
+    let dir = prompt_dir();
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    cmd.current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    apply_no_window_async(&mut cmd);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", engine, e))?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| format!("Failed to open {} stdin", engine))?;
+    let prompt = prompt.to_string();
+    // Written concurrently so a large prompt cannot block on a full stdout pipe
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(prompt.as_bytes()).await;
+    });
+
+    let output = tokio::time::timeout(
+        Duration::from_secs(PROMPT_TIMEOUT_SECS),
+        child.wait_with_output(),
+    )
+    .await
+    .map_err(|_| format!("{} did not answer within {}s", engine, PROMPT_TIMEOUT_SECS))?
+    .map_err(|e| format!("Failed to run {}: {}", engine, e))?;
+    writer.abort();
+
+    if !output.status.success() {
+        return Err(format!(
+            "{} exited with {}: {}",
+            engine,
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+
+    match reply_file {
+        Some(path) => std::fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {} output: {}", engine, e)),
+        None => Ok(String::from_utf8_lossy(&output.stdout).to_string()),
+    }
+}
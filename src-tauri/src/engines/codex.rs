@@ -0,0 +1,90 @@
+//! OpenAI Codex
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use super::{EngineCli, EngineProvider};
+use crate::claude_binary::detect_binary_for_tool;
+use crate::session_runner::StreamDialect;
+
+pub struct CodexEngine;
+
+#[async_trait::async_trait]
+impl EngineProvider for CodexEngine {
+    fn id(&self) -> &'static str {
+        "codex"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Codex"
+    }
+
+    fn commit_tag(&self) -> &'static str {
+        "[Codex]"
+    }
+
+    fn mcp_config_path(&self) -> PathBuf {
+        crate::codex_mcp::user_config_path()
+    }
+
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String> {
+        crate::mcp::import_from_codex()
+    }
+
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String> {
+        crate::mcp::sync_servers_to_codex(servers)
+    }
+
+    fn upsert_mcp_server(&self, id: &str, spec: &Value) -> Result<(), String> {
+        crate::mcp::sync_single_server_to_codex(id, spec)
+    }
+
+    fn remove_mcp_server(&self, id: &str) -> Result<(), String> {
+        crate::mcp::remove_server_from_codex(id)
+    }
+
+    fn cli(&self) -> Option<EngineCli> {
+        Some(EngineCli {
+            program: "codex",
+            install_command: "npm install -g @openai/codex",
+            login_command: "codex login",
+        })
+    }
+
+    fn stream_dialect(&self) -> Option<StreamDialect> {
+        Some(StreamDialect::Codex)
+    }
+
+    fn api_endpoint(&self) -> Option<String> {
+        crate::commands::codex::config::configured_base_url()
+    }
+
+    async fn run_prompt(
+        &self,
+        _app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        let (_env, detected) = detect_binary_for_tool("codex", "CODEX_PATH", "codex");
+        let binary = detected
+            .map(|inst| inst.path)
+            .unwrap_or_else(|| "codex".to_string());
+        let output_file = tempfile::NamedTempFile::new()
+            .map_err(|e| format!("Failed to create temp file: {}", e))?;
+
+        let mut cmd = Command::new(binary);
+        cmd.arg("exec");
+        if let Some(model) = model {
+            cmd.args(["--model", model]);
+        }
+        // codex exec streams progress to stdout, the final message goes to -o;
+        // `-` reads the prompt from stdin
+        cmd.args(["--skip-git-repo-check", "--ephemeral", "-o"])
+            .arg(output_file.path())
+            .arg("-");
+        super::cli::run_prompt(self.id(), cmd, prompt, Some(output_file.path())).await
+    }
+}
@@ -0,0 +1,78 @@
+//! Google Gemini CLI
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+use tokio::process::Command;
+
+use super::{EngineCli, EngineProvider};
+use crate::session_runner::StreamDialect;
+
+pub struct GeminiEngine;
+
+#[async_trait::async_trait]
+impl EngineProvider for GeminiEngine {
+    fn id(&self) -> &'static str {
+        "gemini"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Gemini"
+    }
+
+    fn commit_tag(&self) -> &'static str {
+        "[Gemini]"
+    }
+
+    fn mcp_config_path(&self) -> PathBuf {
+        crate::gemini_mcp::user_config_path()
+    }
+
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String> {
+        crate::mcp::import_from_gemini()
+    }
+
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String> {
+        crate::mcp::sync_servers_to_gemini(servers)
+    }
+
+    fn upsert_mcp_server(&self, id: &str, spec: &Value) -> Result<(), String> {
+        crate::mcp::sync_single_server_to_gemini(id, spec)
+    }
+
+    fn remove_mcp_server(&self, id: &str) -> Result<(), String> {
+        crate::mcp::remove_server_from_gemini(id)
+    }
+
+    fn cli(&self) -> Option<EngineCli> {
+        Some(EngineCli {
+            program: "gemini",
+            install_command: "npm install -g @google/gemini-cli",
+            login_command: "gemini",
+        })
+    }
+
+    fn stream_dialect(&self) -> Option<StreamDialect> {
+        Some(StreamDialect::Unified)
+    }
+
+    fn api_endpoint(&self) -> Option<String> {
+        crate::commands::gemini::provider::configured_base_url()
+    }
+
+    async fn run_prompt(
+        &self,
+        _app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        let binary = crate::commands::gemini::session::find_gemini_binary()?;
+        let mut cmd = Command::new(binary);
+        if let Some(model) = model {
+            cmd.args(["--model", model]);
+        }
+        // Without a TTY on stdin Gemini answers the piped prompt non-interactively
+        super::cli::run_prompt(self.id(), cmd, prompt, None).await
+    }
+}
@@ -0,0 +1,183 @@
+//! Engine abstraction layer
+//!
+//! Every engine the workbench drives (the Claude Code, Codex and Gemini CLIs,
+//! local models served by Ollama and hosted models on OpenRouter) is an
+//! [`EngineProvider`] registered in [`ENGINES`]. Code that needs
+//! engine-specific behavior (commit tags, config paths, MCP config format,
+//! running a prompt) looks the provider up by its id instead of matching on
+//! engine names, so supporting a new engine means adding one implementation
+//! here.
+
+mod claude;
+mod cli;
+mod codex;
+mod gemini;
+mod ollama;
+mod openrouter;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use crate::session_runner::StreamDialect;
+
+pub use claude::ClaudeEngine;
+pub use cli::is_prompt_dir;
+pub use codex::CodexEngine;
+pub use gemini::GeminiEngine;
+pub use ollama::OllamaEngine;
+pub use openrouter::OpenRouterEngine;
+
+/// Command-line tool an engine drives
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EngineCli {
+    /// Program name looked up on PATH (`"claude"`)
+    pub program: &'static str,
+    /// Command that installs the CLI
+    pub install_command: &'static str,
+    /// Command that signs the CLI in
+    pub login_command: &'static str,
+}
+
+/// An engine and the places where it differs from the others
+#[async_trait::async_trait]
+pub trait EngineProvider: Send + Sync {
+    /// Stable id used in settings, sessions and the MCP registry (`"claude"`)
+    fn id(&self) -> &'static str;
+
+    /// Name shown to the user
+    fn display_name(&self) -> &'static str;
+
+    /// Tag that starts auto-commit messages, e.g. `[Codex]`
+    fn commit_tag(&self) -> &'static str;
+
+    /// User config file that holds the engine's MCP servers
+    fn mcp_config_path(&self) -> PathBuf;
+
+    /// Read the MCP servers from the engine config, converted to the unified format
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String>;
+
+    /// Replace the MCP servers in the engine config (specs in the unified format)
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String>;
+
+    /// Add or replace a single MCP server
+    fn upsert_mcp_server(&self, id: &str, spec: &Value) -> Result<(), String> {
+        let mut servers = self.read_mcp_servers()?;
+        servers.insert(id.to_string(), spec.clone());
+        self.write_mcp_servers(&servers)
+    }
+
+    /// Remove a single MCP server
+    fn remove_mcp_server(&self, id: &str) -> Result<(), String> {
+        let mut servers = self.read_mcp_servers()?;
+        servers.remove(id);
+        self.write_mcp_servers(&servers)
+    }
+
+    /// Base URL the engine sends model requests to, when one is configured
+    fn api_endpoint(&self) -> Option<String> {
+        None
+    }
+
+    /// Whether the engine needs internet access
+    ///
+    /// Local-model engines do not, nor do engines pointed at an endpoint on
+    /// this machine or the local network.
+    fn requires_network(&self) -> bool {
+        self.api_endpoint().map_or(true, |url| {
+            !crate::commands::connectivity::is_local_endpoint(&url)
+        })
+    }
+
+    /// The CLI behind the engine, None when the workbench talks to the model itself
+    fn cli(&self) -> Option<EngineCli> {
+        None
+    }
+
+    /// Format of the CLI's streaming JSON output, parsed by the session runner
+    fn stream_dialect(&self) -> Option<StreamDialect> {
+        None
+    }
+
+    /// Answer a one-off prompt non-interactively and return the reply
+    async fn run_prompt(
+        &self,
+        app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String>;
+}
+
+/// Registered engines, in display order
+static ENGINES: &[&dyn EngineProvider] = &[
+    &ClaudeEngine,
+    &CodexEngine,
+    &GeminiEngine,
+    &OllamaEngine,
+    &OpenRouterEngine,
+];
+
+/// All registered engines
+pub fn all() -> &'static [&'static dyn EngineProvider] {
+    ENGINES
+}
+
+/// Look up an engine by id (case-insensitive)
+pub fn get(id: &str) -> Option<&'static dyn EngineProvider> {
+    let id = id.trim();
+    ENGINES
+        .iter()
+        .copied()
+        .find(|engine| engine.id().eq_ignore_ascii_case(id))
+}
+
+/// Look up an engine by id, failing with a user-facing error
+pub fn require(id: &str) -> Result<&'static dyn EngineProvider, String> {
+    get(id).ok_or_else(|| format!("Unsupported engine: {}", id))
+}
+
+/// Summary of a registered engine for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EngineInfo {
+    pub id: String,
+    pub display_name: String,
+    pub commit_tag: String,
+    pub mcp_config_path: String,
+}
+
+impl EngineInfo {
+    pub fn of(engine: &dyn EngineProvider) -> Self {
+        Self {
+            id: engine.id().to_string(),
+            display_name: engine.display_name().to_string(),
+            commit_tag: engine.commit_tag().to_string(),
+            mcp_config_path: engine.mcp_config_path().to_string_lossy().to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_lookup() {
+        let ids: Vec<&str> = all().iter().map(|engine| engine.id()).collect();
+        assert_eq!(
+            ids,
+            vec!["claude", "codex", "gemini", "ollama", "openrouter"]
+        );
+        assert!(!get("ollama").unwrap().requires_network());
+        assert_eq!(get("codex").unwrap().cli().unwrap().program, "codex");
+        assert!(get("openrouter").unwrap().cli().is_none());
+        assert_eq!(
+            get(" Codex ").map(|engine| engine.commit_tag()),
+            Some("[Codex]")
+        );
+        assert!(get("cursor").is_none());
+        assert!(require("cursor").is_err());
+    }
+}
@@ -0,0 +1,63 @@
+//! Ollama and OpenAI-compatible local model servers
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use super::EngineProvider;
+use crate::commands::ollama::client::{self, ChatMessage};
+use crate::commands::ollama::config;
+
+pub struct OllamaEngine;
+
+#[async_trait::async_trait]
+impl EngineProvider for OllamaEngine {
+    fn id(&self) -> &'static str {
+        "ollama"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "Ollama"
+    }
+
+    fn commit_tag(&self) -> &'static str {
+        "[Ollama]"
+    }
+
+    fn mcp_config_path(&self) -> PathBuf {
+        crate::chat_mcp::user_config_path(self.id())
+    }
+
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String> {
+        crate::chat_mcp::read_mcp_servers_map(self.id())
+    }
+
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String> {
+        crate::chat_mcp::set_mcp_servers_map(self.id(), servers)
+    }
+
+    fn requires_network(&self) -> bool {
+        false
+    }
+
+    async fn run_prompt(
+        &self,
+        _app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        let config = config::load_config();
+        let model = config::resolve_model(&config, model)?;
+        let turn = client::chat(
+            &config,
+            config::api_key().as_deref(),
+            &model,
+            &[ChatMessage::user(prompt)],
+            &[],
+            |_| {},
+        )
+        .await?;
+        Ok(turn.content)
+    }
+}
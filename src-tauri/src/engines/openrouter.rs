@@ -0,0 +1,59 @@
+//! OpenRouter hosted models
+
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use tauri::AppHandle;
+
+use super::EngineProvider;
+use crate::commands::ollama::client::{self, ChatMessage};
+use crate::commands::ollama::config;
+
+pub struct OpenRouterEngine;
+
+#[async_trait::async_trait]
+impl EngineProvider for OpenRouterEngine {
+    fn id(&self) -> &'static str {
+        "openrouter"
+    }
+
+    fn display_name(&self) -> &'static str {
+        "OpenRouter"
+    }
+
+    fn commit_tag(&self) -> &'static str {
+        "[OpenRouter]"
+    }
+
+    fn mcp_config_path(&self) -> PathBuf {
+        crate::chat_mcp::user_config_path(self.id())
+    }
+
+    fn read_mcp_servers(&self) -> Result<HashMap<String, Value>, String> {
+        crate::chat_mcp::read_mcp_servers_map(self.id())
+    }
+
+    fn write_mcp_servers(&self, servers: &HashMap<String, Value>) -> Result<(), String> {
+        crate::chat_mcp::set_mcp_servers_map(self.id(), servers)
+    }
+
+    async fn run_prompt(
+        &self,
+        _app: &AppHandle,
+        model: Option<&str>,
+        prompt: &str,
+    ) -> Result<String, String> {
+        let engine = crate::commands::openrouter::chat_engine()?;
+        let model = config::resolve_model(&engine.config, model)?;
+        let turn = client::chat(
+            &engine.config,
+            engine.api_key.as_deref(),
+            &model,
+            &[ChatMessage::user(prompt)],
+            &[],
+            |_| {},
+        )
+        .await?;
+        Ok(turn.content)
+    }
+}
@@ -3,7 +3,11 @@
 
 mod claude_binary;
 mod commands;
+mod engines;
 mod process;
+mod session_runner;
+mod session_store;
+mod usage;
 mod utils; // 新增：通用工具模块
 
 // MCP 多应用支持模块
@@ -11,6 +15,7 @@ mod mcp;
 mod claude_mcp;
 mod codex_mcp;
 mod gemini_mcp;
+mod chat_mcp;
 
 use claude_binary::init_shell_environment;
 
@@ -46,7 +51,39 @@ use commands::mcp::{
     mcp_get_unified_servers,
     // 多引擎独立隔离控制 API（新设计）
     mcp_get_engine_servers, mcp_upsert_engine_server, mcp_delete_engine_server,
-    mcp_toggle_engine_server, mcp_get_engine_servers_with_status,
+    mcp_toggle_engine_server, mcp_get_engine_servers_with_status, mcp_test_server,
+    mcp_list_tools, mcp_list_resources, mcp_set_tool_enabled,
+    // 注册表备份与恢复
+    list_registry_backups, restore_registry_backup,
+    // 项目级注册表
+    mcp_get_project_servers, mcp_upsert_project_server, mcp_demote_server, mcp_promote_server,
+    mcp_set_project_server_enabled,
+    // 进程监督
+    mcp_supervisor_start, mcp_supervisor_stop, mcp_supervisor_restart, mcp_supervisor_list,
+    mcp_supervisor_acquire, mcp_set_server_startup, mcp_set_server_sandbox,
+    mcp_get_server_logs,
+    // 配置组
+    mcp_list_profiles, mcp_create_profile, mcp_delete_profile, mcp_apply_profile,
+    // 外部工具导入与配置包
+    mcp_import_external, mcp_export_bundle, mcp_import_bundle,
+    // 密钥
+    mcp_mark_secret, mcp_unmark_secret,
+    // 差异检测
+    mcp_detect_drift, mcp_reconcile_drift,
+    // 配置验证
+    mcp_validate_spec,
+    // 服务器目录
+    mcp_get_catalog, mcp_install_from_catalog,
+    // 版本更新
+    mcp_check_updates, mcp_update_server,
+    // 工具名冲突
+    mcp_sync_engine, mcp_set_tool_prefix,
+    // 审计日志
+    mcp_get_audit_log,
+    // 搜索和标签
+    mcp_search_servers, mcp_list_server_tags, mcp_set_server_metadata,
+    // 工作台 MCP 服务器
+    mcp_install_workbench_server,
 };
 use commands::storage::{init_database, AgentDb};
 
@@ -60,7 +97,12 @@ use commands::provider::{
     get_current_provider_config, get_provider_config, get_provider_presets, query_provider_usage,
     reorder_provider_configs, switch_provider_config, test_provider_connection, update_provider_config,
 };
-use commands::simple_git::{check_and_init_git, check_reset_safety, precise_revert_code};
+use commands::simple_git::{
+    check_and_init_git, check_reset_safety, git_create_branch, git_delete_branch,
+    git_list_branches, git_reset_mixed, git_reset_soft, git_restore_files, git_revert_commit,
+    git_stash_apply, git_stash_drop, git_stash_list, git_stash_pop, git_switch_branch,
+    git_tag_create, git_tag_delete, git_tag_list, precise_revert_code,
+};
 use commands::storage::{
     storage_analyze_query, storage_delete_row, storage_execute_sql, storage_get_performance_stats,
     storage_insert_row, storage_list_tables, storage_read_table, storage_reset_database,
@@ -71,7 +113,9 @@ use commands::translator::{
     get_translation_config, init_translation_service_command, translate, translate_batch,
     update_translation_config,
 };
-use commands::usage::{get_session_stats, get_usage_by_date_range, get_usage_stats};
+use commands::usage::{
+    get_claude_usage_stats, get_session_stats, get_usage_by_date_range, get_usage_stats,
+};
 use commands::window::{
     broadcast_to_session_windows, close_session_window, create_session_window, emit_to_window,
     focus_session_window, list_session_windows, set_titlebar_theme,
@@ -117,6 +161,9 @@ use commands::codex::{
     update_codex_reasoning_level,
     get_codex_multi_agent_config,
     set_codex_multi_agent_config,
+    // Codex config.toml settings
+    get_codex_toml_settings,
+    update_codex_toml_settings,
     validate_codex_path_cmd,
     CodexProcessState,
 };
@@ -148,6 +195,8 @@ use commands::gemini::{
     get_gemini_provider_presets,
     get_gemini_session_detail,
     get_gemini_session_logs,
+    // Gemini settings.json commands
+    get_gemini_settings,
     get_gemini_system_prompt,
     // Gemini Usage Statistics
     get_gemini_usage_stats,
@@ -164,16 +213,168 @@ use commands::gemini::{
     test_gemini_provider_connection,
     update_gemini_config,
     update_gemini_provider_config,
+    update_gemini_settings,
     GeminiProcessState,
 };
+use commands::ollama::{
+    cancel_ollama, delete_ollama_session, execute_ollama, get_ollama_session, get_ollama_settings,
+    list_ollama_models, list_ollama_sessions, update_ollama_settings,
+};
+use commands::openrouter::{
+    cancel_openrouter, delete_openrouter_session, execute_openrouter, get_openrouter_session,
+    get_openrouter_settings, list_openrouter_models, list_openrouter_sessions,
+    set_openrouter_session_model, update_openrouter_settings,
+};
+use commands::process_reaper::{
+    adopt_orphan_process, list_orphan_processes, terminate_orphan_process,
+};
+use commands::resource_limits::{
+    get_process_resource_usage, get_resource_limits, update_resource_limits,
+};
+use commands::connectivity::{get_connectivity_status, refresh_connectivity_status};
+use commands::onboarding::run_onboarding_checks;
+use commands::doctor::anycode_doctor;
+use commands::engine_doctor::engine_doctor;
+use commands::redaction::{get_redaction_config, redact_export_content, update_redaction_config};
+use commands::session_bookmarks::{
+    add_session_bookmark, build_pinned_context, list_session_bookmarks, remove_session_bookmark,
+    update_session_bookmark,
+};
+use commands::session_search::search_session_history;
+use commands::task_context::{
+    build_task_prompt, delete_task_attachment, get_task_attachment, list_task_attachments,
+};
+use commands::forge::{
+    create_pull_request, get_forge_status, import_issue, link_pull_request_to_issue,
+    set_forge_host, set_forge_token,
+};
+use commands::claude_profiles::{
+    delete_claude_profile, get_session_claude_profile, list_claude_profiles, save_claude_profile,
+    switch_claude_profile,
+};
+use commands::engine_safety::{
+    get_project_safety_settings, get_session_safety_records, list_session_safety_records,
+    respond_tool_approval, update_project_safety_settings,
+};
+use commands::project_defaults::{
+    clear_project_defaults, get_project_defaults, update_project_defaults,
+};
+use commands::prompt_cache::{
+    clear_prompt_cache, get_prompt_cache_stats, run_cached_prompt, update_prompt_cache_config,
+};
+use commands::context_files::read_context_files;
+use commands::token_counter::count_tokens;
+use commands::screenshot::{capture_screenshot, list_screens};
+use commands::speech_to_text::{
+    cancel_voice_recording, get_speech_settings, start_voice_recording, stop_voice_recording,
+    update_speech_settings,
+};
+use commands::prompt_history::{
+    clear_prompt_history, delete_prompt_history_entry, get_prompt_history, search_prompt_history,
+};
+use commands::prompt_drafts::{
+    clear_prompt_draft, get_prompt_draft, list_prompt_drafts, save_prompt_draft,
+};
+use commands::agent_packs::{
+    export_agent_pack, get_pack_public_key, import_agent_pack, list_trusted_pack_publishers,
+    preview_agent_pack, remove_trusted_pack_publisher, trust_pack_publisher,
+};
+use commands::remote_projects::{
+    add_remote_project, cancel_remote_command, disconnect_remote_project, list_remote_directory,
+    list_remote_projects, read_remote_file, remove_remote_project, run_remote_command,
+    run_remote_git, test_remote_connection, write_remote_file,
+};
+use commands::dev_containers::{
+    detect_dev_container, run_in_dev_container, start_dev_container, update_dev_container_settings,
+};
+use commands::monorepo::{
+    apply_package_sparse_checkout, detect_workspace_packages, disable_sparse_checkout,
+    get_session_package_scope, set_session_package_scope,
+};
+use commands::large_file_guard::{
+    check_large_staged_files, get_large_file_guard_config, update_large_file_guard_config,
+};
+use commands::exclusion_policy::{
+    get_exclusion_settings, update_global_exclusions, update_project_exclusions,
+};
+use commands::text_encoding::{read_file_at_revision, read_text_file};
+use commands::disk_space::{check_disk_space_for, get_disk_space};
+use commands::event_batcher::{get_event_batching_config, update_event_batching_config};
+use commands::session_stream::{get_session_stream_settings, update_session_stream_settings};
+use commands::session_cancel::cancel_session;
+use commands::session_history::{get_session, list_sessions, resume_session};
+use commands::profiling::{
+    clear_performance_samples, get_performance_report, set_profiling_enabled,
+};
+use commands::worktrees::{git_worktree_create, git_worktree_list, git_worktree_remove};
+use commands::git_diff::{git_diff_commits, git_diff_working_tree};
+use commands::git_history::git_log;
+use commands::git_recovery::{git_recover_commits, git_restore_orphaned_commit};
+use commands::git_squash::git_squash_session_commits;
+use commands::git_blame::git_blame_file;
+use commands::submodules::{
+    git_list_submodules, git_set_submodules_excluded, git_update_submodules,
+};
+use commands::git_status_cache::{git_status_cached, git_status_unwatch};
+use commands::git_patches::{git_apply_patch, git_export_patch};
+use commands::git_cherry_pick::git_cherry_pick;
+use commands::project_settings::{get_project_settings, update_project_settings};
+use commands::git_executor::{cancel_git_operation, list_git_operations};
+use commands::git_doctor::{git_repo_doctor, git_repo_repair};
+use commands::git_staging::{
+    git_commit_staged, git_diff_unstaged, git_stage_hunks, git_stage_paths, git_unstage_paths,
+};
+use commands::branch_protection::{
+    get_branch_protection_settings, update_branch_protection_settings,
+};
+use commands::secret_scanner::{
+    get_last_secret_scan, get_secret_scan_config, scan_staged_secrets, update_secret_scan_config,
+};
+use commands::checkpoints::{
+    diff_checkpoint, list_checkpoints, name_checkpoint, restore_checkpoint,
+};
+use commands::auto_checkpoint::{get_auto_checkpoint_settings, update_auto_checkpoint_settings};
+use commands::commit_metadata::{get_commit_tag_settings, update_commit_tag_settings};
+use commands::commit_signing::{get_commit_signing_settings, update_commit_signing_settings};
+use commands::commit_templates::{
+    get_commit_template_settings, get_project_commit_template, preview_commit_message,
+    set_project_commit_template, update_commit_template_settings,
+};
+use commands::gitignore::{gitignore_add_patterns, gitignore_list, gitignore_remove_patterns};
+use commands::git_identity::{
+    get_git_identity, get_git_identity_settings, update_git_identity_settings,
+};
+use commands::key_pools::{
+    add_pool_key, get_key_pool_status, remove_pool_key, reset_pool_key, rotate_pool_key,
+};
+use commands::tickets::{configure_jira, configure_linear, get_ticket_integrations, import_ticket};
+use commands::session_titles::{
+    ensure_session_title, get_session_titles, regenerate_session_title, set_session_title,
+};
 use commands::git_stats::{get_git_diff_stats, get_session_code_changes};
+use commands::engines::list_engines;
 use process::ProcessRegistryState;
-use tauri::{Manager, WindowEvent};
+use tauri::{Manager, RunEvent, WindowEvent};
 use tauri_plugin_window_state::Builder as WindowStatePlugin;
 
 fn main() {
-    // Initialize logger
-    env_logger::init();
+    // Run as an MCP tool-prefix proxy when launched by an engine with the proxy flag
+    if let Some(code) = mcp::prefix::run_proxy_from_args() {
+        std::process::exit(code);
+    }
+    // Connect an engine to its supervised MCP server when it launches the bridge
+    if let Some(code) = mcp::bridge::run_from_args() {
+        std::process::exit(code);
+    }
+    // Run as the built-in workbench MCP server when an engine launches it
+    if let Some(code) = mcp::workbench::run_from_args() {
+        std::process::exit(code);
+    }
+
+    // Initialize logger (env_logger + in-memory capture of recent warnings/errors)
+    utils::log_capture::init();
+    // Load redaction patterns and known secret values before anything is logged
+    utils::redaction::reload();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
@@ -201,6 +402,40 @@ fn main() {
             // Initialize process registry
             app.manage(ProcessRegistryState::default());
 
+            // Let queued git operations report their progress
+            commands::git_executor::init(app.handle().clone());
+
+            // Let supervised MCP servers report state changes
+            mcp::supervisor::init(app.handle().clone());
+
+            // Accept engine connections to supervised MCP servers
+            tauri::async_runtime::spawn(mcp::bridge::serve());
+
+            // Start MCP servers whose startup policy is eager
+            tauri::async_runtime::spawn(async { mcp::supervisor::start_eager_servers() });
+
+            // Look for engine/MCP processes left behind by a crashed previous run
+            let app_handle_for_reaper = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::process_reaper::scan_orphans_on_startup(app_handle_for_reaper).await;
+            });
+
+            // Apply MCP resource limits to engine descendants as they appear
+            tauri::async_runtime::spawn(process::limits::start_enforcement_loop());
+
+            // Monitor connectivity so network-dependent commands fail fast while offline
+            let app_handle_for_connectivity = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::connectivity::start_connectivity_monitor(app_handle_for_connectivity)
+                    .await;
+            });
+
+            // Hot-reload hand-edited configuration under ~/.anycode
+            let app_handle_for_config = app.handle().clone();
+            tauri::async_runtime::spawn(async move {
+                commands::config_watcher::start_config_watcher(app_handle_for_config).await;
+            });
+
             // Initialize Claude process state
             app.manage(ClaudeProcessState::default());
 
@@ -350,6 +585,7 @@ fn main() {
             execute_pre_commit_review,
             // Usage & Analytics (Simplified from opcode)
             get_usage_stats,
+            get_claude_usage_stats,
             get_usage_by_date_range,
             get_session_stats,
             // MCP (Model Context Protocol)
@@ -382,6 +618,63 @@ fn main() {
             mcp_delete_engine_server,
             mcp_toggle_engine_server,
             mcp_get_engine_servers_with_status,
+            mcp_test_server,
+            mcp_list_tools,
+            mcp_list_resources,
+            mcp_set_tool_enabled,
+            // MCP 注册表备份与恢复
+            list_registry_backups,
+            restore_registry_backup,
+            // 项目级 MCP 注册表
+            mcp_get_project_servers,
+            mcp_upsert_project_server,
+            mcp_demote_server,
+            mcp_promote_server,
+            mcp_set_project_server_enabled,
+            // MCP 服务器进程监督
+            mcp_supervisor_start,
+            mcp_supervisor_stop,
+            mcp_supervisor_restart,
+            mcp_supervisor_list,
+            mcp_supervisor_acquire,
+            mcp_set_server_startup,
+            mcp_set_server_sandbox,
+            mcp_get_server_logs,
+            // MCP 配置组
+            mcp_list_profiles,
+            mcp_create_profile,
+            mcp_delete_profile,
+            mcp_apply_profile,
+            // 从外部工具导入 MCP 服务器
+            mcp_import_external,
+            // MCP 配置包
+            mcp_export_bundle,
+            mcp_import_bundle,
+            // MCP 密钥
+            mcp_mark_secret,
+            mcp_unmark_secret,
+            // MCP 差异检测
+            mcp_detect_drift,
+            mcp_reconcile_drift,
+            // MCP 配置验证
+            mcp_validate_spec,
+            // MCP 服务器目录
+            mcp_get_catalog,
+            mcp_install_from_catalog,
+            // MCP 版本更新
+            mcp_check_updates,
+            mcp_update_server,
+            // MCP 工具名冲突
+            mcp_sync_engine,
+            mcp_set_tool_prefix,
+            // MCP 审计日志
+            mcp_get_audit_log,
+            // MCP 搜索和标签
+            mcp_search_servers,
+            mcp_list_server_tags,
+            mcp_set_server_metadata,
+            // 工作台 MCP 服务器
+            mcp_install_workbench_server,
             // Storage Management
             storage_list_tables,
             storage_read_table,
@@ -499,6 +792,9 @@ fn main() {
             get_codex_multi_agent_config,
             set_codex_multi_agent_config,
             reorder_codex_provider_configs,
+            // Codex config.toml Settings
+            get_codex_toml_settings,
+            update_codex_toml_settings,
             // Codex Usage Statistics
             get_codex_usage_stats,
             // Session Conversion (Claude ↔ Codex)
@@ -547,9 +843,299 @@ fn main() {
             // Gemini WSL Commands
             get_gemini_wsl_mode_config,
             set_gemini_wsl_mode_config,
+            // Gemini settings.json
+            get_gemini_settings,
+            update_gemini_settings,
             // Gemini Usage Statistics
             get_gemini_usage_stats,
+            // Ollama / Local Models
+            execute_ollama,
+            cancel_ollama,
+            get_ollama_settings,
+            update_ollama_settings,
+            list_ollama_models,
+            list_ollama_sessions,
+            get_ollama_session,
+            delete_ollama_session,
+            // OpenRouter
+            execute_openrouter,
+            cancel_openrouter,
+            get_openrouter_settings,
+            update_openrouter_settings,
+            list_openrouter_models,
+            list_openrouter_sessions,
+            get_openrouter_session,
+            delete_openrouter_session,
+            set_openrouter_session_model,
+            // Orphan Process Reaper
+            list_orphan_processes,
+            terminate_orphan_process,
+            adopt_orphan_process,
+            // Resource Limits
+            get_resource_limits,
+            update_resource_limits,
+            get_process_resource_usage,
+            // Connectivity
+            get_connectivity_status,
+            refresh_connectivity_status,
+            // Onboarding
+            run_onboarding_checks,
+            // Diagnostics
+            anycode_doctor,
+            engine_doctor,
+            // Secret Redaction
+            get_redaction_config,
+            update_redaction_config,
+            redact_export_content,
+            // Session Titles
+            get_session_titles,
+            ensure_session_title,
+            regenerate_session_title,
+            set_session_title,
+            // Session Bookmarks
+            list_session_bookmarks,
+            add_session_bookmark,
+            update_session_bookmark,
+            remove_session_bookmark,
+            build_pinned_context,
+            // Session Search
+            search_session_history,
+            // Task Attachments
+            list_task_attachments,
+            get_task_attachment,
+            delete_task_attachment,
+            build_task_prompt,
+            // Git Forges (GitHub / GitLab / Bitbucket)
+            get_forge_status,
+            set_forge_token,
+            set_forge_host,
+            import_issue,
+            create_pull_request,
+            link_pull_request_to_issue,
+            // Ticket Integrations (Jira / Linear)
+            get_ticket_integrations,
+            configure_jira,
+            configure_linear,
+            import_ticket,
+            // Claude Profiles (account switching)
+            list_claude_profiles,
+            save_claude_profile,
+            delete_claude_profile,
+            switch_claude_profile,
+            get_session_claude_profile,
+            // API Key Pools (rotation / failover)
+            get_key_pool_status,
+            add_pool_key,
+            remove_pool_key,
+            reset_pool_key,
+            rotate_pool_key,
+            // Engine Safety Policy (sandbox / approval flags)
+            get_project_safety_settings,
+            update_project_safety_settings,
+            get_session_safety_records,
+            list_session_safety_records,
+            respond_tool_approval,
+            // Project Defaults (engine / model / mode per project)
+            get_project_defaults,
+            update_project_defaults,
+            clear_project_defaults,
+            // Prompt Cache (one-off engine requests)
+            run_cached_prompt,
+            get_prompt_cache_stats,
+            update_prompt_cache_config,
+            clear_prompt_cache,
+            // Context Files (batched @-mention reads)
+            read_context_files,
+            // Token Counting
+            count_tokens,
+            // Screenshots (visual bug reports)
+            list_screens,
+            capture_screenshot,
+            // Speech-to-Text (dictation)
+            get_speech_settings,
+            update_speech_settings,
+            start_voice_recording,
+            stop_voice_recording,
+            cancel_voice_recording,
+            // Prompt History
+            get_prompt_history,
+            search_prompt_history,
+            delete_prompt_history_entry,
+            clear_prompt_history,
+            // Prompt Drafts
+            save_prompt_draft,
+            get_prompt_draft,
+            list_prompt_drafts,
+            clear_prompt_draft,
+            // Agent Packs
+            export_agent_pack,
+            preview_agent_pack,
+            import_agent_pack,
+            get_pack_public_key,
+            list_trusted_pack_publishers,
+            trust_pack_publisher,
+            remove_trusted_pack_publisher,
+            // Remote Projects (SSH)
+            list_remote_projects,
+            add_remote_project,
+            remove_remote_project,
+            test_remote_connection,
+            disconnect_remote_project,
+            run_remote_git,
+            run_remote_command,
+            cancel_remote_command,
+            list_remote_directory,
+            read_remote_file,
+            write_remote_file,
+            // Dev Containers
+            detect_dev_container,
+            update_dev_container_settings,
+            start_dev_container,
+            run_in_dev_container,
+            // Monorepo Packages
+            detect_workspace_packages,
+            set_session_package_scope,
+            get_session_package_scope,
+            apply_package_sparse_checkout,
+            disable_sparse_checkout,
+            // Large-File Guard
+            get_large_file_guard_config,
+            update_large_file_guard_config,
+            check_large_staged_files,
+            // Exclusion Policy
+            get_exclusion_settings,
+            update_global_exclusions,
+            update_project_exclusions,
+            // Text Encoding
+            read_text_file,
+            read_file_at_revision,
+            // Disk Space
+            get_disk_space,
+            check_disk_space_for,
+            // Event Batching
+            get_event_batching_config,
+            update_event_batching_config,
+            // Session Event Stream
+            get_session_stream_settings,
+            update_session_stream_settings,
+            // Session Cancellation
+            cancel_session,
+            // Session History
+            list_sessions,
+            get_session,
+            resume_session,
+            // Profiling
+            set_profiling_enabled,
+            get_performance_report,
+            clear_performance_samples,
+            // Git Branches
+            git_list_branches,
+            git_create_branch,
+            git_switch_branch,
+            git_delete_branch,
+            // Git Tags
+            git_tag_list,
+            git_tag_create,
+            git_tag_delete,
+            // Git Worktrees
+            git_worktree_create,
+            git_worktree_list,
+            git_worktree_remove,
+            // Git Diff
+            git_diff_commits,
+            git_diff_working_tree,
+            // Git History
+            git_log,
+            // Git Recovery
+            git_recover_commits,
+            git_restore_orphaned_commit,
+            // Git Rollback
+            git_reset_soft,
+            git_reset_mixed,
+            git_revert_commit,
+            git_restore_files,
+            // Git Stash
+            git_stash_list,
+            git_stash_apply,
+            git_stash_pop,
+            git_stash_drop,
+            // Checkpoints
+            list_checkpoints,
+            restore_checkpoint,
+            diff_checkpoint,
+            name_checkpoint,
+            // Auto Checkpoint
+            get_auto_checkpoint_settings,
+            update_auto_checkpoint_settings,
+            // Commit Tags
+            get_commit_tag_settings,
+            update_commit_tag_settings,
+            // Gitignore
+            gitignore_list,
+            gitignore_add_patterns,
+            gitignore_remove_patterns,
+            // Git Identity
+            get_git_identity,
+            get_git_identity_settings,
+            update_git_identity_settings,
+            // Commit Templates
+            get_commit_template_settings,
+            update_commit_template_settings,
+            get_project_commit_template,
+            set_project_commit_template,
+            preview_commit_message,
+            // Commit Signing
+            get_commit_signing_settings,
+            update_commit_signing_settings,
+            // Git Squash
+            git_squash_session_commits,
+            // Git Blame
+            git_blame_file,
+            // Submodules
+            git_list_submodules,
+            git_update_submodules,
+            git_set_submodules_excluded,
+            // Git Status Cache
+            git_status_cached,
+            git_status_unwatch,
+            // Secret Scanning
+            get_secret_scan_config,
+            update_secret_scan_config,
+            scan_staged_secrets,
+            get_last_secret_scan,
+            // Git Patches
+            git_export_patch,
+            git_apply_patch,
+            // Branch Protection
+            get_branch_protection_settings,
+            update_branch_protection_settings,
+            // Git Cherry-pick
+            git_cherry_pick,
+            // Project Settings
+            get_project_settings,
+            update_project_settings,
+            // Git Operations
+            list_git_operations,
+            cancel_git_operation,
+            // Git Repository Doctor
+            git_repo_doctor,
+            git_repo_repair,
+            // Git Interactive Staging
+            git_stage_paths,
+            git_unstage_paths,
+            git_diff_unstaged,
+            git_stage_hunks,
+            git_commit_staged,
+            // Engines
+            list_engines,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Interrupt running engines and wait for them so none outlives the app
+            if let RunEvent::Exit = event {
+                log::info!("[App] Exiting, stopping all running sessions");
+                tauri::async_runtime::block_on(commands::session_cancel::shutdown_all(app));
+            }
+        });
 }
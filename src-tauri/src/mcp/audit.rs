@@ -0,0 +1,292 @@
+//! MCP 注册表变更审计日志
+//!
+//! 每次写入全局注册表时，与写入前的内容逐个服务器比较，把新增、删除、修改和启用状态
+//! 的变化追加到 `~/.anycode/mcp-registry.audit.jsonl`（每行一条 JSON 记录，只追加、
+//! 不改写），用于回答“这个服务器什么时候消失了 / 被改了”。健康检查结果和工具列表
+//! 缓存不是配置，不记录。`env` 和 `headers` 中键名像密钥的值记录为 `[REDACTED]`，
+//! 保存在钥匙串中的密钥本来就只有占位符。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+
+use super::registry::{McpRegistry, RegistryEntry};
+use super::secrets::{self, SECRET_BLOCKS};
+use crate::utils::redaction::{is_secret_key, REDACTED};
+
+/// 读取审计日志时默认返回的记录数
+pub const DEFAULT_LIMIT: usize = 200;
+
+/// 变更类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AuditAction {
+    Added,
+    Removed,
+    /// 配置或名称等发生变化
+    Updated,
+    /// 只有启用状态变化，且都是启用
+    Enabled,
+    /// 只有启用状态变化，且都是禁用
+    Disabled,
+}
+
+/// 一个字段的变化，`path` 形如 `server.args`、`enabled.claude`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditChange {
+    pub path: String,
+    pub before: Option<Value>,
+    pub after: Option<Value>,
+}
+
+/// 审计日志中的一条记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditRecord {
+    /// RFC 3339 时间戳
+    pub timestamp: String,
+    pub server: String,
+    pub action: AuditAction,
+    pub changes: Vec<AuditChange>,
+}
+
+/// 获取审计日志路径
+fn audit_path() -> PathBuf {
+    let home_dir = dirs::home_dir().expect("Failed to get home directory");
+    home_dir.join(".anycode").join("mcp-registry.audit.jsonl")
+}
+
+/// 条目中需要审计的字段，展开为 路径 -> 值（对象逐层展开，数组整体比较）
+fn flatten(entry: &RegistryEntry) -> BTreeMap<String, Value> {
+    let mut fields = BTreeMap::new();
+    let mut snapshot = serde_json::to_value(entry).unwrap_or(Value::Null);
+    if let Some(obj) = snapshot.as_object_mut() {
+        for key in ["id", "health", "introspection"] {
+            obj.remove(key);
+        }
+        flatten_into("", obj, &mut fields);
+    }
+    fields
+}
+
+fn flatten_into(prefix: &str, obj: &Map<String, Value>, fields: &mut BTreeMap<String, Value>) {
+    for (key, value) in obj {
+        let path = if prefix.is_empty() {
+            key.clone()
+        } else {
+            format!("{}.{}", prefix, key)
+        };
+        match value {
+            Value::Object(child) if !child.is_empty() => flatten_into(&path, child, fields),
+            _ => {
+                let secret_block = SECRET_BLOCKS
+                    .iter()
+                    .any(|block| prefix == format!("server.{}", block));
+                let value = if secret_block
+                    && is_secret_key(key)
+                    && value.is_string()
+                    && !secrets::is_marker(value)
+                {
+                    Value::from(REDACTED)
+                } else {
+                    value.clone()
+                };
+                fields.insert(path, value);
+            }
+        }
+    }
+}
+
+/// 比较同一服务器写入前后的条目，没有变化时返回 None
+fn diff_entry(
+    id: &str,
+    before: Option<&RegistryEntry>,
+    after: Option<&RegistryEntry>,
+    timestamp: &str,
+) -> Option<AuditRecord> {
+    let before_fields = before.map(flatten).unwrap_or_default();
+    let after_fields = after.map(flatten).unwrap_or_default();
+
+    let mut paths: Vec<&String> = before_fields.keys().chain(after_fields.keys()).collect();
+    paths.sort();
+    paths.dedup();
+    let changes: Vec<AuditChange> = paths
+        .into_iter()
+        .filter(|path| before_fields.get(*path) != after_fields.get(*path))
+        .map(|path| AuditChange {
+            path: path.clone(),
+            before: before_fields.get(path).cloned(),
+            after: after_fields.get(path).cloned(),
+        })
+        .collect();
+    if changes.is_empty() {
+        return None;
+    }
+
+    let only_enablement = |enabled: bool| {
+        changes.iter().all(|change| {
+            change.path.starts_with("enabled.") && change.after == Some(Value::Bool(enabled))
+        })
+    };
+    let action = match (before, after) {
+        (None, _) => AuditAction::Added,
+        (_, None) => AuditAction::Removed,
+        _ if only_enablement(true) => AuditAction::Enabled,
+        _ if only_enablement(false) => AuditAction::Disabled,
+        _ => AuditAction::Updated,
+    };
+    Some(AuditRecord {
+        timestamp: timestamp.to_string(),
+        server: id.to_string(),
+        action,
+        changes,
+    })
+}
+
+/// 比较写入前后的注册表，按服务器 ID 排序返回变化
+pub(super) fn diff_registries(before: &McpRegistry, after: &McpRegistry) -> Vec<AuditRecord> {
+    let timestamp = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true);
+    let mut ids: Vec<&String> = before.servers.keys().chain(after.servers.keys()).collect();
+    ids.sort();
+    ids.dedup();
+    ids.into_iter()
+        .filter_map(|id| {
+            diff_entry(
+                id,
+                before.servers.get(id),
+                after.servers.get(id),
+                &timestamp,
+            )
+        })
+        .collect()
+}
+
+/// 追加审计记录；失败只记录警告，不影响注册表的写入
+pub(super) fn append(records: &[AuditRecord]) {
+    if records.is_empty() {
+        return;
+    }
+    let result = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(audit_path())
+        .and_then(|mut file| {
+            let mut content = String::new();
+            for record in records {
+                content.push_str(&serde_json::to_string(record).unwrap_or_default());
+                content.push('\n');
+            }
+            file.write_all(content.as_bytes())
+        });
+    if let Err(e) = result {
+        log::warn!("写入 MCP 注册表审计日志失败: {}", e);
+    }
+}
+
+/// 读取审计日志（最新的在前），`server` 给出时只返回该服务器的记录
+pub fn read_log(server: Option<&str>, limit: usize) -> Result<Vec<AuditRecord>, String> {
+    let content = match std::fs::read_to_string(audit_path()) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取审计日志失败: {}", e)),
+    };
+    Ok(content
+        .lines()
+        .rev()
+        .filter_map(|line| serde_json::from_str::<AuditRecord>(line).ok())
+        .filter(|record| server.map_or(true, |id| record.server == id))
+        .take(limit)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    fn entry(id: &str, server: Value, claude: bool) -> RegistryEntry {
+        RegistryEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            server,
+            enabled: HashMap::from([("claude".to_string(), claude)]),
+            health: None,
+            introspection: None,
+            disabled_tools: Vec::new(),
+            tool_prefix: None,
+            startup: Default::default(),
+            description: None,
+            tags: Vec::new(),
+            sandbox: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_registries() {
+        let mut before = McpRegistry::default();
+        let mut after = McpRegistry::default();
+        for (registry, entries) in [
+            (
+                &mut before,
+                vec![
+                    entry("gone", json!({ "command": "npx" }), true),
+                    entry("toggled", json!({ "command": "uvx" }), false),
+                    entry("changed", json!({ "command": "npx", "args": ["a"] }), true),
+                ],
+            ),
+            (
+                &mut after,
+                vec![
+                    entry("toggled", json!({ "command": "uvx" }), true),
+                    entry(
+                        "changed",
+                        json!({ "command": "npx", "args": ["b"], "env": { "API_TOKEN": "t0ps3cret" } }),
+                        true,
+                    ),
+                    entry("new", json!({ "command": "node" }), false),
+                ],
+            ),
+        ] {
+            for entry in entries {
+                registry.servers.insert(entry.id.clone(), entry);
+            }
+        }
+
+        let records = diff_registries(&before, &after);
+        let summary: Vec<(&str, AuditAction)> = records
+            .iter()
+            .map(|record| (record.server.as_str(), record.action))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("changed", AuditAction::Updated),
+                ("gone", AuditAction::Removed),
+                ("new", AuditAction::Added),
+                ("toggled", AuditAction::Enabled),
+            ]
+        );
+        assert_eq!(
+            records[0].changes,
+            vec![
+                AuditChange {
+                    path: "server.args".to_string(),
+                    before: Some(json!(["a"])),
+                    after: Some(json!(["b"])),
+                },
+                AuditChange {
+                    path: "server.env.API_TOKEN".to_string(),
+                    before: None,
+                    after: Some(json!(REDACTED)),
+                },
+            ]
+        );
+
+        // 内容相同时没有记录
+        assert!(diff_registries(&after, &after).is_empty());
+    }
+}
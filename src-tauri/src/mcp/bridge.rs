@@ -0,0 +1,392 @@
+//! 引擎与受监督 MCP 服务器进程之间的桥接
+//!
+//! 注册表中的 stdio 服务器写入引擎配置时不再是原命令，而是
+//! `any-code --mcp-bridge <服务器 ID>`，引擎配置中没有原命令、`env` 和密钥。引擎启动
+//! 桥接进程后，桥接进程通过本机端口连接应用，由监督模块（[`super::supervisor`]）按启动
+//! 策略启动或复用服务器进程，并在进程和引擎之间转发消息（见 [`super::router`]）。
+//! 同一工作目录中的多个引擎会话共享一个进程，工具前缀、资源限制和沙箱
+//! （[`super::sandbox`]）、日志和自动重启都由应用负责。
+//!
+//! 应用未运行时（例如在终端中直接使用引擎），桥接进程按注册表自己启动服务器，
+//! 同样执行沙箱和工具前缀；启动策略为 `manual` 的服务器不会启动。
+//!
+//! 应用启动时监听 127.0.0.1 上的随机端口，端口和令牌写入 `~/.anycode/mcp-bridge.json`，
+//! 只接受带正确令牌的连接。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::net::Ipv4Addr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use super::health::stdio_command;
+use super::prefix::{prefix_tools, strip_tool_call};
+use super::registry::{self, RegistryEntry, DISABLED_TOOLS_KEY};
+use super::sandbox;
+use super::supervisor::{self, ServerLaunch, StartupPolicy};
+use super::McpTransport;
+use crate::utils::config_utils::ConfigPathBuilder;
+
+/// 桥接模式的命令行标志
+pub const BRIDGE_FLAG: &str = "--mcp-bridge";
+
+/// 等待桥接进程发送握手的时间
+const HELLO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// 应用监听的端口和令牌
+#[derive(Debug, Serialize, Deserialize)]
+struct Endpoint {
+    port: u16,
+    token: String,
+}
+
+/// 桥接进程连接后发送的第一行
+#[derive(Debug, Serialize, Deserialize)]
+struct Hello {
+    token: String,
+    server: String,
+    /// 引擎的工作目录
+    cwd: PathBuf,
+}
+
+fn endpoint_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("mcp-bridge.json"))
+}
+
+/// 将 stdio 服务器规范改为通过桥接启动；保留 `type` 和禁用工具
+pub fn wrap(spec: &Value, id: &str) -> Result<Value, String> {
+    if McpTransport::from_spec(spec)? != McpTransport::Stdio {
+        return Err("只有 stdio 服务器可以通过桥接启动".to_string());
+    }
+    let exe = std::env::current_exe().map_err(|e| format!("获取应用路径失败: {}", e))?;
+
+    let mut bridged = json!({
+        "command": exe.to_string_lossy(),
+        "args": [BRIDGE_FLAG, id],
+    });
+    for key in ["type", DISABLED_TOOLS_KEY] {
+        if let Some(value) = spec.get(key) {
+            bridged[key] = value.clone();
+        }
+    }
+    Ok(bridged)
+}
+
+/// [`wrap`] 的逆操作：通过桥接启动的规范返回服务器 ID，否则返回 None
+pub fn unwrap(spec: &Value) -> Option<String> {
+    let args = spec.get("args")?.as_array()?;
+    if args.first()?.as_str()? != BRIDGE_FLAG {
+        return None;
+    }
+    Some(args.get(1)?.as_str()?.to_string())
+}
+
+/// 查找服务器：引擎工作目录的项目注册表优先，然后是全局注册表
+fn find_entry(id: &str, cwd: &Path) -> Result<RegistryEntry, String> {
+    let project = registry::read_project_registry(&cwd.to_string_lossy())?;
+    if let Some(entry) = project.servers.get(id) {
+        return Ok(entry.clone());
+    }
+    registry::get_server(id)?.ok_or_else(|| format!("注册表中没有 MCP 服务器 '{}'", id))
+}
+
+async fn write_line(writer: &mut (impl AsyncWrite + Unpin), line: &str) -> std::io::Result<()> {
+    writer.write_all(format!("{}\n", line).as_bytes()).await?;
+    writer.flush().await
+}
+
+// ============================================================================
+// 应用端
+// ============================================================================
+
+/// 接受桥接进程的连接（应用启动时调用，一直运行）
+pub async fn serve() {
+    if let Err(e) = listen().await {
+        log::warn!("MCP 桥接服务不可用，桥接进程将自己启动服务器: {}", e);
+    }
+}
+
+async fn listen() -> Result<(), String> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .await
+        .map_err(|e| format!("监听本机端口失败: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("获取监听端口失败: {}", e))?
+        .port();
+    let token = uuid::Uuid::new_v4().simple().to_string();
+    write_endpoint(&Endpoint {
+        port,
+        token: token.clone(),
+    })?;
+    log::info!("MCP 桥接服务监听 127.0.0.1:{}", port);
+
+    loop {
+        let (stream, _) = listener
+            .accept()
+            .await
+            .map_err(|e| format!("接受桥接连接失败: {}", e))?;
+        let token = token.clone();
+        tokio::spawn(async move {
+            if let Err(e) = handle(stream, &token).await {
+                log::warn!("MCP 桥接连接失败: {}", e);
+            }
+        });
+    }
+}
+
+/// 写入连接信息，只有当前用户可读
+fn write_endpoint(endpoint: &Endpoint) -> Result<(), String> {
+    let path = endpoint_path()?;
+    if let Some(dir) = path.parent() {
+        std::fs::create_dir_all(dir).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    let content = serde_json::to_string(endpoint).map_err(|e| e.to_string())?;
+    std::fs::write(&path, content).map_err(|e| format!("写入桥接连接信息失败: {}", e))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))
+            .map_err(|e| format!("设置桥接连接信息的权限失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 一个桥接连接：握手后连接受监督的服务器，转发消息直到任一端关闭
+async fn handle(stream: TcpStream, token: &str) -> Result<(), String> {
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let hello = tokio::time::timeout(HELLO_TIMEOUT, lines.next_line())
+        .await
+        .map_err(|_| "等待桥接握手超时".to_string())?
+        .map_err(|e| format!("读取桥接握手失败: {}", e))?
+        .ok_or("桥接进程已断开")?;
+
+    let attached = serde_json::from_str::<Hello>(&hello)
+        .map_err(|e| format!("无效的桥接握手: {}", e))
+        .and_then(|hello| {
+            if hello.token != token {
+                return Err("桥接令牌无效".to_string());
+            }
+            let launch = ServerLaunch::from_entry(&find_entry(&hello.server, &hello.cwd)?)?;
+            supervisor::attach(launch, Some(hello.cwd.as_path()))
+        });
+    let reply = match &attached {
+        Ok(_) => json!({ "ok": true }),
+        Err(e) => json!({ "error": e }),
+    };
+    write_line(&mut writer, &reply.to_string())
+        .await
+        .map_err(|e| format!("回复桥接握手失败: {}", e))?;
+    let mut session = attached?;
+
+    loop {
+        tokio::select! {
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => session.send(&line),
+                _ => break,
+            },
+            message = session.recv() => match message {
+                Some(message) => {
+                    if write_line(&mut writer, &message).await.is_err() {
+                        break;
+                    }
+                }
+                // 服务器已停止
+                None => break,
+            },
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// 桥接进程
+// ============================================================================
+
+/// 以桥接模式启动时（第一个参数为 [`BRIDGE_FLAG`]）运行桥接，返回退出码；否则返回 None
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some(BRIDGE_FLAG) {
+        return None;
+    }
+    let Some(id) = args.get(1) else {
+        eprintln!("用法: {} <服务器 ID>", BRIDGE_FLAG);
+        return Some(1);
+    };
+    let runtime = match tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("创建运行时失败: {}", e);
+            return Some(1);
+        }
+    };
+    let code = runtime.block_on(run_bridge(id)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        1
+    });
+    // 读取 stdin 的后台线程可能仍在阻塞，不等待它结束
+    runtime.shutdown_background();
+    Some(code)
+}
+
+async fn run_bridge(id: &str) -> Result<i32, String> {
+    let cwd = std::env::current_dir().map_err(|e| format!("获取工作目录失败: {}", e))?;
+    match connect(id, &cwd).await? {
+        Some((reader, mut writer)) => {
+            let mut stdin = tokio::io::stdin();
+            let mut reader = reader;
+            let mut stdout = tokio::io::stdout();
+            tokio::select! {
+                _ = tokio::io::copy(&mut stdin, &mut writer) => {}
+                _ = tokio::io::copy(&mut reader, &mut stdout) => {}
+            }
+            Ok(0)
+        }
+        None => {
+            eprintln!("[any-code] 无法连接应用，直接启动 MCP 服务器 '{}'", id);
+            run_direct(id, &cwd).await
+        }
+    }
+}
+
+type Connection = (
+    BufReader<tokio::net::tcp::OwnedReadHalf>,
+    tokio::net::tcp::OwnedWriteHalf,
+);
+
+/// 连接应用；应用未运行时返回 None，应用拒绝时（例如服务器需要手动启动）返回错误
+async fn connect(id: &str, cwd: &Path) -> Result<Option<Connection>, String> {
+    let Some(endpoint) = std::fs::read_to_string(endpoint_path()?)
+        .ok()
+        .and_then(|content| serde_json::from_str::<Endpoint>(&content).ok())
+    else {
+        return Ok(None);
+    };
+    let Ok(stream) = TcpStream::connect((Ipv4Addr::LOCALHOST, endpoint.port)).await else {
+        return Ok(None);
+    };
+    let (reader, mut writer) = stream.into_split();
+    let mut reader = BufReader::new(reader);
+
+    let hello = Hello {
+        token: endpoint.token,
+        server: id.to_string(),
+        cwd: cwd.to_path_buf(),
+    };
+    let hello = serde_json::to_string(&hello).map_err(|e| e.to_string())?;
+    if write_line(&mut writer, &hello).await.is_err() {
+        return Ok(None);
+    }
+    let mut reply = String::new();
+    let reply = match reader.read_line(&mut reply).await {
+        Ok(n) if n > 0 => serde_json::from_str::<Value>(&reply).ok(),
+        // 端口已被其它程序使用（应用上次未正常退出）
+        _ => None,
+    };
+    match reply {
+        Some(reply) if reply.get("ok") == Some(&Value::Bool(true)) => Ok(Some((reader, writer))),
+        Some(reply) => match reply.get("error").and_then(Value::as_str) {
+            Some(e) => Err(e.to_string()),
+            None => Ok(None),
+        },
+        None => Ok(None),
+    }
+}
+
+/// 逐行转发 JSON-RPC 消息，无法解析的行原样转发
+async fn pump(
+    reader: impl AsyncBufRead + Unpin,
+    mut writer: impl AsyncWrite + Unpin,
+    rewrite: impl Fn(&mut Value),
+) {
+    let mut lines = reader.lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let line = match serde_json::from_str::<Value>(&line) {
+            Ok(mut message) => {
+                rewrite(&mut message);
+                message.to_string()
+            }
+            Err(_) => line,
+        };
+        if write_line(&mut writer, &line).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// 应用未运行时自己启动服务器，执行沙箱和工具前缀
+async fn run_direct(id: &str, cwd: &Path) -> Result<i32, String> {
+    let entry = find_entry(id, cwd)?;
+    if entry.startup.policy == StartupPolicy::Manual {
+        return Err(format!("MCP 服务器 '{}' 需要在应用中手动启动", id));
+    }
+    let spec = entry.launch_spec()?;
+    let dir = sandbox::working_dir(&spec, &entry.sandbox, Some(cwd))?;
+    let mut cmd = stdio_command(&spec);
+    cmd.stderr(std::process::Stdio::inherit());
+    let limits = sandbox::apply_to_command(&mut cmd, &entry.sandbox, dir.as_deref())?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("启动 MCP 服务器 '{}' 失败: {}", id, e))?;
+    let _job = match child.id().map(|pid| sandbox::contain(pid, &limits)) {
+        Some(Err(e)) => {
+            let _ = child.start_kill();
+            return Err(format!("无法对 MCP 服务器 '{}' 应用资源限制: {}", id, e));
+        }
+        Some(Ok(job)) => job,
+        None => None,
+    };
+    let child_stdin = child.stdin.take().ok_or("无法获取服务器的 stdin")?;
+    let child_stdout = child.stdout.take().ok_or("无法获取服务器的 stdout")?;
+
+    let prefix = entry.tool_prefix.unwrap_or_default();
+    // 引擎关闭 stdin 后服务器的 stdin 也随之关闭，服务器退出后 stdout 结束
+    tokio::select! {
+        _ = pump(BufReader::new(tokio::io::stdin()), child_stdin, |message| {
+            strip_tool_call(message, &prefix)
+        }) => {}
+        _ = pump(BufReader::new(child_stdout), tokio::io::stdout(), |message| {
+            prefix_tools(message, &prefix)
+        }) => {}
+    }
+
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("等待 MCP 服务器退出失败: {}", e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap() {
+        let spec = json!({
+            "type": "stdio",
+            "command": "npx",
+            "args": ["-y", "server-github"],
+            "env": { "GITHUB_TOKEN": "secret" },
+            "disabledTools": ["delete_repo"],
+        });
+        let bridged = wrap(&spec, "github").unwrap();
+        assert_eq!(bridged["args"], json!([BRIDGE_FLAG, "github"]));
+        assert_eq!(bridged["type"], json!("stdio"));
+        assert_eq!(bridged["disabledTools"], json!(["delete_repo"]));
+        assert!(bridged.get("env").is_none());
+        assert_eq!(unwrap(&bridged), Some("github".to_string()));
+        assert_eq!(unwrap(&spec), None);
+        assert!(wrap(
+            &json!({ "type": "http", "url": "https://x.example/mcp" }),
+            "x"
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,439 @@
+//! MCP 配置包的导出与导入
+//!
+//! 配置包是一个 JSON 文件，包含注册表中的服务器（以及只存在于引擎配置中的服务器）、
+//! 配置组和各引擎启用的服务器列表，团队成员导入后即可得到相同的 MCP 环境。
+//!
+//! 默认不包含密钥：`env` 和 `headers` 中键名像密钥的值（见 `utils::redaction`）
+//! 以及保存在钥匙串中的值（见 `secrets`）被清空，并在 `strippedSecrets` 中列出，
+//! 导入方需要自行填写。导入时如果注册表中同一服务器已有该值，则保留已有的值。
+//! 导入的明文密钥（包含密钥的配置包，或本机沿用的旧明文值）在写入注册表前
+//! 移入系统钥匙串。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use super::registry::{self, McpProfile, McpRegistry, RegistryEntry};
+use super::secrets::{self, SECRET_BLOCKS};
+use super::validation::validate_server_spec;
+use super::AppType;
+use crate::utils::redaction::is_secret_key;
+
+/// 配置包格式标识
+const BUNDLE_FORMAT: &str = "anycode-mcp-bundle";
+/// 当前配置包格式版本
+const BUNDLE_VERSION: u32 = 1;
+
+/// 配置包中的服务器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleServer {
+    pub id: String,
+    pub name: String,
+    pub server: Value,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+}
+
+/// 被清空的密钥位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StrippedSecret {
+    pub server: String,
+    /// "env" 或 "headers"
+    pub block: String,
+    pub key: String,
+}
+
+/// MCP 配置包
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpBundle {
+    pub format: String,
+    pub version: u32,
+    pub exported_at: String,
+    pub servers: Vec<BundleServer>,
+    #[serde(default)]
+    pub profiles: Vec<McpProfile>,
+    /// 引擎 -> 在该引擎中启用的服务器 ID
+    #[serde(default)]
+    pub engines: BTreeMap<String, Vec<String>>,
+    pub secrets_included: bool,
+    #[serde(default)]
+    pub stripped_secrets: Vec<StrippedSecret>,
+}
+
+/// 导入结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleImport {
+    /// 新增或覆盖的服务器
+    pub imported: Vec<String>,
+    /// 注册表中已存在、未覆盖的服务器
+    pub skipped: Vec<String>,
+    /// 导入的配置组
+    pub profiles: Vec<String>,
+    /// 仍需填写的密钥
+    pub missing_secrets: Vec<StrippedSecret>,
+}
+
+/// 清空服务器配置中的密钥，返回被清空的位置
+fn strip_secrets(id: &str, server: &mut Value) -> Vec<StrippedSecret> {
+    let mut stripped = Vec::new();
+    for block in SECRET_BLOCKS {
+        let Some(values) = server.get_mut(*block).and_then(Value::as_object_mut) else {
+            continue;
+        };
+        for (key, value) in values.iter_mut() {
+            let secret = is_secret_key(key) || secrets::is_marker(value);
+            if secret && value.as_str().is_some_and(|v| !v.is_empty()) {
+                *value = Value::String(String::new());
+                stripped.push(StrippedSecret {
+                    server: id.to_string(),
+                    block: block.to_string(),
+                    key: key.clone(),
+                });
+            }
+        }
+    }
+    stripped
+}
+
+/// 由注册表和各引擎的服务器生成配置包
+fn build_bundle(
+    registry: &McpRegistry,
+    engine_servers: &HashMap<String, HashMap<String, Value>>,
+    include_secrets: bool,
+) -> McpBundle {
+    let mut servers: BTreeMap<String, BundleServer> = registry
+        .servers
+        .iter()
+        .map(|(id, entry)| {
+            let server = BundleServer {
+                id: id.clone(),
+                name: entry.name.clone(),
+                server: entry.server.clone(),
+                disabled_tools: entry.disabled_tools.clone(),
+            };
+            (id.clone(), server)
+        })
+        .collect();
+    // 只存在于引擎配置中的服务器
+    for engine in engine_servers.values() {
+        for (id, spec) in engine {
+            servers.entry(id.clone()).or_insert_with(|| BundleServer {
+                id: id.clone(),
+                name: id.clone(),
+                server: spec.clone(),
+                disabled_tools: Vec::new(),
+            });
+        }
+    }
+
+    let mut stripped_secrets = Vec::new();
+    if !include_secrets {
+        for server in servers.values_mut() {
+            stripped_secrets.extend(strip_secrets(&server.id, &mut server.server));
+        }
+    }
+
+    let mut profiles: Vec<McpProfile> = registry.profiles.values().cloned().collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    let engines = engine_servers
+        .iter()
+        .map(|(engine, servers)| {
+            let mut ids: Vec<String> = servers.keys().cloned().collect();
+            ids.sort();
+            (engine.clone(), ids)
+        })
+        .collect();
+
+    McpBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        servers: servers.into_values().collect(),
+        profiles,
+        engines,
+        secrets_included: include_secrets,
+        stripped_secrets,
+    }
+}
+
+/// 将配置包合并到注册表；`overwrite` 为 false 时跳过已存在的服务器
+fn merge_bundle(registry: &mut McpRegistry, bundle: &McpBundle, overwrite: bool) -> BundleImport {
+    let mut result = BundleImport::default();
+
+    for server in &bundle.servers {
+        let existing = registry.servers.get(&server.id);
+        if existing.is_some() && !overwrite {
+            result.skipped.push(server.id.clone());
+            continue;
+        }
+
+        // 被清空的密钥沿用注册表中已有的值
+        let mut spec = server.server.clone();
+        for secret in bundle
+            .stripped_secrets
+            .iter()
+            .filter(|s| s.server == server.id)
+        {
+            let current = existing
+                .and_then(|entry| entry.server.get(&secret.block))
+                .and_then(|block| block.get(&secret.key))
+                .and_then(Value::as_str)
+                .filter(|value| !value.is_empty());
+            match current {
+                Some(value) => spec[&secret.block][&secret.key] = Value::from(value),
+                None => result.missing_secrets.push(secret.clone()),
+            }
+        }
+
+        // 已有服务器保持本机的启用状态，新服务器按配置包中各引擎的列表启用
+        let enabled = existing.map_or_else(
+            || {
+                bundle
+                    .engines
+                    .iter()
+                    .map(|(engine, ids)| (engine.clone(), ids.contains(&server.id)))
+                    .collect()
+            },
+            |entry| entry.enabled.clone(),
+        );
+        registry.servers.insert(
+            server.id.clone(),
+            RegistryEntry {
+                id: server.id.clone(),
+                name: server.name.clone(),
+                server: spec,
+                enabled,
+                health: None,
+                introspection: None,
+                disabled_tools: server.disabled_tools.clone(),
+                tool_prefix: existing.and_then(|entry| entry.tool_prefix.clone()),
+                startup: existing.map(|entry| entry.startup).unwrap_or_default(),
+                description: existing.and_then(|entry| entry.description.clone()),
+                tags: existing.map(|entry| entry.tags.clone()).unwrap_or_default(),
+                sandbox: existing
+                    .map(|entry| entry.sandbox.clone())
+                    .unwrap_or_default(),
+            },
+        );
+        result.imported.push(server.id.clone());
+    }
+
+    for profile in &bundle.profiles {
+        if registry.profiles.contains_key(&profile.name) && !overwrite {
+            continue;
+        }
+        registry
+            .profiles
+            .insert(profile.name.clone(), profile.clone());
+        result.profiles.push(profile.name.clone());
+    }
+    result
+}
+
+/// 把导入服务器中的明文密钥移入钥匙串
+///
+/// 包括键名像密钥的值，以及本机原有配置 `previous` 中已标记为密钥的字段
+fn move_secrets_to_keyring(
+    id: &str,
+    previous: Option<&Value>,
+    spec: &mut Value,
+) -> Result<(), String> {
+    secrets::protect(id, previous, spec)?;
+    for block in SECRET_BLOCKS {
+        let keys: Vec<String> = spec
+            .get(*block)
+            .and_then(Value::as_object)
+            .map(|values| {
+                values
+                    .iter()
+                    .filter(|(key, value)| {
+                        is_secret_key(key)
+                            && !secrets::is_marker(value)
+                            && value.as_str().is_some_and(|value| !value.is_empty())
+                    })
+                    .map(|(key, _)| key.clone())
+                    .collect()
+            })
+            .unwrap_or_default();
+        for key in keys {
+            secrets::mark(id, spec, block, &key, None)?;
+        }
+    }
+    Ok(())
+}
+
+/// 导出配置包到 `path`
+pub fn export_bundle(path: &Path, include_secrets: bool) -> Result<McpBundle, String> {
+    let mut registry = registry::read_registry()?;
+    if include_secrets {
+        for (id, entry) in registry.servers.iter_mut() {
+            entry.server = secrets::resolve(id, &entry.server)?;
+        }
+    }
+    let mut engine_servers = HashMap::new();
+    for app in AppType::ALL {
+        match super::import_from_app(&app) {
+            Ok(servers) => {
+                engine_servers.insert(app.as_str().to_string(), servers);
+            }
+            Err(e) => log::warn!("读取 {} 的 MCP 配置失败，导出时跳过: {}", app.as_str(), e),
+        }
+    }
+
+    let bundle = build_bundle(&registry, &engine_servers, include_secrets);
+    let content =
+        serde_json::to_string_pretty(&bundle).map_err(|e| format!("序列化配置包失败: {}", e))?;
+    std::fs::write(path, content).map_err(|e| format!("写入配置包失败: {}", e))?;
+
+    log::info!(
+        "已导出 {} 个 MCP 服务器到 {}（{}）",
+        bundle.servers.len(),
+        path.display(),
+        if include_secrets {
+            "包含密钥"
+        } else {
+            "不含密钥"
+        }
+    );
+    Ok(bundle)
+}
+
+/// 从 `path` 导入配置包
+///
+/// `sync_engines` 为 true 时，把配置包中各引擎启用的服务器也添加到本机的引擎配置
+/// （不会移除本机已有的服务器）；仍缺少密钥的服务器不会同步
+pub fn import_bundle(
+    path: &Path,
+    overwrite: bool,
+    sync_engines: bool,
+) -> Result<BundleImport, String> {
+    let content = std::fs::read_to_string(path).map_err(|e| format!("读取配置包失败: {}", e))?;
+    let bundle: McpBundle =
+        serde_json::from_str(&content).map_err(|e| format!("解析配置包失败: {}", e))?;
+    if bundle.format != BUNDLE_FORMAT {
+        return Err("不是 MCP 配置包".to_string());
+    }
+    if bundle.version > BUNDLE_VERSION {
+        return Err(format!(
+            "配置包版本 {} 高于当前支持的版本 {}，请升级应用",
+            bundle.version, BUNDLE_VERSION
+        ));
+    }
+    for server in &bundle.servers {
+        validate_server_spec(&server.server)
+            .map_err(|e| format!("配置包中的服务器 '{}' 无效: {}", server.id, e))?;
+    }
+
+    let mut registry = registry::read_registry()?;
+    let previous: HashMap<String, Value> = registry
+        .servers
+        .iter()
+        .map(|(id, entry)| (id.clone(), entry.server.clone()))
+        .collect();
+    let result = merge_bundle(&mut registry, &bundle, overwrite);
+    for id in &result.imported {
+        if let Some(entry) = registry.servers.get_mut(id) {
+            move_secrets_to_keyring(id, previous.get(id), &mut entry.server)
+                .map_err(|e| format!("无法把服务器 '{}' 的密钥保存到系统钥匙串: {}", id, e))?;
+        }
+    }
+    registry::write_registry(&registry)?;
+
+    if sync_engines {
+        for (engine, ids) in &bundle.engines {
+            let app = match AppType::from_str(engine) {
+                Ok(app) => app,
+                Err(e) => {
+                    log::warn!("跳过配置包中的引擎 '{}': {}", engine, e);
+                    continue;
+                }
+            };
+            for id in ids {
+                let incomplete = result.missing_secrets.iter().any(|s| &s.server == id);
+                if !result.imported.contains(id) || incomplete {
+                    continue;
+                }
+                if let Some(entry) = registry.servers.get(id) {
+                    super::sync_server_to_app(id, &entry.engine_spec()?, &app)?;
+                }
+            }
+        }
+    }
+
+    log::info!(
+        "已从 {} 导入 {} 个 MCP 服务器（跳过 {} 个，{} 个密钥待填写）",
+        path.display(),
+        result.imported.len(),
+        result.skipped.len(),
+        result.missing_secrets.len()
+    );
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_bundle_round_trip_strips_secrets() {
+        let mut registry = McpRegistry::default();
+        registry.servers.insert(
+            "github".to_string(),
+            RegistryEntry {
+                id: "github".to_string(),
+                name: "GitHub".to_string(),
+                server: json!({
+                    "command": "npx",
+                    "env": { "GITHUB_TOKEN": "ghp_secret", "LOG_LEVEL": "debug" }
+                }),
+                enabled: HashMap::from([("codex".to_string(), true)]),
+                health: None,
+                introspection: None,
+                disabled_tools: vec!["delete_repo".to_string()],
+                tool_prefix: None,
+                startup: Default::default(),
+                description: None,
+                tags: Vec::new(),
+                sandbox: Default::default(),
+            },
+        );
+        let engines = HashMap::from([(
+            "codex".to_string(),
+            HashMap::from([("local".to_string(), json!({ "command": "local-mcp" }))]),
+        )]);
+
+        let bundle = build_bundle(&registry, &engines, false);
+        assert_eq!(bundle.servers.len(), 2);
+        assert_eq!(bundle.servers[0].server["env"]["GITHUB_TOKEN"], json!(""));
+        assert_eq!(bundle.servers[0].server["env"]["LOG_LEVEL"], json!("debug"));
+        assert_eq!(bundle.stripped_secrets.len(), 1);
+        assert_eq!(bundle.engines["codex"], vec!["local".to_string()]);
+
+        // 已有的密钥保留，新机器上的密钥需要填写
+        let mut existing = registry.clone();
+        let result = merge_bundle(&mut existing, &bundle, true);
+        assert!(result.missing_secrets.is_empty());
+        assert_eq!(
+            existing.servers["github"].server["env"]["GITHUB_TOKEN"],
+            json!("ghp_secret")
+        );
+        assert_eq!(
+            existing.servers["github"].disabled_tools,
+            vec!["delete_repo"]
+        );
+
+        let mut fresh = McpRegistry::default();
+        let result = merge_bundle(&mut fresh, &bundle, false);
+        assert_eq!(result.imported, vec!["github", "local"]);
+        assert_eq!(result.missing_secrets, bundle.stripped_secrets);
+
+        let result = merge_bundle(&mut fresh, &bundle, false);
+        assert_eq!(result.skipped, vec!["github", "local"]);
+    }
+}
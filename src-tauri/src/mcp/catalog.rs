@@ -0,0 +1,382 @@
+//! MCP 服务器目录
+//!
+//! 应用内置一份常用 MCP 服务器的目录（`catalog.json`），记录每个服务器的运行方式
+//! （npx、uvx、docker 或远程地址）以及需要用户填写的参数。也可以从远程 URL 获取
+//! 目录，获取成功后缓存到 `~/.anycode/mcp-catalog.json`，同 ID 的条目覆盖内置条目。
+//!
+//! 从目录安装时按条目生成服务器配置并写入注册表，标记为 `secret` 的 env/headers
+//! 参数直接保存到系统钥匙串（见 `secrets` 模块）。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use super::registry::{self, RegistryEntry};
+use super::secrets;
+use super::validation::validate_server_spec;
+
+/// 内置目录
+const BUNDLED_CATALOG: &str = include_str!("catalog.json");
+/// 获取远程目录的超时时间
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 服务器的运行方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatalogRuntime {
+    /// `npx -y <package>`
+    Npx,
+    /// `uvx <package>`
+    Uvx,
+    /// `docker run -i --rm <image>`
+    Docker,
+    /// 远程 streamable HTTP 服务器
+    Http,
+    /// 远程 SSE 服务器
+    Sse,
+}
+
+/// 参数的用途
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputKind {
+    /// 环境变量
+    Env,
+    /// HTTP 头（远程服务器）
+    Header,
+    /// 替换 `args` 中的 `{name}` 占位符
+    Arg,
+}
+
+/// 安装时需要用户填写的参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogInput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub kind: InputKind,
+    #[serde(default)]
+    pub required: bool,
+    /// 保存到系统钥匙串（只对 env 和 header 参数有效）
+    #[serde(default)]
+    pub secret: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// 目录中的一个服务器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogEntry {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub homepage: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub runtime: CatalogRuntime,
+    /// npm/PyPI 包名或 Docker 镜像（本地运行时）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// 附加参数，可以包含 `{name}` 占位符
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// 服务器地址（远程服务器）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<CatalogInput>,
+}
+
+/// 目录文件格式（`{ "version": 1, "servers": [...] }`）
+#[derive(Debug, Clone, Deserialize)]
+struct CatalogFile {
+    servers: Vec<CatalogEntry>,
+}
+
+/// 返回给前端的目录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogListing {
+    pub servers: Vec<CatalogEntry>,
+    /// 获取远程目录失败时的原因（此时返回内置目录和之前的缓存）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_error: Option<String>,
+}
+
+/// 远程目录的缓存文件
+fn cache_path() -> PathBuf {
+    let home_dir = dirs::home_dir().expect("Failed to get home directory");
+    home_dir.join(".anycode").join("mcp-catalog.json")
+}
+
+fn parse_catalog(content: &str) -> Result<Vec<CatalogEntry>, String> {
+    let file: CatalogFile =
+        serde_json::from_str(content).map_err(|e| format!("解析 MCP 服务器目录失败: {}", e))?;
+    Ok(file.servers)
+}
+
+/// 合并内置目录和远程目录（同 ID 以远程为准），按名称排序
+fn merge(bundled: Vec<CatalogEntry>, remote: Vec<CatalogEntry>) -> Vec<CatalogEntry> {
+    let mut servers: HashMap<String, CatalogEntry> = bundled
+        .into_iter()
+        .map(|entry| (entry.id.clone(), entry))
+        .collect();
+    for entry in remote {
+        servers.insert(entry.id.clone(), entry);
+    }
+    let mut servers: Vec<CatalogEntry> = servers.into_values().collect();
+    servers.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+    servers
+}
+
+/// 读取目录：内置目录加上缓存的远程目录
+pub fn load_catalog() -> Result<Vec<CatalogEntry>, String> {
+    let bundled = parse_catalog(BUNDLED_CATALOG)?;
+    let cached = match std::fs::read_to_string(cache_path()) {
+        Ok(content) => parse_catalog(&content).unwrap_or_else(|e| {
+            log::warn!("忽略无效的 MCP 目录缓存: {}", e);
+            Vec::new()
+        }),
+        Err(_) => Vec::new(),
+    };
+    Ok(merge(bundled, cached))
+}
+
+/// 获取远程目录并写入缓存
+async fn refresh_catalog(url: &str) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .user_agent(concat!("any-code/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("获取 MCP 服务器目录失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "获取 MCP 服务器目录失败: HTTP {}",
+            response.status()
+        ));
+    }
+    let content = response
+        .text()
+        .await
+        .map_err(|e| format!("读取 MCP 服务器目录失败: {}", e))?;
+    let servers = parse_catalog(&content)?;
+
+    let path = cache_path();
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+    std::fs::write(&path, content).map_err(|e| format!("写入 MCP 目录缓存失败: {}", e))?;
+    log::info!("已从 {} 获取 {} 个 MCP 服务器目录条目", url, servers.len());
+    Ok(())
+}
+
+/// 列出目录，给出 `remote_url` 时先获取远程目录
+pub async fn list_catalog(remote_url: Option<&str>) -> Result<CatalogListing, String> {
+    let remote_error = match remote_url {
+        Some(url) => refresh_catalog(url).await.err(),
+        None => None,
+    };
+    if let Some(e) = &remote_error {
+        log::warn!("{}，使用内置目录", e);
+    }
+    Ok(CatalogListing {
+        servers: load_catalog()?,
+        remote_error,
+    })
+}
+
+/// 按目录条目和用户填写的参数生成服务器配置
+fn build_spec(entry: &CatalogEntry, inputs: &HashMap<String, String>) -> Result<Value, String> {
+    let mut values: HashMap<&str, String> = HashMap::new();
+    let mut missing = Vec::new();
+    for input in &entry.inputs {
+        let value = inputs
+            .get(&input.name)
+            .map(|value| value.trim())
+            .filter(|value| !value.is_empty())
+            .or(input.default.as_deref());
+        match value {
+            Some(value) => {
+                values.insert(&input.name, value.to_string());
+            }
+            None if input.required => missing.push(input.name.as_str()),
+            None => {}
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("缺少必填参数: {}", missing.join(", ")));
+    }
+
+    // 替换占位符；占位符没有值（可选参数未填写）的参数整个省略
+    let args: Vec<String> = entry
+        .args
+        .iter()
+        .filter_map(|arg| {
+            let mut arg = arg.clone();
+            for input in entry.inputs.iter().filter(|i| i.kind == InputKind::Arg) {
+                let placeholder = format!("{{{}}}", input.name);
+                if arg.contains(&placeholder) {
+                    arg = arg.replace(&placeholder, values.get(input.name.as_str())?);
+                }
+            }
+            Some(arg)
+        })
+        .collect();
+    let block = |kind: InputKind| -> Map<String, Value> {
+        entry
+            .inputs
+            .iter()
+            .filter(|input| input.kind == kind)
+            .filter_map(|input| {
+                let value = values.get(input.name.as_str())?;
+                Some((input.name.clone(), Value::from(value.as_str())))
+            })
+            .collect()
+    };
+    let env = block(InputKind::Env);
+    let headers = block(InputKind::Header);
+
+    let package = || {
+        entry
+            .package
+            .clone()
+            .ok_or_else(|| format!("目录条目 '{}' 缺少 package", entry.id))
+    };
+    let mut spec = match entry.runtime {
+        CatalogRuntime::Npx => {
+            let mut command_args = vec!["-y".to_string(), package()?];
+            command_args.extend(args);
+            json!({ "type": "stdio", "command": "npx", "args": command_args })
+        }
+        CatalogRuntime::Uvx => {
+            let mut command_args = vec![package()?];
+            command_args.extend(args);
+            json!({ "type": "stdio", "command": "uvx", "args": command_args })
+        }
+        CatalogRuntime::Docker => {
+            // 环境变量通过 `-e NAME` 从 docker 进程的环境传入容器
+            let mut command_args: Vec<String> = vec!["run".into(), "-i".into(), "--rm".into()];
+            for name in env.keys() {
+                command_args.push("-e".into());
+                command_args.push(name.clone());
+            }
+            command_args.push(package()?);
+            command_args.extend(args);
+            json!({ "type": "stdio", "command": "docker", "args": command_args })
+        }
+        CatalogRuntime::Http | CatalogRuntime::Sse => {
+            let url = entry
+                .url
+                .as_deref()
+                .ok_or_else(|| format!("目录条目 '{}' 缺少 url", entry.id))?;
+            let transport = if entry.runtime == CatalogRuntime::Sse {
+                "sse"
+            } else {
+                "http"
+            };
+            json!({ "type": transport, "url": url })
+        }
+    };
+    if !env.is_empty() {
+        spec["env"] = Value::Object(env);
+    }
+    if !headers.is_empty() {
+        spec["headers"] = Value::Object(headers);
+    }
+
+    validate_server_spec(&spec)?;
+    Ok(spec)
+}
+
+/// 从目录安装服务器，返回新的注册表条目
+///
+/// 服务器以禁用状态加入注册表，需要再为各引擎单独启用。
+pub fn install(id: &str, inputs: &HashMap<String, String>) -> Result<RegistryEntry, String> {
+    let entry = load_catalog()?
+        .into_iter()
+        .find(|entry| entry.id == id)
+        .ok_or_else(|| format!("目录中没有 MCP 服务器 '{}'", id))?;
+    if registry::get_server(id)?.is_some() {
+        return Err(format!("注册表中已存在服务器 '{}'", id));
+    }
+
+    let mut spec = build_spec(&entry, inputs)?;
+    for input in entry.inputs.iter().filter(|input| input.secret) {
+        let block = match input.kind {
+            InputKind::Env => "env",
+            InputKind::Header => "headers",
+            InputKind::Arg => continue,
+        };
+        if spec[block].get(&input.name).is_some() {
+            secrets::mark(id, &mut spec, block, &input.name, None)?;
+        }
+    }
+
+    registry::upsert_server(id, &entry.name, &spec, None)?;
+    log::info!("已从目录安装 MCP 服务器 '{}'", id);
+    registry::set_server_metadata(id, Some(entry.description), &entry.tags)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_catalog_specs() {
+        let catalog = parse_catalog(BUNDLED_CATALOG).unwrap();
+        let ids: std::collections::HashSet<&str> = catalog.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids.len(), catalog.len());
+
+        // 每个条目填写必填参数后都能生成有效的配置
+        for entry in &catalog {
+            let inputs: HashMap<String, String> = entry
+                .inputs
+                .iter()
+                .map(|input| (input.name.clone(), "value".to_string()))
+                .collect();
+            build_spec(entry, &inputs).unwrap();
+        }
+
+        let find = |id: &str| catalog.iter().find(|e| e.id == id).unwrap();
+        let filesystem = find("filesystem");
+        assert!(build_spec(filesystem, &HashMap::new()).is_err());
+        let inputs = HashMap::from([("path".to_string(), "/tmp/work".to_string())]);
+        assert_eq!(
+            build_spec(filesystem, &inputs).unwrap()["args"],
+            json!(["-y", "@modelcontextprotocol/server-filesystem", "/tmp/work"])
+        );
+
+        let inputs = HashMap::from([(
+            "GITHUB_PERSONAL_ACCESS_TOKEN".to_string(),
+            "ghp_x".to_string(),
+        )]);
+        let github = build_spec(find("github"), &inputs).unwrap();
+        assert_eq!(github["command"], json!("docker"));
+        assert_eq!(
+            github["args"],
+            json!([
+                "run",
+                "-i",
+                "--rm",
+                "-e",
+                "GITHUB_PERSONAL_ACCESS_TOKEN",
+                "ghcr.io/github/github-mcp-server"
+            ])
+        );
+        assert_eq!(
+            github["env"]["GITHUB_PERSONAL_ACCESS_TOKEN"],
+            json!("ghp_x")
+        );
+    }
+}
@@ -2,6 +2,8 @@
 
 use serde_json::Value;
 use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
 
 use super::validation::validate_server_spec;
 
@@ -46,3 +48,118 @@ pub fn import_from_claude() -> Result<HashMap<String, Value>, String> {
 pub fn sync_servers_to_claude(servers: &HashMap<String, Value>) -> Result<(), String> {
     crate::claude_mcp::set_mcp_servers_map(servers)
 }
+
+/// 将服务器同步到项目的 .mcp.json（Claude 项目级配置）
+///
+/// `servers` 中值为 None 的服务器会被移除，.mcp.json 中的其它服务器保持不变
+pub fn sync_project_servers_to_claude(
+    project_path: &str,
+    servers: &HashMap<String, Option<Value>>,
+) -> Result<(), String> {
+    let path = Path::new(project_path).join(".mcp.json");
+
+    let mut config = if path.exists() {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("读取 .mcp.json 失败: {}", e))?;
+        serde_json::from_str(&content)
+            .map_err(|e| format!("解析 .mcp.json 失败: {}", e))?
+    } else if servers.values().all(Option::is_none) {
+        // 没有需要写入的服务器，不创建文件
+        return Ok(());
+    } else {
+        Value::Object(serde_json::Map::new())
+    };
+
+    let root = config
+        .as_object_mut()
+        .ok_or(".mcp.json 不是 JSON 对象")?;
+    let mcp_servers = root
+        .entry("mcpServers")
+        .or_insert_with(|| Value::Object(serde_json::Map::new()))
+        .as_object_mut()
+        .ok_or(".mcp.json 的 mcpServers 字段不是对象")?;
+
+    // 单独禁用的工具写入项目的 .claude/settings.json
+    let mut disabled_tools: HashMap<String, Vec<String>> = HashMap::new();
+    for (id, spec) in servers {
+        match spec {
+            Some(spec) => {
+                validate_server_spec(spec)?;
+                let mut spec = spec.clone();
+                let tools = spec
+                    .as_object_mut()
+                    .map(crate::claude_mcp::take_disabled_tools)
+                    .unwrap_or_default();
+                disabled_tools.insert(id.clone(), tools);
+                mcp_servers.insert(id.clone(), spec);
+            }
+            None => {
+                disabled_tools.insert(id.clone(), Vec::new());
+                mcp_servers.remove(id);
+            }
+        }
+    }
+
+    let content = serde_json::to_string_pretty(&config)
+        .map_err(|e| format!("序列化 .mcp.json 失败: {}", e))?;
+    fs::write(&path, content)
+        .map_err(|e| format!("写入 .mcp.json 失败: {}", e))?;
+    crate::claude_mcp::write_disabled_tools(
+        &Path::new(project_path).join(".claude").join("settings.json"),
+        &disabled_tools,
+    )?;
+
+    log::info!("已将项目 MCP 服务器同步到 {}", path.display());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_project_disabled_tools_become_deny_rules() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().to_string_lossy().to_string();
+        let settings_path = dir.path().join(".claude").join("settings.json");
+        fs::create_dir_all(settings_path.parent().unwrap()).unwrap();
+        fs::write(
+            &settings_path,
+            json!({ "permissions": { "deny": ["Bash(rm:*)", "mcp__db__old_tool"] } }).to_string(),
+        )
+        .unwrap();
+        let read = |path: &Path| -> Value {
+            serde_json::from_str(&fs::read_to_string(path).unwrap()).unwrap()
+        };
+
+        let spec = json!({
+            "type": "stdio",
+            "command": "npx",
+            "args": ["-y", "db-mcp"],
+            "disabledTools": ["drop_table"]
+        });
+        sync_project_servers_to_claude(
+            &project_path,
+            &HashMap::from([("db".to_string(), Some(spec))]),
+        )
+        .unwrap();
+        let mcp_json = read(&dir.path().join(".mcp.json"));
+        assert!(mcp_json["mcpServers"]["db"].get("disabledTools").is_none());
+        assert_eq!(
+            read(&settings_path)["permissions"]["deny"],
+            json!(["Bash(rm:*)", "mcp__db__drop_table"])
+        );
+
+        // 移除服务器时一并清除其规则，其它规则保持不变
+        sync_project_servers_to_claude(&project_path, &HashMap::from([("db".to_string(), None)]))
+            .unwrap();
+        assert!(read(&dir.path().join(".mcp.json"))["mcpServers"]
+            .get("db")
+            .is_none());
+        assert_eq!(
+            read(&settings_path)["permissions"]["deny"],
+            json!(["Bash(rm:*)"])
+        );
+    }
+}
@@ -0,0 +1,244 @@
+//! 工具名冲突检测
+//!
+//! 同一引擎中启用的两个服务器提供同名工具时，引擎最终调用哪一个不可预测。
+//! 同步注册表到引擎时先按工具列表找出冲突（使用注册表中缓存的内省结果，没有缓存的
+//! 服务器当场内省），并写入同步结果；选择应用前缀时，冲突中尚未设置前缀的 stdio
+//! 服务器自动加上以服务器 ID 命名的前缀（见 [`super::prefix`]），远程服务器只报告。
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::health;
+use super::prefix;
+use super::registry::{self, McpRegistry};
+use super::{AppType, McpTransport};
+
+/// 多个服务器提供的同名工具
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConflict {
+    /// 引擎中看到的工具名（含前缀）
+    pub tool: String,
+    /// 提供该工具的服务器，按 ID 排序
+    pub servers: Vec<String>,
+}
+
+/// 同步结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncReport {
+    pub engine: String,
+    /// 写入引擎配置的服务器数量
+    pub synced: usize,
+    /// 同步后仍存在的冲突
+    pub conflicts: Vec<ToolConflict>,
+    /// 本次自动设置了前缀的服务器
+    pub prefixed: Vec<String>,
+    /// 无法获取工具列表、未参与检测的服务器：ID -> 错误信息
+    pub unchecked: BTreeMap<String, String>,
+}
+
+/// 收集引擎中启用的服务器提供的工具（不含单独禁用的，未加前缀）
+///
+/// 返回工具列表以及无法内省的服务器；新内省的结果缓存到注册表。
+async fn collect_tools(
+    registry: &McpRegistry,
+    app: &AppType,
+) -> (BTreeMap<String, Vec<String>>, BTreeMap<String, String>) {
+    let enabled: Vec<_> = registry
+        .servers
+        .values()
+        .filter(|entry| entry.is_enabled_for(app))
+        .collect();
+
+    let uncached: Vec<_> = enabled
+        .iter()
+        .filter(|entry| entry.introspection.is_none())
+        .collect();
+    let fetched = futures::future::join_all(uncached.iter().map(|entry| async move {
+        let spec = entry.launch_spec()?;
+        health::introspect(&spec).await
+    }))
+    .await;
+
+    let mut fresh = BTreeMap::new();
+    let mut unchecked = BTreeMap::new();
+    for (entry, result) in uncached.iter().zip(fetched) {
+        match result {
+            Ok(introspection) => {
+                if let Err(e) = registry::set_server_introspection(None, &entry.id, &introspection)
+                {
+                    log::warn!("缓存 MCP 服务器 '{}' 的工具列表失败: {}", entry.id, e);
+                }
+                fresh.insert(entry.id.clone(), introspection);
+            }
+            Err(e) => {
+                log::warn!("无法获取 MCP 服务器 '{}' 的工具列表: {}", entry.id, e);
+                unchecked.insert(entry.id.clone(), e);
+            }
+        }
+    }
+
+    let mut tools = BTreeMap::new();
+    for entry in enabled {
+        let Some(introspection) = entry
+            .introspection
+            .as_ref()
+            .or_else(|| fresh.get(&entry.id))
+        else {
+            continue;
+        };
+        let names = introspection
+            .tools
+            .iter()
+            .map(|tool| tool.name.clone())
+            .filter(|name| !entry.disabled_tools.contains(name))
+            .collect();
+        tools.insert(entry.id.clone(), names);
+    }
+    (tools, unchecked)
+}
+
+/// 按各服务器当前的前缀找出同名工具
+fn find_conflicts(
+    registry: &McpRegistry,
+    tools: &BTreeMap<String, Vec<String>>,
+) -> Vec<ToolConflict> {
+    let mut owners: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    for (id, names) in tools {
+        let prefix = registry
+            .servers
+            .get(id)
+            .and_then(|entry| entry.tool_prefix.as_deref())
+            .unwrap_or_default();
+        for name in names {
+            owners
+                .entry(format!("{}{}", prefix, name))
+                .or_default()
+                .insert(id.clone());
+        }
+    }
+    owners
+        .into_iter()
+        .filter(|(_, servers)| servers.len() > 1)
+        .map(|(tool, servers)| ToolConflict {
+            tool,
+            servers: servers.into_iter().collect(),
+        })
+        .collect()
+}
+
+/// 将注册表同步到引擎，同时检测工具名冲突
+///
+/// `apply_prefixes` 为 true 时先给冲突中的 stdio 服务器设置前缀再同步
+pub async fn sync_engine(engine: &str, apply_prefixes: bool) -> Result<SyncReport, String> {
+    let app_type = AppType::from_str(engine)?;
+    let (tools, unchecked) = collect_tools(&registry::read_registry()?, &app_type).await;
+
+    // 重新读取，包含刚缓存的工具列表
+    let mut registry = registry::read_registry()?;
+    let mut conflicts = find_conflicts(&registry, &tools);
+    let mut prefixed = Vec::new();
+
+    if apply_prefixes && !conflicts.is_empty() {
+        let involved: BTreeSet<String> = conflicts
+            .iter()
+            .flat_map(|conflict| conflict.servers.iter().cloned())
+            .collect();
+        for id in involved {
+            let Some(entry) = registry.servers.get_mut(&id) else {
+                continue;
+            };
+            let stdio = McpTransport::from_spec(&super::normalize_transport(&entry.server))
+                .is_ok_and(|transport| transport == McpTransport::Stdio);
+            if entry.tool_prefix.is_some() || !stdio {
+                continue;
+            }
+            entry.tool_prefix = Some(prefix::default_prefix(&id));
+            prefixed.push(id);
+        }
+        if !prefixed.is_empty() {
+            registry::write_registry(&registry)?;
+            conflicts = find_conflicts(&registry, &tools);
+            log::info!(
+                "已为 {} 个 MCP 服务器设置工具前缀: {}",
+                prefixed.len(),
+                prefixed.join(", ")
+            );
+        }
+    }
+
+    let synced = registry::sync_registry_to_engine(engine)?;
+    for conflict in &conflicts {
+        log::warn!(
+            "{} 引擎中的工具 '{}' 由多个服务器提供: {}",
+            engine,
+            conflict.tool,
+            conflict.servers.join(", ")
+        );
+    }
+
+    Ok(SyncReport {
+        engine: app_type.as_str().to_string(),
+        synced,
+        conflicts,
+        prefixed,
+        unchecked,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mcp::registry::RegistryEntry;
+    use serde_json::json;
+    use std::collections::HashMap;
+
+    #[test]
+    fn test_find_conflicts_respects_prefixes() {
+        let mut registry = McpRegistry::default();
+        for (id, tool_prefix) in [("git", None), ("github", None), ("gitlab", Some("gl_"))] {
+            registry.servers.insert(
+                id.to_string(),
+                RegistryEntry {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    server: json!({ "command": "npx" }),
+                    enabled: HashMap::from([("claude".to_string(), true)]),
+                    health: None,
+                    introspection: None,
+                    disabled_tools: Vec::new(),
+                    tool_prefix: tool_prefix.map(str::to_string),
+                    startup: Default::default(),
+                    description: None,
+                    tags: Vec::new(),
+                    sandbox: Default::default(),
+                },
+            );
+        }
+        let tools: BTreeMap<String, Vec<String>> = [
+            ("git", vec!["status", "log"]),
+            ("github", vec!["status", "create_issue"]),
+            ("gitlab", vec!["status", "log"]),
+        ]
+        .into_iter()
+        .map(|(id, names)| {
+            (
+                id.to_string(),
+                names.into_iter().map(String::from).collect(),
+            )
+        })
+        .collect();
+
+        assert_eq!(
+            find_conflicts(&registry, &tools),
+            vec![ToolConflict {
+                tool: "status".to_string(),
+                servers: vec!["git".to_string(), "github".to_string()],
+            }]
+        );
+
+        registry.servers.get_mut("github").unwrap().tool_prefix = Some("gh_".to_string());
+        assert!(find_conflicts(&registry, &tools).is_empty());
+    }
+}
@@ -0,0 +1,368 @@
+//! 注册表与引擎配置的差异检测和协调
+//!
+//! `sync_registry_to_engine` 只会把注册表写入引擎配置，用户直接修改
+//! `~/.claude.json` 或 Codex 配置后两者就不再一致。这里比较注册表和引擎配置，
+//! 列出差异，并按选择的方式协调：
+//! - 以注册表为准：引擎配置被替换为注册表中启用的服务器
+//! - 以引擎为准：注册表按引擎配置更新（启用状态和配置）
+//! - 合并：两边的服务器都保留，配置不同时以注册表为准，再同步到引擎
+//!
+//! 无论哪种方式，只在引擎中存在的服务器都会先保存到注册表，不会丢失。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+
+use super::registry::{self, McpRegistry, RegistryEntry, DISABLED_TOOLS_KEY};
+use super::{bridge, normalize_transport, prefix, AppType};
+
+/// 差异类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DriftKind {
+    /// 注册表中启用，但引擎配置中没有
+    MissingInEngine,
+    /// 引擎配置中有，但注册表中没有
+    EngineOnly,
+    /// 引擎配置中有，但注册表中为禁用状态
+    DisabledInRegistry,
+    /// 两边都有，但配置不同
+    Modified,
+}
+
+/// 一个服务器的差异
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftEntry {
+    pub id: String,
+    pub kind: DriftKind,
+    /// 注册表中的配置（密钥为占位符）
+    pub registry: Option<Value>,
+    /// 引擎配置中的配置
+    pub engine: Option<Value>,
+}
+
+/// 差异检测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriftReport {
+    pub engine: String,
+    pub in_sync: bool,
+    /// 按服务器 ID 排序
+    pub drift: Vec<DriftEntry>,
+}
+
+/// 协调方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ReconcileStrategy {
+    KeepRegistry,
+    KeepEngine,
+    Merge,
+}
+
+impl ReconcileStrategy {
+    pub fn from_str(strategy: &str) -> Result<Self, String> {
+        match strategy.to_lowercase().as_str() {
+            "keep-registry" | "registry" => Ok(Self::KeepRegistry),
+            "keep-engine" | "engine" => Ok(Self::KeepEngine),
+            "merge" => Ok(Self::Merge),
+            _ => Err(format!("不支持的协调方式: {}", strategy)),
+        }
+    }
+}
+
+/// 去掉不影响服务器行为的差别，便于比较
+///
+/// 禁用工具不参与比较（Claude 的禁用工具不在服务器配置中），空字段视为不存在，
+/// 传输类型按 [`normalize_transport`] 转换为统一格式。
+fn normalize(spec: &Value) -> Value {
+    let spec = normalize_transport(spec);
+    let Some(obj) = spec.as_object() else {
+        return spec;
+    };
+    let obj: serde_json::Map<String, Value> = obj
+        .iter()
+        .filter(|(key, value)| {
+            key.as_str() != DISABLED_TOOLS_KEY
+                && !value.is_null()
+                && value.as_array().map_or(true, |a| !a.is_empty())
+                && value.as_object().map_or(true, |o| !o.is_empty())
+        })
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    Value::Object(obj)
+}
+
+/// 比较注册表（该引擎的启用状态）与引擎配置
+fn diff(
+    registry: &McpRegistry,
+    app: &AppType,
+    engine_servers: &HashMap<String, Value>,
+) -> Result<Vec<DriftEntry>, String> {
+    let mut drift = Vec::new();
+
+    for (id, entry) in &registry.servers {
+        let engine = engine_servers.get(id);
+        let enabled = entry.is_enabled_for(app);
+        let kind = match engine {
+            None if enabled => DriftKind::MissingInEngine,
+            None => continue,
+            Some(_) if !enabled => DriftKind::DisabledInRegistry,
+            Some(spec) if normalize(spec) != normalize(&entry.engine_spec()?) => {
+                DriftKind::Modified
+            }
+            Some(_) => continue,
+        };
+        drift.push(DriftEntry {
+            id: id.clone(),
+            kind,
+            registry: Some(entry.server.clone()),
+            engine: engine.cloned(),
+        });
+    }
+
+    for (id, spec) in engine_servers {
+        if !registry.servers.contains_key(id) {
+            drift.push(DriftEntry {
+                id: id.clone(),
+                kind: DriftKind::EngineOnly,
+                registry: None,
+                engine: Some(spec.clone()),
+            });
+        }
+    }
+
+    drift.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(drift)
+}
+
+/// 检测注册表与引擎配置的差异
+pub fn detect_drift(engine: &str) -> Result<DriftReport, String> {
+    let app_type = AppType::from_str(engine)?;
+    let registry = registry::read_registry()?;
+    let engine_servers = super::import_from_app(&app_type)?;
+
+    let drift = diff(&registry, &app_type, &engine_servers)?;
+    Ok(DriftReport {
+        engine: app_type.as_str().to_string(),
+        in_sync: drift.is_empty(),
+        drift,
+    })
+}
+
+/// 引擎配置中的规范转为注册表中保存的形式：禁用工具和工具前缀单独记录，已标记的密钥存回钥匙串
+///
+/// 通过桥接启动的规范只有服务器 ID，沿用注册表中的配置和工具前缀，只采用其中的禁用工具。
+/// `enabled` 只更新 `app` 中的启用状态，其它引擎保持不变
+fn adopt_engine_spec(
+    id: &str,
+    previous: Option<&RegistryEntry>,
+    spec: &Value,
+    app: &AppType,
+    enabled: bool,
+) -> Result<RegistryEntry, String> {
+    let (tool_prefix, mut server) = if bridge::unwrap(spec).is_some() {
+        let previous = previous.ok_or_else(|| {
+            format!(
+                "引擎配置中的 MCP 服务器 '{}' 通过桥接启动，但注册表中没有它的配置",
+                id
+            )
+        })?;
+        let mut server = previous.server.clone();
+        if let (Some(obj), Some(tools)) = (server.as_object_mut(), spec.get(DISABLED_TOOLS_KEY)) {
+            obj.insert(DISABLED_TOOLS_KEY.to_string(), tools.clone());
+        }
+        (previous.tool_prefix.clone(), server)
+    } else {
+        match prefix::unwrap(spec) {
+            Some((prefix, inner)) => (Some(prefix), inner),
+            None => (None, spec.clone()),
+        }
+    };
+    let disabled_tools = match server
+        .as_object_mut()
+        .and_then(|obj| obj.remove(DISABLED_TOOLS_KEY))
+    {
+        Some(Value::Array(tools)) => tools
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|tool| {
+                let prefix = tool_prefix.as_deref().unwrap_or_default();
+                tool.strip_prefix(prefix).unwrap_or(tool).to_string()
+            })
+            .collect(),
+        _ => previous
+            .map(|entry| entry.disabled_tools.clone())
+            .unwrap_or_default(),
+    };
+    super::secrets::protect(id, previous.map(|entry| &entry.server), &mut server)?;
+
+    let mut entry = RegistryEntry {
+        id: id.to_string(),
+        name: previous.map_or_else(|| id.to_string(), |entry| entry.name.clone()),
+        server,
+        enabled: previous
+            .map(|entry| entry.enabled.clone())
+            .unwrap_or_default(),
+        health: previous.and_then(|entry| entry.health.clone()),
+        introspection: previous.and_then(|entry| entry.introspection.clone()),
+        disabled_tools,
+        tool_prefix,
+        startup: previous.map(|entry| entry.startup).unwrap_or_default(),
+        description: previous.and_then(|entry| entry.description.clone()),
+        tags: previous.map(|entry| entry.tags.clone()).unwrap_or_default(),
+        sandbox: previous
+            .map(|entry| entry.sandbox.clone())
+            .unwrap_or_default(),
+    };
+    entry.set_enabled_for(app, enabled);
+    Ok(entry)
+}
+
+/// 按选择的方式协调注册表与引擎配置，返回协调后的差异（正常情况下为空）
+pub fn reconcile(engine: &str, strategy: ReconcileStrategy) -> Result<DriftReport, String> {
+    let app_type = AppType::from_str(engine)?;
+    let mut registry = registry::read_registry()?;
+    let engine_servers = super::import_from_app(&app_type)?;
+    let drift = diff(&registry, &app_type, &engine_servers)?;
+
+    if drift.is_empty() {
+        log::info!("{} 引擎与注册表一致，无需协调", engine);
+        return detect_drift(engine);
+    }
+
+    for item in &drift {
+        let previous = registry.servers.get(&item.id);
+        let engine_spec = item.engine.as_ref();
+        let updated = match (strategy, item.kind, engine_spec) {
+            // 只在引擎中存在的服务器：以注册表为准时保存为禁用，否则保存为启用
+            (_, DriftKind::EngineOnly, Some(spec)) => Some(adopt_engine_spec(
+                &item.id,
+                None,
+                spec,
+                &app_type,
+                strategy != ReconcileStrategy::KeepRegistry,
+            )?),
+            (ReconcileStrategy::KeepEngine, DriftKind::MissingInEngine, _) => {
+                previous.cloned().map(|mut entry| {
+                    entry.set_enabled_for(&app_type, false);
+                    entry
+                })
+            }
+            (ReconcileStrategy::KeepEngine, _, Some(spec)) => Some(adopt_engine_spec(
+                &item.id, previous, spec, &app_type, true,
+            )?),
+            (ReconcileStrategy::Merge, DriftKind::DisabledInRegistry, _) => {
+                previous.cloned().map(|mut entry| {
+                    entry.set_enabled_for(&app_type, true);
+                    entry
+                })
+            }
+            _ => None,
+        };
+        if let Some(entry) = updated {
+            registry.servers.insert(item.id.clone(), entry);
+        }
+    }
+
+    registry::write_registry(&registry)?;
+    if strategy != ReconcileStrategy::KeepEngine {
+        registry::sync_registry_to_engine(engine)?;
+    }
+
+    log::info!(
+        "已协调 {} 引擎与注册表的 {} 处差异（{:?}）",
+        engine,
+        drift.len(),
+        strategy
+    );
+    detect_drift(engine)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: &str, server: Value, enabled: bool) -> RegistryEntry {
+        RegistryEntry {
+            id: id.to_string(),
+            name: id.to_string(),
+            server,
+            // 其它引擎的启用状态不影响 Codex 的比较
+            enabled: HashMap::from([
+                ("codex".to_string(), enabled),
+                ("gemini".to_string(), !enabled),
+            ]),
+            health: None,
+            introspection: None,
+            disabled_tools: Vec::new(),
+            tool_prefix: None,
+            startup: Default::default(),
+            description: None,
+            tags: Vec::new(),
+            sandbox: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_registry_and_engine() {
+        let mut registry = McpRegistry::default();
+        for entry in [
+            entry(
+                "same",
+                json!({ "type": "stdio", "command": "npx", "args": ["a"], "env": {} }),
+                true,
+            ),
+            entry("changed", json!({ "command": "npx", "args": ["b"] }), true),
+            entry("missing", json!({ "command": "uvx" }), true),
+            entry("disabled", json!({ "command": "node" }), false),
+            entry("off", json!({ "command": "deno" }), false),
+        ] {
+            registry.servers.insert(entry.id.clone(), entry);
+        }
+
+        // stdio 服务器在引擎配置中是桥接命令
+        let same = registry.servers["same"].engine_spec().unwrap();
+        let engine: HashMap<String, Value> = [
+            ("same", same),
+            ("changed", json!({ "command": "npx", "args": ["c"] })),
+            ("disabled", json!({ "command": "node" })),
+            (
+                "extra",
+                json!({ "type": "http", "url": "https://x.example/mcp" }),
+            ),
+        ]
+        .into_iter()
+        .map(|(id, spec)| (id.to_string(), spec))
+        .collect();
+
+        let drift = diff(&registry, &AppType::Codex, &engine).unwrap();
+        let summary: Vec<(&str, DriftKind)> = drift
+            .iter()
+            .map(|item| (item.id.as_str(), item.kind))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("changed", DriftKind::Modified),
+                ("disabled", DriftKind::DisabledInRegistry),
+                ("extra", DriftKind::EngineOnly),
+                ("missing", DriftKind::MissingInEngine),
+            ]
+        );
+        assert!(drift[2].registry.is_none());
+        assert!(drift[3].engine.is_none());
+
+        // 采用引擎中的桥接规范时保留注册表中的配置
+        let mut bridged = registry.servers["changed"].engine_spec().unwrap();
+        bridged[DISABLED_TOOLS_KEY] = json!(["push"]);
+        let previous = registry.servers.get("changed");
+        let adopted =
+            adopt_engine_spec("changed", previous, &bridged, &AppType::Codex, true).unwrap();
+        assert_eq!(adopted.server, json!({ "command": "npx", "args": ["b"] }));
+        assert_eq!(adopted.disabled_tools, vec!["push".to_string()]);
+        assert!(adopt_engine_spec("changed", None, &bridged, &AppType::Codex, true).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+//! 从其他工具的配置导入 MCP 服务器
+//!
+//! 支持的配置文件：
+//! - Claude Desktop: `<配置目录>/Claude/claude_desktop_config.json`（`mcpServers`）
+//! - Cursor: `~/.cursor/mcp.json`（`mcpServers`）
+//! - Windsurf: `~/.codeium/windsurf/mcp_config.json`（`mcpServers`，远程服务器使用 `serverUrl`）
+//! - VS Code: `<配置目录>/Code/User/mcp.json`（`servers`）
+//!
+//! 服务器先转换为统一格式，再与注册表比较：同一个命令（或 URL）已经以其它 ID
+//! 注册的视为重复，同 ID 但配置不同的视为冲突，两者都不会导入。预览模式只返回比较结果。
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::PathBuf;
+
+use super::registry::{self, McpRegistry, RegistryEntry};
+use super::validation::validate_server_spec;
+
+/// 导入来源
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExternalSource {
+    ClaudeDesktop,
+    Cursor,
+    Windsurf,
+    Vscode,
+}
+
+impl ExternalSource {
+    pub fn from_str(source: &str) -> Result<Self, String> {
+        match source.to_lowercase().as_str() {
+            "claude-desktop" | "claude_desktop" => Ok(Self::ClaudeDesktop),
+            "cursor" => Ok(Self::Cursor),
+            "windsurf" => Ok(Self::Windsurf),
+            "vscode" | "vs-code" => Ok(Self::Vscode),
+            _ => Err(format!("不支持的导入来源: {}", source)),
+        }
+    }
+
+    /// 该工具默认的配置文件位置
+    pub fn default_config_path(self) -> Result<PathBuf, String> {
+        let home_dir = dirs::home_dir().ok_or("无法获取用户主目录")?;
+        let config_dir = dirs::config_dir().ok_or("无法获取系统配置目录")?;
+        Ok(match self {
+            Self::ClaudeDesktop => config_dir.join("Claude").join("claude_desktop_config.json"),
+            Self::Cursor => home_dir.join(".cursor").join("mcp.json"),
+            Self::Windsurf => home_dir
+                .join(".codeium")
+                .join("windsurf")
+                .join("mcp_config.json"),
+            Self::Vscode => config_dir.join("Code").join("User").join("mcp.json"),
+        })
+    }
+
+    /// 配置中保存服务器映射的字段
+    fn servers_key(self) -> &'static str {
+        match self {
+            Self::Vscode => "servers",
+            _ => "mcpServers",
+        }
+    }
+}
+
+/// 与注册表比较的结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportStatus {
+    /// 注册表中没有，会被导入
+    New,
+    /// 注册表中已有相同 ID、相同配置的服务器
+    Identical,
+    /// 注册表中已有相同 ID 但配置不同的服务器
+    Conflict,
+    /// 相同的命令或 URL 已以其它 ID 注册
+    Duplicate,
+    /// 无法转换为有效的服务器配置
+    Invalid,
+}
+
+/// 外部配置中的一个服务器
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalServer {
+    pub id: String,
+    /// 转换后的统一格式配置
+    pub spec: Value,
+    pub status: ImportStatus,
+    /// 重复时为已注册的服务器 ID
+    pub existing_id: Option<String>,
+    /// 无效时的原因
+    pub error: Option<String>,
+}
+
+/// 导入（或预览）结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalImport {
+    pub source: ExternalSource,
+    pub config_path: String,
+    pub dry_run: bool,
+    pub servers: Vec<ExternalServer>,
+    /// 实际导入的服务器数量（预览时为 0）
+    pub imported: usize,
+}
+
+fn string_map(value: Option<&Value>) -> Option<Value> {
+    let map: Map<String, Value> = value?
+        .as_object()?
+        .iter()
+        .filter_map(|(k, v)| Some((k.clone(), Value::String(v.as_str()?.to_string()))))
+        .collect();
+    (!map.is_empty()).then_some(Value::Object(map))
+}
+
+/// 将外部工具的服务器条目转换为统一格式
+fn to_unified_spec(entry: &Value) -> Result<Value, String> {
+    let obj = entry.as_object().ok_or("服务器条目不是对象")?;
+    let str_field = |key: &str| obj.get(key).and_then(Value::as_str).map(str::trim);
+
+    let mut spec = Map::new();
+    // Windsurf 的远程服务器使用 serverUrl
+    let url = str_field("url").or_else(|| str_field("serverUrl"));
+    match (str_field("command"), url) {
+        (Some(command), _) if !command.is_empty() => {
+            spec.insert("type".into(), Value::from("stdio"));
+            spec.insert("command".into(), Value::from(command));
+            let args: Vec<Value> = obj
+                .get("args")
+                .and_then(Value::as_array)
+                .map(|args| args.iter().filter(|a| a.is_string()).cloned().collect())
+                .unwrap_or_default();
+            if !args.is_empty() {
+                spec.insert("args".into(), Value::Array(args));
+            }
+            if let Some(env) = string_map(obj.get("env")) {
+                spec.insert("env".into(), env);
+            }
+            if let Some(cwd) = str_field("cwd").filter(|cwd| !cwd.is_empty()) {
+                spec.insert("cwd".into(), Value::from(cwd));
+            }
+        }
+        (_, Some(url)) if !url.is_empty() => {
+            let transport = match str_field("type") {
+                Some("sse") => "sse",
+                Some(_) => "http",
+                None if url.trim_end_matches('/').ends_with("/sse") => "sse",
+                None => "http",
+            };
+            spec.insert("type".into(), Value::from(transport));
+            spec.insert("url".into(), Value::from(url));
+            if let Some(headers) = string_map(obj.get("headers")) {
+                spec.insert("headers".into(), headers);
+            }
+        }
+        _ => return Err("缺少 command 或 url 字段".to_string()),
+    }
+
+    let spec = Value::Object(spec);
+    validate_server_spec(&spec)?;
+    Ok(spec)
+}
+
+/// 用于判断重复的标识：stdio 为命令和参数，远程服务器为 URL
+fn identity(spec: &Value) -> Value {
+    match spec.get("url") {
+        Some(url) => url.clone(),
+        None => serde_json::json!([spec.get("command"), spec.get("args")]),
+    }
+}
+
+/// 解析外部配置并与注册表比较
+fn classify(source: ExternalSource, config: &Value, registry: &McpRegistry) -> Vec<ExternalServer> {
+    let Some(servers) = config.get(source.servers_key()).and_then(Value::as_object) else {
+        return Vec::new();
+    };
+
+    let mut result: Vec<ExternalServer> = servers
+        .iter()
+        .map(|(id, entry)| {
+            let spec = match to_unified_spec(entry) {
+                Ok(spec) => spec,
+                Err(error) => {
+                    return ExternalServer {
+                        id: id.clone(),
+                        spec: entry.clone(),
+                        status: ImportStatus::Invalid,
+                        existing_id: None,
+                        error: Some(error),
+                    }
+                }
+            };
+
+            let key = identity(&spec);
+            let (status, existing_id) = match registry.servers.get(id) {
+                Some(existing) if key == identity(&existing.server) => {
+                    (ImportStatus::Identical, None)
+                }
+                Some(_) => (ImportStatus::Conflict, None),
+                None => match registry
+                    .servers
+                    .values()
+                    .find(|existing| identity(&existing.server) == key)
+                {
+                    Some(existing) => (ImportStatus::Duplicate, Some(existing.id.clone())),
+                    None => (ImportStatus::New, None),
+                },
+            };
+            ExternalServer {
+                id: id.clone(),
+                spec,
+                status,
+                existing_id,
+                error: None,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| a.id.cmp(&b.id));
+    result
+}
+
+/// 从外部工具导入服务器
+///
+/// `config_path` 为空时使用该工具的默认配置位置。导入的服务器在注册表中为禁用状态，
+/// 不会自动同步到任何引擎。
+pub fn import_external(
+    source: ExternalSource,
+    config_path: Option<PathBuf>,
+    dry_run: bool,
+) -> Result<ExternalImport, String> {
+    let path = match config_path {
+        Some(path) => path,
+        None => source.default_config_path()?,
+    };
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取配置文件 {} 失败: {}", path.display(), e))?;
+    // VS Code 等工具的配置允许注释和尾随逗号
+    let config = jsonc_parser::parse_to_serde_value(&content, &Default::default())
+        .map_err(|e| format!("解析配置文件 {} 失败: {}", path.display(), e))?
+        .unwrap_or(Value::Null);
+
+    let mut registry = registry::read_registry()?;
+    let servers = classify(source, &config, &registry);
+
+    let mut imported = 0;
+    if !dry_run {
+        for server in servers.iter().filter(|s| s.status == ImportStatus::New) {
+            registry.servers.insert(
+                server.id.clone(),
+                RegistryEntry {
+                    id: server.id.clone(),
+                    name: server.id.clone(),
+                    server: server.spec.clone(),
+                    enabled: HashMap::new(),
+                    health: None,
+                    introspection: None,
+                    disabled_tools: Vec::new(),
+                    tool_prefix: None,
+                    startup: Default::default(),
+                    description: None,
+                    tags: Vec::new(),
+                    sandbox: Default::default(),
+                },
+            );
+            imported += 1;
+        }
+        if imported > 0 {
+            registry::write_registry(&registry)?;
+        }
+        log::info!(
+            "从 {:?} 导入了 {} 个 MCP 服务器（{}）",
+            source,
+            imported,
+            path.display()
+        );
+    }
+
+    Ok(ExternalImport {
+        source,
+        config_path: path.to_string_lossy().to_string(),
+        dry_run,
+        servers,
+        imported,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_classify_external_servers() {
+        let mut registry = McpRegistry::default();
+        for (id, server) in [
+            (
+                "fs",
+                json!({ "type": "stdio", "command": "npx", "args": ["fs-server"] }),
+            ),
+            (
+                "docs",
+                json!({ "type": "http", "url": "https://docs.example/mcp" }),
+            ),
+        ] {
+            registry.servers.insert(
+                id.to_string(),
+                RegistryEntry {
+                    id: id.to_string(),
+                    name: id.to_string(),
+                    server,
+                    enabled: HashMap::from([("claude".to_string(), true)]),
+                    health: None,
+                    introspection: None,
+                    disabled_tools: Vec::new(),
+                    tool_prefix: None,
+                    startup: Default::default(),
+                    description: None,
+                    tags: Vec::new(),
+                    sandbox: Default::default(),
+                },
+            );
+        }
+
+        let windsurf = json!({ "mcpServers": {
+            "fs": { "command": "npx", "args": ["fs-server"], "env": { "DEBUG": "1" } },
+            "docs-copy": { "serverUrl": "https://docs.example/mcp" },
+            "events": { "serverUrl": "https://events.example/sse" },
+            "broken": { "args": [] }
+        }});
+        let servers = classify(ExternalSource::Windsurf, &windsurf, &registry);
+        let summary: Vec<(&str, ImportStatus)> = servers
+            .iter()
+            .map(|server| (server.id.as_str(), server.status))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("broken", ImportStatus::Invalid),
+                ("docs-copy", ImportStatus::Duplicate),
+                ("events", ImportStatus::New),
+                ("fs", ImportStatus::Identical),
+            ]
+        );
+        assert_eq!(servers[1].existing_id.as_deref(), Some("docs"));
+        assert_eq!(servers[2].spec["type"], json!("sse"));
+
+        // VS Code 使用 servers 字段
+        let vscode = json!({ "servers": {
+            "fs": { "type": "stdio", "command": "uvx", "args": ["other"] }
+        }});
+        let servers = classify(ExternalSource::Vscode, &vscode, &registry);
+        assert_eq!(servers[0].status, ImportStatus::Conflict);
+        assert!(classify(ExternalSource::Cursor, &vscode, &registry).is_empty());
+    }
+}
@@ -0,0 +1,721 @@
+//! MCP 服务器健康检查模块
+//!
+//! 真正启动 stdio 服务器（或连接 http/sse 端点），完成 MCP initialize 握手并
+//! 列出工具，报告协议版本、工具数量和延迟。结果保存在注册表条目的 `health`
+//! 字段中，UI 据此显示红/绿状态点。
+//!
+//! 同一个连接也用于内省：列出服务器提供的工具（含输入 schema）和资源，结果缓存在
+//! 注册表条目的 `introspection` 字段中，用户在为引擎启用服务器前即可查看。
+//!
+//! [`McpClient`] 保持连接并调用工具，供自身不支持 MCP 的引擎（Ollama）在会话中使用。
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout};
+use tokio::task::JoinHandle;
+
+use super::validation::validate_server_spec;
+use super::{normalize_transport, McpTransport};
+
+/// 客户端声明的协议版本（服务器可以协商为其它版本）
+const PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// 整个检查（启动 + 握手 + 列出工具）的超时时间
+const CHECK_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// 错误信息中保留的 stderr 长度
+const MAX_STDERR_CHARS: usize = 500;
+
+/// 分页列表最多读取的页数，防止服务器返回的游标永不结束
+const MAX_LIST_PAGES: usize = 50;
+
+/// 健康检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerHealth {
+    pub healthy: bool,
+    /// 服务器协商的协议版本
+    pub protocol_version: Option<String>,
+    /// serverInfo.name
+    pub server_name: Option<String>,
+    pub tool_count: Option<usize>,
+    /// initialize 请求的往返时间（毫秒）
+    pub latency_ms: Option<u64>,
+    pub error: Option<String>,
+    /// 检查时间（Unix 时间戳，秒）
+    pub checked_at: i64,
+}
+
+/// 服务器提供的工具
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    /// 参数的 JSON Schema
+    #[serde(default)]
+    pub input_schema: Value,
+}
+
+/// 服务器提供的资源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpResource {
+    pub uri: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub mime_type: Option<String>,
+}
+
+/// 内省结果：服务器提供的工具和资源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerIntrospection {
+    pub tools: Vec<McpTool>,
+    pub resources: Vec<McpResource>,
+    /// 获取时间（Unix 时间戳，秒）
+    pub fetched_at: i64,
+}
+
+struct Handshake {
+    protocol_version: Option<String>,
+    server_name: Option<String>,
+    tool_count: usize,
+    latency: Duration,
+}
+
+/// 逐个解析 text/event-stream 响应中的事件
+struct SseReader {
+    response: reqwest::Response,
+    buffer: String,
+}
+
+impl SseReader {
+    fn new(response: reqwest::Response) -> Self {
+        Self {
+            response,
+            buffer: String::new(),
+        }
+    }
+
+    /// 下一个事件：(event, data)，流结束时为 None
+    async fn next_event(&mut self) -> Result<Option<(String, String)>, String> {
+        loop {
+            if let Some(end) = self.buffer.find("\n\n") {
+                let block: String = self.buffer.drain(..end + 2).collect();
+                let mut event = "message".to_string();
+                let mut data = Vec::new();
+                for line in block.lines() {
+                    if let Some(value) = line.strip_prefix("event:") {
+                        event = value.trim().to_string();
+                    } else if let Some(value) = line.strip_prefix("data:") {
+                        data.push(value.strip_prefix(' ').unwrap_or(value));
+                    }
+                }
+                if data.is_empty() {
+                    continue;
+                }
+                return Ok(Some((event, data.join("\n"))));
+            }
+
+            match self
+                .response
+                .chunk()
+                .await
+                .map_err(|e| format!("读取事件流失败: {}", e))?
+            {
+                Some(chunk) => self
+                    .buffer
+                    .push_str(&String::from_utf8_lossy(&chunk).replace("\r\n", "\n")),
+                None => return Ok(None),
+            }
+        }
+    }
+
+    /// 读取事件直到收到 `id` 对应的 JSON-RPC 响应
+    async fn response_for(&mut self, id: &Value) -> Result<Value, String> {
+        while let Some((_, data)) = self.next_event().await? {
+            if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                if message.get("id") == Some(id) {
+                    return Ok(message);
+                }
+            }
+        }
+        Err("事件流在收到响应前结束".to_string())
+    }
+}
+
+enum Transport {
+    Stdio {
+        child: Child,
+        stdin: ChildStdin,
+        stdout: Lines<BufReader<ChildStdout>>,
+        stderr: JoinHandle<String>,
+    },
+    /// Streamable HTTP：每条消息一个 POST
+    Http {
+        client: reqwest::Client,
+        url: String,
+        headers: HeaderMap,
+        session_id: Option<String>,
+    },
+    /// 旧版 SSE：GET 事件流接收响应，POST 到 endpoint 事件给出的地址发送消息
+    Sse {
+        client: reqwest::Client,
+        endpoint: reqwest::Url,
+        headers: HeaderMap,
+        events: SseReader,
+    },
+}
+
+pub(super) fn spec_str<'a>(spec: &'a Value, key: &str) -> Option<&'a str> {
+    spec.get(key).and_then(Value::as_str)
+}
+
+fn spec_headers(spec: &Value) -> Result<HeaderMap, String> {
+    let mut headers = HeaderMap::new();
+    if let Some(map) = spec.get("headers").and_then(Value::as_object) {
+        for (key, value) in map {
+            let name = HeaderName::from_bytes(key.as_bytes())
+                .map_err(|e| format!("无效的请求头 '{}': {}", key, e))?;
+            let value = HeaderValue::from_str(value.as_str().unwrap_or_default())
+                .map_err(|e| format!("无效的请求头 '{}': {}", key, e))?;
+            headers.insert(name, value);
+        }
+    }
+    Ok(headers)
+}
+
+/// 按 stdio 服务器规范构建启动命令（三个标准流均为管道，句柄释放时结束进程）
+pub(super) fn stdio_command(spec: &Value) -> tokio::process::Command {
+    let program = spec_str(spec, "command").unwrap_or_default();
+    let mut cmd = crate::claude_binary::create_command_with_env(program);
+    if let Some(args) = spec.get("args").and_then(Value::as_array) {
+        cmd.args(args.iter().filter_map(Value::as_str));
+    }
+    if let Some(env) = spec.get("env").and_then(Value::as_object) {
+        for (key, value) in env {
+            cmd.env(key, value.as_str().unwrap_or_default());
+        }
+    }
+    if let Some(cwd) = spec_str(spec, "cwd") {
+        cmd.current_dir(cwd);
+    }
+    cmd.stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    let mut cmd = tokio::process::Command::from(cmd);
+    cmd.kill_on_drop(true);
+    cmd
+}
+
+pub(super) fn tail(text: &str) -> String {
+    let text = text.trim();
+    let skip = text.chars().count().saturating_sub(MAX_STDERR_CHARS);
+    text.chars().skip(skip).collect()
+}
+
+impl Transport {
+    async fn connect(spec: &Value) -> Result<Self, String> {
+        match McpTransport::from_spec(spec)? {
+            McpTransport::Http => Ok(Transport::Http {
+                client: reqwest::Client::new(),
+                url: spec_str(spec, "url").unwrap_or_default().to_string(),
+                headers: spec_headers(spec)?,
+                session_id: None,
+            }),
+            McpTransport::Sse => Self::connect_sse(spec).await,
+            McpTransport::Stdio => Self::spawn_stdio(spec),
+        }
+    }
+
+    fn spawn_stdio(spec: &Value) -> Result<Self, String> {
+        let program = spec_str(spec, "command").unwrap_or_default();
+        let mut cmd = stdio_command(spec);
+        let mut child = cmd
+            .spawn()
+            .map_err(|e| format!("启动 MCP 服务器 '{}' 失败: {}", program, e))?;
+
+        let stdin = child.stdin.take().ok_or("无法获取服务器 stdin")?;
+        let stdout = child.stdout.take().ok_or("无法获取服务器 stdout")?;
+        let mut stderr_pipe = child.stderr.take().ok_or("无法获取服务器 stderr")?;
+        let stderr = tokio::spawn(async move {
+            let mut output = String::new();
+            let _ = stderr_pipe.read_to_string(&mut output).await;
+            output
+        });
+
+        Ok(Transport::Stdio {
+            child,
+            stdin,
+            stdout: BufReader::new(stdout).lines(),
+            stderr,
+        })
+    }
+
+    async fn connect_sse(spec: &Value) -> Result<Self, String> {
+        let url = spec_str(spec, "url").unwrap_or_default();
+        let base = reqwest::Url::parse(url).map_err(|e| format!("无效的 URL '{}': {}", url, e))?;
+        let client = reqwest::Client::new();
+        let headers = spec_headers(spec)?;
+
+        let response = client
+            .get(base.clone())
+            .headers(headers.clone())
+            .header(ACCEPT, "text/event-stream")
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("连接 SSE 端点失败: {}", e))?;
+        let mut events = SseReader::new(response);
+
+        // 服务器首先通过 endpoint 事件告知消息的 POST 地址
+        let endpoint = loop {
+            match events.next_event().await? {
+                Some((event, data)) if event == "endpoint" => {
+                    break base
+                        .join(data.trim())
+                        .map_err(|e| format!("无效的消息端点 '{}': {}", data, e))?;
+                }
+                Some(_) => continue,
+                None => return Err("SSE 端点没有返回 endpoint 事件".to_string()),
+            }
+        };
+
+        Ok(Transport::Sse {
+            client,
+            endpoint,
+            headers,
+            events,
+        })
+    }
+
+    /// 发送消息；对请求返回对应的响应，对通知返回 None
+    async fn send(&mut self, message: &Value) -> Result<Option<Value>, String> {
+        let id = message.get("id").cloned();
+        match self {
+            Transport::Stdio {
+                child,
+                stdin,
+                stdout,
+                stderr,
+            } => {
+                let line = format!("{}\n", message);
+                let written = match stdin.write_all(line.as_bytes()).await {
+                    Ok(()) => stdin.flush().await,
+                    Err(e) => Err(e),
+                };
+                let Some(id) = id else {
+                    return written.map(|_| None).map_err(|e| e.to_string());
+                };
+                if written.is_ok() {
+                    // 跳过日志输出和服务器发来的其它消息
+                    while let Ok(Some(line)) = stdout.next_line().await {
+                        if let Ok(response) = serde_json::from_str::<Value>(&line) {
+                            if response.get("id") == Some(&id) {
+                                return Ok(Some(response));
+                            }
+                        }
+                    }
+                }
+
+                let _ = child.kill().await;
+                let output = tokio::time::timeout(Duration::from_secs(2), stderr)
+                    .await
+                    .ok()
+                    .and_then(Result::ok)
+                    .unwrap_or_default();
+                let status = child
+                    .try_wait()
+                    .ok()
+                    .flatten()
+                    .map(|status| status.to_string())
+                    .unwrap_or_default();
+                Err(format!("服务器进程已退出 {}\n{}", status, tail(&output))
+                    .trim()
+                    .to_string())
+            }
+            Transport::Http {
+                client,
+                url,
+                headers,
+                session_id,
+            } => {
+                let mut request = client
+                    .post(url.as_str())
+                    .headers(headers.clone())
+                    .header(ACCEPT, "application/json, text/event-stream")
+                    .json(message);
+                if let Some(session_id) = session_id.as_deref() {
+                    request = request.header("Mcp-Session-Id", session_id);
+                }
+                let response = request
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| format!("HTTP 请求失败: {}", e))?;
+                if let Some(value) = response
+                    .headers()
+                    .get("Mcp-Session-Id")
+                    .and_then(|value| value.to_str().ok())
+                {
+                    *session_id = Some(value.to_string());
+                }
+                let Some(id) = id else {
+                    return Ok(None);
+                };
+
+                let is_stream = response
+                    .headers()
+                    .get(CONTENT_TYPE)
+                    .and_then(|value| value.to_str().ok())
+                    .is_some_and(|value| value.starts_with("text/event-stream"));
+                if is_stream {
+                    SseReader::new(response).response_for(&id).await.map(Some)
+                } else {
+                    response
+                        .json::<Value>()
+                        .await
+                        .map(Some)
+                        .map_err(|e| format!("解析响应失败: {}", e))
+                }
+            }
+            Transport::Sse {
+                client,
+                endpoint,
+                headers,
+                events,
+            } => {
+                client
+                    .post(endpoint.clone())
+                    .headers(headers.clone())
+                    .json(message)
+                    .send()
+                    .await
+                    .and_then(|response| response.error_for_status())
+                    .map_err(|e| format!("发送消息失败: {}", e))?;
+                match id {
+                    Some(id) => events.response_for(&id).await.map(Some),
+                    None => Ok(None),
+                }
+            }
+        }
+    }
+
+    async fn request(&mut self, message: Value) -> Result<Value, String> {
+        let response = self.send(&message).await?.ok_or("服务器没有返回响应")?;
+        if let Some(error) = response.get("error") {
+            return Err(format!(
+                "服务器返回错误: {}",
+                error
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("未知错误")
+            ));
+        }
+        Ok(response.get("result").cloned().unwrap_or(Value::Null))
+    }
+
+    async fn close(self) {
+        if let Transport::Stdio { mut child, .. } = self {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+/// 连接服务器并完成 initialize 握手，返回连接、initialize 结果和往返时间
+async fn initialize(spec: &Value) -> Result<(Transport, Value, Duration), String> {
+    validate_server_spec(spec)?;
+    let mut transport = Transport::connect(&normalize_transport(spec)).await?;
+
+    let started = Instant::now();
+    let result = transport
+        .request(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": PROTOCOL_VERSION,
+                "capabilities": {},
+                "clientInfo": { "name": "anycode", "version": env!("CARGO_PKG_VERSION") }
+            }
+        }))
+        .await?;
+    let latency = started.elapsed();
+
+    transport
+        .send(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .await?;
+    Ok((transport, result, latency))
+}
+
+async fn handshake(spec: &Value) -> Result<Handshake, String> {
+    let (mut transport, result, latency) = initialize(spec).await?;
+    let mut next_id = 1;
+
+    // 没有声明 tools 能力的服务器不支持 tools/list
+    let tool_count = if result["capabilities"].get("tools").is_some() {
+        list_all::<Value>(&mut transport, "tools/list", "tools", &mut next_id)
+            .await?
+            .len()
+    } else {
+        0
+    };
+    transport.close().await;
+
+    Ok(Handshake {
+        protocol_version: result["protocolVersion"].as_str().map(str::to_string),
+        server_name: result["serverInfo"]["name"].as_str().map(str::to_string),
+        tool_count,
+        latency,
+    })
+}
+
+/// 读取分页列表（`tools/list`、`resources/list`）的全部条目
+///
+/// 无法解析的条目会被跳过，不影响其余条目
+async fn list_all<T: DeserializeOwned>(
+    transport: &mut Transport,
+    method: &str,
+    key: &str,
+    next_id: &mut u64,
+) -> Result<Vec<T>, String> {
+    let mut items = Vec::new();
+    let mut cursor: Option<String> = None;
+    for _ in 0..MAX_LIST_PAGES {
+        *next_id += 1;
+        let params = match &cursor {
+            Some(cursor) => json!({ "cursor": cursor }),
+            None => json!({}),
+        };
+        let result = transport
+            .request(
+                json!({ "jsonrpc": "2.0", "id": *next_id, "method": method, "params": params }),
+            )
+            .await?;
+        for item in result[key].as_array().into_iter().flatten() {
+            match serde_json::from_value(item.clone()) {
+                Ok(item) => items.push(item),
+                Err(e) => log::debug!("跳过无法解析的 {} 条目: {}", method, e),
+            }
+        }
+        match result["nextCursor"].as_str() {
+            Some(next) => cursor = Some(next.to_string()),
+            None => break,
+        }
+    }
+    Ok(items)
+}
+
+async fn fetch_introspection(spec: &Value) -> Result<ServerIntrospection, String> {
+    let (mut transport, result, _) = initialize(spec).await?;
+    let mut next_id = 1;
+
+    // 只请求服务器声明了的能力
+    let tools = if result["capabilities"].get("tools").is_some() {
+        list_all(&mut transport, "tools/list", "tools", &mut next_id).await?
+    } else {
+        Vec::new()
+    };
+    let resources = if result["capabilities"].get("resources").is_some() {
+        list_all(&mut transport, "resources/list", "resources", &mut next_id).await?
+    } else {
+        Vec::new()
+    };
+    transport.close().await;
+
+    Ok(ServerIntrospection {
+        tools,
+        resources,
+        fetched_at: chrono::Utc::now().timestamp(),
+    })
+}
+
+/// 连接服务器，列出其提供的工具和资源
+pub async fn introspect(spec: &Value) -> Result<ServerIntrospection, String> {
+    tokio::time::timeout(CHECK_TIMEOUT, fetch_introspection(spec))
+        .await
+        .unwrap_or_else(|_| Err(format!("{} 秒内未完成内省", CHECK_TIMEOUT.as_secs())))
+}
+
+/// 工具调用结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolCallResult {
+    /// 文本内容（非文本内容以 JSON 形式给出）
+    pub content: String,
+    pub is_error: bool,
+}
+
+/// 保持连接的 MCP 客户端，列出并调用服务器的工具
+pub struct McpClient {
+    transport: Transport,
+    next_id: u64,
+    pub tools: Vec<McpTool>,
+}
+
+impl McpClient {
+    /// 连接服务器、完成握手并列出工具
+    pub async fn connect(spec: &Value) -> Result<Self, String> {
+        let timeout = CHECK_TIMEOUT.as_secs();
+        tokio::time::timeout(CHECK_TIMEOUT, async {
+            let (mut transport, result, _) = initialize(spec).await?;
+            let mut next_id = 1;
+            let tools = if result["capabilities"].get("tools").is_some() {
+                list_all(&mut transport, "tools/list", "tools", &mut next_id).await?
+            } else {
+                Vec::new()
+            };
+            Ok(Self {
+                transport,
+                next_id,
+                tools,
+            })
+        })
+        .await
+        .unwrap_or_else(|_| Err(format!("{} 秒内未完成握手", timeout)))
+    }
+
+    /// 调用工具；工具自身报告的错误记录在结果的 `is_error` 中
+    pub async fn call_tool(
+        &mut self,
+        name: &str,
+        arguments: Value,
+    ) -> Result<ToolCallResult, String> {
+        self.next_id += 1;
+        let result = self
+            .transport
+            .request(json!({
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": "tools/call",
+                "params": { "name": name, "arguments": arguments }
+            }))
+            .await?;
+
+        let content = result["content"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .map(|item| match item["text"].as_str() {
+                Some(text) => text.to_string(),
+                None => item.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Ok(ToolCallResult {
+            content,
+            is_error: result["isError"].as_bool().unwrap_or(false),
+        })
+    }
+
+    /// 断开连接（stdio 服务器会被结束）
+    pub async fn close(self) {
+        self.transport.close().await;
+    }
+}
+
+/// 检查服务器是否可用（不会返回错误，失败信息记录在结果中）
+pub async fn check_server(spec: &Value) -> ServerHealth {
+    let outcome = tokio::time::timeout(CHECK_TIMEOUT, handshake(spec))
+        .await
+        .unwrap_or_else(|_| Err(format!("{} 秒内未完成握手", CHECK_TIMEOUT.as_secs())));
+    let checked_at = chrono::Utc::now().timestamp();
+
+    match outcome {
+        Ok(handshake) => ServerHealth {
+            healthy: true,
+            protocol_version: handshake.protocol_version,
+            server_name: handshake.server_name,
+            tool_count: Some(handshake.tool_count),
+            latency_ms: Some(handshake.latency.as_millis() as u64),
+            error: None,
+            checked_at,
+        },
+        Err(error) => ServerHealth {
+            healthy: false,
+            protocol_version: None,
+            server_name: None,
+            tool_count: None,
+            latency_ms: None,
+            error: Some(error),
+            checked_at,
+        },
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    /// 最小的 stdio MCP 服务器：回应 initialize、分两页的 tools/list 和 resources/list，
+    /// 忽略其它消息
+    const FAKE_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/.*"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      echo "starting"
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"protocolVersion":"2024-11-05","capabilities":{"tools":{},"resources":{}},"serverInfo":{"name":"fake"}}}\n' "$id" ;;
+    *'"cursor":"page2"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"search","description":"Search the docs","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"echo"},{"name":"add"}],"nextCursor":"page2"}}\n' "$id" ;;
+    *'"method":"resources/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"resources":[{"uri":"file:///README.md","name":"README","mimeType":"text/markdown"},{"name":"no uri"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+    fn fake_server() -> Value {
+        json!({ "command": "sh", "args": ["-c", FAKE_SERVER] })
+    }
+
+    #[tokio::test]
+    async fn test_check_server() {
+        let health = check_server(&fake_server()).await;
+        assert!(health.healthy, "{:?}", health.error);
+        assert_eq!(health.protocol_version.as_deref(), Some("2024-11-05"));
+        assert_eq!(health.server_name.as_deref(), Some("fake"));
+        assert_eq!(health.tool_count, Some(3));
+        assert!(health.latency_ms.is_some());
+
+        // 握手前退出的服务器报告其 stderr
+        let crashing = json!({
+            "command": "sh",
+            "args": ["-c", "echo 'missing API token' >&2; exit 3"]
+        });
+        let health = check_server(&crashing).await;
+        assert!(!health.healthy);
+        assert!(health.error.unwrap().contains("missing API token"));
+    }
+
+    #[tokio::test]
+    async fn test_introspect() {
+        let introspection = introspect(&fake_server()).await.unwrap();
+        let tools: Vec<&str> = introspection
+            .tools
+            .iter()
+            .map(|tool| tool.name.as_str())
+            .collect();
+        assert_eq!(tools, vec!["echo", "add", "search"]);
+        let search = &introspection.tools[2];
+        assert_eq!(search.description.as_deref(), Some("Search the docs"));
+        assert_eq!(search.input_schema, json!({ "type": "object" }));
+
+        // 缺少 uri 的资源被跳过
+        assert_eq!(introspection.resources.len(), 1);
+        let readme = &introspection.resources[0];
+        assert_eq!(readme.uri, "file:///README.md");
+        assert_eq!(readme.mime_type.as_deref(), Some("text/markdown"));
+    }
+}
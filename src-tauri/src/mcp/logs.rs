@@ -0,0 +1,137 @@
+//! MCP 服务器日志
+//!
+//! 受监督服务器的 stderr 和进程生命周期（启动、退出、重启）逐行写入
+//! `~/.anycode/logs/mcp/<id>.log`，每行带 RFC 3339 时间戳。文件超过 1 MB 时
+//! 只保留最后一半，相当于磁盘上的环形缓冲区。新写入的行同时通过
+//! `mcp-server-log` 事件推送给前端。
+
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 单个日志文件的大小上限
+const MAX_LOG_BYTES: u64 = 1024 * 1024;
+/// 读取日志时默认返回的行数
+pub const DEFAULT_TAIL: usize = 200;
+
+/// 串行化写入和截断，避免多个服务器同时轮转时互相覆盖
+static WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+/// 一行服务器日志（也是 `mcp-server-log` 事件的内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpLogLine {
+    pub id: String,
+    pub timestamp: String,
+    pub line: String,
+}
+
+/// 获取日志目录
+fn log_dir() -> PathBuf {
+    let home_dir = dirs::home_dir().expect("Failed to get home directory");
+    home_dir.join(".anycode").join("logs").join("mcp")
+}
+
+/// 服务器 ID 转为安全的文件名
+fn log_path(dir: &Path, id: &str) -> PathBuf {
+    let name: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    dir.join(format!("{}.log", name.trim_start_matches('.')))
+}
+
+/// 追加一行，超过上限时丢弃较早的一半
+fn append_to(path: &Path, line: &str) -> Result<(), String> {
+    let _guard = WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建日志目录失败: {}", e))?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(|e| format!("打开日志文件失败: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("写入日志失败: {}", e))?;
+
+    let size = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if size > MAX_LOG_BYTES {
+        drop(file);
+        let content = std::fs::read(path).map_err(|e| format!("读取日志文件失败: {}", e))?;
+        let cut = content.len() - (MAX_LOG_BYTES / 2) as usize;
+        // 从下一行开始保留，避免留下半行
+        let start = content[cut..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(cut, |offset| cut + offset + 1);
+        std::fs::write(path, &content[start..]).map_err(|e| format!("截断日志失败: {}", e))?;
+    }
+    Ok(())
+}
+
+/// 读取最后 `tail` 行
+fn tail_of(path: &Path, tail: usize) -> Result<Vec<String>, String> {
+    let content = match std::fs::read(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(format!("读取日志文件失败: {}", e)),
+    };
+    let content = String::from_utf8_lossy(&content);
+    let lines: Vec<&str> = content.lines().collect();
+    let skip = lines.len().saturating_sub(tail);
+    Ok(lines[skip..].iter().map(|line| line.to_string()).collect())
+}
+
+/// 记录服务器的一行日志，返回写入的内容
+pub fn append(id: &str, line: &str) -> McpLogLine {
+    let entry = McpLogLine {
+        id: id.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+        line: line.trim_end().to_string(),
+    };
+    let path = log_path(&log_dir(), id);
+    if let Err(e) = append_to(&path, &format!("{} {}", entry.timestamp, entry.line)) {
+        log::debug!("写入 MCP 服务器 '{}' 的日志失败: {}", id, e);
+    }
+    entry
+}
+
+/// 读取服务器日志的最后 `tail` 行
+pub fn read_tail(id: &str, tail: usize) -> Result<Vec<String>, String> {
+    tail_of(&log_path(&log_dir(), id), tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_log_ring_buffer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = log_path(dir.path(), "../my server");
+        assert_eq!(path, dir.path().join("_my_server.log"));
+        assert!(tail_of(&path, 10).unwrap().is_empty());
+
+        let line = "x".repeat(1000);
+        for n in 0..2000 {
+            append_to(&path, &format!("{} {}", n, line)).unwrap();
+        }
+        let size = std::fs::metadata(&path).unwrap().len();
+        assert!(size <= MAX_LOG_BYTES);
+
+        let last = tail_of(&path, 2).unwrap();
+        assert_eq!(last.len(), 2);
+        assert!(last[0].starts_with("1998 "));
+        assert!(last[1].starts_with("1999 "));
+        // 截断后第一行是完整的
+        let all = tail_of(&path, usize::MAX).unwrap();
+        assert!(all[0].ends_with(&line) && !all[0].starts_with('x'));
+    }
+}
@@ -0,0 +1,136 @@
+//! MCP 注册表格式迁移
+//!
+//! 注册表格式每变化一次，`REGISTRY_VERSION` 加一，并在 `MIGRATIONS` 末尾追加
+//! 一个把上一版本升级到该版本的函数。读取注册表时在原始 JSON 上依次执行缺失的
+//! 迁移，再解析为 `McpRegistry`，因此旧文件不会因为字段变化而解析失败、丢失服务器。
+//! 版本高于当前支持的注册表（由更新的应用写入）会被拒绝，避免被旧版本覆盖。
+
+use serde_json::{Map, Value};
+
+/// 当前注册表格式版本
+pub const REGISTRY_VERSION: u32 = 2;
+
+type Migration = fn(&mut Map<String, Value>) -> Result<(), String>;
+
+/// `MIGRATIONS[n]` 将版本 n 升级到 n + 1
+const MIGRATIONS: &[Migration] = &[v0_to_v1, v1_to_v2];
+
+/// v2 之前的启用状态对这些引擎都生效
+const ENGINES: &[&str] = &["claude", "codex", "gemini"];
+
+/// v0（没有 version 字段）：条目可能缺少 id、name 或 enabled，id 以映射的键为准
+fn v0_to_v1(registry: &mut Map<String, Value>) -> Result<(), String> {
+    let servers = registry
+        .entry("servers")
+        .or_insert_with(|| Value::Object(Map::new()))
+        .as_object_mut()
+        .ok_or("注册表的 servers 字段不是对象")?;
+
+    for (id, entry) in servers.iter_mut() {
+        let entry = entry
+            .as_object_mut()
+            .ok_or_else(|| format!("服务器 '{}' 的注册表条目不是对象", id))?;
+        entry.insert("id".to_string(), Value::String(id.clone()));
+        entry
+            .entry("name")
+            .or_insert_with(|| Value::String(id.clone()));
+        entry
+            .entry("server")
+            .or_insert_with(|| Value::Object(Map::new()));
+        entry.entry("enabled").or_insert(Value::Bool(true));
+    }
+    Ok(())
+}
+
+/// v1：`enabled` 是布尔值，对所有引擎生效；v2 改为按引擎记录，旧值复制到每个引擎
+fn v1_to_v2(registry: &mut Map<String, Value>) -> Result<(), String> {
+    let Some(servers) = registry.get_mut("servers").and_then(Value::as_object_mut) else {
+        return Ok(());
+    };
+
+    for (id, entry) in servers.iter_mut() {
+        let entry = entry
+            .as_object_mut()
+            .ok_or_else(|| format!("服务器 '{}' 的注册表条目不是对象", id))?;
+        let enabled = entry
+            .get("enabled")
+            .and_then(Value::as_bool)
+            .unwrap_or(true);
+        let per_engine: Map<String, Value> = ENGINES
+            .iter()
+            .map(|engine| (engine.to_string(), Value::Bool(enabled)))
+            .collect();
+        entry.insert("enabled".to_string(), Value::Object(per_engine));
+    }
+    Ok(())
+}
+
+/// 将注册表 JSON 升级到当前版本
+///
+/// 返回是否执行了迁移（需要写回文件）
+pub fn migrate(value: &mut Value) -> Result<bool, String> {
+    let registry = value.as_object_mut().ok_or("注册表不是 JSON 对象")?;
+
+    let version = match registry.get("version") {
+        None => 0,
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or("注册表的 version 字段无效")?,
+    };
+    if version > REGISTRY_VERSION {
+        return Err(format!(
+            "注册表版本 {} 高于当前支持的版本 {}，请升级应用",
+            version, REGISTRY_VERSION
+        ));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        migration(registry)?;
+        log::info!("注册表已从版本 {} 迁移到版本 {}", from, from + 1);
+    }
+    registry.insert("version".to_string(), Value::from(REGISTRY_VERSION));
+
+    Ok(version < REGISTRY_VERSION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_migrate_registry() {
+        let mut legacy = json!({
+            "servers": {
+                "fs": { "server": { "command": "npx" } },
+                "db": { "id": "old", "name": "Database", "server": {}, "enabled": false }
+            }
+        });
+        assert!(migrate(&mut legacy).unwrap());
+        assert_eq!(legacy["version"], json!(REGISTRY_VERSION));
+        assert_eq!(legacy["servers"]["fs"]["name"], json!("fs"));
+        assert_eq!(
+            legacy["servers"]["fs"]["enabled"],
+            json!({ "claude": true, "codex": true, "gemini": true })
+        );
+        assert_eq!(legacy["servers"]["db"]["id"], json!("db"));
+        assert_eq!(legacy["servers"]["db"]["enabled"]["gemini"], json!(false));
+
+        // v1 的布尔启用状态按引擎展开
+        let mut v1 = json!({
+            "version": 1,
+            "servers": { "docs": { "id": "docs", "name": "docs", "server": {}, "enabled": false } }
+        });
+        assert!(migrate(&mut v1).unwrap());
+        assert_eq!(
+            v1["servers"]["docs"]["enabled"],
+            json!({ "claude": false, "codex": false, "gemini": false })
+        );
+
+        // 当前版本保持不变，更高版本被拒绝
+        assert!(!migrate(&mut legacy).unwrap());
+        let mut newer = json!({ "version": REGISTRY_VERSION + 1, "servers": {} });
+        assert!(migrate(&mut newer).is_err());
+    }
+}
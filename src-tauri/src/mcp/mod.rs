@@ -8,28 +8,63 @@
 //! - `claude` - Claude MCP 同步和导入
 //! - `codex` - Codex MCP 同步和导入
 //! - `gemini` - Gemini MCP 同步和导入
+//! - `registry` - 独立的服务器注册表（含项目级注册表、备份与恢复）
+//! - `migrations` - 注册表格式迁移
+//! - `health` - 服务器健康检查（initialize 握手）
+//! - `supervisor` - stdio 服务器进程监督（自动重启）
+//! - `bridge` - 引擎通过桥接进程使用受监督的服务器
+//! - `router` - 多个引擎会话共享服务器进程时的消息路由
+//! - `logs` - 服务器日志（~/.anycode/logs/mcp）
+//! - `external` - 从 Claude Desktop、Cursor、Windsurf、VS Code 的配置导入
+//! - `bundle` - 可分享的配置包导出与导入
+//! - `secrets` - 保存在系统钥匙串中的 env/headers 密钥
+//! - `drift` - 注册表与引擎配置的差异检测和协调
+//! - `catalog` - 常用 MCP 服务器目录（内置 + 远程），一键安装
+//! - `versions` - npx/uvx 服务器的版本锁定与更新检查
 //!
 //! ## 应用类型
 //!
-//! 支持三种应用类型：
+//! 支持五种应用类型：
 //! - Claude: ~/.claude.json
 //! - Codex: ~/.codex/settings.toml
 //! - Gemini: ~/.gemini/settings.json
+//! - Ollama: ~/.anycode/ollama-mcp.json（本地模型，由工作台自己调用工具）
+//! - OpenRouter: ~/.anycode/openrouter-mcp.json（同上）
 
+pub mod audit;
+pub mod bridge;
+pub mod bundle;
+pub mod catalog;
 mod claude;
 mod codex;
+pub mod conflicts;
+pub mod drift;
+pub mod external;
 mod gemini;
+pub mod health;
+pub mod logs;
+mod migrations;
+pub mod prefix;
 pub mod registry;
+mod router;
+pub mod sandbox;
+pub mod search;
+pub mod secrets;
+pub mod supervisor;
 mod validation;
+pub mod versions;
+pub mod workbench;
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 
+use crate::engines::EngineProvider;
+
 // 重新导出公共 API
 pub use claude::{
-    import_from_claude, remove_server_from_claude, sync_servers_to_claude,
-    sync_single_server_to_claude,
+    import_from_claude, remove_server_from_claude, sync_project_servers_to_claude,
+    sync_servers_to_claude, sync_single_server_to_claude,
 };
 pub use codex::{
     import_from_codex, remove_server_from_codex, sync_servers_to_codex,
@@ -39,7 +74,7 @@ pub use gemini::{
     import_from_gemini, remove_server_from_gemini, sync_servers_to_gemini,
     sync_single_server_to_gemini,
 };
-pub use validation::{extract_server_spec, validate_server_spec};
+pub use validation::{extract_server_spec, validate_server_spec, validate_spec_fields, SpecError};
 
 /// 应用类型
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -48,14 +83,27 @@ pub enum AppType {
     Claude,
     Codex,
     Gemini,
+    Ollama,
+    OpenRouter,
 }
 
 impl AppType {
+    /// 所有应用类型
+    pub const ALL: [AppType; 5] = [
+        AppType::Claude,
+        AppType::Codex,
+        AppType::Gemini,
+        AppType::Ollama,
+        AppType::OpenRouter,
+    ];
+
     pub fn as_str(&self) -> &str {
         match self {
             AppType::Claude => "claude",
             AppType::Codex => "codex",
             AppType::Gemini => "gemini",
+            AppType::Ollama => "ollama",
+            AppType::OpenRouter => "openrouter",
         }
     }
 
@@ -64,9 +112,93 @@ impl AppType {
             "claude" => Ok(AppType::Claude),
             "codex" => Ok(AppType::Codex),
             "gemini" => Ok(AppType::Gemini),
+            "ollama" => Ok(AppType::Ollama),
+            "openrouter" => Ok(AppType::OpenRouter),
             other => Err(format!("不支持的应用类型: '{}'", other)),
         }
     }
+
+    /// 对应的引擎实现（见 `engines` 模块）
+    pub fn provider(&self) -> &'static dyn EngineProvider {
+        crate::engines::get(self.as_str()).expect("每种应用类型都有注册的引擎")
+    }
+}
+
+/// MCP 传输类型
+///
+/// 统一格式中写在 `type` 字段。其他工具使用的别名（"streamable-http"、
+/// "streamableHttp"）以及 Windsurf 的 `serverUrl`、Gemini 的 `httpUrl`
+/// 在写入注册表和同步到引擎前由 [`normalize_transport`] 转换为统一格式。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum McpTransport {
+    Stdio,
+    Sse,
+    Http,
+}
+
+impl McpTransport {
+    pub fn as_str(&self) -> &str {
+        match self {
+            McpTransport::Stdio => "stdio",
+            McpTransport::Sse => "sse",
+            McpTransport::Http => "http",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.trim().to_lowercase().as_str() {
+            "stdio" => Ok(McpTransport::Stdio),
+            "sse" => Ok(McpTransport::Sse),
+            "http" | "streamable-http" | "streamable_http" | "streamablehttp" => {
+                Ok(McpTransport::Http)
+            }
+            other => Err(format!("不支持的传输类型: '{}'", other)),
+        }
+    }
+
+    /// 服务器规范的传输类型
+    ///
+    /// 没有 `type` 时按字段推断：有 command 为 stdio，只有 URL 时路径以 /sse
+    /// 结尾为 sse，否则为 http
+    pub fn from_spec(spec: &Value) -> Result<Self, String> {
+        if let Some(t) = spec.get("type") {
+            return t
+                .as_str()
+                .ok_or_else(|| "type 字段必须是字符串".to_string())
+                .and_then(Self::from_str);
+        }
+        if spec.get("command").is_some() {
+            return Ok(McpTransport::Stdio);
+        }
+        let url = ["url", "serverUrl", "httpUrl"]
+            .iter()
+            .find_map(|key| spec.get(*key).and_then(Value::as_str));
+        Ok(match url {
+            Some(url) if url.trim().trim_end_matches('/').ends_with("/sse") => McpTransport::Sse,
+            Some(_) => McpTransport::Http,
+            None => McpTransport::Stdio,
+        })
+    }
+}
+
+/// 将服务器规范转换为统一格式：`type` 使用标准名称，URL 统一写在 `url` 字段
+///
+/// 无法识别的传输类型保持不变，由验证报告错误
+pub fn normalize_transport(spec: &Value) -> Value {
+    let mut spec = spec.clone();
+    let Ok(transport) = McpTransport::from_spec(&spec) else {
+        return spec;
+    };
+    if let Some(obj) = spec.as_object_mut() {
+        for key in ["serverUrl", "httpUrl"] {
+            if let Some(url) = obj.remove(key) {
+                obj.entry("url").or_insert(url);
+            }
+        }
+        obj.insert("type".to_string(), Value::from(transport.as_str()));
+    }
+    spec
 }
 
 /// MCP 应用启用状态
@@ -78,6 +210,10 @@ pub struct McpApps {
     pub codex: bool,
     #[serde(default)]
     pub gemini: bool,
+    #[serde(default)]
+    pub ollama: bool,
+    #[serde(default)]
+    pub openrouter: bool,
 }
 
 impl McpApps {
@@ -87,6 +223,8 @@ impl McpApps {
             AppType::Claude => self.claude,
             AppType::Codex => self.codex,
             AppType::Gemini => self.gemini,
+            AppType::Ollama => self.ollama,
+            AppType::OpenRouter => self.openrouter,
         }
     }
 
@@ -96,27 +234,22 @@ impl McpApps {
             AppType::Claude => self.claude = enabled,
             AppType::Codex => self.codex = enabled,
             AppType::Gemini => self.gemini = enabled,
+            AppType::Ollama => self.ollama = enabled,
+            AppType::OpenRouter => self.openrouter = enabled,
         }
     }
 
     /// 获取所有启用的应用列表
     pub fn enabled_apps(&self) -> Vec<AppType> {
-        let mut apps = Vec::new();
-        if self.claude {
-            apps.push(AppType::Claude);
-        }
-        if self.codex {
-            apps.push(AppType::Codex);
-        }
-        if self.gemini {
-            apps.push(AppType::Gemini);
-        }
-        apps
+        AppType::ALL
+            .into_iter()
+            .filter(|app| self.is_enabled_for(app))
+            .collect()
     }
 
     /// 检查是否所有应用都未启用
     pub fn is_empty(&self) -> bool {
-        !self.claude && !self.codex && !self.gemini
+        !self.claude && !self.codex && !self.gemini && !self.ollama && !self.openrouter
     }
 }
 
@@ -143,20 +276,13 @@ pub fn sync_server_to_app(
     server_spec: &Value,
     app: &AppType,
 ) -> Result<(), String> {
-    match app {
-        AppType::Claude => sync_single_server_to_claude(id, server_spec),
-        AppType::Codex => sync_single_server_to_codex(id, server_spec),
-        AppType::Gemini => sync_single_server_to_gemini(id, server_spec),
-    }
+    app.provider()
+        .upsert_mcp_server(id, &normalize_transport(server_spec))
 }
 
 /// 从指定应用移除 MCP 服务器
 pub fn remove_server_from_app(id: &str, app: &AppType) -> Result<(), String> {
-    match app {
-        AppType::Claude => remove_server_from_claude(id),
-        AppType::Codex => remove_server_from_codex(id),
-        AppType::Gemini => remove_server_from_gemini(id),
-    }
+    app.provider().remove_mcp_server(id)
 }
 
 /// 将 MCP 服务器同步到所有启用的应用
@@ -175,13 +301,32 @@ pub fn remove_server_from_all_apps(server: &McpServer) -> Result<(), String> {
     Ok(())
 }
 
+/// 将项目注册表同步到项目的 .mcp.json
+///
+/// 启用的项目服务器写入，禁用的以及 `removed`（刚移出项目注册表的）服务器移除。
+/// 目前只有 Claude 支持项目级 MCP 配置。
+pub fn sync_project_registry(project_path: &str, removed: &[&str]) -> Result<(), String> {
+    let project = registry::read_project_registry(project_path)?;
+
+    let mut servers: HashMap<String, Option<Value>> = removed
+        .iter()
+        .map(|id| (id.to_string(), None))
+        .collect();
+    for (id, entry) in project.servers {
+        let spec = if entry.is_enabled_for(&AppType::Claude) {
+            Some(entry.engine_spec()?)
+        } else {
+            None
+        };
+        servers.insert(id, spec);
+    }
+
+    sync_project_servers_to_claude(project_path, &servers)
+}
+
 /// 从指定应用导入 MCP 服务器
 pub fn import_from_app(app: &AppType) -> Result<HashMap<String, Value>, String> {
-    match app {
-        AppType::Claude => import_from_claude(),
-        AppType::Codex => import_from_codex(),
-        AppType::Gemini => import_from_gemini(),
-    }
+    app.provider().read_mcp_servers()
 }
 
 /// 将多个服务器同步到指定应用
@@ -189,11 +334,11 @@ pub fn sync_servers_to_app(
     servers: &HashMap<String, Value>,
     app: &AppType,
 ) -> Result<(), String> {
-    match app {
-        AppType::Claude => sync_servers_to_claude(servers),
-        AppType::Codex => sync_servers_to_codex(servers),
-        AppType::Gemini => sync_servers_to_gemini(servers),
-    }
+    let normalized: HashMap<String, Value> = servers
+        .iter()
+        .map(|(id, spec)| (id.clone(), normalize_transport(spec)))
+        .collect();
+    app.provider().write_mcp_servers(&normalized)
 }
 
 /// 获取所有应用的 MCP 服务器统一视图（合并所有应用配置）
@@ -203,7 +348,7 @@ pub fn sync_servers_to_app(
 pub fn get_unified_servers() -> Result<HashMap<String, McpServer>, String> {
     log::info!("开始获取统一的 MCP 服务器视图");
 
-    // 读取三个应用的配置
+    // 读取各应用的配置
     let claude_servers = import_from_claude().unwrap_or_else(|e| {
         log::warn!("读取 Claude MCP 配置失败: {}", e);
         HashMap::new()
@@ -216,12 +361,23 @@ pub fn get_unified_servers() -> Result<HashMap<String, McpServer>, String> {
         log::warn!("读取 Gemini MCP 配置失败: {}", e);
         HashMap::new()
     });
+    let ollama_servers = crate::chat_mcp::read_mcp_servers_map("ollama").unwrap_or_else(|e| {
+        log::warn!("读取 Ollama MCP 配置失败: {}", e);
+        HashMap::new()
+    });
+    let openrouter_servers =
+        crate::chat_mcp::read_mcp_servers_map("openrouter").unwrap_or_else(|e| {
+            log::warn!("读取 OpenRouter MCP 配置失败: {}", e);
+            HashMap::new()
+        });
 
     log::info!(
-        "配置读取完成 - Claude: {} 个, Codex: {} 个, Gemini: {} 个",
+        "配置读取完成 - Claude: {} 个, Codex: {} 个, Gemini: {} 个, Ollama: {} 个, OpenRouter: {} 个",
         claude_servers.len(),
         codex_servers.len(),
-        gemini_servers.len()
+        gemini_servers.len(),
+        ollama_servers.len(),
+        openrouter_servers.len()
     );
 
     // 合并所有服务器
@@ -232,17 +388,23 @@ pub fn get_unified_servers() -> Result<HashMap<String, McpServer>, String> {
     all_ids.extend(claude_servers.keys().cloned());
     all_ids.extend(codex_servers.keys().cloned());
     all_ids.extend(gemini_servers.keys().cloned());
+    all_ids.extend(ollama_servers.keys().cloned());
+    all_ids.extend(openrouter_servers.keys().cloned());
 
     // 为每个 ID 创建统一的服务器结构
     for id in all_ids {
         let claude_spec = claude_servers.get(&id);
         let codex_spec = codex_servers.get(&id);
         let gemini_spec = gemini_servers.get(&id);
+        let ollama_spec = ollama_servers.get(&id);
+        let openrouter_spec = openrouter_servers.get(&id);
 
-        // 优先使用 Claude 的配置，其次 Codex，最后 Gemini
+        // 优先使用 Claude 的配置，其次 Codex、Gemini、Ollama，最后 OpenRouter
         let server_spec = claude_spec
             .or(codex_spec)
             .or(gemini_spec)
+            .or(ollama_spec)
+            .or(openrouter_spec)
             .cloned()
             .unwrap_or(Value::Object(serde_json::Map::new()));
 
@@ -257,6 +419,8 @@ pub fn get_unified_servers() -> Result<HashMap<String, McpServer>, String> {
                     claude: claude_spec.is_some(),
                     codex: codex_spec.is_some(),
                     gemini: gemini_spec.is_some(),
+                    ollama: ollama_spec.is_some(),
+                    openrouter: openrouter_spec.is_some(),
                 },
                 description: None,
                 homepage: None,
@@ -268,3 +432,64 @@ pub fn get_unified_servers() -> Result<HashMap<String, McpServer>, String> {
 
     Ok(unified)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_transport_from_spec() {
+        let transport = |spec: Value| McpTransport::from_spec(&spec);
+        assert_eq!(
+            transport(json!({ "command": "npx" })),
+            Ok(McpTransport::Stdio)
+        );
+        assert_eq!(
+            transport(json!({ "url": "https://mcp.example.com/sse/" })),
+            Ok(McpTransport::Sse)
+        );
+        assert_eq!(
+            transport(json!({ "httpUrl": "https://mcp.example.com/mcp" })),
+            Ok(McpTransport::Http)
+        );
+        // 显式的 type 优先于推断
+        assert_eq!(
+            transport(json!({ "type": "Streamable-HTTP", "url": "https://mcp.example.com/sse" })),
+            Ok(McpTransport::Http)
+        );
+        assert!(transport(json!({ "type": "websocket", "url": "wss://mcp.example.com" })).is_err());
+        assert!(transport(json!({ "type": 1 })).is_err());
+    }
+
+    #[test]
+    fn test_normalize_transport() {
+        assert_eq!(
+            normalize_transport(&json!({ "serverUrl": "https://mcp.example.com/sse" })),
+            json!({ "type": "sse", "url": "https://mcp.example.com/sse" })
+        );
+        assert_eq!(
+            normalize_transport(&json!({
+                "type": "streamable_http",
+                "httpUrl": "https://mcp.example.com/mcp",
+                "headers": { "Authorization": "Bearer token" }
+            })),
+            json!({
+                "type": "http",
+                "url": "https://mcp.example.com/mcp",
+                "headers": { "Authorization": "Bearer token" }
+            })
+        );
+        // 已有的 url 不被别名覆盖
+        let both = json!({
+            "url": "https://a.example.com/mcp",
+            "httpUrl": "https://b.example.com/mcp"
+        });
+        assert_eq!(
+            normalize_transport(&both),
+            json!({ "type": "http", "url": "https://a.example.com/mcp" })
+        );
+        let unknown = json!({ "type": "websocket", "url": "wss://mcp.example.com" });
+        assert_eq!(normalize_transport(&unknown), unknown);
+    }
+}
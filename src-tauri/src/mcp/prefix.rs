@@ -0,0 +1,237 @@
+//! MCP 工具前缀
+//!
+//! 引擎配置无法给服务器的工具改名，因此由本应用在转发消息时改写：在 `tools/list`
+//! 的结果中给工具名加上前缀，在 `tools/call` 中去掉前缀，其余消息原样转发。
+//! 只有 stdio 服务器支持前缀。
+//!
+//! 引擎通过桥接（见 `bridge` 模块）使用服务器时由路由改写。之前写入引擎配置的
+//! 代理模式仍然可用：`any-code --mcp-tool-prefix <前缀> -- <原命令> <原参数>...`，
+//! `env` 和 `cwd` 由引擎设置，原服务器继承。
+
+use serde_json::Value;
+use std::io::{BufRead, BufReader, Write};
+use std::process::Stdio;
+
+use super::McpTransport;
+
+/// 代理模式的命令行标志
+pub const PROXY_FLAG: &str = "--mcp-tool-prefix";
+
+/// 前缀最长字符数（部分引擎限制工具名不超过 64 个字符）
+const MAX_PREFIX_CHARS: usize = 32;
+
+/// 检查前缀：只能包含字母、数字、`_` 和 `-`
+pub fn validate_prefix(prefix: &str) -> Result<(), String> {
+    if prefix.is_empty() || prefix.chars().count() > MAX_PREFIX_CHARS {
+        return Err(format!("工具前缀长度应为 1 到 {} 个字符", MAX_PREFIX_CHARS));
+    }
+    if !prefix
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!(
+            "工具前缀 '{}' 只能包含字母、数字、下划线和连字符",
+            prefix
+        ));
+    }
+    Ok(())
+}
+
+/// 由服务器 ID 生成的默认前缀，例如 `github` -> `github_`
+pub fn default_prefix(id: &str) -> String {
+    let mut prefix: String = id
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(MAX_PREFIX_CHARS - 1)
+        .collect();
+    prefix.push('_');
+    prefix
+}
+
+/// 将 stdio 服务器规范改为通过代理启动
+pub fn wrap(spec: &Value, prefix: &str) -> Result<Value, String> {
+    validate_prefix(prefix)?;
+    if McpTransport::from_spec(spec)? != McpTransport::Stdio {
+        return Err("只有 stdio 服务器支持工具前缀".to_string());
+    }
+    let command = spec
+        .get("command")
+        .and_then(Value::as_str)
+        .ok_or("服务器缺少 command 字段")?;
+    let exe = std::env::current_exe().map_err(|e| format!("获取应用路径失败: {}", e))?;
+
+    let mut args = vec![
+        Value::from(PROXY_FLAG),
+        Value::from(prefix),
+        Value::from("--"),
+        Value::from(command),
+    ];
+    args.extend(
+        spec.get("args")
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .cloned(),
+    );
+
+    let mut wrapped = spec.clone();
+    wrapped["command"] = Value::from(exe.to_string_lossy().to_string());
+    wrapped["args"] = Value::Array(args);
+    Ok(wrapped)
+}
+
+/// [`wrap`] 的逆操作：代理启动的规范返回前缀和原规范，否则返回 None
+pub fn unwrap(spec: &Value) -> Option<(String, Value)> {
+    let args = spec.get("args")?.as_array()?;
+    if args.first()?.as_str()? != PROXY_FLAG || args.get(2)?.as_str()? != "--" {
+        return None;
+    }
+    let prefix = args.get(1)?.as_str()?.to_string();
+    let command = args.get(3)?.clone();
+
+    let mut inner = spec.clone();
+    inner["command"] = command;
+    inner["args"] = Value::Array(args[4..].to_vec());
+    Some((prefix, inner))
+}
+
+/// 服务器 -> 引擎：`tools/list` 结果中的工具名加上前缀
+pub(super) fn prefix_tools(message: &mut Value, prefix: &str) {
+    let Some(tools) = message
+        .get_mut("result")
+        .and_then(|result| result.get_mut("tools"))
+        .and_then(Value::as_array_mut)
+    else {
+        return;
+    };
+    for tool in tools {
+        if let Some(name) = tool.get("name").and_then(Value::as_str) {
+            tool["name"] = Value::from(format!("{}{}", prefix, name));
+        }
+    }
+}
+
+/// 引擎 -> 服务器：`tools/call` 中的工具名去掉前缀
+pub(super) fn strip_tool_call(message: &mut Value, prefix: &str) {
+    if message.get("method").and_then(Value::as_str) != Some("tools/call") {
+        return;
+    }
+    let Some(params) = message.get_mut("params") else {
+        return;
+    };
+    let stripped = params
+        .get("name")
+        .and_then(Value::as_str)
+        .and_then(|name| name.strip_prefix(prefix))
+        .map(str::to_string);
+    if let Some(name) = stripped {
+        params["name"] = Value::from(name);
+    }
+}
+
+/// 逐行转发 JSON-RPC 消息，无法解析的行原样转发
+fn pump(reader: impl BufRead, mut writer: impl Write, rewrite: impl Fn(&mut Value)) {
+    for line in reader.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let line = match serde_json::from_str::<Value>(&line) {
+            Ok(mut message) => {
+                rewrite(&mut message);
+                message.to_string()
+            }
+            Err(_) => line,
+        };
+        if writeln!(writer, "{}", line)
+            .and_then(|_| writer.flush())
+            .is_err()
+        {
+            break;
+        }
+    }
+}
+
+fn run_proxy(args: &[String]) -> Result<i32, String> {
+    let (prefix, command, command_args) = match args {
+        [prefix, separator, command, rest @ ..] if separator == "--" => (prefix, command, rest),
+        _ => return Err(format!("用法: {} <前缀> -- <命令> [参数...]", PROXY_FLAG)),
+    };
+
+    let mut child = crate::claude_binary::create_command_with_env(command)
+        .args(command_args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .spawn()
+        .map_err(|e| format!("启动 MCP 服务器 '{}' 失败: {}", command, e))?;
+    let child_stdin = child.stdin.take().ok_or("无法获取服务器的 stdin")?;
+    let child_stdout = child.stdout.take().ok_or("无法获取服务器的 stdout")?;
+
+    // 引擎关闭 stdin 后服务器的 stdin 也随之关闭，服务器退出后 stdout 结束
+    let incoming_prefix = prefix.clone();
+    std::thread::spawn(move || {
+        pump(std::io::stdin().lock(), child_stdin, |message| {
+            strip_tool_call(message, &incoming_prefix)
+        })
+    });
+    pump(
+        BufReader::new(child_stdout),
+        std::io::stdout().lock(),
+        |message| prefix_tools(message, prefix),
+    );
+
+    let status = child
+        .wait()
+        .map_err(|e| format!("等待 MCP 服务器退出失败: {}", e))?;
+    Ok(status.code().unwrap_or(1))
+}
+
+/// 以代理模式启动时（第一个参数为 [`PROXY_FLAG`]）运行代理，返回退出码；否则返回 None
+pub fn run_proxy_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some(PROXY_FLAG) {
+        return None;
+    }
+    Some(run_proxy(&args[1..]).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        1
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_wrap_and_rewrite() {
+        let spec = json!({ "command": "npx", "args": ["-y", "server-git"], "env": { "A": "1" } });
+        let wrapped = wrap(&spec, "git_").unwrap();
+        assert_eq!(wrapped["args"][0], json!(PROXY_FLAG));
+        assert_eq!(wrapped["env"], json!({ "A": "1" }));
+        assert_eq!(unwrap(&wrapped), Some(("git_".to_string(), spec.clone())));
+        assert_eq!(unwrap(&spec), None);
+        assert!(wrap(
+            &json!({ "type": "http", "url": "https://x.example/mcp" }),
+            "x_"
+        )
+        .is_err());
+        assert!(validate_prefix("bad prefix").is_err());
+        assert_eq!(default_prefix("my.server"), "my_server_");
+
+        let mut list = json!({ "id": 1, "result": { "tools": [{ "name": "status" }] } });
+        prefix_tools(&mut list, "git_");
+        assert_eq!(list["result"]["tools"][0]["name"], json!("git_status"));
+
+        let mut call =
+            json!({ "id": 2, "method": "tools/call", "params": { "name": "git_status" } });
+        strip_tool_call(&mut call, "git_");
+        assert_eq!(call["params"]["name"], json!("status"));
+    }
+}
@@ -10,16 +10,33 @@
 //! ## 数据结构
 //! ```json
 //! {
+//!   "version": 2,       // 格式版本，见 migrations 模块
 //!   "servers": {
 //!     "server-id": {
 //!       "id": "server-id",
 //!       "name": "Server Name",
-//!       "server": { ... },  // 服务器配置
-//!       "enabled": true     // 启用状态
+//!       "server": { ... },  // 服务器配置（type 为 stdio、sse 或 http）
+//!       "enabled": { "claude": true, "gemini": false }  // 各引擎中的启用状态
 //!     }
 //!   }
 //! }
 //! ```
+//!
+//! ## 项目注册表
+//! 项目可以在 `<项目>/.anycode/mcp-registry.json` 中拥有自己的服务器（格式相同），
+//! 叠加在全局注册表之上：同 ID 时项目服务器优先，`overrides` 记录全局服务器在该
+//! 项目中的启用状态。只有某个项目需要的服务器（例如数据库）可以降级到项目注册表。
+//! 目前只有 Claude 支持项目级 MCP 配置，项目中的启用状态记录在 `claude` 下。
+//!
+//! ## 配置组（profile）
+//! `profiles` 保存命名的服务器组合（例如 "web-dev"、"minimal"）。应用到某个引擎时，
+//! 该引擎的 MCP 配置被整体替换为组内的服务器，`active_profiles` 记录各引擎当前
+//! 使用的配置组。
+//!
+//! ## 备份
+//! 每次写入前，旧文件会先复制到 `~/.anycode/mcp-registry-backups/`（保留最近
+//! 20 份），写入本身通过临时文件完成，错误的写入或格式变更不会丢失已配置的服务器。
+//! 写入后，各服务器的变化追加到审计日志（见 audit 模块）。
 
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -27,6 +44,16 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use super::health::{ServerHealth, ServerIntrospection};
+use super::migrations::{self, REGISTRY_VERSION};
+use super::sandbox::SandboxConfig;
+use super::supervisor::StartupConfig;
+use super::validation::validate_server_spec;
+use super::{AppType, McpTransport};
+
+/// 保留的备份数量
+const MAX_BACKUPS: usize = 20;
+
 /// 注册表中的服务器条目
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RegistryEntry {
@@ -34,18 +61,168 @@ pub struct RegistryEntry {
     pub id: String,
     /// 显示名称
     pub name: String,
+    /// 描述
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 标签，用于筛选和分组（小写、去重、排序）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
     /// 服务器配置（完整的 spec）
     pub server: Value,
-    /// 是否启用
-    pub enabled: bool,
+    /// 各引擎中的启用状态：引擎 -> 是否启用（未记录的引擎视为禁用）
+    #[serde(default)]
+    pub enabled: HashMap<String, bool>,
+    /// 最近一次健康检查的结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub health: Option<ServerHealth>,
+    /// 缓存的工具和资源列表
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introspection: Option<ServerIntrospection>,
+    /// 单独禁用的工具（同步时转换为各引擎的配置）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub disabled_tools: Vec<String>,
+    /// 工具名前缀，用于避免与其它服务器的工具重名（见 `prefix` 模块）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_prefix: Option<String>,
+    /// 启动策略和空闲超时（见 `supervisor` 模块）
+    #[serde(default, skip_serializing_if = "StartupConfig::is_default")]
+    pub startup: StartupConfig,
+    /// 资源限制和沙箱（见 `sandbox` 模块）
+    #[serde(default, skip_serializing_if = "SandboxConfig::is_default")]
+    pub sandbox: SandboxConfig,
+}
+
+/// 统一格式中表示禁用工具的字段，由各引擎的配置写入逻辑转换
+pub const DISABLED_TOOLS_KEY: &str = "disabledTools";
+
+impl RegistryEntry {
+    /// 在指定引擎中是否启用
+    pub fn is_enabled_for(&self, app: &AppType) -> bool {
+        self.enabled.get(app.as_str()).copied().unwrap_or(false)
+    }
+
+    /// 设置在指定引擎中的启用状态
+    pub fn set_enabled_for(&mut self, app: &AppType, enabled: bool) {
+        self.enabled.insert(app.as_str().to_string(), enabled);
+    }
+
+    /// 直接连接服务器（健康检查、内省、进程监督）时的规范：密钥替换为真实值，不经过桥接
+    pub fn launch_spec(&self) -> Result<Value, String> {
+        let spec = super::normalize_transport(&self.server);
+        super::secrets::resolve(&self.id, &spec)
+    }
+
+    /// 写入引擎配置时的规范：以注册表记录的禁用工具为准
+    ///
+    /// stdio 服务器通过桥接连接受监督的进程（见 `bridge` 模块），工具前缀、密钥和资源
+    /// 限制都由应用处理，引擎配置中只有服务器 ID。
+    pub fn engine_spec(&self) -> Result<Value, String> {
+        let spec = super::normalize_transport(&self.server);
+        let stdio = McpTransport::from_spec(&spec)? == McpTransport::Stdio;
+        let mut spec = match (stdio, &self.tool_prefix) {
+            (true, _) => super::bridge::wrap(&spec, &self.id)?,
+            (false, None) => self.launch_spec()?,
+            (false, Some(_)) => return Err("只有 stdio 服务器支持工具前缀".to_string()),
+        };
+        let prefix = self.tool_prefix.as_deref().unwrap_or_default();
+        if let Some(obj) = spec.as_object_mut() {
+            if self.disabled_tools.is_empty() {
+                obj.remove(DISABLED_TOOLS_KEY);
+            } else {
+                // 引擎看到的是加上前缀后的工具名
+                let disabled: Vec<String> = self
+                    .disabled_tools
+                    .iter()
+                    .map(|tool| format!("{}{}", prefix, tool))
+                    .collect();
+                obj.insert(DISABLED_TOOLS_KEY.to_string(), serde_json::json!(disabled));
+            }
+        }
+        Ok(spec)
+    }
+}
+
+/// 更新条目时保留已禁用的工具；新条目沿用规范中自带的列表（例如从引擎配置导入的）
+fn carried_disabled_tools(previous: Option<&RegistryEntry>, server: &Value) -> Vec<String> {
+    match previous {
+        Some(entry) => entry.disabled_tools.clone(),
+        None => server
+            .get(DISABLED_TOOLS_KEY)
+            .and_then(Value::as_array)
+            .map(|tools| tools.iter().filter_map(Value::as_str).map(str::to_string).collect())
+            .unwrap_or_default(),
+    }
 }
 
 /// MCP 服务器注册表
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct McpRegistry {
+    /// 格式版本
+    #[serde(default)]
+    pub version: u32,
     /// 服务器映射：id -> RegistryEntry
     #[serde(default)]
     pub servers: HashMap<String, RegistryEntry>,
+    /// 仅用于项目注册表：全局服务器在该项目中的启用状态
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub overrides: HashMap<String, bool>,
+    /// 配置组：名称 -> McpProfile
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub profiles: HashMap<String, McpProfile>,
+    /// 各引擎当前应用的配置组：引擎 -> 配置组名称
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub active_profiles: HashMap<String, String>,
+}
+
+impl Default for McpRegistry {
+    fn default() -> Self {
+        Self {
+            version: REGISTRY_VERSION,
+            servers: HashMap::new(),
+            overrides: HashMap::new(),
+            profiles: HashMap::new(),
+            active_profiles: HashMap::new(),
+        }
+    }
+}
+
+/// 命名的服务器组合，可一键应用到引擎
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpProfile {
+    /// 配置组名称
+    pub name: String,
+    /// 说明
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// 组内服务器 ID（按 ID 排序）
+    pub servers: Vec<String>,
+}
+
+/// 服务器所在的注册表
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistryScope {
+    Global,
+    Project,
+}
+
+/// 项目中生效的服务器条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScopedEntry {
+    #[serde(flatten)]
+    pub entry: RegistryEntry,
+    pub scope: RegistryScope,
+}
+
+/// 注册表备份
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegistryBackup {
+    /// 备份文件名
+    pub name: String,
+    /// 文件大小（字节）
+    pub size: u64,
+    /// 备份时间（Unix 时间戳，秒）
+    pub timestamp: i64,
 }
 
 /// 获取注册表文件路径
@@ -64,6 +241,27 @@ fn ensure_registry_dir() -> Result<(), String> {
     Ok(())
 }
 
+/// 获取备份目录
+fn backup_dir() -> PathBuf {
+    registry_path().with_file_name("mcp-registry-backups")
+}
+
+/// 解析注册表内容，必要时先迁移到当前版本
+///
+/// 返回注册表以及是否发生了迁移
+fn parse_registry(content: &str) -> Result<(McpRegistry, bool), String> {
+    if content.trim().is_empty() {
+        return Ok((McpRegistry::default(), false));
+    }
+
+    let mut value: Value = serde_json::from_str(content)
+        .map_err(|e| format!("解析注册表失败: {}", e))?;
+    let migrated = migrations::migrate(&mut value)?;
+    let registry = serde_json::from_value(value)
+        .map_err(|e| format!("解析注册表失败: {}", e))?;
+    Ok((registry, migrated))
+}
+
 /// 读取注册表
 pub fn read_registry() -> Result<McpRegistry, String> {
     let path = registry_path();
@@ -75,29 +273,137 @@ pub fn read_registry() -> Result<McpRegistry, String> {
     let content = fs::read_to_string(&path)
         .map_err(|e| format!("读取注册表失败: {}", e))?;
 
-    if content.trim().is_empty() {
-        return Ok(McpRegistry::default());
+    let (registry, migrated) = parse_registry(&content)?;
+    if migrated {
+        // 写回新格式（旧文件会先被备份）
+        if let Err(e) = write_registry(&registry) {
+            log::warn!("保存迁移后的注册表失败: {}", e);
+        }
     }
-
-    serde_json::from_str(&content)
-        .map_err(|e| format!("解析注册表失败: {}", e))
+    Ok(registry)
 }
 
-/// 写入注册表
-pub fn write_registry(registry: &McpRegistry) -> Result<(), String> {
-    ensure_registry_dir()?;
-
+/// 将当前注册表文件复制到备份目录，并清理多余的旧备份
+fn backup_registry() -> Result<(), String> {
     let path = registry_path();
-    let content = serde_json::to_string_pretty(registry)
+    if !path.exists() {
+        return Ok(());
+    }
+
+    let dir = backup_dir();
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("创建备份目录失败: {}", e))?;
+    let name = format!(
+        "mcp-registry-{}.json",
+        chrono::Utc::now().format("%Y%m%d-%H%M%S%.3f")
+    );
+    fs::copy(&path, dir.join(&name))
+        .map_err(|e| format!("备份注册表失败: {}", e))?;
+
+    // 文件名按时间排序，删除最旧的
+    let backups = list_backups()?;
+    for backup in backups.iter().skip(MAX_BACKUPS) {
+        if let Err(e) = fs::remove_file(dir.join(&backup.name)) {
+            log::warn!("删除旧备份 {} 失败: {}", backup.name, e);
+        }
+    }
+    Ok(())
+}
+
+/// 以当前格式版本写入注册表文件（通过临时文件原子替换）
+fn save_registry_file(path: &Path, registry: &McpRegistry) -> Result<(), String> {
+    let mut registry = registry.clone();
+    registry.version = REGISTRY_VERSION;
+    let content = serde_json::to_string_pretty(&registry)
         .map_err(|e| format!("序列化注册表失败: {}", e))?;
 
-    fs::write(&path, content)
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, content)
+        .map_err(|e| format!("写入注册表失败: {}", e))?;
+    fs::rename(&temp_path, path)
         .map_err(|e| format!("写入注册表失败: {}", e))?;
 
     log::info!("注册表已保存到: {}", path.display());
     Ok(())
 }
 
+/// 写入注册表
+///
+/// 先备份旧文件，再通过临时文件原子替换，最后把变化追加到审计日志
+pub fn write_registry(registry: &McpRegistry) -> Result<(), String> {
+    ensure_registry_dir()?;
+    let previous = fs::read_to_string(registry_path())
+        .ok()
+        .and_then(|content| parse_registry(&content).ok())
+        .map(|(previous, _)| previous)
+        .unwrap_or_default();
+    backup_registry()?;
+    save_registry_file(&registry_path(), registry)?;
+    super::audit::append(&super::audit::diff_registries(&previous, registry));
+    Ok(())
+}
+
+/// 列出注册表备份（最新的在前）
+pub fn list_backups() -> Result<Vec<RegistryBackup>, String> {
+    let dir = backup_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut backups: Vec<RegistryBackup> = fs::read_dir(&dir)
+        .map_err(|e| format!("读取备份目录失败: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if !is_backup_name(&name) {
+                return None;
+            }
+            let metadata = entry.metadata().ok()?;
+            let timestamp = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|duration| duration.as_secs() as i64)
+                .unwrap_or_default();
+            Some(RegistryBackup {
+                name,
+                size: metadata.len(),
+                timestamp,
+            })
+        })
+        .collect();
+    backups.sort_by(|a, b| b.name.cmp(&a.name));
+    Ok(backups)
+}
+
+fn is_backup_name(name: &str) -> bool {
+    name.starts_with("mcp-registry-")
+        && name.ends_with(".json")
+        && !name.contains(['/', '\\'])
+        && !name.contains("..")
+}
+
+/// 用指定备份替换当前注册表（当前注册表会先被备份，可再次恢复）
+///
+/// 返回恢复后的服务器数量
+pub fn restore_backup(name: &str) -> Result<usize, String> {
+    if !is_backup_name(name) {
+        return Err(format!("无效的备份名称: {}", name));
+    }
+
+    let content = fs::read_to_string(backup_dir().join(name))
+        .map_err(|e| format!("读取备份 {} 失败: {}", name, e))?;
+    let (registry, _) = parse_registry(&content)?;
+
+    write_registry(&registry)?;
+    log::info!(
+        "已从备份 {} 恢复注册表（{} 个服务器）",
+        name,
+        registry.servers.len()
+    );
+    Ok(registry.servers.len())
+}
+
 /// 获取指定引擎的所有服务器（包括禁用的）
 ///
 /// 返回格式：Vec<(id, spec, enabled)>
@@ -105,7 +411,7 @@ pub fn get_engine_servers_with_status(engine: &str) -> Result<Vec<(String, Value
     let registry = read_registry()?;
 
     // 从引擎配置文件读取当前启用的服务器
-    let app_type = super::AppType::from_str(engine)?;
+    let app_type = AppType::from_str(engine)?;
     let enabled_servers = super::import_from_app(&app_type).unwrap_or_default();
 
     let mut result: Vec<(String, Value, bool)> = Vec::new();
@@ -113,8 +419,8 @@ pub fn get_engine_servers_with_status(engine: &str) -> Result<Vec<(String, Value
 
     // 首先添加注册表中的所有服务器
     for (id, entry) in registry.servers.iter() {
-        // 检查是否在引擎配置中启用
-        let is_enabled = enabled_servers.contains_key(id);
+        // 以注册表中该引擎的启用状态为准（与引擎配置不一致时见 drift 模块）
+        let is_enabled = entry.is_enabled_for(&app_type);
 
         // 使用引擎配置中的 spec（如果存在），否则使用注册表中的
         let spec = enabled_servers.get(id).cloned().unwrap_or_else(|| entry.server.clone());
@@ -134,14 +440,44 @@ pub fn get_engine_servers_with_status(engine: &str) -> Result<Vec<(String, Value
 }
 
 /// 添加或更新服务器到注册表
-pub fn upsert_server(id: &str, name: &str, server: &Value, enabled: bool) -> Result<(), String> {
+///
+/// `enabled` 给出时更新该引擎中的启用状态，其它引擎保持不变（新服务器默认都禁用）。
+/// 启用时先验证配置；禁用时不验证，保证有问题的服务器总能被禁用。
+/// 不改变启用状态时，只要在任一引擎中启用就验证。
+pub fn upsert_server(
+    id: &str,
+    name: &str,
+    server: &Value,
+    enabled: Option<(&AppType, bool)>,
+) -> Result<(), String> {
     let mut registry = read_registry()?;
 
+    let previous = registry.servers.get(id);
+    let (enablement, validate) = updated_enablement(previous, enabled);
+    if validate {
+        validate_server_spec(server)?;
+    }
+
+    let disabled_tools = carried_disabled_tools(previous, server);
+    let mut server = super::normalize_transport(server);
+    // 改为远程服务器后前缀不再适用
+    let tool_prefix = previous
+        .and_then(|entry| entry.tool_prefix.clone())
+        .filter(|_| matches!(super::McpTransport::from_spec(&server), Ok(super::McpTransport::Stdio)));
+    super::secrets::protect(id, previous.map(|entry| &entry.server), &mut server)?;
     registry.servers.insert(id.to_string(), RegistryEntry {
         id: id.to_string(),
         name: name.to_string(),
-        server: server.clone(),
-        enabled,
+        server,
+        enabled: enablement,
+        health: None,
+        introspection: None,
+        disabled_tools,
+        tool_prefix,
+        startup: previous.map(|entry| entry.startup).unwrap_or_default(),
+        description: previous.and_then(|entry| entry.description.clone()),
+        tags: previous.map(|entry| entry.tags.clone()).unwrap_or_default(),
+        sandbox: previous.map(|entry| entry.sandbox.clone()).unwrap_or_default(),
     });
 
     write_registry(&registry)?;
@@ -149,26 +485,43 @@ pub fn upsert_server(id: &str, name: &str, server: &Value, enabled: bool) -> Res
     Ok(())
 }
 
+/// 更新后各引擎的启用状态，以及是否需要验证配置（规则见 [`upsert_server`]）
+fn updated_enablement(
+    previous: Option<&RegistryEntry>,
+    enabled: Option<(&AppType, bool)>,
+) -> (HashMap<String, bool>, bool) {
+    let mut enablement = previous.map(|entry| entry.enabled.clone()).unwrap_or_default();
+    let validate = match enabled {
+        Some((app, enabled)) => {
+            enablement.insert(app.as_str().to_string(), enabled);
+            enabled
+        }
+        None => enablement.values().any(|enabled| *enabled),
+    };
+    (enablement, validate)
+}
+
 /// 从注册表中删除服务器
 pub fn remove_server(id: &str) -> Result<(), String> {
     let mut registry = read_registry()?;
 
-    if registry.servers.remove(id).is_some() {
+    if let Some(entry) = registry.servers.remove(id) {
         write_registry(&registry)?;
+        super::secrets::forget(id, &entry.server);
         log::info!("服务器 '{}' 已从注册表中删除", id);
     }
 
     Ok(())
 }
 
-/// 更新服务器的启用状态
-pub fn set_server_enabled(id: &str, enabled: bool) -> Result<(), String> {
+/// 更新服务器在指定引擎中的启用状态
+pub fn set_server_enabled(id: &str, app: &AppType, enabled: bool) -> Result<(), String> {
     let mut registry = read_registry()?;
 
     if let Some(entry) = registry.servers.get_mut(id) {
-        entry.enabled = enabled;
+        entry.set_enabled_for(app, enabled);
         write_registry(&registry)?;
-        log::info!("服务器 '{}' 启用状态已更新为: {}", id, enabled);
+        log::info!("服务器 '{}' 在 {} 中的启用状态已更新为: {}", id, app.as_str(), enabled);
     }
 
     Ok(())
@@ -182,21 +535,725 @@ pub fn get_server(id: &str) -> Result<Option<RegistryEntry>, String> {
 
 /// 同步注册表与引擎配置
 ///
-/// 将注册表中启用的服务器同步到引擎配置文件
-pub fn sync_registry_to_engine(engine: &str) -> Result<(), String> {
+/// 将注册表中在该引擎启用的服务器同步到引擎配置文件，返回同步的服务器数量
+pub fn sync_registry_to_engine(engine: &str) -> Result<usize, String> {
     let registry = read_registry()?;
-    let app_type = super::AppType::from_str(engine)?;
+    let app_type = AppType::from_str(engine)?;
 
-    // 收集所有启用的服务器
+    // 收集在该引擎启用的服务器
     let enabled_servers: HashMap<String, Value> = registry.servers
         .iter()
-        .filter(|(_, entry)| entry.enabled)
-        .map(|(id, entry)| (id.clone(), entry.server.clone()))
-        .collect();
+        .filter(|(_, entry)| entry.is_enabled_for(&app_type))
+        .map(|(id, entry)| Ok((id.clone(), entry.engine_spec()?)))
+        .collect::<Result<_, String>>()?;
 
     // 同步到引擎配置
     super::sync_servers_to_app(&enabled_servers, &app_type)?;
 
     log::info!("已将 {} 个启用的服务器同步到 {} 引擎", enabled_servers.len(), engine);
+    Ok(enabled_servers.len())
+}
+
+/// 启用或禁用服务器中的单个工具，返回修改后的条目
+///
+/// 优先修改项目注册表（`project_path` 给出且服务器在其中），否则修改全局注册表。
+/// 只修改注册表，调用方负责重新同步到引擎配置。
+pub fn set_tool_enabled(
+    project_path: Option<&str>,
+    id: &str,
+    tool: &str,
+    enabled: bool,
+) -> Result<(RegistryScope, RegistryEntry), String> {
+    let toggle = |entry: &mut RegistryEntry| {
+        entry.disabled_tools.retain(|name| name != tool);
+        if !enabled {
+            entry.disabled_tools.push(tool.to_string());
+            entry.disabled_tools.sort();
+        }
+    };
+
+    if let Some(project_path) = project_path {
+        let mut project = read_project_registry(project_path)?;
+        if let Some(entry) = project.servers.get_mut(id) {
+            toggle(entry);
+            let entry = entry.clone();
+            write_project_registry(project_path, &project)?;
+            return Ok((RegistryScope::Project, entry));
+        }
+    }
+
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    toggle(entry);
+    let entry = entry.clone();
+    write_registry(&registry)?;
+    Ok((RegistryScope::Global, entry))
+}
+
+/// 设置或清除服务器的工具前缀，返回修改后的条目
+///
+/// 只修改全局注册表，调用方负责重新同步到引擎配置。
+pub fn set_tool_prefix(id: &str, prefix: Option<&str>) -> Result<RegistryEntry, String> {
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    if let Some(prefix) = prefix {
+        // 确认服务器可以通过代理启动
+        super::prefix::wrap(&super::normalize_transport(&entry.server), prefix)?;
+    }
+    entry.tool_prefix = prefix.map(str::to_string);
+    let entry = entry.clone();
+    write_registry(&registry)?;
+    log::info!("服务器 '{}' 的工具前缀已设置为: {:?}", id, prefix);
+    Ok(entry)
+}
+
+/// 设置服务器的启动策略和空闲超时（对之后启动的进程生效）
+pub fn set_startup_config(id: &str, startup: StartupConfig) -> Result<RegistryEntry, String> {
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    entry.startup = startup;
+    let entry = entry.clone();
+    write_registry(&registry)?;
+    log::info!("服务器 '{}' 的启动配置已更新: {:?}", id, startup);
+    Ok(entry)
+}
+
+/// 整理标签：去掉空白、转为小写、去重并排序
+pub fn normalize_tags(tags: &[String]) -> Vec<String> {
+    let mut tags: Vec<String> = tags
+        .iter()
+        .map(|tag| tag.trim().to_lowercase())
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// 设置服务器的描述和标签
+pub fn set_server_metadata(
+    id: &str,
+    description: Option<String>,
+    tags: &[String],
+) -> Result<RegistryEntry, String> {
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    entry.description = description
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty());
+    entry.tags = normalize_tags(tags);
+    let entry = entry.clone();
+    write_registry(&registry)?;
+    log::info!("服务器 '{}' 的描述和标签已更新", id);
+    Ok(entry)
+}
+
+/// 设置服务器的资源限制（对之后启动的进程生效）
+pub fn set_sandbox_config(id: &str, sandbox: SandboxConfig) -> Result<RegistryEntry, String> {
+    sandbox.validate()?;
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    log::info!("服务器 '{}' 的资源限制已更新: {:?}", id, sandbox);
+    entry.sandbox = sandbox;
+    let entry = entry.clone();
+    write_registry(&registry)?;
+    Ok(entry)
+}
+
+/// 记录服务器的健康检查结果
+///
+/// 优先写入项目注册表（`project_path` 给出且服务器在其中），否则写入全局注册表。
+/// 健康状态不是配置，写入时不产生备份，避免频繁检查挤掉真正的备份。
+/// 返回服务器是否在注册表中。
+pub fn set_server_health(
+    project_path: Option<&str>,
+    id: &str,
+    health: &ServerHealth,
+) -> Result<bool, String> {
+    update_entry_status(project_path, id, |entry| entry.health = Some(health.clone()))
+}
+
+/// 缓存服务器的工具和资源列表，规则同 [`set_server_health`]
+pub fn set_server_introspection(
+    project_path: Option<&str>,
+    id: &str,
+    introspection: &ServerIntrospection,
+) -> Result<bool, String> {
+    update_entry_status(project_path, id, |entry| {
+        entry.introspection = Some(introspection.clone());
+    })
+}
+
+/// 修改条目中的状态信息（非配置字段），不产生备份
+fn update_entry_status(
+    project_path: Option<&str>,
+    id: &str,
+    update: impl Fn(&mut RegistryEntry),
+) -> Result<bool, String> {
+    if let Some(project_path) = project_path {
+        let mut project = read_project_registry(project_path)?;
+        if let Some(entry) = project.servers.get_mut(id) {
+            update(entry);
+            write_project_registry(project_path, &project)?;
+            return Ok(true);
+        }
+    }
+
+    let mut registry = read_registry()?;
+    match registry.servers.get_mut(id) {
+        Some(entry) => {
+            update(entry);
+            ensure_registry_dir()?;
+            save_registry_file(&registry_path(), &registry)?;
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+// ============================================================================
+// 项目注册表
+// ============================================================================
+
+/// 获取项目注册表文件路径
+fn project_registry_path(project_path: &str) -> PathBuf {
+    Path::new(project_path)
+        .join(".anycode")
+        .join("mcp-registry.json")
+}
+
+/// 读取项目注册表（不存在时为空）
+pub fn read_project_registry(project_path: &str) -> Result<McpRegistry, String> {
+    let path = project_registry_path(project_path);
+
+    if !path.exists() {
+        return Ok(McpRegistry::default());
+    }
+
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("读取项目注册表失败: {}", e))?;
+
+    let (registry, migrated) = parse_registry(&content)?;
+    if migrated {
+        if let Err(e) = write_project_registry(project_path, &registry) {
+            log::warn!("保存迁移后的项目注册表失败: {}", e);
+        }
+    }
+    Ok(registry)
+}
+
+/// 写入项目注册表
+pub fn write_project_registry(project_path: &str, registry: &McpRegistry) -> Result<(), String> {
+    let path = project_registry_path(project_path);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建项目注册表目录失败: {}", e))?;
+    }
+    save_registry_file(&path, registry)
+}
+
+/// 获取项目中生效的所有服务器（全局与项目注册表合并，按 ID 排序）
+pub fn resolve_project_servers(project_path: &str) -> Result<Vec<ScopedEntry>, String> {
+    let global = read_registry()?;
+    let project = read_project_registry(project_path)?;
+    Ok(layer_registries(global, project))
+}
+
+/// 将项目注册表叠加在全局注册表之上
+fn layer_registries(global: McpRegistry, project: McpRegistry) -> Vec<ScopedEntry> {
+    let mut servers: HashMap<String, ScopedEntry> = global
+        .servers
+        .into_iter()
+        .map(|(id, mut entry)| {
+            if let Some(enabled) = project.overrides.get(&id) {
+                entry.set_enabled_for(&AppType::Claude, *enabled);
+            }
+            let scoped = ScopedEntry {
+                entry,
+                scope: RegistryScope::Global,
+            };
+            (id, scoped)
+        })
+        .collect();
+
+    // 同 ID 时项目服务器优先
+    for (id, entry) in project.servers {
+        servers.insert(
+            id,
+            ScopedEntry {
+                entry,
+                scope: RegistryScope::Project,
+            },
+        );
+    }
+
+    let mut servers: Vec<ScopedEntry> = servers.into_values().collect();
+    servers.sort_by(|a, b| a.entry.id.cmp(&b.entry.id));
+    servers
+}
+
+/// 添加或更新项目注册表中的服务器（`enabled` 为在该项目中是否启用），验证规则同 [`upsert_server`]
+pub fn upsert_project_server(
+    project_path: &str,
+    id: &str,
+    name: &str,
+    server: &Value,
+    enabled: bool,
+) -> Result<(), String> {
+    if enabled {
+        validate_server_spec(server)?;
+    }
+    let mut registry = read_project_registry(project_path)?;
+
+    registry.overrides.remove(id);
+    let previous = registry.servers.get(id);
+    let disabled_tools = carried_disabled_tools(previous, server);
+    let mut server = super::normalize_transport(server);
+    // 改为远程服务器后前缀不再适用
+    let tool_prefix = previous
+        .and_then(|entry| entry.tool_prefix.clone())
+        .filter(|_| matches!(super::McpTransport::from_spec(&server), Ok(super::McpTransport::Stdio)));
+    super::secrets::protect(id, previous.map(|entry| &entry.server), &mut server)?;
+    registry.servers.insert(id.to_string(), RegistryEntry {
+        id: id.to_string(),
+        name: name.to_string(),
+        server,
+        enabled: HashMap::from([(AppType::Claude.as_str().to_string(), enabled)]),
+        health: None,
+        introspection: None,
+        disabled_tools,
+        tool_prefix,
+        startup: previous.map(|entry| entry.startup).unwrap_or_default(),
+        description: previous.and_then(|entry| entry.description.clone()),
+        tags: previous.map(|entry| entry.tags.clone()).unwrap_or_default(),
+        sandbox: previous.map(|entry| entry.sandbox.clone()).unwrap_or_default(),
+    });
+
+    write_project_registry(project_path, &registry)?;
+    log::info!("服务器 '{}' 已添加到项目注册表: {}", id, project_path);
+    Ok(())
+}
+
+/// 将全局服务器降级到项目注册表，之后只在该项目中出现
+pub fn demote_server(project_path: &str, id: &str) -> Result<RegistryEntry, String> {
+    let mut global = read_registry()?;
+    let entry = global
+        .servers
+        .get(id)
+        .cloned()
+        .ok_or_else(|| format!("全局注册表中不存在服务器 '{}'", id))?;
+
+    // 先写入项目注册表，失败时全局注册表保持不变
+    let mut project = read_project_registry(project_path)?;
+    project.overrides.remove(id);
+    project.servers.insert(id.to_string(), entry.clone());
+    write_project_registry(project_path, &project)?;
+
+    global.servers.remove(id);
+    write_registry(&global)?;
+
+    log::info!("服务器 '{}' 已从全局注册表移到项目: {}", id, project_path);
+    Ok(entry)
+}
+
+/// 将项目服务器提升到全局注册表
+pub fn promote_server(project_path: &str, id: &str) -> Result<RegistryEntry, String> {
+    let mut project = read_project_registry(project_path)?;
+    let entry = project
+        .servers
+        .get(id)
+        .cloned()
+        .ok_or_else(|| format!("项目注册表中不存在服务器 '{}'", id))?;
+
+    // 先写入全局注册表，失败时项目注册表保持不变
+    let mut global = read_registry()?;
+    global.servers.insert(id.to_string(), entry.clone());
+    write_registry(&global)?;
+
+    project.servers.remove(id);
+    write_project_registry(project_path, &project)?;
+
+    log::info!("服务器 '{}' 已从项目 {} 提升到全局注册表", id, project_path);
+    Ok(entry)
+}
+
+/// 设置服务器在项目中的启用状态
+///
+/// 项目服务器直接修改其条目；全局服务器记录为项目内的覆盖值
+pub fn set_project_server_enabled(
+    project_path: &str,
+    id: &str,
+    enabled: bool,
+) -> Result<(), String> {
+    let mut project = read_project_registry(project_path)?;
+
+    if let Some(entry) = project.servers.get_mut(id) {
+        entry.set_enabled_for(&AppType::Claude, enabled);
+    } else if read_registry()?.servers.contains_key(id) {
+        project.overrides.insert(id.to_string(), enabled);
+    } else {
+        return Err(format!("注册表中不存在服务器 '{}'", id));
+    }
+
+    write_project_registry(project_path, &project)?;
+    log::info!(
+        "服务器 '{}' 在项目 {} 中的启用状态已更新为: {}",
+        id, project_path, enabled
+    );
+    Ok(())
+}
+
+// ============================================================================
+// 配置组
+// ============================================================================
+
+/// 列出所有配置组（按名称排序）
+pub fn list_profiles() -> Result<Vec<McpProfile>, String> {
+    let mut profiles: Vec<McpProfile> = read_registry()?.profiles.into_values().collect();
+    profiles.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(profiles)
+}
+
+/// 创建或更新配置组，组内服务器必须已在注册表中
+pub fn create_profile(
+    name: &str,
+    description: Option<String>,
+    servers: &[String],
+) -> Result<McpProfile, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("配置组名称不能为空".to_string());
+    }
+
+    let mut registry = read_registry()?;
+    if let Some(missing) = servers.iter().find(|id| !registry.servers.contains_key(*id)) {
+        return Err(format!("注册表中不存在服务器 '{}'", missing));
+    }
+
+    let mut servers = servers.to_vec();
+    servers.sort();
+    servers.dedup();
+    let profile = McpProfile {
+        name: name.to_string(),
+        description: description.filter(|d| !d.trim().is_empty()),
+        servers,
+    };
+    registry.profiles.insert(name.to_string(), profile.clone());
+
+    write_registry(&registry)?;
+    log::info!("配置组 '{}' 已保存（{} 个服务器）", name, profile.servers.len());
+    Ok(profile)
+}
+
+/// 删除配置组
+pub fn delete_profile(name: &str) -> Result<(), String> {
+    let mut registry = read_registry()?;
+    if registry.profiles.remove(name).is_none() {
+        return Err(format!("配置组 '{}' 不存在", name));
+    }
+    registry.active_profiles.retain(|_, active| active != name);
+
+    write_registry(&registry)?;
+    log::info!("配置组 '{}' 已删除", name);
+    Ok(())
+}
+
+/// 将配置组应用到引擎：引擎的 MCP 配置替换为组内的服务器
+///
+/// 引擎中原有但不在注册表中的服务器会先加入注册表，切换配置组不会丢失它们。
+/// 注册表中该引擎的启用状态随之更新：组内的服务器启用，其余禁用。
+/// 返回写入引擎的服务器数量。
+pub fn apply_profile(name: &str, engine: &str) -> Result<usize, String> {
+    let app_type = AppType::from_str(engine)?;
+    let mut registry = read_registry()?;
+    let profile = registry
+        .profiles
+        .get(name)
+        .cloned()
+        .ok_or_else(|| format!("配置组 '{}' 不存在", name))?;
+
+    let engine_servers = super::import_from_app(&app_type)?;
+    let servers = switch_profile(&mut registry, &profile, &app_type, engine_servers)?;
+
+    // 先记录注册表，确保引擎原有的服务器已保存
+    write_registry(&registry)?;
+    super::sync_servers_to_app(&servers, &app_type)?;
+
+    log::info!("已将配置组 '{}' 应用到 {} 引擎（{} 个服务器）", name, engine, servers.len());
+    Ok(servers.len())
+}
+
+/// 在注册表中为引擎切换配置组，返回要写入引擎的服务器
+///
+/// `engine_servers` 为引擎当前的 MCP 配置，其中不在注册表中的服务器会加入注册表
+fn switch_profile(
+    registry: &mut McpRegistry,
+    profile: &McpProfile,
+    app_type: &AppType,
+    engine_servers: HashMap<String, Value>,
+) -> Result<HashMap<String, Value>, String> {
+    let engine = app_type.as_str();
+    for (id, spec) in engine_servers {
+        registry.servers.entry(id.clone()).or_insert_with(|| {
+            log::info!("引擎 {} 中的服务器 '{}' 不在注册表中，已保存", engine, id);
+            RegistryEntry {
+                id: id.clone(),
+                name: id.clone(),
+                disabled_tools: carried_disabled_tools(None, &spec),
+                server: spec,
+                enabled: HashMap::new(),
+                health: None,
+                introspection: None,
+                tool_prefix: None,
+                startup: StartupConfig::default(),
+                description: None,
+                tags: Vec::new(),
+                sandbox: SandboxConfig::default(),
+            }
+        });
+    }
+    for (id, entry) in registry.servers.iter_mut() {
+        entry.set_enabled_for(app_type, profile.servers.contains(id));
+    }
+
+    let mut servers: HashMap<String, Value> = HashMap::new();
+    for id in &profile.servers {
+        match registry.servers.get(id) {
+            Some(entry) => {
+                servers.insert(id.clone(), entry.engine_spec()?);
+            }
+            None => log::warn!("配置组 '{}' 中的服务器 '{}' 已不在注册表中，跳过", profile.name, id),
+        }
+    }
+
+    registry
+        .active_profiles
+        .insert(engine.to_string(), profile.name.clone());
+    Ok(servers)
+}
+
+// ============================================================================
+// 密钥
+// ============================================================================
+
+/// 将服务器配置中的 env/headers 字段标记为密钥（值移入系统钥匙串）
+///
+/// 写入时不产生新备份，并把已有备份中该字段的明文替换为占位符
+pub fn mark_secret(id: &str, block: &str, key: &str, value: Option<&str>) -> Result<(), String> {
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    super::secrets::mark(id, &mut entry.server, block, key, value)?;
+
+    ensure_registry_dir()?;
+    save_registry_file(&registry_path(), &registry)?;
+    scrub_backups(id, block, key);
     Ok(())
 }
+
+/// 取消密钥标记，值写回注册表
+pub fn unmark_secret(id: &str, block: &str, key: &str) -> Result<(), String> {
+    let mut registry = read_registry()?;
+    let entry = registry
+        .servers
+        .get_mut(id)
+        .ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    super::secrets::unmark(id, &mut entry.server, block, key)?;
+
+    write_registry(&registry)
+}
+
+/// 将备份中某个字段的明文替换为密钥占位符
+fn scrub_backups(id: &str, block: &str, key: &str) {
+    let escape = |part: &str| part.replace('~', "~0").replace('/', "~1");
+    let pointer = format!(
+        "/servers/{}/server/{}/{}",
+        escape(id),
+        escape(block),
+        escape(key)
+    );
+
+    let Ok(backups) = list_backups() else {
+        return;
+    };
+    for backup in backups {
+        let path = backup_dir().join(&backup.name);
+        let Some(mut value) = fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        else {
+            continue;
+        };
+        match value.pointer_mut(&pointer) {
+            Some(slot) if slot.is_string() && !super::secrets::is_marker(slot) => {
+                *slot = Value::from(super::secrets::SECRET_MARKER);
+            }
+            _ => continue,
+        }
+        let result = serde_json::to_string_pretty(&value)
+            .map_err(|e| e.to_string())
+            .and_then(|content| fs::write(&path, content).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            log::warn!("清除备份 {} 中的密钥失败: {}", backup.name, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: &str, enabled: &[(&str, bool)]) -> RegistryEntry {
+        let enabled: HashMap<String, bool> = enabled
+            .iter()
+            .map(|(app, enabled)| (app.to_string(), *enabled))
+            .collect();
+        serde_json::from_value(json!({
+            "id": id,
+            "name": id,
+            "server": { "type": "stdio", "command": "npx", "args": ["-y", id] },
+            "enabled": enabled,
+        }))
+        .unwrap()
+    }
+
+    fn registry(entries: Vec<RegistryEntry>) -> McpRegistry {
+        McpRegistry {
+            servers: entries
+                .into_iter()
+                .map(|entry| (entry.id.clone(), entry))
+                .collect(),
+            ..McpRegistry::default()
+        }
+    }
+
+    #[test]
+    fn test_project_registry_layers_over_global() {
+        let dir = tempfile::tempdir().unwrap();
+        let project_path = dir.path().to_string_lossy().to_string();
+        assert!(read_project_registry(&project_path)
+            .unwrap()
+            .servers
+            .is_empty());
+
+        let mut project = registry(vec![
+            entry("db", &[("claude", true)]),
+            entry("shared", &[("claude", false)]),
+        ]);
+        project.overrides.insert("github".to_string(), false);
+        write_project_registry(&project_path, &project).unwrap();
+        let project = read_project_registry(&project_path).unwrap();
+        assert_eq!(project.overrides.get("github"), Some(&false));
+
+        let global = registry(vec![
+            entry("github", &[("claude", true), ("codex", true)]),
+            entry("shared", &[("claude", true)]),
+        ]);
+        let servers = layer_registries(global, project);
+        let scopes: Vec<(&str, RegistryScope)> = servers
+            .iter()
+            .map(|scoped| (scoped.entry.id.as_str(), scoped.scope))
+            .collect();
+        assert_eq!(
+            scopes,
+            vec![
+                ("db", RegistryScope::Project),
+                ("github", RegistryScope::Global),
+                ("shared", RegistryScope::Project),
+            ]
+        );
+        // 覆盖值只作用于 Claude
+        assert!(!servers[1].entry.is_enabled_for(&AppType::Claude));
+        assert!(servers[1].entry.is_enabled_for(&AppType::Codex));
+        assert!(!servers[2].entry.is_enabled_for(&AppType::Claude));
+    }
+
+    #[test]
+    fn test_switch_profile() {
+        let mut registry = registry(vec![
+            entry("github", &[("claude", false), ("codex", true)]),
+            entry("playwright", &[("claude", true)]),
+        ]);
+        let profile = McpProfile {
+            name: "web-dev".to_string(),
+            description: None,
+            servers: vec!["github".to_string(), "removed".to_string()],
+        };
+        // 引擎中有注册表未记录的服务器
+        let engine_servers = HashMap::from([(
+            "local".to_string(),
+            json!({ "type": "http", "url": "https://mcp.example.com/mcp" }),
+        )]);
+
+        let servers =
+            switch_profile(&mut registry, &profile, &AppType::Claude, engine_servers).unwrap();
+        assert_eq!(servers.len(), 1);
+        assert_eq!(
+            crate::mcp::bridge::unwrap(&servers["github"]).as_deref(),
+            Some("github")
+        );
+
+        assert!(registry.servers["github"].is_enabled_for(&AppType::Claude));
+        assert!(!registry.servers["playwright"].is_enabled_for(&AppType::Claude));
+        assert!(!registry.servers["local"].is_enabled_for(&AppType::Claude));
+        // 其它引擎的启用状态不变
+        assert!(registry.servers["github"].is_enabled_for(&AppType::Codex));
+        assert_eq!(
+            registry.active_profiles.get("claude").map(String::as_str),
+            Some("web-dev")
+        );
+    }
+
+    #[test]
+    fn test_enablement_per_engine() {
+        // 新服务器只在指定的引擎中启用
+        let (enablement, validate) = updated_enablement(None, Some((&AppType::Codex, true)));
+        assert_eq!(enablement, HashMap::from([("codex".to_string(), true)]));
+        assert!(validate);
+
+        // 禁用不验证配置，其它引擎的状态保持不变
+        let github = entry("github", &[("claude", true), ("gemini", true)]);
+        let (enablement, validate) =
+            updated_enablement(Some(&github), Some((&AppType::Claude, false)));
+        assert_eq!(enablement.get("claude"), Some(&false));
+        assert_eq!(enablement.get("gemini"), Some(&true));
+        assert!(!validate);
+        assert!(updated_enablement(Some(&github), None).1);
+        assert!(!updated_enablement(Some(&entry("off", &[])), None).1);
+
+        // 旧格式的布尔启用状态读取后对每个引擎生效
+        let (registry, migrated) = parse_registry(
+            &json!({
+                "version": 1,
+                "servers": {
+                    "docs": { "id": "docs", "name": "docs", "server": { "command": "npx" }, "enabled": true }
+                }
+            })
+            .to_string(),
+        )
+        .unwrap();
+        assert!(migrated);
+        let docs = &registry.servers["docs"];
+        assert!(docs.is_enabled_for(&AppType::Claude));
+        assert!(docs.is_enabled_for(&AppType::Gemini));
+        assert!(!docs.is_enabled_for(&AppType::Ollama));
+    }
+}
@@ -0,0 +1,410 @@
+//! 多个引擎会话共享一个 MCP 服务器进程时的 JSON-RPC 消息路由
+//!
+//! 受监督的服务器进程（[`super::supervisor`]）可能同时连接多个桥接会话
+//! （[`super::bridge`]）。路由负责：
+//! - 请求 ID：各会话的请求改用路由分配的 ID 发给服务器，响应按原 ID 返回给发起的会话
+//! - 初始化：`initialize` 只发给服务器一次，结果缓存后直接回复之后连接的会话；
+//!   `notifications/initialized` 也只转发一次
+//! - 服务器发起的请求交给最近活跃的会话，服务器的通知发给所有会话
+//! - 进程重启后用第一次的参数重新初始化，期间的消息暂存，初始化完成后再发送；
+//!   尚未响应的请求返回错误
+//! - 设置了工具前缀时改写工具名（见 [`super::prefix`]）
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tokio::sync::mpsc::UnboundedSender;
+
+use super::prefix::{prefix_tools, strip_tool_call};
+
+/// JSON-RPC 内部错误码
+const INTERNAL_ERROR: i64 = -32603;
+
+enum Init {
+    /// 还没有发送 initialize
+    Idle,
+    /// 等待服务器响应；`internal` 表示是重启后由路由自己发起的
+    InFlight {
+        server_id: u64,
+        waiters: Vec<(u64, Value)>,
+        internal: bool,
+    },
+    /// 已初始化，缓存服务器返回的结果
+    Ready(Value),
+}
+
+pub(super) struct Router {
+    prefix: Option<String>,
+    /// 写入服务器 stdin 的队列；进程未运行时为空
+    server: Option<UnboundedSender<String>>,
+    clients: HashMap<u64, UnboundedSender<String>>,
+    next_client: u64,
+    next_id: u64,
+    /// 发给服务器的请求 ID -> (会话, 原请求 ID)
+    pending: HashMap<u64, (u64, Value)>,
+    init: Init,
+    /// 第一次 initialize 的参数，重启后重新初始化时使用
+    init_params: Option<Value>,
+    /// 是否已向当前进程发送 `notifications/initialized`
+    initialized: bool,
+    /// 等待进程启动或初始化完成的会话消息
+    held: Vec<(u64, Value)>,
+    last_active: Option<u64>,
+}
+
+impl Router {
+    pub(super) fn new(prefix: Option<String>) -> Self {
+        Self {
+            prefix,
+            server: None,
+            clients: HashMap::new(),
+            next_client: 0,
+            next_id: 0,
+            pending: HashMap::new(),
+            init: Init::Idle,
+            init_params: None,
+            initialized: false,
+            held: Vec::new(),
+            last_active: None,
+        }
+    }
+
+    /// 连接一个会话，返回会话 ID
+    pub(super) fn add_client(&mut self, sender: UnboundedSender<String>) -> u64 {
+        self.next_client += 1;
+        self.clients.insert(self.next_client, sender);
+        self.next_client
+    }
+
+    /// 断开会话，丢弃它尚未完成的请求；返回剩余的会话数
+    pub(super) fn remove_client(&mut self, client: u64) -> usize {
+        self.clients.remove(&client);
+        self.pending.retain(|_, (owner, _)| *owner != client);
+        self.held.retain(|(owner, _)| *owner != client);
+        if let Init::InFlight { waiters, .. } = &mut self.init {
+            waiters.retain(|(owner, _)| *owner != client);
+        }
+        if self.last_active == Some(client) {
+            self.last_active = None;
+        }
+        self.clients.len()
+    }
+
+    pub(super) fn client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    /// 断开所有会话（服务器停止时），会话的连接随之关闭
+    pub(super) fn disconnect_all(&mut self) {
+        self.clients.clear();
+        self.pending.clear();
+        self.held.clear();
+        self.last_active = None;
+    }
+
+    /// 新进程已启动：重新初始化（之前初始化过时），然后发送暂存的消息
+    pub(super) fn attach_server(&mut self, server: UnboundedSender<String>) {
+        self.server = Some(server);
+        match self.init_params.clone() {
+            Some(params) => {
+                let server_id = self.next_id();
+                self.send(json!({
+                    "jsonrpc": "2.0",
+                    "id": server_id,
+                    "method": "initialize",
+                    "params": params,
+                }));
+                self.init = Init::InFlight {
+                    server_id,
+                    waiters: Vec::new(),
+                    internal: true,
+                };
+            }
+            None => self.flush(),
+        }
+    }
+
+    /// 进程已退出：尚未响应的请求返回错误
+    pub(super) fn detach_server(&mut self) {
+        self.server = None;
+        self.initialized = false;
+        let waiters = match std::mem::replace(&mut self.init, Init::Idle) {
+            Init::InFlight { waiters, .. } => waiters,
+            _ => Vec::new(),
+        };
+        let pending: Vec<(u64, Value)> = self.pending.drain().map(|(_, request)| request).collect();
+        for (client, id) in waiters.into_iter().chain(pending) {
+            self.reply_error(
+                client,
+                id,
+                json!({ "code": INTERNAL_ERROR, "message": "MCP 服务器进程已退出" }),
+            );
+        }
+    }
+
+    /// 处理会话发来的消息
+    pub(super) fn from_client(&mut self, client: u64, mut message: Value) {
+        if !self.clients.contains_key(&client) {
+            return;
+        }
+        self.last_active = Some(client);
+        if let Some(prefix) = &self.prefix {
+            strip_tool_call(&mut message, prefix);
+        }
+        let method = message
+            .get("method")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        let id = message.get("id").cloned();
+
+        match (method.as_deref(), id) {
+            (Some("initialize"), Some(id)) => self.initialize(client, id, message),
+            _ if !self.ready() => self.held.push((client, message)),
+            (Some("notifications/initialized"), None) => {
+                if !self.initialized {
+                    self.initialized = true;
+                    self.send(message);
+                }
+            }
+            (Some("notifications/cancelled"), None) => {
+                let request = message.pointer("/params/requestId").cloned();
+                let server_id = self
+                    .pending
+                    .iter()
+                    .find(|(_, (owner, id))| *owner == client && Some(id) == request.as_ref())
+                    .map(|(server_id, _)| *server_id);
+                if let Some(server_id) = server_id {
+                    message["params"]["requestId"] = Value::from(server_id);
+                    self.send(message);
+                }
+            }
+            (Some(_), Some(id)) => {
+                let server_id = self.next_id();
+                message["id"] = Value::from(server_id);
+                self.pending.insert(server_id, (client, id));
+                self.send(message);
+            }
+            // 其它通知，以及对服务器请求的响应（ID 由服务器分配，原样转发）
+            (Some(_), None) | (None, Some(_)) => self.send(message),
+            (None, None) => {}
+        }
+    }
+
+    /// 处理服务器发来的消息
+    pub(super) fn from_server(&mut self, mut message: Value) {
+        let id = message.get("id").cloned();
+        if message.get("method").is_some() {
+            match id {
+                // 服务器发起的请求（例如 sampling）交给最近活跃的会话
+                Some(id) => match self
+                    .last_active
+                    .filter(|client| self.clients.contains_key(client))
+                {
+                    Some(client) => self.to_client(client, &message),
+                    None => self.send(json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": INTERNAL_ERROR, "message": "没有可以处理该请求的会话" },
+                    })),
+                },
+                None => {
+                    for client in self.clients.keys().copied().collect::<Vec<_>>() {
+                        self.to_client(client, &message);
+                    }
+                }
+            }
+            return;
+        }
+
+        let Some(server_id) = id.as_ref().and_then(Value::as_u64) else {
+            return;
+        };
+        if matches!(&self.init, Init::InFlight { server_id: init_id, .. } if *init_id == server_id)
+        {
+            self.finish_initialize(message);
+            return;
+        }
+        let Some((client, id)) = self.pending.remove(&server_id) else {
+            return;
+        };
+        if let Some(prefix) = &self.prefix {
+            prefix_tools(&mut message, prefix);
+        }
+        message["id"] = id;
+        self.to_client(client, &message);
+    }
+
+    fn ready(&self) -> bool {
+        self.server.is_some() && !matches!(self.init, Init::InFlight { .. })
+    }
+
+    fn initialize(&mut self, client: u64, id: Value, mut message: Value) {
+        match &mut self.init {
+            Init::Ready(result) => {
+                let response = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                self.to_client(client, &response);
+            }
+            Init::InFlight { waiters, .. } => waiters.push((client, id)),
+            Init::Idle if self.server.is_none() => self.held.push((client, message)),
+            Init::Idle => {
+                self.init_params = message.get("params").cloned();
+                let server_id = self.next_id();
+                message["id"] = Value::from(server_id);
+                self.send(message);
+                self.init = Init::InFlight {
+                    server_id,
+                    waiters: vec![(client, id)],
+                    internal: false,
+                };
+            }
+        }
+    }
+
+    fn finish_initialize(&mut self, response: Value) {
+        let Init::InFlight {
+            waiters, internal, ..
+        } = std::mem::replace(&mut self.init, Init::Idle)
+        else {
+            return;
+        };
+        match response.get("result") {
+            Some(result) => {
+                for (client, id) in waiters {
+                    let reply = json!({ "jsonrpc": "2.0", "id": id, "result": result });
+                    self.to_client(client, &reply);
+                }
+                self.init = Init::Ready(result.clone());
+                // 会话已经完成过初始化，不会再发送这个通知
+                if internal && !self.initialized {
+                    self.initialized = true;
+                    self.send(json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }));
+                }
+            }
+            None => {
+                let error = response.get("error").cloned().unwrap_or_else(
+                    || json!({ "code": INTERNAL_ERROR, "message": "MCP 服务器初始化失败" }),
+                );
+                for (client, id) in waiters {
+                    self.reply_error(client, id, error.clone());
+                }
+            }
+        }
+        self.flush();
+    }
+
+    /// 重新处理暂存的消息
+    fn flush(&mut self) {
+        for (client, message) in std::mem::take(&mut self.held) {
+            self.from_client(client, message);
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        self.next_id += 1;
+        self.next_id
+    }
+
+    fn send(&mut self, message: Value) {
+        match &self.server {
+            Some(server) => {
+                let _ = server.send(message.to_string());
+            }
+            None => log::debug!("MCP 服务器进程未运行，丢弃消息: {}", message),
+        }
+    }
+
+    fn to_client(&self, client: u64, message: &Value) {
+        if let Some(sender) = self.clients.get(&client) {
+            let _ = sender.send(message.to_string());
+        }
+    }
+
+    fn reply_error(&self, client: u64, id: Value, error: Value) {
+        self.to_client(
+            client,
+            &json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
+
+    fn next(rx: &mut UnboundedReceiver<String>) -> Value {
+        serde_json::from_str(&rx.try_recv().expect("没有消息")).unwrap()
+    }
+
+    #[test]
+    fn test_shared_server() {
+        let mut router = Router::new(Some("git_".to_string()));
+        let (server_tx, mut server) = unbounded_channel();
+        router.attach_server(server_tx);
+        let (a_tx, mut a) = unbounded_channel();
+        let (b_tx, mut b) = unbounded_channel();
+        let client_a = router.add_client(a_tx);
+        let client_b = router.add_client(b_tx);
+
+        // 只有第一个 initialize 发给服务器，第二个使用缓存的结果
+        router.from_client(
+            client_a,
+            json!({ "id": 1, "method": "initialize", "params": { "v": 1 } }),
+        );
+        let init = next(&mut server);
+        router.from_server(json!({ "id": init["id"], "result": { "serverInfo": {} } }));
+        assert_eq!(
+            next(&mut a),
+            json!({ "jsonrpc": "2.0", "id": 1, "result": { "serverInfo": {} } })
+        );
+        router.from_client(client_b, json!({ "id": 1, "method": "initialize" }));
+        assert_eq!(next(&mut b)["result"], json!({ "serverInfo": {} }));
+        assert!(server.try_recv().is_err());
+
+        router.from_client(client_a, json!({ "method": "notifications/initialized" }));
+        router.from_client(client_b, json!({ "method": "notifications/initialized" }));
+        assert_eq!(
+            next(&mut server)["method"],
+            json!("notifications/initialized")
+        );
+        assert!(server.try_recv().is_err());
+
+        // 两个会话的同一个请求 ID 不会冲突，工具名按前缀改写
+        router.from_client(client_a, json!({ "id": 2, "method": "tools/list" }));
+        router.from_client(
+            client_b,
+            json!({ "id": 2, "method": "tools/call", "params": { "name": "git_status" } }),
+        );
+        let list = next(&mut server);
+        let call = next(&mut server);
+        assert_ne!(list["id"], call["id"]);
+        assert_eq!(call["params"]["name"], json!("status"));
+        router.from_server(json!({ "id": call["id"], "result": {} }));
+        router.from_server(
+            json!({ "id": list["id"], "result": { "tools": [{ "name": "status" }] } }),
+        );
+        assert_eq!(next(&mut b), json!({ "id": 2, "result": {} }));
+        let tools = next(&mut a);
+        assert_eq!(tools["id"], json!(2));
+        assert_eq!(tools["result"]["tools"][0]["name"], json!("git_status"));
+
+        // 进程重启：未完成的请求返回错误，新进程用原参数重新初始化后再发送暂存的消息
+        router.from_client(client_a, json!({ "id": 3, "method": "tools/list" }));
+        next(&mut server);
+        router.detach_server();
+        assert!(next(&mut a)["error"].is_object());
+        router.from_client(client_b, json!({ "id": 4, "method": "tools/list" }));
+        let (server_tx, mut server) = unbounded_channel();
+        router.attach_server(server_tx);
+        let init = next(&mut server);
+        assert_eq!(init["params"], json!({ "v": 1 }));
+        assert!(server.try_recv().is_err());
+        router.from_server(json!({ "id": init["id"], "result": {} }));
+        assert_eq!(
+            next(&mut server)["method"],
+            json!("notifications/initialized")
+        );
+        assert_eq!(next(&mut server)["method"], json!("tools/list"));
+
+        assert_eq!(router.remove_client(client_a), 1);
+    }
+}
@@ -0,0 +1,299 @@
+//! MCP 服务器进程的资源限制和沙箱
+//!
+//! 注册表条目中的 [`SandboxConfig`] 由监督模块（[`super::supervisor`]）和桥接进程
+//! （[`super::bridge`]）在启动服务器时执行，全局的 MCP 资源限制
+//! （[`crate::process::limits`]）同时生效，内存上限取两者中较小的一个：
+//! - 内存和 CPU 时间：Unix 在 fork 之后、exec 之前设置 RLIMIT_DATA / RLIMIT_CPU，
+//!   服务器启动的子进程一并继承。macOS 不执行内存限制，设置了内存上限时拒绝启动。
+//!   Windows 在启动后立即把进程放入带内存和 CPU 上限的 Job Object（进程在此之前
+//!   启动的子进程不受限制）；Windows 不支持 CPU 时间上限，设置了时拒绝启动
+//! - 优先级：Unix 在 exec 之前设置 nice 值，Windows 使用低于正常 / 空闲优先级类
+//! - 启动目录：进程在指定目录中启动，`cwd` 和参数中的路径（相对路径按工作目录
+//!   解析）在解析符号链接后不能指向目录之外。这只是启动前的检查，不是操作系统级的
+//!   文件系统隔离，服务器运行时仍可访问目录之外的文件
+//! - 环境变量白名单：只传递运行必需的变量、服务器配置中的 `env` 和白名单中的变量
+//!
+//! 限制无法生效时不启动服务器。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+use crate::process::limits::{self, ResourceLimits};
+use crate::process::JobObject;
+
+/// 最低的优先级（Unix nice 值）
+const MAX_NICE: i32 = 19;
+
+/// 服务器的资源限制，保存在注册表条目中；字段为空表示不限制
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SandboxConfig {
+    /// 内存上限（MB）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_memory_mb: Option<u64>,
+    /// nice 值（0-19，越大优先级越低）；Windows 上 1-9 为低于正常，10 以上为空闲
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nice: Option<i32>,
+    /// 在这个目录中启动，启动前检查 `cwd` 和参数中的路径不在目录之外；
+    /// 不限制服务器运行时访问的文件（旧配置中名为 `confineTo`）
+    #[serde(default, alias = "confineTo", skip_serializing_if = "Option::is_none")]
+    pub launch_root: Option<String>,
+    /// 允许从应用继承的环境变量；为空时继承方式不变
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_allowlist: Option<Vec<String>>,
+}
+
+impl SandboxConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// 检查配置是否有效
+    pub fn validate(&self) -> Result<(), String> {
+        if self.max_memory_mb == Some(0) {
+            return Err("内存上限必须大于 0".to_string());
+        }
+        if let Some(nice) = self.nice {
+            if !(0..=MAX_NICE).contains(&nice) {
+                return Err(format!(
+                    "nice 值应在 0 到 {} 之间，当前为 {}",
+                    MAX_NICE, nice
+                ));
+            }
+        }
+        if let Some(dir) = &self.launch_root {
+            let path = Path::new(dir);
+            if !path.is_absolute() {
+                return Err(format!("启动目录 '{}' 必须是绝对路径", dir));
+            }
+            if !path.is_dir() {
+                return Err(format!("启动目录 '{}' 不存在", dir));
+            }
+        }
+        if let Some(names) = &self.env_allowlist {
+            if names
+                .iter()
+                .any(|name| name.trim().is_empty() || name.contains('='))
+            {
+                return Err("环境变量白名单中包含无效的变量名".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 去掉 `.` 和 `..`（不访问文件系统，路径不必存在）
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+/// 解析符号链接后的路径；路径不存在时解析其存在的最长前缀
+fn resolve(path: &Path) -> PathBuf {
+    if let Ok(real) = std::fs::canonicalize(path) {
+        return real;
+    }
+    let path = normalize(path);
+    let mut existing = path.as_path();
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(real) = std::fs::canonicalize(existing) {
+            return rest.iter().rev().fold(real, |real, name| real.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => return path,
+        }
+    }
+}
+
+/// 检查 `cwd` 和参数中的路径都在启动目录中，返回进程的工作目录
+pub(super) fn rooted_cwd(spec: &Value, root: &str) -> Result<PathBuf, String> {
+    let root = resolve(Path::new(root));
+    let cwd = match spec.get("cwd").and_then(Value::as_str) {
+        Some(cwd) => resolve(&root.join(cwd)),
+        None => root.clone(),
+    };
+    if !cwd.starts_with(&root) {
+        return Err(format!("工作目录 '{}' 不在启动目录中", cwd.display()));
+    }
+
+    let args = spec.get("args").and_then(Value::as_array);
+    for arg in args.into_iter().flatten().filter_map(Value::as_str) {
+        // 同时检查 `--config=/path` 形式的参数；相对路径按工作目录解析
+        let value = arg.split_once('=').map_or(arg, |(_, value)| value);
+        if value.is_empty() || value.starts_with('-') {
+            continue;
+        }
+        if !resolve(&cwd.join(value)).starts_with(&root) {
+            return Err(format!("参数 '{}' 指向启动目录之外的路径", arg));
+        }
+    }
+    Ok(cwd)
+}
+
+/// 服务器进程的工作目录
+///
+/// 设置了启动目录时检查后返回其中的目录；否则为规范中的 `cwd`（相对路径基于
+/// `base`），没有时为 `base`（引擎的工作目录）。
+pub(super) fn working_dir(
+    spec: &Value,
+    config: &SandboxConfig,
+    base: Option<&Path>,
+) -> Result<Option<PathBuf>, String> {
+    if let Some(root) = &config.launch_root {
+        return rooted_cwd(spec, root).map(Some);
+    }
+    let cwd = spec.get("cwd").and_then(Value::as_str).map(Path::new);
+    Ok(match (cwd, base) {
+        (Some(cwd), Some(base)) => Some(base.join(cwd)),
+        (Some(cwd), None) => Some(cwd.to_path_buf()),
+        (None, base) => base.map(Path::to_path_buf),
+    })
+}
+
+/// 服务器进程的资源限制：全局 MCP 限制，内存上限与服务器自己的上限取较小者
+fn process_limits(config: &SandboxConfig) -> Result<ResourceLimits, String> {
+    let global = limits::load_config().map_err(|e| format!("读取资源限制配置失败: {}", e))?;
+    let mut process = global.mcp_limits().cloned().unwrap_or_default();
+    if let Some(mb) = config.max_memory_mb {
+        process.max_memory_mb = Some(process.max_memory_mb.map_or(mb, |global| global.min(mb)));
+    }
+    Ok(process)
+}
+
+/// 启动前的限制：工作目录、环境变量白名单、资源限制和优先级
+///
+/// 返回启动后需要交给 [`contain`] 的限制。
+pub(super) fn apply_to_command(
+    cmd: &mut tokio::process::Command,
+    config: &SandboxConfig,
+    cwd: Option<&Path>,
+) -> Result<ResourceLimits, String> {
+    if let Some(cwd) = cwd {
+        cmd.current_dir(cwd);
+    }
+
+    if let Some(allowlist) = &config.env_allowlist {
+        // 已显式设置的变量（运行必需的变量和服务器配置中的 env）保留
+        let explicit: Vec<_> = cmd
+            .as_std()
+            .get_envs()
+            .filter_map(|(key, value)| Some((key.to_os_string(), value?.to_os_string())))
+            .collect();
+        cmd.env_clear();
+        for (key, value) in std::env::vars_os() {
+            let allowed = key
+                .to_str()
+                .is_some_and(|key| allowlist.iter().any(|name| name == key));
+            if allowed {
+                cmd.env(key, value);
+            }
+        }
+        cmd.envs(explicit);
+    }
+
+    let resources = process_limits(config)?;
+    let nice = config.nice.filter(|nice| *nice > 0);
+
+    #[cfg(unix)]
+    limits::limit_before_exec(cmd, &resources, nice)?;
+
+    #[cfg(target_os = "windows")]
+    {
+        if resources.max_cpu_seconds.is_some() {
+            return Err("Windows 不支持限制 MCP 服务器的 CPU 时间".to_string());
+        }
+        if let Some(nice) = nice {
+            const CREATE_NO_WINDOW: u32 = 0x08000000;
+            const BELOW_NORMAL_PRIORITY_CLASS: u32 = 0x00004000;
+            const IDLE_PRIORITY_CLASS: u32 = 0x00000040;
+            let priority = if nice >= 10 {
+                IDLE_PRIORITY_CLASS
+            } else {
+                BELOW_NORMAL_PRIORITY_CLASS
+            };
+            cmd.creation_flags(CREATE_NO_WINDOW | priority);
+        }
+    }
+
+    Ok(resources)
+}
+
+/// 启动后的限制：Windows 上把进程放入带内存和 CPU 上限的 Job Object（其它平台已在
+/// 启动前设置，直接返回）
+///
+/// 返回的 Job Object 需要与进程同生命周期保存（关闭即结束进程）。
+pub(super) fn contain(pid: u32, limits: &ResourceLimits) -> Result<Option<JobObject>, String> {
+    if !cfg!(target_os = "windows")
+        || (limits.max_memory_mb.is_none() && limits.max_cpu_percent.is_none())
+    {
+        return Ok(None);
+    }
+    let job = JobObject::create()?;
+    job.assign_process_by_pid(pid)?;
+    job.set_resource_limits(
+        None,
+        limits.memory_bytes().map(|bytes| bytes as usize),
+        limits.max_cpu_percent,
+    )?;
+    Ok(Some(job))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_rooted_cwd() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = std::fs::canonicalize(dir.path()).unwrap();
+        std::fs::create_dir(root.join("sub")).unwrap();
+        let root_str = root.to_str().unwrap();
+        let outside = if cfg!(windows) { "C:\\Windows" } else { "/etc" };
+
+        let spec = json!({ "command": "npx", "args": ["-y", "server-fs", root.join("data")], "cwd": "sub" });
+        assert_eq!(rooted_cwd(&spec, root_str).unwrap(), root.join("sub"));
+
+        let spec = json!({ "command": "npx", "args": [format!("--root={}", outside)] });
+        assert!(rooted_cwd(&spec, root_str).is_err());
+        let spec = json!({ "command": "npx", "cwd": "../other" });
+        assert!(rooted_cwd(&spec, root_str).is_err());
+        // 相对路径参数按工作目录解析
+        let spec = json!({ "command": "node", "args": ["../../secret"], "cwd": "sub" });
+        assert!(rooted_cwd(&spec, root_str).is_err());
+
+        // 指向目录之外的符号链接
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(outside, root.join("link")).unwrap();
+            let spec = json!({ "command": "npx", "args": ["link/passwd"] });
+            assert!(rooted_cwd(&spec, root_str).is_err());
+        }
+
+        assert!(SandboxConfig {
+            nice: Some(-5),
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(SandboxConfig::default().is_default());
+        let legacy: SandboxConfig =
+            serde_json::from_value(json!({ "confineTo": root_str })).unwrap();
+        assert_eq!(legacy.launch_root.as_deref(), Some(root_str));
+    }
+}
@@ -0,0 +1,162 @@
+//! 注册表搜索
+//!
+//! 按 ID、名称、描述、标签和启动命令模糊匹配服务器：查询按空白拆分为多个词，每个词
+//! 都必须命中某个字段；完全匹配、前缀、子串依次得分更高，ID 和名称还支持按顺序出现的
+//! 字符匹配（如 `gh` 匹配 `github`）。标签筛选要求服务器包含所有给出的标签。
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use super::registry::{self, McpRegistry, RegistryEntry};
+
+/// 搜索结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub entry: RegistryEntry,
+    /// 相关度，越大越相关
+    pub score: u32,
+}
+
+/// `needle` 的字符是否按顺序出现在 `haystack` 中
+fn is_subsequence(needle: &str, haystack: &str) -> bool {
+    let mut chars = haystack.chars();
+    needle.chars().all(|c| chars.any(|h| h == c))
+}
+
+/// 单个字段的得分，`fuzzy` 为 true 时允许按顺序出现的字符匹配
+fn field_score(term: &str, field: &str, fuzzy: bool) -> u32 {
+    let field = field.to_lowercase();
+    if field == term {
+        100
+    } else if field.starts_with(term) {
+        60
+    } else if field.contains(term) {
+        40
+    } else if fuzzy && is_subsequence(term, &field) {
+        10
+    } else {
+        0
+    }
+}
+
+/// 条目的得分，任一查询词没有命中时返回 None
+fn score(entry: &RegistryEntry, terms: &[String]) -> Option<u32> {
+    let command = entry
+        .server
+        .get("command")
+        .or_else(|| entry.server.get("url"))
+        .and_then(|value| value.as_str())
+        .unwrap_or_default();
+    terms.iter().try_fold(0, |total, term| {
+        let best = [
+            field_score(term, &entry.id, true),
+            field_score(term, &entry.name, true),
+            field_score(
+                term,
+                entry.description.as_deref().unwrap_or_default(),
+                false,
+            ) / 2,
+            field_score(term, command, false) / 2,
+        ]
+        .into_iter()
+        .chain(entry.tags.iter().map(|tag| field_score(term, tag, false)))
+        .max()
+        .unwrap_or_default();
+        (best > 0).then_some(total + best)
+    })
+}
+
+/// 在注册表中搜索，结果按相关度和名称排序；查询为空时只按标签筛选
+pub fn search(registry: &McpRegistry, query: &str, tags: &[String]) -> Vec<SearchHit> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    let tags = registry::normalize_tags(tags);
+
+    let mut hits: Vec<SearchHit> = registry
+        .servers
+        .values()
+        .filter(|entry| tags.iter().all(|tag| entry.tags.contains(tag)))
+        .filter_map(|entry| {
+            Some(SearchHit {
+                score: score(entry, &terms)?,
+                entry: entry.clone(),
+            })
+        })
+        .collect();
+    hits.sort_by(|a, b| {
+        b.score.cmp(&a.score).then_with(|| {
+            a.entry
+                .name
+                .to_lowercase()
+                .cmp(&b.entry.name.to_lowercase())
+        })
+    });
+    hits
+}
+
+/// 所有标签及使用该标签的服务器数量
+pub fn tag_counts(registry: &McpRegistry) -> BTreeMap<String, usize> {
+    let mut counts = BTreeMap::new();
+    for tag in registry.servers.values().flat_map(|entry| &entry.tags) {
+        *counts.entry(tag.clone()).or_insert(0) += 1;
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entry(id: &str, name: &str, description: Option<&str>, tags: &[&str]) -> RegistryEntry {
+        RegistryEntry {
+            id: id.to_string(),
+            name: name.to_string(),
+            description: description.map(str::to_string),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            server: json!({ "command": "npx" }),
+            enabled: Default::default(),
+            health: None,
+            introspection: None,
+            disabled_tools: Vec::new(),
+            tool_prefix: None,
+            startup: Default::default(),
+            sandbox: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_search() {
+        let mut registry = McpRegistry::default();
+        for entry in [
+            entry(
+                "github",
+                "GitHub",
+                Some("Issues and pull requests"),
+                &["vcs", "remote"],
+            ),
+            entry("git", "Git", None, &["vcs"]),
+            entry("postgres", "Postgres", Some("Query a database"), &["db"]),
+        ] {
+            registry.servers.insert(entry.id.clone(), entry);
+        }
+        let ids = |hits: Vec<SearchHit>| -> Vec<String> {
+            hits.into_iter().map(|hit| hit.entry.id).collect()
+        };
+
+        assert_eq!(ids(search(&registry, "git", &[])), vec!["git", "github"]);
+        assert_eq!(ids(search(&registry, "ghb", &[])), vec!["github"]);
+        assert_eq!(ids(search(&registry, "database", &[])), vec!["postgres"]);
+        assert_eq!(ids(search(&registry, "git pull", &[])), vec!["github"]);
+        assert_eq!(
+            ids(search(
+                &registry,
+                "",
+                &["VCS".to_string(), "remote".to_string()]
+            )),
+            vec!["github"]
+        );
+        assert_eq!(search(&registry, "", &[]).len(), 3);
+        assert_eq!(tag_counts(&registry).get("vcs"), Some(&2));
+    }
+}
@@ -0,0 +1,188 @@
+//! MCP 服务器密钥
+//!
+//! 服务器配置中 `env` 和 `headers` 的值可以标记为密钥：值保存到系统钥匙串
+//! （名称为 `mcp-<服务器>-<块>-<键>`），注册表中只保留占位符 [`SECRET_MARKER`]。
+//! 同步到引擎或启动服务器时再把占位符替换为真实值，因此注册表文件、配置包和
+//! 日志中都不会出现明文密钥。从钥匙串读取的值会登记到 `utils::redaction`。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::utils::keyring_store;
+
+/// 注册表中代替密钥值的占位符
+pub const SECRET_MARKER: &str = "${keyring}";
+
+/// 可以包含密钥的配置块
+pub const SECRET_BLOCKS: &[&str] = &["env", "headers"];
+
+/// 密钥在服务器配置中的位置
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SecretField {
+    /// "env" 或 "headers"
+    pub block: String,
+    pub key: String,
+}
+
+fn keyring_name(id: &str, block: &str, key: &str) -> String {
+    format!("mcp-{}-{}-{}", id, block, key)
+}
+
+fn check_block(block: &str) -> Result<(), String> {
+    if SECRET_BLOCKS.contains(&block) {
+        Ok(())
+    } else {
+        Err(format!(
+            "只有 env 和 headers 中的值可以标记为密钥，而不是 '{}'",
+            block
+        ))
+    }
+}
+
+pub fn is_marker(value: &Value) -> bool {
+    value.as_str() == Some(SECRET_MARKER)
+}
+
+/// 配置中已标记为密钥的字段
+pub fn secret_fields(spec: &Value) -> Vec<SecretField> {
+    let mut fields = Vec::new();
+    for block in SECRET_BLOCKS {
+        let Some(values) = spec.get(*block).and_then(Value::as_object) else {
+            continue;
+        };
+        for (key, value) in values {
+            if is_marker(value) {
+                fields.push(SecretField {
+                    block: block.to_string(),
+                    key: key.clone(),
+                });
+            }
+        }
+    }
+    fields
+}
+
+/// 将字段标记为密钥：值存入钥匙串，配置中替换为占位符
+///
+/// `value` 为空时使用配置中当前的明文值
+pub fn mark(
+    id: &str,
+    spec: &mut Value,
+    block: &str,
+    key: &str,
+    value: Option<&str>,
+) -> Result<(), String> {
+    check_block(block)?;
+    let slot = spec
+        .get_mut(block)
+        .and_then(Value::as_object_mut)
+        .and_then(|values| values.get_mut(key))
+        .ok_or_else(|| format!("服务器 '{}' 的 {} 中没有 '{}'", id, block, key))?;
+
+    let value = match value {
+        Some(value) => value.to_string(),
+        None if is_marker(slot) => return Ok(()),
+        None => slot
+            .as_str()
+            .ok_or_else(|| format!("{}.{} 不是字符串", block, key))?
+            .to_string(),
+    };
+    keyring_store::set_secret(&keyring_name(id, block, key), &value)?;
+    *slot = Value::from(SECRET_MARKER);
+    log::info!("服务器 '{}' 的 {}.{} 已保存到系统钥匙串", id, block, key);
+    Ok(())
+}
+
+/// 取消密钥标记：把钥匙串中的值写回配置，并删除钥匙串条目
+pub fn unmark(id: &str, spec: &mut Value, block: &str, key: &str) -> Result<(), String> {
+    check_block(block)?;
+    let slot = spec
+        .get_mut(block)
+        .and_then(Value::as_object_mut)
+        .and_then(|values| values.get_mut(key))
+        .filter(|slot| is_marker(slot))
+        .ok_or_else(|| format!("服务器 '{}' 的 {}.{} 不是密钥", id, block, key))?;
+
+    let name = keyring_name(id, block, key);
+    let value = keyring_store::get_secret(&name)?.unwrap_or_default();
+    *slot = Value::from(value);
+    keyring_store::delete_secret(&name)
+}
+
+/// 替换配置中的占位符为钥匙串中的值
+pub fn resolve(id: &str, spec: &Value) -> Result<Value, String> {
+    let mut spec = spec.clone();
+    for field in secret_fields(&spec) {
+        let value = keyring_store::get_secret(&keyring_name(id, &field.block, &field.key))?
+            .ok_or_else(|| {
+                format!(
+                    "服务器 '{}' 的密钥 {}.{} 不在系统钥匙串中，请重新填写",
+                    id, field.block, field.key
+                )
+            })?;
+        spec[&field.block][&field.key] = Value::from(value);
+    }
+    Ok(spec)
+}
+
+/// 更新配置时保持密钥：`previous` 中已标记的字段若在 `spec` 中给出了明文值，
+/// 新值存入钥匙串并换回占位符（例如从引擎配置读回的、已替换过占位符的配置）
+pub fn protect(id: &str, previous: Option<&Value>, spec: &mut Value) -> Result<(), String> {
+    let Some(previous) = previous else {
+        return Ok(());
+    };
+    for field in secret_fields(previous) {
+        let has_value = spec
+            .get(&field.block)
+            .and_then(|values| values.get(&field.key))
+            .is_some_and(|value| value.is_string());
+        if has_value {
+            mark(id, spec, &field.block, &field.key, None)?;
+        }
+    }
+    Ok(())
+}
+
+/// 删除服务器的所有密钥
+pub fn forget(id: &str, spec: &Value) {
+    for field in secret_fields(spec) {
+        if let Err(e) = keyring_store::delete_secret(&keyring_name(id, &field.block, &field.key)) {
+            log::warn!("删除服务器 '{}' 的密钥失败: {}", id, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_secret_fields() {
+        let spec = json!({
+            "command": "npx",
+            "env": { "API_KEY": SECRET_MARKER, "LOG_LEVEL": "debug" },
+            "headers": { "Authorization": SECRET_MARKER }
+        });
+        assert_eq!(
+            secret_fields(&spec),
+            vec![
+                SecretField {
+                    block: "env".to_string(),
+                    key: "API_KEY".to_string()
+                },
+                SecretField {
+                    block: "headers".to_string(),
+                    key: "Authorization".to_string()
+                },
+            ]
+        );
+        assert!(check_block("args").is_err());
+        // 没有密钥的配置不访问钥匙串
+        assert_eq!(
+            resolve("plain", &json!({ "command": "x" })).unwrap()["command"],
+            json!("x")
+        );
+    }
+}
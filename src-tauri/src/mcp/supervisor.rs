@@ -0,0 +1,764 @@
+//! MCP 服务器进程监督模块
+//!
+//! 按注册表中的配置启动、停止和重启 stdio MCP 服务器，记录每个服务器的 PID、
+//! 运行时长和重启次数。意外退出的服务器按指数退避（1 秒起，最长 60 秒）自动
+//! 重启；稳定运行 60 秒后退避重新计算，连续崩溃 5 次后放弃并标记为失败。
+//!
+//! 引擎通过桥接（[`super::bridge`]）使用这里的进程：每个服务器在每个工作目录中
+//! 最多运行一个实例，连接到同一实例的引擎会话共享进程，消息由 [`Router`] 转发。
+//! 进程重启后路由重新初始化服务器，会话不会断开；停止服务器时会话随之断开。
+//!
+//! 服务器的 stderr 和生命周期记录在 [`super::logs`] 中，资源限制见 [`super::sandbox`]。
+//! 每个服务器是自己进程组的组长，停止、空闲停止和崩溃后都结束整个进程组，服务器
+//! 启动的子进程（例如 `npx` 启动的 node）不会残留。
+//!
+//! 注册表条目中的启动配置（[`StartupConfig`]）决定引擎使用的进程何时启动和停止：
+//! - `on-first-use`（默认）：第一个引擎会话连接（[`attach`]）或 [`acquire`] 时启动
+//! - `eager`：有固定工作目录（`cwd` 或启动目录）的服务器在应用启动时启动，其它的
+//!   在第一个引擎会话连接时启动；之后一直运行，不会空闲停止
+//! - `manual`：只能在应用中手动启动，未启动时引擎会话连接失败
+//!
+//! 设置了空闲超时的服务器在没有引擎会话连接、且超过该时间没有被使用后自动停止，
+//! 下次使用时再启动。
+//!
+//! 事件：
+//! - `mcp-server-state` - 服务器状态变化时发送 [`SupervisedServer`]
+//! - `mcp-server-log` - 服务器输出一行日志时发送 [`McpLogLine`]
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::{Lazy, OnceCell};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::Child;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+use tokio::sync::Notify;
+
+use super::health::{spec_str, stdio_command, tail};
+use super::logs::{self, McpLogLine};
+use super::registry::RegistryEntry;
+use super::router::Router;
+use super::sandbox::{self, SandboxConfig};
+use super::validation::validate_server_spec;
+use super::McpTransport;
+use crate::process::{interrupt, JobObject};
+
+/// 第一次自动重启前的等待时间，之后每次翻倍
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// 运行超过这个时间后退出不再计入连续崩溃
+const STABLE_AFTER: Duration = Duration::from_secs(60);
+/// 连续崩溃这么多次后不再重启
+const MAX_CONSECUTIVE_CRASHES: u32 = 5;
+
+/// 启动策略
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum StartupPolicy {
+    /// 应用启动时启动（在任一引擎中启用的服务器），一直运行
+    Eager,
+    /// 第一次使用时启动
+    #[default]
+    OnFirstUse,
+    /// 只在手动启动时启动
+    Manual,
+}
+
+/// 服务器的启动配置，保存在注册表条目中
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupConfig {
+    #[serde(default)]
+    pub policy: StartupPolicy,
+    /// 没有引擎会话连接超过这么多秒后自动停止；为空时不自动停止（`eager` 的服务器忽略）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub idle_timeout_secs: Option<u64>,
+}
+
+impl StartupConfig {
+    pub fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    fn idle_timeout(&self) -> Option<Duration> {
+        if self.policy == StartupPolicy::Eager {
+            return None;
+        }
+        self.idle_timeout_secs
+            .filter(|secs| *secs > 0)
+            .map(Duration::from_secs)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SupervisedState {
+    Running,
+    /// 已崩溃，等待自动重启
+    Restarting,
+    Stopped,
+    /// 连续崩溃次数过多，已放弃
+    Failed,
+}
+
+/// 启动服务器需要的配置
+#[derive(Debug, Clone)]
+pub struct ServerLaunch {
+    pub id: String,
+    /// 密钥已替换为真实值的规范
+    pub spec: Value,
+    pub startup: StartupConfig,
+    pub sandbox: SandboxConfig,
+    pub tool_prefix: Option<String>,
+}
+
+impl ServerLaunch {
+    pub fn from_entry(entry: &RegistryEntry) -> Result<Self, String> {
+        Ok(Self {
+            id: entry.id.clone(),
+            spec: entry.launch_spec()?,
+            startup: entry.startup,
+            sandbox: entry.sandbox.clone(),
+            tool_prefix: entry.tool_prefix.clone(),
+        })
+    }
+
+    /// 不在注册表中的服务器，使用默认配置
+    pub fn from_spec(id: &str, spec: Value) -> Self {
+        Self {
+            id: id.to_string(),
+            spec,
+            startup: StartupConfig::default(),
+            sandbox: SandboxConfig::default(),
+            tool_prefix: None,
+        }
+    }
+}
+
+/// 受监督服务器实例的状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisedServer {
+    pub id: String,
+    /// 进程的工作目录；为空时使用应用的工作目录
+    pub cwd: Option<String>,
+    pub state: SupervisedState,
+    pub pid: Option<u32>,
+    pub started_at: Option<DateTime<Utc>>,
+    /// 当前进程已运行的秒数
+    pub uptime_secs: Option<u64>,
+    /// 自动或手动重启的次数
+    pub restart_count: u32,
+    /// 上一次退出的原因（退出码和 stderr 末尾）
+    pub last_exit: Option<String>,
+    /// 连接的引擎会话数
+    pub clients: usize,
+}
+
+/// 实例：服务器 ID 和进程的工作目录
+type Key = (String, Option<PathBuf>);
+
+struct Supervised {
+    status: SupervisedServer,
+    stop: Arc<Notify>,
+    router: Arc<Mutex<Router>>,
+    /// 启动时给出的工作目录（规范中的相对 `cwd` 基于它），重启时使用
+    base: Option<PathBuf>,
+    /// 最近一次被使用的时间，用于空闲超时
+    last_used: Instant,
+}
+
+static APP: OnceCell<AppHandle> = OnceCell::new();
+static SERVERS: Lazy<Mutex<HashMap<Key, Supervised>>> = Lazy::new(|| Mutex::new(HashMap::new()));
+
+/// 让监督模块可以发送 `mcp-server-state` 和 `mcp-server-log` 事件
+pub fn init(app: AppHandle) {
+    let _ = APP.set(app);
+}
+
+fn emit<S: Serialize + Clone>(event: &str, payload: &S) {
+    if let Some(app) = APP.get() {
+        if let Err(e) = app.emit(event, payload) {
+            log::debug!("发送 {} 事件失败: {}", event, e);
+        }
+    }
+}
+
+/// 写入服务器日志并推送给前端
+fn log_line(id: &str, line: &str) {
+    let entry: McpLogLine = logs::append(id, line);
+    emit("mcp-server-log", &entry);
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn snapshot(server: &Supervised) -> SupervisedServer {
+    let mut status = server.status.clone();
+    status.clients = lock(&server.router).client_count();
+    if status.state == SupervisedState::Running {
+        status.uptime_secs = status
+            .started_at
+            .map(|started| (Utc::now() - started).num_seconds().max(0) as u64);
+    }
+    status
+}
+
+/// 修改服务器状态并通知前端
+fn update(key: &Key, change: impl FnOnce(&mut SupervisedServer)) {
+    let event = {
+        let mut servers = lock(&SERVERS);
+        let Some(server) = servers.get_mut(key) else {
+            return;
+        };
+        change(&mut server.status);
+        snapshot(server)
+    };
+    emit("mcp-server-state", &event);
+}
+
+/// 正在运行的服务器进程
+struct Running {
+    child: Child,
+    stderr: Arc<Mutex<String>>,
+    /// 内存上限所在的 Job Object（仅 Windows），与进程同生命周期
+    _job: Option<JobObject>,
+}
+
+/// 启动进程，stdin 和 stdout 交给路由
+fn spawn(
+    launch: &ServerLaunch,
+    cwd: Option<&Path>,
+    router: &Arc<Mutex<Router>>,
+) -> Result<Running, String> {
+    let id = launch.id.as_str();
+    let program = spec_str(&launch.spec, "command").unwrap_or_default();
+    let mut cmd = stdio_command(&launch.spec);
+    // 在 Windows 上设置优先级时会覆盖这里的创建标志，那里通过进程树结束子进程
+    interrupt::new_process_group(&mut cmd);
+    let limits = sandbox::apply_to_command(&mut cmd, &launch.sandbox, cwd)
+        .inspect_err(|e| log_line(id, e))?;
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("启动 MCP 服务器 '{}' 失败: {}", program, e))
+        .inspect_err(|e| log_line(id, e))?;
+
+    let job = match child.id().map(|pid| sandbox::contain(pid, &limits)) {
+        Some(Err(e)) => {
+            let _ = child.start_kill();
+            let e = format!("无法对 MCP 服务器 '{}' 应用资源限制: {}", id, e);
+            log_line(id, &e);
+            return Err(e);
+        }
+        Some(Ok(job)) => job,
+        None => None,
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<String>();
+        tokio::spawn(async move {
+            while let Some(line) = receiver.recv().await {
+                let line = format!("{}\n", line);
+                if stdin.write_all(line.as_bytes()).await.is_err() || stdin.flush().await.is_err() {
+                    break;
+                }
+            }
+        });
+        lock(router).attach_server(sender);
+    }
+    if let Some(stdout) = child.stdout.take() {
+        let router = router.clone();
+        let id = id.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                match serde_json::from_str::<Value>(&line) {
+                    Ok(message) => lock(&router).from_server(message),
+                    // 不是 JSON-RPC 消息的输出记入日志
+                    Err(_) => log_line(&id, &line),
+                }
+            }
+        });
+    }
+    // 记录 stderr，并在内存中保留末尾，用于报告崩溃原因
+    let stderr = Arc::new(Mutex::new(String::new()));
+    if let Some(pipe) = child.stderr.take() {
+        let stderr = stderr.clone();
+        let id = id.to_string();
+        tokio::spawn(async move {
+            let mut lines = BufReader::new(pipe).lines();
+            while let Ok(Some(line)) = lines.next_line().await {
+                log_line(&id, &line);
+                let mut output = lock(&stderr);
+                output.push_str(&line);
+                output.push('\n');
+                if output.len() > 4096 {
+                    *output = tail(&output);
+                }
+            }
+        });
+    }
+
+    Ok(Running {
+        child,
+        stderr,
+        _job: job,
+    })
+}
+
+/// 结束服务器和它启动的所有进程
+async fn kill(running: &mut Running) {
+    if let Some(pid) = running.child.id() {
+        interrupt::kill_group(pid, true);
+    }
+    let _ = running.child.kill().await;
+}
+
+fn backoff(crashes: u32) -> Duration {
+    INITIAL_BACKOFF
+        .saturating_mul(1 << crashes.saturating_sub(1).min(6))
+        .min(MAX_BACKOFF)
+}
+
+/// 等到服务器空闲（没有引擎会话连接）超过 `timeout`；没有超时设置时永不返回
+async fn wait_idle(key: &Key, timeout: Option<Duration>) {
+    let Some(timeout) = timeout else {
+        return std::future::pending().await;
+    };
+    loop {
+        let state = lock(&SERVERS)
+            .get(key)
+            .map(|server| (server.last_used, lock(&server.router).client_count()));
+        let Some((last_used, clients)) = state else {
+            return std::future::pending().await;
+        };
+        // 会话断开时刷新 `last_used`，从那时起计时
+        if clients > 0 {
+            tokio::time::sleep(timeout).await;
+            continue;
+        }
+        let idle = last_used.elapsed();
+        if idle >= timeout {
+            return;
+        }
+        tokio::time::sleep(timeout - idle).await;
+    }
+}
+
+/// 监督循环：等待进程退出，按需重启，直到被停止、空闲超时或放弃
+async fn supervise(
+    key: Key,
+    launch: ServerLaunch,
+    mut running: Running,
+    stop: Arc<Notify>,
+    router: Arc<Mutex<Router>>,
+) {
+    let id = launch.id.clone();
+    let cwd = key.1.clone();
+    let idle_timeout = launch.startup.idle_timeout();
+    let mut crashes = 0;
+    loop {
+        let started = Instant::now();
+        let pid = running.child.id();
+        update(&key, |status| {
+            status.state = SupervisedState::Running;
+            status.pid = pid;
+            status.started_at = Some(Utc::now());
+        });
+        log::info!("MCP 服务器 '{}' 已启动，PID {:?}", id, pid);
+        log_line(&id, &format!("[supervisor] 进程已启动，PID {:?}", pid));
+
+        let exit = tokio::select! {
+            exit = running.child.wait() => exit,
+            _ = stop.notified() => {
+                kill(&mut running).await;
+                lock(&router).disconnect_all();
+                update(&key, |status| {
+                    status.state = SupervisedState::Stopped;
+                    status.pid = None;
+                });
+                log::info!("MCP 服务器 '{}' 已停止", id);
+                log_line(&id, "[supervisor] 进程已停止");
+                return;
+            }
+            _ = wait_idle(&key, idle_timeout) => {
+                kill(&mut running).await;
+                lock(&router).disconnect_all();
+                update(&key, |status| {
+                    status.state = SupervisedState::Stopped;
+                    status.pid = None;
+                    status.last_exit = Some("空闲超时，已自动停止".to_string());
+                });
+                log::info!("MCP 服务器 '{}' 空闲超时，已停止", id);
+                log_line(&id, "[supervisor] 空闲超时，进程已停止");
+                return;
+            }
+        };
+        lock(&router).detach_server();
+        // 服务器退出后它启动的进程仍在进程组中，重启前结束它们
+        if let Some(pid) = pid {
+            interrupt::kill_group(pid, false);
+        }
+
+        let stderr = tail(&lock(&running.stderr));
+        let reason = match exit {
+            Ok(status) => format!("进程退出（{}）", status),
+            Err(e) => format!("等待进程失败: {}", e),
+        };
+        log_line(&id, &format!("[supervisor] {}", reason));
+        let reason = format!("{}\n{}", reason, stderr).trim().to_string();
+        log::warn!("MCP 服务器 '{}' 意外退出: {}", id, reason);
+
+        if started.elapsed() >= STABLE_AFTER {
+            crashes = 0;
+        }
+        // 启动失败也算作一次崩溃
+        let next = loop {
+            crashes += 1;
+            if crashes > MAX_CONSECUTIVE_CRASHES {
+                lock(&router).disconnect_all();
+                update(&key, |status| {
+                    status.state = SupervisedState::Failed;
+                    status.pid = None;
+                    status.last_exit = Some(reason.clone());
+                });
+                log::error!(
+                    "MCP 服务器 '{}' 连续崩溃 {} 次，不再重启",
+                    id,
+                    MAX_CONSECUTIVE_CRASHES
+                );
+                log_line(&id, "[supervisor] 连续崩溃次数过多，不再重启");
+                return;
+            }
+
+            let delay = backoff(crashes);
+            log_line(&id, &format!("[supervisor] {} 秒后重启", delay.as_secs()));
+            update(&key, |status| {
+                status.state = SupervisedState::Restarting;
+                status.pid = None;
+                status.last_exit = Some(reason.clone());
+            });
+            tokio::select! {
+                _ = tokio::time::sleep(delay) => {}
+                _ = stop.notified() => {
+                    lock(&router).disconnect_all();
+                    update(&key, |status| status.state = SupervisedState::Stopped);
+                    return;
+                }
+            }
+
+            update(&key, |status| status.restart_count += 1);
+            match spawn(&launch, cwd.as_deref(), &router) {
+                Ok(next) => break next,
+                Err(e) => log::warn!("重启 MCP 服务器 '{}' 失败: {}", id, e),
+            }
+        };
+        running = next;
+    }
+}
+
+fn is_active(state: SupervisedState) -> bool {
+    matches!(
+        state,
+        SupervisedState::Running | SupervisedState::Restarting
+    )
+}
+
+/// 服务器正在运行的实例
+fn active_instances(id: &str) -> Vec<(Key, Option<PathBuf>, Arc<Notify>)> {
+    lock(&SERVERS)
+        .iter()
+        .filter(|(key, server)| key.0 == id && is_active(server.status.state))
+        .map(|(key, server)| (key.clone(), server.base.clone(), server.stop.clone()))
+        .collect()
+}
+
+/// 检查规范并确定实例：工作目录由沙箱限制、规范中的 `cwd` 和 `base` 决定
+fn instance_key(launch: &ServerLaunch, base: Option<&Path>) -> Result<Key, String> {
+    validate_server_spec(&launch.spec)?;
+    if McpTransport::from_spec(&launch.spec)? != McpTransport::Stdio {
+        return Err("只有 stdio 类型的 MCP 服务器需要进程监督".to_string());
+    }
+    if super::bridge::unwrap(&launch.spec).is_some() {
+        return Err(format!(
+            "MCP 服务器 '{}' 的配置是桥接命令，请先将它保存到注册表",
+            launch.id
+        ));
+    }
+    let cwd = sandbox::working_dir(&launch.spec, &launch.sandbox, base)?;
+    Ok((launch.id.clone(), cwd))
+}
+
+/// 在已加锁的实例表中启动实例，重启次数在之前的基础上加 `restarts`
+fn start_locked(
+    servers: &mut HashMap<Key, Supervised>,
+    key: &Key,
+    launch: ServerLaunch,
+    base: Option<&Path>,
+    restarts: u32,
+) -> Result<SupervisedServer, String> {
+    let restart_count = match servers.get(key) {
+        Some(server) if is_active(server.status.state) => {
+            return Err(format!("MCP 服务器 '{}' 已在运行", launch.id));
+        }
+        Some(server) => server.status.restart_count + restarts,
+        None => restarts,
+    };
+
+    let router = Arc::new(Mutex::new(Router::new(launch.tool_prefix.clone())));
+    let running = spawn(&launch, key.1.as_deref(), &router)?;
+    let stop = Arc::new(Notify::new());
+    let server = Supervised {
+        status: SupervisedServer {
+            id: launch.id.clone(),
+            cwd: key.1.as_ref().map(|cwd| cwd.to_string_lossy().to_string()),
+            state: SupervisedState::Running,
+            pid: running.child.id(),
+            started_at: Some(Utc::now()),
+            uptime_secs: Some(0),
+            restart_count,
+            last_exit: None,
+            clients: 0,
+        },
+        stop: stop.clone(),
+        router: router.clone(),
+        base: base.map(Path::to_path_buf),
+        last_used: Instant::now(),
+    };
+    let status = snapshot(&server);
+    servers.insert(key.clone(), server);
+
+    tokio::spawn(supervise(key.clone(), launch, running, stop, router));
+    Ok(status)
+}
+
+/// 启动服务器并开始监督；`cwd` 为引擎或项目的工作目录
+pub fn start(launch: ServerLaunch, cwd: Option<&Path>) -> Result<SupervisedServer, String> {
+    let key = instance_key(&launch, cwd)?;
+    start_locked(&mut lock(&SERVERS), &key, launch, cwd, 0)
+}
+
+/// 使用服务器：正在运行时刷新空闲计时，否则按启动策略启动（`manual` 的服务器不会自动启动）
+pub fn acquire(launch: ServerLaunch, cwd: Option<&Path>) -> Result<SupervisedServer, String> {
+    let key = instance_key(&launch, cwd)?;
+    let mut servers = lock(&SERVERS);
+    if let Some(server) = servers
+        .get_mut(&key)
+        .filter(|server| is_active(server.status.state))
+    {
+        server.last_used = Instant::now();
+        return Ok(snapshot(server));
+    }
+    if launch.startup.policy == StartupPolicy::Manual {
+        return Err(format!("MCP 服务器 '{}' 需要手动启动", launch.id));
+    }
+    start_locked(&mut servers, &key, launch, cwd, 0)
+}
+
+/// 引擎会话与服务器实例的连接，释放时断开
+pub struct Attachment {
+    key: Key,
+    client: u64,
+    router: Arc<Mutex<Router>>,
+    receiver: UnboundedReceiver<String>,
+}
+
+impl Attachment {
+    /// 转发会话发来的一行消息（无法解析的行丢弃）
+    pub fn send(&self, line: &str) {
+        match serde_json::from_str::<Value>(line) {
+            Ok(message) => lock(&self.router).from_client(self.client, message),
+            Err(e) => log::debug!("丢弃无法解析的 MCP 消息: {}", e),
+        }
+    }
+
+    /// 下一条发给会话的消息；服务器停止后返回 None
+    pub async fn recv(&mut self) -> Option<String> {
+        self.receiver.recv().await
+    }
+}
+
+impl Drop for Attachment {
+    fn drop(&mut self) {
+        lock(&self.router).remove_client(self.client);
+        let event = {
+            let mut servers = lock(&SERVERS);
+            let Some(server) = servers
+                .get_mut(&self.key)
+                .filter(|server| Arc::ptr_eq(&server.router, &self.router))
+            else {
+                return;
+            };
+            server.last_used = Instant::now();
+            snapshot(server)
+        };
+        emit("mcp-server-state", &event);
+    }
+}
+
+/// 引擎会话连接服务器：实例正在运行时直接连接，否则按启动策略启动
+/// （`manual` 的服务器需要先手动启动）
+pub fn attach(launch: ServerLaunch, cwd: Option<&Path>) -> Result<Attachment, String> {
+    let key = instance_key(&launch, cwd)?;
+    let mut servers = lock(&SERVERS);
+    let running = servers
+        .get(&key)
+        .is_some_and(|server| is_active(server.status.state));
+    if !running {
+        if launch.startup.policy == StartupPolicy::Manual {
+            return Err(format!("MCP 服务器 '{}' 需要先在应用中手动启动", launch.id));
+        }
+        log::info!("引擎会话首次使用 MCP 服务器 '{}'，启动进程", launch.id);
+        start_locked(&mut servers, &key, launch, cwd, 0)?;
+    }
+    let server = servers.get_mut(&key).ok_or("MCP 服务器实例不存在")?;
+    server.last_used = Instant::now();
+    let (sender, receiver) = mpsc::unbounded_channel();
+    let router = server.router.clone();
+    let client = lock(&router).add_client(sender);
+    let event = snapshot(server);
+    drop(servers);
+    emit("mcp-server-state", &event);
+
+    Ok(Attachment {
+        key,
+        client,
+        router,
+        receiver,
+    })
+}
+
+/// 启动策略为 `eager`、在任一引擎中启用且有固定工作目录的 stdio 服务器（应用启动时调用）
+///
+/// 没有固定工作目录的服务器在各项目中的引擎会话连接时启动。
+pub fn start_eager_servers() {
+    let registry = match super::registry::read_registry() {
+        Ok(registry) => registry,
+        Err(e) => {
+            log::warn!("读取注册表失败，跳过自动启动 MCP 服务器: {}", e);
+            return;
+        }
+    };
+    for entry in registry.servers.values() {
+        let eager = entry.startup.policy == StartupPolicy::Eager
+            && entry.enabled.values().any(|enabled| *enabled);
+        let stdio = McpTransport::from_spec(&super::normalize_transport(&entry.server))
+            .is_ok_and(|transport| transport == McpTransport::Stdio);
+        if !eager || !stdio {
+            continue;
+        }
+        let result = ServerLaunch::from_entry(entry).and_then(|launch| {
+            let fixed = sandbox::working_dir(&launch.spec, &launch.sandbox, None)?
+                .is_some_and(|cwd| cwd.is_absolute());
+            if !fixed {
+                log::debug!(
+                    "MCP 服务器 '{}' 没有固定工作目录，在引擎会话连接时启动",
+                    entry.id
+                );
+                return Ok(());
+            }
+            start(launch, None).map(|_| ())
+        });
+        if let Err(e) = result {
+            log::warn!("自动启动 MCP 服务器 '{}' 失败: {}", entry.id, e);
+        }
+    }
+}
+
+/// 停止服务器的所有实例（不会再自动重启），连接的引擎会话随之断开
+pub async fn stop(id: &str) -> Result<(), String> {
+    let instances = active_instances(id);
+    if instances.is_empty() {
+        return Err(format!("MCP 服务器 '{}' 未在运行", id));
+    }
+    for (_, _, stop) in &instances {
+        stop.notify_one();
+    }
+
+    // 等待监督循环结束进程
+    for _ in 0..50 {
+        if active_instances(id).is_empty() {
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    Err(format!("停止 MCP 服务器 '{}' 超时", id))
+}
+
+/// 使用最新的配置重启服务器的所有实例；未运行时在 `cwd` 中启动
+pub async fn restart(
+    launch: ServerLaunch,
+    cwd: Option<&Path>,
+) -> Result<Vec<SupervisedServer>, String> {
+    let bases: Vec<Option<PathBuf>> = active_instances(&launch.id)
+        .into_iter()
+        .map(|(_, base, _)| base)
+        .collect();
+    if bases.is_empty() {
+        return start(launch, cwd).map(|status| vec![status]);
+    }
+
+    stop(&launch.id).await?;
+    bases
+        .iter()
+        .map(|base| {
+            let key = instance_key(&launch, base.as_deref())?;
+            start_locked(
+                &mut lock(&SERVERS),
+                &key,
+                launch.clone(),
+                base.as_deref(),
+                1,
+            )
+        })
+        .collect()
+}
+
+/// 所有受监督服务器实例的当前状态（按 ID 和工作目录排序）
+pub fn list() -> Vec<SupervisedServer> {
+    let servers = lock(&SERVERS);
+    let mut list: Vec<SupervisedServer> = servers.values().map(snapshot).collect();
+    list.sort_by(|a, b| (&a.id, &a.cwd).cmp(&(&b.id, &b.cwd)));
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(2), Duration::from_secs(2));
+        assert_eq!(backoff(4), Duration::from_secs(8));
+        assert_eq!(backoff(20), MAX_BACKOFF);
+    }
+
+    #[test]
+    fn test_startup_config() {
+        let config: StartupConfig =
+            serde_json::from_value(serde_json::json!({ "policy": "on-first-use" })).unwrap();
+        assert!(config.is_default());
+        assert_eq!(config.idle_timeout(), None);
+
+        let config: StartupConfig = serde_json::from_value(
+            serde_json::json!({ "policy": "manual", "idleTimeoutSecs": 300 }),
+        )
+        .unwrap();
+        assert_eq!(config.policy, StartupPolicy::Manual);
+        assert_eq!(config.idle_timeout(), Some(Duration::from_secs(300)));
+
+        // eager 的服务器一直运行
+        let config = StartupConfig {
+            policy: StartupPolicy::Eager,
+            idle_timeout_secs: Some(300),
+        };
+        assert_eq!(config.idle_timeout(), None);
+    }
+}
@@ -1,45 +1,165 @@
 //! MCP 服务器配置验证模块
 
-use serde_json::Value;
+use reqwest::header::HeaderName;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use super::{normalize_transport, McpTransport};
+
+/// 字段级的验证错误，添加服务器的表单可以据此定位到具体字段
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpecError {
+    /// 出错的字段，例如 "command"、"args[1]"、"env.API_KEY"；整个定义出错时为空
+    pub field: String,
+    pub message: String,
+}
+
+impl SpecError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SpecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.field, self.message)
+        }
+    }
+}
 
 /// 验证服务器规范
 pub fn validate_server_spec(spec: &Value) -> Result<(), String> {
-    if !spec.is_object() {
-        return Err("MCP 服务器定义必须为 JSON 对象".into());
+    let errors = validate_spec_fields(spec);
+    if errors.is_empty() {
+        return Ok(());
     }
+    Err(errors
+        .iter()
+        .map(SpecError::to_string)
+        .collect::<Vec<_>>()
+        .join("；"))
+}
 
-    let obj = spec.as_object().unwrap();
+/// 验证服务器规范，返回所有字段错误（为空表示有效）
+pub fn validate_spec_fields(spec: &Value) -> Vec<SpecError> {
+    // 别名和其他工具的 URL 字段先转换为统一格式
+    let spec = normalize_transport(spec);
+    let Some(obj) = spec.as_object() else {
+        return vec![SpecError::new("", "MCP 服务器定义必须为 JSON 对象")];
+    };
+    let mut errors = Vec::new();
 
     // 验证传输类型
-    let t_opt = obj.get("type").and_then(|x| x.as_str());
-    let is_stdio = t_opt.map(|t| t == "stdio").unwrap_or(true);
-    let is_http = t_opt.map(|t| t == "http").unwrap_or(false);
-    let is_sse = t_opt.map(|t| t == "sse").unwrap_or(false);
-
-    if !(is_stdio || is_http || is_sse) {
-        return Err("传输类型必须是 'stdio'、'http' 或 'sse'".into());
-    }
+    let Ok(transport) = McpTransport::from_spec(&spec) else {
+        errors.push(SpecError::new(
+            "type",
+            "传输类型必须是 'stdio'、'http' 或 'sse'",
+        ));
+        return errors;
+    };
 
-    // stdio 类型必须有 command
-    if is_stdio {
-        let cmd = obj.get("command").and_then(|x| x.as_str()).unwrap_or("");
-        if cmd.is_empty() {
-            return Err("stdio 类型的 MCP 服务器缺少 command 字段".into());
+    if transport == McpTransport::Stdio {
+        // stdio 类型必须有 command，参数写在 args 中
+        match obj.get("command") {
+            Some(Value::String(cmd)) if !cmd.trim().is_empty() => {}
+            Some(Value::String(_)) | None => errors.push(SpecError::new(
+                "command",
+                "stdio 类型的 MCP 服务器缺少 command 字段",
+            )),
+            Some(_) => errors.push(SpecError::new("command", "command 必须是字符串")),
         }
-    }
-
-    // http/sse 类型必须有 url
-    if is_http || is_sse {
-        let url = obj.get("url").and_then(|x| x.as_str()).unwrap_or("");
-        if url.is_empty() {
-            return Err(format!(
-                "{} 类型的 MCP 服务器缺少 url 字段",
-                if is_http { "http" } else { "sse" }
+        match obj.get("args") {
+            None => {}
+            Some(Value::Array(args)) => {
+                for (i, arg) in args.iter().enumerate() {
+                    if !arg.is_string() {
+                        errors.push(SpecError::new(format!("args[{}]", i), "参数必须是字符串"));
+                    }
+                }
+            }
+            Some(_) => errors.push(SpecError::new("args", "args 必须是字符串数组")),
+        }
+        if obj.get("cwd").is_some_and(|cwd| !cwd.is_string()) {
+            errors.push(SpecError::new("cwd", "cwd 必须是字符串"));
+        }
+        if obj.contains_key("url") {
+            errors.push(SpecError::new(
+                "url",
+                "stdio 类型的服务器不使用 url，远程服务器请将 type 设为 http 或 sse",
             ));
         }
+    } else {
+        if obj.contains_key("command") {
+            errors.push(SpecError::new(
+                "command",
+                format!("{} 类型的服务器不使用 command", transport.as_str()),
+            ));
+        }
+        // http/sse 类型必须有有效的 url
+        match obj.get("url").and_then(Value::as_str).map(str::trim) {
+            None | Some("") => errors.push(SpecError::new(
+                "url",
+                format!("{} 类型的 MCP 服务器缺少 url 字段", transport.as_str()),
+            )),
+            Some(url) => match reqwest::Url::parse(url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") && parsed.has_host() => {}
+                Ok(_) => errors.push(SpecError::new(
+                    "url",
+                    "url 必须是以 http:// 或 https:// 开头的地址",
+                )),
+                Err(e) => errors.push(SpecError::new("url", format!("url 无效: {}", e))),
+            },
+        }
+        check_string_map(
+            obj,
+            "headers",
+            |name| HeaderName::from_bytes(name.as_bytes()).is_ok(),
+            "不是有效的 HTTP 头名称",
+            &mut errors,
+        );
     }
 
-    Ok(())
+    check_string_map(obj, "env", is_env_name, "不是有效的环境变量名", &mut errors);
+    errors
+}
+
+fn is_env_name(name: &str) -> bool {
+    !name.is_empty() && !name.contains(['=', '\0'])
+}
+
+/// 检查 env/headers 这类名称到字符串值的映射
+fn check_string_map(
+    obj: &Map<String, Value>,
+    block: &str,
+    valid_name: impl Fn(&str) -> bool,
+    name_error: &str,
+    errors: &mut Vec<SpecError>,
+) {
+    let Some(value) = obj.get(block) else {
+        return;
+    };
+    let Some(map) = value.as_object() else {
+        errors.push(SpecError::new(
+            block,
+            format!("{} 必须是名称到字符串值的对象", block),
+        ));
+        return;
+    };
+    for (name, value) in map {
+        let field = format!("{}.{}", block, name);
+        if !valid_name(name) {
+            errors.push(SpecError::new(&field, format!("'{}' {}", name, name_error)));
+        }
+        if !value.is_string() {
+            errors.push(SpecError::new(field, "值必须是字符串"));
+        }
+    }
 }
 
 /// 提取服务器规范（移除 UI 辅助字段）
@@ -68,3 +188,47 @@ pub fn extract_server_spec(entry: &Value) -> Result<Value, String> {
 
     Ok(spec)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn fields(spec: Value) -> Vec<String> {
+        validate_spec_fields(&spec)
+            .into_iter()
+            .map(|error| error.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_spec_fields() {
+        assert!(fields(json!({ "command": "npx", "args": ["-y", "server"] })).is_empty());
+        assert!(fields(json!({ "type": "sse", "url": "https://example.com/sse" })).is_empty());
+
+        assert_eq!(
+            fields(json!({ "command": " ", "args": ["ok", 1], "env": { "A=B": "x", "N": 2 } })),
+            vec!["command", "args[1]", "env.A=B", "env.N"]
+        );
+        assert_eq!(
+            fields(
+                json!({ "type": "http", "url": "ftp://example.com", "headers": { "Bad Header": "x" } })
+            ),
+            vec!["url", "headers.Bad Header"]
+        );
+        assert_eq!(fields(json!({ "type": "ws" })), vec!["type"]);
+        // 其他工具的别名和字段
+        assert!(
+            fields(json!({ "type": "streamable-http", "serverUrl": "https://x.example/mcp" }))
+                .is_empty()
+        );
+        assert_eq!(
+            fields(json!({ "command": "npx", "url": "https://x.example/mcp" })),
+            vec!["url"]
+        );
+        assert_eq!(
+            validate_server_spec(&json!({ "type": "http" })).unwrap_err(),
+            "url: http 类型的 MCP 服务器缺少 url 字段"
+        );
+    }
+}
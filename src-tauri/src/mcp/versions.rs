@@ -0,0 +1,380 @@
+//! MCP 服务器版本锁定与更新检查
+//!
+//! 通过 npx 或 uvx 启动的服务器，包名写在 `args` 中，版本锁定也写在包名上：
+//! npm 为 `name@1.2.3`，PyPI 为 `name@1.2.3`（`--from` 时为 `name==1.2.3`）。
+//! 检查更新时查询 npm / PyPI 上的最新版本；用户确认后再把锁定的版本改为新版本。
+//! 未锁定版本（或使用 `latest` 这类标签）的服务器每次启动都会使用最新版本，
+//! 只报告最新版本，不提示更新。
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::time::Duration;
+
+use super::registry::{self, RegistryEntry};
+
+/// 查询包版本的超时时间
+const QUERY_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// npx 中带值的选项
+const NPX_VALUE_FLAGS: &[&str] = &["--package", "-p", "--registry", "--cache"];
+/// uvx 中带值的选项（`--from` 单独处理）
+const UVX_VALUE_FLAGS: &[&str] = &["--with", "--python", "-p", "--index", "--index-url"];
+
+/// 包所在的仓库
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PackageRegistry {
+    Npm,
+    Pypi,
+}
+
+/// 服务器配置中的包
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackageRef {
+    pub registry: PackageRegistry,
+    pub name: String,
+    /// 包名上写的版本（可能是 `latest` 这类标签）
+    pub version: Option<String>,
+    /// 包在 `args` 中的位置
+    arg_index: usize,
+    /// 名称与版本之间的分隔符
+    separator: &'static str,
+}
+
+impl PackageRef {
+    /// 是否锁定到具体版本
+    pub fn is_pinned(&self) -> bool {
+        self.version
+            .as_deref()
+            .is_some_and(|v| v.starts_with(|c: char| c.is_ascii_digit()))
+    }
+}
+
+/// 一个服务器的更新检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateCheck {
+    pub id: String,
+    pub registry: PackageRegistry,
+    pub package: String,
+    pub current: Option<String>,
+    pub pinned: bool,
+    pub latest: Option<String>,
+    pub update_available: bool,
+    /// 查询失败的原因
+    pub error: Option<String>,
+}
+
+/// 从 npm 包参数中拆出名称和版本（`@scope/name@1.0.0`）
+fn split_npm(arg: &str) -> (String, Option<String>) {
+    let search_from = usize::from(arg.starts_with('@'));
+    match arg[search_from..].find('@') {
+        Some(pos) => {
+            let pos = search_from + pos;
+            (arg[..pos].to_string(), Some(arg[pos + 1..].to_string()))
+        }
+        None => (arg.to_string(), None),
+    }
+}
+
+/// 从 PyPI 包参数中拆出名称、版本和分隔符（`name==1.0.0` 或 `name@1.0.0`）
+fn split_pypi(arg: &str) -> (String, Option<String>, &'static str) {
+    for separator in ["==", "@"] {
+        if let Some((name, version)) = arg.split_once(separator) {
+            return (name.to_string(), Some(version.to_string()), separator);
+        }
+    }
+    (arg.to_string(), None, "@")
+}
+
+/// 找出 npx/uvx 服务器的包
+pub fn package_ref(spec: &Value) -> Option<PackageRef> {
+    let command = spec.get("command")?.as_str()?;
+    let program = std::path::Path::new(command)
+        .file_stem()?
+        .to_str()?
+        .to_lowercase();
+    let registry = match program.as_str() {
+        "npx" => PackageRegistry::Npm,
+        "uvx" => PackageRegistry::Pypi,
+        _ => return None,
+    };
+    let value_flags = match registry {
+        PackageRegistry::Npm => NPX_VALUE_FLAGS,
+        PackageRegistry::Pypi => UVX_VALUE_FLAGS,
+    };
+    let args: Vec<&str> = spec
+        .get("args")?
+        .as_array()?
+        .iter()
+        .map(|arg| arg.as_str().unwrap_or_default())
+        .collect();
+
+    // 显式指定包（npx --package / uvx --from）时以它为准，否则为第一个非选项参数
+    let explicit = match registry {
+        PackageRegistry::Npm => ["--package", "-p"].as_slice(),
+        PackageRegistry::Pypi => ["--from"].as_slice(),
+    };
+    let mut index = None;
+    let mut explicit_flag = false;
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i];
+        if explicit.contains(&arg) {
+            index = Some(i + 1);
+            explicit_flag = true;
+            break;
+        }
+        if value_flags.contains(&arg) {
+            i += 2;
+            continue;
+        }
+        if !arg.starts_with('-') {
+            index = Some(i);
+            break;
+        }
+        i += 1;
+    }
+    let arg_index = index?;
+    let arg = *args.get(arg_index)?;
+
+    let (name, version, separator) = match registry {
+        PackageRegistry::Npm => {
+            let (name, version) = split_npm(arg);
+            (name, version, "@")
+        }
+        // --from 的值是依赖声明，版本只能用 ==
+        PackageRegistry::Pypi if explicit_flag => {
+            let (name, version, _) = split_pypi(arg);
+            (name, version, "==")
+        }
+        PackageRegistry::Pypi => split_pypi(arg),
+    };
+    (!name.is_empty()).then_some(PackageRef {
+        registry,
+        name,
+        version: version.filter(|v| !v.is_empty()),
+        arg_index,
+        separator,
+    })
+}
+
+/// 将服务器配置锁定到指定版本
+pub fn pin_version(spec: &Value, version: &str) -> Result<Value, String> {
+    let package = package_ref(spec).ok_or("只有通过 npx 或 uvx 启动的服务器可以锁定版本")?;
+    let version = version.trim();
+    if version.is_empty() {
+        return Err("版本号不能为空".to_string());
+    }
+    let mut spec = spec.clone();
+    spec["args"][package.arg_index] =
+        Value::from(format!("{}{}{}", package.name, package.separator, version));
+    Ok(spec)
+}
+
+/// 比较两个版本号（`1.2.3`、`1.2.3-beta.1`），预发布版本低于对应的正式版本
+fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| {
+        let v = v.trim().trim_start_matches('v');
+        let (release, pre) = match v.split_once(['-', '+']) {
+            Some((release, pre)) => (release, !pre.is_empty()),
+            None => (v, false),
+        };
+        let parts: Vec<u64> = release
+            .split('.')
+            .map(|part| {
+                let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().unwrap_or(0)
+            })
+            .collect();
+        (parts, pre)
+    };
+    let (a_parts, a_pre) = parse(a);
+    let (b_parts, b_pre) = parse(b);
+    let len = a_parts.len().max(b_parts.len());
+    for i in 0..len {
+        let ordering = a_parts
+            .get(i)
+            .unwrap_or(&0)
+            .cmp(b_parts.get(i).unwrap_or(&0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    // 同一版本号：正式版本高于预发布版本
+    b_pre.cmp(&a_pre)
+}
+
+/// 查询包的最新版本
+async fn latest_version(
+    client: &reqwest::Client,
+    registry: PackageRegistry,
+    name: &str,
+) -> Result<String, String> {
+    let (url, pointer) = match registry {
+        PackageRegistry::Npm => (
+            format!(
+                "https://registry.npmjs.org/{}/latest",
+                name.replace('/', "%2F")
+            ),
+            "/version",
+        ),
+        PackageRegistry::Pypi => (
+            format!("https://pypi.org/pypi/{}/json", name),
+            "/info/version",
+        ),
+    };
+    let response = client
+        .get(&url)
+        .send()
+        .await
+        .map_err(|e| format!("查询 {} 的最新版本失败: {}", name, e))?;
+    if !response.status().is_success() {
+        return Err(format!(
+            "查询 {} 的最新版本失败: HTTP {}",
+            name,
+            response.status()
+        ));
+    }
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("解析 {} 的版本信息失败: {}", name, e))?;
+    body.pointer(pointer)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("{} 的版本信息中没有版本号", name))
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(QUERY_TIMEOUT)
+        .user_agent(concat!("any-code/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .map_err(|e| format!("创建 HTTP 客户端失败: {}", e))
+}
+
+/// 检查注册表中所有 npx/uvx 服务器的更新，按服务器 ID 排序
+pub async fn check_updates() -> Result<Vec<UpdateCheck>, String> {
+    let registry = registry::read_registry()?;
+    let client = http_client()?;
+
+    let mut packages: Vec<(String, PackageRef)> = registry
+        .servers
+        .values()
+        .filter_map(|entry| Some((entry.id.clone(), package_ref(&entry.server)?)))
+        .collect();
+    packages.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let queries = packages
+        .iter()
+        .map(|(_, package)| latest_version(&client, package.registry, &package.name));
+    let results = futures::future::join_all(queries).await;
+
+    Ok(packages
+        .into_iter()
+        .zip(results)
+        .map(|((id, package), result)| {
+            let pinned = package.is_pinned();
+            let (latest, error) = match result {
+                Ok(latest) => (Some(latest), None),
+                Err(e) => (None, Some(e)),
+            };
+            let update_available = match (&package.version, &latest) {
+                (Some(current), Some(latest)) if pinned => {
+                    compare_versions(latest, current) == Ordering::Greater
+                }
+                _ => false,
+            };
+            UpdateCheck {
+                id,
+                registry: package.registry,
+                package: package.name,
+                current: package.version,
+                pinned,
+                latest,
+                update_available,
+                error,
+            }
+        })
+        .collect())
+}
+
+/// 将服务器锁定的版本改为 `version`（为空时使用最新版本），返回更新后的条目
+///
+/// 只修改注册表，调用方负责重新同步到引擎配置。
+pub async fn update_server(id: &str, version: Option<&str>) -> Result<RegistryEntry, String> {
+    let entry =
+        registry::get_server(id)?.ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))?;
+    let package = package_ref(&entry.server)
+        .ok_or_else(|| format!("服务器 '{}' 不是通过 npx 或 uvx 启动的", id))?;
+    let version = match version {
+        Some(version) => version.to_string(),
+        None => latest_version(&http_client()?, package.registry, &package.name).await?,
+    };
+
+    let spec = pin_version(&entry.server, &version)?;
+    registry::upsert_server(id, &entry.name, &spec, None)?;
+    log::info!(
+        "服务器 '{}' 的 {} 已锁定到版本 {}",
+        id,
+        package.name,
+        version
+    );
+    registry::get_server(id)?.ok_or_else(|| format!("注册表中不存在服务器 '{}'", id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_package_ref_and_pin() {
+        let spec = json!({
+            "command": "npx",
+            "args": ["-y", "@modelcontextprotocol/server-filesystem@0.6.2", "/tmp"]
+        });
+        let package = package_ref(&spec).unwrap();
+        assert_eq!(package.registry, PackageRegistry::Npm);
+        assert_eq!(package.name, "@modelcontextprotocol/server-filesystem");
+        assert_eq!(package.version.as_deref(), Some("0.6.2"));
+        assert!(package.is_pinned());
+        assert_eq!(
+            pin_version(&spec, "0.7.0").unwrap()["args"],
+            json!([
+                "-y",
+                "@modelcontextprotocol/server-filesystem@0.7.0",
+                "/tmp"
+            ])
+        );
+
+        let latest = package_ref(&json!({ "command": "npx", "args": ["@playwright/mcp@latest"] }));
+        assert!(!latest.unwrap().is_pinned());
+
+        let uvx = json!({
+            "command": "uvx",
+            "args": ["--python", "3.12", "--from", "mcp-server-git==0.6.0", "mcp-server-git"]
+        });
+        let package = package_ref(&uvx).unwrap();
+        assert_eq!(package.name, "mcp-server-git");
+        assert_eq!(
+            pin_version(&uvx, "0.7.0").unwrap()["args"][3],
+            json!("mcp-server-git==0.7.0")
+        );
+        assert_eq!(
+            pin_version(
+                &json!({ "command": "uvx", "args": ["mcp-server-time"] }),
+                "1.0"
+            )
+            .unwrap()["args"],
+            json!(["mcp-server-time@1.0"])
+        );
+        assert!(package_ref(&json!({ "command": "node", "args": ["server.js"] })).is_none());
+
+        assert_eq!(compare_versions("1.10.0", "1.9.3"), Ordering::Greater);
+        assert_eq!(compare_versions("2.0.0-beta.1", "2.0.0"), Ordering::Less);
+        assert_eq!(compare_versions("2025.4.7", "2025.4.7"), Ordering::Equal);
+    }
+}
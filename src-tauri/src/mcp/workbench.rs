@@ -0,0 +1,296 @@
+//! 内置的工作台 MCP 服务器
+//!
+//! 让引擎通过标准 MCP 协议查询工作台状态。引擎以 stdio 方式启动本应用：
+//! `any-code --mcp-workbench [项目路径]`（未给出时使用引擎设置的工作目录，即项目目录），
+//! 提供以下工具：
+//! - `read_project_file`：读取项目中的文件（不能访问项目目录之外的路径）
+//! - `list_checkpoints`：列出项目的检查点（见 `commands::checkpoints`）
+//! - `git_diff`：未提交的改动，或两个提交 / 检查点之间的差异
+//!
+//! 注册表中的服务器 ID 为 [`SERVER_ID`]，通过 `mcp_install_workbench_server` 添加。
+
+use serde_json::{json, Value};
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use crate::commands::git_backend::{backend_for, GitBackend};
+
+/// 服务器模式的命令行标志
+pub const SERVER_FLAG: &str = "--mcp-workbench";
+
+/// 注册表中的服务器 ID
+pub const SERVER_ID: &str = "anycode-workbench";
+
+/// 客户端未给出协议版本时使用的版本
+const DEFAULT_PROTOCOL_VERSION: &str = "2025-03-26";
+
+/// 单个文件最多读取的字节数
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// diff 最多返回的字符数
+const MAX_DIFF_CHARS: usize = 200_000;
+
+/// 列出检查点时默认返回的数量
+const DEFAULT_CHECKPOINT_LIMIT: usize = 50;
+
+/// 注册到引擎的服务器规范
+pub fn server_spec() -> Result<Value, String> {
+    let exe = std::env::current_exe().map_err(|e| format!("获取应用路径失败: {}", e))?;
+    Ok(json!({
+        "type": "stdio",
+        "command": exe.to_string_lossy(),
+        "args": [SERVER_FLAG],
+    }))
+}
+
+fn tool_definitions() -> Value {
+    json!([
+        {
+            "name": "read_project_file",
+            "description": "Read a text file from the current project. Paths are relative to the project root.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string", "description": "File path relative to the project root" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "list_checkpoints",
+            "description": "List the workbench checkpoints (engine-created commits) of the current project, newest first.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "sessionId": { "type": "string", "description": "Only list checkpoints of this session" },
+                    "limit": { "type": "integer", "description": "Maximum number of checkpoints (default 50)" }
+                }
+            }
+        },
+        {
+            "name": "git_diff",
+            "description": "Unified diff of uncommitted changes, or between two commits or checkpoint names when `from` is given.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from": { "type": "string", "description": "Base commit, tag or checkpoint name" },
+                    "to": { "type": "string", "description": "Target commit (default HEAD)" }
+                }
+            }
+        }
+    ])
+}
+
+/// 解析项目内的路径，拒绝指向项目目录之外的路径（包括通过符号链接）
+fn resolve_in_project(root: &Path, relative: &str) -> Result<PathBuf, String> {
+    let root = root
+        .canonicalize()
+        .map_err(|e| format!("无法访问项目目录: {}", e))?;
+    let path = root
+        .join(relative)
+        .canonicalize()
+        .map_err(|e| format!("无法访问文件 '{}': {}", relative, e))?;
+    if !path.starts_with(&root) {
+        return Err(format!("'{}' 不在项目目录中", relative));
+    }
+    Ok(path)
+}
+
+fn read_project_file(root: &Path, args: &Value) -> Result<String, String> {
+    let relative = args["path"].as_str().ok_or("缺少参数 path")?;
+    let path = resolve_in_project(root, relative)?;
+    let metadata = std::fs::metadata(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("'{}' 不是文件", relative));
+    }
+    if metadata.len() > MAX_FILE_BYTES {
+        return Err(format!(
+            "文件过大（{} 字节，上限 {} 字节）",
+            metadata.len(),
+            MAX_FILE_BYTES
+        ));
+    }
+    let content = std::fs::read(&path).map_err(|e| format!("读取文件失败: {}", e))?;
+    Ok(String::from_utf8_lossy(&content).into_owned())
+}
+
+fn list_checkpoints(root: &Path, args: &Value) -> Result<String, String> {
+    let session_id = args["sessionId"].as_str().map(str::to_string);
+    let limit = args["limit"]
+        .as_u64()
+        .map_or(DEFAULT_CHECKPOINT_LIMIT, |limit| limit as usize);
+    let mut checkpoints =
+        futures::executor::block_on(crate::commands::checkpoints::list_checkpoints(
+            root.to_string_lossy().to_string(),
+            session_id,
+        ))?;
+    checkpoints.truncate(limit);
+    serde_json::to_string_pretty(&checkpoints).map_err(|e| e.to_string())
+}
+
+fn git_diff(root: &Path, args: &Value) -> Result<String, String> {
+    let project_path = root.to_string_lossy().to_string();
+    let backend = backend_for(&project_path);
+    let patch = match args["from"].as_str() {
+        Some(from) => {
+            let to = args["to"].as_str().unwrap_or("HEAD");
+            crate::commands::git_diff::validate_revision(from)?;
+            crate::commands::git_diff::validate_revision(to)?;
+            backend.diff_commits(&project_path, from, to)?
+        }
+        None => backend.diff_working_tree(&project_path)?,
+    };
+    if patch.is_empty() {
+        return Ok("没有差异".to_string());
+    }
+    if patch.chars().count() > MAX_DIFF_CHARS {
+        let truncated: String = patch.chars().take(MAX_DIFF_CHARS).collect();
+        return Ok(format!(
+            "{}\n... diff 超过 {} 个字符，已截断",
+            truncated, MAX_DIFF_CHARS
+        ));
+    }
+    Ok(patch)
+}
+
+fn call_tool(root: &Path, name: &str, args: &Value) -> Result<String, String> {
+    match name {
+        "read_project_file" => read_project_file(root, args),
+        "list_checkpoints" => list_checkpoints(root, args),
+        "git_diff" => git_diff(root, args),
+        _ => Err(format!("未知工具: {}", name)),
+    }
+}
+
+/// 处理一条 JSON-RPC 消息，通知（没有 id）返回 None
+fn handle(root: &Path, message: &Value) -> Option<Value> {
+    let id = message.get("id")?.clone();
+    let method = message["method"].as_str().unwrap_or_default();
+    let params = &message["params"];
+
+    let result = match method {
+        "initialize" => json!({
+            "protocolVersion": params["protocolVersion"]
+                .as_str()
+                .unwrap_or(DEFAULT_PROTOCOL_VERSION),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": SERVER_ID, "version": env!("CARGO_PKG_VERSION") },
+        }),
+        "ping" => json!({}),
+        "tools/list" => json!({ "tools": tool_definitions() }),
+        "tools/call" => {
+            let name = params["name"].as_str().unwrap_or_default();
+            let args = params.get("arguments").cloned().unwrap_or(json!({}));
+            match call_tool(root, name, &args) {
+                Ok(text) => json!({ "content": [{ "type": "text", "text": text }] }),
+                Err(e) => json!({ "content": [{ "type": "text", "text": e }], "isError": true }),
+            }
+        }
+        _ => {
+            return Some(json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+            }))
+        }
+    };
+    Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+}
+
+fn serve(root: &Path) -> Result<(), String> {
+    let stdin = std::io::stdin();
+    let mut stdout = std::io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|e| format!("读取 stdin 失败: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str::<Value>(&line) {
+            Ok(message) => handle(root, &message),
+            Err(e) => Some(json!({
+                "jsonrpc": "2.0",
+                "id": null,
+                "error": { "code": -32700, "message": format!("Parse error: {}", e) },
+            })),
+        };
+        if let Some(response) = response {
+            writeln!(stdout, "{}", response)
+                .and_then(|_| stdout.flush())
+                .map_err(|e| format!("写入 stdout 失败: {}", e))?;
+        }
+    }
+    Ok(())
+}
+
+/// 以服务器模式启动时（第一个参数为 [`SERVER_FLAG`]）运行服务器，返回退出码；否则返回 None
+pub fn run_from_args() -> Option<i32> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) != Some(SERVER_FLAG) {
+        return None;
+    }
+    let root = match args.get(1) {
+        Some(path) => PathBuf::from(path),
+        None => match std::env::current_dir() {
+            Ok(dir) => dir,
+            Err(e) => {
+                eprintln!("获取工作目录失败: {}", e);
+                return Some(1);
+            }
+        },
+    };
+    Some(match serve(&root) {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_handle_tool_calls() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "hello").unwrap();
+
+        let init = handle(
+            dir.path(),
+            &json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {} }),
+        )
+        .unwrap();
+        assert_eq!(init["result"]["serverInfo"]["name"], json!(SERVER_ID));
+        assert!(handle(
+            dir.path(),
+            &json!({ "jsonrpc": "2.0", "method": "notifications/initialized" })
+        )
+        .is_none());
+
+        let read = |path: &str| {
+            handle(
+                dir.path(),
+                &json!({
+                    "jsonrpc": "2.0",
+                    "id": 2,
+                    "method": "tools/call",
+                    "params": { "name": "read_project_file", "arguments": { "path": path } }
+                }),
+            )
+            .unwrap()
+        };
+        assert_eq!(
+            read("notes.txt")["result"]["content"][0]["text"],
+            json!("hello")
+        );
+        assert_eq!(read("../outside.txt")["result"]["isError"], json!(true));
+
+        let unknown = handle(
+            dir.path(),
+            &json!({ "jsonrpc": "2.0", "id": 3, "method": "resources/list" }),
+        )
+        .unwrap();
+        assert_eq!(unknown["error"]["code"], json!(-32601));
+    }
+}
@@ -0,0 +1,262 @@
+/// Graceful shutdown of engine processes
+///
+/// Engine CLIs are started as the leader of their own process group (see
+/// [`new_process_group`]), so an interrupt reaches the CLI together with the
+/// MCP servers and tools it started: SIGINT to the group on Unix, CTRL_BREAK to
+/// the console process group on Windows (which requires the engine to have been
+/// spawned with CREATE_NEW_PROCESS_GROUP).
+///
+/// [`shutdown`] sends the interrupt, gives the engine [`GRACE_PERIOD`] to save
+/// its session and exit, then kills whatever is left of the group (or of the
+/// Job Object on Windows) so that no child outlives the session.
+use super::JobObject;
+use serde::Serialize;
+use std::time::Duration;
+use tokio::process::Child;
+
+#[cfg(unix)]
+use std::process::Command;
+
+/// Time an engine gets to exit after the interrupt before it is killed
+pub const GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Time the process tree gets to disappear after a hard kill
+const KILL_TIMEOUT: Duration = Duration::from_secs(2);
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+#[cfg(target_os = "windows")]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x00000200;
+
+#[cfg(target_os = "windows")]
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// How an engine process went away
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Shutdown {
+    /// The engine exited on the interrupt
+    Graceful,
+    /// The engine ignored the interrupt and was killed
+    Forced,
+}
+
+/// Spawn the engine as the leader of a new process group, without a console window
+pub fn new_process_group(cmd: &mut tokio::process::Command) {
+    #[cfg(unix)]
+    {
+        cmd.process_group(0);
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        cmd.creation_flags(CREATE_NO_WINDOW | CREATE_NEW_PROCESS_GROUP);
+    }
+}
+
+/// Whether a child process has exited (reaping it if so)
+pub fn child_exited(child: &mut Child) -> bool {
+    child.try_wait().map_or(true, |status| status.is_some())
+}
+
+/// Send the interrupt to the process group led by `pid`
+pub fn interrupt(pid: u32) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        if signal_group(pid, "INT") {
+            return Ok(());
+        }
+        // Not a group leader (spawned before process groups were used): signal the process only
+        let output = Command::new("kill")
+            .args(["-INT", &pid.to_string()])
+            .output()
+            .map_err(|e| format!("Failed to execute kill: {}", e))?;
+        if !output.status.success() {
+            return Err(format!(
+                "kill -INT failed for PID {}: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr).trim()
+            ));
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        windows_console::send_ctrl_break(pid)
+    }
+}
+
+/// Interrupt an engine, wait for it to exit and kill whatever is left of its process tree
+///
+/// `exited` reports whether the engine process itself is gone; it is polled
+/// until the grace period runs out.
+pub async fn shutdown(
+    pid: u32,
+    job_object: Option<&JobObject>,
+    mut exited: impl FnMut() -> bool,
+) -> Shutdown {
+    log::info!("Interrupting engine process group {}", pid);
+    if let Err(e) = interrupt(pid) {
+        log::warn!("Failed to interrupt PID {}: {}", pid, e);
+    }
+
+    let outcome = if wait_for(GRACE_PERIOD, &mut exited).await {
+        log::info!("Engine process {} exited after the interrupt", pid);
+        Shutdown::Graceful
+    } else {
+        log::warn!(
+            "Engine process {} still running {}s after the interrupt, killing it",
+            pid,
+            GRACE_PERIOD.as_secs()
+        );
+        Shutdown::Forced
+    };
+
+    // MCP servers and tools may outlive a CLI that exited cleanly, so always clear the tree
+    kill_remaining(pid, job_object, outcome == Shutdown::Forced);
+    if !wait_for(KILL_TIMEOUT, &mut exited).await {
+        log::error!("Engine process {} did not exit after being killed", pid);
+    }
+    outcome
+}
+
+async fn wait_for(timeout: Duration, exited: &mut impl FnMut() -> bool) -> bool {
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        if exited() {
+            return true;
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// Kill the process group led by `pid`, and the leader's process tree while it
+/// still runs (which is how its children are reached on Windows)
+pub fn kill_group(pid: u32, leader_running: bool) {
+    kill_remaining(pid, None, leader_running);
+}
+
+fn kill_remaining(pid: u32, job_object: Option<&JobObject>, leader_running: bool) {
+    #[cfg(unix)]
+    {
+        let _ = job_object;
+        signal_group(pid, "KILL");
+        if leader_running {
+            if let Err(e) = crate::commands::claude::kill_process_tree(pid) {
+                log::warn!("Failed to kill PID {}: {}", pid, e);
+            }
+        }
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        match job_object {
+            Some(job) => {
+                if let Err(e) = job.terminate_all(1) {
+                    log::warn!("Failed to terminate Job Object of PID {}: {}", pid, e);
+                }
+            }
+            None if leader_running => {
+                if let Err(e) = crate::commands::claude::kill_process_tree(pid) {
+                    log::warn!("Failed to kill process tree of PID {}: {}", pid, e);
+                }
+            }
+            None => {}
+        }
+    }
+}
+
+/// Send a signal to every process of the group led by `pid`
+#[cfg(unix)]
+fn signal_group(pid: u32, signal: &str) -> bool {
+    Command::new("kill")
+        .args([&format!("-{}", signal), "--", &format!("-{}", pid)])
+        .output()
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(target_os = "windows")]
+mod windows_console {
+    use std::sync::Mutex;
+    use windows::Win32::System::Console::{
+        AttachConsole, FreeConsole, GenerateConsoleCtrlEvent, SetConsoleCtrlHandler,
+        CTRL_BREAK_EVENT,
+    };
+
+    /// A process is attached to at most one console, so attaching is serialized
+    static CONSOLE_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Send CTRL_BREAK to the console process group led by `pid`
+    ///
+    /// The app has no console of its own: attach to the engine's hidden console,
+    /// ignore the event ourselves, raise it for the engine's group and detach.
+    pub fn send_ctrl_break(pid: u32) -> Result<(), String> {
+        let _guard = CONSOLE_LOCK.lock().map_err(|e| e.to_string())?;
+        unsafe {
+            let _ = FreeConsole();
+            AttachConsole(pid).map_err(|e| format!("Failed to attach to console: {:?}", e))?;
+            let _ = SetConsoleCtrlHandler(None, true);
+            let result = GenerateConsoleCtrlEvent(CTRL_BREAK_EVENT, pid)
+                .map_err(|e| format!("Failed to send CTRL_BREAK: {:?}", e));
+            let _ = FreeConsole();
+            let _ = SetConsoleCtrlHandler(None, false);
+            result
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_shutdown_interrupts_group() {
+        let mut cmd = tokio::process::Command::new("sleep");
+        cmd.arg("30");
+        new_process_group(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let pid = child.id().unwrap();
+
+        let outcome = shutdown(pid, None, || child_exited(&mut child)).await;
+
+        assert_eq!(outcome, Shutdown::Graceful);
+        assert!(child_exited(&mut child));
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_kill_group_reaches_grandchildren() {
+        use tokio::io::AsyncBufReadExt;
+
+        // Exited and zombie processes both count as gone
+        let alive = |pid: u32| {
+            std::fs::read_to_string(format!("/proc/{}/stat", pid))
+                .map(|stat| {
+                    !stat[stat.rfind(')').unwrap() + 1..]
+                        .trim_start()
+                        .starts_with('Z')
+                })
+                .unwrap_or(false)
+        };
+
+        let mut cmd = tokio::process::Command::new("sh");
+        cmd.args(["-c", "sleep 30 & echo $!; wait"])
+            .stdout(std::process::Stdio::piped());
+        new_process_group(&mut cmd);
+        let mut child = cmd.spawn().unwrap();
+        let pid = child.id().unwrap();
+        let stdout = child.stdout.take().unwrap();
+        let mut lines = tokio::io::BufReader::new(stdout).lines();
+        let grandchild: u32 = lines.next_line().await.unwrap().unwrap().parse().unwrap();
+        assert!(alive(grandchild));
+
+        kill_group(pid, true);
+        child.wait().await.unwrap();
+        assert!(wait_for(KILL_TIMEOUT, &mut || !alive(grandchild)).await);
+    }
+}
@@ -100,6 +100,66 @@ pub mod windows_job {
             }
         }
 
+        /// Apply memory and CPU ceilings to the job
+        ///
+        /// # Arguments
+        /// * `job_memory_bytes` - Committed memory limit for all processes in the job combined
+        /// * `process_memory_bytes` - Committed memory limit for each individual process
+        /// * `cpu_rate_percent` - Hard cap on CPU usage (1-100, percent of all cores)
+        pub fn set_resource_limits(
+            &self,
+            job_memory_bytes: Option<usize>,
+            process_memory_bytes: Option<usize>,
+            cpu_rate_percent: Option<u32>,
+        ) -> Result<(), String> {
+            unsafe {
+                let mut info: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+
+                // Keep the kill-on-close behavior, the limits are additive
+                info.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+
+                if let Some(bytes) = job_memory_bytes {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_JOB_MEMORY;
+                    info.JobMemoryLimit = bytes;
+                }
+
+                if let Some(bytes) = process_memory_bytes {
+                    info.BasicLimitInformation.LimitFlags |= JOB_OBJECT_LIMIT_PROCESS_MEMORY;
+                    info.ProcessMemoryLimit = bytes;
+                }
+
+                SetInformationJobObject(
+                    self.handle,
+                    JobObjectExtendedLimitInformation,
+                    &info as *const _ as *const _,
+                    std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+                )
+                .map_err(|e| format!("Failed to set job memory limits: {:?}", e))?;
+
+                if let Some(percent) = cpu_rate_percent {
+                    let mut cpu_info: JOBOBJECT_CPU_RATE_CONTROL_INFORMATION = std::mem::zeroed();
+                    cpu_info.ControlFlags = JOB_OBJECT_CPU_RATE_CONTROL_ENABLE
+                        | JOB_OBJECT_CPU_RATE_CONTROL_HARD_CAP;
+                    // CpuRate is expressed in 1/100 of a percent
+                    cpu_info.Anonymous.CpuRate = percent.clamp(1, 100) * 100;
+
+                    SetInformationJobObject(
+                        self.handle,
+                        JobObjectCpuRateControlInformation,
+                        &cpu_info as *const _ as *const _,
+                        std::mem::size_of::<JOBOBJECT_CPU_RATE_CONTROL_INFORMATION>() as u32,
+                    )
+                    .map_err(|e| format!("Failed to set job CPU rate limit: {:?}", e))?;
+                }
+
+                info!(
+                    "Applied resource limits to job object: job_memory={:?}, process_memory={:?}, cpu_rate={:?}%",
+                    job_memory_bytes, process_memory_bytes, cpu_rate_percent
+                );
+                Ok(())
+            }
+        }
+
         /// Terminate all processes in the job
         #[allow(dead_code)]
         pub fn terminate_all(&self, exit_code: u32) -> Result<(), String> {
@@ -156,6 +216,16 @@ pub mod windows_job {
             // No-op on non-Windows platforms
             Ok(())
         }
+
+        pub fn set_resource_limits(
+            &self,
+            _job_memory_bytes: Option<usize>,
+            _process_memory_bytes: Option<usize>,
+            _cpu_rate_percent: Option<u32>,
+        ) -> Result<(), String> {
+            // No-op on non-Windows platforms (see process::limits for rlimit handling)
+            Ok(())
+        }
     }
 }
 
@@ -0,0 +1,361 @@
+/// Persistent process ledger
+///
+/// Every process registered with the `ProcessRegistry` is also recorded on disk
+/// (`~/.anycode/process-ledger.json`) together with a start-time fingerprint.
+/// If the app crashes, the ledger survives and the next launch can find engine
+/// and MCP processes that are still alive from the previous run.
+///
+/// The fingerprint guards against PID reuse: a PID is only considered to be
+/// "ours" if the OS still reports the same process start time for it.
+use super::ProcessInfo;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+lazy_static::lazy_static! {
+    /// Serializes read-modify-write cycles on the ledger file
+    static ref LEDGER_LOCK: Mutex<()> = Mutex::new(());
+}
+
+/// A single ledger record
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LedgerEntry {
+    pub info: ProcessInfo,
+    /// OS-reported process start time, `None` if it could not be determined
+    pub fingerprint: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+struct ProcessLedger {
+    #[serde(default)]
+    entries: Vec<LedgerEntry>,
+}
+
+/// A process from a previous run that is still alive
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanProcess {
+    pub pid: u32,
+    pub info: ProcessInfo,
+    pub recorded_at: DateTime<Utc>,
+    /// Lock files in the project that the orphan may still be holding
+    pub stale_locks: Vec<String>,
+}
+
+fn ledger_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("process-ledger.json"))
+}
+
+fn read_ledger(path: &Path) -> Result<ProcessLedger, String> {
+    load_json_config(path)
+}
+
+fn write_ledger(path: &Path, ledger: &ProcessLedger) -> Result<(), String> {
+    save_json_config(ledger, path)
+}
+
+/// Record a newly registered process
+///
+/// Reads and rewrites the ledger file (and may spawn `ps`), so callers
+/// must not hold the registry lock.
+pub fn record(info: &ProcessInfo) {
+    if let Err(e) = ledger_path().and_then(|path| record_at(&path, info)) {
+        log::warn!("Failed to record PID {} in process ledger: {}", info.pid, e);
+    }
+}
+
+fn record_at(path: &Path, info: &ProcessInfo) -> Result<(), String> {
+    // Taken before locking, so a slow `ps` doesn't hold up other writers
+    let fingerprint = process_fingerprint(info.pid);
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = read_ledger(path)?;
+    ledger.entries.retain(|entry| entry.info.pid != info.pid);
+    ledger.entries.push(LedgerEntry {
+        info: info.clone(),
+        fingerprint,
+        recorded_at: Utc::now(),
+    });
+    write_ledger(path, &ledger)
+}
+
+/// Remove a process from the ledger (it exited or was killed)
+pub fn forget(pid: u32) {
+    if let Err(e) = ledger_path().and_then(|path| forget_at(&path, pid)) {
+        log::warn!("Failed to remove PID {} from process ledger: {}", pid, e);
+    }
+}
+
+fn forget_at(path: &Path, pid: u32) -> Result<(), String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = read_ledger(path)?;
+    let before = ledger.entries.len();
+    ledger.entries.retain(|entry| entry.info.pid != pid);
+    if ledger.entries.len() == before {
+        return Ok(());
+    }
+    write_ledger(path, &ledger)
+}
+
+/// Find ledger entries that are still alive but not tracked by this run
+///
+/// Entries whose process is gone (or whose PID was reused by an unrelated
+/// process) are pruned from the ledger as a side effect.
+pub fn scan_orphans(live_pids: &[u32]) -> Result<Vec<OrphanProcess>, String> {
+    scan_orphans_at(&ledger_path()?, live_pids)
+}
+
+fn scan_orphans_at(path: &Path, live_pids: &[u32]) -> Result<Vec<OrphanProcess>, String> {
+    let _guard = LEDGER_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let mut ledger = read_ledger(path)?;
+    let mut orphans = Vec::new();
+    let before = ledger.entries.len();
+
+    ledger.entries.retain(|entry| {
+        let pid = entry.info.pid;
+        if live_pids.contains(&pid) {
+            return true;
+        }
+
+        let current = process_fingerprint(pid);
+        let alive = match (&current, &entry.fingerprint) {
+            (Some(now), Some(recorded)) => now == recorded,
+            // Without a recorded fingerprint we can only trust liveness
+            (Some(_), None) => true,
+            (None, _) => false,
+        };
+
+        if alive {
+            orphans.push(OrphanProcess {
+                pid,
+                info: entry.info.clone(),
+                recorded_at: entry.recorded_at,
+                stale_locks: find_stale_locks(&entry.info.project_path),
+            });
+        } else {
+            log::info!("Pruning dead process {} from ledger", pid);
+        }
+        alive
+    });
+
+    if ledger.entries.len() != before {
+        write_ledger(path, &ledger)?;
+    }
+
+    Ok(orphans)
+}
+
+/// Look up a single orphan by PID, verifying its fingerprint again
+pub fn find_orphan(pid: u32, live_pids: &[u32]) -> Result<Option<OrphanProcess>, String> {
+    Ok(scan_orphans(live_pids)?
+        .into_iter()
+        .find(|orphan| orphan.pid == pid))
+}
+
+/// Lock files commonly left behind by a process killed mid-operation
+fn find_stale_locks(project_path: &str) -> Vec<String> {
+    if project_path.is_empty() {
+        return Vec::new();
+    }
+
+    let git_dir = Path::new(project_path).join(".git");
+    ["index.lock", "HEAD.lock", "config.lock", "shallow.lock"]
+        .iter()
+        .map(|name| git_dir.join(name))
+        .filter(|path| path.exists())
+        .map(|path| path.to_string_lossy().to_string())
+        .collect()
+}
+
+/// Delete the given lock files, returning the ones that were removed
+pub fn remove_stale_locks(locks: &[String]) -> Vec<String> {
+    locks
+        .iter()
+        .filter(|lock| match std::fs::remove_file(lock) {
+            Ok(_) => {
+                log::info!("Removed stale lock file: {}", lock);
+                true
+            }
+            Err(e) => {
+                log::warn!("Failed to remove stale lock file {}: {}", lock, e);
+                false
+            }
+        })
+        .cloned()
+        .collect()
+}
+
+/// Terminate a process tree by PID, escalating to a hard kill if needed
+pub fn terminate_pid(pid: u32) -> Result<(), String> {
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("taskkill")
+            .args(["/F", "/T", "/PID", &pid.to_string()])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .map_err(|e| format!("Failed to execute taskkill: {}", e))?;
+
+        if !output.status.success() {
+            return Err(format!(
+                "taskkill failed for PID {}: {}",
+                pid,
+                String::from_utf8_lossy(&output.stderr)
+            ));
+        }
+    }
+
+    #[cfg(unix)]
+    {
+        let _ = Command::new("pkill")
+            .args(["-TERM", "-P", &pid.to_string()])
+            .output();
+        let _ = Command::new("kill")
+            .args(["-TERM", &pid.to_string()])
+            .output();
+
+        std::thread::sleep(std::time::Duration::from_millis(1500));
+
+        if process_fingerprint(pid).is_some() {
+            log::warn!("Process {} survived SIGTERM, sending SIGKILL", pid);
+            let _ = Command::new("pkill")
+                .args(["-KILL", "-P", &pid.to_string()])
+                .output();
+            let output = Command::new("kill")
+                .args(["-KILL", &pid.to_string()])
+                .output()
+                .map_err(|e| format!("Failed to execute kill: {}", e))?;
+
+            if !output.status.success() {
+                return Err(format!(
+                    "kill failed for PID {}: {}",
+                    pid,
+                    String::from_utf8_lossy(&output.stderr)
+                ));
+            }
+        }
+    }
+
+    forget(pid);
+    Ok(())
+}
+
+/// Get a stable start-time fingerprint for a PID, `None` if the process is not running
+pub fn process_fingerprint(pid: u32) -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        // Field 22 of /proc/<pid>/stat is the start time in clock ticks since boot.
+        // The command name (field 2) may contain spaces, so split after the last ')'.
+        let stat = std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?;
+        let rest = &stat[stat.rfind(')')? + 1..];
+        rest.split_whitespace().nth(19).map(|s| s.to_string())
+    }
+
+    #[cfg(all(unix, not(target_os = "linux")))]
+    {
+        let output = Command::new("ps")
+            .args(["-o", "lstart=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let started = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        if !output.status.success() || started.is_empty() {
+            return None;
+        }
+        Some(started)
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("wmic")
+            .args([
+                "process",
+                "where",
+                &format!("ProcessId={}", pid),
+                "get",
+                "CreationDate",
+            ])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .ok()?;
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(1) // Skip header
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+            .map(|s| s.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::process::ProcessType;
+
+    fn process_info(pid: u32) -> ProcessInfo {
+        ProcessInfo {
+            run_id: 1,
+            process_type: ProcessType::ClaudeSession {
+                session_id: "s1".to_string(),
+            },
+            pid,
+            started_at: Utc::now(),
+            project_path: String::new(),
+            task: "task".to_string(),
+            model: "model".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_process_fingerprint() {
+        let pid = std::process::id();
+        let fingerprint = process_fingerprint(pid);
+        assert!(fingerprint.is_some());
+        assert_eq!(process_fingerprint(pid), fingerprint);
+
+        #[cfg(unix)]
+        {
+            let mut child = Command::new("true").spawn().unwrap();
+            let child_pid = child.id();
+            child.wait().unwrap();
+            assert_eq!(process_fingerprint(child_pid), None);
+        }
+    }
+
+    #[test]
+    fn test_ledger_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("process-ledger.json");
+        let pid = std::process::id();
+
+        record_at(&path, &process_info(pid)).unwrap();
+        record_at(&path, &process_info(pid)).unwrap();
+        let ledger = read_ledger(&path).unwrap();
+        assert_eq!(ledger.entries.len(), 1);
+        assert_eq!(ledger.entries[0].fingerprint, process_fingerprint(pid));
+
+        // Tracked by this run: not an orphan
+        assert!(scan_orphans_at(&path, &[pid]).unwrap().is_empty());
+        // Alive with a matching fingerprint: an orphan
+        let orphans = scan_orphans_at(&path, &[]).unwrap();
+        assert_eq!(orphans.len(), 1);
+        assert_eq!(orphans[0].pid, pid);
+
+        // A reused PID no longer matches and is pruned
+        let mut ledger = read_ledger(&path).unwrap();
+        ledger.entries[0].fingerprint = Some("reused".to_string());
+        write_ledger(&path, &ledger).unwrap();
+        assert!(scan_orphans_at(&path, &[]).unwrap().is_empty());
+        assert!(read_ledger(&path).unwrap().entries.is_empty());
+
+        record_at(&path, &process_info(pid)).unwrap();
+        forget_at(&path, pid).unwrap();
+        assert!(read_ledger(&path).unwrap().entries.is_empty());
+    }
+}
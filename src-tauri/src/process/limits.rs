@@ -0,0 +1,597 @@
+/// Resource limits for engine and MCP subprocesses
+///
+/// Limits are configured in `~/.anycode/resource-limits.json` with separate
+/// ceilings for engine CLIs and for the MCP servers they spawn.
+///
+/// Enforcement is platform specific:
+/// - Unix: RLIMIT_DATA / RLIMIT_CPU are set in the child between fork and exec
+///   ([`limit_before_exec`]), so everything the process starts inherits them.
+///   RLIMIT_DATA only counts writable private memory, unlike RLIMIT_AS it leaves
+///   the large virtual reservations of V8-based CLIs alone. macOS does not
+///   enforce data limits, so memory ceilings are rejected there.
+/// - Windows: the engine's Job Object gets the engine's memory limit and CPU hard
+///   cap (covering the engine and everything it spawns). CPU percent caps exist
+///   only there and are rejected on other platforms.
+/// - MCP servers started by the app get the MCP limits, combined with their own
+///   sandbox settings, when they are spawned (see `mcp::sandbox`). MCP servers
+///   an engine starts itself are limited with `prlimit` by the enforcement loop
+///   on Linux, as it discovers them.
+use super::JobObject;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::process::Command;
+use std::sync::Mutex;
+
+#[cfg(target_os = "windows")]
+use std::os::windows::process::CommandExt;
+
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+
+/// Interval of the background loop that applies MCP limits to new descendants
+const ENFORCEMENT_INTERVAL_SECS: u64 = 10;
+
+/// Ceilings for one class of processes; `None` means unlimited
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimits {
+    /// Maximum memory in megabytes
+    pub max_memory_mb: Option<u64>,
+    /// Maximum CPU usage in percent of all cores (Windows only)
+    pub max_cpu_percent: Option<u32>,
+    /// Maximum CPU time in seconds (Unix only, process is killed when exceeded)
+    pub max_cpu_seconds: Option<u64>,
+}
+
+impl ResourceLimits {
+    pub fn is_unlimited(&self) -> bool {
+        self.max_memory_mb.is_none()
+            && self.max_cpu_percent.is_none()
+            && self.max_cpu_seconds.is_none()
+    }
+
+    pub(crate) fn memory_bytes(&self) -> Option<u64> {
+        self.max_memory_mb.map(|mb| mb.saturating_mul(1024 * 1024))
+    }
+
+    /// Check the ceilings; `label` names the process class in errors
+    fn validate(&self, label: &str) -> Result<(), String> {
+        if let Some(percent) = self.max_cpu_percent {
+            if percent == 0 || percent > 100 {
+                return Err(format!(
+                    "{} CPU limit must be between 1 and 100, got {}",
+                    label, percent
+                ));
+            }
+            if !cfg!(target_os = "windows") {
+                return Err(format!(
+                    "{} CPU percent limits are only enforced on Windows",
+                    label
+                ));
+            }
+        }
+        if self.max_memory_mb == Some(0) {
+            return Err(format!("{} memory limit must be greater than 0", label));
+        }
+        if self.max_cpu_seconds == Some(0) {
+            return Err(format!("{} CPU time limit must be greater than 0", label));
+        }
+        Ok(())
+    }
+}
+
+/// Resource limit configuration
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceLimitsConfig {
+    /// Master switch, limits are only applied when enabled
+    #[serde(default)]
+    pub enabled: bool,
+    /// Limits for engine CLIs (claude / codex / gemini)
+    #[serde(default)]
+    pub engine: ResourceLimits,
+    /// Limits for MCP servers spawned by the engines
+    #[serde(default)]
+    pub mcp: ResourceLimits,
+}
+
+impl ResourceLimitsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        self.engine.validate("Engine")?;
+        self.mcp.validate("MCP")
+    }
+
+    /// Limits for engine CLIs, None when nothing is enforced
+    pub fn engine_limits(&self) -> Option<&ResourceLimits> {
+        Some(&self.engine).filter(|limits| self.enabled && !limits.is_unlimited())
+    }
+
+    /// Limits for MCP servers, None when nothing is enforced
+    pub fn mcp_limits(&self) -> Option<&ResourceLimits> {
+        Some(&self.mcp).filter(|limits| self.enabled && !limits.is_unlimited())
+    }
+}
+
+/// Job Object limits of an engine: (job memory, per-process memory, CPU percent)
+///
+/// Only the engine's own ceilings apply to its job; MCP limits never cap the engine.
+fn engine_job_limits(limits: &ResourceLimits) -> (Option<usize>, Option<usize>, Option<u32>) {
+    (
+        limits.memory_bytes().map(|bytes| bytes as usize),
+        None,
+        limits.max_cpu_percent,
+    )
+}
+
+/// Point-in-time resource usage of a tracked process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProcessUsage {
+    pub pid: u32,
+    /// Engine PID this process belongs to (`None` for the engine itself)
+    pub parent_pid: Option<u32>,
+    /// Engine label ("claude", "codex", "gemini") the process tree belongs to
+    pub engine: String,
+    pub memory_bytes: Option<u64>,
+    pub cpu_percent: Option<f32>,
+    /// Whether limits were applied to this process
+    pub limited: bool,
+}
+
+lazy_static::lazy_static! {
+    /// Engine PIDs with limits applied -> engine label
+    static ref TRACKED_ENGINES: Mutex<HashMap<u32, String>> = Mutex::new(HashMap::new());
+    /// Descendant PIDs that already received MCP limits
+    static ref LIMITED_DESCENDANTS: Mutex<HashSet<u32>> = Mutex::new(HashSet::new());
+}
+
+fn config_path() -> Result<std::path::PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("resource-limits.json"))
+}
+
+/// Load the resource limit configuration
+pub fn load_config() -> Result<ResourceLimitsConfig, String> {
+    load_json_config(config_path()?)
+}
+
+/// Save the resource limit configuration
+pub fn save_config(config: &ResourceLimitsConfig) -> Result<(), String> {
+    config.validate()?;
+    save_json_config(config, config_path()?)
+}
+
+/// Set the configured engine limits on an engine command before it is spawned
+pub fn prepare_engine_command(engine: &str, cmd: &mut tokio::process::Command) {
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("[Limits] Failed to load resource limits: {}", e);
+            return;
+        }
+    };
+    let Some(limits) = config.engine_limits() else {
+        return;
+    };
+
+    #[cfg(unix)]
+    if let Err(e) = limit_before_exec(cmd, limits, None) {
+        log::warn!("[Limits] Not limiting {}: {}", engine, e);
+    }
+
+    #[cfg(not(unix))]
+    {
+        // Enforced through the engine's Job Object after spawn
+        let _ = (engine, cmd, limits);
+    }
+}
+
+/// Track a freshly spawned engine process and apply its Job Object limits
+///
+/// Must be called right after spawn (and after the Job Object assignment on
+/// Windows). Unix limits are set before exec by [`prepare_engine_command`].
+pub fn apply_to_engine(engine: &str, pid: u32, job: Option<&JobObject>) {
+    if let Ok(mut tracked) = TRACKED_ENGINES.lock() {
+        tracked.insert(pid, engine.to_string());
+    }
+
+    let config = match load_config() {
+        Ok(config) => config,
+        Err(e) => {
+            log::warn!("[Limits] Failed to load resource limits: {}", e);
+            return;
+        }
+    };
+    let (Some(job), Some(limits)) = (job, config.engine_limits()) else {
+        return;
+    };
+
+    let (job_memory, process_memory, cpu_percent) = engine_job_limits(limits);
+    if let Err(e) = job.set_resource_limits(job_memory, process_memory, cpu_percent) {
+        log::warn!(
+            "[Limits] Failed to limit {} job (PID {}): {}",
+            engine,
+            pid,
+            e
+        );
+    }
+}
+
+/// A Unix resource limit enforcing part of [`ResourceLimits`]
+#[cfg(unix)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rlimit {
+    /// RLIMIT_DATA, in bytes
+    Data,
+    /// RLIMIT_CPU, in seconds
+    Cpu,
+}
+
+#[cfg(target_os = "linux")]
+impl Rlimit {
+    /// `prlimit` argument setting the soft and hard limit to `value`
+    fn prlimit_arg(self, value: u64) -> String {
+        match self {
+            Rlimit::Data => format!("--data={0}:{0}", value),
+            Rlimit::Cpu => format!("--cpu={0}:{0}", value),
+        }
+    }
+}
+
+/// The rlimits enforcing `limits`, each set as both soft and hard limit
+#[cfg(unix)]
+fn rlimits(limits: &ResourceLimits) -> Vec<(Rlimit, u64)> {
+    limits
+        .memory_bytes()
+        .map(|bytes| (Rlimit::Data, bytes))
+        .into_iter()
+        .chain(limits.max_cpu_seconds.map(|secs| (Rlimit::Cpu, secs)))
+        .collect()
+}
+
+/// Set rlimits and priority in the child between fork and exec
+///
+/// Everything the process starts inherits the limits. Fails on macOS when a
+/// memory limit is requested, since it does not enforce data limits, and
+/// when a CPU percent cap is requested, which only Windows enforces.
+#[cfg(unix)]
+pub(crate) fn limit_before_exec(
+    cmd: &mut tokio::process::Command,
+    limits: &ResourceLimits,
+    nice: Option<i32>,
+) -> Result<(), String> {
+    if cfg!(target_os = "macos") && limits.max_memory_mb.is_some() {
+        return Err("macOS does not enforce memory limits on child processes".to_string());
+    }
+    if limits.max_cpu_percent.is_some() {
+        return Err("CPU percent limits are only enforced on Windows".to_string());
+    }
+    let rlimits = rlimits(limits);
+    if rlimits.is_empty() && nice.is_none() {
+        return Ok(());
+    }
+
+    // Only async-signal-safe calls between fork and exec
+    unsafe {
+        cmd.pre_exec(move || {
+            for (resource, value) in &rlimits {
+                let limit = libc::rlimit {
+                    rlim_cur: *value as libc::rlim_t,
+                    rlim_max: *value as libc::rlim_t,
+                };
+                let resource = match resource {
+                    Rlimit::Data => libc::RLIMIT_DATA,
+                    Rlimit::Cpu => libc::RLIMIT_CPU,
+                };
+                if libc::setrlimit(resource, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            if let Some(nice) = nice {
+                if libc::setpriority(libc::PRIO_PROCESS, 0, nice) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+            }
+            Ok(())
+        });
+    }
+    Ok(())
+}
+
+/// Apply rlimits to a running process the app did not spawn (Linux only, no-op elsewhere)
+pub(crate) fn apply_rlimits(pid: u32, limits: &ResourceLimits) -> Result<(), String> {
+    #[cfg(target_os = "linux")]
+    {
+        let rlimits = rlimits(limits);
+        if rlimits.is_empty() {
+            return Ok(());
+        }
+
+        let mut args = vec!["--pid".to_string(), pid.to_string()];
+        args.extend(
+            rlimits
+                .into_iter()
+                .map(|(resource, value)| resource.prlimit_arg(value)),
+        );
+
+        let output = Command::new("prlimit")
+            .args(&args)
+            .output()
+            .map_err(|e| format!("Failed to execute prlimit: {}", e))?;
+
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        log::debug!("[Limits] Applied rlimits to PID {}: {:?}", pid, limits);
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = (pid, limits);
+    }
+
+    Ok(())
+}
+
+/// Start the background loop that applies MCP limits to engine descendants
+/// and forgets engines that have exited
+pub async fn start_enforcement_loop() {
+    loop {
+        tokio::time::sleep(tokio::time::Duration::from_secs(ENFORCEMENT_INTERVAL_SECS)).await;
+
+        let result = tokio::task::spawn_blocking(enforce_once).await;
+        if let Err(e) = result {
+            log::warn!("[Limits] Enforcement pass panicked: {}", e);
+        }
+    }
+}
+
+fn enforce_once() {
+    let engines: Vec<u32> = match TRACKED_ENGINES.lock() {
+        Ok(tracked) => tracked.keys().copied().collect(),
+        Err(_) => return,
+    };
+    if engines.is_empty() {
+        return;
+    }
+
+    let config = load_config().unwrap_or_default();
+    let mcp_limits = config.mcp_limits();
+
+    for pid in engines {
+        if super::ledger::process_fingerprint(pid).is_none() {
+            if let Ok(mut tracked) = TRACKED_ENGINES.lock() {
+                tracked.remove(&pid);
+            }
+            continue;
+        }
+
+        let Some(mcp_limits) = mcp_limits else {
+            continue;
+        };
+
+        for child in list_descendants(pid) {
+            let is_new = LIMITED_DESCENDANTS
+                .lock()
+                .map(|mut seen| seen.insert(child))
+                .unwrap_or(false);
+            if is_new {
+                if let Err(e) = apply_rlimits(child, mcp_limits) {
+                    log::warn!("[Limits] Failed to limit MCP process {}: {}", child, e);
+                }
+            }
+        }
+    }
+
+    // Drop descendants that no longer exist so PID reuse does not skip them
+    if let Ok(mut seen) = LIMITED_DESCENDANTS.lock() {
+        seen.retain(|pid| super::ledger::process_fingerprint(*pid).is_some());
+    }
+}
+
+/// Report resource usage for every tracked engine and its descendants
+pub fn collect_usage() -> Vec<ProcessUsage> {
+    let engines: Vec<(u32, String)> = match TRACKED_ENGINES.lock() {
+        Ok(tracked) => tracked.iter().map(|(pid, e)| (*pid, e.clone())).collect(),
+        Err(_) => return Vec::new(),
+    };
+    let config = load_config().unwrap_or_default();
+    let limited_descendants = LIMITED_DESCENDANTS
+        .lock()
+        .map(|seen| seen.clone())
+        .unwrap_or_default();
+
+    let mut usage = Vec::new();
+    for (pid, engine) in engines {
+        let Some((memory_bytes, cpu_percent)) = sample_process(pid) else {
+            continue;
+        };
+        usage.push(ProcessUsage {
+            pid,
+            parent_pid: None,
+            engine: engine.clone(),
+            memory_bytes,
+            cpu_percent,
+            limited: config.engine_limits().is_some(),
+        });
+
+        for child in list_descendants(pid) {
+            if let Some((memory_bytes, cpu_percent)) = sample_process(child) {
+                usage.push(ProcessUsage {
+                    pid: child,
+                    parent_pid: Some(pid),
+                    engine: engine.clone(),
+                    memory_bytes,
+                    cpu_percent,
+                    limited: limited_descendants.contains(&child),
+                });
+            }
+        }
+    }
+    usage
+}
+
+/// Recursively list the descendants of a process
+fn list_descendants(pid: u32) -> Vec<u32> {
+    let mut result = Vec::new();
+    let mut queue = vec![pid];
+    while let Some(parent) = queue.pop() {
+        for child in list_children(parent) {
+            if !result.contains(&child) && child != pid {
+                result.push(child);
+                queue.push(child);
+            }
+        }
+    }
+    result
+}
+
+fn list_children(pid: u32) -> Vec<u32> {
+    #[cfg(target_os = "windows")]
+    let output = Command::new("wmic")
+        .args([
+            "process",
+            "where",
+            &format!("ParentProcessId={}", pid),
+            "get",
+            "ProcessId",
+        ])
+        .creation_flags(0x08000000) // CREATE_NO_WINDOW
+        .output();
+
+    #[cfg(unix)]
+    let output = Command::new("pgrep")
+        .args(["-P", &pid.to_string()])
+        .output();
+
+    match output {
+        Ok(output) => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.trim().parse::<u32>().ok())
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Sample (memory bytes, cpu percent) for a process, `None` if it is gone
+fn sample_process(pid: u32) -> Option<(Option<u64>, Option<f32>)> {
+    #[cfg(unix)]
+    {
+        let output = Command::new("ps")
+            .args(["-o", "rss=,%cpu=", "-p", &pid.to_string()])
+            .output()
+            .ok()?;
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut fields = stdout.split_whitespace();
+        let rss_kb = fields.next()?.parse::<u64>().ok();
+        let cpu = fields.next().and_then(|v| v.parse::<f32>().ok());
+        Some((rss_kb.map(|kb| kb * 1024), cpu))
+    }
+
+    #[cfg(target_os = "windows")]
+    {
+        let output = Command::new("wmic")
+            .args([
+                "process",
+                "where",
+                &format!("ProcessId={}", pid),
+                "get",
+                "WorkingSetSize",
+            ])
+            .creation_flags(0x08000000) // CREATE_NO_WINDOW
+            .output()
+            .ok()?;
+        let memory = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .skip(1) // Skip header
+            .find_map(|line| line.trim().parse::<u64>().ok())?;
+        Some((Some(memory), None))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_limits() {
+        let mut config = ResourceLimitsConfig {
+            enabled: true,
+            engine: ResourceLimits {
+                max_memory_mb: Some(2048),
+                ..Default::default()
+            },
+            mcp: ResourceLimits {
+                max_cpu_percent: Some(150),
+                ..Default::default()
+            },
+        };
+        // The MCP value is checked even though the engine one is set
+        assert!(config.validate().unwrap_err().starts_with("MCP"));
+
+        // CPU percent caps are only accepted where they are enforced
+        config.mcp.max_cpu_percent = Some(25);
+        assert_eq!(config.validate().is_ok(), cfg!(target_os = "windows"));
+        config.mcp.max_cpu_percent = None;
+        config.mcp.max_cpu_seconds = Some(600);
+        assert!(config.validate().is_ok());
+        config.engine.max_memory_mb = Some(0);
+        assert!(config.validate().unwrap_err().starts_with("Engine"));
+        config.engine.max_memory_mb = None;
+        config.engine.max_cpu_seconds = Some(0);
+        assert!(config.validate().unwrap_err().starts_with("Engine"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_rlimit_mapping() {
+        let limits = ResourceLimits {
+            max_memory_mb: Some(512),
+            max_cpu_seconds: Some(60),
+            ..Default::default()
+        };
+        assert_eq!(
+            rlimits(&limits),
+            vec![(Rlimit::Data, 512 * 1024 * 1024), (Rlimit::Cpu, 60)]
+        );
+        assert!(rlimits(&ResourceLimits::default()).is_empty());
+
+        #[cfg(target_os = "linux")]
+        assert_eq!(Rlimit::Cpu.prlimit_arg(60), "--cpu=60:60");
+
+        let mut cmd = tokio::process::Command::new("true");
+        let percent = ResourceLimits {
+            max_cpu_percent: Some(50),
+            ..Default::default()
+        };
+        assert!(limit_before_exec(&mut cmd, &percent, None).is_err());
+    }
+
+    #[test]
+    fn test_limit_selection() {
+        let mut config = ResourceLimitsConfig {
+            enabled: false,
+            engine: ResourceLimits {
+                max_memory_mb: Some(2048),
+                max_cpu_percent: Some(50),
+                ..Default::default()
+            },
+            mcp: ResourceLimits {
+                max_memory_mb: Some(512),
+                ..Default::default()
+            },
+        };
+        assert!(config.engine_limits().is_none());
+        assert!(config.mcp_limits().is_none());
+
+        config.enabled = true;
+        assert_eq!(config.mcp_limits().and_then(|l| l.max_memory_mb), Some(512));
+        // The MCP ceiling never becomes a per-process cap on the engine job
+        let limits = config.engine_limits().unwrap();
+        assert_eq!(
+            engine_job_limits(limits),
+            (Some(2048 * 1024 * 1024), None, Some(50))
+        );
+
+        config.mcp = ResourceLimits::default();
+        assert!(config.mcp_limits().is_none());
+    }
+}
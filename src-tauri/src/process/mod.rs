@@ -1,4 +1,7 @@
+pub mod interrupt;
 pub mod job_object;
+pub mod ledger;
+pub mod limits;
 pub mod registry;
 
 pub use job_object::JobObject;
@@ -1,3 +1,4 @@
+use super::interrupt::{child_exited, shutdown, Shutdown};
 use super::JobObject;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
@@ -182,7 +183,10 @@ impl ProcessRegistry {
             job_object,
         };
 
+        let info = process_handle.info.clone();
         processes.insert(run_id, process_handle);
+        drop(processes);
+        super::ledger::record(&info);
         Ok(run_id)
     }
 
@@ -217,7 +221,10 @@ impl ProcessRegistry {
             live_output: Arc::new(Mutex::new(String::new())),
         };
 
+        let info = process_handle.info.clone();
         processes.insert(run_id, process_handle);
+        drop(processes);
+        super::ledger::record(&info);
         Ok(run_id)
     }
 
@@ -267,7 +274,10 @@ impl ProcessRegistry {
             job_object,
         };
 
+        let info = process_handle.info.clone();
         processes.insert(run_id, process_handle);
+        drop(processes);
+        super::ledger::record(&info);
         Ok(())
     }
 
@@ -301,11 +311,47 @@ impl ProcessRegistry {
     /// Unregister a process (called when it completes)
     #[allow(dead_code)]
     pub fn unregister_process(&self, run_id: i64) -> Result<(), String> {
-        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
-        processes.remove(&run_id);
+        let removed = self
+            .processes
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(&run_id);
+        if let Some(handle) = removed {
+            super::ledger::forget(handle.info.pid);
+        }
         Ok(())
     }
 
+    /// Adopt a still-running process left over from a previous app run
+    ///
+    /// The process gets a fresh run_id (run IDs are not stable across restarts)
+    /// and no child handle, so it is killed through the PID fallback path.
+    pub fn adopt_process(&self, mut info: ProcessInfo) -> Result<i64, String> {
+        let run_id = self.generate_id()?;
+        info.run_id = run_id;
+
+        let mut processes = self.processes.lock().map_err(|e| e.to_string())?;
+        let process_handle = ProcessHandle {
+            info,
+            child: Arc::new(Mutex::new(None)),
+            live_output: Arc::new(Mutex::new(String::new())),
+            #[cfg(windows)]
+            job_object: None,
+        };
+
+        let info = process_handle.info.clone();
+        processes.insert(run_id, process_handle);
+        drop(processes);
+        super::ledger::record(&info);
+        Ok(run_id)
+    }
+
+    /// Get the PIDs of all tracked processes
+    pub fn get_tracked_pids(&self) -> Result<Vec<u32>, String> {
+        let processes = self.processes.lock().map_err(|e| e.to_string())?;
+        Ok(processes.values().map(|handle| handle.info.pid).collect())
+    }
+
     /// Get all running processes
     #[allow(dead_code)]
     pub fn get_running_processes(&self) -> Result<Vec<ProcessInfo>, String> {
@@ -336,6 +382,39 @@ impl ProcessRegistry {
         Ok(processes.get(&run_id).map(|handle| handle.info.clone()))
     }
 
+    /// Interrupt a running process, giving it time to exit before its tree is killed
+    ///
+    /// Returns None when the process is not registered.
+    pub async fn interrupt_process(&self, run_id: i64) -> Result<Option<Shutdown>, String> {
+        let (pid, child_arc, job_object) = {
+            let processes = self.processes.lock().map_err(|e| e.to_string())?;
+            let Some(handle) = processes.get(&run_id) else {
+                return Ok(None);
+            };
+            #[cfg(windows)]
+            let job_object = handle.job_object.clone();
+            #[cfg(not(windows))]
+            let job_object: Option<Arc<JobObject>> = None;
+            (handle.info.pid, handle.child.clone(), job_object)
+        };
+
+        // Claude sessions have no child handle here; their wait task unregisters them on exit
+        let processes = self.processes.clone();
+        let exited = || match child_arc.lock() {
+            Ok(mut child) => match child.as_mut() {
+                Some(child) => child_exited(child),
+                None => !processes
+                    .lock()
+                    .map_or(false, |processes| processes.contains_key(&run_id)),
+            },
+            Err(_) => true,
+        };
+        let outcome = shutdown(pid, job_object.as_deref(), exited).await;
+
+        self.unregister_process(run_id)?;
+        Ok(Some(outcome))
+    }
+
     /// Kill a running process with proper cleanup
     pub async fn kill_process(&self, run_id: i64) -> Result<bool, String> {
         use log::{error, info, warn};
@@ -712,12 +791,17 @@ impl ProcessRegistry {
             }
         }
 
-        // Then remove them from the registry
-        {
+        // Then remove them from the registry, and the ledger once the lock is released
+        let removed: Vec<u32> = {
             let mut processes = processes_lock.lock().map_err(|e| e.to_string())?;
-            for run_id in &finished_runs {
-                processes.remove(run_id);
-            }
+            finished_runs
+                .iter()
+                .filter_map(|run_id| processes.remove(run_id))
+                .map(|handle| handle.info.pid)
+                .collect()
+        };
+        for pid in removed {
+            super::ledger::forget(pid);
         }
 
         Ok(finished_runs)
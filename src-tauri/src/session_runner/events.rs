@@ -0,0 +1,76 @@
+//! Typed events of an engine session
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How a tool changed a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FileEditKind {
+    Create,
+    /// In-place edit or whole-file write
+    Modify,
+    Delete,
+}
+
+/// One event of an engine session, whatever the engine's output format
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(
+    tag = "type",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum SessionEvent {
+    /// The engine opened or resumed its session
+    Started {
+        session_id: Option<String>,
+        model: Option<String>,
+    },
+    /// Chunk of assistant text
+    Text { text: String },
+    /// Chunk of reasoning
+    Thinking { text: String },
+    /// The model called a tool
+    ToolCall {
+        id: String,
+        name: String,
+        input: Value,
+    },
+    /// A tool call returned
+    ToolResult {
+        id: String,
+        output: String,
+        is_error: bool,
+    },
+    /// A tool changed a file
+    FileEdit {
+        path: String,
+        kind: FileEditKind,
+        tool: String,
+    },
+    /// Error reported by the engine
+    Error { message: String },
+    /// Token usage of a turn, with its cost when the engine reports one
+    Usage {
+        input_tokens: u64,
+        output_tokens: u64,
+        cached_input_tokens: u64,
+        cost_usd: Option<f64>,
+    },
+    /// The engine's output ended; `success` is false when it reported an error
+    Completed { success: bool },
+}
+
+impl SessionEvent {
+    /// Append `next` when both are chunks of the same text stream
+    pub(super) fn merge(&mut self, next: &SessionEvent) -> bool {
+        match (self, next) {
+            (Self::Text { text }, Self::Text { text: more })
+            | (Self::Thinking { text }, Self::Thinking { text: more }) => {
+                text.push_str(more);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,282 @@
+//! Session runner
+//!
+//! Engine CLIs stream one JSON object per line, each in its own dialect. The
+//! session runner sits between an engine process and the frontend: every
+//! stdout line is parsed into typed [`SessionEvent`]s (text chunks, tool calls
+//! and results, file edits, errors, usage and cost) that are forwarded on
+//! `session-events` and `session-events:<session id>` through the
+//! [`event_batcher`], which sends them as ordered batches once per frame.
+//!
+//! Forwarding applies backpressure. Events go through a bounded queue whose
+//! forwarder hands at most [`MAX_EVENTS_PER_FRAME`] events per frame to the
+//! batcher, merging adjacent text chunks. When the engine outpaces that, the
+//! queue fills up, [`SessionRunner::push_line`] waits, the engine's stdout pipe
+//! stops being drained and the CLI blocks on its writes instead of flooding
+//! the webview. Recording happens on a writer thread of its own.
+//!
+//! Typed events, and whether the raw stdout lines are still forwarded on
+//! `<engine>-output` next to them, are configured in
+//! `~/.anycode/session-stream.json`. Whether or not typed events are sent,
+//! every run is recorded in the session store, and a session without a title
+//! gets one when its run completes.
+
+mod events;
+mod parser;
+mod recorder;
+
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use std::sync::RwLock;
+use tauri::AppHandle;
+use tokio::sync::mpsc;
+
+use crate::commands::event_batcher;
+use crate::utils::config_utils::{load_json_config, save_json_config, ConfigPathBuilder};
+use events::SessionEvent;
+use parser::StreamParser;
+use recorder::Recorder;
+
+pub use parser::StreamDialect;
+
+/// Events an engine may queue ahead of the forwarder
+const QUEUE_CAPACITY: usize = 256;
+
+/// Most events handed to the batcher per frame
+const MAX_EVENTS_PER_FRAME: usize = 64;
+
+/// Event type of the typed events, as configured in the batcher
+const SESSION_EVENTS: &str = "session-events";
+
+/// Session stream settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionStreamSettings {
+    /// Send typed events on `session-events`
+    #[serde(default = "default_true")]
+    pub typed_events: bool,
+    /// Keep forwarding raw stdout lines on `<engine>-output`
+    #[serde(default = "default_true")]
+    pub raw_output: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for SessionStreamSettings {
+    fn default() -> Self {
+        Self {
+            typed_events: true,
+            raw_output: true,
+        }
+    }
+}
+
+static SETTINGS: Lazy<RwLock<SessionStreamSettings>> =
+    Lazy::new(|| RwLock::new(config_path().and_then(load_json_config).unwrap_or_default()));
+
+fn config_path() -> Result<PathBuf, String> {
+    Ok(ConfigPathBuilder::from_home_subdir(".anycode")?.build("session-stream.json"))
+}
+
+/// Re-read the settings after the file was edited outside the app
+pub(crate) fn reload_settings() {
+    if let Ok(mut settings) = SETTINGS.write() {
+        *settings = config_path().and_then(load_json_config).unwrap_or_default();
+    }
+}
+
+pub fn settings() -> SessionStreamSettings {
+    SETTINGS.read().map(|s| *s).unwrap_or_default()
+}
+
+pub fn save_settings(settings: SessionStreamSettings) -> Result<(), String> {
+    if !settings.typed_events && !settings.raw_output {
+        return Err("Typed events and raw output cannot both be disabled".to_string());
+    }
+    save_json_config(&settings, config_path()?)?;
+    *SETTINGS.write().map_err(|e| e.to_string())? = settings;
+    Ok(())
+}
+
+/// Whether engine runners still forward raw stdout lines
+pub fn raw_output_enabled() -> bool {
+    settings().raw_output
+}
+
+/// One event as sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct EventPayload<'a> {
+    engine: &'a str,
+    session_id: Option<&'a str>,
+    tab_id: Option<&'a str>,
+    event: SessionEvent,
+}
+
+/// Typed event stream of one engine process
+pub struct SessionRunner {
+    parser: StreamParser,
+    queue: mpsc::Sender<SessionEvent>,
+    failed: bool,
+}
+
+impl SessionRunner {
+    /// Start forwarding and recording for an engine process
+    ///
+    /// `session_id` names the per-session channel; when None, the id the
+    /// engine reports in its first event is used. `prompt` is recorded as the
+    /// run's first entry. Returns None when the engine has no streaming dialect.
+    pub fn start(
+        app: &AppHandle,
+        engine: &str,
+        session_id: Option<String>,
+        tab_id: Option<String>,
+        project_path: &str,
+        prompt: Option<&str>,
+    ) -> Option<Self> {
+        let dialect = crate::engines::get(engine)?.stream_dialect()?;
+        let recorder = Recorder::spawn(app, engine, project_path, session_id.clone(), prompt);
+        let (queue, receiver) = mpsc::channel(QUEUE_CAPACITY);
+        tauri::async_runtime::spawn(forward(
+            app.clone(),
+            engine.to_string(),
+            session_id,
+            tab_id,
+            settings().typed_events,
+            recorder,
+            receiver,
+        ));
+        Some(Self {
+            parser: StreamParser::new(dialect),
+            queue,
+            failed: false,
+        })
+    }
+
+    /// Parse one stdout line and queue its events, waiting while the queue is full
+    pub async fn push_line(&mut self, line: &str) {
+        for event in self.parser.parse_line(line) {
+            self.failed |= matches!(event, SessionEvent::Error { .. });
+            if self.queue.send(event).await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// End of the engine's output
+    pub async fn finish(self) {
+        let _ = self
+            .queue
+            .send(SessionEvent::Completed {
+                success: !self.failed,
+            })
+            .await;
+    }
+}
+
+/// Queue `event`, merging it into the previous one when both are text chunks
+fn push_merged(batch: &mut Vec<SessionEvent>, event: SessionEvent) {
+    if let Some(last) = batch.last_mut() {
+        if last.merge(&event) {
+            return;
+        }
+    }
+    batch.push(event);
+}
+
+async fn forward(
+    app: AppHandle,
+    engine: String,
+    mut session_id: Option<String>,
+    tab_id: Option<String>,
+    emit: bool,
+    recorder: std::sync::mpsc::Sender<SessionEvent>,
+    mut queue: mpsc::Receiver<SessionEvent>,
+) {
+    let mut events = Vec::new();
+    while let Some(event) = queue.recv().await {
+        let frame_end = tokio::time::Instant::now() + event_batcher::frame_length(SESSION_EVENTS);
+        push_merged(&mut events, event);
+        // Take what is already waiting, up to a frame's worth
+        while events.len() < MAX_EVENTS_PER_FRAME {
+            match queue.try_recv() {
+                Ok(event) => push_merged(&mut events, event),
+                Err(_) => break,
+            }
+        }
+        let full = events.len() == MAX_EVENTS_PER_FRAME;
+        for event in &events {
+            let _ = recorder.send(event.clone());
+        }
+        if !emit {
+            events.clear();
+            continue;
+        }
+
+        if session_id.is_none() {
+            session_id = events.iter().find_map(|event| match event {
+                SessionEvent::Started { session_id, .. } => session_id.clone(),
+                _ => None,
+            });
+        }
+
+        let session_channel = session_id
+            .as_deref()
+            .map(|session_id| format!("{}:{}", SESSION_EVENTS, session_id));
+        for event in events.drain(..) {
+            let payload = EventPayload {
+                engine: &engine,
+                session_id: session_id.as_deref(),
+                tab_id: tab_id.as_deref(),
+                event,
+            };
+            if let Some(channel) = &session_channel {
+                if let Err(e) = event_batcher::emit(&app, channel, &payload) {
+                    log::warn!("[SessionRunner] Failed to emit {} events: {}", engine, e);
+                }
+            }
+            let _ = event_batcher::emit(&app, SESSION_EVENTS, &payload);
+        }
+
+        // A full frame's worth waits out the frame, which bounds the event rate
+        if full {
+            tokio::time::sleep_until(frame_end).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_merged() {
+        let mut batch = Vec::new();
+        for event in [
+            SessionEvent::Text {
+                text: "Hel".to_string(),
+            },
+            SessionEvent::Text {
+                text: "lo".to_string(),
+            },
+            SessionEvent::Thinking {
+                text: "hmm".to_string(),
+            },
+            SessionEvent::Text {
+                text: "!".to_string(),
+            },
+        ] {
+            push_merged(&mut batch, event);
+        }
+
+        assert_eq!(batch.len(), 3);
+        assert_eq!(
+            batch[0],
+            SessionEvent::Text {
+                text: "Hello".to_string()
+            }
+        );
+    }
+}
@@ -0,0 +1,375 @@
+//! Parsers for the JSON lines engine CLIs print
+
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+use super::events::{FileEditKind, SessionEvent};
+use crate::commands::auto_checkpoint::FILE_EDIT_TOOLS;
+
+/// Output format of an engine CLI
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamDialect {
+    /// Claude `--output-format stream-json`, also what Gemini output is converted to
+    Unified,
+    /// Codex `exec --json` thread/turn/item events
+    Codex,
+}
+
+/// Line parser for one engine process
+pub struct StreamParser {
+    dialect: StreamDialect,
+    /// Claude partial messages were seen, so complete messages repeat text already sent
+    saw_partial_text: bool,
+    /// Bytes of text already sent per Codex item (updates carry the full text so far)
+    item_text: HashMap<String, usize>,
+}
+
+fn string_field(value: &Value) -> Option<String> {
+    value.as_str().map(str::to_string)
+}
+
+fn error_message(error: &Value) -> String {
+    error["message"]
+        .as_str()
+        .or_else(|| error.as_str())
+        .unwrap_or("Unknown error")
+        .to_string()
+}
+
+/// Tool output as text: plain strings, text content blocks, or the JSON itself
+fn content_text(content: &Value) -> String {
+    match content {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(blocks) => {
+            let texts: Vec<&str> = blocks.iter().filter_map(|b| b["text"].as_str()).collect();
+            if texts.is_empty() {
+                content.to_string()
+            } else {
+                texts.join("\n")
+            }
+        }
+        other => other.to_string(),
+    }
+}
+
+/// File edit announced by a unified-format tool call
+fn file_edit(tool: &str, input: &Value) -> Option<SessionEvent> {
+    if !FILE_EDIT_TOOLS.contains(&tool) {
+        return None;
+    }
+    let path = ["file_path", "notebook_path", "path", "absolute_path"]
+        .iter()
+        .find_map(|key| input[key].as_str())?;
+    Some(SessionEvent::FileEdit {
+        path: path.to_string(),
+        kind: FileEditKind::Modify,
+        tool: tool.to_string(),
+    })
+}
+
+impl StreamParser {
+    pub fn new(dialect: StreamDialect) -> Self {
+        Self {
+            dialect,
+            saw_partial_text: false,
+            item_text: HashMap::new(),
+        }
+    }
+
+    /// Events of one output line; lines that are not JSON yield none
+    pub fn parse_line(&mut self, line: &str) -> Vec<SessionEvent> {
+        let Ok(event) = serde_json::from_str::<Value>(line.trim()) else {
+            return Vec::new();
+        };
+        let mut events = Vec::new();
+        match self.dialect {
+            StreamDialect::Unified => self.parse_unified(&event, &mut events),
+            StreamDialect::Codex => self.parse_codex(&event, &mut events),
+        }
+        events
+    }
+
+    fn parse_unified(&mut self, event: &Value, events: &mut Vec<SessionEvent>) {
+        match event["type"].as_str().unwrap_or_default() {
+            "system" => match event["subtype"].as_str() {
+                Some("init") => events.push(SessionEvent::Started {
+                    session_id: string_field(&event["session_id"]),
+                    model: string_field(&event["model"]),
+                }),
+                Some("error") => events.push(SessionEvent::Error {
+                    message: error_message(&event["error"]),
+                }),
+                _ => {}
+            },
+            // Claude `--include-partial-messages`
+            "stream_event" => {
+                let delta = &event["event"]["delta"];
+                let (text, thinking) = (delta["text"].as_str(), delta["thinking"].as_str());
+                match delta["type"].as_str() {
+                    Some("text_delta") => events.push(SessionEvent::Text {
+                        text: text.unwrap_or_default().to_string(),
+                    }),
+                    Some("thinking_delta") => events.push(SessionEvent::Thinking {
+                        text: thinking.unwrap_or_default().to_string(),
+                    }),
+                    _ => return,
+                }
+                self.saw_partial_text = true;
+            }
+            "assistant" => {
+                for block in event["message"]["content"].as_array().into_iter().flatten() {
+                    match block["type"].as_str().unwrap_or_default() {
+                        "text" if !self.saw_partial_text => events.push(SessionEvent::Text {
+                            text: block["text"].as_str().unwrap_or_default().to_string(),
+                        }),
+                        "thinking" if !self.saw_partial_text => {
+                            events.push(SessionEvent::Thinking {
+                                text: block["thinking"].as_str().unwrap_or_default().to_string(),
+                            })
+                        }
+                        "tool_use" => {
+                            let name = block["name"].as_str().unwrap_or_default();
+                            events.push(SessionEvent::ToolCall {
+                                id: block["id"].as_str().unwrap_or_default().to_string(),
+                                name: name.to_string(),
+                                input: block["input"].clone(),
+                            });
+                            events.extend(file_edit(name, &block["input"]));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            "user" => {
+                for block in event["message"]["content"].as_array().into_iter().flatten() {
+                    if block["type"] == "tool_result" {
+                        events.push(SessionEvent::ToolResult {
+                            id: block["tool_use_id"]
+                                .as_str()
+                                .unwrap_or_default()
+                                .to_string(),
+                            output: content_text(&block["content"]),
+                            is_error: block["is_error"].as_bool().unwrap_or(false),
+                        });
+                    }
+                }
+            }
+            "result" => {
+                let usage = &event["usage"];
+                let cost_usd = event["total_cost_usd"].as_f64();
+                if usage.is_object() || cost_usd.is_some() {
+                    events.push(SessionEvent::Usage {
+                        input_tokens: usage["input_tokens"].as_u64().unwrap_or(0),
+                        output_tokens: usage["output_tokens"].as_u64().unwrap_or(0),
+                        cached_input_tokens: usage["cache_read_input_tokens"]
+                            .as_u64()
+                            .or_else(|| usage["cached_input_tokens"].as_u64())
+                            .unwrap_or(0),
+                        cost_usd,
+                    });
+                }
+                let subtype = event["subtype"].as_str().unwrap_or_default();
+                if event["is_error"].as_bool() == Some(true)
+                    || event["status"] == "error"
+                    || subtype.starts_with("error")
+                {
+                    events.push(SessionEvent::Error {
+                        message: event["result"].as_str().unwrap_or(subtype).to_string(),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn parse_codex(&mut self, event: &Value, events: &mut Vec<SessionEvent>) {
+        match event["type"].as_str().unwrap_or_default() {
+            "thread.started" => events.push(SessionEvent::Started {
+                session_id: string_field(&event["thread_id"]),
+                model: None,
+            }),
+            phase @ ("item.started" | "item.updated" | "item.completed") => {
+                self.parse_codex_item(phase, &event["item"], events)
+            }
+            "turn.completed" => {
+                let usage = &event["usage"];
+                events.push(SessionEvent::Usage {
+                    input_tokens: usage["input_tokens"].as_u64().unwrap_or(0),
+                    output_tokens: usage["output_tokens"].as_u64().unwrap_or(0),
+                    cached_input_tokens: usage["cached_input_tokens"].as_u64().unwrap_or(0),
+                    cost_usd: None,
+                });
+            }
+            "turn.failed" => events.push(SessionEvent::Error {
+                message: error_message(&event["error"]),
+            }),
+            "error" => events.push(SessionEvent::Error {
+                message: error_message(event),
+            }),
+            _ => {}
+        }
+    }
+
+    fn parse_codex_item(&mut self, phase: &str, item: &Value, events: &mut Vec<SessionEvent>) {
+        let id = item["id"].as_str().unwrap_or_default().to_string();
+        let started = phase == "item.started";
+        let completed = phase == "item.completed";
+
+        match item["type"].as_str().unwrap_or_default() {
+            kind @ ("agent_message" | "reasoning") => {
+                let text = item["text"].as_str().unwrap_or_default();
+                let sent = self.item_text.get(&id).copied().unwrap_or(0);
+                if text.len() > sent && text.is_char_boundary(sent) {
+                    let text = text[sent..].to_string();
+                    events.push(if kind == "agent_message" {
+                        SessionEvent::Text { text }
+                    } else {
+                        SessionEvent::Thinking { text }
+                    });
+                }
+                if completed {
+                    self.item_text.remove(&id);
+                } else {
+                    self.item_text.insert(id, text.len().max(sent));
+                }
+            }
+            "command_execution" if started => events.push(SessionEvent::ToolCall {
+                id,
+                name: "shell".to_string(),
+                input: json!({ "command": item["command"] }),
+            }),
+            "command_execution" if completed => events.push(SessionEvent::ToolResult {
+                id,
+                output: item["aggregated_output"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+                is_error: item["status"] == "failed"
+                    || item["exit_code"].as_i64().map_or(false, |code| code != 0),
+            }),
+            "mcp_tool_call" if started => events.push(SessionEvent::ToolCall {
+                id,
+                name: format!(
+                    "mcp__{}__{}",
+                    item["server"].as_str().unwrap_or_default(),
+                    item["tool"].as_str().unwrap_or_default()
+                ),
+                input: item["arguments"].clone(),
+            }),
+            "mcp_tool_call" if completed => {
+                let failed = item["status"] == "failed";
+                events.push(SessionEvent::ToolResult {
+                    id,
+                    output: if failed {
+                        error_message(&item["error"])
+                    } else {
+                        content_text(&item["result"]["content"])
+                    },
+                    is_error: failed,
+                });
+            }
+            "web_search" if started => events.push(SessionEvent::ToolCall {
+                id,
+                name: "web_search".to_string(),
+                input: json!({ "query": item["query"] }),
+            }),
+            "file_change" if completed && item["status"] != "failed" => {
+                for change in item["changes"].as_array().into_iter().flatten() {
+                    let Some(path) = change["path"].as_str() else {
+                        continue;
+                    };
+                    events.push(SessionEvent::FileEdit {
+                        path: path.to_string(),
+                        kind: match change["kind"].as_str() {
+                            Some("add") => FileEditKind::Create,
+                            Some("delete") => FileEditKind::Delete,
+                            _ => FileEditKind::Modify,
+                        },
+                        tool: "apply_patch".to_string(),
+                    });
+                }
+            }
+            "error" => events.push(SessionEvent::Error {
+                message: error_message(item),
+            }),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_lines() {
+        let mut claude = StreamParser::new(StreamDialect::Unified);
+        let events = claude.parse_line(
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Fixing"},{"type":"tool_use","id":"t1","name":"Edit","input":{"file_path":"/p/a.rs"}}]}}"#,
+        );
+        assert_eq!(events.len(), 3);
+        assert_eq!(
+            events[0],
+            SessionEvent::Text {
+                text: "Fixing".to_string()
+            }
+        );
+        assert!(matches!(
+            &events[2],
+            SessionEvent::FileEdit { path, kind: FileEditKind::Modify, .. } if path == "/p/a.rs"
+        ));
+        let events = claude.parse_line(
+            r#"{"type":"result","subtype":"success","is_error":false,"total_cost_usd":0.02,"usage":{"input_tokens":10,"output_tokens":5,"cache_read_input_tokens":3}}"#,
+        );
+        assert_eq!(
+            events,
+            vec![SessionEvent::Usage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cached_input_tokens: 3,
+                cost_usd: Some(0.02),
+            }]
+        );
+        assert!(claude.parse_line("not json").is_empty());
+
+        // Codex re-sends the whole message text, only the new part is emitted
+        let mut codex = StreamParser::new(StreamDialect::Codex);
+        codex.parse_line(
+            r#"{"type":"item.started","item":{"id":"m","type":"agent_message","text":"Hel"}}"#,
+        );
+        let events = codex.parse_line(
+            r#"{"type":"item.completed","item":{"id":"m","type":"agent_message","text":"Hello"}}"#,
+        );
+        assert_eq!(
+            events,
+            vec![SessionEvent::Text {
+                text: "lo".to_string()
+            }]
+        );
+        let events = codex.parse_line(
+            r#"{"type":"item.completed","item":{"id":"f","type":"file_change","status":"completed","changes":[{"path":"new.rs","kind":"add"},{"path":"old.rs","kind":"delete"}]}}"#,
+        );
+        assert!(matches!(
+            events[0],
+            SessionEvent::FileEdit {
+                kind: FileEditKind::Create,
+                ..
+            }
+        ));
+        assert!(matches!(
+            events[1],
+            SessionEvent::FileEdit {
+                kind: FileEditKind::Delete,
+                ..
+            }
+        ));
+        let events = codex.parse_line(r#"{"type":"turn.failed","error":{"message":"quota"}}"#);
+        assert_eq!(
+            events,
+            vec![SessionEvent::Error {
+                message: "quota".to_string()
+            }]
+        );
+    }
+}
@@ -0,0 +1,207 @@
+//! Records a session's events in the session store
+
+use serde_json::{json, Value};
+use std::sync::mpsc;
+use tauri::AppHandle;
+
+use super::events::SessionEvent;
+use crate::commands::session_titles;
+use crate::session_store::{self, EntryKind, SessionStatus};
+
+/// Writes one engine run to the session store
+///
+/// Entries are held back until the engine reports its session id, which is
+/// the key the run is stored (and later resumed) under.
+pub(super) struct Recorder {
+    engine: String,
+    project_path: String,
+    /// Id the run was started under, if any
+    run_key: Option<String>,
+    session_id: Option<String>,
+    pending: Vec<(EntryKind, Value)>,
+    /// Assistant text since the last non-text event
+    response: String,
+    /// The run's prompt and all its assistant text, for the session title
+    prompt: Option<String>,
+    reply: String,
+}
+
+impl Recorder {
+    /// Record on a writer thread of its own the events sent to the returned channel,
+    /// so the store's blocking writes stay off the async runtime
+    pub(super) fn spawn(
+        app: &AppHandle,
+        engine: &str,
+        project_path: &str,
+        run_key: Option<String>,
+        prompt: Option<&str>,
+    ) -> mpsc::Sender<SessionEvent> {
+        let (sender, receiver) = mpsc::channel::<SessionEvent>();
+        let engine = engine.to_string();
+        let project_path = project_path.to_string();
+        let prompt = prompt.map(str::to_string);
+        let app = app.clone();
+        std::thread::spawn(move || {
+            let mut recorder = Recorder::new(&engine, &project_path, run_key, prompt.as_deref());
+            // Ends when the forwarder drops the sender
+            for event in receiver {
+                recorder.record(&event);
+                if matches!(event, SessionEvent::Completed { success: true }) {
+                    recorder.request_title(&app);
+                }
+            }
+        });
+        sender
+    }
+
+    fn new(
+        engine: &str,
+        project_path: &str,
+        run_key: Option<String>,
+        prompt: Option<&str>,
+    ) -> Self {
+        let mut recorder = Self {
+            engine: engine.to_string(),
+            project_path: project_path.to_string(),
+            run_key,
+            session_id: None,
+            pending: Vec::new(),
+            response: String::new(),
+            prompt: None,
+            reply: String::new(),
+        };
+        if let Some(prompt) = prompt.filter(|prompt| !prompt.trim().is_empty()) {
+            recorder.prompt = Some(prompt.to_string());
+            recorder.write(EntryKind::Prompt, json!({ "text": prompt }));
+        }
+        recorder
+    }
+
+    pub(super) fn record(&mut self, event: &SessionEvent) {
+        let kind = match event {
+            SessionEvent::Started { session_id, model } => {
+                if let Some(session_id) = session_id {
+                    self.open(session_id.clone(), model.as_deref());
+                }
+                return;
+            }
+            SessionEvent::Text { text } => {
+                self.response.push_str(text);
+                return;
+            }
+            SessionEvent::Thinking { .. } | SessionEvent::Usage { .. } => return,
+            SessionEvent::Completed { success } => {
+                self.finish(*success);
+                return;
+            }
+            SessionEvent::ToolCall { .. } => EntryKind::ToolCall,
+            SessionEvent::ToolResult { .. } => EntryKind::ToolResult,
+            SessionEvent::FileEdit { .. } => EntryKind::FileEdit,
+            SessionEvent::Error { .. } => EntryKind::Error,
+        };
+        match serde_json::to_value(event) {
+            Ok(content) => self.write(kind, content),
+            Err(e) => log::warn!("[SessionStore] Failed to serialize event: {}", e),
+        }
+    }
+
+    /// Start the stored session and write the entries held back so far
+    fn open(&mut self, session_id: String, model: Option<&str>) {
+        if self.session_id.is_some() {
+            return;
+        }
+        if let Err(e) = session_store::start_session(
+            &session_id,
+            self.run_key.as_deref(),
+            &self.engine,
+            &self.project_path,
+            model,
+        ) {
+            log::warn!(
+                "[SessionStore] Failed to record {} session {}: {}",
+                self.engine,
+                session_id,
+                e
+            );
+            return;
+        }
+        self.session_id = Some(session_id);
+        for (kind, content) in std::mem::take(&mut self.pending) {
+            self.write(kind, content);
+        }
+    }
+
+    fn write(&mut self, kind: EntryKind, content: Value) {
+        if kind != EntryKind::Response {
+            self.flush_response();
+        }
+        match &self.session_id {
+            Some(session_id) => {
+                if let Err(e) = session_store::append_entry(session_id, kind, &content) {
+                    log::warn!("[SessionStore] Failed to record entry: {}", e);
+                }
+            }
+            None => self.pending.push((kind, content)),
+        }
+    }
+
+    fn flush_response(&mut self) {
+        let text = std::mem::take(&mut self.response);
+        if !text.trim().is_empty() {
+            if !self.reply.is_empty() {
+                self.reply.push_str("\n\n");
+            }
+            self.reply.push_str(text.trim());
+            self.write(EntryKind::Response, json!({ "text": text }));
+        }
+    }
+
+    /// Title the session from this run, in the background, unless it has a title already
+    fn request_title(&mut self, app: &AppHandle) {
+        let (Some(session_id), Some(prompt)) = (self.session_id.clone(), self.prompt.take()) else {
+            return;
+        };
+        let reply = std::mem::take(&mut self.reply);
+        if reply.is_empty() {
+            return;
+        }
+        let app = app.clone();
+        let engine = self.engine.clone();
+        tauri::async_runtime::spawn(async move {
+            let messages = [prompt, reply];
+            if let Err(e) =
+                session_titles::ensure_title(&app, &engine, &session_id, &messages).await
+            {
+                log::warn!(
+                    "[SessionTitles] Failed to title {} session {}: {}",
+                    engine,
+                    session_id,
+                    e
+                );
+            }
+        });
+    }
+
+    fn finish(&mut self, success: bool) {
+        self.flush_response();
+        // The engine never reported a session id; keep the run under the id it was started with
+        if self.session_id.is_none() {
+            let session_id = self
+                .run_key
+                .clone()
+                .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+            self.open(session_id, None);
+        }
+        let Some(session_id) = &self.session_id else {
+            return;
+        };
+        let status = if success {
+            SessionStatus::Completed
+        } else {
+            SessionStatus::Failed
+        };
+        if let Err(e) = session_store::finish_session(session_id, status) {
+            log::warn!("[SessionStore] Failed to finish session: {}", e);
+        }
+    }
+}
@@ -0,0 +1,468 @@
+//! Session store
+//!
+//! Every engine run that goes through the session runner is recorded in
+//! `~/.anycode/sessions.db`: the session's engine, project and model, and its
+//! entries in order (prompts, responses, tool calls and results, file edits,
+//! errors and the checkpoints taken for it). The store outlives the app, so an
+//! interrupted run can be looked up and resumed after a restart.
+//!
+//! Sessions are keyed by the id the engine reports, which is also the id used
+//! to resume them. The id the run was started under (e.g. Codex's channel id)
+//! is kept as `run_key`, so checkpoints taken under either id find the session.
+//! Sessions still marked running when the store is opened were cut off by a
+//! crash or a kill and are marked interrupted.
+
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use rusqlite::{params, Connection, OptionalExtension, Row};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::utils::config_utils::ConfigPathBuilder;
+
+/// Checkpoints kept while waiting for their session to be recorded
+const MAX_PENDING_CHECKPOINTS: usize = 64;
+
+/// Length of a title derived from the first prompt
+const TITLE_CHARS: usize = 80;
+
+static DB: Lazy<Result<Mutex<Connection>, String>> = Lazy::new(|| {
+    let path = ConfigPathBuilder::from_home_subdir(".anycode")?.build("sessions.db");
+    open(&path)
+        .map(Mutex::new)
+        .map_err(|e| format!("Failed to open session store {:?}: {}", path, e))
+});
+
+/// Checkpoints taken before their session was recorded: (engine, session id, checkpoint)
+static PENDING_CHECKPOINTS: Lazy<Mutex<Vec<(String, String, String)>>> =
+    Lazy::new(|| Mutex::new(Vec::new()));
+
+/// State of a stored session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SessionStatus {
+    Running,
+    Completed,
+    Failed,
+    /// The app exited or crashed while the engine was running
+    Interrupted,
+}
+
+impl SessionStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Running => "running",
+            Self::Completed => "completed",
+            Self::Failed => "failed",
+            Self::Interrupted => "interrupted",
+        }
+    }
+
+    fn parse(status: &str) -> Self {
+        match status {
+            "running" => Self::Running,
+            "completed" => Self::Completed,
+            "failed" => Self::Failed,
+            _ => Self::Interrupted,
+        }
+    }
+}
+
+/// Kind of a session entry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EntryKind {
+    Prompt,
+    Response,
+    ToolCall,
+    ToolResult,
+    FileEdit,
+    Error,
+    Checkpoint,
+}
+
+impl EntryKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Prompt => "prompt",
+            Self::Response => "response",
+            Self::ToolCall => "toolCall",
+            Self::ToolResult => "toolResult",
+            Self::FileEdit => "fileEdit",
+            Self::Error => "error",
+            Self::Checkpoint => "checkpoint",
+        }
+    }
+
+    fn parse(kind: &str) -> Option<Self> {
+        serde_json::from_value(Value::String(kind.to_string())).ok()
+    }
+}
+
+/// A recorded session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoredSession {
+    /// Session id reported by the engine
+    pub id: String,
+    pub engine: String,
+    pub project_path: String,
+    pub model: Option<String>,
+    pub status: SessionStatus,
+    /// Generated title, else the start of the first prompt
+    pub title: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One recorded entry of a session
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionEntry {
+    pub id: i64,
+    pub kind: EntryKind,
+    pub content: Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A session with all of its entries
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionDetail {
+    #[serde(flatten)]
+    pub session: StoredSession,
+    pub entries: Vec<SessionEntry>,
+}
+
+fn open(path: &Path) -> rusqlite::Result<Connection> {
+    if let Some(dir) = path.parent() {
+        let _ = std::fs::create_dir_all(dir);
+    }
+    let conn = Connection::open(path)?;
+    conn.pragma_update(None, "journal_mode", "WAL")?;
+    init_schema(&conn)?;
+
+    let interrupted = conn.execute(
+        "UPDATE sessions SET status = 'interrupted' WHERE status = 'running'",
+        [],
+    )?;
+    if interrupted > 0 {
+        log::info!(
+            "[SessionStore] Marked {} sessions cut off by the last exit as interrupted",
+            interrupted
+        );
+    }
+    Ok(conn)
+}
+
+fn init_schema(conn: &Connection) -> rusqlite::Result<()> {
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS sessions (
+            id TEXT PRIMARY KEY,
+            engine TEXT NOT NULL,
+            project_path TEXT NOT NULL,
+            run_key TEXT,
+            model TEXT,
+            status TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS session_entries (
+            id INTEGER PRIMARY KEY AUTOINCREMENT,
+            session_id TEXT NOT NULL REFERENCES sessions(id) ON DELETE CASCADE,
+            kind TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE INDEX IF NOT EXISTS idx_sessions_project
+            ON sessions(project_path, updated_at DESC);
+        CREATE INDEX IF NOT EXISTS idx_session_entries_session
+            ON session_entries(session_id, id);",
+    )
+}
+
+fn db() -> Result<MutexGuard<'static, Connection>, String> {
+    DB.as_ref()
+        .map_err(Clone::clone)?
+        .lock()
+        .map_err(|e| e.to_string())
+}
+
+/// Record the start (or resumption) of a session
+pub fn start_session(
+    session_id: &str,
+    run_key: Option<&str>,
+    engine: &str,
+    project_path: &str,
+    model: Option<&str>,
+) -> Result<(), String> {
+    let conn = db()?;
+    upsert_session(&conn, session_id, run_key, engine, project_path, model)
+        .map_err(|e| e.to_string())?;
+
+    // Checkpoints taken before the engine reported its session id
+    let claimed: Vec<String> = {
+        let mut pending = PENDING_CHECKPOINTS.lock().map_err(|e| e.to_string())?;
+        let (claimed, rest): (Vec<_>, Vec<_>) = std::mem::take(&mut *pending)
+            .into_iter()
+            .partition(|(owner, id, _)| {
+                owner == engine && (id == session_id || Some(id.as_str()) == run_key)
+            });
+        *pending = rest;
+        claimed
+            .into_iter()
+            .map(|(_, _, checkpoint)| checkpoint)
+            .collect()
+    };
+    for checkpoint in claimed {
+        insert_entry(
+            &conn,
+            session_id,
+            EntryKind::Checkpoint,
+            &serde_json::json!({ "checkpoint": checkpoint }),
+        )
+        .map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+/// Append an entry to a recorded session
+pub fn append_entry(session_id: &str, kind: EntryKind, content: &Value) -> Result<(), String> {
+    insert_entry(&*db()?, session_id, kind, content).map_err(|e| e.to_string())
+}
+
+/// Record how a session's run ended
+pub fn finish_session(session_id: &str, status: SessionStatus) -> Result<(), String> {
+    db()?
+        .execute(
+            "UPDATE sessions SET status = ?2, updated_at = ?3 WHERE id = ?1",
+            params![session_id, status.as_str(), Utc::now().to_rfc3339()],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+}
+
+/// Record a checkpoint taken for a session; `session_id` may be its id or run key
+///
+/// Checkpoints of sessions that are not recorded yet are kept until they are.
+pub fn record_checkpoint(engine: &str, session_id: &str, checkpoint: &str) -> Result<(), String> {
+    let conn = db()?;
+    let owner: Option<String> = conn
+        .query_row(
+            "SELECT id FROM sessions WHERE engine = ?1 AND (id = ?2 OR run_key = ?2)
+             ORDER BY updated_at DESC LIMIT 1",
+            params![engine, session_id],
+            |row| row.get(0),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+
+    match owner {
+        Some(owner) => insert_entry(
+            &conn,
+            &owner,
+            EntryKind::Checkpoint,
+            &serde_json::json!({ "checkpoint": checkpoint }),
+        )
+        .map_err(|e| e.to_string()),
+        None => {
+            let mut pending = PENDING_CHECKPOINTS.lock().map_err(|e| e.to_string())?;
+            if pending.len() >= MAX_PENDING_CHECKPOINTS {
+                pending.remove(0);
+            }
+            pending.push((
+                engine.to_string(),
+                session_id.to_string(),
+                checkpoint.to_string(),
+            ));
+            Ok(())
+        }
+    }
+}
+
+/// Recorded sessions, most recently active first
+pub fn list_sessions(
+    project_path: Option<&str>,
+    engine: Option<&str>,
+    limit: usize,
+) -> Result<Vec<StoredSession>, String> {
+    query_sessions(&*db()?, project_path, engine, limit).map_err(|e| e.to_string())
+}
+
+/// A recorded session with its entries
+pub fn get_session(session_id: &str) -> Result<Option<SessionDetail>, String> {
+    load_session(&*db()?, session_id).map_err(|e| e.to_string())
+}
+
+fn upsert_session(
+    conn: &Connection,
+    session_id: &str,
+    run_key: Option<&str>,
+    engine: &str,
+    project_path: &str,
+    model: Option<&str>,
+) -> rusqlite::Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO sessions
+            (id, engine, project_path, run_key, model, status, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, 'running', ?6, ?6)
+         ON CONFLICT(id) DO UPDATE SET
+            run_key = COALESCE(excluded.run_key, run_key),
+            model = COALESCE(excluded.model, model),
+            status = 'running',
+            updated_at = excluded.updated_at",
+        params![session_id, engine, project_path, run_key, model, now],
+    )?;
+    Ok(())
+}
+
+fn insert_entry(
+    conn: &Connection,
+    session_id: &str,
+    kind: EntryKind,
+    content: &Value,
+) -> rusqlite::Result<()> {
+    let now = Utc::now().to_rfc3339();
+    conn.execute(
+        "INSERT INTO session_entries (session_id, kind, content, created_at)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![session_id, kind.as_str(), content.to_string(), now],
+    )?;
+    conn.execute(
+        "UPDATE sessions SET updated_at = ?2 WHERE id = ?1",
+        params![session_id, now],
+    )?;
+    Ok(())
+}
+
+fn parse_time(value: String) -> DateTime<Utc> {
+    DateTime::parse_from_rfc3339(&value)
+        .map(|time| time.with_timezone(&Utc))
+        .unwrap_or_default()
+}
+
+/// Session columns followed by the content of its first prompt
+const SESSION_COLUMNS: &str = "s.id, s.engine, s.project_path, s.model, s.status,
+    s.created_at, s.updated_at,
+    (SELECT content FROM session_entries e
+     WHERE e.session_id = s.id AND e.kind = 'prompt' ORDER BY e.id LIMIT 1)";
+
+fn session_from_row(row: &Row) -> rusqlite::Result<StoredSession> {
+    let first_prompt: Option<String> = row.get(7)?;
+    let title = first_prompt
+        .and_then(|content| serde_json::from_str::<Value>(&content).ok())
+        .and_then(|content| content["text"].as_str().map(prompt_title));
+    Ok(StoredSession {
+        id: row.get(0)?,
+        engine: row.get(1)?,
+        project_path: row.get(2)?,
+        model: row.get(3)?,
+        status: SessionStatus::parse(&row.get::<_, String>(4)?),
+        title,
+        created_at: parse_time(row.get(5)?),
+        updated_at: parse_time(row.get(6)?),
+    })
+}
+
+fn prompt_title(prompt: &str) -> String {
+    let line = prompt.trim().lines().next().unwrap_or_default();
+    if line.chars().count() > TITLE_CHARS {
+        format!("{}…", line.chars().take(TITLE_CHARS).collect::<String>())
+    } else {
+        line.to_string()
+    }
+}
+
+fn query_sessions(
+    conn: &Connection,
+    project_path: Option<&str>,
+    engine: Option<&str>,
+    limit: usize,
+) -> rusqlite::Result<Vec<StoredSession>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM sessions s
+         WHERE (?1 IS NULL OR s.project_path = ?1) AND (?2 IS NULL OR s.engine = ?2)
+         ORDER BY s.updated_at DESC LIMIT ?3",
+        SESSION_COLUMNS
+    ))?;
+    let sessions = stmt
+        .query_map(
+            params![project_path, engine, limit as i64],
+            session_from_row,
+        )?
+        .collect::<rusqlite::Result<Vec<_>>>()?;
+    Ok(sessions)
+}
+
+fn load_session(conn: &Connection, session_id: &str) -> rusqlite::Result<Option<SessionDetail>> {
+    let session = conn
+        .query_row(
+            &format!("SELECT {} FROM sessions s WHERE s.id = ?1", SESSION_COLUMNS),
+            params![session_id],
+            session_from_row,
+        )
+        .optional()?;
+    let Some(session) = session else {
+        return Ok(None);
+    };
+
+    let mut stmt = conn.prepare(
+        "SELECT id, kind, content, created_at FROM session_entries
+         WHERE session_id = ?1 ORDER BY id",
+    )?;
+    let entries = stmt
+        .query_map(params![session_id], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+            ))
+        })?
+        .filter_map(|row| {
+            let (id, kind, content, created_at) = row.ok()?;
+            Some(SessionEntry {
+                id,
+                kind: EntryKind::parse(&kind)?,
+                content: serde_json::from_str(&content).unwrap_or(Value::String(content)),
+                created_at: parse_time(created_at),
+            })
+        })
+        .collect();
+    Ok(Some(SessionDetail { session, entries }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_record_and_load_session() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+
+        upsert_session(&conn, "s1", Some("codex-1"), "codex", "/p", None).unwrap();
+        let prompt = json!({ "text": "Fix the build\nthen run the tests" });
+        insert_entry(&conn, "s1", EntryKind::Prompt, &prompt).unwrap();
+        insert_entry(&conn, "s1", EntryKind::Response, &json!({ "text": "Done" })).unwrap();
+        upsert_session(&conn, "s1", None, "codex", "/p", Some("gpt-5")).unwrap();
+
+        let sessions = query_sessions(&conn, Some("/p"), None, 10).unwrap();
+        assert_eq!(sessions.len(), 1);
+        assert_eq!(sessions[0].title.as_deref(), Some("Fix the build"));
+        assert_eq!(sessions[0].model.as_deref(), Some("gpt-5"));
+        assert!(query_sessions(&conn, None, Some("claude"), 10)
+            .unwrap()
+            .is_empty());
+
+        let detail = load_session(&conn, "s1").unwrap().unwrap();
+        assert_eq!(detail.session.status, SessionStatus::Running);
+        assert_eq!(detail.entries.len(), 2);
+        assert_eq!(detail.entries[1].kind, EntryKind::Response);
+    }
+}
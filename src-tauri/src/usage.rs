@@ -0,0 +1,212 @@
+//! Token usage and cost across engines
+//!
+//! Each engine keeps its own logs: Claude writes one JSONL line per message
+//! under `~/.claude/projects`, Codex reports `token_count` events in its
+//! rollout files and Gemini stores per-message `tokens` in its chat files. The
+//! engines' usage modules parse these into [`UsageRecord`]s, priced with the
+//! engine's own model table, and [`aggregate`] sums them up by project, engine
+//! or day for the usage dashboard.
+
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Tokens and cost of one message (Claude, Gemini) or one usage event (Codex)
+#[derive(Debug, Clone)]
+pub struct UsageRecord {
+    pub engine: &'static str,
+    pub session_id: String,
+    pub project_path: String,
+    pub model: String,
+    pub timestamp: DateTime<Utc>,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost: f64,
+}
+
+/// Period to report on, in local dates
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum UsageRange {
+    All,
+    /// Today and the `days` days before it
+    LastDays {
+        days: u32,
+    },
+    /// Both ends included
+    Between {
+        start: NaiveDate,
+        end: NaiveDate,
+    },
+}
+
+impl UsageRange {
+    fn contains(&self, date: NaiveDate, today: NaiveDate) -> bool {
+        match *self {
+            UsageRange::All => true,
+            UsageRange::LastDays { days } => date >= today - chrono::Duration::days(days as i64),
+            UsageRange::Between { start, end } => date >= start && date <= end,
+        }
+    }
+}
+
+/// What usage is grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGroupBy {
+    Project,
+    Engine,
+    Day,
+}
+
+/// Summed usage
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct UsageTotals {
+    pub total_cost: f64,
+    pub total_tokens: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub session_count: u64,
+}
+
+/// Usage of one project, engine or day
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageGroup {
+    /// Project path, engine id or `YYYY-MM-DD`
+    pub key: String,
+    #[serde(flatten)]
+    pub totals: UsageTotals,
+    pub models_used: Vec<String>,
+}
+
+/// Usage over a range, grouped one way
+#[derive(Debug, Clone, Serialize)]
+pub struct UsageReport {
+    pub range: UsageRange,
+    pub group_by: UsageGroupBy,
+    pub totals: UsageTotals,
+    /// Days in order; projects and engines by cost, highest first
+    pub groups: Vec<UsageGroup>,
+}
+
+/// Running totals, counting each engine session once
+#[derive(Default)]
+struct Accumulator<'a> {
+    totals: UsageTotals,
+    sessions: HashSet<(&'static str, &'a str)>,
+    models: Vec<String>,
+}
+
+impl<'a> Accumulator<'a> {
+    fn add(&mut self, record: &'a UsageRecord) {
+        let totals = &mut self.totals;
+        totals.total_cost += record.cost;
+        totals.input_tokens += record.input_tokens;
+        totals.output_tokens += record.output_tokens;
+        totals.cache_creation_tokens += record.cache_creation_tokens;
+        totals.cache_read_tokens += record.cache_read_tokens;
+        totals.total_tokens += record.input_tokens
+            + record.output_tokens
+            + record.cache_creation_tokens
+            + record.cache_read_tokens;
+        if self.sessions.insert((record.engine, &record.session_id)) {
+            totals.session_count += 1;
+        }
+        if !self.models.contains(&record.model) {
+            self.models.push(record.model.clone());
+        }
+    }
+}
+
+/// Sum up the records falling in `range`, grouped by `group_by`
+pub fn aggregate(
+    records: &[UsageRecord],
+    range: UsageRange,
+    group_by: UsageGroupBy,
+) -> UsageReport {
+    let today = Local::now().date_naive();
+    let mut overall = Accumulator::default();
+    let mut groups: HashMap<String, Accumulator> = HashMap::new();
+
+    for record in records {
+        let date = record.timestamp.with_timezone(&Local).date_naive();
+        if !range.contains(date, today) {
+            continue;
+        }
+        let key = match group_by {
+            UsageGroupBy::Project => record.project_path.clone(),
+            UsageGroupBy::Engine => record.engine.to_string(),
+            UsageGroupBy::Day => date.format("%Y-%m-%d").to_string(),
+        };
+        overall.add(record);
+        groups.entry(key).or_default().add(record);
+    }
+
+    let mut groups: Vec<UsageGroup> = groups
+        .into_iter()
+        .map(|(key, acc)| UsageGroup {
+            key,
+            totals: acc.totals,
+            models_used: acc.models,
+        })
+        .collect();
+    if group_by == UsageGroupBy::Day {
+        groups.sort_by(|a, b| a.key.cmp(&b.key));
+    } else {
+        groups.sort_by(|a, b| b.totals.total_cost.total_cmp(&a.totals.total_cost));
+    }
+
+    UsageReport {
+        range,
+        group_by,
+        totals: overall.totals,
+        groups,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(engine: &'static str, session_id: &str, days_ago: i64, cost: f64) -> UsageRecord {
+        UsageRecord {
+            engine,
+            session_id: session_id.to_string(),
+            project_path: "/work/app".to_string(),
+            model: format!("{}-model", engine),
+            timestamp: Utc::now() - chrono::Duration::days(days_ago),
+            input_tokens: 100,
+            output_tokens: 50,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 10,
+            cost,
+        }
+    }
+
+    #[test]
+    fn test_aggregate_by_engine() {
+        let records = vec![
+            record("claude", "a", 0, 0.5),
+            record("claude", "a", 1, 0.25),
+            record("codex", "b", 2, 2.0),
+            record("gemini", "c", 30, 1.0),
+        ];
+
+        let report = aggregate(
+            &records,
+            UsageRange::LastDays { days: 7 },
+            UsageGroupBy::Engine,
+        );
+
+        assert_eq!(report.totals.session_count, 2);
+        assert_eq!(report.totals.total_tokens, 3 * 160);
+        let keys: Vec<&str> = report.groups.iter().map(|g| g.key.as_str()).collect();
+        assert_eq!(keys, ["codex", "claude"]);
+        assert_eq!(report.groups[1].totals.session_count, 1);
+        assert_eq!(report.groups[1].totals.total_cost, 0.75);
+    }
+}
@@ -0,0 +1,50 @@
+/// 系统钥匙串凭证存储
+///
+/// 通过操作系统的凭证管理器（macOS Keychain / Windows Credential Manager /
+/// Linux Secret Service）保存 API 令牌，避免明文写入配置文件。
+/// 读取到的凭证会登记到 [`super::redaction`]，确保不会出现在日志和导出内容中。
+///
+/// # 使用示例
+///
+/// ```rust
+/// use crate::utils::keyring_store;
+///
+/// keyring_store::set_secret("jira-api-token", "xxxx")?;
+/// let token = keyring_store::get_secret("jira-api-token")?;
+/// ```
+
+/// 钥匙串中的服务名
+const SERVICE: &str = "any-code";
+
+fn entry(name: &str) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(SERVICE, name).map_err(|e| format!("无法访问系统钥匙串: {}", e))
+}
+
+/// 保存凭证
+pub fn set_secret(name: &str, value: &str) -> Result<(), String> {
+    entry(name)?
+        .set_password(value)
+        .map_err(|e| format!("保存凭证 {} 失败: {}", name, e))?;
+    super::redaction::register_secret(value);
+    Ok(())
+}
+
+/// 读取凭证，不存在时返回 `Ok(None)`
+pub fn get_secret(name: &str) -> Result<Option<String>, String> {
+    match entry(name)?.get_password() {
+        Ok(value) => {
+            super::redaction::register_secret(&value);
+            Ok(Some(value))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(format!("读取凭证 {} 失败: {}", name, e)),
+    }
+}
+
+/// 删除凭证（不存在时视为成功）
+pub fn delete_secret(name: &str) -> Result<(), String> {
+    match entry(name)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("删除凭证 {} 失败: {}", name, e)),
+    }
+}
@@ -0,0 +1,107 @@
+/// 日志捕获模块
+///
+/// 包装 env_logger，在正常输出日志的同时把最近的 warn/error 记录保存在内存环形缓冲区中，
+/// 供诊断报告（doctor）读取。所有写出和捕获的消息都会先经过 [`super::redaction`] 脱敏。
+///
+/// # 使用示例
+///
+/// ```rust
+/// // 在 main 中替代 env_logger::init()
+/// crate::utils::log_capture::init();
+///
+/// // 读取最近的错误
+/// let errors = crate::utils::log_capture::recent_records();
+/// ```
+use chrono::{DateTime, Utc};
+use log::{Level, Log, Metadata, Record};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 环形缓冲区容量
+const MAX_RECORDS: usize = 200;
+
+/// 捕获的日志记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedRecord {
+    pub timestamp: DateTime<Utc>,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+lazy_static::lazy_static! {
+    static ref RECENT: Mutex<VecDeque<CapturedRecord>> = Mutex::new(VecDeque::with_capacity(MAX_RECORDS));
+}
+
+struct CapturingLogger {
+    inner: env_logger::Logger,
+}
+
+impl Log for CapturingLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= Level::Warn || self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        let capture = record.level() <= Level::Warn;
+        let output = self.inner.matches(record);
+        if !capture && !output {
+            return;
+        }
+
+        let message = super::redaction::redact(&record.args().to_string());
+
+        if capture {
+            if let Ok(mut recent) = RECENT.lock() {
+                if recent.len() == MAX_RECORDS {
+                    recent.pop_front();
+                }
+                recent.push_back(CapturedRecord {
+                    timestamp: Utc::now(),
+                    level: record.level().to_string(),
+                    target: record.target().to_string(),
+                    message: message.clone(),
+                });
+            }
+        }
+
+        if output {
+            self.inner.log(
+                &Record::builder()
+                    .args(format_args!("{}", message))
+                    .level(record.level())
+                    .target(record.target())
+                    .module_path(record.module_path())
+                    .file(record.file())
+                    .line(record.line())
+                    .build(),
+            );
+        }
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// 初始化日志系统（替代 `env_logger::init()`）
+///
+/// 过滤规则仍由 `RUST_LOG` 控制，warn/error 记录无论过滤级别如何都会被捕获。
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter().max(log::LevelFilter::Warn);
+
+    if log::set_boxed_logger(Box::new(CapturingLogger { inner })).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
+/// 获取最近捕获的 warn/error 记录（按时间从旧到新）
+pub fn recent_records() -> Vec<CapturedRecord> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
@@ -3,3 +3,6 @@
 /// 包含各种通用的辅助功能
 
 pub mod config_utils;
+pub mod keyring_store;
+pub mod log_capture;
+pub mod redaction;
@@ -15,7 +15,7 @@ import {
   DropdownMenuLabel,
 } from '@/components/ui/dropdown-menu';
 import { cn } from '@/lib/utils';
-import { exportSession, copyToClipboard, exportAsJsonl, exportAsMarkdown, exportAsJson } from '@/lib/sessionExport';
+import { exportSession, copyToClipboard, exportAsJsonl, exportAsMarkdown, exportAsJson, redactForExport } from '@/lib/sessionExport';
 import type { ClaudeStreamMessage } from '@/types/claude';
 import type { Session } from '@/lib/api';
 
@@ -89,7 +89,7 @@ export const SessionToolbar: React.FC<SessionToolbarProps> = ({
           break;
       }
 
-      await copyToClipboard(content);
+      await copyToClipboard(await redactForExport(content));
       showStatus('success', `已复制为 ${label}`);
       setIsMenuOpen(false);
     } catch (error) {
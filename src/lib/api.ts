@@ -1145,7 +1145,7 @@ export const api = {
    */
   async getUsageStats(): Promise<UsageStats> {
     try {
-      return await invoke<UsageStats>("get_usage_stats");
+      return await invoke<UsageStats>("get_claude_usage_stats");
     } catch (error) {
       console.error("Failed to get usage stats:", error);
       throw error;
@@ -3,6 +3,7 @@
  * 提供会话记录导出功能，支持多种格式
  */
 
+import { invoke } from '@tauri-apps/api/core';
 import { save } from '@tauri-apps/plugin-dialog';
 import { writeTextFile } from '@tauri-apps/plugin-fs';
 import type { ClaudeStreamMessage } from '@/types/claude';
@@ -204,6 +205,13 @@ function extractMessageContent(msg: ClaudeStreamMessage): string {
   return '';
 }
 
+/**
+ * 导出前对内容脱敏，掩码其中的 API 密钥和令牌
+ */
+export async function redactForExport(content: string): Promise<string> {
+  return await invoke<string>('redact_export_content', { content });
+}
+
 /**
  * 保存文件到用户选择的路径（使用 Tauri 文件对话框）
  * @returns 保存的文件路径，如果用户取消则返回 null
@@ -277,7 +285,7 @@ export async function exportSession(
   }
 
   const filename = generateExportFilename(session, format);
-  return await saveFileWithDialog(content, filename, filters);
+  return await saveFileWithDialog(await redactForExport(content), filename, filters);
 }
 
 /**